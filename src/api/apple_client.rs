@@ -1,19 +1,14 @@
+use crate::api::apple_errors::ApiError;
+use crate::api::recording::RecordedSend;
 use reqwest::header::{HeaderMap, AUTHORIZATION};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use std::sync::Arc;
 
+const API_ROOT: &str = "https://api.appstoreconnect.apple.com";
 const BASE_URL: &str = "https://api.appstoreconnect.apple.com/v1";
 const MAX_ERROR_LEN: usize = 512;
 
-fn truncate_error(body: &str) -> &str {
-    if body.len() <= MAX_ERROR_LEN {
-        body
-    } else {
-        &body[..MAX_ERROR_LEN]
-    }
-}
-
 /// API client for App Store Connect.
 ///
 /// Cheaply cloneable — uses `Arc` internally so the connection pool is shared.
@@ -21,20 +16,46 @@ fn truncate_error(body: &str) -> &str {
 pub struct AppleClient {
     client: Arc<reqwest::Client>,
     token: String,
+    /// Profile's `default_app_id`, used when a command omits the app
+    default_app: Option<String>,
 }
 
 impl AppleClient {
     pub fn new(token: String) -> Self {
         Self {
-            client: Arc::new(reqwest::Client::new()),
+            client: super::session::http(),
             token,
+            default_app: None,
         }
     }
 
     /// Create a client with a shared `reqwest::Client` (for connection pooling).
-    #[allow(dead_code)]
     pub fn with_client(client: Arc<reqwest::Client>, token: String) -> Self {
-        Self { client, token }
+        Self {
+            client,
+            token,
+            default_app: None,
+        }
+    }
+
+    /// Fall back to this app ID or bundle ID when a command omits the app.
+    pub fn with_default_app(mut self, app: Option<String>) -> Self {
+        self.default_app = app;
+        self
+    }
+
+    pub fn default_app(&self) -> Option<&str> {
+        self.default_app.as_deref()
+    }
+
+    /// Resolve a request path. Paths are relative to `/v1` unless they start
+    /// with an explicit API version such as `/v2/`.
+    fn url(path: &str) -> String {
+        if path.starts_with("/v2/") {
+            format!("{API_ROOT}{path}")
+        } else {
+            format!("{BASE_URL}{path}")
+        }
     }
 
     fn headers(&self) -> Result<HeaderMap, Box<dyn std::error::Error>> {
@@ -48,40 +69,134 @@ impl AppleClient {
         path: &str,
         query: &[(&str, &str)],
     ) -> Result<T, Box<dyn std::error::Error>> {
-        let url = format!("{BASE_URL}{path}");
+        let url = Self::url(path);
         let resp = self
             .client
             .get(&url)
             .headers(self.headers()?)
             .query(query)
-            .send()
+            .send_recorded()
             .await?;
+        super::rate_limit::record_apple(resp.headers());
         let status = resp.status();
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
-            return Err(format!("Apple API error {status}: {}", truncate_error(&body)).into());
+            return Err(Box::new(ApiError::new(status, &body, MAX_ERROR_LEN)));
         }
         Ok(resp.json().await?)
     }
 
+    /// GET a list page, passing each element of its `key` array to `on_item` as it
+    /// is parsed instead of buffering the body. Returns the other top-level fields
+    /// (`links`, `meta`, `included`).
+    pub async fn get_streamed(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        key: &str,
+        on_item: &mut dyn FnMut(Value),
+    ) -> Result<serde_json::Map<String, Value>, Box<dyn std::error::Error>> {
+        let url = Self::url(path);
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers()?)
+            .query(query)
+            .send_recorded()
+            .await?;
+        super::rate_limit::record_apple(resp.headers());
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(Box::new(ApiError::new(status, &body, MAX_ERROR_LEN)));
+        }
+        super::json_stream::for_each(resp, key, on_item).await
+    }
+
+    /// Download a report (sales and finance reports are gzip-compressed TSV).
+    ///
+    /// `None` means the report does not exist (yet) for the requested date.
+    pub async fn get_report(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        let url = Self::url(path);
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers()?)
+            .header(reqwest::header::ACCEPT, "application/a-gzip")
+            .query(query)
+            .send_recorded()
+            .await?;
+        super::rate_limit::record_apple(resp.headers());
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(Box::new(ApiError::new(status, &body, MAX_ERROR_LEN)));
+        }
+        Ok(Some(resp.bytes().await?.to_vec()))
+    }
+
+    /// GET that sends `If-None-Match`; `None` means the resource is unchanged (304).
+    ///
+    /// Returns the body and the response ETag, if any.
+    pub async fn get_if_none_match(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        etag: Option<&str>,
+    ) -> Result<Option<(Value, Option<String>)>, Box<dyn std::error::Error>> {
+        let url = Self::url(path);
+        let mut req = self.client.get(&url).headers(self.headers()?).query(query);
+        if let Some(etag) = etag {
+            req = req.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let resp = req.send_recorded().await?;
+        super::rate_limit::record_apple(resp.headers());
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(Box::new(ApiError::new(status, &body, MAX_ERROR_LEN)));
+        }
+        let new_etag = resp
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        Ok(Some((resp.json().await?, new_etag)))
+    }
+
     pub async fn post(
         &self,
         path: &str,
         body: &Value,
     ) -> Result<Value, Box<dyn std::error::Error>> {
-        let url = format!("{BASE_URL}{path}");
+        let url = Self::url(path);
         let resp = self
             .client
             .post(&url)
             .headers(self.headers()?)
             .header("Content-Type", "application/json")
             .json(body)
-            .send()
+            .send_recorded()
             .await?;
+        super::rate_limit::record_apple(resp.headers());
         let status = resp.status();
+        // Relationship endpoints answer 204 with no body
+        if status == reqwest::StatusCode::NO_CONTENT {
+            return Ok(serde_json::json!({"status": "ok"}));
+        }
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
-            return Err(format!("Apple API error {status}: {}", truncate_error(&body)).into());
+            return Err(Box::new(ApiError::new(status, &body, MAX_ERROR_LEN)));
         }
         Ok(resp.json().await?)
     }
@@ -91,38 +206,113 @@ impl AppleClient {
         path: &str,
         body: &Value,
     ) -> Result<Value, Box<dyn std::error::Error>> {
-        let url = format!("{BASE_URL}{path}");
+        let url = Self::url(path);
         let resp = self
             .client
             .patch(&url)
             .headers(self.headers()?)
             .header("Content-Type", "application/json")
             .json(body)
-            .send()
+            .send_recorded()
             .await?;
+        super::rate_limit::record_apple(resp.headers());
         let status = resp.status();
+        if status == reqwest::StatusCode::NO_CONTENT {
+            return Ok(serde_json::json!({"status": "ok"}));
+        }
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
-            return Err(format!("Apple API error {status}: {}", truncate_error(&body)).into());
+            return Err(Box::new(ApiError::new(status, &body, MAX_ERROR_LEN)));
         }
         Ok(resp.json().await?)
     }
 
     pub async fn delete(&self, path: &str) -> Result<Value, Box<dyn std::error::Error>> {
-        let url = format!("{BASE_URL}{path}");
+        let url = Self::url(path);
+        let resp = self
+            .client
+            .delete(&url)
+            .headers(self.headers()?)
+            .send_recorded()
+            .await?;
+        super::rate_limit::record_apple(resp.headers());
+        let status = resp.status();
+        if status == reqwest::StatusCode::NO_CONTENT {
+            return Ok(serde_json::json!({"status": "deleted"}));
+        }
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(Box::new(ApiError::new(status, &body, MAX_ERROR_LEN)));
+        }
+        Ok(resp
+            .json()
+            .await
+            .unwrap_or(serde_json::json!({"status": "ok"})))
+    }
+
+    /// Send an arbitrary request for `apple api`.
+    ///
+    /// `path` is resolved like every other path (relative to `/v1` unless it names a
+    /// version); full URLs are accepted only on the App Store Connect host.
+    pub async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(String, String)],
+        body: Option<&Value>,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        let url = if path.starts_with("https://") {
+            if !path.starts_with(&format!("{API_ROOT}/")) {
+                return Err(format!("refusing to send credentials outside {API_ROOT}").into());
+            }
+            path.to_string()
+        } else {
+            Self::url(path)
+        };
+        let mut req = self
+            .client
+            .request(method, &url)
+            .headers(self.headers()?)
+            .query(query);
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+        let resp = req.send_recorded().await?;
+        super::rate_limit::record_apple(resp.headers());
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(Box::new(ApiError::new(status, &text, MAX_ERROR_LEN)));
+        }
+        if text.trim().is_empty() {
+            return Ok(serde_json::json!({ "status": status.as_u16() }));
+        }
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// DELETE with a JSON body, as relationship endpoints require to remove links.
+    pub async fn delete_with_body(
+        &self,
+        path: &str,
+        body: &Value,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        let url = Self::url(path);
         let resp = self
             .client
             .delete(&url)
             .headers(self.headers()?)
-            .send()
+            .header("Content-Type", "application/json")
+            .json(body)
+            .send_recorded()
             .await?;
+        super::rate_limit::record_apple(resp.headers());
         let status = resp.status();
         if status == reqwest::StatusCode::NO_CONTENT {
             return Ok(serde_json::json!({"status": "deleted"}));
         }
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
-            return Err(format!("Apple API error {status}: {}", truncate_error(&body)).into());
+            return Err(Box::new(ApiError::new(status, &body, MAX_ERROR_LEN)));
         }
         Ok(resp
             .json()
@@ -130,3 +320,24 @@ impl AppleClient {
             .unwrap_or(serde_json::json!({"status": "ok"})))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_defaults_to_v1() {
+        assert_eq!(
+            AppleClient::url("/apps"),
+            "https://api.appstoreconnect.apple.com/v1/apps"
+        );
+    }
+
+    #[test]
+    fn url_honors_explicit_version() {
+        assert_eq!(
+            AppleClient::url("/v2/appAvailabilities"),
+            "https://api.appstoreconnect.apple.com/v2/appAvailabilities"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Recognition of common App Store Connect state errors.
+//!
+//! Conflicts such as editing a live version or creating a locale twice come back
+//! as 409/422 responses whose JSON:API `errors` are hard to act on. Failed
+//! mutating requests return an `ApiError`, which names the problem when it is one
+//! of these and adds a hint on how to fix it; commands that can fix it themselves
+//! (`--auto-remediate`) downcast to it.
+
+use serde_json::Value;
+
+/// A state conflict App Store Connect reported for a mutating request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateProblem {
+    /// The app already has a version being prepared, so no new one can be created
+    VersionInProgress,
+    /// The version is live, in review or approved and can no longer be edited
+    NotEditable,
+    /// A resource with the same unique values (such as a locale) already exists
+    Duplicate,
+}
+
+impl StateProblem {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StateProblem::VersionInProgress => "version_in_progress",
+            StateProblem::NotEditable => "not_editable",
+            StateProblem::Duplicate => "duplicate",
+        }
+    }
+
+    pub fn hint(self) -> &'static str {
+        match self {
+            StateProblem::VersionInProgress => {
+                "the app already has a version in progress; edit that one instead \
+                 (`storeops apple versions list --state PREPARE_FOR_SUBMISSION`)"
+            }
+            StateProblem::NotEditable => {
+                "this version can no longer be edited; create a new version with \
+                 `storeops apple versions create` and edit that"
+            }
+            StateProblem::Duplicate => "it already exists; update the existing one instead",
+        }
+    }
+}
+
+/// Which state problem, if any, an error response describes.
+pub fn recognize(status: u16, body: &str) -> Option<StateProblem> {
+    if status != 409 && status != 422 {
+        return None;
+    }
+    let body: Value = serde_json::from_str(body).ok()?;
+    body["errors"].as_array()?.iter().find_map(|error| {
+        let code = error["code"].as_str().unwrap_or_default();
+        let detail = error["detail"].as_str().unwrap_or_default().to_lowercase();
+        if detail.contains("create a new version") {
+            Some(StateProblem::VersionInProgress)
+        } else if code.contains("DUPLICATE") || detail.contains("already exists") {
+            Some(StateProblem::Duplicate)
+        } else if code.starts_with("STATE_ERROR")
+            || detail.contains("cannot be edited")
+            || detail.contains("not editable")
+            || detail.contains("not in a valid state")
+        {
+            Some(StateProblem::NotEditable)
+        } else {
+            None
+        }
+    })
+}
+
+/// A failed App Store Connect request.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: reqwest::StatusCode,
+    /// Response body, truncated for display
+    pub body: String,
+    pub problem: Option<StateProblem>,
+}
+
+impl ApiError {
+    pub fn new(status: reqwest::StatusCode, body: &str, max_len: usize) -> Self {
+        let mut end = body.len().min(max_len);
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        Self {
+            status,
+            problem: recognize(status.as_u16(), body),
+            body: body[..end].to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Apple API error {}: {}", self.status, self.body)?;
+        if let Some(problem) = self.problem {
+            write!(f, " (hint: {})", problem.hint())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// The state problem behind a command error, if it was a recognized `ApiError`.
+pub fn problem_of(error: &(dyn std::error::Error + 'static)) -> Option<StateProblem> {
+    error.downcast_ref::<ApiError>()?.problem
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_state_conflicts() {
+        let live = r#"{"errors":[{"status":"409","code":"STATE_ERROR","title":"The request cannot be fulfilled because of the state of another resource.","detail":"The version is not in a valid state for this operation."}]}"#;
+        let duplicate = r#"{"errors":[{"status":"409","code":"ENTITY_ERROR.ATTRIBUTE.INVALID.DUPLICATE","detail":"A localization for locale de-DE already exists."}]}"#;
+        let in_progress = r#"{"errors":[{"status":"409","code":"ENTITY_ERROR.RELATIONSHIP.INVALID","detail":"You cannot create a new version of the App in the current state."}]}"#;
+        assert_eq!(recognize(409, live), Some(StateProblem::NotEditable));
+        assert_eq!(recognize(409, duplicate), Some(StateProblem::Duplicate));
+        assert_eq!(
+            recognize(409, in_progress),
+            Some(StateProblem::VersionInProgress)
+        );
+        assert_eq!(recognize(404, live), None);
+        assert_eq!(recognize(409, "not json"), None);
+
+        let error = ApiError::new(reqwest::StatusCode::CONFLICT, live, 512);
+        assert!(error
+            .to_string()
+            .starts_with("Apple API error 409 Conflict: {"));
+        assert!(error.to_string().ends_with("(hint: this version can no longer be edited; create a new version with `storeops apple versions create` and edit that)"));
+    }
+}
@@ -0,0 +1,305 @@
+//! Reserve → upload → commit for App Store Connect assets.
+//!
+//! Screenshots, preview videos, App Review attachments and subscription images all
+//! follow the same flow: a reservation (`POST /<type>`) returns `uploadOperations`,
+//! byte ranges of the file each with its own URL, method and headers; once every
+//! range is sent, the asset is committed (`PATCH /<type>/<id>`) with the MD5 of the
+//! local file. The ranges are sent concurrently (a few at a time), every response
+//! status is checked, and transient failures are retried with backoff. File-backed
+//! uploads read each range from disk when it is sent, so a preview video is never
+//! held in memory whole.
+
+use crate::api::apple_client::AppleClient;
+use crate::api::recording::RecordedSend;
+use futures::stream::{self, TryStreamExt};
+use md5::{Digest, Md5};
+use serde_json::{json, Value};
+use std::io::SeekFrom;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+const MAX_CONCURRENT_CHUNKS: usize = 4;
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Hex MD5 of an upload, as App Store Connect expects in `sourceFileChecksum`.
+pub fn md5_checksum(bytes: &[u8]) -> String {
+    hex::encode(Md5::digest(bytes))
+}
+
+/// Hex MD5 of a file, read in blocks.
+pub async fn md5_file(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Md5::new();
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Where the bytes of an upload come from.
+pub enum UploadSource<'a> {
+    Bytes(&'a [u8]),
+    File(&'a Path),
+}
+
+impl UploadSource<'_> {
+    async fn len(&self) -> Result<u64, Box<dyn std::error::Error>> {
+        Ok(match self {
+            UploadSource::Bytes(bytes) => bytes.len() as u64,
+            UploadSource::File(path) => tokio::fs::metadata(path).await?.len(),
+        })
+    }
+
+    async fn checksum(&self) -> Result<String, Box<dyn std::error::Error>> {
+        match self {
+            UploadSource::Bytes(bytes) => Ok(md5_checksum(bytes)),
+            UploadSource::File(path) => md5_file(path).await,
+        }
+    }
+
+    /// Read `length` bytes starting at `offset` (fewer at the end of the source).
+    async fn read(&self, offset: u64, length: u64) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match self {
+            UploadSource::Bytes(bytes) => {
+                let start = (offset as usize).min(bytes.len());
+                let end = start.saturating_add(length as usize).min(bytes.len());
+                Ok(bytes[start..end].to_vec())
+            }
+            UploadSource::File(path) => {
+                let mut file = tokio::fs::File::open(path).await?;
+                file.seek(SeekFrom::Start(offset)).await?;
+                let mut chunk = Vec::with_capacity(length as usize);
+                file.take(length).read_to_end(&mut chunk).await?;
+                Ok(chunk)
+            }
+        }
+    }
+}
+
+/// Whether a failed chunk is worth sending again.
+fn is_transient(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+}
+
+/// Send one upload operation; returns the number of bytes sent.
+async fn send_chunk(
+    http: &reqwest::Client,
+    op: &Value,
+    source: &UploadSource<'_>,
+    total: u64,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let url = op["url"].as_str().ok_or("missing upload url")?;
+    let method = reqwest::Method::from_bytes(op["method"].as_str().unwrap_or("PUT").as_bytes())?;
+    let offset = op["offset"].as_u64().unwrap_or(0);
+    let length = op["length"].as_u64().unwrap_or(total);
+    let chunk = source.read(offset, length).await?;
+
+    let mut attempt = 1;
+    loop {
+        let mut req = http.request(method.clone(), url);
+        if let Some(headers) = op["requestHeaders"].as_array() {
+            for h in headers {
+                if let (Some(name), Some(value)) = (h["name"].as_str(), h["value"].as_str()) {
+                    req = req.header(name, value);
+                }
+            }
+        }
+        let failure = match req.body(chunk.clone()).send_recorded().await {
+            Ok(resp) if resp.status().is_success() => return Ok(chunk.len() as u64),
+            Ok(resp) if !is_transient(resp.status()) => {
+                return Err(format!(
+                    "upload of bytes {offset}..{} failed: HTTP {}",
+                    offset + chunk.len() as u64,
+                    resp.status()
+                )
+                .into())
+            }
+            Ok(resp) => format!("HTTP {}", resp.status()),
+            Err(e) => e.to_string(),
+        };
+        if attempt == MAX_ATTEMPTS {
+            return Err(format!(
+                "upload of bytes {offset}..{} failed after {MAX_ATTEMPTS} attempts: {failure}",
+                offset + chunk.len() as u64
+            )
+            .into());
+        }
+        eprintln!("  Retrying upload of bytes at offset {offset} ({failure})");
+        tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+        attempt += 1;
+    }
+}
+
+/// Send every upload operation of a reservation response.
+///
+/// Progress is reported on stderr for uploads split into several operations.
+pub async fn run_operations(
+    reserved: &Value,
+    source: UploadSource<'_>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(ops) = reserved["data"]["attributes"]["uploadOperations"].as_array() else {
+        return Ok(());
+    };
+    let total = source.len().await?;
+    let http = super::session::http();
+    let sent = AtomicU64::new(0);
+    stream::iter(ops.iter().map(Ok))
+        .try_for_each_concurrent(MAX_CONCURRENT_CHUNKS, |op| {
+            let (http, source, sent) = (&http, &source, &sent);
+            async move {
+                let n = send_chunk(http, op, source, total).await?;
+                let done = sent.fetch_add(n, Ordering::Relaxed) + n;
+                if ops.len() > 1 {
+                    eprintln!("  Uploaded {done}/{total} bytes");
+                }
+                Ok(())
+            }
+        })
+        .await
+}
+
+/// An asset to reserve, upload and commit.
+pub struct Asset<'a> {
+    /// Resource type, e.g. `appScreenshots`
+    pub kind: &'a str,
+    pub file_name: &'a str,
+    pub source: UploadSource<'a>,
+    /// Reservation attributes beyond `fileName` and `fileSize` (e.g. `mimeType`)
+    pub attributes: Value,
+    /// Relationship name, resource type and ID of the resource the asset belongs to
+    pub parent: (&'a str, &'a str, &'a str),
+}
+
+/// A committed upload.
+pub struct UploadedAsset {
+    pub id: String,
+    /// API path of the asset, e.g. `/appScreenshots/{id}`
+    pub path: String,
+    /// MD5 of the uploaded file, sent as `sourceFileChecksum`
+    pub checksum: String,
+}
+
+fn reservation_body(asset: &Asset<'_>, file_size: u64) -> Value {
+    let mut attributes = json!({ "fileName": asset.file_name, "fileSize": file_size });
+    if let (Some(attrs), Some(extra)) = (attributes.as_object_mut(), asset.attributes.as_object()) {
+        attrs.extend(extra.clone());
+    }
+    let (relationship, parent_type, parent_id) = asset.parent;
+    json!({
+        "data": {
+            "type": asset.kind,
+            "attributes": attributes,
+            "relationships": {
+                relationship: { "data": { "type": parent_type, "id": parent_id } }
+            }
+        }
+    })
+}
+
+fn commit_body(kind: &str, id: &str, checksum: &str) -> Value {
+    json!({
+        "data": {
+            "type": kind,
+            "id": id,
+            "attributes": {
+                "uploaded": true,
+                "sourceFileChecksum": checksum
+            }
+        }
+    })
+}
+
+/// Reserve, upload and commit an asset.
+///
+/// The reservation is tracked as in-flight until committed, so a cancelled upload
+/// is cleaned up rather than left half-uploaded.
+pub async fn upload(
+    client: &AppleClient,
+    asset: Asset<'_>,
+) -> Result<UploadedAsset, Box<dyn std::error::Error>> {
+    let file_size = asset.source.len().await?;
+    let reserved: Value = client
+        .post(
+            &format!("/{}", asset.kind),
+            &reservation_body(&asset, file_size),
+        )
+        .await?;
+    let id = reserved["data"]["id"]
+        .as_str()
+        .ok_or_else(|| format!("no {} id in reservation response", asset.kind))?;
+    let path = format!("/{}/{id}", asset.kind);
+    crate::api::inflight::track_apple_asset(client, &path);
+
+    let checksum = asset.source.checksum().await?;
+    run_operations(&reserved, asset.source).await?;
+
+    client
+        .patch(&path, &commit_body(asset.kind, id, &checksum))
+        .await?;
+    crate::api::inflight::release_apple_asset(&path);
+
+    Ok(UploadedAsset {
+        id: id.to_string(),
+        path,
+        checksum,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn file_and_byte_sources_read_the_same_ranges() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let path = std::env::temp_dir().join(format!("storeops-upload-{}", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+
+        let file = UploadSource::File(&path);
+        let memory = UploadSource::Bytes(&bytes);
+        assert_eq!(file.len().await.unwrap(), 256);
+        for (offset, length) in [(0, 16), (100, 50), (250, 50), (300, 10)] {
+            assert_eq!(
+                file.read(offset, length).await.unwrap(),
+                memory.read(offset, length).await.unwrap()
+            );
+        }
+        assert_eq!(md5_file(&path).await.unwrap(), md5_checksum(&bytes));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn reservation_merges_extra_attributes_and_names_the_parent() {
+        let asset = Asset {
+            kind: "appPreviews",
+            file_name: "intro.mp4",
+            source: UploadSource::Bytes(b""),
+            attributes: json!({ "mimeType": "video/mp4" }),
+            parent: ("appPreviewSet", "appPreviewSets", "set-1"),
+        };
+        assert_eq!(
+            reservation_body(&asset, 42),
+            json!({"data": {
+                "type": "appPreviews",
+                "attributes": {"fileName": "intro.mp4", "fileSize": 42, "mimeType": "video/mp4"},
+                "relationships": {
+                    "appPreviewSet": {"data": {"type": "appPreviewSets", "id": "set-1"}}
+                }
+            }})
+        );
+        assert_eq!(
+            commit_body("appPreviews", "p-1", "abc")["data"]["attributes"],
+            json!({"uploaded": true, "sourceFileChecksum": "abc"})
+        );
+    }
+}
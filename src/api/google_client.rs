@@ -1,9 +1,11 @@
+use crate::api::recording::RecordedSend;
 use reqwest::header::{HeaderMap, AUTHORIZATION};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 const BASE_URL: &str = "https://androidpublisher.googleapis.com/androidpublisher/v3/applications";
+const REPORTING_BASE_URL: &str = "https://playdeveloperreporting.googleapis.com/v1beta1/apps";
 const MAX_ERROR_LEN: usize = 512;
 
 fn truncate_error(body: &str) -> &str {
@@ -14,6 +16,26 @@ fn truncate_error(body: &str) -> &str {
     }
 }
 
+/// Refuse to send credentials to a full URL outside `googleapis.com`.
+fn check_google_host(url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let host = reqwest::Url::parse(url)?
+        .host_str()
+        .unwrap_or_default()
+        .to_string();
+    if host != "googleapis.com" && !host.ends_with(".googleapis.com") {
+        return Err(format!("refusing to send credentials to {host}").into());
+    }
+    Ok(())
+}
+
+/// An edit shared across commands and committed explicitly with `google edits commit`.
+#[derive(Clone, Debug)]
+pub struct EditSession {
+    /// Package the edit belongs to (`None` when only the ID is known, e.g. `--edit-id`)
+    pub package_name: Option<String>,
+    pub edit_id: String,
+}
+
 /// API client for Google Play Store.
 ///
 /// Cheaply cloneable — uses `Arc` internally so the connection pool is shared.
@@ -21,20 +43,193 @@ fn truncate_error(body: &str) -> &str {
 pub struct GoogleClient {
     client: Arc<reqwest::Client>,
     token: String,
+    session: Option<EditSession>,
+    /// `--send-for-review` / `--no-send-for-review`; `None` uses each command's default
+    send_for_review: Option<bool>,
+    /// Review mode of the last commit, reported in command output
+    committed: Arc<Mutex<Option<bool>>>,
+    /// Profile's `default_package`, used when a command omits the package name
+    default_package: Option<String>,
 }
 
 impl GoogleClient {
     pub fn new(token: String) -> Self {
         Self {
-            client: Arc::new(reqwest::Client::new()),
+            client: super::session::http(),
             token,
+            session: None,
+            send_for_review: None,
+            committed: Arc::default(),
+            default_package: None,
         }
     }
 
     /// Create a client with a shared `reqwest::Client` (for connection pooling).
-    #[allow(dead_code)]
     pub fn with_client(client: Arc<reqwest::Client>, token: String) -> Self {
-        Self { client, token }
+        Self {
+            client,
+            token,
+            session: None,
+            send_for_review: None,
+            committed: Arc::default(),
+            default_package: None,
+        }
+    }
+
+    /// Route edit-based commands through a shared edit instead of opening their own.
+    pub fn with_edit_session(mut self, session: Option<EditSession>) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Override whether commits send changes for review.
+    pub fn with_send_for_review(mut self, send_for_review: Option<bool>) -> Self {
+        self.send_for_review = send_for_review;
+        self
+    }
+
+    /// Fall back to this package name when a command omits it.
+    pub fn with_default_package(mut self, package_name: Option<String>) -> Self {
+        self.default_package = package_name;
+        self
+    }
+
+    /// The given package name, or the profile default when omitted.
+    pub fn package_name(&self, given: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+        given
+            .or(self.default_package.as_deref())
+            .map(String::from)
+            .ok_or_else(|| {
+                "no package name given and the profile has no default_package \
+                 (`storeops google apps list` shows the packages you can use)"
+                    .into()
+            })
+    }
+
+    /// Whether the last commit through this client sent changes for review.
+    pub fn committed_send_for_review(&self) -> Option<bool> {
+        self.committed.lock().ok().and_then(|c| *c)
+    }
+
+    fn session_for(&self, package_name: &str) -> Option<&EditSession> {
+        self.session.as_ref().filter(|s| {
+            s.package_name
+                .as_deref()
+                .is_none_or(|pkg| pkg == package_name)
+        })
+    }
+
+    /// Open an edit, or reuse the shared session edit for this package.
+    pub async fn open_edit(
+        &self,
+        package_name: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(session) = self.session_for(package_name) {
+            return Ok(session.edit_id.clone());
+        }
+        let edit = self
+            .post(&format!("/{package_name}/edits"), &serde_json::json!({}))
+            .await?;
+        let edit_id = edit["id"].as_str().ok_or("no edit id")?.to_string();
+        super::inflight::track_google_edit(self, package_name, &edit_id);
+        Ok(edit_id)
+    }
+
+    /// Keep using `previous` if Play still has it open, otherwise open a new edit.
+    ///
+    /// The shared session edit always takes precedence.
+    pub async fn resume_edit(
+        &self,
+        package_name: &str,
+        previous: Option<&str>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if let (None, Some(edit_id)) = (self.session_for(package_name), previous) {
+            let path = format!("/{package_name}/edits/{edit_id}");
+            if self.get::<Value>(&path, &[]).await.is_ok() {
+                super::inflight::track_google_edit(self, package_name, edit_id);
+                return Ok(edit_id.to_string());
+            }
+            eprintln!("Edit {edit_id} has expired; starting a new one");
+        }
+        self.open_edit(package_name).await
+    }
+
+    /// Commit an edit opened with `open_edit`, sending changes for review by default.
+    ///
+    /// A shared session edit is left open; its changes are published by `google edits commit`.
+    pub async fn commit_edit(
+        &self,
+        package_name: &str,
+        edit_id: &str,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        self.commit_edit_with(package_name, edit_id, true).await
+    }
+
+    /// Like `commit_edit` with a command-specific default for sending changes for review.
+    ///
+    /// `--no-send-for-review` commits with `changesNotSentForReview`, which managed
+    /// publishing requires; the changes must then be sent from the Play Console.
+    pub async fn commit_edit_with(
+        &self,
+        package_name: &str,
+        edit_id: &str,
+        default_send_for_review: bool,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        if self.session_for(package_name).is_some() {
+            eprintln!("Staged in edit {edit_id}; run `google edits commit` to publish");
+            return Ok(serde_json::json!({ "id": edit_id, "status": "staged" }));
+        }
+        let send_for_review = self.send_for_review.unwrap_or(default_send_for_review);
+        let query = if send_for_review {
+            ""
+        } else {
+            "?changesNotSentForReview=true"
+        };
+        let result = self
+            .post(
+                &format!("/{package_name}/edits/{edit_id}:commit{query}"),
+                &serde_json::json!({}),
+            )
+            .await?;
+        super::inflight::release_google_edit(package_name, edit_id);
+        if let Ok(mut committed) = self.committed.lock() {
+            *committed = Some(send_for_review);
+        }
+        Ok(result)
+    }
+
+    /// Ask Play to validate an edit without committing it.
+    ///
+    /// On failure the edit is discarded (unless it is the shared session edit) so
+    /// nothing half-applied is left behind.
+    pub async fn validate_edit(
+        &self,
+        package_name: &str,
+        edit_id: &str,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        match self
+            .post(
+                &format!("/{package_name}/edits/{edit_id}:validate"),
+                &serde_json::json!({}),
+            )
+            .await
+        {
+            Ok(v) => Ok(v),
+            Err(e) => {
+                self.discard_edit(package_name, edit_id).await;
+                Err(format!("edit validation failed: {e}").into())
+            }
+        }
+    }
+
+    /// Discard a read-only edit opened with `open_edit`; a shared session edit is kept.
+    pub async fn discard_edit(&self, package_name: &str, edit_id: &str) {
+        if self.session_for(package_name).is_none() {
+            let _ = self
+                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
+                .await;
+            super::inflight::release_google_edit(package_name, edit_id);
+        }
     }
 
     fn headers(&self) -> Result<HeaderMap, Box<dyn std::error::Error>> {
@@ -54,16 +249,78 @@ impl GoogleClient {
             .get(&url)
             .headers(self.headers()?)
             .query(query)
-            .send()
+            .send_recorded()
             .await?;
         let status = resp.status();
+        super::rate_limit::record_google();
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
             return Err(format!("Google API error {status}: {}", truncate_error(&body)).into());
         }
         Ok(resp.json().await?)
     }
 
+    /// GET a list page, passing each element of its `key` array to `on_item` as it
+    /// is parsed instead of buffering the body. Returns the other top-level fields.
+    pub async fn get_streamed(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        key: &str,
+        on_item: &mut dyn FnMut(Value),
+    ) -> Result<serde_json::Map<String, Value>, Box<dyn std::error::Error>> {
+        let url = format!("{BASE_URL}{path}");
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers()?)
+            .query(query)
+            .send_recorded()
+            .await?;
+        let status = resp.status();
+        super::rate_limit::record_google();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
+            return Err(format!("Google API error {status}: {}", truncate_error(&body)).into());
+        }
+        super::json_stream::for_each(resp, key, on_item).await
+    }
+
+    /// GET that sends `If-None-Match`; `None` means the resource is unchanged (304).
+    ///
+    /// Returns the body and the response ETag, if any.
+    pub async fn get_if_none_match(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        etag: Option<&str>,
+    ) -> Result<Option<(Value, Option<String>)>, Box<dyn std::error::Error>> {
+        let url = format!("{BASE_URL}{path}");
+        let mut req = self.client.get(&url).headers(self.headers()?).query(query);
+        if let Some(etag) = etag {
+            req = req.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let resp = req.send_recorded().await?;
+        let status = resp.status();
+        super::rate_limit::record_google();
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
+            return Err(format!("Google API error {status}: {}", truncate_error(&body)).into());
+        }
+        let new_etag = resp
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        Ok(Some((resp.json().await?, new_etag)))
+    }
+
     pub async fn post(
         &self,
         path: &str,
@@ -76,11 +333,13 @@ impl GoogleClient {
             .headers(self.headers()?)
             .header("Content-Type", "application/json")
             .json(body)
-            .send()
+            .send_recorded()
             .await?;
         let status = resp.status();
+        super::rate_limit::record_google();
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
             return Err(format!("Google API error {status}: {}", truncate_error(&body)).into());
         }
         Ok(resp.json().await?)
@@ -94,11 +353,37 @@ impl GoogleClient {
             .headers(self.headers()?)
             .header("Content-Type", "application/json")
             .json(body)
-            .send()
+            .send_recorded()
+            .await?;
+        let status = resp.status();
+        super::rate_limit::record_google();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
+            return Err(format!("Google API error {status}: {}", truncate_error(&body)).into());
+        }
+        Ok(resp.json().await?)
+    }
+
+    pub async fn patch(
+        &self,
+        path: &str,
+        body: &Value,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        let url = format!("{BASE_URL}{path}");
+        let resp = self
+            .client
+            .patch(&url)
+            .headers(self.headers()?)
+            .header("Content-Type", "application/json")
+            .json(body)
+            .send_recorded()
             .await?;
         let status = resp.status();
+        super::rate_limit::record_google();
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
             return Err(format!("Google API error {status}: {}", truncate_error(&body)).into());
         }
         Ok(resp.json().await?)
@@ -110,9 +395,10 @@ impl GoogleClient {
             .client
             .delete(&url)
             .headers(self.headers()?)
-            .send()
+            .send_recorded()
             .await?;
         let status = resp.status();
+        super::rate_limit::record_google();
 
         // 204 No Content is success for DELETE
         if status == reqwest::StatusCode::NO_CONTENT {
@@ -126,6 +412,7 @@ impl GoogleClient {
 
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
             return Err(format!("Google API error {status}: {}", truncate_error(&body)).into());
         }
         Ok(resp
@@ -134,6 +421,128 @@ impl GoogleClient {
             .unwrap_or(serde_json::json!({"status": "ok"})))
     }
 
+    /// Send an arbitrary request for `google api`.
+    ///
+    /// `path` is relative to `/androidpublisher/v3/applications` like every other
+    /// path; full URLs are accepted only on `googleapis.com` hosts (e.g. the
+    /// reporting API).
+    pub async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(String, String)],
+        body: Option<&Value>,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        let url = if path.starts_with("https://") {
+            check_google_host(path)?;
+            path.to_string()
+        } else {
+            format!("{BASE_URL}{path}")
+        };
+        let mut req = self
+            .client
+            .request(method, &url)
+            .headers(self.headers()?)
+            .query(query);
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+        let resp = req.send_recorded().await?;
+        let status = resp.status();
+        super::rate_limit::record_google();
+        let text = resp.text().await.unwrap_or_default();
+        if !status.is_success() {
+            super::rate_limit::record_google_error(status, &text);
+            return Err(format!("Google API error {status}: {}", truncate_error(&text)).into());
+        }
+        if text.trim().is_empty() {
+            return Ok(serde_json::json!({ "status": status.as_u16() }));
+        }
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Download a file from a `googleapis.com` URL, such as a report object in the
+    /// developer's Cloud Storage bucket.
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        check_google_host(url)?;
+        let resp = self
+            .client
+            .get(url)
+            .headers(self.headers()?)
+            .send_recorded()
+            .await?;
+        let status = resp.status();
+        super::rate_limit::record_google();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
+            return Err(format!("Google API error {status}: {}", truncate_error(&body)).into());
+        }
+        Ok(resp.bytes().await?.to_vec())
+    }
+
+    /// Query a Play Developer Reporting API metric set (e.g. `crashRateMetricSet`).
+    pub async fn query_metric_set(
+        &self,
+        package_name: &str,
+        metric_set: &str,
+        body: &Value,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        let url = format!("{REPORTING_BASE_URL}/{package_name}/{metric_set}:query");
+        let resp = self
+            .client
+            .post(&url)
+            .headers(self.headers()?)
+            .header("Content-Type", "application/json")
+            .json(body)
+            .send_recorded()
+            .await?;
+        let status = resp.status();
+        super::rate_limit::record_google();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
+            return Err(format!(
+                "Google Reporting API error {status}: {}",
+                truncate_error(&body)
+            )
+            .into());
+        }
+        Ok(resp.json().await?)
+    }
+
+    /// One page of the apps the credentials can access, across every developer
+    /// account the service account was invited to (Play Developer Reporting API).
+    pub async fn search_apps(
+        &self,
+        page_token: Option<&str>,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        let url = format!("{REPORTING_BASE_URL}:search");
+        let mut query = vec![("pageSize", "1000")];
+        if let Some(token) = page_token {
+            query.push(("pageToken", token));
+        }
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers()?)
+            .query(&query)
+            .send_recorded()
+            .await?;
+        let status = resp.status();
+        super::rate_limit::record_google();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
+            return Err(format!(
+                "Google Reporting API error {status}: {}",
+                truncate_error(&body)
+            )
+            .into());
+        }
+        Ok(resp.json().await?)
+    }
+
     pub async fn upload_image(
         &self,
         package_name: &str,
@@ -157,11 +566,13 @@ impl GoogleClient {
             .headers(self.headers()?)
             .header("Content-Type", content_type)
             .body(file_bytes)
-            .send()
+            .send_recorded()
             .await?;
         let status = resp.status();
+        super::rate_limit::record_google();
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
             return Err(format!(
                 "Google API upload error {status}: {} (URL: {url})",
                 truncate_error(&body)
@@ -187,11 +598,13 @@ impl GoogleClient {
             .headers(self.headers()?)
             .header("Content-Type", "application/octet-stream")
             .body(file_bytes)
-            .send()
+            .send_recorded()
             .await?;
         let status = resp.status();
+        super::rate_limit::record_google();
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
+            super::rate_limit::record_google_error(status, &body);
             return Err(format!(
                 "Google API upload error {status}: {}",
                 truncate_error(&body)
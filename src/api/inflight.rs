@@ -0,0 +1,140 @@
+//! Store resources opened by the running command and not yet finished.
+//!
+//! Google edits and reserved Apple screenshots/previews are tracked from the moment
+//! they are created until they are committed or discarded. When the command is
+//! cancelled with Ctrl-C, `cleanup` aborts whatever is still open so nothing is
+//! leaked or left half-uploaded.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use super::apple_client::AppleClient;
+use super::google_client::GoogleClient;
+
+enum Pending {
+    GoogleEdit {
+        client: GoogleClient,
+        package_name: String,
+        edit_id: String,
+    },
+    AppleAsset {
+        client: AppleClient,
+        path: String,
+    },
+}
+
+static PENDING: Mutex<BTreeMap<String, Pending>> = Mutex::new(BTreeMap::new());
+
+fn google_key(package_name: &str, edit_id: &str) -> String {
+    format!("google edit {package_name}/{edit_id}")
+}
+
+fn apple_key(path: &str) -> String {
+    format!("apple asset {path}")
+}
+
+fn insert(key: String, pending: Pending) {
+    if let Ok(mut map) = PENDING.lock() {
+        map.insert(key, pending);
+    }
+}
+
+fn remove(key: &str) {
+    if let Ok(mut map) = PENDING.lock() {
+        map.remove(key);
+    }
+}
+
+/// Track an edit opened for a single command.
+pub fn track_google_edit(client: &GoogleClient, package_name: &str, edit_id: &str) {
+    insert(
+        google_key(package_name, edit_id),
+        Pending::GoogleEdit {
+            client: client.clone(),
+            package_name: package_name.to_string(),
+            edit_id: edit_id.to_string(),
+        },
+    );
+}
+
+/// Stop tracking an edit once it is committed or discarded.
+pub fn release_google_edit(package_name: &str, edit_id: &str) {
+    remove(&google_key(package_name, edit_id));
+}
+
+/// Track a reserved asset (e.g. `/appScreenshots/{id}`) until its upload is committed.
+pub fn track_apple_asset(client: &AppleClient, path: &str) {
+    insert(
+        apple_key(path),
+        Pending::AppleAsset {
+            client: client.clone(),
+            path: path.to_string(),
+        },
+    );
+}
+
+pub fn release_apple_asset(path: &str) {
+    remove(&apple_key(path));
+}
+
+/// Abort everything still open, returning a description of each cleaned-up resource.
+pub async fn cleanup() -> Vec<String> {
+    let pending = match PENDING.lock() {
+        Ok(mut map) => std::mem::take(&mut *map),
+        Err(_) => return Vec::new(),
+    };
+    let mut cleaned = Vec::new();
+    for (key, item) in pending {
+        let ok = match item {
+            Pending::GoogleEdit {
+                client,
+                package_name,
+                edit_id,
+            } => client
+                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
+                .await
+                .is_ok(),
+            Pending::AppleAsset { client, path } => client.delete(&path).await.is_ok(),
+        };
+        if ok {
+            cleaned.push(key);
+        } else {
+            eprintln!("Could not clean up {key}");
+        }
+    }
+    cleaned
+}
+
+/// Error returned when a command is interrupted with Ctrl-C.
+#[derive(Debug)]
+pub struct Cancelled {
+    /// Resources aborted on the way out
+    pub cleaned_up: Vec<String>,
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cancelled")?;
+        if !self.cleaned_up.is_empty() {
+            write!(f, " (cleaned up {})", self.cleaned_up.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn released_resources_are_not_cleaned_up() {
+        let client = AppleClient::new("token".into());
+        track_apple_asset(&client, "/appScreenshots/test-released");
+        release_apple_asset("/appScreenshots/test-released");
+        let map = PENDING.lock().unwrap();
+        assert!(!map.contains_key(&apple_key("/appScreenshots/test-released")));
+    }
+}
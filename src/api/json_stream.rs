@@ -0,0 +1,233 @@
+//! Incremental parsing of large list responses.
+//!
+//! List endpoints return one object with a large array (`data`, `reviews`) next to
+//! small fields such as `links` or `tokenPagination`. `Splitter` is fed the body
+//! chunk by chunk and hands out each element of that array as soon as it is
+//! complete, so only one record is held in memory at a time; every other top-level
+//! field is parsed whole and returned at the end.
+
+use serde_json::{Map, Value};
+
+type Error = Box<dyn std::error::Error>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    Start,
+    Key,
+    Colon,
+    ValueStart,
+    Value,
+    Items,
+    Item,
+    AfterValue,
+    Done,
+}
+
+/// Push-parser for `{"<key>": [items...], "other": ...}` documents.
+pub struct Splitter {
+    key: String,
+    stage: Stage,
+    /// Bytes of the key, value or item being read
+    current: Vec<u8>,
+    current_key: String,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    fields: Map<String, Value>,
+}
+
+impl Splitter {
+    /// Stream the elements of the top-level array under `key`.
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            stage: Stage::Start,
+            current: Vec::new(),
+            current_key: String::new(),
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            fields: Map::new(),
+        }
+    }
+
+    /// Feed the next chunk, calling `on_item` for every completed element.
+    pub fn push(&mut self, chunk: &[u8], on_item: &mut dyn FnMut(Value)) -> Result<(), Error> {
+        for &b in chunk {
+            match self.stage {
+                Stage::Start => match b {
+                    b'{' => self.stage = Stage::Key,
+                    b if b.is_ascii_whitespace() => {}
+                    _ => return Err("response is not a JSON object".into()),
+                },
+                Stage::Key => match b {
+                    b'}' if self.current.is_empty() => self.stage = Stage::Done,
+                    b if b.is_ascii_whitespace() && self.current.is_empty() => {}
+                    _ => {
+                        self.current.push(b);
+                        if self.track(b) && self.current.len() > 1 {
+                            self.current_key = serde_json::from_slice(&self.current)?;
+                            self.current.clear();
+                            self.stage = Stage::Colon;
+                        }
+                    }
+                },
+                Stage::Colon => match b {
+                    b':' => self.stage = Stage::ValueStart,
+                    b if b.is_ascii_whitespace() => {}
+                    _ => return Err("malformed JSON: expected ':'".into()),
+                },
+                Stage::ValueStart => match b {
+                    b'[' if self.current_key == self.key => self.stage = Stage::Items,
+                    b if b.is_ascii_whitespace() => {}
+                    _ => {
+                        self.stage = Stage::Value;
+                        self.capture(b, on_item)?;
+                    }
+                },
+                Stage::Items => match b {
+                    b']' => self.stage = Stage::AfterValue,
+                    b if b.is_ascii_whitespace() => {}
+                    _ => {
+                        self.stage = Stage::Item;
+                        self.capture(b, on_item)?;
+                    }
+                },
+                Stage::Value | Stage::Item => self.capture(b, on_item)?,
+                Stage::AfterValue => match b {
+                    b',' => self.stage = Stage::Key,
+                    b'}' => self.stage = Stage::Done,
+                    b if b.is_ascii_whitespace() => {}
+                    _ => return Err("malformed JSON: expected ',' or '}'".into()),
+                },
+                Stage::Done => {
+                    if !b.is_ascii_whitespace() {
+                        return Err("unexpected data after JSON object".into());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The top-level fields other than the streamed array.
+    pub fn finish(self) -> Result<Map<String, Value>, Error> {
+        if self.stage != Stage::Done {
+            return Err("response ended before the JSON object was complete".into());
+        }
+        Ok(self.fields)
+    }
+
+    /// Track string and nesting state; true when `b` closed a string at depth 0.
+    fn track(&mut self, b: u8) -> bool {
+        if self.in_string {
+            match b {
+                _ if self.escaped => self.escaped = false,
+                b'\\' => self.escaped = true,
+                b'"' => {
+                    self.in_string = false;
+                    return self.depth == 0;
+                }
+                _ => {}
+            }
+            return false;
+        }
+        match b {
+            b'"' => self.in_string = true,
+            b'{' | b'[' => self.depth += 1,
+            b'}' | b']' => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+        false
+    }
+
+    /// Add a byte of a field value or array item, completing it at a delimiter.
+    fn capture(&mut self, b: u8, on_item: &mut dyn FnMut(Value)) -> Result<(), Error> {
+        let at_top = self.depth == 0 && !self.in_string;
+        let end = match self.stage {
+            Stage::Item => [b',', b']'],
+            _ => [b',', b'}'],
+        };
+        if at_top && end.contains(&b) {
+            let value: Value = serde_json::from_slice(self.current.trim_ascii())?;
+            self.current.clear();
+            self.stage = match (self.stage, b) {
+                (Stage::Item, b',') => Stage::Items,
+                (Stage::Item, _) => Stage::AfterValue,
+                (_, b',') => Stage::Key,
+                _ => Stage::Done,
+            };
+            if self.stage == Stage::Items || self.stage == Stage::AfterValue {
+                on_item(value);
+            } else {
+                self.fields
+                    .insert(std::mem::take(&mut self.current_key), value);
+            }
+            return Ok(());
+        }
+        self.track(b);
+        self.current.push(b);
+        Ok(())
+    }
+}
+
+/// Stream the elements under `key` of a response body, returning the other fields.
+pub async fn for_each(
+    mut resp: reqwest::Response,
+    key: &str,
+    on_item: &mut dyn FnMut(Value),
+) -> Result<Map<String, Value>, Error> {
+    let mut splitter = Splitter::new(key);
+    while let Some(chunk) = resp.chunk().await? {
+        splitter.push(&chunk, on_item)?;
+    }
+    splitter.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn splits_items_across_chunk_boundaries() {
+        let body = json!({
+            "data": [
+                {"id": "1", "attributes": {"body": "a \"quoted\" ] } , text"}},
+                {"id": "2", "attributes": {"list": [1, 2, {"x": null}]}},
+                "plain"
+            ],
+            "included": [{"id": "t1"}],
+            "links": {"next": "https://example.com/v1/apps?cursor=abc"},
+            "meta": {"paging": {"total": 3}}
+        })
+        .to_string();
+        // Every split point must give the same result
+        for size in [1, 2, 7, body.len()] {
+            let mut items = Vec::new();
+            let mut splitter = Splitter::new("data");
+            for chunk in body.as_bytes().chunks(size) {
+                splitter.push(chunk, &mut |item| items.push(item)).unwrap();
+            }
+            let fields = splitter.finish().unwrap();
+            assert_eq!(items.len(), 3);
+            assert_eq!(items[0]["attributes"]["body"], "a \"quoted\" ] } , text");
+            assert_eq!(items[1]["attributes"]["list"][2], json!({"x": null}));
+            assert_eq!(items[2], "plain");
+            assert_eq!(fields["included"][0]["id"], "t1");
+            assert_eq!(fields["meta"]["paging"]["total"], 3);
+            assert!(fields.get("data").is_none());
+        }
+
+        let mut splitter = Splitter::new("reviews");
+        splitter
+            .push(b"{ \"reviews\" : [ ] , \"n\": 1 }", &mut |_| panic!())
+            .unwrap();
+        assert_eq!(splitter.finish().unwrap()["n"], 1);
+        let mut truncated = Splitter::new("data");
+        truncated
+            .push(b"{\"data\": [{\"id\": 1}", &mut |_| {})
+            .unwrap();
+        assert!(truncated.finish().is_err());
+    }
+}
@@ -1,2 +1,10 @@
 pub mod apple_client;
+pub mod apple_errors;
+pub mod asset_upload;
 pub mod google_client;
+pub mod inflight;
+pub mod json_stream;
+pub mod rate_limit;
+pub mod recording;
+pub mod session;
+pub mod translator;
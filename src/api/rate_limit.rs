@@ -0,0 +1,145 @@
+//! Rate-limit budget of the running command.
+//!
+//! App Store Connect reports the hourly request budget on every response in the
+//! `X-Rate-Limit` header (`user-hour-lim:3600;user-hour-rem:3412;`). Google Play has
+//! no such header, so only requests and quota errors (429 / `RESOURCE_EXHAUSTED`)
+//! are counted. A warning is printed once when the budget runs low, and the totals
+//! are reported as `meta.rate_limit` in JSON output so bulk scripts can throttle.
+
+use serde_json::{json, Value};
+use std::sync::Mutex;
+
+/// Warn when less than this fraction of the hourly Apple budget is left.
+const LOW_BUDGET_FRACTION: f64 = 0.1;
+
+#[derive(Default)]
+struct Usage {
+    apple_requests: u64,
+    /// Latest `(limit, remaining)` reported by App Store Connect
+    apple_budget: Option<(u64, u64)>,
+    apple_warned: bool,
+    google_requests: u64,
+    google_quota_errors: u64,
+}
+
+static USAGE: Mutex<Usage> = Mutex::new(Usage {
+    apple_requests: 0,
+    apple_budget: None,
+    apple_warned: false,
+    google_requests: 0,
+    google_quota_errors: 0,
+});
+
+/// Parse `user-hour-lim:3600;user-hour-rem:3412;` into `(limit, remaining)`.
+fn parse_apple_header(value: &str) -> Option<(u64, u64)> {
+    let mut limit = None;
+    let mut remaining = None;
+    for part in value.split(';') {
+        match part.trim().split_once(':') {
+            Some(("user-hour-lim", v)) => limit = v.trim().parse().ok(),
+            Some(("user-hour-rem", v)) => remaining = v.trim().parse().ok(),
+            _ => {}
+        }
+    }
+    Some((limit?, remaining?))
+}
+
+fn is_low(limit: u64, remaining: u64) -> bool {
+    limit > 0 && (remaining as f64) < limit as f64 * LOW_BUDGET_FRACTION
+}
+
+/// Record an App Store Connect response.
+pub fn record_apple(headers: &reqwest::header::HeaderMap) {
+    let budget = headers
+        .get("x-rate-limit")
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_apple_header);
+    let Ok(mut usage) = USAGE.lock() else {
+        return;
+    };
+    usage.apple_requests += 1;
+    if let Some((limit, remaining)) = budget {
+        usage.apple_budget = Some((limit, remaining));
+        if is_low(limit, remaining) && !usage.apple_warned {
+            usage.apple_warned = true;
+            eprintln!(
+                "Warning: App Store Connect rate limit low: {remaining} of {limit} requests left this hour"
+            );
+        }
+    }
+}
+
+/// Record a Google Play request.
+pub fn record_google() {
+    if let Ok(mut usage) = USAGE.lock() {
+        usage.google_requests += 1;
+    }
+}
+
+/// Record a failed Google Play request, counting quota errors.
+pub fn record_google_error(status: reqwest::StatusCode, body: &str) {
+    let quota = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || ["RESOURCE_EXHAUSTED", "rateLimitExceeded", "quotaExceeded"]
+            .iter()
+            .any(|marker| body.contains(marker));
+    if !quota {
+        return;
+    }
+    if let Ok(mut usage) = USAGE.lock() {
+        usage.google_quota_errors += 1;
+        if usage.google_quota_errors == 1 {
+            eprintln!("Warning: Google Play API quota exceeded; slow down or retry later");
+        }
+    }
+}
+
+/// Budget used by this command, or `None` when no store was called.
+pub fn report() -> Option<Value> {
+    let usage = USAGE.lock().ok()?;
+    let mut out = serde_json::Map::new();
+    if usage.apple_requests > 0 {
+        let mut apple = json!({ "requests": usage.apple_requests });
+        if let Some((limit, remaining)) = usage.apple_budget {
+            apple["limit"] = json!(limit);
+            apple["remaining"] = json!(remaining);
+            apple["low"] = json!(is_low(limit, remaining));
+        }
+        out.insert("apple".into(), apple);
+    }
+    if usage.google_requests > 0 || usage.google_quota_errors > 0 {
+        out.insert(
+            "google".into(),
+            json!({
+                "requests": usage.google_requests,
+                "quota_errors": usage.google_quota_errors,
+            }),
+        );
+    }
+    (!out.is_empty()).then_some(Value::Object(out))
+}
+
+/// Attach the budget to an output object as `meta.rate_limit`.
+pub fn attach(value: &mut Value) {
+    if let (Some(report), Some(obj)) = (report(), value.as_object_mut()) {
+        let meta = obj.entry("meta").or_insert_with(|| json!({}));
+        if meta.is_object() {
+            meta["rate_limit"] = report;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_apple_rate_limit_header() {
+        assert_eq!(
+            parse_apple_header("user-hour-lim:3600;user-hour-rem:3412;"),
+            Some((3600, 3412))
+        );
+        assert_eq!(parse_apple_header("user-hour-lim:3600;"), None);
+        assert!(is_low(3600, 300));
+        assert!(!is_low(3600, 400));
+    }
+}
@@ -0,0 +1,323 @@
+//! Record HTTP traffic to a session file, or replay it instead of the network.
+//!
+//! `--record session.json` captures every App Store Connect, Google Play and asset
+//! download request made by the command, with credentials redacted: request headers
+//! are never stored, and query parameters or JSON fields whose names look like
+//! secrets are replaced. `--replay session.json` answers the same requests from the
+//! file, in order, without credentials or network access, so a session attached to a
+//! bug report reproduces the failure.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const REDACTED: &str = "[REDACTED]";
+/// Name fragments of query parameters and JSON fields that are never recorded.
+const SECRET_NAMES: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "signature",
+    "credential",
+    "private_key",
+    "privatekey",
+    "assertion",
+    "api_key",
+];
+/// Response headers kept in a recording; the rest are dropped.
+const KEPT_HEADERS: &[&str] = &["content-type", "etag", "location", "x-rate-limit"];
+
+/// Failure of a recorded or replayed request.
+#[derive(Debug)]
+pub enum Error {
+    Http(reqwest::Error),
+    Session(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Http(e) => e.fmt(f),
+            Error::Session(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Session {
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<Value>,
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+    /// Non-UTF-8 response bodies, such as images
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_base64: Option<String>,
+    #[serde(default, skip)]
+    used: bool,
+}
+
+enum Mode {
+    Record(PathBuf, Session),
+    Replay(PathBuf, Session),
+}
+
+static MODE: Mutex<Option<Mode>> = Mutex::new(None);
+
+/// Start recording to `path`.
+pub fn record(path: &Path) {
+    *MODE.lock().unwrap() = Some(Mode::Record(path.to_path_buf(), Session::default()));
+}
+
+/// Load `path` and serve its responses for the rest of the run.
+pub fn replay(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read session {}: {e}", path.display()))?;
+    let session: Session = serde_json::from_str(&content)
+        .map_err(|e| format!("invalid session {}: {e}", path.display()))?;
+    *MODE.lock().unwrap() = Some(Mode::Replay(path.to_path_buf(), session));
+    Ok(())
+}
+
+/// Whether responses come from a session file (so no credentials are needed).
+pub fn is_replaying() -> bool {
+    matches!(*MODE.lock().unwrap(), Some(Mode::Replay(..)))
+}
+
+/// Write the recorded session, if recording.
+pub fn finish() {
+    let guard = MODE.lock().unwrap();
+    let Some(Mode::Record(path, session)) = &*guard else {
+        return;
+    };
+    let written = serde_json::to_string_pretty(session)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+    match written {
+        Ok(()) => eprintln!(
+            "Recorded {} request(s) to {}",
+            session.entries.len(),
+            path.display()
+        ),
+        Err(e) => eprintln!("Warning: cannot write session {}: {e}", path.display()),
+    }
+}
+
+/// `RequestBuilder::send` that honours `--record` and `--replay`.
+pub trait RecordedSend {
+    fn send_recorded(
+        self,
+    ) -> impl std::future::Future<Output = Result<reqwest::Response, Error>> + Send;
+}
+
+impl RecordedSend for reqwest::RequestBuilder {
+    async fn send_recorded(self) -> Result<reqwest::Response, Error> {
+        let recording = match &*MODE.lock().unwrap() {
+            None => None,
+            Some(Mode::Record(..)) => Some(true),
+            Some(Mode::Replay(..)) => Some(false),
+        };
+        let Some(recording) = recording else {
+            return Ok(self.send().await?);
+        };
+        let (client, request) = self.build_split();
+        let request = request?;
+        let method = request.method().to_string();
+        let url = redact_url(request.url());
+        if !recording {
+            return replay_response(&method, &url);
+        }
+
+        let request_body = request.body().and_then(|b| b.as_bytes()).map(body_value);
+        let response = client.execute(request).await?;
+        let status = response.status().as_u16();
+        let headers: Vec<(String, String)> = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| KEPT_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let bytes = response.bytes().await?;
+
+        let (body, body_base64) = match std::str::from_utf8(&bytes) {
+            Ok(text) => (
+                Some(match serde_json::from_str::<Value>(text) {
+                    Ok(mut json) => {
+                        redact_json(&mut json);
+                        json
+                    }
+                    Err(_) => Value::String(text.to_string()),
+                }),
+                None,
+            ),
+            Err(_) => (
+                None,
+                Some(base64::engine::general_purpose::STANDARD.encode(&bytes)),
+            ),
+        };
+        let entry = Entry {
+            method,
+            url,
+            request_body,
+            status,
+            headers: headers.clone(),
+            body,
+            body_base64,
+            used: false,
+        };
+        if let Some(Mode::Record(_, session)) = &mut *MODE.lock().unwrap() {
+            session.entries.push(entry);
+        }
+        build_response(status, &headers, bytes.to_vec())
+    }
+}
+
+/// The next unused recorded response for `method url`.
+fn replay_response(method: &str, url: &str) -> Result<reqwest::Response, Error> {
+    let mut guard = MODE.lock().unwrap();
+    let Some(Mode::Replay(path, session)) = &mut *guard else {
+        return Err(Error::Session("replay session is not loaded".into()));
+    };
+    let entry = session
+        .entries
+        .iter_mut()
+        .find(|e| !e.used && e.method == method && e.url == url)
+        .ok_or_else(|| {
+            Error::Session(format!(
+                "no recorded response for {method} {url} in {}",
+                path.display()
+            ))
+        })?;
+    entry.used = true;
+    let bytes = match (&entry.body_base64, &entry.body) {
+        (Some(encoded), _) => base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| Error::Session(format!("invalid body_base64: {e}")))?,
+        (None, Some(Value::String(text))) => text.clone().into_bytes(),
+        (None, Some(json)) => json.to_string().into_bytes(),
+        (None, None) => Vec::new(),
+    };
+    build_response(entry.status, &entry.headers, bytes)
+}
+
+fn build_response(
+    status: u16,
+    headers: &[(String, String)],
+    body: Vec<u8>,
+) -> Result<reqwest::Response, Error> {
+    let mut builder = http::Response::builder().status(status);
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(body)
+        .map_err(|e| Error::Session(format!("invalid recorded response: {e}")))?;
+    Ok(reqwest::Response::from(response))
+}
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase().replace('-', "_");
+    SECRET_NAMES.iter().any(|s| name.contains(s)) || name == "key" || name == "sig"
+}
+
+/// The URL with secret-looking query parameter values replaced.
+fn redact_url(url: &reqwest::Url) -> String {
+    if url.query().is_none() {
+        return url.to_string();
+    }
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| {
+            let v = if is_secret(&k) {
+                REDACTED.to_string()
+            } else {
+                v.into_owned()
+            };
+            (k.into_owned(), v)
+        })
+        .collect();
+    let mut url = url.clone();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+    url.to_string()
+}
+
+/// Replace values of secret-looking fields anywhere in a JSON document.
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if is_secret(key) {
+                    *v = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// A request body as recorded: redacted JSON, or just its size.
+fn body_value(bytes: &[u8]) -> Value {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json
+        }
+        Err(_) => serde_json::json!({ "bytes": bytes.len() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_secret_query_parameters_and_fields() {
+        let url = reqwest::Url::parse(
+            "https://example.com/upload?X-Amz-Signature=abc&X-Amz-Credential=me&limit=5",
+        )
+        .unwrap();
+        assert_eq!(
+            redact_url(&url),
+            "https://example.com/upload?X-Amz-Signature=%5BREDACTED%5D&X-Amz-Credential=%5BREDACTED%5D&limit=5"
+        );
+
+        let mut body = serde_json::json!({
+            "access_token": "ya29.secret",
+            "data": [{"attributes": {"keywords": "photo", "privateKey": "-----BEGIN"}}]
+        });
+        redact_json(&mut body);
+        assert_eq!(body["access_token"], REDACTED);
+        assert_eq!(body["data"][0]["attributes"]["privateKey"], REDACTED);
+        assert_eq!(body["data"][0]["attributes"]["keywords"], "photo");
+    }
+
+    #[test]
+    fn binary_request_bodies_record_only_their_size() {
+        assert_eq!(
+            body_value(&[0xff, 0x00, 0x12]),
+            serde_json::json!({"bytes": 3})
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Connections and access tokens shared by every client built in this process.
+//!
+//! A single command builds one client, but `batch` and the REPL run many commands
+//! in one process. Sharing the connection pool, and reusing a store's token until
+//! shortly before it expires, saves a TLS handshake and a token exchange per command.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Tokens this close to expiry are replaced rather than reused.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Idle connections are kept this long for the next request to the same host.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// Ping interval that keeps HTTP/2 connections open between requests.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+static HTTP: OnceLock<Arc<reqwest::Client>> = OnceLock::new();
+static TOKENS: Mutex<Option<HashMap<String, (String, Instant)>>> = Mutex::new(None);
+
+/// The process-wide HTTP client.
+///
+/// Every API client, download and upload shares it, so a `sync`, `batch` or REPL
+/// session reuses pooled (HTTP/2 where the server supports it) connections instead
+/// of paying TLS setup on each call.
+pub fn http() -> Arc<reqwest::Client> {
+    HTTP.get_or_init(|| {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("storeops/", env!("CARGO_PKG_VERSION")))
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(KEEP_ALIVE_INTERVAL)
+            .http2_keep_alive_interval(KEEP_ALIVE_INTERVAL)
+            .http2_keep_alive_while_idle(true)
+            .build()
+            .unwrap_or_default();
+        Arc::new(client)
+    })
+    .clone()
+}
+
+/// A token stored under `key` that is still valid for a while.
+pub fn cached_token(key: &str) -> Option<String> {
+    let tokens = TOKENS.lock().unwrap();
+    let (token, expires) = tokens.as_ref()?.get(key)?;
+    (Instant::now() + EXPIRY_MARGIN < *expires).then(|| token.clone())
+}
+
+/// Remember a token issued now that is valid for `lifetime`.
+pub fn store_token(key: &str, token: &str, lifetime: Duration) {
+    TOKENS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(
+            key.to_string(),
+            (token.to_string(), Instant::now() + lifetime),
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_tokens_until_close_to_expiry() {
+        store_token("test:fresh", "t1", Duration::from_secs(600));
+        store_token("test:stale", "t2", Duration::from_secs(30));
+        assert_eq!(cached_token("test:fresh").as_deref(), Some("t1"));
+        assert_eq!(cached_token("test:stale"), None);
+        assert_eq!(cached_token("test:missing"), None);
+    }
+}
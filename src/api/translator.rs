@@ -0,0 +1,157 @@
+//! Machine translation backends (DeepL, OpenAI) used to draft localized metadata.
+
+use serde_json::{json, Value};
+
+use crate::config::translation::{Provider, TranslationConfig};
+
+const DEEPL_URL: &str = "https://api.deepl.com/v2/translate";
+const DEEPL_FREE_URL: &str = "https://api-free.deepl.com/v2/translate";
+const OPENAI_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+pub struct Translator {
+    client: std::sync::Arc<reqwest::Client>,
+    provider: Provider,
+    api_key: String,
+    model: String,
+}
+
+impl Translator {
+    pub fn new(provider: Provider, config: &TranslationConfig) -> Result<Self, String> {
+        let api_key = config.api_key(provider).ok_or_else(|| {
+            format!("no API key for {provider}; set it under [translation] in the config")
+        })?;
+        Ok(Self {
+            client: super::session::http(),
+            provider,
+            api_key,
+            model: config.openai_model().to_string(),
+        })
+    }
+
+    /// Translate `text` between two locale codes (e.g. `en-US` -> `de-DE`).
+    pub async fn translate(
+        &self,
+        text: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        match self.provider {
+            Provider::Deepl => self.deepl(text, from, to).await,
+            Provider::Openai => self.openai(text, from, to).await,
+        }
+    }
+
+    async fn deepl(
+        &self,
+        text: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        // Free-tier keys end in ":fx" and use a separate host
+        let url = if self.api_key.ends_with(":fx") {
+            DEEPL_FREE_URL
+        } else {
+            DEEPL_URL
+        };
+        let body = json!({
+            "text": [text],
+            "source_lang": deepl_source_lang(from),
+            "target_lang": deepl_target_lang(to),
+            "preserve_formatting": true,
+        });
+        let resp: Value = self
+            .send(
+                self.client
+                    .post(url)
+                    .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+                    .json(&body),
+            )
+            .await?;
+        Ok(resp["translations"][0]["text"]
+            .as_str()
+            .ok_or("DeepL returned no translation")?
+            .to_string())
+    }
+
+    async fn openai(
+        &self,
+        text: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let body = json!({
+            "model": self.model,
+            "temperature": 0,
+            "messages": [
+                {
+                    "role": "system",
+                    "content": format!(
+                        "Translate app store metadata from {from} to {to}. Preserve line breaks, \
+                         list separators, URLs and brand names. Reply with the translation only."
+                    )
+                },
+                { "role": "user", "content": text }
+            ]
+        });
+        let resp: Value = self
+            .send(
+                self.client
+                    .post(OPENAI_URL)
+                    .bearer_auth(&self.api_key)
+                    .json(&body),
+            )
+            .await?;
+        Ok(resp["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or("OpenAI returned no translation")?
+            .trim()
+            .to_string())
+    }
+
+    async fn send(
+        &self,
+        req: reqwest::RequestBuilder,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        let resp = req.send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("{} API error {status}: {body}", self.provider).into());
+        }
+        Ok(resp.json().await?)
+    }
+}
+
+/// DeepL source languages are bare language codes (`EN`, `DE`).
+fn deepl_source_lang(locale: &str) -> String {
+    locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or(locale)
+        .to_uppercase()
+}
+
+/// DeepL target languages are bare codes except for a few regional variants.
+fn deepl_target_lang(locale: &str) -> String {
+    let upper = locale.replace('_', "-").to_uppercase();
+    match upper.as_str() {
+        "EN-GB" | "EN-US" | "PT-BR" | "PT-PT" => upper,
+        "EN" => "EN-US".to_string(),
+        "PT" => "PT-PT".to_string(),
+        "ZH-TW" | "ZH-HANT" => "ZH-HANT".to_string(),
+        _ => deepl_source_lang(locale),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deepl_language_codes() {
+        assert_eq!(deepl_source_lang("en-US"), "EN");
+        assert_eq!(deepl_target_lang("de-DE"), "DE");
+        assert_eq!(deepl_target_lang("pt-BR"), "PT-BR");
+        assert_eq!(deepl_target_lang("zh-TW"), "ZH-HANT");
+    }
+}
@@ -1,6 +1,9 @@
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 use serde::Serialize;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a generated App Store Connect token is valid (Apple allows up to 20 minutes).
+pub const TOKEN_LIFETIME: Duration = Duration::from_secs(20 * 60);
 
 #[derive(Debug, Serialize, serde::Deserialize)]
 struct Claims {
@@ -19,7 +22,7 @@ pub fn generate_token(
     let claims = Claims {
         iss: issuer_id.to_string(),
         iat: now,
-        exp: now + 20 * 60, // 20 minutes
+        exp: now + TOKEN_LIFETIME.as_secs(),
         aud: "appstoreconnect-v1".to_string(),
     };
     let mut header = Header::new(Algorithm::ES256);
@@ -1,6 +1,6 @@
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Deserialize)]
 pub struct ServiceAccount {
@@ -18,6 +18,9 @@ struct Claims {
     exp: u64,
 }
 
+/// How long a Google access token is valid.
+pub const TOKEN_LIFETIME: Duration = Duration::from_secs(3600);
+
 #[derive(Debug, Deserialize)]
 struct TokenResponse {
     access_token: String,
@@ -28,6 +31,14 @@ const ALLOWED_TOKEN_URIS: &[&str] = &[
     "https://accounts.google.com/o/oauth2/token",
 ];
 
+/// Publisher API for edits/reviews, Reporting API for Android Vitals, Cloud
+/// Storage for the monthly earnings and sales reports.
+const SCOPES: &[&str] = &[
+    "https://www.googleapis.com/auth/androidpublisher",
+    "https://www.googleapis.com/auth/playdeveloperreporting",
+    "https://www.googleapis.com/auth/devstorage.read_only",
+];
+
 pub async fn get_access_token(sa_path: &str) -> Result<String, Box<dyn std::error::Error>> {
     let content = std::fs::read_to_string(sa_path)?;
     let sa: ServiceAccount = serde_json::from_str(&content)?;
@@ -39,16 +50,16 @@ pub async fn get_access_token(sa_path: &str) -> Result<String, Box<dyn std::erro
     let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
     let claims = Claims {
         iss: sa.client_email.clone(),
-        scope: "https://www.googleapis.com/auth/androidpublisher".to_string(),
+        scope: SCOPES.join(" "),
         aud: sa.token_uri.clone(),
         iat: now,
-        exp: now + 3600,
+        exp: now + TOKEN_LIFETIME.as_secs(),
     };
 
     let key = EncodingKey::from_rsa_pem(sa.private_key.as_bytes())?;
     let jwt = encode(&Header::new(Algorithm::RS256), &claims, &key)?;
 
-    let client = reqwest::Client::new();
+    let client = crate::api::session::http();
     let resp: TokenResponse = client
         .post(&sa.token_uri)
         .form(&[
@@ -2,7 +2,69 @@ use crate::config::profiles::Credentials;
 use crate::config::Config;
 use std::path::Path;
 
+/// Expand a leading `~` and `$VAR` / `${VAR}` references in a credential path.
+pub fn expand_path(path: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = path;
+    if rest == "~" || rest.starts_with("~/") {
+        let home = directories::BaseDirs::new()
+            .ok_or("cannot determine home directory")?
+            .home_dir()
+            .to_string_lossy()
+            .into_owned();
+        out.push_str(&home);
+        rest = &rest[1..];
+    }
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let (name, remainder) = match after.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced
+                    .find('}')
+                    .ok_or_else(|| format!("unterminated ${{ in path: {path}"))?;
+                (&braced[..end], &braced[end + 1..])
+            }
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        if name.is_empty() {
+            out.push('$');
+        } else {
+            let value = std::env::var(name)
+                .map_err(|_| format!("environment variable {name} in {path} is not set"))?;
+            out.push_str(&value);
+        }
+        rest = remainder;
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Warning for a file that group or other users can read, if any.
+#[cfg(unix)]
+pub fn permission_warning(path: &str) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(path).ok()?.permissions().mode() & 0o777;
+    (mode & 0o077 != 0).then(|| {
+        format!(
+            "{path} is readable by group or other users (mode {mode:o}); run `chmod 600 {path}`"
+        )
+    })
+}
+
+#[cfg(not(unix))]
+pub fn permission_warning(_path: &str) -> Option<String> {
+    None
+}
+
 fn validate_file_path(path: &str) -> Result<String, String> {
+    let expanded = expand_path(path)?;
+    let path = expanded.as_str();
     let p = Path::new(path);
     if !p.exists() {
         return Err(format!("file not found: {path}"));
@@ -75,3 +137,24 @@ pub fn resolve_google_credentials(
         _ => Err("active profile is not a Google profile".to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_home_and_environment_variables() {
+        std::env::set_var("STOREOPS_TEST_KEYS", "/secure/keys");
+        assert_eq!(
+            expand_path("$STOREOPS_TEST_KEYS/AuthKey.p8").unwrap(),
+            "/secure/keys/AuthKey.p8"
+        );
+        assert_eq!(
+            expand_path("${STOREOPS_TEST_KEYS}_old/sa.json").unwrap(),
+            "/secure/keys_old/sa.json"
+        );
+        assert!(expand_path("$STOREOPS_TEST_UNSET_VAR/key.p8").is_err());
+        assert_eq!(expand_path("/plain/path").unwrap(), "/plain/path");
+        assert!(!expand_path("~/key.p8").unwrap().starts_with('~'));
+    }
+}
@@ -0,0 +1,187 @@
+//! Raw API requests shared by `apple api` and `google api`.
+//!
+//! An escape hatch for endpoints storeops does not wrap yet: the request is sent with
+//! the profile's credentials, so no hand-made JWT or OAuth token is needed. Bodies
+//! are checked before anything is sent (valid JSON, and a JSON:API `data` object
+//! for App Store Connect writes), and `--dry-run` prints the request without
+//! authenticating at all.
+
+use clap::{Args, ValueEnum};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, ValueEnum)]
+#[value(rename_all = "UPPER")]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+impl Method {
+    pub fn as_reqwest(self) -> reqwest::Method {
+        match self {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Patch => reqwest::Method::PATCH,
+            Method::Delete => reqwest::Method::DELETE,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ApiArgs {
+    /// HTTP method
+    #[arg(value_enum, ignore_case = true)]
+    pub method: Method,
+    /// Path relative to the API base (see the command help), or a full URL on the same API host
+    pub path: String,
+    /// Query parameter as key=value (repeatable)
+    #[arg(long, value_parser = parse_query)]
+    pub query: Vec<(String, String)>,
+    /// JSON request body file
+    #[arg(long, conflicts_with = "body")]
+    pub body_file: Option<PathBuf>,
+    /// Inline JSON request body
+    #[arg(long)]
+    pub body: Option<String>,
+    /// Validate and print the request without sending it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+fn parse_query(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .filter(|(k, _)| !k.is_empty())
+        .ok_or_else(|| format!("expected key=value, got '{s}'"))
+}
+
+/// Which API the request goes to, for store-specific body checks.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Api {
+    Apple,
+    Google,
+}
+
+impl ApiArgs {
+    /// The parsed request body, if one was given.
+    pub fn body(&self) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+        let text = match (&self.body_file, &self.body) {
+            (Some(path), _) => std::fs::read_to_string(path)
+                .map_err(|e| format!("cannot read {}: {e}", path.display()))?,
+            (None, Some(body)) => body.clone(),
+            (None, None) => return Ok(None),
+        };
+        let value: Value = serde_json::from_str(&text)
+            .map_err(|e| format!("request body is not valid JSON: {e}"))?;
+        Ok(Some(value))
+    }
+
+    /// Check the request shape before it is sent; returns the parsed body.
+    pub fn validate(&self, api: Api) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+        if !self.path.starts_with('/') && !self.path.starts_with("https://") {
+            return Err(format!(
+                "path must start with '/' or be an https URL, got '{}'",
+                self.path
+            )
+            .into());
+        }
+        let body = self.body()?;
+        match (self.method, &body) {
+            (Method::Get, Some(_)) => return Err("GET requests cannot have a body".into()),
+            (Method::Post | Method::Put | Method::Patch, None) => {
+                return Err("this method needs --body-file or --body".into())
+            }
+            _ => {}
+        }
+        if let (Api::Apple, Some(body), Method::Post | Method::Patch) = (api, &body, self.method) {
+            check_json_api(body)?;
+        }
+        Ok(body)
+    }
+
+    /// What would be sent, for `--dry-run`.
+    pub fn preview(&self, api: Api) -> Result<Value, Box<dyn std::error::Error>> {
+        let body = self.validate(api)?;
+        Ok(json!({
+            "dry_run": true,
+            "method": self.method.as_reqwest().as_str(),
+            "path": self.path,
+            "query": self.query.iter().map(|(k, v)| format!("{k}={v}")).collect::<Vec<_>>(),
+            "body": body,
+        }))
+    }
+}
+
+/// App Store Connect writes take a JSON:API document: `{"data": {"type": ..., ...}}`.
+fn check_json_api(body: &Value) -> Result<(), Box<dyn std::error::Error>> {
+    let data = &body["data"];
+    let resources: Vec<&Value> = match data {
+        Value::Object(_) => vec![data],
+        Value::Array(items) => items.iter().collect(),
+        _ => return Err("App Store Connect bodies need a top-level \"data\" object".into()),
+    };
+    for resource in resources {
+        if !resource["type"].is_string() {
+            return Err("every resource in \"data\" needs a \"type\"".into());
+        }
+        for key in ["attributes", "relationships"] {
+            if !resource[key].is_null() && !resource[key].is_object() {
+                return Err(format!("\"{key}\" must be an object").into());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(method: Method, body: Option<&str>) -> ApiArgs {
+        ApiArgs {
+            method,
+            path: "/apps".into(),
+            query: vec![],
+            body_file: None,
+            body: body.map(String::from),
+            dry_run: true,
+        }
+    }
+
+    #[test]
+    fn validates_bodies_per_method_and_store() {
+        assert!(args(Method::Get, None).validate(Api::Apple).is_ok());
+        assert!(args(Method::Get, Some("{}")).validate(Api::Apple).is_err());
+        assert!(args(Method::Post, None).validate(Api::Google).is_err());
+        assert!(args(Method::Put, Some("{not json"))
+            .validate(Api::Google)
+            .is_err());
+        assert!(args(Method::Put, Some(r#"{"track": "beta"}"#))
+            .validate(Api::Google)
+            .is_ok());
+
+        assert!(args(Method::Patch, Some(r#"{"name": "x"}"#))
+            .validate(Api::Apple)
+            .is_err());
+        assert!(args(
+            Method::Patch,
+            Some(r#"{"data": {"type": "apps", "id": "1", "attributes": {}}}"#)
+        )
+        .validate(Api::Apple)
+        .is_ok());
+    }
+
+    #[test]
+    fn parses_query_pairs() {
+        assert_eq!(
+            parse_query("filter[platform]=IOS").unwrap(),
+            ("filter[platform]".to_string(), "IOS".to_string())
+        );
+        assert!(parse_query("noequals").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
 
+use super::resolve;
 use crate::api::apple_client::AppleClient;
 
 #[derive(Subcommand)]
@@ -8,8 +9,8 @@ use crate::api::apple_client::AppleClient;
 pub enum AgeRatingCommand {
     /// Get the age rating declaration for an app
     Get {
-        /// App ID (will look up the app info automatically)
-        app_id: String,
+        /// App ID or bundle ID (will look up the app info automatically)
+        app_id: Option<String>,
     },
     /// Update age rating declaration
     Update {
@@ -57,6 +58,7 @@ pub async fn handle(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         AgeRatingCommand::Get { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             let app_info: Value = client
                 .get::<Value>(&format!("/apps/{app_id}/appInfos"), &[("limit", "1")])
                 .await?;
@@ -1,31 +1,411 @@
-use clap::Subcommand;
-use serde_json::Value;
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use clap::{Subcommand, ValueEnum};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Read;
 
+use super::resolve;
 use crate::api::apple_client::AppleClient;
+use crate::api::recording::RecordedSend;
+use crate::output::chart;
+
+/// Product type identifiers of first-time app downloads in sales reports
+const DOWNLOAD_TYPES: &[&str] = &["1", "1F", "1T", "F1", "1E", "1EP", "1EU"];
+/// Widest bar drawn by `--chart`, in terminal cells
+const CHART_WIDTH: usize = 40;
+/// Analytics reports with crashing devices and active devices per app version
+const CRASHES_REPORT: &str = "App Crashes";
+const SESSIONS_REPORT: &str = "App Sessions";
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SalesMetric {
+    /// First-time downloads
+    Downloads,
+    /// All units, including updates, re-downloads and in-app purchases
+    Units,
+    /// Developer proceeds, summed in the proceeds currencies of the report
+    Proceeds,
+}
 
 #[derive(Subcommand)]
 pub enum AnalyticsCommand {
     /// Download sales and trends report
+    ///
+    /// Examples:
+    ///   storeops apple analytics sales 85012345
+    ///   storeops apple analytics sales 85012345 --chart --metric proceeds --points 30
     Sales {
         /// App ID (vendor number)
         app_id: String,
         /// Report period
         #[arg(long, default_value = "daily")]
         period: String,
+        /// Chart the trend of recent reports in the terminal
+        #[arg(long)]
+        chart: bool,
+        /// Metric to chart
+        #[arg(long, value_enum, default_value = "downloads")]
+        metric: SalesMetric,
+        /// Number of reports (days, weeks or months) to chart
+        #[arg(long, default_value = "14")]
+        points: u32,
+    },
+    /// Compare crash-free device-days across app versions
+    ///
+    /// Reads the daily App Crashes and App Sessions analytics reports of the app's
+    /// ongoing report request, creating the request on first use (Apple then takes a
+    /// day or two to produce the first reports). The reports count unique devices per
+    /// day, so a device active on three days counts three times: the figure is the
+    /// share of device-days without a crash, not of distinct users.
+    ///
+    /// Examples:
+    ///   storeops apple analytics crash-free --app 1234567890 --versions 1.4.0,1.4.1
+    ///   storeops apple analytics crash-free --app com.example.app --versions 1.4.0,1.4.1 --days 14
+    CrashFree {
+        /// App ID or bundle ID
+        #[arg(long)]
+        app: Option<String>,
+        /// App versions to compare (comma-separated)
+        #[arg(long, value_delimiter = ',', required = true)]
+        versions: Vec<String>,
+        /// Days of daily reports to read
+        #[arg(long, default_value = "7")]
+        days: u32,
     },
 }
 
+/// Rows of a sales or finance report, keyed by column header.
+///
+/// Reports are gzip-compressed TSV; uncompressed bodies are accepted as well.
+pub fn parse_report(
+    bytes: &[u8],
+) -> Result<Vec<HashMap<String, String>>, Box<dyn std::error::Error>> {
+    let text = if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(bytes).read_to_string(&mut text)?;
+        text
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    };
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let columns: Vec<&str> = header.split('\t').map(str::trim).collect();
+    Ok(lines
+        .map(|line| {
+            columns
+                .iter()
+                .zip(line.split('\t'))
+                .map(|(c, v)| (c.to_string(), v.trim().to_string()))
+                .collect()
+        })
+        .collect())
+}
+
+/// Total of `metric` over a sales summary report, and the proceeds currencies seen.
+fn total(rows: &[HashMap<String, String>], metric: SalesMetric) -> (f64, BTreeSet<String>) {
+    let number = |row: &HashMap<String, String>, column: &str| {
+        row.get(column)
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0)
+    };
+    let mut currencies = BTreeSet::new();
+    let mut sum = 0.0;
+    for row in rows {
+        let units = number(row, "Units");
+        sum += match metric {
+            SalesMetric::Units => units,
+            SalesMetric::Downloads => {
+                let kind = row
+                    .get("Product Type Identifier")
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                if DOWNLOAD_TYPES.contains(&kind) {
+                    units
+                } else {
+                    0.0
+                }
+            }
+            SalesMetric::Proceeds => {
+                let proceeds = units * number(row, "Developer Proceeds");
+                if proceeds != 0.0 {
+                    if let Some(currency) = row.get("Currency of Proceeds") {
+                        currencies.insert(currency.clone());
+                    }
+                }
+                proceeds
+            }
+        };
+    }
+    (sum, currencies)
+}
+
+/// `reportDate` values of the `points` most recent reports, oldest first.
+fn report_dates(frequency: &str, points: u32, today: NaiveDate) -> Vec<String> {
+    let mut dates: Vec<String> = match frequency {
+        "WEEKLY" => {
+            // Weekly reports cover Monday to Sunday and are named by the Sunday
+            let back = match today.weekday().num_days_from_sunday() {
+                0 => 7,
+                n => n,
+            };
+            let last = today - Duration::days(back as i64);
+            (0..points)
+                .map(|i| (last - Duration::weeks(i as i64)).to_string())
+                .collect()
+        }
+        "MONTHLY" => {
+            let mut month = today.with_day(1).unwrap_or(today);
+            (0..points)
+                .map(|_| {
+                    month = (month - Duration::days(1)).with_day(1).unwrap_or(month);
+                    month.format("%Y-%m").to_string()
+                })
+                .collect()
+        }
+        _ => (1..=points)
+            .map(|i| (today - Duration::days(i as i64)).to_string())
+            .collect(),
+    };
+    dates.reverse();
+    dates
+}
+
+async fn chart_sales(
+    vendor_number: &str,
+    frequency: &str,
+    metric: SalesMetric,
+    points: u32,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let dates = report_dates(frequency, points, Utc::now().date_naive());
+    eprintln!(
+        "Fetching {} {} report(s)...",
+        dates.len(),
+        frequency.to_lowercase()
+    );
+    let mut series = Vec::new();
+    let mut currencies = BTreeSet::new();
+    for date in &dates {
+        let report = client
+            .get_report(
+                "/salesReports",
+                &[
+                    ("filter[vendorNumber]", vendor_number),
+                    ("filter[frequency]", frequency),
+                    ("filter[reportType]", "SALES"),
+                    ("filter[reportSubType]", "SUMMARY"),
+                    ("filter[version]", "1_0"),
+                    ("filter[reportDate]", date),
+                ],
+            )
+            .await?;
+        let value = match report {
+            Some(bytes) => {
+                let (sum, seen) = total(&parse_report(&bytes)?, metric);
+                currencies.extend(seen);
+                Some(sum)
+            }
+            None => None,
+        };
+        series.push((date.clone(), value));
+    }
+
+    let metric_name = metric.to_possible_value().map(|v| v.get_name().to_string());
+    let values: Vec<Option<f64>> = series.iter().map(|(_, v)| *v).collect();
+    let sparkline = chart::sparkline(&values);
+    eprintln!(
+        "{} ({}): {sparkline}",
+        metric_name.as_deref().unwrap_or_default(),
+        frequency.to_lowercase()
+    );
+    eprint!("{}", chart::bars(&series, CHART_WIDTH));
+    if currencies.len() > 1 {
+        eprintln!(
+            "Note: proceeds are summed across currencies ({})",
+            currencies.iter().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    Ok(json!({
+        "vendor_number": vendor_number,
+        "frequency": frequency,
+        "metric": metric_name,
+        "currencies": currencies,
+        "sparkline": sparkline,
+        "series": series
+            .iter()
+            .map(|(date, value)| json!({ "date": date, "value": value }))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+/// Crash figures of one app version over the days read. Daily unique-device counts
+/// are summed, so device figures are device-days.
+#[derive(Debug, PartialEq)]
+struct CrashFree {
+    version: String,
+    crashes: f64,
+    crashing_device_days: f64,
+    active_device_days: f64,
+}
+
+impl CrashFree {
+    /// Percentage of active device-days without a crash, when the version had any.
+    fn percent(&self) -> Option<f64> {
+        (self.active_device_days > 0.0).then(|| {
+            let share = 1.0 - (self.crashing_device_days / self.active_device_days).min(1.0);
+            (share * 10_000.0).round() / 100.0
+        })
+    }
+}
+
+/// Crash figures of each version from App Crashes and App Sessions report rows.
+fn crash_free(
+    crash_rows: &[HashMap<String, String>],
+    session_rows: &[HashMap<String, String>],
+    versions: &[String],
+) -> Vec<CrashFree> {
+    let sum = |rows: &[HashMap<String, String>], version: &str, column: &str| -> f64 {
+        rows.iter()
+            .filter(|r| r.get("App Version").map(String::as_str) == Some(version))
+            .filter_map(|r| r.get(column)?.parse::<f64>().ok())
+            .sum()
+    };
+    versions
+        .iter()
+        .map(|v| CrashFree {
+            version: v.clone(),
+            crashes: sum(crash_rows, v, "Crashes"),
+            crashing_device_days: sum(crash_rows, v, "Unique Devices"),
+            active_device_days: sum(session_rows, v, "Unique Devices"),
+        })
+        .collect()
+}
+
+/// ID of the app's ongoing analytics report request, created when missing.
+async fn ongoing_report_request(
+    app_id: &str,
+    client: &AppleClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let requests: Value = client
+        .get(
+            &format!("/apps/{app_id}/analyticsReportRequests"),
+            &[("filter[accessType]", "ONGOING")],
+        )
+        .await?;
+    if let Some(id) = requests["data"][0]["id"].as_str() {
+        return Ok(id.to_string());
+    }
+    let body = json!({
+        "data": {
+            "type": "analyticsReportRequests",
+            "attributes": { "accessType": "ONGOING" },
+            "relationships": {
+                "app": { "data": { "type": "apps", "id": app_id } }
+            }
+        }
+    });
+    client.post("/analyticsReportRequests", &body).await?;
+    Err("created an ongoing analytics report request for the app; \
+         Apple takes a day or two to produce the first reports, run this again then"
+        .into())
+}
+
+/// Rows of the daily instances of a report processed in the last `days` days.
+async fn report_rows(
+    request_id: &str,
+    name: &str,
+    days: u32,
+    client: &AppleClient,
+) -> Result<Vec<HashMap<String, String>>, Box<dyn std::error::Error>> {
+    let reports: Value = client
+        .get(
+            &format!("/analyticsReportRequests/{request_id}/reports"),
+            &[("filter[name]", name)],
+        )
+        .await?;
+    let report_id = reports["data"][0]["id"]
+        .as_str()
+        .ok_or_else(|| format!("the {name} report is not available yet"))?;
+    let (instances, _) = super::pricing::all_pages(
+        &format!("/analyticsReports/{report_id}/instances"),
+        &[("filter[granularity]", "DAILY"), ("limit", "200")],
+        client,
+    )
+    .await?;
+    let since = (Utc::now().date_naive() - Duration::days(days as i64)).to_string();
+    let http = crate::api::session::http();
+    let mut rows = Vec::new();
+    for instance in &instances {
+        let date = instance["attributes"]["processingDate"]
+            .as_str()
+            .unwrap_or_default();
+        let Some(instance_id) = instance["id"].as_str().filter(|_| date >= since.as_str()) else {
+            continue;
+        };
+        eprintln!("Fetching {name} report of {date}...");
+        let segments: Value = client
+            .get(
+                &format!("/analyticsReportInstances/{instance_id}/segments"),
+                &[],
+            )
+            .await?;
+        for segment in segments["data"].as_array().into_iter().flatten() {
+            let Some(url) = segment["attributes"]["url"].as_str() else {
+                continue;
+            };
+            let bytes = http
+                .get(url)
+                .send_recorded()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?;
+            rows.extend(parse_report(&bytes)?);
+        }
+    }
+    Ok(rows)
+}
+
+/// Crash rate of one version over the last `days` days: the percentage of its active
+/// device-days with a crash, `None` while it has no sessions.
+pub(super) async fn crash_rate(
+    app_id: &str,
+    version: &str,
+    days: u32,
+    client: &AppleClient,
+) -> Result<Option<f64>, Box<dyn std::error::Error>> {
+    let request_id = &ongoing_report_request(app_id, client).await?;
+    let crash_rows = report_rows(request_id, CRASHES_REPORT, days, client).await?;
+    let session_rows = report_rows(request_id, SESSIONS_REPORT, days, client).await?;
+    let stats = crash_free(&crash_rows, &session_rows, &[version.to_string()]);
+    Ok(stats[0]
+        .percent()
+        .map(|p| ((100.0 - p) * 100.0).round() / 100.0))
+}
+
 pub async fn handle(
     cmd: &AnalyticsCommand,
     client: &AppleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
-        AnalyticsCommand::Sales { app_id, period } => {
+        AnalyticsCommand::Sales {
+            app_id,
+            period,
+            chart,
+            metric,
+            points,
+        } => {
             let frequency = match period.as_str() {
                 "weekly" => "WEEKLY",
                 "monthly" => "MONTHLY",
                 _ => "DAILY",
             };
+            if *chart {
+                return chart_sales(app_id, frequency, *metric, *points, client).await;
+            }
             client
                 .get(
                     "/salesReports",
@@ -38,5 +418,108 @@ pub async fn handle(
                 )
                 .await
         }
+        AnalyticsCommand::CrashFree {
+            app,
+            versions,
+            days,
+        } => {
+            let app_id = &resolve::app_id(app.as_deref(), client).await?;
+            let request_id = &ongoing_report_request(app_id, client).await?;
+            let crash_rows = report_rows(request_id, CRASHES_REPORT, *days, client).await?;
+            let session_rows = report_rows(request_id, SESSIONS_REPORT, *days, client).await?;
+
+            let stats = crash_free(&crash_rows, &session_rows, versions);
+            let baseline = stats.first().and_then(CrashFree::percent);
+            let rows: Vec<Value> = stats
+                .iter()
+                .map(|s| {
+                    let percent = s.percent();
+                    // Percentage points against the first version given
+                    let change = percent
+                        .zip(baseline)
+                        .map(|(p, b)| ((p - b) * 100.0).round() / 100.0);
+                    json!({
+                        "version": s.version,
+                        "crash_free_device_days": percent,
+                        "change": change,
+                        "crashes": s.crashes,
+                        "crashing_device_days": s.crashing_device_days,
+                        "active_device_days": s.active_device_days,
+                    })
+                })
+                .collect();
+            Ok(json!({
+                "app_id": app_id,
+                "days": days,
+                "versions": rows,
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_downloads_and_proceeds_from_a_summary_report() {
+        let tsv =
+            "Provider\tProduct Type Identifier\tUnits\tDeveloper Proceeds\tCurrency of Proceeds\n\
+                   APPLE\t1\t12\t0\tUSD\n\
+                   APPLE\t7\t30\t0\tUSD\n\
+                   APPLE\tIA1\t3\t0.70\tUSD\n\
+                   APPLE\t1F\t2\t0\tEUR\n";
+        let rows = parse_report(tsv.as_bytes()).unwrap();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(total(&rows, SalesMetric::Downloads).0, 14.0);
+        assert_eq!(total(&rows, SalesMetric::Units).0, 47.0);
+        let (proceeds, currencies) = total(&rows, SalesMetric::Proceeds);
+        assert!((proceeds - 2.1).abs() < 1e-9);
+        assert_eq!(currencies.into_iter().collect::<Vec<_>>(), ["USD"]);
+    }
+
+    #[test]
+    fn crash_free_device_days_per_version() {
+        let crashes = parse_report(
+            "Date\tApp Version\tDevice\tCrashes\tUnique Devices\n\
+             2025-03-10\t1.4.0\tiPhone\t30\t20\n\
+             2025-03-11\t1.4.0\tiPad\t5\t5\n\
+             2025-03-11\t1.4.1\tiPhone\t2\t2\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let sessions = parse_report(
+            "Date\tApp Version\tSessions\tUnique Devices\n\
+             2025-03-10\t1.4.0\t900\t400\n\
+             2025-03-11\t1.4.0\t300\t100\n\
+             2025-03-11\t1.4.1\t800\t400\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let versions = [
+            "1.4.0".to_string(),
+            "1.4.1".to_string(),
+            "1.5.0".to_string(),
+        ];
+        let stats = crash_free(&crashes, &sessions, &versions);
+        assert_eq!(stats[0].crashes, 35.0);
+        assert_eq!(stats[0].percent(), Some(95.0));
+        assert_eq!(stats[1].percent(), Some(99.5));
+        assert_eq!(stats[2].percent(), None);
+    }
+
+    #[test]
+    fn report_dates_end_at_the_latest_complete_period() {
+        // 2025-03-12 is a Wednesday
+        let today = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        assert_eq!(
+            report_dates("DAILY", 2, today),
+            ["2025-03-10", "2025-03-11"]
+        );
+        assert_eq!(
+            report_dates("WEEKLY", 2, today),
+            ["2025-03-02", "2025-03-09"]
+        );
+        assert_eq!(report_dates("MONTHLY", 2, today), ["2025-01", "2025-02"]);
     }
 }
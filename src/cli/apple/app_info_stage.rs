@@ -0,0 +1,220 @@
+//! Name and subtitle changes waiting for an editable app info.
+//!
+//! Name and subtitle can only change while the app info is in
+//! `PREPARE_FOR_SUBMISSION`, which only happens together with a new version.
+//! `apple metadata app-info stage` records the change in
+//! `<data dir>/app-info-stage.json` (keyed by app ID, then App Store Connect locale),
+//! and it is applied by the first of `app-info stage`, `app-info apply`,
+//! `versions create` or `sync push` that finds the app info editable.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use super::sync;
+use crate::api::apple_client::AppleClient;
+use crate::config::Config;
+
+const FILE_NAME: &str = "app-info-stage.json";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StagedChange {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    pub staged_at: String,
+}
+
+impl StagedChange {
+    /// The change as app info localization attributes.
+    fn attributes(&self) -> serde_json::Map<String, Value> {
+        let mut attrs = serde_json::Map::new();
+        if let Some(name) = &self.name {
+            attrs.insert("name".to_string(), json!(name));
+        }
+        if let Some(subtitle) = &self.subtitle {
+            attrs.insert("subtitle".to_string(), json!(subtitle));
+        }
+        attrs
+    }
+}
+
+/// Staged changes: app ID -> locale -> change.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Stage {
+    pub apps: BTreeMap<String, BTreeMap<String, StagedChange>>,
+}
+
+impl Stage {
+    pub fn path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Ok(Config::data_dir()
+            .ok_or("cannot determine data directory")?
+            .join(FILE_NAME))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("cannot parse {}: {e}", path.display()).into()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record a change, merged over anything already staged for the locale.
+    pub fn add(
+        &mut self,
+        app_id: &str,
+        locale: &str,
+        name: Option<&str>,
+        subtitle: Option<&str>,
+    ) -> StagedChange {
+        let entry = self
+            .apps
+            .entry(app_id.to_string())
+            .or_default()
+            .entry(locale.to_string())
+            .or_default();
+        if let Some(name) = name {
+            entry.name = Some(name.to_string());
+        }
+        if let Some(subtitle) = subtitle {
+            entry.subtitle = Some(subtitle.to_string());
+        }
+        entry.staged_at = Utc::now().to_rfc3339();
+        entry.clone()
+    }
+
+    /// Drop an applied change, and the app once nothing is left for it.
+    fn remove(&mut self, app_id: &str, locale: &str) {
+        if let Some(locales) = self.apps.get_mut(app_id) {
+            locales.remove(locale);
+            if locales.is_empty() {
+                self.apps.remove(app_id);
+            }
+        }
+    }
+}
+
+/// Apply the changes staged for `app_id` if its app info is editable now.
+///
+/// Returns `None` when nothing is staged. Applied changes leave the stage; those
+/// that fail stay for the next attempt.
+pub async fn apply_pending(
+    app_id: &str,
+    client: &AppleClient,
+) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+    let path = Stage::path()?;
+    let mut stage = Stage::load(&path)?;
+    let Some(pending) = stage.apps.get(app_id).cloned() else {
+        return Ok(None);
+    };
+
+    let (app_info, editable) = sync::get_app_info(app_id, client).await?;
+    if !editable {
+        let state = app_info["attributes"]["appStoreState"]
+            .as_str()
+            .unwrap_or_default();
+        return Ok(Some(json!({
+            "applied": [],
+            "pending": pending.keys().collect::<Vec<_>>(),
+            "reason": format!("app info is {state}; staged changes apply once a new version is created"),
+        })));
+    }
+    let app_info_id = app_info["id"].as_str().ok_or("app info has no id")?;
+    let locs: Value = client
+        .get(
+            &format!("/appInfos/{app_info_id}/appInfoLocalizations"),
+            &[("limit", "100")],
+        )
+        .await?;
+    let loc_ids: HashMap<&str, &str> = locs["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|l| Some((l["attributes"]["locale"].as_str()?, l["id"].as_str()?)))
+        .collect();
+
+    let mut applied = Vec::new();
+    let mut failed = Vec::new();
+    for (locale, change) in &pending {
+        let mut attrs = change.attributes();
+        let response = match loc_ids.get(locale.as_str()) {
+            Some(loc_id) => {
+                let body = json!({
+                    "data": { "type": "appInfoLocalizations", "id": loc_id, "attributes": attrs }
+                });
+                client
+                    .patch(&format!("/appInfoLocalizations/{loc_id}"), &body)
+                    .await
+            }
+            None => {
+                attrs.insert("locale".to_string(), json!(locale));
+                let body = json!({
+                    "data": {
+                        "type": "appInfoLocalizations",
+                        "attributes": attrs,
+                        "relationships": {
+                            "appInfo": { "data": { "type": "appInfos", "id": app_info_id } }
+                        }
+                    }
+                });
+                client.post("/appInfoLocalizations", &body).await
+            }
+        };
+        match response {
+            Ok(_) => {
+                eprintln!("Applied staged name/subtitle for {locale}");
+                stage.remove(app_id, locale);
+                applied.push(locale.clone());
+            }
+            Err(e) => {
+                eprintln!("Warning: could not apply staged name/subtitle for {locale}: {e}");
+                failed.push(json!({ "locale": locale, "error": e.to_string() }));
+            }
+        }
+    }
+    stage.save(&path)?;
+
+    let still_pending: Vec<&str> = failed.iter().filter_map(|f| f["locale"].as_str()).collect();
+    let mut result = json!({ "applied": applied, "pending": still_pending });
+    if !failed.is_empty() {
+        result["failed"] = json!(failed);
+    }
+    Ok(Some(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_changes_per_locale_and_drops_applied_ones() {
+        let mut stage = Stage::default();
+        stage.add("123", "en-US", Some("Notes"), None);
+        let merged = stage.add("123", "en-US", None, Some("Write it down"));
+        assert_eq!(merged.name.as_deref(), Some("Notes"));
+        assert_eq!(merged.subtitle.as_deref(), Some("Write it down"));
+        assert_eq!(
+            Value::Object(merged.attributes()),
+            json!({ "name": "Notes", "subtitle": "Write it down" })
+        );
+
+        stage.add("123", "de-DE", None, Some("Notizen"));
+        stage.remove("123", "en-US");
+        assert_eq!(stage.apps["123"].len(), 1);
+        stage.remove("123", "de-DE");
+        assert!(stage.apps.is_empty());
+    }
+}
@@ -1,7 +1,11 @@
 use clap::Subcommand;
-use serde_json::Value;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
 
+use super::resolve;
 use crate::api::apple_client::AppleClient;
+use crate::api::recording::RecordedSend;
 
 #[derive(Subcommand)]
 pub enum AppsCommand {
@@ -10,15 +14,144 @@ pub enum AppsCommand {
     /// Examples:
     ///   storeops apple apps list
     ///   storeops apple apps list --limit 10
-    List,
+    ///   storeops apple apps list --bundle-id com.example.app --include appStoreVersions
+    List {
+        /// Filter by bundle ID (comma-separated)
+        #[arg(long)]
+        bundle_id: Option<String>,
+        /// Filter by app name (comma-separated)
+        #[arg(long)]
+        name: Option<String>,
+        /// Filter by SKU (comma-separated)
+        #[arg(long)]
+        sku: Option<String>,
+        /// Related resources to include (comma-separated, e.g., appStoreVersions,builds)
+        #[arg(long)]
+        include: Option<String>,
+    },
     /// Get detailed information for a specific app
     ///
     /// Examples:
     ///   storeops apple apps info --app-id 1234567890
     Info {
-        /// Your App Store Connect app ID (not the bundle ID)
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Related resources to include (comma-separated, e.g., appStoreVersions,builds)
+        #[arg(long)]
+        include: Option<String>,
+    },
+    /// Print the app ID for a bundle ID
+    ///
+    /// Examples:
+    ///   APP_ID=$(storeops apple apps lookup com.example.app)
+    Lookup {
+        /// Bundle ID (e.g., com.example.app)
+        bundle_id: String,
+    },
+    /// Register a bundle ID (the app record itself must be created in App Store Connect)
+    ///
+    /// Examples:
+    ///   storeops apple apps register-bundle-id --identifier com.example.app --name "Example"
+    RegisterBundleId {
+        /// Bundle identifier (e.g., com.example.app)
+        #[arg(long)]
+        identifier: String,
+        /// Display name for the bundle ID
+        #[arg(long)]
+        name: String,
+        /// Platform: IOS, MAC_OS, UNIVERSAL
+        #[arg(long, default_value = "IOS")]
+        platform: String,
+    },
+    /// Set up a freshly created app from a single TOML file
+    ///
+    /// Creates the first version, fills the primary localization, and sets
+    /// categories, pricing, and availability. Sections missing from the file
+    /// are skipped.
+    ///
+    /// Examples:
+    ///   storeops apple apps bootstrap 1234567890 --config bootstrap.toml
+    Bootstrap {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Path to the bootstrap TOML file
+        #[arg(long)]
+        config: PathBuf,
     },
+    /// Download the app icon from the newest processed build
+    ///
+    /// App Store Connect renders the icon from the build's asset catalog, so the
+    /// icon of the latest valid build (or --build) is the one the store shows.
+    ///
+    /// Examples:
+    ///   storeops apple apps icon 1234567890 --out icon.png
+    ///   storeops apple apps icon com.example.app --out icon-512.png --size 512
+    Icon {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// File to write the PNG to
+        #[arg(long)]
+        out: PathBuf,
+        /// Edge length in pixels (defaults to the full 1024px asset)
+        #[arg(long)]
+        size: Option<u32>,
+        /// Take the icon from this build ID instead of the newest valid build
+        #[arg(long)]
+        build: Option<String>,
+    },
+}
+
+/// Contents of an `apps bootstrap` file.
+#[derive(Debug, Deserialize)]
+pub struct BootstrapConfig {
+    /// Version string for the first version (e.g., "1.0.0")
+    pub version: String,
+    #[serde(default = "default_platform")]
+    pub platform: String,
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    #[serde(default)]
+    pub localization: Option<BootstrapLocalization>,
+    #[serde(default)]
+    pub categories: Option<BootstrapCategories>,
+    #[serde(default)]
+    pub pricing: Option<BootstrapPricing>,
+    #[serde(default)]
+    pub availability: Option<BootstrapAvailability>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BootstrapLocalization {
+    pub description: Option<String>,
+    pub keywords: Option<String>,
+    pub promotional_text: Option<String>,
+    pub marketing_url: Option<String>,
+    pub support_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BootstrapCategories {
+    pub primary: String,
+    pub secondary: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BootstrapPricing {
+    pub price_point: String,
+    pub start_date: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BootstrapAvailability {
+    pub territories: Vec<String>,
+}
+
+fn default_platform() -> String {
+    "IOS".to_string()
+}
+
+fn default_locale() -> String {
+    "en-US".to_string()
 }
 
 pub async fn handle(
@@ -27,12 +160,357 @@ pub async fn handle(
     limit: Option<u32>,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
-        AppsCommand::List => {
+        AppsCommand::List {
+            bundle_id,
+            name,
+            sku,
+            include,
+        } => {
             let mut query = vec![];
             let limit_str = limit.unwrap_or(50).to_string();
             query.push(("limit", limit_str.as_str()));
+            if let Some(v) = bundle_id {
+                query.push(("filter[bundleId]", v.as_str()));
+            }
+            if let Some(v) = name {
+                query.push(("filter[name]", v.as_str()));
+            }
+            if let Some(v) = sku {
+                query.push(("filter[sku]", v.as_str()));
+            }
+            if let Some(v) = include {
+                query.push(("include", v.as_str()));
+            }
             client.get("/apps", &query).await
         }
-        AppsCommand::Info { app_id } => client.get::<Value>(&format!("/apps/{app_id}"), &[]).await,
+        AppsCommand::Info { app_id, include } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let mut query = vec![];
+            if let Some(v) = include {
+                query.push(("include", v.as_str()));
+            }
+            client
+                .get::<Value>(&format!("/apps/{app_id}"), &query)
+                .await
+        }
+        AppsCommand::Lookup { bundle_id } => {
+            let app_id = super::sync::lookup_app_by_bundle_id(bundle_id, client).await?;
+            Ok(Value::String(app_id))
+        }
+        AppsCommand::RegisterBundleId {
+            identifier,
+            name,
+            platform,
+        } => {
+            let body = json!({
+                "data": {
+                    "type": "bundleIds",
+                    "attributes": {
+                        "identifier": identifier,
+                        "name": name,
+                        "platform": platform.to_uppercase()
+                    }
+                }
+            });
+            client.post("/bundleIds", &body).await
+        }
+        AppsCommand::Bootstrap { app_id, config } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let content = std::fs::read_to_string(config)
+                .map_err(|e| format!("cannot read {}: {e}", config.display()))?;
+            let bootstrap: BootstrapConfig = toml::from_str(&content)?;
+            bootstrap_app(app_id, &bootstrap, client).await
+        }
+        AppsCommand::Icon {
+            app_id,
+            out,
+            size,
+            build,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let fields = [("fields[builds]", "version,iconAssetToken")];
+            let build = match build {
+                Some(id) => {
+                    let build: Value = client.get(&format!("/builds/{id}"), &fields).await?;
+                    build["data"].clone()
+                }
+                None => {
+                    let mut query = vec![
+                        ("filter[app]", app_id.as_str()),
+                        ("filter[processingState]", "VALID"),
+                        ("sort", "-uploadedDate"),
+                        ("limit", "1"),
+                    ];
+                    query.extend(fields);
+                    let builds: Value = client.get("/builds", &query).await?;
+                    builds["data"][0].clone()
+                }
+            };
+            let build_id = build["id"].as_str().ok_or("no processed build found")?;
+            let url = icon_url(&build["attributes"]["iconAssetToken"], *size)
+                .map_err(|e| format!("build {build_id}: {e}"))?;
+            let bytes = crate::api::session::http()
+                .get(&url)
+                .send_recorded()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?;
+            if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(out, &bytes)?;
+            eprintln!("Saved icon of build {build_id} to {}", out.display());
+            Ok(json!({
+                "app_id": app_id,
+                "build_id": build_id,
+                "build_version": build["attributes"]["version"],
+                "path": out.display().to_string(),
+                "bytes": bytes.len(),
+            }))
+        }
+    }
+}
+
+/// Download URL of an image asset at `size` pixels (never above the original).
+fn icon_url(asset: &Value, size: Option<u32>) -> Result<String, String> {
+    let template = asset["templateUrl"]
+        .as_str()
+        .ok_or("no icon asset (is the build still processing?)")?;
+    let full = asset["width"].as_u64().unwrap_or(1024);
+    let edge = size.map_or(full, |s| u64::from(s).min(full));
+    Ok(template
+        .replace("{w}", &edge.to_string())
+        .replace("{h}", &edge.to_string())
+        .replace("{f}", "png"))
+}
+
+async fn bootstrap_app(
+    app_id: &str,
+    cfg: &BootstrapConfig,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut steps = Vec::new();
+    let platform = cfg.platform.to_uppercase();
+
+    // Version: reuse one with the same version string if it already exists
+    let existing: Value = client
+        .get(
+            &format!("/apps/{app_id}/appStoreVersions"),
+            &[
+                ("filter[versionString]", cfg.version.as_str()),
+                ("filter[platform]", platform.as_str()),
+            ],
+        )
+        .await?;
+    let version_id = if let Some(id) = existing["data"][0]["id"].as_str() {
+        eprintln!("Using existing version {} ({id})", cfg.version);
+        steps.push(json!({"step": "version", "status": "exists", "id": id}));
+        id.to_string()
+    } else {
+        let body = json!({
+            "data": {
+                "type": "appStoreVersions",
+                "attributes": {
+                    "versionString": cfg.version,
+                    "platform": platform
+                },
+                "relationships": {
+                    "app": {
+                        "data": { "type": "apps", "id": app_id }
+                    }
+                }
+            }
+        });
+        let created = client.post("/appStoreVersions", &body).await?;
+        let id = created["data"]["id"]
+            .as_str()
+            .ok_or("no version id in response")?
+            .to_string();
+        eprintln!("Created version {} ({id})", cfg.version);
+        steps.push(json!({"step": "version", "status": "created", "id": id}));
+        id
+    };
+
+    if let Some(loc) = &cfg.localization {
+        let mut attrs = json!({});
+        if let Some(v) = &loc.description {
+            attrs["description"] = json!(v);
+        }
+        if let Some(v) = &loc.keywords {
+            attrs["keywords"] = json!(v);
+        }
+        if let Some(v) = &loc.promotional_text {
+            attrs["promotionalText"] = json!(v);
+        }
+        if let Some(v) = &loc.marketing_url {
+            attrs["marketingUrl"] = json!(v);
+        }
+        if let Some(v) = &loc.support_url {
+            attrs["supportUrl"] = json!(v);
+        }
+
+        let locs: Value = client
+            .get(
+                &format!("/appStoreVersions/{version_id}/appStoreVersionLocalizations"),
+                &[("limit", "100")],
+            )
+            .await?;
+        let existing_id = locs["data"].as_array().and_then(|arr| {
+            arr.iter()
+                .find(|l| l["attributes"]["locale"].as_str() == Some(cfg.locale.as_str()))
+                .and_then(|l| l["id"].as_str())
+        });
+
+        if let Some(loc_id) = existing_id {
+            let body = json!({
+                "data": {
+                    "type": "appStoreVersionLocalizations",
+                    "id": loc_id,
+                    "attributes": attrs
+                }
+            });
+            client
+                .patch(&format!("/appStoreVersionLocalizations/{loc_id}"), &body)
+                .await?;
+            steps.push(json!({"step": "localization", "status": "updated", "locale": cfg.locale}));
+        } else {
+            attrs["locale"] = json!(cfg.locale);
+            let body = json!({
+                "data": {
+                    "type": "appStoreVersionLocalizations",
+                    "attributes": attrs,
+                    "relationships": {
+                        "appStoreVersion": {
+                            "data": { "type": "appStoreVersions", "id": version_id }
+                        }
+                    }
+                }
+            });
+            client.post("/appStoreVersionLocalizations", &body).await?;
+            steps.push(json!({"step": "localization", "status": "created", "locale": cfg.locale}));
+        }
+        eprintln!("Localization {} ready", cfg.locale);
+    }
+
+    if let Some(cats) = &cfg.categories {
+        let infos: Value = client
+            .get(&format!("/apps/{app_id}/appInfos"), &[("limit", "1")])
+            .await?;
+        let app_info_id = infos["data"][0]["id"].as_str().ok_or("no app info found")?;
+        let mut relationships = json!({
+            "primaryCategory": {
+                "data": { "type": "appCategories", "id": cats.primary }
+            }
+        });
+        if let Some(sec) = &cats.secondary {
+            relationships["secondaryCategory"] = json!({
+                "data": { "type": "appCategories", "id": sec }
+            });
+        }
+        let body = json!({
+            "data": {
+                "type": "appInfos",
+                "id": app_info_id,
+                "relationships": relationships
+            }
+        });
+        client
+            .patch(&format!("/appInfos/{app_info_id}"), &body)
+            .await?;
+        eprintln!("Categories set");
+        steps.push(json!({"step": "categories", "status": "updated"}));
+    }
+
+    if let Some(pricing) = &cfg.pricing {
+        let body = super::pricing::price_schedule_body(
+            app_id,
+            &pricing.price_point,
+            pricing.start_date.as_deref(),
+        );
+        client.post("/appPriceSchedules", &body).await?;
+        eprintln!("Price schedule set");
+        steps.push(json!({"step": "pricing", "status": "updated"}));
+    }
+
+    if let Some(availability) = &cfg.availability {
+        let territories = crate::config::territories::resolve(
+            &availability.territories,
+            crate::config::territories::CodeStyle::Alpha3,
+        )?;
+        let body = super::availability::availability_body(app_id, &territories);
+        client.post("/v2/appAvailabilities", &body).await?;
+        eprintln!("Availability set for {} territories", territories.len());
+        steps.push(json!({"step": "availability", "status": "updated"}));
+    }
+
+    Ok(json!({
+        "app_id": app_id,
+        "version_id": version_id,
+        "steps": steps,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icon_url_fills_size_template() {
+        let asset = json!({
+            "templateUrl": "https://is1-ssl.mzstatic.com/image/thumb/Purple/icon.png/{w}x{h}bb.{f}",
+            "width": 1024,
+            "height": 1024
+        });
+        assert!(icon_url(&asset, None)
+            .unwrap()
+            .ends_with("/1024x1024bb.png"));
+        assert!(icon_url(&asset, Some(512))
+            .unwrap()
+            .ends_with("/512x512bb.png"));
+        assert!(icon_url(&asset, Some(4096))
+            .unwrap()
+            .ends_with("/1024x1024bb.png"));
+        assert!(icon_url(&Value::Null, None).is_err());
+    }
+
+    #[test]
+    fn bootstrap_config_applies_defaults() {
+        let cfg: BootstrapConfig = toml::from_str(r#"version = "1.0.0""#).unwrap();
+        assert_eq!(cfg.platform, "IOS");
+        assert_eq!(cfg.locale, "en-US");
+        assert!(cfg.localization.is_none());
+        assert!(cfg.pricing.is_none());
+    }
+
+    #[test]
+    fn bootstrap_config_parses_all_sections() {
+        let cfg: BootstrapConfig = toml::from_str(
+            r#"
+            version = "1.0.0"
+            locale = "de-DE"
+
+            [localization]
+            description = "Beschreibung"
+            support_url = "https://example.com/support"
+
+            [categories]
+            primary = "GAMES"
+
+            [pricing]
+            price_point = "eyJzIjoiMTAwMDAwMDAwMCJ9"
+
+            [availability]
+            territories = ["USA", "DEU"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(cfg.locale, "de-DE");
+        assert_eq!(
+            cfg.localization.unwrap().support_url.as_deref(),
+            Some("https://example.com/support")
+        );
+        assert_eq!(cfg.categories.unwrap().primary, "GAMES");
+        assert_eq!(cfg.availability.unwrap().territories, vec!["USA", "DEU"]);
     }
 }
@@ -1,25 +1,76 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
 
+use super::resolve;
 use crate::api::apple_client::AppleClient;
+use crate::config::territories::{self, CodeStyle};
 
 #[derive(Subcommand)]
 pub enum AvailabilityCommand {
     /// Get app territory availability
     Get {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
     },
     /// List all available territories
     Territories,
-    /// Set territory availability for an app
+    /// Set territory availability for an app (replaces the whole set)
     Set {
-        /// App ID
-        app_id: String,
-        /// Territory IDs (comma-separated, e.g., USA,GBR,JPN)
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Territory IDs or @groups (comma-separated, e.g., USA,GBR,@eu)
         #[arg(long, value_delimiter = ',')]
         territories: Vec<String>,
     },
+    /// Make the app available in additional territories
+    ///
+    /// Examples:
+    ///   storeops apple availability add 1234567890 --territories DEU,FRA
+    Add {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Territory IDs or @groups (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        territories: Vec<String>,
+    },
+    /// Remove the app from territories
+    ///
+    /// Examples:
+    ///   storeops apple availability remove 1234567890 --territories RUS
+    Remove {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Territory IDs or @groups (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        territories: Vec<String>,
+    },
+    /// Compare current availability against a desired-territory file
+    ///
+    /// The file lists territory IDs separated by commas or newlines, or as a JSON array.
+    /// `availability apply` makes the changes.
+    ///
+    /// Examples:
+    ///   storeops apple availability diff 1234567890 --against territories.txt
+    Diff {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Desired territories file
+        #[arg(long, visible_alias = "against")]
+        file: PathBuf,
+    },
+    /// Add and remove territories until the app matches a desired-territory file
+    ///
+    /// Examples:
+    ///   storeops apple availability apply 1234567890 --against territories.txt
+    Apply {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Desired territories file
+        #[arg(long, visible_alias = "against")]
+        file: PathBuf,
+    },
 }
 
 pub async fn handle(
@@ -29,44 +80,264 @@ pub async fn handle(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         AvailabilityCommand::Get { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let availability_id = availability_id(app_id, client).await?;
             client
                 .get::<Value>(
-                    &format!("/apps/{app_id}/appAvailability"),
-                    &[("include", "availableTerritories")],
+                    &format!("/v2/appAvailabilities/{availability_id}/territoryAvailabilities"),
+                    &[("include", "territory"), ("limit", "200")],
                 )
                 .await
         }
         AvailabilityCommand::Territories => {
             let limit_str = limit.unwrap_or(200).to_string();
-            client
-                .get("/territories", &[("limit", limit_str.as_str())])
-                .await
+            let query = [("limit", limit_str.as_str())];
+            if crate::output::jsonl::enabled() {
+                return super::pricing::stream_pages("/territories", &query, client).await;
+            }
+            client.get("/territories", &query).await
         }
         AvailabilityCommand::Set {
             app_id,
             territories,
         } => {
-            let territory_data: Vec<Value> = territories
-                .iter()
-                .map(|t| json!({ "type": "territories", "id": t }))
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let territories = territories::resolve(territories, CodeStyle::Alpha3)?;
+            let body = availability_body(app_id, &territories);
+            client.post("/v2/appAvailabilities", &body).await
+        }
+        AvailabilityCommand::Add {
+            app_id,
+            territories,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            set_territories_available(app_id, territories, true, client).await
+        }
+        AvailabilityCommand::Remove {
+            app_id,
+            territories,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            set_territories_available(app_id, territories, false, client).await
+        }
+        AvailabilityCommand::Diff { app_id, file }
+        | AvailabilityCommand::Apply { app_id, file } => {
+            let apply = matches!(cmd, AvailabilityCommand::Apply { .. });
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let content = std::fs::read_to_string(file)
+                .map_err(|e| format!("cannot read {}: {e}", file.display()))?;
+            let desired = parse_territory_list(&content)?;
+            let current: BTreeSet<String> = territory_availabilities(app_id, client)
+                .await?
+                .into_iter()
+                .filter(|(_, _, available)| *available)
+                .map(|(territory, _, _)| territory)
                 .collect();
-            let body = json!({
-                "data": {
-                    "type": "appAvailabilities",
-                    "attributes": {
-                        "availableInNewTerritories": false
-                    },
-                    "relationships": {
-                        "app": {
-                            "data": { "type": "apps", "id": app_id }
-                        },
-                        "availableTerritories": {
-                            "data": territory_data
-                        }
+            let mut diff = diff_territories(&current, &desired);
+            if apply && diff["in_sync"] == false {
+                let to_add: Vec<String> = desired.difference(&current).cloned().collect();
+                let to_remove: Vec<String> = current.difference(&desired).cloned().collect();
+                if !to_add.is_empty() {
+                    set_territories_available(app_id, &to_add, true, client).await?;
+                }
+                if !to_remove.is_empty() {
+                    set_territories_available(app_id, &to_remove, false, client).await?;
+                }
+                diff["applied"] = json!(true);
+            }
+            Ok(diff)
+        }
+    }
+}
+
+/// Request body making an app available in exactly the given territories (v2 shape).
+pub fn availability_body(app_id: &str, territories: &[String]) -> Value {
+    let refs: Vec<Value> = territories
+        .iter()
+        .map(|t| json!({ "type": "territoryAvailabilities", "id": format!("${{{t}}}") }))
+        .collect();
+    let included: Vec<Value> = territories
+        .iter()
+        .map(|t| {
+            json!({
+                "type": "territoryAvailabilities",
+                "id": format!("${{{t}}}"),
+                "attributes": { "available": true },
+                "relationships": {
+                    "territory": {
+                        "data": { "type": "territories", "id": t }
                     }
                 }
-            });
-            client.post("/appAvailabilities", &body).await
+            })
+        })
+        .collect();
+    json!({
+        "data": {
+            "type": "appAvailabilities",
+            "attributes": {
+                "availableInNewTerritories": false
+            },
+            "relationships": {
+                "app": {
+                    "data": { "type": "apps", "id": app_id }
+                },
+                "territoryAvailabilities": {
+                    "data": refs
+                }
+            }
+        },
+        "included": included
+    })
+}
+
+async fn availability_id(
+    app_id: &str,
+    client: &AppleClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let availability: Value = client
+        .get(&format!("/apps/{app_id}/appAvailabilityV2"), &[])
+        .await?;
+    Ok(availability["data"]["id"]
+        .as_str()
+        .ok_or("app has no availability yet; use `availability set` first")?
+        .to_string())
+}
+
+/// Fetch `(territory, territoryAvailability id, available)` for every territory.
+pub async fn territory_availabilities(
+    app_id: &str,
+    client: &AppleClient,
+) -> Result<Vec<(String, String, bool)>, Box<dyn std::error::Error>> {
+    let availability_id = availability_id(app_id, client).await?;
+    let resp: Value = client
+        .get(
+            &format!("/v2/appAvailabilities/{availability_id}/territoryAvailabilities"),
+            &[("include", "territory"), ("limit", "200")],
+        )
+        .await?;
+    Ok(resp["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|ta| {
+            let territory = ta["relationships"]["territory"]["data"]["id"].as_str()?;
+            let id = ta["id"].as_str()?;
+            let available = ta["attributes"]["available"].as_bool().unwrap_or(false);
+            Some((territory.to_string(), id.to_string(), available))
+        })
+        .collect())
+}
+
+async fn set_territories_available(
+    app_id: &str,
+    territories: &[String],
+    available: bool,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let territories = territories::resolve(territories, CodeStyle::Alpha3)?;
+    let current = territory_availabilities(app_id, client).await?;
+    let mut changed = Vec::new();
+    let mut unchanged = Vec::new();
+
+    for territory in &territories {
+        let (_, ta_id, is_available) = current
+            .iter()
+            .find(|(t, _, _)| t == territory)
+            .ok_or_else(|| format!("unknown territory: {territory}"))?;
+        if *is_available == available {
+            unchanged.push(territory.clone());
+            continue;
         }
+        let body = json!({
+            "data": {
+                "type": "territoryAvailabilities",
+                "id": ta_id,
+                "attributes": { "available": available }
+            }
+        });
+        client
+            .patch(&format!("/territoryAvailabilities/{ta_id}"), &body)
+            .await?;
+        changed.push(territory.clone());
+    }
+
+    Ok(json!({
+        "app_id": app_id,
+        "available": available,
+        "changed": changed,
+        "unchanged": unchanged,
+    }))
+}
+
+/// Parse territory IDs separated by commas/whitespace, or a JSON array.
+fn parse_territory_list(content: &str) -> Result<BTreeSet<String>, Box<dyn std::error::Error>> {
+    let trimmed = content.trim();
+    let items: Vec<String> = if trimmed.starts_with('[') {
+        serde_json::from_str(trimmed)?
+    } else {
+        trimmed
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|t| !t.is_empty() && !t.starts_with('#'))
+            .map(String::from)
+            .collect()
+    };
+    Ok(territories::resolve(&items, CodeStyle::Alpha3)?
+        .into_iter()
+        .collect())
+}
+
+fn diff_territories(current: &BTreeSet<String>, desired: &BTreeSet<String>) -> Value {
+    let to_add: Vec<&String> = desired.difference(current).collect();
+    let to_remove: Vec<&String> = current.difference(desired).collect();
+    json!({
+        "in_sync": to_add.is_empty() && to_remove.is_empty(),
+        "to_add": to_add,
+        "to_remove": to_remove,
+        "unchanged": current.intersection(desired).count(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_comma_and_newline_lists() {
+        let list = parse_territory_list("usa, GBR\nDEU\n\nJPN").unwrap();
+        assert_eq!(
+            list.into_iter().collect::<Vec<_>>(),
+            vec!["DEU", "GBR", "JPN", "USA"]
+        );
+    }
+
+    #[test]
+    fn parses_json_array() {
+        let list = parse_territory_list(r#"["USA", "fra"]"#).unwrap();
+        assert!(list.contains("FRA"));
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn diff_reports_additions_and_removals() {
+        let current: BTreeSet<String> = ["USA", "GBR", "RUS"].map(String::from).into();
+        let desired: BTreeSet<String> = ["USA", "GBR", "DEU"].map(String::from).into();
+        let diff = diff_territories(&current, &desired);
+        assert_eq!(diff["to_add"], json!(["DEU"]));
+        assert_eq!(diff["to_remove"], json!(["RUS"]));
+        assert_eq!(diff["unchanged"], 2);
+        assert_eq!(diff["in_sync"], false);
+    }
+
+    #[test]
+    fn availability_body_uses_inline_territory_resources() {
+        let body = availability_body("123", &["USA".to_string()]);
+        assert_eq!(
+            body["data"]["relationships"]["territoryAvailabilities"]["data"][0]["id"],
+            "${USA}"
+        );
+        assert_eq!(
+            body["included"][0]["relationships"]["territory"]["data"]["id"],
+            "USA"
+        );
     }
 }
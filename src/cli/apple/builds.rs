@@ -1,19 +1,74 @@
 use clap::Subcommand;
-use serde_json::Value;
+use serde_json::{json, Value};
 
+use super::platform::Platform;
+use super::resolve;
 use crate::api::apple_client::AppleClient;
 
 #[derive(Subcommand)]
 pub enum BuildsCommand {
     /// List builds for an app
+    ///
+    /// Examples:
+    ///   storeops apple builds list 1234567890 --version 2.1.0 --state valid
+    ///   storeops apple builds list com.example.app --beta-group GROUP_ID --latest
     List {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Pre-release version strings (comma-separated, CFBundleShortVersionString)
+        #[arg(long)]
+        version: Option<String>,
+        /// Processing states (comma-separated: processing, failed, invalid, valid)
+        #[arg(long)]
+        state: Option<String>,
+        /// Only builds assigned to this TestFlight beta group ID
+        #[arg(long)]
+        beta_group: Option<String>,
+        /// Return only the most recently uploaded matching build
+        #[arg(long)]
+        latest: bool,
+        /// Only builds for this platform
+        #[arg(long, value_enum)]
+        platform: Option<Platform>,
     },
     /// Get build details
+    ///
+    /// Examples:
+    ///   storeops apple builds info BUILD_ID
+    ///   storeops apple builds get BUILD_ID --include buildBetaDetail,preReleaseVersion
+    #[command(alias = "get")]
     Info {
         /// Build ID
         build_id: String,
+        /// Related resources to include (comma-separated, e.g., buildBetaDetail,preReleaseVersion)
+        #[arg(long)]
+        include: Option<String>,
+    },
+    /// Get the newest successfully processed build for an app
+    ///
+    /// Examples:
+    ///   storeops apple builds latest --app 1234567890
+    ///   storeops apple builds latest --app com.example.app --platform tvos
+    Latest {
+        /// App ID or bundle ID
+        #[arg(long)]
+        app: Option<String>,
+        /// Only consider builds for this version string (CFBundleShortVersionString)
+        #[arg(long)]
+        version: Option<String>,
+        /// Only consider builds for this platform
+        #[arg(long, value_enum)]
+        platform: Option<Platform>,
+    },
+    /// Expire a build so it can no longer be tested or submitted
+    Expire {
+        /// Build ID
+        build_id: String,
+    },
+    /// List the icons attached to a build
+    Icons {
+        /// Build ID
+        build_id: String,
     },
 }
 
@@ -23,16 +78,92 @@ pub async fn handle(
     limit: Option<u32>,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
-        BuildsCommand::List { app_id } => {
+        BuildsCommand::List {
+            app_id,
+            version,
+            state,
+            beta_group,
+            latest,
+            platform,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             let mut query = vec![];
-            let limit_str = limit.unwrap_or(50).to_string();
+            let limit_str = if *latest {
+                "1".to_string()
+            } else {
+                limit.unwrap_or(50).to_string()
+            };
             query.push(("limit", limit_str.as_str()));
             query.push(("filter[app]", app_id.as_str()));
+            if let Some(v) = version {
+                query.push(("filter[preReleaseVersion.version]", v.as_str()));
+            }
+            let state = state.as_ref().map(|s| s.to_uppercase());
+            if let Some(s) = &state {
+                query.push(("filter[processingState]", s.as_str()));
+            }
+            if let Some(g) = beta_group {
+                query.push(("filter[betaGroups]", g.as_str()));
+            }
+            if let Some(p) = platform {
+                query.push(("filter[preReleaseVersion.platform]", p.api_value()));
+            }
+            if *latest {
+                query.push(("sort", "-uploadedDate"));
+            }
             client.get("/builds", &query).await
         }
-        BuildsCommand::Info { build_id } => {
+        BuildsCommand::Info { build_id, include } => {
+            let mut query = vec![];
+            if let Some(inc) = include {
+                query.push(("include", inc.as_str()));
+            }
+            client
+                .get::<Value>(&format!("/builds/{build_id}"), &query)
+                .await
+        }
+        BuildsCommand::Latest {
+            app,
+            version,
+            platform,
+        } => {
+            let app = &resolve::app_id(app.as_deref(), client).await?;
+            let mut query = vec![
+                ("filter[app]", app.as_str()),
+                ("filter[processingState]", "VALID"),
+                ("filter[expired]", "false"),
+                ("sort", "-uploadedDate"),
+                ("include", "preReleaseVersion"),
+                ("limit", "1"),
+            ];
+            if let Some(v) = version {
+                query.push(("filter[preReleaseVersion.version]", v.as_str()));
+            }
+            if let Some(p) = platform {
+                query.push(("filter[preReleaseVersion.platform]", p.api_value()));
+            }
+            let builds: Value = client.get("/builds", &query).await?;
+            let build = builds["data"]
+                .as_array()
+                .and_then(|arr| arr.first())
+                .ok_or("no processed build found")?;
+            Ok(json!({ "data": build, "included": builds["included"] }))
+        }
+        BuildsCommand::Expire { build_id } => {
+            let body = json!({
+                "data": {
+                    "type": "builds",
+                    "id": build_id,
+                    "attributes": {
+                        "expired": true
+                    }
+                }
+            });
+            client.patch(&format!("/builds/{build_id}"), &body).await
+        }
+        BuildsCommand::Icons { build_id } => {
             client
-                .get::<Value>(&format!("/builds/{build_id}"), &[])
+                .get::<Value>(&format!("/builds/{build_id}/icons"), &[])
                 .await
         }
     }
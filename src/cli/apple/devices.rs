@@ -1,5 +1,7 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::PathBuf;
 
 use crate::api::apple_client::AppleClient;
 
@@ -23,6 +25,102 @@ pub enum DevicesCommand {
         #[arg(long)]
         platform: String,
     },
+    /// Register every device listed in a CSV file (name,udid,platform)
+    ///
+    /// Devices whose UDID is already registered, or repeated in the file, are skipped.
+    ///
+    /// Examples:
+    ///   storeops apple devices register-bulk --file devices.csv --dry-run
+    RegisterBulk {
+        /// CSV file with name,udid,platform rows (a header row is optional)
+        #[arg(long)]
+        file: PathBuf,
+        /// Show what would be registered without registering anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Disable a registered device
+    Disable {
+        /// Device ID
+        device_id: String,
+    },
+    /// Rename a registered device
+    Rename {
+        /// Device ID
+        device_id: String,
+        /// New device name
+        #[arg(long)]
+        name: String,
+    },
+}
+
+/// UDIDs looked up per request when checking for registered devices.
+const UDID_BATCH: usize = 50;
+
+#[derive(Debug, PartialEq)]
+struct DeviceRow {
+    line: usize,
+    name: String,
+    udid: String,
+    platform: String,
+}
+
+/// API value for a platform name.
+fn platform_value(platform: &str) -> Result<&'static str, Box<dyn std::error::Error>> {
+    match platform.trim().to_lowercase().as_str() {
+        "ios" => Ok("IOS"),
+        "macos" | "mac_os" => Ok("MAC_OS"),
+        other => Err(format!("unknown platform '{other}' (expected ios or macos)").into()),
+    }
+}
+
+/// Parse `name,udid,platform` rows; the name may itself contain commas.
+fn parse_devices(csv: &str) -> Result<Vec<DeviceRow>, Box<dyn std::error::Error>> {
+    let mut rows = Vec::new();
+    for (i, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.rsplitn(3, ',').map(str::trim);
+        let (Some(platform), Some(udid), Some(name)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(format!("line {}: expected name,udid,platform", i + 1).into());
+        };
+        if rows.is_empty() && name.eq_ignore_ascii_case("name") && udid.eq_ignore_ascii_case("udid")
+        {
+            continue;
+        }
+        let name = name.trim_matches('"');
+        if name.is_empty() || udid.is_empty() {
+            return Err(format!("line {}: name and udid are required", i + 1).into());
+        }
+        rows.push(DeviceRow {
+            line: i + 1,
+            name: name.to_string(),
+            udid: udid.to_string(),
+            platform: platform_value(platform)
+                .map_err(|e| format!("line {}: {e}", i + 1))?
+                .to_string(),
+        });
+    }
+    Ok(rows)
+}
+
+async fn update_device(
+    client: &AppleClient,
+    device_id: &str,
+    attributes: Value,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let body = json!({
+        "data": {
+            "type": "devices",
+            "id": device_id,
+            "attributes": attributes
+        }
+    });
+    client.patch(&format!("/devices/{device_id}"), &body).await
 }
 
 pub async fn handle(
@@ -53,11 +151,123 @@ pub async fn handle(
                     "attributes": {
                         "name": name,
                         "udid": udid,
-                        "platform": platform.to_uppercase()
+                        "platform": platform_value(platform)?
                     }
                 }
             });
             client.post("/devices", &body).await
         }
+        DevicesCommand::RegisterBulk { file, dry_run } => {
+            let rows = parse_devices(&std::fs::read_to_string(file)?)?;
+
+            // UDIDs already registered with the team, checked in batches
+            let mut registered: HashSet<String> = HashSet::new();
+            for batch in rows.chunks(UDID_BATCH) {
+                let udids = batch
+                    .iter()
+                    .map(|r| r.udid.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let limit_str = UDID_BATCH.to_string();
+                let found: Value = client
+                    .get(
+                        "/devices",
+                        &[
+                            ("filter[udid]", udids.as_str()),
+                            ("limit", limit_str.as_str()),
+                        ],
+                    )
+                    .await?;
+                registered.extend(
+                    found["data"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|d| d["attributes"]["udid"].as_str())
+                        .map(str::to_lowercase),
+                );
+            }
+
+            let mut seen: HashSet<String> = HashSet::new();
+            let mut created = Vec::new();
+            let mut skipped = Vec::new();
+            let mut failed = Vec::new();
+            for row in &rows {
+                let key = row.udid.to_lowercase();
+                let reason = if registered.contains(&key) {
+                    Some("already registered")
+                } else if !seen.insert(key) {
+                    Some("duplicate UDID in file")
+                } else {
+                    None
+                };
+                if let Some(reason) = reason {
+                    skipped.push(json!({ "line": row.line, "udid": row.udid, "reason": reason }));
+                    continue;
+                }
+                if *dry_run {
+                    created.push(
+                        json!({ "name": row.name, "udid": row.udid, "platform": row.platform }),
+                    );
+                    continue;
+                }
+                eprintln!("Registering {} ({})", row.name, row.udid);
+                let body = json!({
+                    "data": {
+                        "type": "devices",
+                        "attributes": {
+                            "name": row.name,
+                            "udid": row.udid,
+                            "platform": row.platform
+                        }
+                    }
+                });
+                match client.post("/devices", &body).await {
+                    Ok(device) => created.push(json!({
+                        "id": device["data"]["id"],
+                        "name": row.name,
+                        "udid": row.udid,
+                    })),
+                    Err(e) => failed.push(
+                        json!({ "line": row.line, "udid": row.udid, "reason": e.to_string() }),
+                    ),
+                }
+            }
+            Ok(json!({
+                "success": failed.is_empty(),
+                "dry_run": dry_run,
+                "registered": created,
+                "skipped": skipped,
+                "failed": failed,
+            }))
+        }
+        DevicesCommand::Disable { device_id } => {
+            update_device(client, device_id, json!({ "status": "DISABLED" })).await
+        }
+        DevicesCommand::Rename { device_id, name } => {
+            update_device(client, device_id, json!({ "name": name })).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_device_csv_with_header_and_commas_in_names() {
+        let csv = "name,udid,platform\n\
+                   QA iPhone 15,00008120-001A,ios\n\
+                   # lab machines\n\
+                   Build Mac, Lab 2,ABCD-1234,macos\n";
+        let rows = parse_devices(csv).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].platform, "IOS");
+        assert_eq!(rows[1].name, "Build Mac, Lab 2");
+        assert_eq!(rows[1].platform, "MAC_OS");
+        assert_eq!(rows[1].line, 4);
+
+        assert!(parse_devices("Phone,UDID1,android").is_err());
+        assert!(parse_devices("just-a-name").is_err());
     }
 }
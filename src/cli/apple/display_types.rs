@@ -0,0 +1,139 @@
+//! Screenshot display types and the `sync` directory each one lives in.
+//!
+//! Pull writes a set's screenshots to `<locale>/screenshots/<dir>/` and push maps the
+//! directory back to the display type it creates. A `[screenshot_dirs]` table in the
+//! config renames directories or maps display types missing from the built-in table:
+//!
+//! ```toml
+//! [screenshot_dirs]
+//! APP_WATCH_ULTRA = "watch"
+//! APP_DESKTOP = "macos"
+//! ```
+
+use std::collections::HashMap;
+
+use crate::config::Config;
+
+/// Display type and directory pairs. Pull uses the first directory listed for a display
+/// type; push uses the first display type listed for a directory.
+const BUILTIN: &[(&str, &str)] = &[
+    // APP_IPHONE_67 covers both 6.7" (1290x2796) and 6.9" (1320x2868) devices
+    ("APP_IPHONE_67", "iphone67"),
+    ("APP_IPHONE_67", "iphone69"),
+    ("APP_IPHONE_65", "iphone65"),
+    ("APP_IPHONE_61", "iphone61"),
+    ("APP_IPHONE_58", "iphone58"),
+    ("APP_IPHONE_55", "iphone55"),
+    ("APP_IPHONE_47", "iphone47"),
+    ("APP_IPHONE_40", "iphone40"),
+    ("APP_IPHONE_35", "iphone35"),
+    ("APP_IPAD_PRO_3GEN_129", "ipadPro129"),
+    ("APP_IPAD_PRO_129", "ipadPro129"),
+    ("APP_IPAD_PRO_3GEN_11", "ipadPro11"),
+    ("APP_IPAD_PRO_11", "ipadPro11"),
+    ("APP_IPAD_105", "ipad105"),
+    ("APP_IPAD_97", "ipad97"),
+    ("APP_WATCH_ULTRA", "watchUltra"),
+    ("APP_WATCH_SERIES_10", "watchSeries10"),
+    ("APP_WATCH_SERIES_7", "watchSeries7"),
+    ("APP_WATCH_SERIES_4", "watchSeries4"),
+    ("APP_WATCH_SERIES_3", "watchSeries3"),
+    ("APP_DESKTOP", "mac"),
+    ("APP_APPLE_TV", "appletv"),
+    ("APP_APPLE_VISION_PRO", "visionpro"),
+    ("IMESSAGE_APP_IPHONE_67", "imessageIphone67"),
+    ("IMESSAGE_APP_IPHONE_65", "imessageIphone65"),
+    ("IMESSAGE_APP_IPHONE_61", "imessageIphone61"),
+    ("IMESSAGE_APP_IPHONE_58", "imessageIphone58"),
+    ("IMESSAGE_APP_IPHONE_55", "imessageIphone55"),
+    ("IMESSAGE_APP_IPHONE_47", "imessageIphone47"),
+    ("IMESSAGE_APP_IPHONE_40", "imessageIphone40"),
+    ("IMESSAGE_APP_IPAD_PRO_3GEN_129", "imessageIpadPro129"),
+    ("IMESSAGE_APP_IPAD_PRO_129", "imessageIpadPro129"),
+    ("IMESSAGE_APP_IPAD_PRO_3GEN_11", "imessageIpadPro11"),
+    ("IMESSAGE_APP_IPAD_105", "imessageIpad105"),
+    ("IMESSAGE_APP_IPAD_97", "imessageIpad97"),
+];
+
+/// Display type ↔ directory mapping: the built-in table plus config overrides.
+pub struct DisplayTypeDirs {
+    /// Display type -> directory, from `[screenshot_dirs]`
+    custom: HashMap<String, String>,
+}
+
+impl DisplayTypeDirs {
+    /// The built-in table with the config's `[screenshot_dirs]` applied.
+    pub fn load() -> Self {
+        Self::with_overrides(
+            Config::load()
+                .map(|c| c.screenshot_dirs)
+                .unwrap_or_default(),
+        )
+    }
+
+    pub fn with_overrides(custom: HashMap<String, String>) -> Self {
+        Self { custom }
+    }
+
+    /// Directory for a display type, or `None` when it is not mapped.
+    pub fn dir<'a>(&'a self, display_type: &str) -> Option<&'a str> {
+        self.custom
+            .get(display_type)
+            .map(String::as_str)
+            .or_else(|| {
+                BUILTIN
+                    .iter()
+                    .find(|(t, _)| *t == display_type)
+                    .map(|(_, dir)| *dir)
+            })
+    }
+
+    /// Display type for a directory, or `None` when it is not mapped.
+    ///
+    /// A display type whose directory was renamed in the config no longer matches
+    /// its built-in directory.
+    pub fn display_type<'a>(&'a self, dir: &str) -> Option<&'a str> {
+        if let Some((display_type, _)) = self.custom.iter().find(|(_, d)| *d == dir) {
+            return Some(display_type);
+        }
+        BUILTIN
+            .iter()
+            .find(|(t, d)| *d == dir && !self.custom.contains_key(*t))
+            .map(|(t, _)| *t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_builtin_types_both_ways() {
+        let dirs = DisplayTypeDirs::with_overrides(HashMap::new());
+        assert_eq!(dirs.dir("APP_IPHONE_67"), Some("iphone67"));
+        assert_eq!(dirs.dir("APP_IPAD_PRO_129"), Some("ipadPro129"));
+        assert_eq!(dirs.dir("APP_WATCH_ULTRA"), Some("watchUltra"));
+        assert_eq!(dirs.dir("IMESSAGE_APP_IPHONE_65"), Some("imessageIphone65"));
+        assert_eq!(dirs.dir("APP_SOMETHING_NEW"), None);
+        assert_eq!(dirs.display_type("iphone69"), Some("APP_IPHONE_67"));
+        assert_eq!(
+            dirs.display_type("ipadPro129"),
+            Some("APP_IPAD_PRO_3GEN_129")
+        );
+        assert_eq!(dirs.display_type("visionpro"), Some("APP_APPLE_VISION_PRO"));
+        assert_eq!(dirs.display_type("misc"), None);
+    }
+
+    #[test]
+    fn config_overrides_rename_and_add_directories() {
+        let dirs = DisplayTypeDirs::with_overrides(HashMap::from([
+            ("APP_WATCH_ULTRA".to_string(), "watch".to_string()),
+            ("APP_SOMETHING_NEW".to_string(), "new".to_string()),
+        ]));
+        assert_eq!(dirs.dir("APP_WATCH_ULTRA"), Some("watch"));
+        assert_eq!(dirs.display_type("watch"), Some("APP_WATCH_ULTRA"));
+        assert_eq!(dirs.display_type("watchUltra"), None);
+        assert_eq!(dirs.dir("APP_SOMETHING_NEW"), Some("new"));
+        assert_eq!(dirs.display_type("new"), Some("APP_SOMETHING_NEW"));
+    }
+}
@@ -0,0 +1,307 @@
+//! Alternative distribution (EU Digital Markets Act) settings.
+//!
+//! Apps distributed through alternative marketplaces need an alternative distribution
+//! key, a package built from each approved version, and optionally marketplace search
+//! details pointing at the app's catalog entry.
+
+use clap::Subcommand;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+use super::resolve;
+use crate::api::apple_client::AppleClient;
+
+#[derive(Subcommand)]
+pub enum DistributionCommand {
+    /// Alternative distribution packages built from App Store versions
+    Package {
+        #[command(subcommand)]
+        command: PackageCommand,
+    },
+    /// Alternative distribution key used to sign packages
+    Key {
+        #[command(subcommand)]
+        command: KeyCommand,
+    },
+    /// Marketplace search details (catalog URL)
+    SearchDetail {
+        #[command(subcommand)]
+        command: SearchDetailCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PackageCommand {
+    /// Show the alternative distribution package of a version
+    Get {
+        /// App Store version ID or bundle[@version]
+        version_id: String,
+    },
+    /// Request an alternative distribution package for an approved version
+    ///
+    /// Examples:
+    ///   storeops apple distribution package create com.example.app@2.0.0
+    Create {
+        /// App Store version ID or bundle[@version]
+        version_id: String,
+    },
+    /// List the package's versions with their variant and delta download URLs
+    Versions {
+        /// Alternative distribution package ID
+        package_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum KeyCommand {
+    /// Show the app's alternative distribution key
+    Get {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+    },
+    /// Register the public key of an alternative distribution key pair
+    ///
+    /// Examples:
+    ///   storeops apple distribution key add com.example.app --public-key-file adp_public.pem
+    Add {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// PEM file containing the public key
+        #[arg(long)]
+        public_key_file: PathBuf,
+    },
+    /// Delete an alternative distribution key
+    Delete {
+        /// Alternative distribution key ID
+        key_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SearchDetailCommand {
+    /// Show the app's marketplace search details
+    Get {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+    },
+    /// Create or update the catalog URL marketplaces use to find the app
+    ///
+    /// Examples:
+    ///   storeops apple distribution search-detail set com.example.app --catalog-url https://example.com/catalog.json
+    Set {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Catalog URL
+        #[arg(long)]
+        catalog_url: String,
+    },
+    /// Remove the app's marketplace search details
+    Delete {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+    },
+}
+
+/// The PEM body without armor lines or whitespace, as the API expects.
+fn public_key_body(pem: &str) -> String {
+    pem.lines()
+        .filter(|l| !l.starts_with("-----"))
+        .map(str::trim)
+        .collect()
+}
+
+pub async fn handle(
+    cmd: &DistributionCommand,
+    client: &AppleClient,
+    limit: Option<u32>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        DistributionCommand::Package { command } => handle_package(command, client, limit).await,
+        DistributionCommand::Key { command } => handle_key(command, client).await,
+        DistributionCommand::SearchDetail { command } => {
+            handle_search_detail(command, client).await
+        }
+    }
+}
+
+async fn handle_package(
+    cmd: &PackageCommand,
+    client: &AppleClient,
+    limit: Option<u32>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        PackageCommand::Get { version_id } => {
+            let version_id = &resolve::version_id(version_id, client).await?;
+            client
+                .get::<Value>(
+                    &format!("/appStoreVersions/{version_id}/alternativeDistributionPackage"),
+                    &[],
+                )
+                .await
+        }
+        PackageCommand::Create { version_id } => {
+            let version_id = &resolve::version_id(version_id, client).await?;
+            let body = json!({
+                "data": {
+                    "type": "alternativeDistributionPackages",
+                    "relationships": {
+                        "appStoreVersion": {
+                            "data": {
+                                "type": "appStoreVersions",
+                                "id": version_id
+                            }
+                        }
+                    }
+                }
+            });
+            client.post("/alternativeDistributionPackages", &body).await
+        }
+        PackageCommand::Versions { package_id } => {
+            let limit_str = limit.unwrap_or(50).to_string();
+            client
+                .get::<Value>(
+                    &format!("/alternativeDistributionPackages/{package_id}/versions"),
+                    &[
+                        ("limit", limit_str.as_str()),
+                        ("include", "variants,deltas"),
+                    ],
+                )
+                .await
+        }
+    }
+}
+
+async fn handle_key(
+    cmd: &KeyCommand,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        KeyCommand::Get { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            client
+                .get::<Value>(&format!("/apps/{app_id}/alternativeDistributionKey"), &[])
+                .await
+        }
+        KeyCommand::Add {
+            app_id,
+            public_key_file,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let pem = std::fs::read_to_string(public_key_file)?;
+            let public_key = public_key_body(&pem);
+            if public_key.is_empty() {
+                return Err(format!("{} contains no key", public_key_file.display()).into());
+            }
+            let body = json!({
+                "data": {
+                    "type": "alternativeDistributionKeys",
+                    "attributes": {
+                        "publicKey": public_key
+                    },
+                    "relationships": {
+                        "app": {
+                            "data": {
+                                "type": "apps",
+                                "id": app_id
+                            }
+                        }
+                    }
+                }
+            });
+            client.post("/alternativeDistributionKeys", &body).await
+        }
+        KeyCommand::Delete { key_id } => {
+            client
+                .delete(&format!("/alternativeDistributionKeys/{key_id}"))
+                .await
+        }
+    }
+}
+
+/// ID of the app's marketplace search detail, if it has one.
+async fn search_detail_id(
+    app_id: &str,
+    client: &AppleClient,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let detail: Value = client
+        .get(&format!("/apps/{app_id}/marketplaceSearchDetail"), &[])
+        .await?;
+    Ok(detail["data"]["id"].as_str().map(String::from))
+}
+
+async fn handle_search_detail(
+    cmd: &SearchDetailCommand,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        SearchDetailCommand::Get { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            client
+                .get::<Value>(&format!("/apps/{app_id}/marketplaceSearchDetail"), &[])
+                .await
+        }
+        SearchDetailCommand::Set {
+            app_id,
+            catalog_url,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            // A missing detail comes back as an error or as `data: null`
+            let existing = search_detail_id(app_id, client).await.ok().flatten();
+            match existing {
+                Some(id) => {
+                    let body = json!({
+                        "data": {
+                            "type": "marketplaceSearchDetails",
+                            "id": id,
+                            "attributes": {
+                                "catalogUrl": catalog_url
+                            }
+                        }
+                    });
+                    client
+                        .patch(&format!("/marketplaceSearchDetails/{id}"), &body)
+                        .await
+                }
+                None => {
+                    let body = json!({
+                        "data": {
+                            "type": "marketplaceSearchDetails",
+                            "attributes": {
+                                "catalogUrl": catalog_url
+                            },
+                            "relationships": {
+                                "app": {
+                                    "data": {
+                                        "type": "apps",
+                                        "id": app_id
+                                    }
+                                }
+                            }
+                        }
+                    });
+                    client.post("/marketplaceSearchDetails", &body).await
+                }
+            }
+        }
+        SearchDetailCommand::Delete { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let id = search_detail_id(app_id, client)
+                .await?
+                .ok_or("app has no marketplace search details")?;
+            client
+                .delete(&format!("/marketplaceSearchDetails/{id}"))
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_pem_armor_and_line_breaks() {
+        let pem = "-----BEGIN PUBLIC KEY-----\nMFkwEwYH\nKoZIzj0C\n-----END PUBLIC KEY-----\n";
+        assert_eq!(public_key_body(pem), "MFkwEwYHKoZIzj0C");
+    }
+}
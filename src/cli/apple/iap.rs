@@ -1,14 +1,17 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
 
+use super::price_matrix::{self, Product};
+use super::resolve;
 use crate::api::apple_client::AppleClient;
+use std::path::PathBuf;
 
 #[derive(Subcommand)]
 pub enum IapCommand {
     /// List in-app purchases for an app
     List {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
     },
     /// Get in-app purchase details
     Get {
@@ -17,8 +20,8 @@ pub enum IapCommand {
     },
     /// Create an in-app purchase
     Create {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
         /// Product name
         #[arg(long)]
         name: String,
@@ -113,6 +116,40 @@ pub enum IapPricesCommand {
         #[arg(long)]
         territory: Option<String>,
     },
+    /// Export the prices of all in-app purchases as a CSV matrix
+    ///
+    /// One row per product ID and one column per territory, each cell holding the
+    /// customer price in that territory's currency.
+    ///
+    /// Examples:
+    ///   storeops apple iap prices export com.example.app --out prices.csv
+    Export {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// CSV file to write
+        #[arg(long)]
+        out: PathBuf,
+        /// Export prices in effect on this day instead of today (YYYY-MM-DD)
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+    },
+    /// Apply a CSV price matrix written by `export`
+    ///
+    /// Changed cells are matched to the territory's price point; empty cells are left
+    /// alone. Cells without a matching price point are reported and skipped.
+    ///
+    /// Examples:
+    ///   storeops apple iap prices import com.example.app --file prices.csv --dry-run
+    Import {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// CSV price matrix
+        #[arg(long)]
+        file: PathBuf,
+        /// Show the changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 pub async fn handle(
@@ -122,6 +159,7 @@ pub async fn handle(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         IapCommand::List { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             let limit_str = limit.unwrap_or(50).to_string();
             client
                 .get(
@@ -141,6 +179,7 @@ pub async fn handle(
             product_id,
             iap_type,
         } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             let body = json!({
                 "data": {
                     "type": "inAppPurchases",
@@ -301,5 +340,18 @@ async fn handle_iap_prices(
                 .get(&format!("/inAppPurchasesV2/{iap_id}/pricePoints"), &query)
                 .await
         }
+        IapPricesCommand::Export { app_id, out, date } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let date = date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+            price_matrix::export(Product::Iap, app_id, out, &date.to_string(), client).await
+        }
+        IapPricesCommand::Import {
+            app_id,
+            file,
+            dry_run,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            price_matrix::import(Product::Iap, app_id, file, *dry_run, client).await
+        }
     }
 }
@@ -0,0 +1,205 @@
+//! Keyword field budget analysis.
+//!
+//! The App Store indexes the words of an app's name and subtitle as well as its
+//! keywords, so repeating them in the 100-character keyword field spends budget
+//! without adding reach. The analysis reports the characters used, terms that
+//! duplicate each other or the name/subtitle, and what removing them would free.
+
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use super::sync;
+use crate::api::apple_client::AppleClient;
+
+/// App Store limit for the keywords field, in characters
+const KEYWORDS_LIMIT: usize = 100;
+
+/// Text the analysis works from.
+#[derive(Default)]
+pub struct KeywordSource {
+    pub keywords: String,
+    pub name: String,
+    pub subtitle: String,
+}
+
+/// Lowercased words of `text`, split on anything that is not a letter or digit.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Characters, duplicate terms and suggested removals for one locale.
+pub fn analyze(locale: &str, source: &KeywordSource) -> Value {
+    let chars = source.keywords.chars().count();
+    let name_words: BTreeSet<String> = words(&source.name).into_iter().collect();
+    let subtitle_words: BTreeSet<String> = words(&source.subtitle).into_iter().collect();
+
+    let mut seen = BTreeSet::new();
+    let mut terms = Vec::new();
+    let mut removals = Vec::new();
+    for raw in source.keywords.split(',') {
+        let term = raw.trim();
+        if term.is_empty() {
+            continue;
+        }
+        terms.push(term.to_string());
+        // Removing a term also frees its separating comma
+        let saves = term.chars().count() + 1;
+        let term_words = words(term);
+        if !seen.insert(term.to_lowercase()) {
+            removals.push(json!({ "term": term, "reason": "duplicate keyword", "saves": saves }));
+            continue;
+        }
+        let covered: Vec<&String> = term_words
+            .iter()
+            .filter(|w| name_words.contains(*w) || subtitle_words.contains(*w))
+            .collect();
+        if covered.is_empty() {
+            continue;
+        }
+        let place = if covered.iter().all(|w| name_words.contains(*w)) {
+            "name"
+        } else if covered.iter().all(|w| subtitle_words.contains(*w)) {
+            "subtitle"
+        } else {
+            "name and subtitle"
+        };
+        if covered.len() == term_words.len() {
+            removals.push(json!({
+                "term": term,
+                "reason": format!("already in the {place}"),
+                "saves": saves,
+            }));
+        } else {
+            let rest: Vec<&String> = term_words.iter().filter(|w| !covered.contains(w)).collect();
+            let shortened = rest
+                .iter()
+                .map(|w| w.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            removals.push(json!({
+                "term": term,
+                "reason": format!(
+                    "{} already in the {place}; keep \"{shortened}\"",
+                    covered.iter().map(|w| w.as_str()).collect::<Vec<_>>().join(", ")
+                ),
+                "saves": term.chars().count() - shortened.chars().count(),
+            }));
+        }
+    }
+
+    // Spaces after commas count against the limit but are not needed
+    let spaces = source
+        .keywords
+        .split(',')
+        .skip(1)
+        .filter(|t| t.starts_with(' '))
+        .count();
+    let saved: u64 = removals
+        .iter()
+        .filter_map(|r| r["saves"].as_u64())
+        .sum::<u64>()
+        + spaces as u64;
+    json!({
+        "locale": locale,
+        "chars": chars,
+        "limit": KEYWORDS_LIMIT,
+        "remaining": KEYWORDS_LIMIT as i64 - chars as i64,
+        "terms": terms.len(),
+        "spaces_after_commas": spaces,
+        "removals": removals,
+        "chars_freed": saved,
+    })
+}
+
+/// Keywords, name and subtitle of `locale` from a pulled metadata directory.
+pub fn load_local(dir: &Path, locale: &str) -> Result<KeywordSource, Box<dyn std::error::Error>> {
+    let dir = dir.join(sync::asc_to_internal_locale(locale));
+    if !dir.is_dir() {
+        return Err(format!("no metadata for {locale} in {}", dir.display()).into());
+    }
+    let read = |file: &str| {
+        std::fs::read_to_string(dir.join(file))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    Ok(KeywordSource {
+        keywords: read("keywords.txt"),
+        name: read("name.txt"),
+        subtitle: read("subtitle.txt"),
+    })
+}
+
+/// Keywords of the editable (or latest) version and the app info name/subtitle.
+pub async fn load_remote(
+    app_id: &str,
+    locale: &str,
+    client: &AppleClient,
+) -> Result<KeywordSource, Box<dyn std::error::Error>> {
+    let version = sync::get_editable_version(app_id, None, client).await?;
+    let version_id = version["id"].as_str().ok_or("version has no id")?;
+    let version_locs: Value = client
+        .get(
+            &format!("/appStoreVersions/{version_id}/appStoreVersionLocalizations"),
+            &[("filter[locale]", locale), ("limit", "1")],
+        )
+        .await?;
+    let version_loc = version_locs["data"]
+        .get(0)
+        .ok_or_else(|| format!("version has no {locale} localization"))?;
+
+    let (app_info, _) = sync::get_app_info(app_id, client).await?;
+    let app_info_id = app_info["id"].as_str().ok_or("app info has no id")?;
+    let info_locs: Value = client
+        .get(
+            &format!("/appInfos/{app_info_id}/appInfoLocalizations"),
+            &[("filter[locale]", locale), ("limit", "1")],
+        )
+        .await?;
+    let info = &info_locs["data"][0]["attributes"];
+
+    let text = |v: &Value| v.as_str().unwrap_or_default().to_string();
+    Ok(KeywordSource {
+        keywords: text(&version_loc["attributes"]["keywords"]),
+        name: text(&info["name"]),
+        subtitle: text(&info["subtitle"]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_terms_already_in_name_or_subtitle() {
+        let report = analyze(
+            "en-US",
+            &KeywordSource {
+                keywords: "photo, editor,filters,Photo,collage maker,retouch".to_string(),
+                name: "PhotoLab".to_string(),
+                subtitle: "Photo editor & collage".to_string(),
+            },
+        );
+        assert_eq!(report["chars"], 49);
+        assert_eq!(report["remaining"], 51);
+        assert_eq!(report["terms"], 6);
+        assert_eq!(report["spaces_after_commas"], 1);
+        assert_eq!(
+            report["removals"],
+            json!([
+                {"term": "photo", "reason": "already in the subtitle", "saves": 6},
+                {"term": "editor", "reason": "already in the subtitle", "saves": 7},
+                {"term": "Photo", "reason": "duplicate keyword", "saves": 6},
+                {
+                    "term": "collage maker",
+                    "reason": "collage already in the subtitle; keep \"maker\"",
+                    "saves": 8
+                },
+            ])
+        );
+        assert_eq!(report["chars_freed"], 28);
+    }
+}
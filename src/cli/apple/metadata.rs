@@ -1,7 +1,30 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+use super::app_info_stage::{self, Stage};
+use super::platform::Platform;
+use super::{availability, keywords, remediate, resolve, sync};
 use crate::api::apple_client::AppleClient;
+use crate::api::apple_errors::{problem_of, StateProblem};
+use crate::config::territories;
+
+/// Version localization fields required for submission
+const REQUIRED_VERSION_FIELDS: &[&str] = &["description", "keywords", "supportUrl"];
+/// App info localization fields required for submission
+const REQUIRED_APP_INFO_FIELDS: &[&str] = &["name"];
+/// App Store limit for promotional text, in characters
+const PROMO_TEXT_LIMIT: usize = 170;
+/// App Store limit for what's new text, in characters
+const WHATS_NEW_LIMIT: usize = 4000;
+/// Version states in which what's new text can still be edited
+const WHATS_NEW_EDITABLE_STATES: &[&str] = &[
+    "PREPARE_FOR_SUBMISSION",
+    "DEVELOPER_REJECTED",
+    "REJECTED",
+    "METADATA_REJECTED",
+];
 
 #[derive(Subcommand)]
 pub enum MetadataCommand {
@@ -11,6 +34,7 @@ pub enum MetadataCommand {
         command: LocalizationsCommand,
     },
     /// Manage app-level info localizations (subtitle, privacy text)
+    #[command(alias = "appinfo")]
     AppInfo {
         #[command(subcommand)]
         command: AppInfoCommand,
@@ -20,23 +44,73 @@ pub enum MetadataCommand {
         #[command(subcommand)]
         command: CategoriesCommand,
     },
+    /// Update promotional text on the live version without a new submission
+    PromoText {
+        #[command(subcommand)]
+        command: PromoTextCommand,
+    },
+    /// Set what's new text across the editable version's localizations
+    WhatsNew {
+        #[command(subcommand)]
+        command: WhatsNewCommand,
+    },
+    /// Analyze keyword field usage
+    Keywords {
+        #[command(subcommand)]
+        command: KeywordsCommand,
+    },
+    /// Report locale coverage: missing required fields and unlocalized markets
+    ///
+    /// Checks the editable (or latest) version and app info localizations, then
+    /// flags available territories whose language has no localization.
+    ///
+    /// Examples:
+    ///   storeops apple metadata coverage 1234567890
+    Coverage {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum KeywordsCommand {
+    /// Report keyword character usage and terms that waste budget
+    ///
+    /// Flags duplicate keywords and terms already in the app name or subtitle (which
+    /// are indexed anyway), and how many characters removing them would free.
+    ///
+    /// Examples:
+    ///   storeops apple metadata keywords analyze com.example.app --locale en-US
+    ///   storeops apple metadata keywords analyze --locale de-DE --metadata-dir metadata/apple
+    Analyze {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Locale (e.g., en-US, ja)
+        #[arg(long)]
+        locale: String,
+        /// Read a pulled metadata directory instead of App Store Connect
+        #[arg(long)]
+        metadata_dir: Option<PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
 pub enum LocalizationsCommand {
     /// List all localizations for a version
     List {
-        /// App Store Version ID
+        /// App Store version ID or bundle[@version]
         version_id: String,
     },
     /// Get a specific localization
     Get {
-        /// Localization ID
+        /// App Store version localization ID or bundle[@version]/locale
         localization_id: String,
     },
     /// Create a localization for a version
+    ///
+    /// With `--auto-remediate`, a locale that already exists is updated instead.
     Create {
-        /// App Store Version ID
+        /// App Store version ID or bundle[@version]
         version_id: String,
         /// Locale (e.g., en-US, pt-BR, ja)
         #[arg(long)]
@@ -59,10 +133,16 @@ pub enum LocalizationsCommand {
         /// Support URL
         #[arg(long)]
         support_url: Option<String>,
+        /// On a state conflict, do what the error hint suggests instead of failing
+        #[arg(long)]
+        auto_remediate: bool,
     },
     /// Update a localization
+    ///
+    /// With `--auto-remediate`, an update to a version that can no longer be edited
+    /// is applied to the editable version, creating the next patch version if needed.
     Update {
-        /// Localization ID
+        /// App Store version localization ID or bundle[@version]/locale
         localization_id: String,
         /// App description
         #[arg(long)]
@@ -82,14 +162,73 @@ pub enum LocalizationsCommand {
         /// Support URL
         #[arg(long)]
         support_url: Option<String>,
+        /// On a state conflict, do what the error hint suggests instead of failing
+        #[arg(long)]
+        auto_remediate: bool,
     },
     /// Delete a localization
     Delete {
-        /// Localization ID
+        /// App Store version localization ID or bundle[@version]/locale
         localization_id: String,
     },
 }
 
+#[derive(Subcommand)]
+pub enum PromoTextCommand {
+    /// Set promotional text for one locale of the live version
+    ///
+    /// Apple lets promotional text change at any time without review, so only
+    /// `promotionalText` is sent; no other localization field is touched. An app
+    /// live on several platforms has one live version per platform: all of them are
+    /// updated unless --platform picks one.
+    ///
+    /// Examples:
+    ///   storeops apple metadata promo-text set --bundle-id com.example.app --locale en-US --text "Now with widgets"
+    ///   storeops apple metadata promo-text set --locale en-US --text "Now on Mac" --platform macos
+    Set {
+        /// App ID or bundle ID (defaults to the profile's default_app_id)
+        #[arg(long)]
+        bundle_id: Option<String>,
+        /// Locale of the live version localization (e.g., en-US)
+        #[arg(long)]
+        locale: String,
+        /// Promotional text (up to 170 characters)
+        #[arg(long)]
+        text: String,
+        /// Only the live version of this platform (default: every live version)
+        #[arg(long, value_enum)]
+        platform: Option<Platform>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WhatsNewCommand {
+    /// Write what's new text to the editable version's localizations
+    ///
+    /// `--file` is either one text file used for every locale, or a directory with
+    /// `<locale>.txt` (or a pulled `<locale>/release_notes.txt`) per locale. Locales
+    /// without a file in the directory are skipped.
+    ///
+    /// Examples:
+    ///   storeops apple metadata whats-new set --bundle-id com.example.app --file notes.txt --all-locales
+    ///   storeops apple metadata whats-new set --file release-notes/ --all-locales
+    ///   storeops apple metadata whats-new set --file notes.txt --locale en-US,en-GB
+    Set {
+        /// App ID or bundle ID (defaults to the profile's default_app_id)
+        #[arg(long)]
+        bundle_id: Option<String>,
+        /// Text file, or directory of per-locale files
+        #[arg(long)]
+        file: PathBuf,
+        /// Update every existing version localization
+        #[arg(long, conflicts_with = "locale", required_unless_present = "locale")]
+        all_locales: bool,
+        /// Only update these locales (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        locale: Vec<String>,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum AppInfoCommand {
     /// List app info localizations
@@ -133,6 +272,38 @@ pub enum AppInfoCommand {
         /// App Info Localization ID
         localization_id: String,
     },
+    /// Record a name/subtitle change to apply once the app info is editable
+    ///
+    /// Applied right away when the app info is editable; otherwise by the next
+    /// `app-info apply`, `versions create` or `sync push` that finds it editable.
+    ///
+    /// Examples:
+    ///   storeops apple metadata app-info stage com.example.app --locale en-US --name "Notes Pro"
+    ///   storeops apple metadata app-info stage 1234567890 --locale de-DE --subtitle "Notizen, schnell"
+    #[command(group = clap::ArgGroup::new("change").required(true).multiple(true).args(["name", "subtitle"]))]
+    Stage {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// App Store Connect locale (e.g., en-US)
+        #[arg(long)]
+        locale: String,
+        /// New app name
+        #[arg(long)]
+        name: Option<String>,
+        /// New subtitle
+        #[arg(long)]
+        subtitle: Option<String>,
+    },
+    /// List staged name/subtitle changes
+    Staged {
+        /// App ID or bundle ID (all apps when omitted)
+        app_id: Option<String>,
+    },
+    /// Apply staged name/subtitle changes if the app info is editable now
+    Apply {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -168,7 +339,322 @@ pub async fn handle(
         }
         MetadataCommand::AppInfo { command } => handle_app_info(command, client, limit).await,
         MetadataCommand::Categories { command } => handle_categories(command, client, limit).await,
+        MetadataCommand::PromoText {
+            command:
+                PromoTextCommand::Set {
+                    bundle_id,
+                    locale,
+                    text,
+                    platform,
+                },
+        } => {
+            let app_id = &resolve::app_id(bundle_id.as_deref(), client).await?;
+            set_promo_text(app_id, locale, text, *platform, client).await
+        }
+        MetadataCommand::WhatsNew {
+            command:
+                WhatsNewCommand::Set {
+                    bundle_id,
+                    file,
+                    all_locales: _,
+                    locale,
+                },
+        } => {
+            let app_id = &resolve::app_id(bundle_id.as_deref(), client).await?;
+            set_whats_new(app_id, file, locale, client).await
+        }
+        MetadataCommand::Keywords {
+            command:
+                KeywordsCommand::Analyze {
+                    app_id,
+                    locale,
+                    metadata_dir,
+                },
+        } => {
+            let source = match metadata_dir {
+                Some(dir) => keywords::load_local(dir, locale)?,
+                None => {
+                    let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+                    keywords::load_remote(app_id, locale, client).await?
+                }
+            };
+            Ok(keywords::analyze(locale, &source))
+        }
+        MetadataCommand::Coverage { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            coverage(app_id, client).await
+        }
+    }
+}
+
+/// Required fields that are absent or blank in a resource's attributes.
+fn missing_fields(attrs: &Value, required: &[&str]) -> Vec<String> {
+    required
+        .iter()
+        .filter(|f| attrs[**f].as_str().is_none_or(|v| v.trim().is_empty()))
+        .map(|f| f.to_string())
+        .collect()
+}
+
+/// Missing required fields per locale across several localization lists.
+///
+/// A locale absent from one list is missing all of that list's required fields.
+fn locale_coverage(sources: &[(&Value, &[&str])]) -> BTreeMap<String, Vec<String>> {
+    let locales_of = |resp: &Value| -> Vec<(String, Value)> {
+        resp["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|l| {
+                let attrs = &l["attributes"];
+                Some((attrs["locale"].as_str()?.to_string(), attrs.clone()))
+            })
+            .collect()
+    };
+    let mut by_locale: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (resp, _) in sources {
+        for (locale, _) in locales_of(resp) {
+            by_locale.entry(locale).or_default();
+        }
+    }
+    for (resp, required) in sources {
+        let locs = locales_of(resp);
+        for (locale, missing) in by_locale.iter_mut() {
+            match locs.iter().find(|(l, _)| l == locale) {
+                Some((_, attrs)) => missing.extend(missing_fields(attrs, required)),
+                None => missing.extend(required.iter().map(|f| f.to_string())),
+            }
+        }
+    }
+    by_locale
+}
+
+async fn coverage(app_id: &str, client: &AppleClient) -> Result<Value, Box<dyn std::error::Error>> {
+    let version = sync::get_editable_version(app_id, None, client).await?;
+    let version_id = version["id"].as_str().ok_or("version has no id")?;
+    let version_locs: Value = client
+        .get(
+            &format!("/appStoreVersions/{version_id}/appStoreVersionLocalizations"),
+            &[("limit", "200")],
+        )
+        .await?;
+    let (app_info, _) = sync::get_app_info(app_id, client).await?;
+    let app_info_id = app_info["id"].as_str().ok_or("app info has no id")?;
+    let info_locs: Value = client
+        .get(
+            &format!("/appInfos/{app_info_id}/appInfoLocalizations"),
+            &[("limit", "200")],
+        )
+        .await?;
+
+    let by_locale = locale_coverage(&[
+        (&version_locs, REQUIRED_VERSION_FIELDS),
+        (&info_locs, REQUIRED_APP_INFO_FIELDS),
+    ]);
+
+    let territories: Vec<String> = availability::territory_availabilities(app_id, client)
+        .await?
+        .into_iter()
+        .filter(|(_, _, available)| *available)
+        .map(|(territory, _, _)| territory)
+        .collect();
+    let locales: Vec<String> = by_locale.keys().cloned().collect();
+    let uncovered = territories::uncovered_markets(&territories, &locales);
+
+    Ok(json!({
+        "app_id": app_id,
+        "version": version["attributes"]["versionString"],
+        "locales": by_locale
+            .into_iter()
+            .map(|(locale, missing)| json!({
+                "locale": locale,
+                "complete": missing.is_empty(),
+                "missing": missing,
+            }))
+            .collect::<Vec<_>>(),
+        "territories": territories.len(),
+        "uncovered_markets": uncovered,
+    }))
+}
+
+/// PATCH body for a version localization that carries promotional text and nothing else.
+fn promo_text_body(localization_id: &str, text: &str) -> Result<Value, String> {
+    let len = text.chars().count();
+    if len > PROMO_TEXT_LIMIT {
+        return Err(format!(
+            "promotional text is {len} characters; the limit is {PROMO_TEXT_LIMIT}"
+        ));
+    }
+    Ok(json!({
+        "data": {
+            "type": "appStoreVersionLocalizations",
+            "id": localization_id,
+            "attributes": { "promotionalText": text }
+        }
+    }))
+}
+
+async fn set_promo_text(
+    app_id: &str,
+    locale: &str,
+    text: &str,
+    platform: Option<Platform>,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut query = vec![
+        ("filter[appStoreState]", "READY_FOR_SALE"),
+        ("limit", "200"),
+    ];
+    if let Some(platform) = platform {
+        query.push(("filter[platform]", platform.api_value()));
+    }
+    let live: Value = client
+        .get(&format!("/apps/{app_id}/appStoreVersions"), &query)
+        .await?;
+    let versions = live["data"].as_array().cloned().unwrap_or_default();
+    if versions.is_empty() {
+        return Err(
+            "app has no live version; edit the pending version's localization instead".into(),
+        );
+    }
+
+    // Find every localization before changing any, so a missing locale changes nothing
+    let mut targets = Vec::new();
+    for version in &versions {
+        let version_id = version["id"].as_str().ok_or("version has no id")?;
+        let version_platform = version["attributes"]["platform"].as_str().unwrap_or("?");
+        let locs: Value = client
+            .get(
+                &format!("/appStoreVersions/{version_id}/appStoreVersionLocalizations"),
+                &[("filter[locale]", locale), ("limit", "1")],
+            )
+            .await?;
+        let localization_id = locs["data"][0]["id"].as_str().ok_or_else(|| {
+            format!("live {version_platform} version has no {locale} localization")
+        })?;
+        targets.push((version, localization_id.to_string()));
+    }
+
+    let mut updated = Vec::new();
+    for (version, localization_id) in targets {
+        let body = promo_text_body(&localization_id, text)?;
+        client
+            .patch(
+                &format!("/appStoreVersionLocalizations/{localization_id}"),
+                &body,
+            )
+            .await?;
+        updated.push(json!({
+            "platform": version["attributes"]["platform"],
+            "version": version["attributes"]["versionString"],
+            "localization_id": localization_id,
+        }));
     }
+    Ok(json!({
+        "status": "ok",
+        "app_id": app_id,
+        "locale": locale,
+        "promotional_text": text,
+        "versions": updated,
+    }))
+}
+
+/// What's new text for `locale` from a single file or a per-locale directory.
+fn whats_new_for_locale(source: &Path, locale: &str) -> std::io::Result<Option<String>> {
+    if !source.is_dir() {
+        return std::fs::read_to_string(source).map(|t| Some(t.trim().to_string()));
+    }
+    for path in [
+        source.join(format!("{locale}.txt")),
+        source.join(locale).join("release_notes.txt"),
+    ] {
+        if path.is_file() {
+            return std::fs::read_to_string(path).map(|t| Some(t.trim().to_string()));
+        }
+    }
+    Ok(None)
+}
+
+async fn set_whats_new(
+    app_id: &str,
+    source: &Path,
+    only: &[String],
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    if !source.exists() {
+        return Err(format!("file not found: {}", source.display()).into());
+    }
+    let version = sync::get_editable_version(app_id, None, client).await?;
+    let state = version["attributes"]["appStoreState"]
+        .as_str()
+        .unwrap_or_default();
+    if !WHATS_NEW_EDITABLE_STATES.contains(&state) {
+        return Err(
+            format!("no editable version (latest is {state}); create a version first").into(),
+        );
+    }
+    let version_id = version["id"].as_str().ok_or("version has no id")?;
+    let locs: Value = client
+        .get(
+            &format!("/appStoreVersions/{version_id}/appStoreVersionLocalizations"),
+            &[("limit", "200")],
+        )
+        .await?;
+
+    let mut updated = Vec::new();
+    let mut skipped = Vec::new();
+    for loc in locs["data"].as_array().into_iter().flatten() {
+        let locale = loc["attributes"]["locale"].as_str().unwrap_or_default();
+        let localization_id = loc["id"].as_str().unwrap_or_default();
+        if !only.is_empty() && !only.iter().any(|l| l == locale) {
+            continue;
+        }
+        let Some(text) = whats_new_for_locale(source, locale)? else {
+            skipped.push(json!({ "locale": locale, "reason": "no file for locale" }));
+            continue;
+        };
+        let len = text.chars().count();
+        if text.is_empty() || len > WHATS_NEW_LIMIT {
+            skipped.push(json!({
+                "locale": locale,
+                "reason": format!("{len} characters; must be 1-{WHATS_NEW_LIMIT}"),
+            }));
+            continue;
+        }
+        eprintln!("Updating what's new for {locale}...");
+        let body = json!({
+            "data": {
+                "type": "appStoreVersionLocalizations",
+                "id": localization_id,
+                "attributes": { "whatsNew": text }
+            }
+        });
+        client
+            .patch(
+                &format!("/appStoreVersionLocalizations/{localization_id}"),
+                &body,
+            )
+            .await?;
+        updated.push(locale.to_string());
+    }
+    for locale in only {
+        if !locs["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|l| l["attributes"]["locale"].as_str() == Some(locale))
+        {
+            skipped.push(json!({ "locale": locale, "reason": "no localization on version" }));
+        }
+    }
+
+    Ok(json!({
+        "status": "ok",
+        "app_id": app_id,
+        "version": version["attributes"]["versionString"],
+        "updated": updated,
+        "skipped": skipped,
+    }))
 }
 
 async fn handle_localizations(
@@ -178,6 +664,7 @@ async fn handle_localizations(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         LocalizationsCommand::List { version_id } => {
+            let version_id = &resolve::version_id(version_id, client).await?;
             let limit_str = limit.unwrap_or(50).to_string();
             client
                 .get(
@@ -187,6 +674,7 @@ async fn handle_localizations(
                 .await
         }
         LocalizationsCommand::Get { localization_id } => {
+            let localization_id = &resolve::localization_id(localization_id, client).await?;
             client
                 .get::<Value>(
                     &format!("/appStoreVersionLocalizations/{localization_id}"),
@@ -203,7 +691,9 @@ async fn handle_localizations(
             promo_text,
             marketing_url,
             support_url,
+            auto_remediate,
         } => {
+            let version_id = &resolve::version_id(version_id, client).await?;
             let mut attrs = json!({ "locale": locale });
             if let Some(v) = description {
                 attrs["description"] = json!(v);
@@ -237,7 +727,21 @@ async fn handle_localizations(
                     }
                 }
             });
-            client.post("/appStoreVersionLocalizations", &body).await
+            match client.post("/appStoreVersionLocalizations", &body).await {
+                Err(e)
+                    if *auto_remediate
+                        && problem_of(e.as_ref()) == Some(StateProblem::Duplicate) =>
+                {
+                    remediate::update_existing_localization(
+                        version_id,
+                        locale,
+                        &body["data"]["attributes"],
+                        client,
+                    )
+                    .await
+                }
+                result => result,
+            }
         }
         LocalizationsCommand::Update {
             localization_id,
@@ -247,7 +751,9 @@ async fn handle_localizations(
             promo_text,
             marketing_url,
             support_url,
+            auto_remediate,
         } => {
+            let localization_id = &resolve::localization_id(localization_id, client).await?;
             let mut attrs = json!({});
             if let Some(v) = description {
                 attrs["description"] = json!(v);
@@ -274,14 +780,29 @@ async fn handle_localizations(
                     "attributes": attrs
                 }
             });
-            client
+            let result = client
                 .patch(
                     &format!("/appStoreVersionLocalizations/{localization_id}"),
                     &body,
                 )
-                .await
+                .await;
+            match result {
+                Err(e)
+                    if *auto_remediate
+                        && problem_of(e.as_ref()) == Some(StateProblem::NotEditable) =>
+                {
+                    remediate::update_in_editable_version(
+                        localization_id,
+                        &body["data"]["attributes"],
+                        client,
+                    )
+                    .await
+                }
+                result => result,
+            }
         }
         LocalizationsCommand::Delete { localization_id } => {
+            let localization_id = &resolve::localization_id(localization_id, client).await?;
             client
                 .delete(&format!("/appStoreVersionLocalizations/{localization_id}"))
                 .await
@@ -369,6 +890,42 @@ async fn handle_app_info(
                 .delete(&format!("/appInfoLocalizations/{localization_id}"))
                 .await
         }
+        AppInfoCommand::Stage {
+            app_id,
+            locale,
+            name,
+            subtitle,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let path = Stage::path()?;
+            let mut stage = Stage::load(&path)?;
+            let change = stage.add(app_id, locale, name.as_deref(), subtitle.as_deref());
+            stage.save(&path)?;
+            let apply = app_info_stage::apply_pending(app_id, client).await?;
+            Ok(json!({
+                "app_id": app_id,
+                "locale": locale,
+                "staged": change,
+                "apply": apply,
+            }))
+        }
+        AppInfoCommand::Staged { app_id } => {
+            let stage = Stage::load(&Stage::path()?)?;
+            match app_id {
+                Some(app_id) => {
+                    let app_id = resolve::app_id(Some(app_id), client).await?;
+                    Ok(
+                        json!({ app_id.clone(): stage.apps.get(&app_id).cloned().unwrap_or_default() }),
+                    )
+                }
+                None => Ok(json!(stage.apps)),
+            }
+        }
+        AppInfoCommand::Apply { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let result = app_info_stage::apply_pending(app_id, client).await?;
+            Ok(result.unwrap_or_else(|| json!({ "applied": [], "pending": [] })))
+        }
     }
 }
 
@@ -426,3 +983,66 @@ async fn handle_categories(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_treats_blank_as_missing() {
+        let attrs = json!({"description": "An app", "keywords": "  ", "supportUrl": null});
+        assert_eq!(
+            missing_fields(&attrs, REQUIRED_VERSION_FIELDS),
+            vec!["keywords", "supportUrl"]
+        );
+    }
+
+    #[test]
+    fn promo_text_body_only_sets_promotional_text() {
+        let body = promo_text_body("loc-1", "New levels every week").unwrap();
+        let attrs = body["data"]["attributes"].as_object().unwrap();
+        assert_eq!(attrs.keys().collect::<Vec<_>>(), ["promotionalText"]);
+        assert!(promo_text_body("loc-1", &"x".repeat(171)).is_err());
+    }
+
+    #[test]
+    fn whats_new_reads_single_file_or_per_locale_directory() {
+        let dir = std::env::temp_dir().join(format!("storeops-whats-new-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("de-DE")).unwrap();
+        std::fs::write(dir.join("en-US.txt"), "Bug fixes\n").unwrap();
+        std::fs::write(dir.join("de-DE/release_notes.txt"), "Fehlerbehebungen").unwrap();
+
+        let single = whats_new_for_locale(&dir.join("en-US.txt"), "ja").unwrap();
+        let en = whats_new_for_locale(&dir, "en-US").unwrap();
+        let de = whats_new_for_locale(&dir, "de-DE").unwrap();
+        let fr = whats_new_for_locale(&dir, "fr-FR").unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(single.as_deref(), Some("Bug fixes"));
+        assert_eq!(en.as_deref(), Some("Bug fixes"));
+        assert_eq!(de.as_deref(), Some("Fehlerbehebungen"));
+        assert_eq!(fr, None);
+    }
+
+    #[test]
+    fn locale_coverage_flags_locales_missing_from_one_side() {
+        let versions = json!({"data": [
+            {"attributes": {"locale": "en-US", "description": "d", "keywords": "k", "supportUrl": "u"}},
+            {"attributes": {"locale": "de-DE", "description": "d"}}
+        ]});
+        let infos = json!({"data": [
+            {"attributes": {"locale": "en-US", "name": "App"}},
+            {"attributes": {"locale": "fr-FR", "name": "App"}}
+        ]});
+        let report = locale_coverage(&[
+            (&versions, REQUIRED_VERSION_FIELDS),
+            (&infos, REQUIRED_APP_INFO_FIELDS),
+        ]);
+        assert!(report["en-US"].is_empty());
+        assert_eq!(report["de-DE"], vec!["keywords", "supportUrl", "name"]);
+        assert_eq!(
+            report["fr-FR"],
+            vec!["description", "keywords", "supportUrl"]
+        );
+    }
+}
@@ -1,19 +1,30 @@
 pub mod age_rating;
 pub mod analytics;
+pub mod app_info_stage;
 pub mod apps;
 pub mod availability;
 pub mod builds;
 pub mod devices;
+pub mod display_types;
+pub mod distribution;
 pub mod iap;
+pub mod keywords;
 pub mod metadata;
 pub mod phased_release;
+pub mod platform;
+pub mod preorder;
 pub mod previews;
+pub mod price_matrix;
 pub mod pricing;
+pub mod privacy;
+pub mod remediate;
+pub mod resolve;
 pub mod reviews;
 pub mod screenshots;
 pub mod submit;
 pub mod subscriptions;
 pub mod sync;
+pub mod teams;
 pub mod testflight;
 pub mod versions;
 
@@ -42,12 +53,26 @@ pub enum AppleCommand {
         command: testflight::TestflightCommand,
     },
     /// Submit for review
+    ///
+    /// Examples:
+    ///   storeops apple submit com.example.app --version 1.4.0
+    ///   storeops apple submit com.example.app --version 1.4.0 --include-iaps coins.100,remove_ads
+    ///   storeops apple submit review-details com.example.app@1.4.0 --notes "Use the demo account"
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Submit {
-        /// App ID
-        app_id: String,
+        #[command(subcommand)]
+        command: Option<submit::SubmitCommand>,
+        /// App ID or bundle ID
+        app_id: Option<String>,
         /// Version string
-        #[arg(long)]
-        version: String,
+        #[arg(long, required = true)]
+        version: Option<String>,
+        /// In-app purchases to submit with the version (IDs or product IDs, comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        include_iaps: Vec<String>,
+        /// Subscriptions to submit with the version (IDs or product IDs, comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        include_subscriptions: Vec<String>,
     },
     /// Customer reviews
     Reviews {
@@ -84,6 +109,11 @@ pub enum AppleCommand {
         #[command(subcommand)]
         command: pricing::PricingCommand,
     },
+    /// Manage app privacy details (nutrition labels)
+    Privacy {
+        #[command(subcommand)]
+        command: privacy::PrivacyCommand,
+    },
     /// Manage age rating declarations
     AgeRating {
         #[command(subcommand)]
@@ -94,6 +124,16 @@ pub enum AppleCommand {
         #[command(subcommand)]
         command: phased_release::PhasedReleaseCommand,
     },
+    /// Alternative distribution in the EU (packages, keys, marketplace search details)
+    Distribution {
+        #[command(subcommand)]
+        command: distribution::DistributionCommand,
+    },
+    /// Manage pre-orders and their expected release date
+    Preorder {
+        #[command(subcommand)]
+        command: preorder::PreorderCommand,
+    },
     /// Manage in-app purchases
     Iap {
         #[command(subcommand)]
@@ -114,17 +154,94 @@ pub enum AppleCommand {
         #[command(subcommand)]
         command: sync::SyncCommand,
     },
+    /// Show the team of the current API key and the apps it can reach
+    ///
+    /// Keys only see their own team's apps. Use this when a command works locally but
+    /// returns 403 elsewhere: it shows which key is in use (profile or
+    /// STOREOPS_APPLE_* variables), the teams (issuer IDs) configured across
+    /// profiles, and the apps visible to the key. With `--app` it fails when that app
+    /// is not visible.
+    ///
+    /// Examples:
+    ///   storeops apple teams
+    ///   storeops apple teams --app com.example.app
+    Teams(teams::TeamsArgs),
+    /// Send a raw App Store Connect API request with the profile's credentials
+    ///
+    /// Paths are relative to https://api.appstoreconnect.apple.com/v1 unless they
+    /// start with another version such as /v2/.
+    ///
+    /// Examples:
+    ///   storeops apple api GET /apps --query filter[bundleId]=com.example.app
+    ///   storeops apple api PATCH /appStoreVersions/123 --body-file req.json --dry-run
+    Api(crate::cli::api_request::ApiArgs),
+}
+
+/// Build an authenticated App Store Connect client for the selected profile.
+pub fn client(
+    cli: &crate::cli::Cli,
+) -> Result<crate::api::apple_client::AppleClient, Box<dyn std::error::Error>> {
+    client_for_profile(cli.profile.as_deref())
+}
+
+/// Build an authenticated App Store Connect client for a named (or the active) profile.
+pub fn client_for_profile(
+    profile: Option<&str>,
+) -> Result<crate::api::apple_client::AppleClient, Box<dyn std::error::Error>> {
+    let config = crate::config::Config::load()?;
+    let defaults = config.profile_defaults(profile);
+    if crate::api::recording::is_replaying() {
+        return Ok(crate::api::apple_client::AppleClient::new(String::new())
+            .with_default_app(defaults.default_app_id));
+    }
+    let (key_id, issuer_id, key_pem) =
+        crate::auth::store::resolve_apple_credentials(&config, profile)?;
+    let cache_key = format!("apple:{key_id}:{issuer_id}");
+    let token = match crate::api::session::cached_token(&cache_key) {
+        Some(token) => token,
+        None => {
+            let token = crate::auth::apple::generate_token(&key_id, &issuer_id, &key_pem)?;
+            crate::api::session::store_token(
+                &cache_key,
+                &token,
+                crate::auth::apple::TOKEN_LIFETIME,
+            );
+            token
+        }
+    };
+    Ok(
+        crate::api::apple_client::AppleClient::with_client(crate::api::session::http(), token)
+            .with_default_app(defaults.default_app_id),
+    )
 }
 
 pub async fn execute(
     cmd: &AppleCommand,
     cli: &crate::cli::Cli,
 ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
-    let config = crate::config::Config::load()?;
-    let (key_id, issuer_id, key_pem) =
-        crate::auth::store::resolve_apple_credentials(&config, cli.profile.as_deref())?;
-    let token = crate::auth::apple::generate_token(&key_id, &issuer_id, &key_pem)?;
-    let client = crate::api::apple_client::AppleClient::new(token);
+    if let AppleCommand::Api(args) = cmd {
+        if args.dry_run {
+            return args.preview(crate::cli::api_request::Api::Apple);
+        }
+    }
+    if let AppleCommand::Metadata {
+        command:
+            metadata::MetadataCommand::Keywords {
+                command:
+                    metadata::KeywordsCommand::Analyze {
+                        locale,
+                        metadata_dir: Some(dir),
+                        ..
+                    },
+            },
+    } = cmd
+    {
+        return Ok(keywords::analyze(
+            locale,
+            &keywords::load_local(dir, locale)?,
+        ));
+    }
+    let client = client(cli)?;
 
     match cmd {
         AppleCommand::Apps { command } => apps::handle(command, &client, cli.limit).await,
@@ -133,7 +250,28 @@ pub async fn execute(
         AppleCommand::Testflight { command } => {
             testflight::handle(command, &client, cli.limit).await
         }
-        AppleCommand::Submit { app_id, version } => submit::handle(app_id, version, &client).await,
+        AppleCommand::Submit {
+            command: Some(command),
+            ..
+        } => submit::handle_command(command, &client).await,
+        AppleCommand::Submit {
+            app_id,
+            version,
+            include_iaps,
+            include_subscriptions,
+            ..
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), &client).await?;
+            let version = version.as_deref().ok_or("--version is required")?;
+            submit::handle(
+                app_id,
+                version,
+                include_iaps,
+                include_subscriptions,
+                &client,
+            )
+            .await
+        }
         AppleCommand::Reviews { command } => reviews::handle(command, &client, cli.limit).await,
         AppleCommand::Devices { command } => devices::handle(command, &client, cli.limit).await,
         AppleCommand::Analytics { command } => analytics::handle(command, &client).await,
@@ -143,8 +281,13 @@ pub async fn execute(
         }
         AppleCommand::Previews { command } => previews::handle(command, &client, cli.limit).await,
         AppleCommand::Pricing { command } => pricing::handle(command, &client, cli.limit).await,
+        AppleCommand::Privacy { command } => privacy::handle(command, &client).await,
         AppleCommand::AgeRating { command } => age_rating::handle(command, &client).await,
         AppleCommand::PhasedRelease { command } => phased_release::handle(command, &client).await,
+        AppleCommand::Distribution { command } => {
+            distribution::handle(command, &client, cli.limit).await
+        }
+        AppleCommand::Preorder { command } => preorder::handle(command, &client).await,
         AppleCommand::Iap { command } => iap::handle(command, &client, cli.limit).await,
         AppleCommand::Subscriptions { command } => {
             subscriptions::handle(command, &client, cli.limit).await
@@ -153,5 +296,17 @@ pub async fn execute(
             availability::handle(command, &client, cli.limit).await
         }
         AppleCommand::Sync { command } => sync::handle(command, &client, cli.limit).await,
+        AppleCommand::Teams(args) => teams::handle(args, &client, cli.profile.as_deref()).await,
+        AppleCommand::Api(args) => {
+            let body = args.validate(crate::cli::api_request::Api::Apple)?;
+            client
+                .request(
+                    args.method.as_reqwest(),
+                    &args.path,
+                    &args.query,
+                    body.as_ref(),
+                )
+                .await
+        }
     }
 }
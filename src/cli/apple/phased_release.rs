@@ -1,18 +1,19 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
 
+use super::resolve;
 use crate::api::apple_client::AppleClient;
 
 #[derive(Subcommand)]
 pub enum PhasedReleaseCommand {
     /// Get phased release status for a version
     Get {
-        /// App Store Version ID
+        /// App Store version ID or bundle[@version]
         version_id: String,
     },
     /// Enable phased release for a version
     Create {
-        /// App Store Version ID
+        /// App Store version ID or bundle[@version]
         version_id: String,
     },
     /// Update phased release state (ACTIVE, PAUSE, COMPLETE)
@@ -28,6 +29,53 @@ pub enum PhasedReleaseCommand {
         /// Phased Release ID
         release_id: String,
     },
+    /// Poll phased release progress until it completes
+    ///
+    /// With --max-crash-rate, each poll also reads the version's crash rate from the
+    /// App Crashes and App Sessions analytics reports (see `analytics crash-free`);
+    /// when it passes the limit the rollout is paused and the command exits with an
+    /// error, so a scheduler or CI job can notify. A paused rollout can be resumed with
+    /// `phased-release update --state ACTIVE`.
+    ///
+    /// Examples:
+    ///   storeops apple phased-release monitor VERSION_ID --interval 3600
+    ///   storeops apple phased-release monitor VERSION_ID --max-crash-rate 2
+    ///   storeops apple phased-release monitor VERSION_ID --complete-at-day 4
+    Monitor {
+        /// App Store version ID or bundle[@version]
+        version_id: String,
+        /// Seconds between polls
+        #[arg(long, default_value = "3600")]
+        interval: u64,
+        /// Stop after this many polls (default: until COMPLETE)
+        #[arg(long)]
+        max_polls: Option<u32>,
+        /// Pause the rollout when the version's crash rate passes this percentage
+        /// of active device-days
+        #[arg(long)]
+        max_crash_rate: Option<f64>,
+        /// Days of daily analytics reports the crash rate is computed over
+        #[arg(long, default_value = "3")]
+        crash_days: u32,
+        /// Release to all users once the rollout reaches this day (1-7)
+        #[arg(long)]
+        complete_at_day: Option<u64>,
+    },
+}
+
+/// Percentage of users receiving the update on each day of a phased release.
+/// Apple's schedule is fixed: 1%, 2%, 5%, 10%, 20%, 50%, 100%.
+fn rollout_percentage(day: u64) -> u64 {
+    match day {
+        0 => 0,
+        1 => 1,
+        2 => 2,
+        3 => 5,
+        4 => 10,
+        5 => 20,
+        6 => 50,
+        _ => 100,
+    }
 }
 
 pub async fn handle(
@@ -36,6 +84,7 @@ pub async fn handle(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         PhasedReleaseCommand::Get { version_id } => {
+            let version_id = &resolve::version_id(version_id, client).await?;
             client
                 .get::<Value>(
                     &format!("/appStoreVersions/{version_id}/appStoreVersionPhasedRelease"),
@@ -44,6 +93,7 @@ pub async fn handle(
                 .await
         }
         PhasedReleaseCommand::Create { version_id } => {
+            let version_id = &resolve::version_id(version_id, client).await?;
             let body = json!({
                 "data": {
                     "type": "appStoreVersionPhasedReleases",
@@ -63,26 +113,213 @@ pub async fn handle(
             client.post("/appStoreVersionPhasedReleases", &body).await
         }
         PhasedReleaseCommand::Update { release_id, state } => {
-            let body = json!({
-                "data": {
-                    "type": "appStoreVersionPhasedReleases",
-                    "id": release_id,
-                    "attributes": {
-                        "phasedReleaseState": state
-                    }
-                }
-            });
-            client
-                .patch(
-                    &format!("/appStoreVersionPhasedReleases/{release_id}"),
-                    &body,
-                )
-                .await
+            set_state(release_id, state, client).await
         }
         PhasedReleaseCommand::Delete { release_id } => {
             client
                 .delete(&format!("/appStoreVersionPhasedReleases/{release_id}"))
                 .await
         }
+        PhasedReleaseCommand::Monitor {
+            version_id,
+            interval,
+            max_polls,
+            max_crash_rate,
+            crash_days,
+            complete_at_day,
+        } => {
+            let version_id = &resolve::version_id(version_id, client).await?;
+            let guard = match max_crash_rate {
+                Some(limit) => {
+                    Some(CrashGuard::new(version_id, *limit, *crash_days, client).await?)
+                }
+                None => None,
+            };
+            monitor(
+                version_id,
+                *interval,
+                *max_polls,
+                guard.as_ref(),
+                *complete_at_day,
+                client,
+            )
+            .await
+        }
+    }
+}
+
+/// Crash-rate limit the monitor pauses the rollout at.
+struct CrashGuard {
+    app_id: String,
+    version: String,
+    limit: f64,
+    days: u32,
+}
+
+impl CrashGuard {
+    async fn new(
+        version_id: &str,
+        limit: f64,
+        days: u32,
+        client: &AppleClient,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let version: Value = client
+            .get(
+                &format!("/appStoreVersions/{version_id}"),
+                &[("include", "app")],
+            )
+            .await?;
+        let data = &version["data"];
+        Ok(CrashGuard {
+            app_id: data["relationships"]["app"]["data"]["id"]
+                .as_str()
+                .ok_or("version has no app")?
+                .to_string(),
+            version: data["attributes"]["versionString"]
+                .as_str()
+                .ok_or("version has no version string")?
+                .to_string(),
+            limit,
+            days,
+        })
+    }
+}
+
+/// Set the phased release state (ACTIVE, PAUSE or COMPLETE).
+async fn set_state(
+    release_id: &str,
+    state: &str,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let body = json!({
+        "data": {
+            "type": "appStoreVersionPhasedReleases",
+            "id": release_id,
+            "attributes": {
+                "phasedReleaseState": state
+            }
+        }
+    });
+    client
+        .patch(
+            &format!("/appStoreVersionPhasedReleases/{release_id}"),
+            &body,
+        )
+        .await
+}
+
+async fn monitor(
+    version_id: &str,
+    interval: u64,
+    max_polls: Option<u32>,
+    guard: Option<&CrashGuard>,
+    complete_at_day: Option<u64>,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut snapshots = Vec::new();
+    let mut polls = 0u32;
+
+    loop {
+        let release: Value = client
+            .get(
+                &format!("/appStoreVersions/{version_id}/appStoreVersionPhasedRelease"),
+                &[],
+            )
+            .await?;
+        let release_id = release["data"]["id"]
+            .as_str()
+            .ok_or("no phased release found for this version")?
+            .to_string();
+        let attrs = &release["data"]["attributes"];
+        let mut state = attrs["phasedReleaseState"]
+            .as_str()
+            .unwrap_or("UNKNOWN")
+            .to_string();
+        let day = attrs["currentDayNumber"].as_u64().unwrap_or(0);
+        let percentage = if state == "COMPLETE" {
+            100
+        } else {
+            rollout_percentage(day)
+        };
+        eprintln!("Phased release {release_id}: {state}, day {day} ({percentage}% of users)");
+
+        let mut action = None;
+        let mut crash_rate = None;
+        if let Some(guard) = guard.filter(|_| state == "ACTIVE") {
+            crash_rate =
+                super::analytics::crash_rate(&guard.app_id, &guard.version, guard.days, client)
+                    .await?;
+            if let Some(rate) = crash_rate.filter(|rate| *rate > guard.limit) {
+                eprintln!(
+                    "Crash rate {rate}% is above {}%, pausing the rollout",
+                    guard.limit
+                );
+                set_state(&release_id, "PAUSE", client).await?;
+                state = "PAUSE".to_string();
+                action = Some("paused");
+            }
+        }
+        if let Some(target) = complete_at_day {
+            if state == "ACTIVE" && day >= target {
+                eprintln!("Reached day {day}, releasing to all users");
+                set_state(&release_id, "COMPLETE", client).await?;
+                state = "COMPLETE".to_string();
+                action = Some("completed");
+            }
+        }
+
+        snapshots.push(json!({
+            "polled_at": chrono::Utc::now().to_rfc3339(),
+            "release_id": release_id,
+            "state": state,
+            "current_day": day,
+            "percentage": percentage,
+            "crash_rate": crash_rate,
+            "action": action,
+        }));
+
+        polls += 1;
+        if action == Some("paused") {
+            let report = json!({
+                "version_id": version_id,
+                "polls": polls,
+                "snapshots": snapshots,
+                "error": format!(
+                    "crash rate {}% is above {}%; the rollout was paused",
+                    crash_rate.unwrap_or_default(),
+                    guard.map(|g| g.limit).unwrap_or_default()
+                ),
+            });
+            return Err(Box::new(crate::output::Failure(report)));
+        }
+        if state == "COMPLETE" || max_polls.is_some_and(|m| polls >= m) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+    }
+
+    Ok(json!({
+        "version_id": version_id,
+        "polls": polls,
+        "snapshots": snapshots,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rollout_percentage_follows_apple_schedule() {
+        assert_eq!(rollout_percentage(1), 1);
+        assert_eq!(rollout_percentage(3), 5);
+        assert_eq!(rollout_percentage(6), 50);
+        assert_eq!(rollout_percentage(7), 100);
+    }
+
+    #[test]
+    fn rollout_percentage_caps_at_full_release() {
+        assert_eq!(rollout_percentage(0), 0);
+        assert_eq!(rollout_percentage(30), 100);
     }
 }
@@ -0,0 +1,69 @@
+//! `--platform`: which of an app's platforms a command works on.
+//!
+//! An app can ship on several platforms, each with its own App Store versions,
+//! builds and screenshot display types. Commands default to iOS when creating and
+//! to any platform when reading.
+
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Platform {
+    /// iOS and iPadOS (including watchOS and iMessage screenshots)
+    Ios,
+    /// macOS
+    #[value(alias = "mac_os")]
+    Macos,
+    /// tvOS
+    #[value(alias = "tv_os")]
+    Tvos,
+    /// visionOS
+    #[value(alias = "vision_os")]
+    Visionos,
+}
+
+impl Platform {
+    /// Value of the `platform` attribute and filters in the API.
+    pub fn api_value(self) -> &'static str {
+        match self {
+            Platform::Ios => "IOS",
+            Platform::Macos => "MAC_OS",
+            Platform::Tvos => "TV_OS",
+            Platform::Visionos => "VISION_OS",
+        }
+    }
+
+    /// The platform a screenshot display type belongs to.
+    pub fn of_display_type(display_type: &str) -> Self {
+        match display_type {
+            "APP_DESKTOP" => Platform::Macos,
+            "APP_APPLE_TV" => Platform::Tvos,
+            "APP_APPLE_VISION_PRO" => Platform::Visionos,
+            _ => Platform::Ios,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_names_and_display_types() {
+        for (name, api) in [
+            ("ios", "IOS"),
+            ("macos", "MAC_OS"),
+            ("mac_os", "MAC_OS"),
+            ("tvos", "TV_OS"),
+            ("vision_os", "VISION_OS"),
+        ] {
+            assert_eq!(Platform::from_str(name, false).unwrap().api_value(), api);
+        }
+        assert_eq!(Platform::of_display_type("APP_DESKTOP"), Platform::Macos);
+        assert_eq!(Platform::of_display_type("APP_APPLE_TV"), Platform::Tvos);
+        assert_eq!(
+            Platform::of_display_type("APP_APPLE_VISION_PRO"),
+            Platform::Visionos
+        );
+        assert_eq!(Platform::of_display_type("APP_WATCH_ULTRA"), Platform::Ios);
+    }
+}
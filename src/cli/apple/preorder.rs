@@ -0,0 +1,134 @@
+use chrono::NaiveDate;
+use clap::Subcommand;
+use serde_json::{json, Value};
+
+use super::resolve;
+use crate::api::apple_client::AppleClient;
+
+#[derive(Subcommand)]
+pub enum PreorderCommand {
+    /// Show an app's pre-order and its expected release date
+    Get {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+    },
+    /// Make an app available for pre-order
+    ///
+    /// Examples:
+    ///   storeops apple preorder enable com.example.app --release-date 2026-12-01
+    Enable {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Expected release date (YYYY-MM-DD)
+        #[arg(long)]
+        release_date: NaiveDate,
+    },
+    /// Change the expected release date of a pre-order
+    Update {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// New expected release date (YYYY-MM-DD)
+        #[arg(long)]
+        release_date: NaiveDate,
+    },
+    /// Cancel a pre-order
+    Cancel {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+    },
+}
+
+/// Apple only accepts release dates after today.
+fn check_release_date(date: NaiveDate, today: NaiveDate) -> Result<String, String> {
+    if date <= today {
+        return Err(format!("release date {date} must be after today ({today})"));
+    }
+    Ok(date.format("%Y-%m-%d").to_string())
+}
+
+/// ID of the app's pre-order, if it has one.
+async fn preorder_id(
+    app_id: &str,
+    client: &AppleClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let preorder: Value = client.get(&format!("/apps/{app_id}/preOrder"), &[]).await?;
+    Ok(preorder["data"]["id"]
+        .as_str()
+        .ok_or("app is not available for pre-order")?
+        .to_string())
+}
+
+pub async fn handle(
+    cmd: &PreorderCommand,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let today = chrono::Utc::now().date_naive();
+    match cmd {
+        PreorderCommand::Get { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            client
+                .get::<Value>(&format!("/apps/{app_id}/preOrder"), &[])
+                .await
+        }
+        PreorderCommand::Enable {
+            app_id,
+            release_date,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let release_date = check_release_date(*release_date, today)?;
+            let body = json!({
+                "data": {
+                    "type": "appPreOrders",
+                    "attributes": {
+                        "appReleaseDate": release_date
+                    },
+                    "relationships": {
+                        "app": {
+                            "data": {
+                                "type": "apps",
+                                "id": app_id
+                            }
+                        }
+                    }
+                }
+            });
+            client.post("/appPreOrders", &body).await
+        }
+        PreorderCommand::Update {
+            app_id,
+            release_date,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let release_date = check_release_date(*release_date, today)?;
+            let id = preorder_id(app_id, client).await?;
+            let body = json!({
+                "data": {
+                    "type": "appPreOrders",
+                    "id": id,
+                    "attributes": {
+                        "appReleaseDate": release_date
+                    }
+                }
+            });
+            client.patch(&format!("/appPreOrders/{id}"), &body).await
+        }
+        PreorderCommand::Cancel { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let id = preorder_id(app_id, client).await?;
+            client.delete(&format!("/appPreOrders/{id}")).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_date_must_be_in_the_future() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let tomorrow = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        assert_eq!(check_release_date(tomorrow, today).unwrap(), "2026-10-17");
+        assert!(check_release_date(today, today).is_err());
+    }
+}
@@ -1,7 +1,10 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
+use std::path::Path;
 
+use super::resolve;
 use crate::api::apple_client::AppleClient;
+use crate::api::asset_upload::{self, Asset, UploadSource};
 
 #[derive(Subcommand)]
 pub enum PreviewsCommand {
@@ -21,12 +24,12 @@ pub enum PreviewsCommand {
 pub enum PreviewSetsCommand {
     /// List app preview sets for a localization
     List {
-        /// App Store Version Localization ID
+        /// App Store version localization ID or bundle[@version]/locale
         localization_id: String,
     },
     /// Create a preview set
     Create {
-        /// App Store Version Localization ID
+        /// App Store version localization ID or bundle[@version]/locale
         localization_id: String,
         /// Preview type (e.g., IPHONE_67, IPAD_PRO_129)
         #[arg(long)]
@@ -85,6 +88,7 @@ async fn handle_sets(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         PreviewSetsCommand::List { localization_id } => {
+            let localization_id = &resolve::localization_id(localization_id, client).await?;
             let limit_str = limit.unwrap_or(50).to_string();
             client
                 .get(
@@ -97,6 +101,7 @@ async fn handle_sets(
             localization_id,
             preview_type,
         } => {
+            let localization_id = &resolve::localization_id(localization_id, client).await?;
             let body = json!({
                 "data": {
                     "type": "appPreviewSets",
@@ -137,68 +142,20 @@ async fn handle_videos(
             filename,
             mime_type,
         } => {
-            let file_size = tokio::fs::metadata(file).await?.len();
-            let reservation = json!({
-                "data": {
-                    "type": "appPreviews",
-                    "attributes": {
-                        "fileName": filename,
-                        "fileSize": file_size,
-                        "mimeType": mime_type
-                    },
-                    "relationships": {
-                        "appPreviewSet": {
-                            "data": {
-                                "type": "appPreviewSets",
-                                "id": set_id
-                            }
-                        }
-                    }
-                }
-            });
-            let reserved: Value = client.post("/appPreviews", &reservation).await?;
-            let preview_id = reserved["data"]["id"]
-                .as_str()
-                .ok_or("no preview id in reservation response")?;
-
-            let upload_ops = &reserved["data"]["attributes"]["uploadOperations"];
-            let file_bytes = tokio::fs::read(file).await?;
-
-            if let Some(ops) = upload_ops.as_array() {
-                for op in ops {
-                    let url = op["url"].as_str().ok_or("missing upload url")?;
-                    let offset = op["offset"].as_u64().unwrap_or(0) as usize;
-                    let length = op["length"].as_u64().unwrap_or(file_bytes.len() as u64) as usize;
-                    let chunk =
-                        &file_bytes[offset..std::cmp::min(offset + length, file_bytes.len())];
-
-                    let mut req = reqwest::Client::new().put(url);
-                    if let Some(headers) = op["requestHeaders"].as_array() {
-                        for h in headers {
-                            if let (Some(name), Some(value)) =
-                                (h["name"].as_str(), h["value"].as_str())
-                            {
-                                req = req.header(name, value);
-                            }
-                        }
-                    }
-                    req.body(chunk.to_vec()).send().await?;
-                }
-            }
-
-            let commit_body = json!({
-                "data": {
-                    "type": "appPreviews",
-                    "id": preview_id,
-                    "attributes": {
-                        "uploaded": true,
-                        "sourceFileChecksum": reserved["data"]["attributes"]["sourceFileChecksum"]
-                    }
-                }
-            });
-            client
-                .patch(&format!("/appPreviews/{preview_id}"), &commit_body)
-                .await
+            // Videos are streamed from disk range by range rather than read whole
+            let asset = asset_upload::upload(
+                client,
+                Asset {
+                    kind: "appPreviews",
+                    file_name: filename,
+                    source: UploadSource::File(Path::new(file)),
+                    attributes: json!({ "mimeType": mime_type }),
+                    parent: ("appPreviewSet", "appPreviewSets", set_id),
+                },
+            )
+            .await?;
+            super::screenshots::verify_delivery(client, &asset.path, &asset.checksum).await?;
+            client.get::<Value>(&asset.path, &[]).await
         }
         PreviewVideosCommand::Delete { preview_id } => {
             client.delete(&format!("/appPreviews/{preview_id}")).await
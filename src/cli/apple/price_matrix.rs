@@ -0,0 +1,624 @@
+//! Price matrix export/import for in-app purchases and subscriptions.
+//!
+//! The matrix is a CSV with one row per product (`product_id`) and one column per
+//! territory, each cell holding the customer price in that territory's currency.
+//! Export writes the prices in effect today. Import compares each cell with the
+//! current price, resolves changed cells to price points, and applies them; a cell
+//! that cannot be resolved or applied is reported without stopping the run.
+//!
+//! `apple pricing diff` plans and applies the app's own price the same way, from a
+//! `territory,customer_price` file.
+
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use super::pricing::{all_pages, price_rows};
+use crate::api::apple_client::AppleClient;
+use crate::output::csv;
+
+#[derive(Clone, Copy)]
+pub enum Product {
+    /// The app itself, keyed by its app ID
+    App,
+    Iap,
+    Subscription,
+}
+
+/// Current price of one product in one territory.
+pub(super) struct Current {
+    price: String,
+    manual: bool,
+    point: Option<String>,
+}
+
+/// A changed cell resolved to a price point.
+pub(super) struct Change {
+    pub territory: String,
+    pub from: Option<String>,
+    pub to: String,
+    pub point: String,
+}
+
+/// Planned price changes of one product.
+pub(super) struct Plan {
+    pub current: BTreeMap<String, Current>,
+    pub changes: Vec<Change>,
+    pub unchanged: usize,
+    /// `(territory, error)` of prices that match no price point
+    pub unresolved: Vec<(String, String)>,
+}
+
+impl Product {
+    fn point_rel(self) -> &'static str {
+        match self {
+            Product::App => "appPricePoint",
+            Product::Iap => "inAppPurchasePricePoint",
+            Product::Subscription => "subscriptionPricePoint",
+        }
+    }
+
+    /// `(resource ID, productId)` of every product of this kind in the app.
+    async fn list(
+        self,
+        app_id: &str,
+        client: &AppleClient,
+    ) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+        let resources = match self {
+            Product::App => return Ok(vec![(app_id.to_string(), app_id.to_string())]),
+            Product::Iap => {
+                all_pages(
+                    &format!("/apps/{app_id}/inAppPurchasesV2"),
+                    &[("limit", "200")],
+                    client,
+                )
+                .await?
+                .0
+            }
+            Product::Subscription => {
+                let (groups, _) = all_pages(
+                    &format!("/apps/{app_id}/subscriptionGroups"),
+                    &[("limit", "200")],
+                    client,
+                )
+                .await?;
+                let mut subs = Vec::new();
+                for group in &groups {
+                    let group_id = group["id"].as_str().unwrap_or_default();
+                    let (data, _) = all_pages(
+                        &format!("/subscriptionGroups/{group_id}/subscriptions"),
+                        &[("limit", "200")],
+                        client,
+                    )
+                    .await?;
+                    subs.extend(data);
+                }
+                subs
+            }
+        };
+        Ok(resources
+            .iter()
+            .filter_map(|r| {
+                Some((
+                    r["id"].as_str()?.to_string(),
+                    r["attributes"]["productId"].as_str()?.to_string(),
+                ))
+            })
+            .collect())
+    }
+
+    /// Prices in effect on `date`, keyed by territory.
+    async fn current(
+        self,
+        id: &str,
+        date: &str,
+        client: &AppleClient,
+    ) -> Result<BTreeMap<String, Current>, Box<dyn std::error::Error>> {
+        let include = format!("{},territory", self.point_rel());
+        let query = [("include", include.as_str()), ("limit", "200")];
+        let paths = match self {
+            Product::App => vec![
+                format!("/appPriceSchedules/{id}/manualPrices"),
+                format!("/appPriceSchedules/{id}/automaticPrices"),
+            ],
+            Product::Iap => vec![
+                format!("/inAppPurchasePriceSchedules/{id}/manualPrices"),
+                format!("/inAppPurchasePriceSchedules/{id}/automaticPrices"),
+            ],
+            Product::Subscription => vec![format!("/subscriptions/{id}/prices")],
+        };
+        let mut prices = Vec::new();
+        let mut included = Vec::new();
+        for path in paths {
+            let (data, inc) = all_pages(&path, &query, client).await?;
+            prices.extend(data);
+            included.extend(inc);
+        }
+        Ok(price_rows(&prices, &included, date, self.point_rel())
+            .into_iter()
+            .filter_map(|row| {
+                Some((
+                    row["territory"].as_str()?.to_string(),
+                    Current {
+                        price: row["customer_price"].as_str()?.to_string(),
+                        manual: row["manual"].as_bool().unwrap_or(false),
+                        point: row["price_point"].as_str().map(String::from),
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    /// Price point for `price` in `territory`, if one exists.
+    async fn find_point(
+        self,
+        id: &str,
+        territory: &str,
+        price: &str,
+        client: &AppleClient,
+    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let path = match self {
+            Product::App => format!("/apps/{id}/appPricePoints"),
+            Product::Iap => format!("/inAppPurchasesV2/{id}/pricePoints"),
+            Product::Subscription => format!("/subscriptions/{id}/pricePoints"),
+        };
+        let (points, _) = all_pages(
+            &path,
+            &[("filter[territory]", territory), ("limit", "200")],
+            client,
+        )
+        .await?;
+        Ok(points
+            .iter()
+            .find(|p| {
+                p["attributes"]["customerPrice"]
+                    .as_str()
+                    .is_some_and(|c| same_price(c, price))
+            })
+            .and_then(|p| p["id"].as_str())
+            .map(String::from))
+    }
+
+    /// Compare `cells` (territory → price) with the prices in effect on `date` and
+    /// resolve the changed ones to price points.
+    pub(super) async fn plan(
+        self,
+        id: &str,
+        cells: &BTreeMap<String, String>,
+        date: &str,
+        client: &AppleClient,
+    ) -> Result<Plan, Box<dyn std::error::Error>> {
+        let current = self.current(id, date, client).await?;
+        let mut changes = Vec::new();
+        let mut unchanged = 0;
+        let mut unresolved = Vec::new();
+        for (territory, price) in cells {
+            let from = current.get(territory).map(|c| c.price.clone());
+            if from.as_deref().is_some_and(|f| same_price(f, price)) {
+                unchanged += 1;
+                continue;
+            }
+            match self.find_point(id, territory, price, client).await? {
+                Some(point) => changes.push(Change {
+                    territory: territory.clone(),
+                    from,
+                    to: price.clone(),
+                    point,
+                }),
+                None => unresolved.push((
+                    territory.clone(),
+                    format!("no price point for {price} in {territory}"),
+                )),
+            }
+        }
+        Ok(Plan {
+            current,
+            changes,
+            unchanged,
+            unresolved,
+        })
+    }
+
+    /// Apply a product's resolved changes, returning per-cell errors.
+    pub(super) async fn apply(
+        self,
+        id: &str,
+        current: &BTreeMap<String, Current>,
+        changes: &[Change],
+        client: &AppleClient,
+    ) -> Vec<(String, String)> {
+        match self {
+            Product::App | Product::Iap => {
+                match replace_schedule(self, id, current, changes, client).await {
+                    Ok(()) => Vec::new(),
+                    Err(e) => changes
+                        .iter()
+                        .map(|c| (c.territory.clone(), e.to_string()))
+                        .collect(),
+                }
+            }
+            Product::Subscription => {
+                let mut errors = Vec::new();
+                for change in changes {
+                    let body = json!({
+                        "data": {
+                            "type": "subscriptionPrices",
+                            "attributes": { "preserveCurrentPrice": false },
+                            "relationships": {
+                                "subscription": { "data": { "type": "subscriptions", "id": id } },
+                                "subscriptionPricePoint": {
+                                    "data": { "type": "subscriptionPricePoints", "id": change.point }
+                                },
+                                "territory": {
+                                    "data": { "type": "territories", "id": change.territory }
+                                }
+                            }
+                        }
+                    });
+                    if let Err(e) = client.post("/subscriptionPrices", &body).await {
+                        errors.push((change.territory.clone(), e.to_string()));
+                    }
+                }
+                errors
+            }
+        }
+    }
+}
+
+/// Whether two decimal price strings denote the same amount.
+fn same_price(a: &str, b: &str) -> bool {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => (a - b).abs() < 0.005,
+        _ => a.trim() == b.trim(),
+    }
+}
+
+/// `(territory, price point, start date)` of the manual prices that start after `date`.
+fn scheduled_prices(
+    prices: &[Value],
+    date: &str,
+    point_rel: &str,
+) -> Vec<(String, String, String)> {
+    prices
+        .iter()
+        .filter_map(|price| {
+            let start = price["attributes"]["startDate"].as_str()?;
+            let rel = &price["relationships"];
+            Some((
+                rel["territory"]["data"]["id"].as_str()?.to_string(),
+                rel[point_rel]["data"]["id"].as_str()?.to_string(),
+                start.to_string(),
+            ))
+        })
+        .filter(|(_, _, start)| start.as_str() > date)
+        .collect()
+}
+
+/// Replace an app's or IAP's price schedule, keeping the base territory, existing
+/// manual prices and scheduled future price changes, and adding the changed cells
+/// as manual prices. Territories left on automatic (equalized) prices stay automatic.
+async fn replace_schedule(
+    kind: Product,
+    id: &str,
+    current: &BTreeMap<String, Current>,
+    changes: &[Change],
+    client: &AppleClient,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (schedule_type, price_type, parent) = match kind {
+        Product::App => ("appPriceSchedules", "appPrices", ("app", "apps")),
+        _ => (
+            "inAppPurchasePriceSchedules",
+            "inAppPurchasePrices",
+            ("inAppPurchase", "inAppPurchases"),
+        ),
+    };
+    let point_rel = kind.point_rel();
+    let base: Value = client
+        .get(&format!("/{schedule_type}/{id}/baseTerritory"), &[])
+        .await?;
+    let base = base["data"]["id"].as_str().unwrap_or("USA").to_string();
+    // Relationship IDs are only returned for included resources
+    let include = format!("{point_rel},territory");
+    let (manual_prices, _) = all_pages(
+        &format!("/{schedule_type}/{id}/manualPrices"),
+        &[("include", include.as_str()), ("limit", "200")],
+        client,
+    )
+    .await?;
+    let today = chrono::Utc::now().date_naive().to_string();
+    let scheduled = scheduled_prices(&manual_prices, &today, point_rel);
+
+    let mut manual: BTreeMap<&str, &str> = current
+        .iter()
+        .filter(|(t, c)| c.manual || **t == base)
+        .filter_map(|(t, c)| Some((t.as_str(), c.point.as_deref()?)))
+        .collect();
+    for change in changes {
+        manual.insert(&change.territory, &change.point);
+    }
+
+    // (local ID, price point, start date); `None` starts now
+    let entries: Vec<(String, &str, Option<&str>)> = manual
+        .iter()
+        .map(|(t, point)| (format!("${{price-{t}}}"), *point, None))
+        .chain(scheduled.iter().map(|(t, point, start)| {
+            (
+                format!("${{price-{t}-{start}}}"),
+                point.as_str(),
+                Some(start.as_str()),
+            )
+        }))
+        .collect();
+    let refs: Vec<Value> = entries
+        .iter()
+        .map(|(local_id, ..)| json!({ "type": price_type, "id": local_id }))
+        .collect();
+    let included: Vec<Value> = entries
+        .iter()
+        .map(|(local_id, point, start)| {
+            let mut price = json!({
+                "type": price_type,
+                "id": local_id,
+                "attributes": { "startDate": start },
+                "relationships": {
+                    point_rel: { "data": { "type": format!("{point_rel}s"), "id": point } }
+                }
+            });
+            if let Product::Iap = kind {
+                price["relationships"]["inAppPurchaseV2"] =
+                    json!({ "data": { "type": "inAppPurchases", "id": id } });
+            }
+            price
+        })
+        .collect();
+    let body = json!({
+        "data": {
+            "type": schedule_type,
+            "relationships": {
+                parent.0: { "data": { "type": parent.1, "id": id } },
+                "baseTerritory": { "data": { "type": "territories", "id": base } },
+                "manualPrices": { "data": refs }
+            }
+        },
+        "included": included
+    });
+    client.post(&format!("/{schedule_type}"), &body).await?;
+    Ok(())
+}
+
+/// Write the current price matrix of every product of `kind` to `out`.
+pub async fn export(
+    kind: Product,
+    app_id: &str,
+    out: &Path,
+    date: &str,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let products = kind.list(app_id, client).await?;
+    let mut territories = BTreeSet::new();
+    let mut rows = Vec::new();
+    for (id, product_id) in &products {
+        eprintln!("Fetching prices for {product_id}...");
+        let current = kind.current(id, date, client).await?;
+        let mut row = json!({ "product_id": product_id });
+        for (territory, price) in current {
+            row[&territory] = json!(price.price);
+            territories.insert(territory);
+        }
+        rows.push(row);
+    }
+    let mut columns = vec!["product_id"];
+    columns.extend(territories.iter().map(String::as_str));
+    std::fs::write(out, csv::render(&rows, &columns))?;
+    Ok(json!({
+        "status": "ok",
+        "file": out.display().to_string(),
+        "products": rows.len(),
+        "territories": territories.len(),
+    }))
+}
+
+/// Prices of one product in effect today, keyed by territory.
+pub async fn prices(
+    kind: Product,
+    id: &str,
+    client: &AppleClient,
+) -> Result<BTreeMap<String, String>, Box<dyn std::error::Error>> {
+    let date = chrono::Utc::now().date_naive().to_string();
+    Ok(kind
+        .current(id, &date, client)
+        .await?
+        .into_iter()
+        .map(|(territory, current)| (territory, current.price))
+        .collect())
+}
+
+/// Set one product's prices (territory → customer price), as one row of `import`.
+///
+/// Returns the number of changed and unchanged territories and the `(territory,
+/// error)` of those that could not be set.
+pub async fn set_prices(
+    kind: Product,
+    id: &str,
+    cells: &BTreeMap<String, String>,
+    dry_run: bool,
+    client: &AppleClient,
+) -> Result<(usize, usize, Vec<(String, String)>), Box<dyn std::error::Error>> {
+    let date = chrono::Utc::now().date_naive().to_string();
+    let Plan {
+        current,
+        changes,
+        unchanged,
+        mut unresolved,
+    } = kind.plan(id, cells, &date, client).await?;
+    // An IAP price schedule is replaced as a whole, so a bad cell holds back the rest
+    if dry_run
+        || changes.is_empty()
+        || (!unresolved.is_empty() && !matches!(kind, Product::Subscription))
+    {
+        return Ok((
+            if dry_run { changes.len() } else { 0 },
+            unchanged,
+            unresolved,
+        ));
+    }
+    let failed = kind.apply(id, &current, &changes, client).await;
+    let changed = changes.len() - failed.len();
+    unresolved.extend(failed);
+    Ok((changed, unchanged, unresolved))
+}
+
+/// `(product_id, territory → price)` for one matrix row.
+type MatrixRow = (String, BTreeMap<String, String>);
+
+/// Rows of a matrix file; empty cells are dropped.
+fn parse_matrix(text: &str) -> Result<Vec<MatrixRow>, String> {
+    let mut records = csv::parse(text).into_iter();
+    let header = records.next().ok_or("price matrix is empty")?;
+    if header.first().map(|h| h.trim()) != Some("product_id") {
+        return Err("first column of the price matrix must be product_id".to_string());
+    }
+    let territories: Vec<String> = header[1..].iter().map(|t| t.trim().to_string()).collect();
+    Ok(records
+        .map(|record| {
+            let cells = territories
+                .iter()
+                .zip(record.iter().skip(1))
+                .filter(|(_, v)| !v.trim().is_empty())
+                .map(|(t, v)| (t.clone(), v.trim().to_string()))
+                .collect();
+            (record[0].trim().to_string(), cells)
+        })
+        .collect())
+}
+
+/// Apply a price matrix, reporting every changed, unchanged and failed cell.
+pub async fn import(
+    kind: Product,
+    app_id: &str,
+    file: &Path,
+    dry_run: bool,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let matrix = parse_matrix(&std::fs::read_to_string(file)?)?;
+    let date = chrono::Utc::now().date_naive().to_string();
+    let ids: HashMap<String, String> = kind
+        .list(app_id, client)
+        .await?
+        .into_iter()
+        .map(|(id, product_id)| (product_id, id))
+        .collect();
+
+    let mut changed = Vec::new();
+    let mut errors = Vec::new();
+    let mut unchanged = 0usize;
+    for (product_id, cells) in &matrix {
+        let Some(id) = ids.get(product_id) else {
+            errors.push(json!({
+                "product_id": product_id,
+                "territory": null,
+                "error": "no product with this product_id in the app",
+            }));
+            continue;
+        };
+        let Plan {
+            current,
+            changes,
+            unchanged: same,
+            unresolved,
+        } = kind.plan(id, cells, &date, client).await?;
+        unchanged += same;
+        let row_failed = !unresolved.is_empty();
+        for (territory, error) in unresolved {
+            errors.push(json!({
+                "product_id": product_id,
+                "territory": territory,
+                "error": error,
+            }));
+        }
+        // An IAP price schedule is replaced as a whole, so a bad cell holds back its row
+        if changes.is_empty() || (row_failed && !matches!(kind, Product::Subscription)) {
+            continue;
+        }
+        let failed: HashMap<String, String> = if dry_run {
+            HashMap::new()
+        } else {
+            eprintln!("Updating {} price(s) for {product_id}...", changes.len());
+            kind.apply(id, &current, &changes, client)
+                .await
+                .into_iter()
+                .collect()
+        };
+        for change in &changes {
+            match failed.get(&change.territory) {
+                Some(error) => errors.push(json!({
+                    "product_id": product_id,
+                    "territory": change.territory,
+                    "error": error,
+                })),
+                None => changed.push(json!({
+                    "product_id": product_id,
+                    "territory": change.territory,
+                    "from": change.from,
+                    "to": change.to,
+                    "price_point": change.point,
+                })),
+            }
+        }
+    }
+
+    Ok(json!({
+        "status": if dry_run { "dry_run" } else if errors.is_empty() { "ok" } else { "partial" },
+        "changed": changed,
+        "unchanged": unchanged,
+        "failed": errors,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_manual_prices_scheduled_after_today() {
+        let price = |territory: &str, point: &str, start: Value| {
+            json!({
+                "type": "appPrices",
+                "attributes": {"manual": true, "startDate": start},
+                "relationships": {
+                    "territory": {"data": {"type": "territories", "id": territory}},
+                    "appPricePoint": {"data": {"type": "appPricePoints", "id": point}}
+                }
+            })
+        };
+        let prices = [
+            price("USA", "p-now", Value::Null),
+            price("USA", "p-past", json!("2026-01-01")),
+            price("USA", "p-next", json!("2026-12-01")),
+        ];
+        assert_eq!(
+            scheduled_prices(&prices, "2026-06-01", "appPricePoint"),
+            [(
+                "USA".to_string(),
+                "p-next".to_string(),
+                "2026-12-01".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn parses_matrix_and_drops_empty_cells() {
+        let matrix = parse_matrix("product_id,USA,DEU\ncoins_100,0.99,\npro,4.99,5.49\n").unwrap();
+        assert_eq!(matrix[0].0, "coins_100");
+        assert_eq!(matrix[0].1.len(), 1);
+        assert_eq!(matrix[1].1["DEU"], "5.49");
+        assert!(parse_matrix("sku,USA\n").is_err());
+    }
+
+    #[test]
+    fn compares_prices_numerically() {
+        assert!(same_price("0.99", "0.990"));
+        assert!(same_price("1000", "1000.00"));
+        assert!(!same_price("0.99", "1.09"));
+    }
+}
@@ -1,27 +1,93 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 
+use super::price_matrix::{Plan, Product};
+use super::resolve;
+use super::reviews::next_cursor;
 use crate::api::apple_client::AppleClient;
+use crate::output::csv;
+
+/// Columns of `pricing report` rows, in CSV order.
+const REPORT_COLUMNS: &[&str] = &[
+    "territory",
+    "currency",
+    "customer_price",
+    "proceeds",
+    "manual",
+    "start_date",
+    "end_date",
+];
 
 #[derive(Subcommand)]
 pub enum PricingCommand {
     /// Get current price schedule for an app
     Get {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
     },
     /// List available price points for an app
     Points {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
         /// Filter by territory (e.g., USA, GBR)
         #[arg(long)]
         territory: Option<String>,
     },
+    /// Effective customer price and proceeds in every territory
+    ///
+    /// Joins the current price schedule's manual and automatically equalized prices
+    /// with their price points and territory currencies. Prices are in each
+    /// territory's local currency.
+    ///
+    /// Examples:
+    ///   storeops apple pricing report com.example.app
+    ///   storeops apple pricing report com.example.app --file prices.csv
+    ///   storeops apple pricing report com.example.app --date 2026-01-01
+    Report {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Also write the rows as CSV to this file
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Report prices in effect on this day instead of today (YYYY-MM-DD)
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+    },
+    /// Compare current prices against a desired-price file and plan the changes
+    ///
+    /// The file is a CSV with `territory` and `customer_price` columns (the format
+    /// `pricing report --file` writes); territories not in the file are left as
+    /// they are. Changed prices are resolved to price points; `pricing apply` sets them.
+    ///
+    /// Examples:
+    ///   storeops apple pricing diff com.example.app --against prices.csv
+    Diff {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Desired prices CSV
+        #[arg(long, visible_alias = "file")]
+        against: PathBuf,
+    },
+    /// Set the prices a `pricing diff` plans
+    ///
+    /// Sets the changed prices as manual prices, keeping the base territory and other
+    /// manual prices. Nothing is applied while any price matches no price point.
+    ///
+    /// Examples:
+    ///   storeops apple pricing apply com.example.app --against prices.csv
+    Apply {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Desired prices CSV
+        #[arg(long, visible_alias = "file")]
+        against: PathBuf,
+    },
     /// Set the base price for an app
     Set {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
         /// Price point ID
         #[arg(long)]
         price_point: String,
@@ -38,6 +104,7 @@ pub async fn handle(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         PricingCommand::Get { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             client
                 .get::<Value>(
                     &format!("/apps/{app_id}/appPriceSchedule"),
@@ -46,59 +113,376 @@ pub async fn handle(
                 .await
         }
         PricingCommand::Points { app_id, territory } => {
-            let limit_str = limit.unwrap_or(50).to_string();
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let streaming = crate::output::jsonl::enabled();
+            let limit_str = limit
+                .unwrap_or(if streaming { 200 } else { 50 })
+                .to_string();
             let mut query = vec![("limit", limit_str.as_str())];
             let territory_val;
             if let Some(t) = territory {
                 territory_val = t.clone();
                 query.push(("filter[territory]", &territory_val));
             }
-            client
-                .get(&format!("/apps/{app_id}/appPricePoints"), &query)
-                .await
+            let path = format!("/apps/{app_id}/appPricePoints");
+            if streaming {
+                return stream_pages(&path, &query, client).await;
+            }
+            client.get(&path, &query).await
+        }
+        PricingCommand::Report { app_id, file, date } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let date = date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+            let schedule: Value = client
+                .get(
+                    &format!("/apps/{app_id}/appPriceSchedule"),
+                    &[("include", "baseTerritory")],
+                )
+                .await?;
+            let schedule_id = schedule["data"]["id"]
+                .as_str()
+                .ok_or("app has no price schedule")?;
+            let mut prices = Vec::new();
+            let mut included = Vec::new();
+            for kind in ["manualPrices", "automaticPrices"] {
+                eprintln!("Fetching {kind}...");
+                let (data, inc) = price_pages(schedule_id, kind, client).await?;
+                prices.extend(data);
+                included.extend(inc);
+            }
+            let rows = price_rows(&prices, &included, &date.to_string(), "appPricePoint");
+            if let Some(file) = file {
+                std::fs::write(file, crate::output::csv::render(&rows, REPORT_COLUMNS))?;
+            }
+            Ok(json!({
+                "app_id": app_id,
+                "date": date.to_string(),
+                "base_territory": schedule["data"]["relationships"]["baseTerritory"]["data"]["id"],
+                "territories": rows.len(),
+                "file": file.as_ref().map(|f| f.display().to_string()),
+                "prices": rows,
+            }))
+        }
+        PricingCommand::Diff { app_id, against } | PricingCommand::Apply { app_id, against } => {
+            let apply = matches!(cmd, PricingCommand::Apply { .. });
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let content = std::fs::read_to_string(against)
+                .map_err(|e| format!("cannot read {}: {e}", against.display()))?;
+            let desired = parse_price_list(&content)?;
+            let date = chrono::Utc::now().date_naive().to_string();
+            let plan = Product::App.plan(app_id, &desired, &date, client).await?;
+            let mut report = plan_report(app_id, &plan);
+            if !apply || plan.changes.is_empty() {
+                return Ok(report);
+            }
+            if !plan.unresolved.is_empty() {
+                report["error"] = json!(format!(
+                    "{} price(s) match no price point; nothing was applied",
+                    plan.unresolved.len()
+                ));
+                return Err(Box::new(crate::output::Failure(report)));
+            }
+            eprintln!("Updating {} price(s)...", plan.changes.len());
+            let failed = Product::App
+                .apply(app_id, &plan.current, &plan.changes, client)
+                .await;
+            if let Some((_, error)) = failed.first() {
+                report["error"] = json!(error);
+                return Err(Box::new(crate::output::Failure(report)));
+            }
+            report["status"] = json!("applied");
+            Ok(report)
         }
         PricingCommand::Set {
             app_id,
             price_point,
             start_date,
         } => {
-            let mut attrs = json!({});
-            if let Some(sd) = start_date {
-                attrs["startDate"] = json!(sd);
-            }
-            let body = json!({
-                "data": {
-                    "type": "appPriceSchedules",
-                    "relationships": {
-                        "app": {
-                            "data": { "type": "apps", "id": app_id }
-                        },
-                        "manualPrices": {
-                            "data": [{
-                                "type": "appPrices",
-                                "id": "${new}"
-                            }]
-                        },
-                        "baseTerritory": {
-                            "data": { "type": "territories", "id": "USA" }
-                        }
-                    }
-                },
-                "included": [{
-                    "type": "appPrices",
-                    "id": "${new}",
-                    "attributes": attrs,
-                    "relationships": {
-                        "appPricePoint": {
-                            "data": {
-                                "type": "appPricePoints",
-                                "id": price_point
-                            }
-                        }
-                    }
-                }]
-            });
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let body = price_schedule_body(app_id, price_point, start_date.as_deref());
             client.post("/appPriceSchedules", &body).await
         }
     }
 }
+
+/// Desired `territory → customer price` from a CSV with `territory` and
+/// `customer_price` columns, keyed by upper-cased alpha-3 territory code.
+fn parse_price_list(content: &str) -> Result<BTreeMap<String, String>, Box<dyn std::error::Error>> {
+    let mut records = csv::parse(content).into_iter();
+    let header = records.next().ok_or("price file is empty")?;
+    let column = |name: &str| header.iter().position(|h| h.trim() == name);
+    let (Some(territory), Some(price)) = (column("territory"), column("customer_price")) else {
+        return Err("price file needs `territory` and `customer_price` columns".into());
+    };
+    let mut prices = BTreeMap::new();
+    for record in records {
+        let (Some(t), Some(p)) = (record.get(territory), record.get(price)) else {
+            continue;
+        };
+        if t.trim().is_empty() || p.trim().is_empty() {
+            continue;
+        }
+        prices.insert(t.trim().to_uppercase(), p.trim().to_string());
+    }
+    Ok(prices)
+}
+
+/// Planned changes of `pricing diff`.
+fn plan_report(app_id: &str, plan: &Plan) -> Value {
+    let in_sync = plan.changes.is_empty() && plan.unresolved.is_empty();
+    json!({
+        "app_id": app_id,
+        "status": if in_sync { "in_sync" } else { "planned" },
+        "changes": plan
+            .changes
+            .iter()
+            .map(|c| json!({
+                "territory": c.territory,
+                "from": c.from,
+                "to": c.to,
+                "price_point": c.point,
+            }))
+            .collect::<Vec<_>>(),
+        "unchanged": plan.unchanged,
+        "unresolved": plan
+            .unresolved
+            .iter()
+            .map(|(territory, error)| json!({ "territory": territory, "error": error }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Every page of a list endpoint, with the resources it included.
+pub(crate) async fn all_pages(
+    path: &str,
+    query: &[(&str, &str)],
+    client: &AppleClient,
+) -> Result<(Vec<Value>, Vec<Value>), Box<dyn std::error::Error>> {
+    let mut data = Vec::new();
+    let mut included = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut page_query = query.to_vec();
+        if let Some(c) = &cursor {
+            page_query.push(("cursor", c.as_str()));
+        }
+        let page: Value = client.get(path, &page_query).await?;
+        data.extend(page["data"].as_array().into_iter().flatten().cloned());
+        included.extend(page["included"].as_array().into_iter().flatten().cloned());
+        cursor = page["links"]["next"].as_str().and_then(next_cursor);
+        if cursor.is_none() {
+            return Ok((data, included));
+        }
+    }
+}
+
+/// Print every record of a list endpoint as JSON lines (`--output jsonl`), page by
+/// page and record by record, so that memory stays flat however many there are.
+pub(super) async fn stream_pages(
+    path: &str,
+    query: &[(&str, &str)],
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut count = 0;
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut page_query = query.to_vec();
+        if let Some(c) = &cursor {
+            page_query.push(("cursor", c.as_str()));
+        }
+        let rest = client
+            .get_streamed(path, &page_query, "data", &mut |record| {
+                crate::output::jsonl::emit(&record);
+                count += 1;
+            })
+            .await?;
+        cursor = rest["links"]["next"].as_str().and_then(next_cursor);
+        if cursor.is_none() {
+            eprintln!("Streamed {count} record(s)");
+            return Ok(Value::Null);
+        }
+    }
+}
+
+/// Every page of a schedule's `manualPrices` or `automaticPrices`, with the
+/// included price points and territories.
+async fn price_pages(
+    schedule_id: &str,
+    kind: &str,
+    client: &AppleClient,
+) -> Result<(Vec<Value>, Vec<Value>), Box<dyn std::error::Error>> {
+    all_pages(
+        &format!("/appPriceSchedules/{schedule_id}/{kind}"),
+        &[
+            ("include", "appPricePoint,territory"),
+            ("fields[appPricePoints]", "customerPrice,proceeds"),
+            ("fields[territories]", "currency"),
+            ("limit", "200"),
+        ],
+        client,
+    )
+    .await
+}
+
+/// One row per territory with the price in effect on `date` (YYYY-MM-DD).
+///
+/// `point_rel` names the price point relationship (`appPricePoint`,
+/// `inAppPurchasePricePoint`, ...). A manual price wins over an automatic one for
+/// the same territory; among those, the latest to start on or before `date` wins,
+/// since subscription prices carry no end date.
+pub(super) fn price_rows(
+    prices: &[Value],
+    included: &[Value],
+    date: &str,
+    point_rel: &str,
+) -> Vec<Value> {
+    let lookup: HashMap<(&str, &str), &Value> = included
+        .iter()
+        .filter_map(|r| Some(((r["type"].as_str()?, r["id"].as_str()?), &r["attributes"])))
+        .collect();
+    let related = |price: &Value, rel: &str| -> Option<(String, &Value)> {
+        let data = &price["relationships"][rel]["data"];
+        let id = data["id"].as_str()?;
+        Some((id.to_string(), *lookup.get(&(data["type"].as_str()?, id))?))
+    };
+
+    let mut by_territory: BTreeMap<String, Value> = BTreeMap::new();
+    for price in prices {
+        let attrs = &price["attributes"];
+        let starts = attrs["startDate"].as_str();
+        let ends = attrs["endDate"].as_str();
+        if starts.is_some_and(|s| s > date) || ends.is_some_and(|e| e <= date) {
+            continue;
+        }
+        let Some((territory, territory_attrs)) = related(price, "territory") else {
+            continue;
+        };
+        let point = related(price, point_rel);
+        let manual = attrs["manual"].as_bool().unwrap_or(false);
+        if by_territory.get(&territory).is_some_and(|r| {
+            let kept = (
+                r["manual"] == true,
+                r["start_date"].as_str().unwrap_or_default(),
+            );
+            kept >= (manual, starts.unwrap_or_default())
+        }) {
+            continue;
+        }
+        by_territory.insert(
+            territory.clone(),
+            json!({
+                "territory": territory,
+                "currency": territory_attrs["currency"],
+                "customer_price": point.as_ref().map(|(_, p)| p["customerPrice"].clone()),
+                "proceeds": point.as_ref().map(|(_, p)| p["proceeds"].clone()),
+                "price_point": point.map(|(id, _)| id),
+                "manual": manual,
+                "start_date": starts,
+                "end_date": ends,
+            }),
+        );
+    }
+    by_territory.into_values().collect()
+}
+
+/// Request body for a manual price schedule with a USA base territory.
+pub fn price_schedule_body(app_id: &str, price_point: &str, start_date: Option<&str>) -> Value {
+    let mut attrs = json!({});
+    if let Some(sd) = start_date {
+        attrs["startDate"] = json!(sd);
+    }
+    json!({
+        "data": {
+            "type": "appPriceSchedules",
+            "relationships": {
+                "app": {
+                    "data": { "type": "apps", "id": app_id }
+                },
+                "manualPrices": {
+                    "data": [{
+                        "type": "appPrices",
+                        "id": "${new}"
+                    }]
+                },
+                "baseTerritory": {
+                    "data": { "type": "territories", "id": "USA" }
+                }
+            }
+        },
+        "included": [{
+            "type": "appPrices",
+            "id": "${new}",
+            "attributes": attrs,
+            "relationships": {
+                "appPricePoint": {
+                    "data": {
+                        "type": "appPricePoints",
+                        "id": price_point
+                    }
+                }
+            }
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_desired_prices_by_column_name() {
+        let prices = parse_price_list(
+            "currency,territory,customer_price\nUSD,USA,4.99\nEUR,deu,5.49\nJPY,JPN,\n",
+        )
+        .unwrap();
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices["DEU"], "5.49");
+        assert!(parse_price_list("territory,price\nUSA,1\n").is_err());
+    }
+
+    #[test]
+    fn report_joins_price_points_and_prefers_manual_prices() {
+        let price = |id: &str, territory: &str, point: &str, manual: bool, start: Value| {
+            json!({
+                "type": "appPrices", "id": id,
+                "attributes": {"manual": manual, "startDate": start, "endDate": null},
+                "relationships": {
+                    "territory": {"data": {"type": "territories", "id": territory}},
+                    "appPricePoint": {"data": {"type": "appPricePoints", "id": point}}
+                }
+            })
+        };
+        let prices = [
+            price("1", "USA", "p-usa", true, Value::Null),
+            price("2", "USA", "p-usa-auto", false, Value::Null),
+            price("3", "DEU", "p-deu", false, json!("2026-01-01")),
+            price("4", "JPN", "p-jpn", false, json!("2027-01-01")),
+        ];
+        let included = [
+            json!({"type": "territories", "id": "USA", "attributes": {"currency": "USD"}}),
+            json!({"type": "territories", "id": "DEU", "attributes": {"currency": "EUR"}}),
+            json!({"type": "territories", "id": "JPN", "attributes": {"currency": "JPY"}}),
+            json!({"type": "appPricePoints", "id": "p-usa", "attributes": {"customerPrice": "4.99", "proceeds": "4.24"}}),
+            json!({"type": "appPricePoints", "id": "p-usa-auto", "attributes": {"customerPrice": "5.99", "proceeds": "5.09"}}),
+            json!({"type": "appPricePoints", "id": "p-deu", "attributes": {"customerPrice": "4.99", "proceeds": "3.57"}}),
+        ];
+
+        let rows = price_rows(&prices, &included, "2026-06-01", "appPricePoint");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["territory"], "DEU");
+        assert_eq!(rows[0]["currency"], "EUR");
+        assert_eq!(rows[0]["proceeds"], "3.57");
+        assert_eq!(rows[1]["territory"], "USA");
+        assert_eq!(rows[1]["customer_price"], "4.99");
+        assert_eq!(rows[1]["manual"], true);
+        assert_eq!(rows[1]["price_point"], "p-usa");
+
+        // Without end dates, the latest start on or before the date wins
+        let later = [
+            price("5", "DEU", "p-deu-new", false, json!("2026-03-01")),
+            price("3", "DEU", "p-deu", false, json!("2026-01-01")),
+        ];
+        let rows = price_rows(&later, &included, "2026-06-01", "appPricePoint");
+        assert_eq!(rows[0]["start_date"], "2026-03-01");
+    }
+}
@@ -0,0 +1,474 @@
+//! App privacy details ("nutrition labels").
+//!
+//! Apple exposes data usages through `appDataUsages` resources only to some API
+//! keys; when they are unavailable the API error is returned as-is. The local
+//! JSON file format is store-agnostic so labels can be versioned in a repo:
+//!
+//! ```json
+//! {
+//!   "data_not_collected": false,
+//!   "usages": [
+//!     {
+//!       "category": "EMAIL_ADDRESS",
+//!       "purposes": ["APP_FUNCTIONALITY"],
+//!       "protections": ["DATA_LINKED_TO_YOU"]
+//!     }
+//!   ]
+//! }
+//! ```
+
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::resolve;
+use crate::api::apple_client::AppleClient;
+
+const PURPOSES: &[&str] = &[
+    "THIRD_PARTY_ADVERTISING",
+    "DEVELOPERS_ADVERTISING",
+    "ANALYTICS",
+    "PRODUCT_PERSONALIZATION",
+    "APP_FUNCTIONALITY",
+    "OTHER_PURPOSES",
+];
+
+const PROTECTIONS: &[&str] = &[
+    "DATA_USED_TO_TRACK_YOU",
+    "DATA_LINKED_TO_YOU",
+    "DATA_NOT_LINKED_TO_YOU",
+];
+
+#[derive(Subcommand)]
+pub enum PrivacyCommand {
+    /// Get the raw data usages declared for an app
+    Get {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+    },
+    /// Export the app's privacy details to a local JSON file
+    ///
+    /// Examples:
+    ///   storeops apple privacy export 1234567890 --file privacy.json
+    Export {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Output file (prints to stdout when omitted)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Replace the app's privacy details with the contents of a local JSON file
+    ///
+    /// Creates the declared usages the app lacks before deleting the ones no longer
+    /// declared, so a failure part-way leaves the old labels in place.
+    ///
+    /// Examples:
+    ///   storeops apple privacy import 1234567890 --file privacy.json --publish
+    Import {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// Privacy details JSON file
+        #[arg(long)]
+        file: PathBuf,
+        /// Publish the privacy details after uploading
+        #[arg(long)]
+        publish: bool,
+    },
+    /// Validate a local privacy details file without calling the API
+    Validate {
+        /// Privacy details JSON file
+        #[arg(long)]
+        file: PathBuf,
+    },
+}
+
+/// Local, version-controllable representation of an app's privacy details.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyDetails {
+    #[serde(default)]
+    pub data_not_collected: bool,
+    #[serde(default)]
+    pub usages: Vec<DataUsage>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct DataUsage {
+    pub category: String,
+    #[serde(default)]
+    pub purposes: Vec<String>,
+    #[serde(default)]
+    pub protections: Vec<String>,
+}
+
+impl PrivacyDetails {
+    /// Group `appDataUsages` resources by category.
+    fn from_api(resp: &Value) -> Self {
+        let mut by_category: BTreeMap<String, (Vec<String>, Vec<String>)> = BTreeMap::new();
+        let mut data_not_collected = false;
+
+        for usage in resp["data"].as_array().into_iter().flatten() {
+            let rel = &usage["relationships"];
+            let protection = rel["dataProtection"]["data"]["id"].as_str();
+            if protection == Some("DATA_NOT_COLLECTED") {
+                data_not_collected = true;
+                continue;
+            }
+            let Some(category) = rel["category"]["data"]["id"].as_str() else {
+                continue;
+            };
+            let entry = by_category.entry(category.to_string()).or_default();
+            if let Some(p) = rel["purpose"]["data"]["id"].as_str() {
+                if !entry.0.iter().any(|x| x == p) {
+                    entry.0.push(p.to_string());
+                }
+            }
+            if let Some(p) = protection {
+                if !entry.1.iter().any(|x| x == p) {
+                    entry.1.push(p.to_string());
+                }
+            }
+        }
+
+        Self {
+            data_not_collected,
+            usages: by_category
+                .into_iter()
+                .map(|(category, (purposes, protections))| DataUsage {
+                    category,
+                    purposes,
+                    protections,
+                })
+                .collect(),
+        }
+    }
+
+    /// Check the file against the values Apple accepts.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.data_not_collected && !self.usages.is_empty() {
+            problems.push("data_not_collected is true but usages are declared".to_string());
+        }
+        if !self.data_not_collected && self.usages.is_empty() {
+            problems
+                .push("no usages declared; set data_not_collected = true if intended".to_string());
+        }
+        for usage in &self.usages {
+            if usage.category.is_empty() {
+                problems.push("usage with empty category".to_string());
+            }
+            if usage.purposes.is_empty() {
+                problems.push(format!(
+                    "{}: at least one purpose is required",
+                    usage.category
+                ));
+            }
+            if usage.protections.is_empty() {
+                problems.push(format!(
+                    "{}: at least one protection is required",
+                    usage.category
+                ));
+            }
+            for p in &usage.purposes {
+                if !PURPOSES.contains(&p.as_str()) {
+                    problems.push(format!("{}: unknown purpose '{p}'", usage.category));
+                }
+            }
+            for p in &usage.protections {
+                if !PROTECTIONS.contains(&p.as_str()) {
+                    problems.push(format!("{}: unknown protection '{p}'", usage.category));
+                }
+            }
+        }
+        problems
+    }
+
+    /// Expand into one `appDataUsages` create body per category/purpose/protection.
+    fn to_api_bodies(&self, app_id: &str) -> Vec<Value> {
+        let rel = |kind: &str, id: &str| json!({ "data": { "type": kind, "id": id } });
+
+        if self.data_not_collected {
+            return vec![json!({
+                "data": {
+                    "type": "appDataUsages",
+                    "relationships": {
+                        "app": rel("apps", app_id),
+                        "dataProtection": rel("appDataUsageDataProtections", "DATA_NOT_COLLECTED")
+                    }
+                }
+            })];
+        }
+
+        let mut bodies = Vec::new();
+        for usage in &self.usages {
+            for purpose in &usage.purposes {
+                for protection in &usage.protections {
+                    bodies.push(json!({
+                        "data": {
+                            "type": "appDataUsages",
+                            "relationships": {
+                                "app": rel("apps", app_id),
+                                "category": rel("appDataUsageCategories", &usage.category),
+                                "purpose": rel("appDataUsagePurposes", purpose),
+                                "dataProtection": rel("appDataUsageDataProtections", protection)
+                            }
+                        }
+                    }));
+                }
+            }
+        }
+        bodies
+    }
+}
+
+fn read_details(path: &PathBuf) -> Result<PrivacyDetails, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Every data usage of the app, as one `{"data", "included"}` document.
+async fn fetch_usages(
+    app_id: &str,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let (data, included) = super::pricing::all_pages(
+        &format!("/apps/{app_id}/dataUsages"),
+        &[
+            ("include", "category,purpose,dataProtection"),
+            ("limit", "500"),
+        ],
+        client,
+    )
+    .await?;
+    Ok(json!({ "data": data, "included": included }))
+}
+
+/// Category, purpose and protection IDs of a data usage's relationships.
+fn combination(relationships: &Value) -> [Option<&str>; 3] {
+    ["category", "purpose", "dataProtection"].map(|r| relationships[r]["data"]["id"].as_str())
+}
+
+/// Create bodies for the declared usages the app does not have yet, and IDs of the
+/// existing usages that are no longer declared.
+fn plan_import(existing: &[Value], bodies: Vec<Value>) -> (Vec<Value>, Vec<String>) {
+    let wanted: Vec<_> = bodies
+        .iter()
+        .map(|b| combination(&b["data"]["relationships"]))
+        .collect();
+    let current: Vec<_> = existing
+        .iter()
+        .map(|u| combination(&u["relationships"]))
+        .collect();
+    let create = bodies
+        .iter()
+        .zip(&wanted)
+        .filter(|(_, key)| !current.contains(key))
+        .map(|(body, _)| body.clone())
+        .collect();
+    let delete = existing
+        .iter()
+        .zip(&current)
+        .filter(|(_, key)| !wanted.contains(key))
+        .filter_map(|(usage, _)| usage["id"].as_str().map(String::from))
+        .collect();
+    (create, delete)
+}
+
+pub async fn handle(
+    cmd: &PrivacyCommand,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        PrivacyCommand::Get { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            fetch_usages(app_id, client).await
+        }
+        PrivacyCommand::Export { app_id, file } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let resp = fetch_usages(app_id, client).await?;
+            let details = PrivacyDetails::from_api(&resp);
+            let value = serde_json::to_value(&details)?;
+            if let Some(path) = file {
+                std::fs::write(path, serde_json::to_string_pretty(&value)?)?;
+                return Ok(json!({
+                    "status": "ok",
+                    "file": path.display().to_string(),
+                    "categories": details.usages.len(),
+                }));
+            }
+            Ok(value)
+        }
+        PrivacyCommand::Import {
+            app_id,
+            file,
+            publish,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let details = read_details(file)?;
+            let problems = details.validate();
+            if !problems.is_empty() {
+                return Err(format!("invalid privacy file: {}", problems.join("; ")).into());
+            }
+
+            // Create the missing usages before deleting stale ones, so a failure
+            // part-way never leaves the app with fewer labels than either version
+            let existing = fetch_usages(app_id, client).await?;
+            let existing = existing["data"].as_array().cloned().unwrap_or_default();
+            let bodies = details.to_api_bodies(app_id);
+            let (create, delete) = plan_import(&existing, bodies);
+            let unchanged = existing.len() - delete.len();
+            let mut result = json!({
+                "status": "ok",
+                "app_id": app_id,
+                "created": 0,
+                "deleted": 0,
+                "unchanged": unchanged,
+            });
+            for (i, body) in create.iter().enumerate() {
+                if let Err(e) = client.post("/appDataUsages", body).await {
+                    result["status"] = json!("failed");
+                    result["created"] = json!(i);
+                    result["error"] = json!(format!("{e}; no existing usage was deleted"));
+                    return Err(Box::new(crate::output::Failure(result)));
+                }
+            }
+            result["created"] = json!(create.len());
+            for (i, id) in delete.iter().enumerate() {
+                if let Err(e) = client.delete(&format!("/appDataUsages/{id}")).await {
+                    result["status"] = json!("failed");
+                    result["deleted"] = json!(i);
+                    result["not_deleted"] = json!(delete[i..]);
+                    result["error"] = json!(e.to_string());
+                    return Err(Box::new(crate::output::Failure(result)));
+                }
+            }
+            result["deleted"] = json!(delete.len());
+            eprintln!(
+                "Created {} and deleted {} data usages ({unchanged} unchanged)",
+                create.len(),
+                delete.len()
+            );
+
+            if *publish {
+                let state: Value = client
+                    .get(&format!("/apps/{app_id}/dataUsagePublishState"), &[])
+                    .await?;
+                let state_id = state["data"]["id"]
+                    .as_str()
+                    .ok_or("no publish state found")?;
+                let body = json!({
+                    "data": {
+                        "type": "appDataUsagesPublishState",
+                        "id": state_id,
+                        "attributes": { "published": true }
+                    }
+                });
+                client
+                    .patch(&format!("/appDataUsagesPublishState/{state_id}"), &body)
+                    .await?;
+                result["published"] = json!(true);
+            }
+            Ok(result)
+        }
+        PrivacyCommand::Validate { file } => {
+            let details = read_details(file)?;
+            let problems = details.validate();
+            Ok(json!({
+                "valid": problems.is_empty(),
+                "problems": problems,
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(category: &str, purpose: &str, protection: &str) -> Value {
+        json!({
+            "type": "appDataUsages",
+            "id": format!("{category}-{purpose}-{protection}"),
+            "relationships": {
+                "category": {"data": {"id": category}},
+                "purpose": {"data": {"id": purpose}},
+                "dataProtection": {"data": {"id": protection}}
+            }
+        })
+    }
+
+    #[test]
+    fn from_api_groups_by_category() {
+        let resp = json!({"data": [
+            usage("EMAIL_ADDRESS", "APP_FUNCTIONALITY", "DATA_LINKED_TO_YOU"),
+            usage("EMAIL_ADDRESS", "ANALYTICS", "DATA_LINKED_TO_YOU"),
+            usage("CRASH_DATA", "ANALYTICS", "DATA_NOT_LINKED_TO_YOU"),
+        ]});
+        let details = PrivacyDetails::from_api(&resp);
+        assert!(!details.data_not_collected);
+        assert_eq!(details.usages.len(), 2);
+        let email = details
+            .usages
+            .iter()
+            .find(|u| u.category == "EMAIL_ADDRESS")
+            .unwrap();
+        assert_eq!(email.purposes, vec!["APP_FUNCTIONALITY", "ANALYTICS"]);
+        assert_eq!(email.protections, vec!["DATA_LINKED_TO_YOU"]);
+    }
+
+    #[test]
+    fn to_api_bodies_expands_combinations() {
+        let details = PrivacyDetails {
+            data_not_collected: false,
+            usages: vec![DataUsage {
+                category: "EMAIL_ADDRESS".to_string(),
+                purposes: vec!["ANALYTICS".to_string(), "APP_FUNCTIONALITY".to_string()],
+                protections: vec!["DATA_LINKED_TO_YOU".to_string()],
+            }],
+        };
+        assert_eq!(details.to_api_bodies("123").len(), 2);
+        assert!(details.validate().is_empty());
+    }
+
+    #[test]
+    fn import_creates_missing_and_deletes_stale_usages() {
+        let existing = [
+            usage("EMAIL_ADDRESS", "ANALYTICS", "DATA_LINKED_TO_YOU"),
+            usage("CRASH_DATA", "ANALYTICS", "DATA_NOT_LINKED_TO_YOU"),
+        ];
+        let details = PrivacyDetails {
+            data_not_collected: false,
+            usages: vec![DataUsage {
+                category: "EMAIL_ADDRESS".to_string(),
+                purposes: vec!["ANALYTICS".to_string(), "APP_FUNCTIONALITY".to_string()],
+                protections: vec!["DATA_LINKED_TO_YOU".to_string()],
+            }],
+        };
+        let (create, delete) = plan_import(&existing, details.to_api_bodies("123"));
+        assert_eq!(create.len(), 1);
+        assert_eq!(
+            create[0]["data"]["relationships"]["purpose"]["data"]["id"],
+            "APP_FUNCTIONALITY"
+        );
+        assert_eq!(delete, ["CRASH_DATA-ANALYTICS-DATA_NOT_LINKED_TO_YOU"]);
+    }
+
+    #[test]
+    fn validate_flags_unknown_values() {
+        let details = PrivacyDetails {
+            data_not_collected: false,
+            usages: vec![DataUsage {
+                category: "EMAIL_ADDRESS".to_string(),
+                purposes: vec!["SELLING".to_string()],
+                protections: vec![],
+            }],
+        };
+        let problems = details.validate();
+        assert!(problems.iter().any(|p| p.contains("unknown purpose")));
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("protection is required")));
+    }
+}
@@ -0,0 +1,177 @@
+//! `--auto-remediate`: recover from App Store Connect state conflicts.
+//!
+//! When a mutating call fails with a recognized `StateProblem`, these helpers do
+//! what the error hint suggests instead: update the localization that already
+//! exists, move an edit to the next editable version, or reuse the version that is
+//! already in progress. Each result carries a `remediation` entry saying so.
+
+use serde_json::{json, Value};
+
+use super::sync;
+use crate::api::apple_client::AppleClient;
+use crate::api::apple_errors::StateProblem;
+use crate::cli::version::BumpPart;
+
+/// Note on a command result what was done instead of the failed request.
+fn remediated(mut result: Value, problem: StateProblem, action: String) -> Value {
+    eprintln!("Remediated {}: {action}", problem.as_str());
+    result["remediation"] = json!({ "problem": problem.as_str(), "action": action });
+    result
+}
+
+/// ID of the `locale` localization of a version, if there is one.
+async fn find_localization(
+    version_id: &str,
+    locale: &str,
+    client: &AppleClient,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let locs: Value = client
+        .get(
+            &format!("/appStoreVersions/{version_id}/appStoreVersionLocalizations"),
+            &[("limit", "200")],
+        )
+        .await?;
+    Ok(locs["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|l| l["attributes"]["locale"].as_str() == Some(locale))
+        .and_then(|l| l["id"].as_str())
+        .map(String::from))
+}
+
+async fn patch_localization(
+    localization_id: &str,
+    attrs: &Value,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let body = json!({
+        "data": {
+            "type": "appStoreVersionLocalizations",
+            "id": localization_id,
+            "attributes": attrs
+        }
+    });
+    client
+        .patch(
+            &format!("/appStoreVersionLocalizations/{localization_id}"),
+            &body,
+        )
+        .await
+}
+
+/// A localization create that hit an existing locale: update that one instead.
+pub async fn update_existing_localization(
+    version_id: &str,
+    locale: &str,
+    attrs: &Value,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let id = find_localization(version_id, locale, client)
+        .await?
+        .ok_or_else(|| format!("no existing {locale} localization found to update"))?;
+    let mut attrs = attrs.clone();
+    if let Some(attrs) = attrs.as_object_mut() {
+        attrs.remove("locale");
+    }
+    let result = patch_localization(&id, &attrs, client).await?;
+    Ok(remediated(
+        result,
+        StateProblem::Duplicate,
+        format!("updated the existing {locale} localization {id}"),
+    ))
+}
+
+/// A localization update on a version that can no longer be edited: apply it to
+/// the app's editable version, creating the next patch version if there is none.
+pub async fn update_in_editable_version(
+    localization_id: &str,
+    attrs: &Value,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let localization: Value = client
+        .get(
+            &format!("/appStoreVersionLocalizations/{localization_id}"),
+            &[("include", "appStoreVersion")],
+        )
+        .await?;
+    let locale = localization["data"]["attributes"]["locale"]
+        .as_str()
+        .ok_or("localization has no locale")?;
+    let version_id = localization["data"]["relationships"]["appStoreVersion"]["data"]["id"]
+        .as_str()
+        .ok_or("localization has no version")?;
+    let app: Value = client
+        .get(&format!("/appStoreVersions/{version_id}/app"), &[])
+        .await?;
+    let app_id = app["data"]["id"].as_str().ok_or("version has no app")?;
+
+    let (version, _) = sync::get_or_create_editable_version(
+        app_id,
+        None,
+        &sync::CreateVersion::Auto(BumpPart::Patch),
+        client,
+    )
+    .await?;
+    let editable_id = version["id"].as_str().ok_or("version has no id")?;
+    let version_string = version["attributes"]["versionString"]
+        .as_str()
+        .unwrap_or(editable_id);
+    // New versions start with a copy of the previous version's localizations
+    let target = find_localization(editable_id, locale, client)
+        .await?
+        .ok_or_else(|| format!("version {version_string} has no {locale} localization"))?;
+    let result = patch_localization(&target, attrs, client).await?;
+    Ok(remediated(
+        result,
+        StateProblem::NotEditable,
+        format!("applied the update to {locale} of editable version {version_string}"),
+    ))
+}
+
+/// A version create while another version is in progress: reuse that version,
+/// renaming it to `version` if needed.
+pub async fn reuse_version_in_progress(
+    app_id: &str,
+    version: &str,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let versions: Value = client
+        .get(
+            &format!("/apps/{app_id}/appStoreVersions"),
+            &[
+                ("filter[appStoreState]", &sync::EDITABLE_STATES.join(",")),
+                ("limit", "1"),
+            ],
+        )
+        .await?;
+    let existing = versions["data"][0].clone();
+    let id = existing["id"]
+        .as_str()
+        .ok_or("no version in progress found to reuse")?;
+    let current = existing["attributes"]["versionString"]
+        .as_str()
+        .unwrap_or_default();
+    if current == version {
+        return Ok(remediated(
+            json!({ "data": existing }),
+            StateProblem::VersionInProgress,
+            format!("version {version} is already in progress ({id})"),
+        ));
+    }
+    let body = json!({
+        "data": {
+            "type": "appStoreVersions",
+            "id": id,
+            "attributes": { "versionString": version }
+        }
+    });
+    let result = client
+        .patch(&format!("/appStoreVersions/{id}"), &body)
+        .await?;
+    Ok(remediated(
+        result,
+        StateProblem::VersionInProgress,
+        format!("renamed version {current} in progress ({id}) to {version}"),
+    ))
+}
@@ -0,0 +1,227 @@
+//! Resolve human-friendly references into App Store Connect resource IDs.
+//!
+//! Anywhere an Apple command takes an app, version, or version localization ID it
+//! also accepts a reference built from the bundle ID:
+//!
+//! - app: `com.example.app`
+//! - version: `com.example.app@1.2.0` (or `com.example.app` for the editable version)
+//! - localization: `com.example.app@1.2.0/en-US` (or `com.example.app/en-US`)
+//!
+//! Plain IDs are passed through untouched. Resolved IDs for pinned references are
+//! cached in `ids.json` next to the config file; references to the editable version
+//! are always looked up, since that version changes with every release.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::sync;
+use crate::api::apple_client::AppleClient;
+use crate::config::Config;
+
+/// A parsed resource reference.
+#[derive(Debug, PartialEq, Eq)]
+struct Reference<'a> {
+    bundle_id: &'a str,
+    version: Option<&'a str>,
+    locale: Option<&'a str>,
+}
+
+/// Parse `bundle[@version][/locale]`, returning `None` for plain resource IDs.
+///
+/// Bundle IDs always contain a dot, which App Store Connect IDs never do.
+fn parse_reference(input: &str) -> Option<Reference<'_>> {
+    let (rest, locale) = match input.split_once('/') {
+        Some((rest, locale)) => (rest, Some(locale)),
+        None => (input, None),
+    };
+    let (bundle_id, version) = match rest.split_once('@') {
+        Some((bundle_id, version)) => (bundle_id, Some(version)),
+        None => (rest, None),
+    };
+    if !bundle_id.contains('.') {
+        return None;
+    }
+    Some(Reference {
+        bundle_id,
+        version,
+        locale,
+    })
+}
+
+fn cache_path() -> Option<PathBuf> {
+    Config::config_dir().map(|d| d.join("ids.json"))
+}
+
+fn load_cache() -> BTreeMap<String, String> {
+    cache_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Best-effort write-through; a failed cache write never fails the command.
+fn store_cached(key: &str, id: &str) {
+    let Some(path) = cache_path() else { return };
+    let mut cache = load_cache();
+    cache.insert(key.to_string(), id.to_string());
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(content) = serde_json::to_string_pretty(&cache) {
+        let _ = std::fs::write(path, content);
+    }
+}
+
+async fn cached<F, Fut>(key: &str, lookup: F) -> Result<String, Box<dyn std::error::Error>>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<String, Box<dyn std::error::Error>>>,
+{
+    if let Some(id) = load_cache().get(key) {
+        return Ok(id.clone());
+    }
+    let id = lookup().await?;
+    store_cached(key, &id);
+    Ok(id)
+}
+
+async fn resolve_app(
+    bundle_id: &str,
+    client: &AppleClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    cached(bundle_id, || {
+        sync::lookup_app_by_bundle_id(bundle_id, client)
+    })
+    .await
+}
+
+async fn resolve_version(
+    reference: &Reference<'_>,
+    client: &AppleClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let app_id = resolve_app(reference.bundle_id, client).await?;
+    let Some(version) = reference.version else {
+        let editable = sync::get_editable_version(&app_id, None, client).await?;
+        return editable["id"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| "version has no id".into());
+    };
+    let key = format!("{}@{version}", reference.bundle_id);
+    cached(&key, || async {
+        let versions: Value = client
+            .get(
+                &format!("/apps/{app_id}/appStoreVersions"),
+                &[("filter[versionString]", version), ("limit", "1")],
+            )
+            .await?;
+        versions["data"][0]["id"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| {
+                format!("version {version} not found for {}", reference.bundle_id).into()
+            })
+    })
+    .await
+}
+
+/// Resolve an app ID or bundle ID to an app ID, falling back to the profile's
+/// `default_app_id` when none is given.
+pub async fn app_id(
+    input: Option<&str>,
+    client: &AppleClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let input = input
+        .or(client.default_app())
+        .ok_or("no app given and the profile has no default_app_id")?;
+    match parse_reference(input) {
+        Some(r) if r.version.is_none() && r.locale.is_none() => {
+            resolve_app(r.bundle_id, client).await
+        }
+        Some(_) => Err(format!("expected an app ID or bundle ID, got {input}").into()),
+        None => Ok(input.to_string()),
+    }
+}
+
+/// Resolve a version ID or `bundle[@version]` reference to an App Store version ID.
+pub async fn version_id(
+    input: &str,
+    client: &AppleClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    match parse_reference(input) {
+        Some(r) if r.locale.is_none() => resolve_version(&r, client).await,
+        Some(_) => Err(format!("expected a version ID or bundle[@version], got {input}").into()),
+        None => Ok(input.to_string()),
+    }
+}
+
+/// Resolve a localization ID or `bundle[@version]/locale` reference to an App Store
+/// version localization ID.
+pub async fn localization_id(
+    input: &str,
+    client: &AppleClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let Some(reference) = parse_reference(input) else {
+        return Ok(input.to_string());
+    };
+    let locale = reference.locale.ok_or_else(|| {
+        format!("expected a localization ID or bundle[@version]/locale, got {input}")
+    })?;
+    let version_id = resolve_version(&reference, client).await?;
+    let lookup = || async {
+        let locs: Value = client
+            .get(
+                &format!("/appStoreVersions/{version_id}/appStoreVersionLocalizations"),
+                &[("limit", "200")],
+            )
+            .await?;
+        locs["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|l| l["attributes"]["locale"].as_str() == Some(locale))
+            .and_then(|l| l["id"].as_str())
+            .map(String::from)
+            .ok_or_else(|| format!("no {locale} localization for {input}").into())
+    };
+    if reference.version.is_some() {
+        cached(input, lookup).await
+    } else {
+        lookup().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_ids_are_not_references() {
+        assert_eq!(parse_reference("1234567890"), None);
+        assert_eq!(
+            parse_reference("a1b2c3d4-e5f6-7890-abcd-ef1234567890"),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_version_and_locale_parts() {
+        assert_eq!(
+            parse_reference("com.example.app@1.2.0/en-US"),
+            Some(Reference {
+                bundle_id: "com.example.app",
+                version: Some("1.2.0"),
+                locale: Some("en-US"),
+            })
+        );
+        assert_eq!(
+            parse_reference("com.example.app/de-DE"),
+            Some(Reference {
+                bundle_id: "com.example.app",
+                version: None,
+                locale: Some("de-DE"),
+            })
+        );
+    }
+}
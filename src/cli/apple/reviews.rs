@@ -1,14 +1,16 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
 
+use super::resolve;
 use crate::api::apple_client::AppleClient;
+use crate::cli::review_export::{self, ExportArgs};
 
 #[derive(Subcommand)]
 pub enum ReviewsCommand {
     /// List customer reviews
     List {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
         /// Filter by rating (1-5)
         #[arg(long)]
         rating: Option<u8>,
@@ -16,6 +18,16 @@ pub enum ReviewsCommand {
         #[arg(long, default_value = "recent")]
         sort: String,
     },
+    /// Export all reviews in a date range to CSV or JSON lines
+    ///
+    /// Examples:
+    ///   storeops apple reviews export com.example.app --since 2026-09-01 --until 2026-09-30 --file reviews.csv --sentiment
+    Export {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        #[command(flatten)]
+        args: ExportArgs,
+    },
     /// Respond to a review
     Respond {
         /// Review ID
@@ -37,8 +49,12 @@ pub async fn handle(
             rating,
             sort,
         } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let streaming = crate::output::jsonl::enabled();
             let mut query = vec![];
-            let limit_str = limit.unwrap_or(50).to_string();
+            let limit_str = limit
+                .unwrap_or(if streaming { 200 } else { 50 })
+                .to_string();
             query.push(("limit", limit_str.as_str()));
             let sort_value = match sort.as_str() {
                 "helpful" => "-rating",
@@ -50,9 +66,16 @@ pub async fn handle(
                 rating_str = r.to_string();
                 query.push(("filter[rating]", &rating_str));
             }
-            client
-                .get(&format!("/apps/{app_id}/customerReviews"), &query)
-                .await
+            let path = format!("/apps/{app_id}/customerReviews");
+            if streaming {
+                return super::pricing::stream_pages(&path, &query, client).await;
+            }
+            client.get(&path, &query).await
+        }
+        ReviewsCommand::Export { app_id, args } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let rows = export_rows(app_id, args, client).await?;
+            review_export::write(rows, args)
         }
         ReviewsCommand::Respond { review_id, body } => {
             let payload = json!({
@@ -75,3 +98,69 @@ pub async fn handle(
         }
     }
 }
+
+/// Page through reviews newest first, stopping once past `--since`.
+pub async fn export_rows(
+    app_id: &str,
+    args: &ExportArgs,
+    client: &AppleClient,
+) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+    let mut rows = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut query = vec![
+            ("limit", "200"),
+            ("sort", "-createdDate"),
+            ("include", "response"),
+        ];
+        if let Some(c) = &cursor {
+            query.push(("cursor", c.as_str()));
+        }
+        let page: Value = client
+            .get(&format!("/apps/{app_id}/customerReviews"), &query)
+            .await?;
+        let mut past_range = false;
+        for review in page["data"].as_array().into_iter().flatten() {
+            let attrs = &review["attributes"];
+            let Some(date) = attrs["createdDate"]
+                .as_str()
+                .and_then(|d| chrono::DateTime::parse_from_rfc3339(d).ok())
+                .map(|d| d.to_utc())
+            else {
+                continue;
+            };
+            if args.before_range(&date) {
+                past_range = true;
+                break;
+            }
+            if !args.in_range(&date) {
+                continue;
+            }
+            rows.push(json!({
+                "store": "apple",
+                "review_id": review["id"],
+                "date": date.to_rfc3339(),
+                "rating": attrs["rating"],
+                "title": attrs["title"],
+                "body": attrs["body"],
+                "author": attrs["reviewerNickname"],
+                "territory": attrs["territory"],
+                "replied": !review["relationships"]["response"]["data"].is_null(),
+            }));
+        }
+        eprintln!("Fetched {} review(s)", rows.len());
+        cursor = page["links"]["next"].as_str().and_then(next_cursor);
+        if past_range || cursor.is_none() {
+            return Ok(rows);
+        }
+    }
+}
+
+/// The `cursor` query parameter of a `links.next` URL.
+pub fn next_cursor(next: &str) -> Option<String> {
+    let (_, query) = next.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("cursor="))
+        .map(str::to_string)
+}
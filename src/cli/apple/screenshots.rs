@@ -1,7 +1,11 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
 
+use super::platform::Platform;
+use super::resolve;
 use crate::api::apple_client::AppleClient;
+use crate::api::asset_upload::{self, Asset, UploadSource, UploadedAsset};
+use crate::api::recording::RecordedSend;
 
 #[derive(Subcommand)]
 pub enum ScreenshotsCommand {
@@ -20,15 +24,22 @@ pub enum ScreenshotsCommand {
 #[derive(Subcommand)]
 pub enum SetsCommand {
     /// List screenshot sets for a version localization
+    ///
+    /// Examples:
+    ///   storeops apple screenshots sets list LOCALIZATION_ID
+    ///   storeops apple screenshots sets list com.example.app/en-US --platform macos
     List {
-        /// App Store Version Localization ID
+        /// App Store version localization ID or bundle[@version]/locale
         localization_id: String,
+        /// Only sets with display types for this platform
+        #[arg(long, value_enum)]
+        platform: Option<Platform>,
     },
     /// Create a screenshot set
     Create {
-        /// App Store Version Localization ID
+        /// App Store version localization ID or bundle[@version]/locale
         localization_id: String,
-        /// Display type (e.g., APP_IPHONE_67, APP_IPHONE_65, APP_IPAD_PRO_129, etc.)
+        /// Display type (e.g., APP_IPHONE_67, APP_IPAD_PRO_3GEN_129, APP_DESKTOP, APP_APPLE_TV, APP_APPLE_VISION_PRO)
         #[arg(long)]
         display_type: String,
     },
@@ -37,8 +48,59 @@ pub enum SetsCommand {
         /// Screenshot Set ID
         set_id: String,
     },
+    /// Remove empty and obsolete display-type sets from a localization
+    ///
+    /// Examples:
+    ///   storeops apple screenshots sets prune LOCALIZATION_ID --dry-run
+    Prune {
+        /// App Store version localization ID or bundle[@version]/locale
+        localization_id: String,
+        /// Keep sets for obsolete display types that still contain screenshots
+        #[arg(long)]
+        keep_obsolete: bool,
+        /// Report what would be deleted without deleting
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Copy screenshots from one display type to another (e.g., for a new device class)
+    ///
+    /// Examples:
+    ///   storeops apple screenshots sets migrate LOCALIZATION_ID --from APP_IPHONE_65 --to APP_IPHONE_67
+    Migrate {
+        /// App Store version localization ID or bundle[@version]/locale
+        localization_id: String,
+        /// Source display type
+        #[arg(long)]
+        from: String,
+        /// Target display type (the set is created if missing)
+        #[arg(long)]
+        to: String,
+        /// Replace screenshots already in the target set (removed after the copies are verified)
+        #[arg(long)]
+        replace: bool,
+        /// Delete the source set after copying
+        #[arg(long)]
+        delete_source: bool,
+    },
 }
 
+/// Most screenshots App Store Connect accepts in one set.
+const MAX_SCREENSHOTS_PER_SET: usize = 10;
+
+/// Display types App Store Connect no longer requires or shows for new submissions.
+const OBSOLETE_DISPLAY_TYPES: &[&str] = &[
+    "APP_IPHONE_35",
+    "APP_IPHONE_40",
+    "APP_IPHONE_47",
+    "APP_IPAD_97",
+    "APP_IPAD_105",
+    "APP_WATCH_SERIES_3",
+    "IMESSAGE_APP_IPHONE_40",
+    "IMESSAGE_APP_IPHONE_47",
+    "IMESSAGE_APP_IPAD_97",
+    "IMESSAGE_APP_IPAD_105",
+];
+
 #[derive(Subcommand)]
 pub enum ImagesCommand {
     /// List screenshots in a set
@@ -89,19 +151,33 @@ async fn handle_sets(
     limit: Option<u32>,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
-        SetsCommand::List { localization_id } => {
+        SetsCommand::List {
+            localization_id,
+            platform,
+        } => {
+            let localization_id = &resolve::localization_id(localization_id, client).await?;
             let limit_str = limit.unwrap_or(50).to_string();
-            client
+            let mut sets: Value = client
                 .get(
                     &format!("/appStoreVersionLocalizations/{localization_id}/appScreenshotSets"),
                     &[("limit", limit_str.as_str())],
                 )
-                .await
+                .await?;
+            if let (Some(platform), Some(data)) = (platform, sets["data"].as_array_mut()) {
+                data.retain(|set| {
+                    let display_type = set["attributes"]["screenshotDisplayType"]
+                        .as_str()
+                        .unwrap_or_default();
+                    Platform::of_display_type(display_type) == *platform
+                });
+            }
+            Ok(sets)
         }
         SetsCommand::Create {
             localization_id,
             display_type,
         } => {
+            let localization_id = &resolve::localization_id(localization_id, client).await?;
             let body = json!({
                 "data": {
                     "type": "appScreenshotSets",
@@ -123,9 +199,332 @@ async fn handle_sets(
         SetsCommand::Delete { set_id } => {
             client.delete(&format!("/appScreenshotSets/{set_id}")).await
         }
+        SetsCommand::Prune {
+            localization_id,
+            keep_obsolete,
+            dry_run,
+        } => {
+            let localization_id = &resolve::localization_id(localization_id, client).await?;
+            let sets = list_sets(localization_id, client).await?;
+            let mut pruned = Vec::new();
+            let mut kept = Vec::new();
+            for (set_id, display_type) in &sets {
+                let count = list_screenshots(set_id, client).await?.len();
+                let obsolete = OBSOLETE_DISPLAY_TYPES.contains(&display_type.as_str());
+                let reason = if count == 0 {
+                    "empty"
+                } else if obsolete && !keep_obsolete {
+                    "obsolete"
+                } else {
+                    kept.push(display_type.clone());
+                    continue;
+                };
+                if !dry_run {
+                    client
+                        .delete(&format!("/appScreenshotSets/{set_id}"))
+                        .await?;
+                    eprintln!("Deleted {display_type} set ({reason})");
+                }
+                pruned.push(json!({
+                    "set_id": set_id,
+                    "display_type": display_type,
+                    "reason": reason,
+                    "screenshots": count,
+                }));
+            }
+            Ok(json!({
+                "status": if *dry_run { "dry_run" } else { "ok" },
+                "pruned": pruned,
+                "kept": kept,
+            }))
+        }
+        SetsCommand::Migrate {
+            localization_id,
+            from,
+            to,
+            replace,
+            delete_source,
+        } => {
+            let localization_id = &resolve::localization_id(localization_id, client).await?;
+            let sets = list_sets(localization_id, client).await?;
+            let find = |display_type: &str| {
+                sets.iter()
+                    .find(|(_, t)| t == display_type)
+                    .map(|(id, _)| id.clone())
+            };
+            let source_id = find(from).ok_or_else(|| format!("no {from} set found"))?;
+            let source = list_screenshots(&source_id, client).await?;
+            if source.is_empty() {
+                return Err(format!("{from} set has no screenshots").into());
+            }
+
+            // Download every source image before the target set is touched
+            let http_client = crate::api::session::http();
+            let mut images = Vec::new();
+            for (idx, ss) in source.iter().enumerate() {
+                let asset = &ss["attributes"]["imageAsset"];
+                let template = asset["templateUrl"]
+                    .as_str()
+                    .ok_or("screenshot has no image asset (still processing?)")?;
+                let url = template
+                    .replace("{w}", &asset["width"].as_u64().unwrap_or(0).to_string())
+                    .replace("{h}", &asset["height"].as_u64().unwrap_or(0).to_string())
+                    .replace("{f}", "png");
+                let bytes = http_client
+                    .get(&url)
+                    .send_recorded()
+                    .await?
+                    .error_for_status()?
+                    .bytes()
+                    .await?;
+                let filename = ss["attributes"]["fileName"]
+                    .as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("{:02}.png", idx + 1));
+                images.push((filename, bytes));
+            }
+
+            let (target_id, old_ids) = match find(to) {
+                Some(id) => {
+                    let existing = list_screenshots(&id, client).await?;
+                    if !existing.is_empty() && !replace {
+                        return Err(format!(
+                            "{to} set already has {} screenshots; pass --replace to overwrite",
+                            existing.len()
+                        )
+                        .into());
+                    }
+                    let old_ids: Vec<String> = existing
+                        .iter()
+                        .filter_map(|ss| ss["id"].as_str().map(String::from))
+                        .collect();
+                    (id, old_ids)
+                }
+                None => (create_set(localization_id, to, client).await?, Vec::new()),
+            };
+
+            // Old screenshots are deleted once every copy is verified, unless old
+            // and new together exceed the set limit; then they have to go first.
+            let delete_first = old_ids.len() + images.len() > MAX_SCREENSHOTS_PER_SET;
+            let mut deleted = Vec::new();
+            let mut copied = Vec::new();
+            let result = async {
+                if delete_first {
+                    for id in &old_ids {
+                        client.delete(&format!("/appScreenshots/{id}")).await?;
+                        deleted.push(id.clone());
+                    }
+                }
+                for (filename, bytes) in &images {
+                    let asset = upload_screenshot(client, &target_id, filename, bytes).await?;
+                    verify_delivery(client, &asset.path, &asset.checksum).await?;
+                    eprintln!("Copied {filename} -> {to}");
+                    copied.push(asset.id);
+                }
+                if !delete_first {
+                    for id in &old_ids {
+                        client.delete(&format!("/appScreenshots/{id}")).await?;
+                        deleted.push(id.clone());
+                    }
+                }
+                Ok::<_, Box<dyn std::error::Error>>(())
+            }
+            .await;
+            if let Err(e) = result {
+                return Err(Box::new(crate::output::Failure(json!({
+                    "status": "failed",
+                    "target_set_id": target_id,
+                    "copied": copied,
+                    "deleted": deleted,
+                    "error": e.to_string(),
+                }))));
+            }
+
+            if *delete_source {
+                client
+                    .delete(&format!("/appScreenshotSets/{source_id}"))
+                    .await?;
+            }
+            Ok(json!({
+                "status": "ok",
+                "from": from,
+                "to": to,
+                "target_set_id": target_id,
+                "copied": copied,
+                "replaced": deleted,
+                "source_deleted": delete_source,
+            }))
+        }
+    }
+}
+
+/// `(set id, display type)` for every screenshot set of a localization.
+async fn list_sets(
+    localization_id: &str,
+    client: &AppleClient,
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let resp: Value = client
+        .get(
+            &format!("/appStoreVersionLocalizations/{localization_id}/appScreenshotSets"),
+            &[("limit", "50")],
+        )
+        .await?;
+    Ok(resp["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|set| {
+            Some((
+                set["id"].as_str()?.to_string(),
+                set["attributes"]["screenshotDisplayType"]
+                    .as_str()?
+                    .to_string(),
+            ))
+        })
+        .collect())
+}
+
+async fn list_screenshots(
+    set_id: &str,
+    client: &AppleClient,
+) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+    let resp: Value = client
+        .get(
+            &format!("/appScreenshotSets/{set_id}/appScreenshots"),
+            &[("limit", "50")],
+        )
+        .await?;
+    Ok(resp["data"].as_array().cloned().unwrap_or_default())
+}
+
+async fn create_set(
+    localization_id: &str,
+    display_type: &str,
+    client: &AppleClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let body = json!({
+        "data": {
+            "type": "appScreenshotSets",
+            "attributes": {
+                "screenshotDisplayType": display_type
+            },
+            "relationships": {
+                "appStoreVersionLocalization": {
+                    "data": {
+                        "type": "appStoreVersionLocalizations",
+                        "id": localization_id
+                    }
+                }
+            }
+        }
+    });
+    let created: Value = client.post("/appScreenshotSets", &body).await?;
+    Ok(created["data"]["id"]
+        .as_str()
+        .ok_or("no set id in response")?
+        .to_string())
+}
+
+/// Apple's processing state of an uploaded screenshot or preview.
+#[derive(Debug, PartialEq)]
+pub enum Delivery {
+    Complete,
+    /// Processing failed; holds Apple's error descriptions
+    Failed(String),
+    /// Still processing when polling gave up
+    Pending,
+}
+
+const DELIVERY_POLLS: u32 = 30;
+const DELIVERY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+fn delivery_state(resource: &Value, checksum: &str) -> Delivery {
+    let attrs = &resource["data"]["attributes"];
+    let state = &attrs["assetDeliveryState"];
+    match state["state"].as_str() {
+        Some("COMPLETE") => match attrs["sourceFileChecksum"].as_str() {
+            Some(delivered) if !delivered.eq_ignore_ascii_case(checksum) => Delivery::Failed(
+                format!("checksum mismatch: uploaded {checksum}, delivered {delivered}"),
+            ),
+            _ => Delivery::Complete,
+        },
+        Some("FAILED") => {
+            let errors: Vec<&str> = state["errors"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e["description"].as_str().or(e["code"].as_str()))
+                .collect();
+            Delivery::Failed(if errors.is_empty() {
+                "asset processing failed".to_string()
+            } else {
+                errors.join("; ")
+            })
+        }
+        _ => Delivery::Pending,
+    }
+}
+
+/// Poll a committed asset (`/appScreenshots/{id}` or `/appPreviews/{id}`) until Apple
+/// reports its delivery state as COMPLETE or FAILED, and check the delivered asset's
+/// checksum against the local file's.
+///
+/// A committed upload can still fail processing; without this check the failure only
+/// shows up in the App Store Connect web UI.
+pub async fn await_delivery(
+    client: &AppleClient,
+    path: &str,
+    checksum: &str,
+) -> Result<Delivery, Box<dyn std::error::Error>> {
+    for attempt in 0..DELIVERY_POLLS {
+        if attempt > 0 {
+            tokio::time::sleep(DELIVERY_POLL_INTERVAL).await;
+        }
+        let resource: Value = client.get(path, &[]).await?;
+        match delivery_state(&resource, checksum) {
+            Delivery::Pending => continue,
+            done => return Ok(done),
+        }
+    }
+    Ok(Delivery::Pending)
+}
+
+/// Wait for a single uploaded asset, failing the command if Apple rejects it.
+pub async fn verify_delivery(
+    client: &AppleClient,
+    path: &str,
+    checksum: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match await_delivery(client, path, checksum).await? {
+        Delivery::Complete => Ok(()),
+        Delivery::Failed(reason) => Err(format!("{path} failed processing: {reason}").into()),
+        Delivery::Pending => {
+            eprintln!("Warning: {path} is still processing; check App Store Connect");
+            Ok(())
+        }
     }
 }
 
+/// Reserve, upload and commit a screenshot.
+pub async fn upload_screenshot(
+    client: &AppleClient,
+    set_id: &str,
+    filename: &str,
+    file_bytes: &[u8],
+) -> Result<UploadedAsset, Box<dyn std::error::Error>> {
+    asset_upload::upload(
+        client,
+        Asset {
+            kind: "appScreenshots",
+            file_name: filename,
+            source: UploadSource::Bytes(file_bytes),
+            attributes: json!({}),
+            parent: ("appScreenshotSet", "appScreenshotSets", set_id),
+        },
+    )
+    .await
+}
+
 async fn handle_images(
     cmd: &ImagesCommand,
     client: &AppleClient,
@@ -146,70 +545,10 @@ async fn handle_images(
             file,
             filename,
         } => {
-            let file_size = tokio::fs::metadata(file).await?.len();
-            // Step 1: Reserve the screenshot
-            let reservation = json!({
-                "data": {
-                    "type": "appScreenshots",
-                    "attributes": {
-                        "fileName": filename,
-                        "fileSize": file_size
-                    },
-                    "relationships": {
-                        "appScreenshotSet": {
-                            "data": {
-                                "type": "appScreenshotSets",
-                                "id": set_id
-                            }
-                        }
-                    }
-                }
-            });
-            let reserved: Value = client.post("/appScreenshots", &reservation).await?;
-            let screenshot_id = reserved["data"]["id"]
-                .as_str()
-                .ok_or("no screenshot id in reservation response")?;
-
-            // Step 2: Upload the asset
-            let upload_ops = &reserved["data"]["attributes"]["uploadOperations"];
             let file_bytes = tokio::fs::read(file).await?;
-
-            if let Some(ops) = upload_ops.as_array() {
-                for op in ops {
-                    let url = op["url"].as_str().ok_or("missing upload url")?;
-                    let offset = op["offset"].as_u64().unwrap_or(0) as usize;
-                    let length = op["length"].as_u64().unwrap_or(file_bytes.len() as u64) as usize;
-                    let chunk =
-                        &file_bytes[offset..std::cmp::min(offset + length, file_bytes.len())];
-
-                    let mut req = reqwest::Client::new().put(url);
-                    if let Some(headers) = op["requestHeaders"].as_array() {
-                        for h in headers {
-                            if let (Some(name), Some(value)) =
-                                (h["name"].as_str(), h["value"].as_str())
-                            {
-                                req = req.header(name, value);
-                            }
-                        }
-                    }
-                    req.body(chunk.to_vec()).send().await?;
-                }
-            }
-
-            // Step 3: Commit the upload
-            let commit_body = json!({
-                "data": {
-                    "type": "appScreenshots",
-                    "id": screenshot_id,
-                    "attributes": {
-                        "uploaded": true,
-                        "sourceFileChecksum": reserved["data"]["attributes"]["sourceFileChecksum"]
-                    }
-                }
-            });
-            client
-                .patch(&format!("/appScreenshots/{screenshot_id}"), &commit_body)
-                .await
+            let asset = upload_screenshot(client, set_id, filename, &file_bytes).await?;
+            verify_delivery(client, &asset.path, &asset.checksum).await?;
+            client.get::<Value>(&asset.path, &[]).await
         }
         ImagesCommand::Delete { screenshot_id } => {
             client
@@ -236,3 +575,49 @@ async fn handle_images(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_asset_delivery_state() {
+        let complete =
+            json!({"data": {"attributes": {"assetDeliveryState": {"state": "COMPLETE"}}}});
+        assert_eq!(delivery_state(&complete, "abc"), Delivery::Complete);
+
+        let uploading =
+            json!({"data": {"attributes": {"assetDeliveryState": {"state": "UPLOAD_COMPLETE"}}}});
+        assert_eq!(delivery_state(&uploading, "abc"), Delivery::Pending);
+
+        let failed = json!({"data": {"attributes": {"assetDeliveryState": {
+            "state": "FAILED",
+            "errors": [{"code": "IMAGE_TOOL_FAILURE", "description": "Image is corrupt"}]
+        }}}});
+        assert_eq!(
+            delivery_state(&failed, "abc"),
+            Delivery::Failed("Image is corrupt".to_string())
+        );
+    }
+
+    #[test]
+    fn checksum_mismatch_fails_a_delivered_asset() {
+        let checksum = asset_upload::md5_checksum(b"hello");
+        assert_eq!(checksum, "5d41402abc4b2a76b9719d911017c592");
+
+        let delivered = |sum: &str| {
+            json!({"data": {"attributes": {
+                "sourceFileChecksum": sum,
+                "assetDeliveryState": {"state": "COMPLETE"}
+            }}})
+        };
+        assert_eq!(
+            delivery_state(&delivered("5D41402ABC4B2A76B9719D911017C592"), &checksum),
+            Delivery::Complete
+        );
+        assert!(matches!(
+            delivery_state(&delivered("0000"), &checksum),
+            Delivery::Failed(reason) if reason.starts_with("checksum mismatch")
+        ));
+    }
+}
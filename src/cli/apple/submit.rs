@@ -1,10 +1,60 @@
+use clap::Subcommand;
 use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
 
+use super::resolve;
 use crate::api::apple_client::AppleClient;
+use crate::api::apple_errors::ApiError;
+use crate::api::asset_upload::{self, Asset, UploadSource};
 
+#[derive(Subcommand)]
+pub enum SubmitCommand {
+    /// Set the App Review details of a version and upload review attachments
+    ///
+    /// With no options, shows the current details.
+    ///
+    /// Examples:
+    ///   storeops apple submit review-details com.example.app@1.4.0 --contact-email qa@example.com --demo-account-name demo --demo-account-password secret
+    ///   storeops apple submit review-details VERSION_ID --attachment walkthrough.mp4
+    ReviewDetails {
+        /// App Store version ID or bundle[@version]
+        version_id: String,
+        #[arg(long)]
+        contact_first_name: Option<String>,
+        #[arg(long)]
+        contact_last_name: Option<String>,
+        #[arg(long)]
+        contact_phone: Option<String>,
+        #[arg(long)]
+        contact_email: Option<String>,
+        /// Sign-in user name for App Review
+        #[arg(long)]
+        demo_account_name: Option<String>,
+        #[arg(long)]
+        demo_account_password: Option<String>,
+        /// Whether App Review needs the demo account to sign in
+        #[arg(long)]
+        demo_account_required: Option<bool>,
+        /// Notes for the reviewer
+        #[arg(long)]
+        notes: Option<String>,
+        /// File to attach for App Review (repeatable)
+        #[arg(long = "attachment")]
+        attachments: Vec<PathBuf>,
+    },
+}
+
+/// Submit a version for review, together with the in-app purchases and
+/// subscriptions given by ID or product ID.
+///
+/// Every item is looked up before anything is submitted. The items are submitted
+/// first so App Review gets them with the version, as in App Store Connect; if
+/// one of them fails, the version is not submitted.
 pub async fn handle(
     app_id: &str,
     version: &str,
+    iaps: &[String],
+    subscriptions: &[String],
     client: &AppleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let versions: Value = client
@@ -18,6 +68,90 @@ pub async fn handle(
         .as_str()
         .ok_or("version not found")?;
 
+    let iaps = if iaps.is_empty() {
+        Vec::new()
+    } else {
+        let listed: Value = client
+            .get(
+                &format!("/apps/{app_id}/inAppPurchasesV2"),
+                &[("limit", "200")],
+            )
+            .await?;
+        find_products(listed["data"].as_array(), iaps, "in-app purchase")?
+    };
+    let subscriptions = if subscriptions.is_empty() {
+        Vec::new()
+    } else {
+        let groups: Value = client
+            .get(
+                &format!("/apps/{app_id}/subscriptionGroups"),
+                &[
+                    ("include", "subscriptions"),
+                    ("limit", "200"),
+                    ("limit[subscriptions]", "50"),
+                ],
+            )
+            .await?;
+        let listed: Vec<Value> = groups["included"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|i| i["type"] == "subscriptions")
+            .cloned()
+            .collect();
+        find_products(Some(&listed), subscriptions, "subscription")?
+    };
+
+    let mut items = Vec::new();
+    let mut failed = Vec::new();
+    // (kind, products, submission type, relationship, related type)
+    let kinds = [
+        (
+            "iap",
+            &iaps,
+            "inAppPurchaseSubmissions",
+            "inAppPurchaseV2",
+            "inAppPurchases",
+        ),
+        (
+            "subscription",
+            &subscriptions,
+            "subscriptionSubmissions",
+            "subscription",
+            "subscriptions",
+        ),
+    ];
+    for (kind, products, submission_type, relationship, related_type) in kinds {
+        for (id, product_id) in products {
+            let body = json!({
+                "data": {
+                    "type": submission_type,
+                    "relationships": {
+                        relationship: { "data": { "type": related_type, "id": id } }
+                    }
+                }
+            });
+            match client.post(&format!("/{submission_type}"), &body).await {
+                Ok(_) => {
+                    eprintln!("Submitted {kind} {product_id}");
+                    items.push(json!({ "type": kind, "id": id, "product_id": product_id }));
+                }
+                Err(e) => failed.push(format!("{kind} {product_id}: {e}")),
+            }
+        }
+    }
+    if !failed.is_empty() {
+        // Report what already went to review alongside what did not
+        return Err(Box::new(crate::output::Failure(json!({
+            "submitted": items,
+            "failed": failed,
+            "error": format!(
+                "version {version} not submitted; could not submit {} item(s)",
+                failed.len()
+            ),
+        }))));
+    }
+
     let body = json!({
         "data": {
             "type": "appStoreVersionSubmissions",
@@ -32,5 +166,200 @@ pub async fn handle(
         }
     });
 
-    client.post("/appStoreVersionSubmissions", &body).await
+    let submission: Value = client.post("/appStoreVersionSubmissions", &body).await?;
+    if items.is_empty() {
+        return Ok(submission);
+    }
+    Ok(json!({
+        "submission": submission["data"],
+        "items": items,
+    }))
+}
+
+/// Match each requested ID or product ID against the listed products; returns
+/// `(id, product ID)` pairs, or an error naming the ones not found.
+fn find_products(
+    listed: Option<&Vec<Value>>,
+    wanted: &[String],
+    kind: &str,
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let listed = listed.map(Vec::as_slice).unwrap_or_default();
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+    for want in wanted {
+        let product = listed
+            .iter()
+            .find(|p| p["id"] == want.as_str() || p["attributes"]["productId"] == want.as_str());
+        match product
+            .and_then(|p| Some((p["id"].as_str()?, p["attributes"]["productId"].as_str()?)))
+        {
+            Some((id, product_id)) => found.push((id.to_string(), product_id.to_string())),
+            None => missing.push(want.as_str()),
+        }
+    }
+    if !missing.is_empty() {
+        return Err(format!("{kind} not found: {}", missing.join(", ")).into());
+    }
+    Ok(found)
+}
+
+pub async fn handle_command(
+    cmd: &SubmitCommand,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        SubmitCommand::ReviewDetails {
+            version_id,
+            contact_first_name,
+            contact_last_name,
+            contact_phone,
+            contact_email,
+            demo_account_name,
+            demo_account_password,
+            demo_account_required,
+            notes,
+            attachments,
+        } => {
+            let version_id = &resolve::version_id(version_id, client).await?;
+            let mut attrs = serde_json::Map::new();
+            for (key, value) in [
+                ("contactFirstName", contact_first_name),
+                ("contactLastName", contact_last_name),
+                ("contactPhone", contact_phone),
+                ("contactEmail", contact_email),
+                ("demoAccountName", demo_account_name),
+                ("demoAccountPassword", demo_account_password),
+                ("notes", notes),
+            ] {
+                if let Some(v) = value {
+                    attrs.insert(key.to_string(), json!(v));
+                }
+            }
+            if let Some(required) = demo_account_required {
+                attrs.insert("demoAccountRequired".to_string(), json!(required));
+            }
+
+            // Only a 404 or a null `data` means there is no detail yet; other errors stop here
+            let existing = match client
+                .get::<Value>(
+                    &format!("/appStoreVersions/{version_id}/appStoreReviewDetail"),
+                    &[],
+                )
+                .await
+            {
+                Ok(detail) => Some(detail).filter(|d| !d["data"].is_null()),
+                Err(e)
+                    if e.downcast_ref::<ApiError>()
+                        .is_some_and(|e| e.status == reqwest::StatusCode::NOT_FOUND) =>
+                {
+                    None
+                }
+                Err(e) => return Err(e),
+            };
+            if attrs.is_empty() && attachments.is_empty() {
+                return existing.ok_or_else(|| "version has no App Review details yet".into());
+            }
+
+            let detail = match existing {
+                Some(detail) if attrs.is_empty() => detail,
+                Some(detail) => {
+                    let detail_id = detail["data"]["id"].as_str().ok_or("no review detail id")?;
+                    let body = json!({
+                        "data": {
+                            "type": "appStoreReviewDetails",
+                            "id": detail_id,
+                            "attributes": attrs
+                        }
+                    });
+                    client
+                        .patch(&format!("/appStoreReviewDetails/{detail_id}"), &body)
+                        .await?
+                }
+                None => {
+                    let body = json!({
+                        "data": {
+                            "type": "appStoreReviewDetails",
+                            "attributes": attrs,
+                            "relationships": {
+                                "appStoreVersion": {
+                                    "data": { "type": "appStoreVersions", "id": version_id }
+                                }
+                            }
+                        }
+                    });
+                    client.post("/appStoreReviewDetails", &body).await?
+                }
+            };
+            let detail_id = detail["data"]["id"].as_str().ok_or("no review detail id")?;
+
+            let mut uploaded = Vec::new();
+            for file in attachments {
+                let id = upload_attachment(detail_id, file, client).await?;
+                eprintln!("Attached {}", file.display());
+                uploaded.push(json!({ "id": id, "file": file.display().to_string() }));
+            }
+
+            Ok(json!({
+                "status": "ok",
+                "review_detail_id": detail_id,
+                "attributes": detail["data"]["attributes"],
+                "attachments_uploaded": uploaded,
+            }))
+        }
+    }
+}
+
+/// Reserve, upload and commit one App Review attachment; returns its ID.
+async fn upload_attachment(
+    detail_id: &str,
+    file: &Path,
+    client: &AppleClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let file_name = file
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("attachment path has no file name")?;
+    let asset = asset_upload::upload(
+        client,
+        Asset {
+            kind: "appStoreReviewAttachments",
+            file_name,
+            source: UploadSource::File(file),
+            attributes: json!({}),
+            parent: ("appStoreReviewDetail", "appStoreReviewDetails", detail_id),
+        },
+    )
+    .await?;
+    super::screenshots::verify_delivery(client, &asset.path, &asset.checksum).await?;
+    Ok(asset.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_products_by_id_or_product_id() {
+        let listed = vec![
+            json!({ "id": "111", "attributes": { "productId": "coins.100" } }),
+            json!({ "id": "222", "attributes": { "productId": "remove_ads" } }),
+        ];
+        let found = find_products(
+            Some(&listed),
+            &["remove_ads".to_string(), "111".to_string()],
+            "in-app purchase",
+        )
+        .unwrap();
+        assert_eq!(
+            found,
+            vec![
+                ("222".to_string(), "remove_ads".to_string()),
+                ("111".to_string(), "coins.100".to_string())
+            ]
+        );
+
+        let err = find_products(Some(&listed), &["coins.500".to_string()], "in-app purchase")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "in-app purchase not found: coins.500");
+    }
 }
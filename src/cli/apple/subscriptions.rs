@@ -1,7 +1,11 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
 
+use super::price_matrix::{self, Product};
+use super::resolve;
 use crate::api::apple_client::AppleClient;
+use crate::api::asset_upload::{self, Asset, UploadSource};
+use std::path::PathBuf;
 
 #[derive(Subcommand)]
 pub enum SubscriptionsCommand {
@@ -30,19 +34,24 @@ pub enum SubscriptionsCommand {
         #[command(subcommand)]
         command: OffersCommand,
     },
+    /// Manage subscription promotional images
+    Images {
+        #[command(subcommand)]
+        command: SubImagesCommand,
+    },
 }
 
 #[derive(Subcommand)]
 pub enum GroupsCommand {
     /// List subscription groups for an app
     List {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
     },
     /// Create a subscription group
     Create {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
         /// Group reference name
         #[arg(long)]
         name: String,
@@ -60,6 +69,53 @@ pub enum GroupsCommand {
         /// Subscription Group ID
         group_id: String,
     },
+    /// Manage group localizations (the name shown on the subscription management sheet)
+    Localizations {
+        #[command(subcommand)]
+        command: GroupLocalizationsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GroupLocalizationsCommand {
+    /// List localizations for a subscription group
+    List {
+        /// Subscription Group ID
+        group_id: String,
+    },
+    /// Create a localization
+    ///
+    /// Examples:
+    ///   storeops apple subscriptions groups localizations create 1234567890 --locale de-DE --name "Premium"
+    Create {
+        /// Subscription Group ID
+        group_id: String,
+        /// Locale
+        #[arg(long)]
+        locale: String,
+        /// Display name of the group
+        #[arg(long)]
+        name: String,
+        /// App name shown with the group, if it differs from the app's name
+        #[arg(long)]
+        custom_app_name: Option<String>,
+    },
+    /// Update a localization
+    Update {
+        /// Localization ID
+        localization_id: String,
+        /// Display name of the group
+        #[arg(long)]
+        name: Option<String>,
+        /// App name shown with the group
+        #[arg(long)]
+        custom_app_name: Option<String>,
+    },
+    /// Delete a localization
+    Delete {
+        /// Localization ID
+        localization_id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -149,6 +205,65 @@ pub enum SubPricesCommand {
         #[arg(long)]
         territory: Option<String>,
     },
+    /// Export the prices of all subscriptions as a CSV matrix
+    ///
+    /// One row per product ID and one column per territory, each cell holding the
+    /// customer price in that territory's currency.
+    ///
+    /// Examples:
+    ///   storeops apple subscriptions prices export com.example.app --out prices.csv
+    Export {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// CSV file to write
+        #[arg(long)]
+        out: PathBuf,
+        /// Export prices in effect on this day instead of today (YYYY-MM-DD)
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+    },
+    /// Apply a CSV price matrix written by `export`
+    ///
+    /// Changed cells are matched to the territory's price point; empty cells are left
+    /// alone. Cells without a matching price point are reported and skipped.
+    ///
+    /// Examples:
+    ///   storeops apple subscriptions prices import com.example.app --file prices.csv --dry-run
+    Import {
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// CSV price matrix
+        #[arg(long)]
+        file: PathBuf,
+        /// Show the changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SubImagesCommand {
+    /// List promotional images for a subscription
+    List {
+        /// Subscription ID
+        subscription_id: String,
+    },
+    /// Upload a promotional image (1024x1024 PNG or JPEG)
+    ///
+    /// Examples:
+    ///   storeops apple subscriptions images upload 1234567890 --file artwork/premium.png
+    Upload {
+        /// Subscription ID
+        subscription_id: String,
+        /// Image file to upload
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// Delete a promotional image
+    Delete {
+        /// Subscription image ID
+        image_id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -198,6 +313,7 @@ pub async fn handle(
         }
         SubscriptionsCommand::Prices { command } => handle_prices(command, client, limit).await,
         SubscriptionsCommand::Offers { command } => handle_offers(command, client, limit).await,
+        SubscriptionsCommand::Images { command } => handle_images(command, client).await,
     }
 }
 
@@ -208,6 +324,7 @@ async fn handle_groups(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         GroupsCommand::List { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             let limit_str = limit.unwrap_or(50).to_string();
             client
                 .get(
@@ -217,6 +334,7 @@ async fn handle_groups(
                 .await
         }
         GroupsCommand::Create { app_id, name } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             let body = json!({
                 "data": {
                     "type": "subscriptionGroups",
@@ -245,6 +363,83 @@ async fn handle_groups(
                 .delete(&format!("/subscriptionGroups/{group_id}"))
                 .await
         }
+        GroupsCommand::Localizations { command } => {
+            handle_group_localizations(command, client, limit).await
+        }
+    }
+}
+
+async fn handle_group_localizations(
+    cmd: &GroupLocalizationsCommand,
+    client: &AppleClient,
+    limit: Option<u32>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        GroupLocalizationsCommand::List { group_id } => {
+            let limit_str = limit.unwrap_or(50).to_string();
+            client
+                .get(
+                    &format!("/subscriptionGroups/{group_id}/subscriptionGroupLocalizations"),
+                    &[("limit", limit_str.as_str())],
+                )
+                .await
+        }
+        GroupLocalizationsCommand::Create {
+            group_id,
+            locale,
+            name,
+            custom_app_name,
+        } => {
+            let mut attrs = json!({ "locale": locale, "name": name });
+            if let Some(c) = custom_app_name {
+                attrs["customAppName"] = json!(c);
+            }
+            let body = json!({
+                "data": {
+                    "type": "subscriptionGroupLocalizations",
+                    "attributes": attrs,
+                    "relationships": {
+                        "subscriptionGroup": {
+                            "data": { "type": "subscriptionGroups", "id": group_id }
+                        }
+                    }
+                }
+            });
+            client.post("/subscriptionGroupLocalizations", &body).await
+        }
+        GroupLocalizationsCommand::Update {
+            localization_id,
+            name,
+            custom_app_name,
+        } => {
+            let mut attrs = json!({});
+            if let Some(n) = name {
+                attrs["name"] = json!(n);
+            }
+            if let Some(c) = custom_app_name {
+                attrs["customAppName"] = json!(c);
+            }
+            let body = json!({
+                "data": {
+                    "type": "subscriptionGroupLocalizations",
+                    "id": localization_id,
+                    "attributes": attrs
+                }
+            });
+            client
+                .patch(
+                    &format!("/subscriptionGroupLocalizations/{localization_id}"),
+                    &body,
+                )
+                .await
+        }
+        GroupLocalizationsCommand::Delete { localization_id } => {
+            client
+                .delete(&format!(
+                    "/subscriptionGroupLocalizations/{localization_id}"
+                ))
+                .await
+        }
     }
 }
 
@@ -403,6 +598,26 @@ async fn handle_prices(
                 )
                 .await
         }
+        SubPricesCommand::Export { app_id, out, date } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let date = date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+            price_matrix::export(
+                Product::Subscription,
+                app_id,
+                out,
+                &date.to_string(),
+                client,
+            )
+            .await
+        }
+        SubPricesCommand::Import {
+            app_id,
+            file,
+            dry_run,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            price_matrix::import(Product::Subscription, app_id, file, *dry_run, client).await
+        }
     }
 }
 
@@ -455,3 +670,42 @@ async fn handle_offers(
         }
     }
 }
+
+async fn handle_images(
+    cmd: &SubImagesCommand,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        SubImagesCommand::List { subscription_id } => {
+            client
+                .get::<Value>(&format!("/subscriptions/{subscription_id}/images"), &[])
+                .await
+        }
+        SubImagesCommand::Upload {
+            subscription_id,
+            file,
+        } => {
+            let file_name = file
+                .file_name()
+                .and_then(|n| n.to_str())
+                .ok_or("image path has no file name")?;
+            let asset = asset_upload::upload(
+                client,
+                Asset {
+                    kind: "subscriptionImages",
+                    file_name,
+                    source: UploadSource::File(file),
+                    attributes: json!({}),
+                    parent: ("subscription", "subscriptions", subscription_id),
+                },
+            )
+            .await?;
+            client.get::<Value>(&asset.path, &[]).await
+        }
+        SubImagesCommand::Delete { image_id } => {
+            client
+                .delete(&format!("/subscriptionImages/{image_id}"))
+                .await
+        }
+    }
+}
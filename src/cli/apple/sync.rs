@@ -5,15 +5,29 @@
 
 use clap::Subcommand;
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use tokio::fs;
 
+use super::app_info_stage::{self, Stage};
+use super::display_types::DisplayTypeDirs;
+use super::platform::Platform;
+use super::screenshots::{await_delivery, Delivery};
 use crate::api::apple_client::AppleClient;
+use crate::api::asset_upload::UploadedAsset;
+use crate::cli::checkpoint::PushCheckpoint;
+use crate::cli::download::{download_all, Download, FetchOptions, DEFAULT_CONCURRENCY};
+use crate::cli::pull_manifest::PullManifest;
+use crate::cli::sync::{
+    changed_fields, field_report, locale_selected, ordered_images, write_order,
+};
+use crate::cli::template;
+use crate::cli::version::{bump_version, release_version, BumpPart};
+use crate::output::stream;
 
 /// App Store Connect locale codes mapped to internal standardized codes.
 /// Format: "asc_locale" -> "internal_locale"
-fn asc_to_internal_locale(asc_locale: &str) -> String {
+pub(super) fn asc_to_internal_locale(asc_locale: &str) -> String {
     match asc_locale {
         "ja" => "ja-JP".to_string(),
         "ko" => "ko-KR".to_string(),
@@ -45,43 +59,11 @@ fn internal_to_asc_locale(internal_locale: &str) -> String {
     }
 }
 
-/// Screenshot display type to directory name mapping
-fn display_type_to_dir(display_type: &str) -> &str {
-    match display_type {
-        // APP_IPHONE_67 covers both 6.7" (1290x2796) and 6.9" (1320x2868) devices
-        "APP_IPHONE_67" => "iphone67",
-        "APP_IPHONE_65" => "iphone65",
-        "APP_IPHONE_61" => "iphone61",
-        "APP_IPHONE_58" => "iphone58",
-        "APP_IPHONE_55" => "iphone55",
-        "APP_IPAD_PRO_129" | "APP_IPAD_PRO_3GEN_129" => "ipadPro129",
-        "APP_IPAD_PRO_11" | "APP_IPAD_PRO_3GEN_11" => "ipadPro11",
-        _ => display_type,
-    }
-}
-
-/// Directory name to screenshot display type mapping
-fn dir_to_display_type(dir_name: &str) -> &str {
-    match dir_name {
-        // iphone69 uses APP_IPHONE_67 - Apple uses this type for both 6.7" and 6.9" displays
-        // iPhone 16 Pro Max (6.9" - 1320x2868) and iPhone 14 Pro Max (6.7" - 1290x2796)
-        // both use the APP_IPHONE_67 display type
-        "iphone69" | "iphone67" => "APP_IPHONE_67",
-        "iphone65" => "APP_IPHONE_65",
-        "iphone61" => "APP_IPHONE_61",
-        "iphone58" => "APP_IPHONE_58",
-        "iphone55" => "APP_IPHONE_55",
-        "ipadPro129" => "APP_IPAD_PRO_3GEN_129",
-        "ipadPro11" => "APP_IPAD_PRO_3GEN_11",
-        _ => dir_name,
-    }
-}
-
 #[derive(Subcommand)]
 pub enum SyncCommand {
     /// Pull (download) all metadata and screenshots for an app
     Pull {
-        /// iOS Bundle ID (e.g., com.example.app)
+        /// Bundle ID (e.g., com.example.app)
         bundle_id: String,
         /// Output directory for downloaded metadata
         #[arg(long)]
@@ -95,10 +77,22 @@ pub enum SyncCommand {
         /// Return screenshot URLs in JSON output instead of downloading
         #[arg(long, default_value = "false")]
         urls_only: bool,
+        /// Screenshots downloaded at the same time
+        #[arg(long, default_value_t = DEFAULT_CONCURRENCY)]
+        concurrency: usize,
+        /// Ignore ETags from the previous pull and fetch everything
+        #[arg(long)]
+        no_cache: bool,
+        /// Platform version to pull (default: the newest version of any platform)
+        #[arg(long, value_enum)]
+        platform: Option<Platform>,
+        /// Only these locales (comma-separated; `*` globs such as `es-*`)
+        #[arg(long, value_delimiter = ',')]
+        locales: Vec<String>,
     },
     /// Push (upload) all metadata and screenshots for an app
     Push {
-        /// iOS Bundle ID (e.g., com.example.app)
+        /// Bundle ID (e.g., com.example.app)
         bundle_id: String,
         /// Directory containing metadata to upload
         #[arg(long)]
@@ -109,6 +103,27 @@ pub enum SyncCommand {
         /// Skip uploading metadata
         #[arg(long, default_value = "false")]
         skip_metadata: bool,
+        /// Skip locales and screenshot sets finished by an interrupted push
+        #[arg(long)]
+        resume: bool,
+        /// Platform version to push to (default: the newest version of any platform)
+        #[arg(long, value_enum)]
+        platform: Option<Platform>,
+        /// Only these locales (comma-separated; `*` globs such as `es-*`)
+        #[arg(long, value_delimiter = ',')]
+        locales: Vec<String>,
+        /// Value for a `{{key}}` placeholder in metadata files (repeatable)
+        #[arg(long = "var", value_name = "KEY=VALUE")]
+        vars: Vec<String>,
+        /// When no version is editable: `never` (fail), `auto` (bump the latest) or a version string
+        #[arg(long, default_value = "auto", value_name = "never|auto|X.Y.Z")]
+        create_version: CreateVersion,
+        /// Component `--create-version auto` bumps
+        #[arg(long, value_enum, default_value_t = BumpPart::Patch)]
+        bump: BumpPart,
+        /// Take the version string from a file (e.g. VERSION) instead of --create-version
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["create_version", "bump"])]
+        version_file: Option<PathBuf>,
     },
 }
 
@@ -124,13 +139,25 @@ pub async fn handle(
             skip_screenshots,
             skip_metadata,
             urls_only,
+            concurrency,
+            no_cache,
+            platform,
+            locales,
         } => {
             handle_pull(
                 bundle_id,
                 output_dir,
-                *skip_screenshots,
-                *skip_metadata,
-                *urls_only,
+                PullParts {
+                    skip_screenshots: *skip_screenshots,
+                    skip_metadata: *skip_metadata,
+                    urls_only: *urls_only,
+                    locales,
+                },
+                FetchOptions {
+                    concurrency: *concurrency,
+                    no_cache: *no_cache,
+                },
+                *platform,
                 client,
             )
             .await
@@ -140,12 +167,31 @@ pub async fn handle(
             metadata_dir,
             skip_screenshots,
             skip_metadata,
+            resume,
+            platform,
+            locales,
+            vars,
+            create_version,
+            bump,
+            version_file,
         } => {
+            let create_version = match (version_file, create_version) {
+                (Some(path), _) => read_version_file(path)?,
+                (None, CreateVersion::Auto(_)) => CreateVersion::Auto(*bump),
+                (None, create) => create.clone(),
+            };
             handle_push(
                 bundle_id,
                 metadata_dir,
-                *skip_screenshots,
-                *skip_metadata,
+                PushParts {
+                    skip_screenshots: *skip_screenshots,
+                    skip_metadata: *skip_metadata,
+                    locales,
+                    vars,
+                    create_version: &create_version,
+                },
+                *resume,
+                *platform,
                 client,
             )
             .await
@@ -154,7 +200,7 @@ pub async fn handle(
 }
 
 /// Lookup app by bundle ID and return the app ID
-async fn lookup_app_by_bundle_id(
+pub async fn lookup_app_by_bundle_id(
     bundle_id: &str,
     client: &AppleClient,
 ) -> Result<String, Box<dyn std::error::Error>> {
@@ -172,22 +218,24 @@ async fn lookup_app_by_bundle_id(
 }
 
 /// States where metadata can be edited
-const EDITABLE_STATES: &[&str] = &["PREPARE_FOR_SUBMISSION", "DEVELOPER_REJECTED", "REJECTED"];
+pub(super) const EDITABLE_STATES: &[&str] =
+    &["PREPARE_FOR_SUBMISSION", "DEVELOPER_REJECTED", "REJECTED"];
 
-/// Get the editable (or latest) App Store version for an app
-async fn get_editable_version(
+/// Get the editable (or latest) App Store version for an app, optionally of one platform
+pub async fn get_editable_version(
     app_id: &str,
+    platform: Option<Platform>,
     client: &AppleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
+    let platform_filter = platform.map(|p| ("filter[platform]", p.api_value()));
     // First try to get editable version (non-live states)
+    let mut query = vec![
+        ("filter[appStoreState]", "PREPARE_FOR_SUBMISSION,READY_FOR_REVIEW,WAITING_FOR_REVIEW,IN_REVIEW,PENDING_DEVELOPER_RELEASE,PENDING_APPLE_RELEASE"),
+        ("limit", "1"),
+    ];
+    query.extend(platform_filter);
     let versions: Value = client
-        .get(
-            &format!("/apps/{app_id}/appStoreVersions"),
-            &[
-                ("filter[appStoreState]", "PREPARE_FOR_SUBMISSION,READY_FOR_REVIEW,WAITING_FOR_REVIEW,IN_REVIEW,PENDING_DEVELOPER_RELEASE,PENDING_APPLE_RELEASE"),
-                ("limit", "1"),
-            ],
-        )
+        .get(&format!("/apps/{app_id}/appStoreVersions"), &query)
         .await?;
 
     if let Some(version) = versions["data"].as_array().and_then(|arr| arr.first()) {
@@ -195,11 +243,10 @@ async fn get_editable_version(
     }
 
     // Fall back to any version
+    let mut query = vec![("limit", "1")];
+    query.extend(platform_filter);
     let versions: Value = client
-        .get(
-            &format!("/apps/{app_id}/appStoreVersions"),
-            &[("limit", "1")],
-        )
+        .get(&format!("/apps/{app_id}/appStoreVersions"), &query)
         .await?;
 
     versions["data"]
@@ -209,36 +256,115 @@ async fn get_editable_version(
         .ok_or_else(|| "No App Store version found".into())
 }
 
-/// Get or create an editable version for pushing metadata
-async fn get_or_create_editable_version(
+/// `sync push --create-version`: what to do when no version is editable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CreateVersion {
+    /// Fail instead of creating a version
+    Never,
+    /// Create the next version, bumping this component of the latest one
+    Auto(BumpPart),
+    /// Push to this version string, creating the version or renaming the editable one
+    Exact(String),
+}
+
+impl std::str::FromStr for CreateVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(Self::Never),
+            "auto" => Ok(Self::Auto(BumpPart::Patch)),
+            // App Store Connect takes no pre-release or build suffix: "2.0.0-rc1" -> "2.0.0"
+            v if release_version(v)
+                .split('.')
+                .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit())) =>
+            {
+                Ok(Self::Exact(release_version(v).to_string()))
+            }
+            _ => Err(format!(
+                "expected never, auto or a version such as 1.2.3, got '{s}'"
+            )),
+        }
+    }
+}
+
+/// `--version-file`: the version string in a file such as `VERSION`.
+fn read_version_file(path: &Path) -> Result<CreateVersion, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    match content.trim().parse() {
+        Ok(CreateVersion::Exact(v)) => Ok(CreateVersion::Exact(v)),
+        _ => Err(format!(
+            "{} does not contain a version string such as 1.2.3",
+            path.display()
+        )
+        .into()),
+    }
+}
+
+/// Get or create an editable version for pushing metadata, optionally of one platform.
+///
+/// Returns the version and the decision taken (`reused`, `renamed` or `created`),
+/// for the command result.
+pub(super) async fn get_or_create_editable_version(
     app_id: &str,
+    platform: Option<Platform>,
+    create: &CreateVersion,
     client: &AppleClient,
-) -> Result<Value, Box<dyn std::error::Error>> {
+) -> Result<(Value, Value), Box<dyn std::error::Error>> {
+    let platform_filter = platform.map(|p| ("filter[platform]", p.api_value()));
     // First check for existing editable versions
+    let states = EDITABLE_STATES.join(",");
+    let mut query = vec![("filter[appStoreState]", states.as_str()), ("limit", "1")];
+    query.extend(platform_filter);
     let versions: Value = client
-        .get(
-            &format!("/apps/{app_id}/appStoreVersions"),
-            &[
-                ("filter[appStoreState]", &EDITABLE_STATES.join(",")),
-                ("limit", "1"),
-            ],
-        )
+        .get(&format!("/apps/{app_id}/appStoreVersions"), &query)
         .await?;
 
     if let Some(version) = versions["data"].as_array().and_then(|arr| arr.first()) {
         let state = version["attributes"]["appStoreState"]
             .as_str()
             .unwrap_or("");
+        let current = version["attributes"]["versionString"]
+            .as_str()
+            .unwrap_or("");
         eprintln!("Found editable version in state: {}", state);
-        return Ok(version.clone());
+        let CreateVersion::Exact(wanted) = create else {
+            return Ok((
+                version.clone(),
+                json!({ "action": "reused", "version": current, "state": state }),
+            ));
+        };
+        if wanted == current {
+            return Ok((
+                version.clone(),
+                json!({ "action": "reused", "version": current, "state": state }),
+            ));
+        }
+        // An explicit version string renames the version already in progress
+        let id = version["id"].as_str().ok_or("version has no id")?;
+        eprintln!("Renaming editable version {current} to {wanted}");
+        let body = json!({
+            "data": {
+                "type": "appStoreVersions",
+                "id": id,
+                "attributes": { "versionString": wanted }
+            }
+        });
+        let renamed: Value = client
+            .patch(&format!("/appStoreVersions/{id}"), &body)
+            .await?;
+        return Ok((
+            renamed["data"].clone(),
+            json!({ "action": "renamed", "version": wanted, "from": current, "state": state }),
+        ));
     }
 
     // Get the latest version to determine the version string for the new version
+    let mut query = vec![("limit", "1")];
+    query.extend(platform_filter);
     let latest_versions: Value = client
-        .get(
-            &format!("/apps/{app_id}/appStoreVersions"),
-            &[("limit", "1")],
-        )
+        .get(&format!("/apps/{app_id}/appStoreVersions"), &query)
         .await?;
 
     let latest_version = latest_versions["data"]
@@ -252,14 +378,28 @@ async fn get_or_create_editable_version(
     let current_version_string = latest_version["attributes"]["versionString"]
         .as_str()
         .unwrap_or("1.0.0");
+    // Without --platform the new version follows the latest version's platform
+    let new_platform = match platform {
+        Some(p) => p.api_value(),
+        None => latest_version["attributes"]["platform"]
+            .as_str()
+            .unwrap_or("IOS"),
+    };
 
+    let new_version_string = match create {
+        CreateVersion::Never => {
+            return Err(format!(
+                "no editable version: {current_version_string} is {current_state}; pass --create-version auto or a version string to create one"
+            )
+            .into())
+        }
+        CreateVersion::Auto(part) => bump_version(current_version_string, *part),
+        CreateVersion::Exact(v) => v.clone(),
+    };
     eprintln!(
         "Current version {} is in state: {} - creating new version",
         current_version_string, current_state
     );
-
-    // Increment the version string (simple approach: increment patch version)
-    let new_version_string = increment_version(current_version_string);
     eprintln!("Creating new version: {}", new_version_string);
 
     // Create a new version
@@ -268,7 +408,7 @@ async fn get_or_create_editable_version(
             "type": "appStoreVersions",
             "attributes": {
                 "versionString": new_version_string,
-                "platform": "IOS"
+                "platform": new_platform
             },
             "relationships": {
                 "app": {
@@ -283,36 +423,27 @@ async fn get_or_create_editable_version(
 
     let result: Value = client.post("/appStoreVersions", &body).await?;
 
-    result["data"]
+    let version = result["data"]
         .as_object()
         .map(|_| result["data"].clone())
-        .ok_or_else(|| "Failed to create new version".into())
-}
-
-/// Increment a version string (e.g., "1.2.3" -> "1.2.4")
-fn increment_version(version: &str) -> String {
-    let parts: Vec<&str> = version.split('.').collect();
-    match parts.len() {
-        3 => {
-            let major = parts[0];
-            let minor = parts[1];
-            let patch: u32 = parts[2].parse().unwrap_or(0);
-            format!("{}.{}.{}", major, minor, patch + 1)
-        }
-        2 => {
-            let major = parts[0];
-            let minor: u32 = parts[1].parse().unwrap_or(0);
-            format!("{}.{}", major, minor + 1)
-        }
-        _ => format!("{}.1", version),
-    }
+        .ok_or("Failed to create new version")?;
+    Ok((
+        version,
+        json!({
+            "action": "created",
+            "version": new_version_string,
+            "previous": current_version_string,
+            "previous_state": current_state,
+        }),
+    ))
 }
 
 /// States where app info can be edited
-const APP_INFO_EDITABLE_STATES: &[&str] = &["READY_FOR_SUBMISSION", "PREPARE_FOR_SUBMISSION"];
+pub(super) const APP_INFO_EDITABLE_STATES: &[&str] =
+    &["READY_FOR_SUBMISSION", "PREPARE_FOR_SUBMISSION"];
 
 /// Get the latest app info for an app and check if it's editable
-async fn get_app_info(
+pub async fn get_app_info(
     app_id: &str,
     client: &AppleClient,
 ) -> Result<(Value, bool), Box<dyn std::error::Error>> {
@@ -343,19 +474,64 @@ async fn get_app_info(
     Err("No app info found".into())
 }
 
-async fn handle_pull(
+/// Pull only the metadata text files into `output_dir`, bypassing the pull manifest.
+///
+/// Used by `sync check` to compare remote content against a local directory.
+pub async fn pull_metadata(
     bundle_id: &str,
     output_dir: &PathBuf,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let fetch = FetchOptions {
+        concurrency: DEFAULT_CONCURRENCY,
+        no_cache: true,
+    };
+    let parts = PullParts {
+        skip_screenshots: true,
+        skip_metadata: false,
+        urls_only: false,
+        locales: &[],
+    };
+    handle_pull(bundle_id, output_dir, parts, fetch, None, client).await
+}
+
+/// Which parts of the listing a pull fetches.
+struct PullParts<'a> {
     skip_screenshots: bool,
     skip_metadata: bool,
     urls_only: bool,
+    locales: &'a [String],
+}
+
+/// Which parts of the listing a push uploads.
+struct PushParts<'a> {
+    skip_screenshots: bool,
+    skip_metadata: bool,
+    locales: &'a [String],
+    /// `--var KEY=VALUE` placeholder values
+    vars: &'a [String],
+    create_version: &'a CreateVersion,
+}
+
+async fn handle_pull(
+    bundle_id: &str,
+    output_dir: &PathBuf,
+    parts: PullParts<'_>,
+    fetch: FetchOptions,
+    platform: Option<Platform>,
     client: &AppleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
+    let PullParts {
+        skip_screenshots,
+        skip_metadata,
+        urls_only,
+        locales,
+    } = parts;
     eprintln!("Looking up app: {}", bundle_id);
     let app_id = lookup_app_by_bundle_id(bundle_id, client).await?;
     eprintln!("Found app ID: {}", app_id);
 
-    let version = get_editable_version(&app_id, client).await?;
+    let version = get_editable_version(&app_id, platform, client).await?;
     let version_id = version["id"].as_str().ok_or("Version ID not found")?;
     eprintln!("Using version: {}", version_id);
 
@@ -370,116 +546,155 @@ async fn handle_pull(
     let mut screenshots_downloaded = 0u32;
     // For urls_only mode: locale -> device -> [urls]
     let mut screenshot_urls: HashMap<String, HashMap<String, Vec<String>>> = HashMap::new();
+    let mut downloads = Vec::new();
+    let mut manifest = PullManifest::open(output_dir, fetch.no_cache);
+    let mut metadata_unchanged = false;
+    let mut screenshots_unchanged = 0u32;
 
     if !skip_metadata {
-        // Get version localizations (description, keywords, whatsNew, etc.)
-        eprintln!("Fetching version localizations...");
-        let version_locs: Value = client
-            .get(
-                &format!("/appStoreVersions/{version_id}/appStoreVersionLocalizations"),
+        // Version localizations (description, keywords, whatsNew, etc.) and app info
+        // localizations (name, subtitle), skipped when neither changed since the last pull
+        eprintln!("Fetching localizations...");
+        let version_path = format!("/appStoreVersions/{version_id}/appStoreVersionLocalizations");
+        let app_info_path = format!("/appInfos/{app_info_id}/appInfoLocalizations");
+        let version_key = format!("apple:{version_id}:versionLocalizations");
+        let app_info_key = format!("apple:{app_info_id}:appInfoLocalizations");
+        let version_fetch = client
+            .get_if_none_match(
+                &version_path,
                 &[("limit", "100")],
+                manifest.etag(&version_key),
             )
             .await?;
-
-        // Get app info localizations (name, subtitle)
-        eprintln!("Fetching app info localizations...");
-        let app_info_locs: Value = client
-            .get(
-                &format!("/appInfos/{app_info_id}/appInfoLocalizations"),
+        let app_info_fetch = client
+            .get_if_none_match(
+                &app_info_path,
                 &[("limit", "100")],
+                manifest.etag(&app_info_key),
             )
             .await?;
 
-        // Build a map of app info localizations by locale
-        let mut app_info_by_locale: HashMap<String, &Value> = HashMap::new();
-        if let Some(locs) = app_info_locs["data"].as_array() {
-            for loc in locs {
-                if let Some(locale) = loc["attributes"]["locale"].as_str() {
-                    app_info_by_locale.insert(locale.to_string(), loc);
+        if version_fetch.is_none() && app_info_fetch.is_none() {
+            eprintln!("Metadata unchanged since last pull");
+            metadata_unchanged = true;
+        } else {
+            // Both listings are needed to write a locale, so refetch the unchanged one
+            let (version_locs, version_etag) = match version_fetch {
+                Some(fetched) => fetched,
+                None => (client.get(&version_path, &[("limit", "100")]).await?, None),
+            };
+            let (app_info_locs, app_info_etag) = match app_info_fetch {
+                Some(fetched) => fetched,
+                None => (client.get(&app_info_path, &[("limit", "100")]).await?, None),
+            };
+            // A partial pull must not mark the other locales as up to date
+            if version_etag.is_some() && locales.is_empty() {
+                manifest.set(&version_key, version_etag);
+            }
+            if app_info_etag.is_some() && locales.is_empty() {
+                manifest.set(&app_info_key, app_info_etag);
+            }
+
+            // Build a map of app info localizations by locale
+            let mut app_info_by_locale: HashMap<String, &Value> = HashMap::new();
+            if let Some(locs) = app_info_locs["data"].as_array() {
+                for loc in locs {
+                    if let Some(locale) = loc["attributes"]["locale"].as_str() {
+                        app_info_by_locale.insert(locale.to_string(), loc);
+                    }
                 }
             }
-        }
 
-        // Process version localizations
-        if let Some(locs) = version_locs["data"].as_array() {
-            for loc in locs {
-                let asc_locale = loc["attributes"]["locale"].as_str().unwrap_or("en-US");
-                let internal_locale = asc_to_internal_locale(asc_locale);
-                let locale_dir = output_dir.join(&internal_locale);
-                fs::create_dir_all(&locale_dir).await?;
+            // Process version localizations
+            if let Some(locs) = version_locs["data"].as_array() {
+                for loc in locs {
+                    let asc_locale = loc["attributes"]["locale"].as_str().unwrap_or("en-US");
+                    let internal_locale = asc_to_internal_locale(asc_locale);
+                    if !locale_selected(locales, &internal_locale)
+                        && !locale_selected(locales, asc_locale)
+                    {
+                        continue;
+                    }
+                    let locale_dir = output_dir.join(&internal_locale);
+                    fs::create_dir_all(&locale_dir).await?;
 
-                // Version-level metadata
-                let attrs = &loc["attributes"];
+                    // Version-level metadata
+                    let attrs = &loc["attributes"];
 
-                // description.txt
-                if let Some(desc) = attrs["description"].as_str() {
-                    if !desc.is_empty() {
-                        fs::write(locale_dir.join("description.txt"), desc).await?;
+                    // description.txt
+                    if let Some(desc) = attrs["description"].as_str() {
+                        if !desc.is_empty() {
+                            fs::write(locale_dir.join("description.txt"), desc).await?;
+                        }
                     }
-                }
 
-                // keywords.txt
-                if let Some(keywords) = attrs["keywords"].as_str() {
-                    if !keywords.is_empty() {
-                        fs::write(locale_dir.join("keywords.txt"), keywords).await?;
+                    // keywords.txt
+                    if let Some(keywords) = attrs["keywords"].as_str() {
+                        if !keywords.is_empty() {
+                            fs::write(locale_dir.join("keywords.txt"), keywords).await?;
+                        }
                     }
-                }
 
-                // release_notes.txt (whatsNew)
-                if let Some(whats_new) = attrs["whatsNew"].as_str() {
-                    if !whats_new.is_empty() {
-                        fs::write(locale_dir.join("release_notes.txt"), whats_new).await?;
+                    // release_notes.txt (whatsNew)
+                    if let Some(whats_new) = attrs["whatsNew"].as_str() {
+                        if !whats_new.is_empty() {
+                            fs::write(locale_dir.join("release_notes.txt"), whats_new).await?;
+                        }
                     }
-                }
 
-                // promotional_text.txt
-                if let Some(promo) = attrs["promotionalText"].as_str() {
-                    if !promo.is_empty() {
-                        fs::write(locale_dir.join("promotional_text.txt"), promo).await?;
+                    // promotional_text.txt
+                    if let Some(promo) = attrs["promotionalText"].as_str() {
+                        if !promo.is_empty() {
+                            fs::write(locale_dir.join("promotional_text.txt"), promo).await?;
+                        }
                     }
-                }
 
-                // marketing_url.txt
-                if let Some(url) = attrs["marketingUrl"].as_str() {
-                    if !url.is_empty() {
-                        fs::write(locale_dir.join("marketing_url.txt"), url).await?;
+                    // marketing_url.txt
+                    if let Some(url) = attrs["marketingUrl"].as_str() {
+                        if !url.is_empty() {
+                            fs::write(locale_dir.join("marketing_url.txt"), url).await?;
+                        }
                     }
-                }
 
-                // support_url.txt
-                if let Some(url) = attrs["supportUrl"].as_str() {
-                    if !url.is_empty() {
-                        fs::write(locale_dir.join("support_url.txt"), url).await?;
+                    // support_url.txt
+                    if let Some(url) = attrs["supportUrl"].as_str() {
+                        if !url.is_empty() {
+                            fs::write(locale_dir.join("support_url.txt"), url).await?;
+                        }
                     }
-                }
 
-                // App info-level metadata (name, subtitle)
-                if let Some(app_info_loc) = app_info_by_locale.get(asc_locale) {
-                    let app_attrs = &app_info_loc["attributes"];
+                    // App info-level metadata (name, subtitle)
+                    if let Some(app_info_loc) = app_info_by_locale.get(asc_locale) {
+                        let app_attrs = &app_info_loc["attributes"];
 
-                    // name.txt
-                    if let Some(name) = app_attrs["name"].as_str() {
-                        if !name.is_empty() {
-                            fs::write(locale_dir.join("name.txt"), name).await?;
+                        // name.txt
+                        if let Some(name) = app_attrs["name"].as_str() {
+                            if !name.is_empty() {
+                                fs::write(locale_dir.join("name.txt"), name).await?;
+                            }
                         }
-                    }
 
-                    // subtitle.txt
-                    if let Some(subtitle) = app_attrs["subtitle"].as_str() {
-                        if !subtitle.is_empty() {
-                            fs::write(locale_dir.join("subtitle.txt"), subtitle).await?;
+                        // subtitle.txt
+                        if let Some(subtitle) = app_attrs["subtitle"].as_str() {
+                            if !subtitle.is_empty() {
+                                fs::write(locale_dir.join("subtitle.txt"), subtitle).await?;
+                            }
                         }
-                    }
 
-                    // privacy_url.txt
-                    if let Some(url) = app_attrs["privacyPolicyUrl"].as_str() {
-                        if !url.is_empty() {
-                            fs::write(locale_dir.join("privacy_url.txt"), url).await?;
+                        // privacy_url.txt
+                        if let Some(url) = app_attrs["privacyPolicyUrl"].as_str() {
+                            if !url.is_empty() {
+                                fs::write(locale_dir.join("privacy_url.txt"), url).await?;
+                            }
                         }
                     }
-                }
 
-                locales_downloaded.push(internal_locale);
+                    stream::emit(
+                        "locale",
+                        json!({ "locale": internal_locale, "status": "downloaded" }),
+                    );
+                    locales_downloaded.push(internal_locale);
+                }
             }
         }
     }
@@ -489,6 +704,7 @@ async fn handle_pull(
             "Fetching screenshots{}...",
             if urls_only { " (URLs only)" } else { "" }
         );
+        let dirs = DisplayTypeDirs::load();
 
         // Get all version localizations for screenshot sets
         let version_locs: Value = client
@@ -503,6 +719,11 @@ async fn handle_pull(
                 let loc_id = loc["id"].as_str().unwrap_or("");
                 let asc_locale = loc["attributes"]["locale"].as_str().unwrap_or("en-US");
                 let internal_locale = asc_to_internal_locale(asc_locale);
+                if !locale_selected(locales, &internal_locale)
+                    && !locale_selected(locales, asc_locale)
+                {
+                    continue;
+                }
 
                 // Get screenshot sets for this localization
                 let sets: Value = client
@@ -518,7 +739,12 @@ async fn handle_pull(
                         let display_type = set["attributes"]["screenshotDisplayType"]
                             .as_str()
                             .unwrap_or("");
-                        let dir_name = display_type_to_dir(display_type);
+                        let dir_name = dirs.dir(display_type).unwrap_or_else(|| {
+                            eprintln!(
+                                "  Warning: no directory mapped for {display_type}, using its raw name (map it under [screenshot_dirs] in the config)"
+                            );
+                            display_type
+                        });
 
                         // Get screenshots in this set
                         let screenshots: Value = client
@@ -528,6 +754,7 @@ async fn handle_pull(
                             )
                             .await?;
 
+                        let mut pulled = Vec::new();
                         if let Some(ss_arr) = screenshots["data"].as_array() {
                             for (idx, ss) in ss_arr.iter().enumerate() {
                                 if let Some(url) =
@@ -556,43 +783,59 @@ async fn handle_pull(
                                             .push(download_url);
                                         screenshots_downloaded += 1;
                                     } else if !skip_screenshots {
-                                        // Create screenshots directory and download
-                                        let ss_dir = output_dir
-                                            .join(&internal_locale)
-                                            .join("screenshots")
-                                            .join(dir_name);
-                                        fs::create_dir_all(&ss_dir).await?;
-
                                         let filename = format!("{:02}.png", idx + 1);
-                                        let file_path = ss_dir.join(&filename);
-
-                                        match download_image(&download_url, &file_path).await {
-                                            Ok(_) => {
-                                                screenshots_downloaded += 1;
-                                                eprintln!(
-                                                    "  Downloaded: {}/{}/screenshots/{}/{}",
-                                                    internal_locale, asc_locale, dir_name, filename
-                                                );
-                                            }
-                                            Err(e) => {
-                                                eprintln!("  Failed to download screenshot: {}", e);
-                                            }
-                                        }
+                                        downloads.push(Download {
+                                            url: download_url,
+                                            path: output_dir
+                                                .join(&internal_locale)
+                                                .join("screenshots")
+                                                .join(dir_name)
+                                                .join(&filename),
+                                            locale: internal_locale.clone(),
+                                            file: format!("screenshots/{dir_name}/{filename}"),
+                                            etag: None,
+                                        });
+                                        pulled.push(filename);
                                     }
                                 }
                             }
                         }
+                        if !pulled.is_empty() {
+                            write_order(
+                                &output_dir
+                                    .join(&internal_locale)
+                                    .join("screenshots")
+                                    .join(dir_name),
+                                &pulled,
+                            )?;
+                        }
                     }
                 }
             }
         }
     }
 
+    if !downloads.is_empty() {
+        eprintln!("Downloading {} screenshot(s)...", downloads.len());
+        for d in &mut downloads {
+            d.etag = manifest.etag(&d.key()).map(String::from);
+        }
+        let done = download_all(downloads, fetch.concurrency).await;
+        screenshots_downloaded += done.downloaded;
+        screenshots_unchanged = done.unchanged;
+        for (key, etag) in done.etags {
+            manifest.set(&key, etag);
+        }
+    }
+    manifest.save();
+
     let mut result = json!({
         "success": true,
         "app_id": app_id,
         "locales_downloaded": locales_downloaded,
+        "metadata_unchanged": metadata_unchanged,
         "screenshots_downloaded": screenshots_downloaded,
+        "screenshots_unchanged": screenshots_unchanged,
         "output_dir": output_dir.to_string_lossy()
     });
 
@@ -603,44 +846,57 @@ async fn handle_pull(
     Ok(result)
 }
 
-async fn download_image(url: &str, path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    let response = client.get(url).send().await?;
-
-    if !response.status().is_success() {
-        return Err(format!("HTTP {}: {}", response.status(), url).into());
-    }
-
-    let bytes = response.bytes().await?;
-    fs::write(path, bytes).await?;
-    Ok(())
-}
-
 async fn handle_push(
     bundle_id: &str,
     metadata_dir: &PathBuf,
-    skip_screenshots: bool,
-    skip_metadata: bool,
+    parts: PushParts<'_>,
+    resume: bool,
+    platform: Option<Platform>,
     client: &AppleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
+    let PushParts {
+        skip_screenshots,
+        skip_metadata,
+        locales,
+        vars,
+        create_version,
+    } = parts;
+    let cli_vars = template::parse_vars(vars)?;
     eprintln!("Looking up app: {}", bundle_id);
     let app_id = lookup_app_by_bundle_id(bundle_id, client).await?;
     eprintln!("Found app ID: {}", app_id);
+    let mut checkpoint = PushCheckpoint::open(metadata_dir, bundle_id, resume);
+    let dirs = DisplayTypeDirs::load();
 
     // Get or create an editable version (creates new version if current is not editable)
-    let version = get_or_create_editable_version(&app_id, client).await?;
+    let (version, version_decision) =
+        get_or_create_editable_version(&app_id, platform, create_version, client).await?;
     let version_id = version["id"].as_str().ok_or("Version ID not found")?;
     let version_string = version["attributes"]["versionString"]
         .as_str()
         .unwrap_or("unknown");
     eprintln!("Using version: {} ({})", version_id, version_string);
 
+    // Placeholder values resolved from the store; --var overrides them per locale
+    let mut resolved_vars = BTreeMap::from([("date".to_string(), template::today())]);
+    if let Some(v) = version["attributes"]["versionString"].as_str() {
+        resolved_vars.insert("version".to_string(), v.to_string());
+    }
+
     let (app_info, app_info_editable) = get_app_info(&app_id, client).await?;
     let app_info_id = app_info["id"].as_str().ok_or("App Info ID not found")?;
 
     if !app_info_editable {
         eprintln!("Note: App info (name/subtitle) may not be editable in current state");
     }
+    // Earlier staged name/subtitle changes land first, so the files pushed below win
+    let staged_app_info = if app_info_editable {
+        app_info_stage::apply_pending(&app_id, client).await?
+    } else {
+        None
+    };
+    let stage_path = Stage::path()?;
+    let mut staged = Stage::load(&stage_path)?;
 
     // Get existing localizations
     let version_locs: Value = client
@@ -658,30 +914,39 @@ async fn handle_push(
         .await?;
 
     // Build maps of existing localizations by locale
+    // Their current attributes are what changed fields are detected against
     let mut version_loc_map: HashMap<String, String> = HashMap::new();
+    let mut version_loc_attrs: HashMap<String, Value> = HashMap::new();
     if let Some(locs) = version_locs["data"].as_array() {
         for loc in locs {
             if let (Some(locale), Some(id)) =
                 (loc["attributes"]["locale"].as_str(), loc["id"].as_str())
             {
                 version_loc_map.insert(locale.to_string(), id.to_string());
+                version_loc_attrs.insert(locale.to_string(), loc["attributes"].clone());
             }
         }
     }
 
     let mut app_info_loc_map: HashMap<String, String> = HashMap::new();
+    let mut app_info_loc_attrs: HashMap<String, Value> = HashMap::new();
     if let Some(locs) = app_info_locs["data"].as_array() {
         for loc in locs {
             if let (Some(locale), Some(id)) =
                 (loc["attributes"]["locale"].as_str(), loc["id"].as_str())
             {
                 app_info_loc_map.insert(locale.to_string(), id.to_string());
+                app_info_loc_attrs.insert(locale.to_string(), loc["attributes"].clone());
             }
         }
     }
 
     let mut locales_pushed = Vec::new();
+    let mut field_changes = serde_json::Map::new();
     let mut screenshots_uploaded = 0u32;
+    let mut skipped = Vec::new();
+    let mut failed = Vec::new();
+    let mut warnings = Vec::new();
 
     // Scan metadata directory for locale folders
     let mut entries = fs::read_dir(metadata_dir).await?;
@@ -706,115 +971,165 @@ async fn handle_push(
         }
 
         let asc_locale = internal_to_asc_locale(&internal_locale);
+        if !locale_selected(locales, &internal_locale) && !locale_selected(locales, &asc_locale) {
+            continue;
+        }
         eprintln!(
             "Processing locale: {} (ASC: {})",
             internal_locale, asc_locale
         );
 
-        if !skip_metadata {
-            // Read metadata files
-            let name = read_file_if_exists(&path.join("name.txt")).await;
-            let subtitle = read_file_if_exists(&path.join("subtitle.txt")).await;
-            let description = read_file_if_exists(&path.join("description.txt")).await;
-            let keywords = read_file_if_exists(&path.join("keywords.txt")).await;
-            let whats_new = read_file_if_exists(&path.join("release_notes.txt")).await;
-            let promo_text = read_file_if_exists(&path.join("promotional_text.txt")).await;
-            let marketing_url = read_file_if_exists(&path.join("marketing_url.txt")).await;
-            let support_url = read_file_if_exists(&path.join("support_url.txt")).await;
+        if !skip_metadata && checkpoint.is_done(&internal_locale) {
+            eprintln!("  Metadata already pushed");
+            skipped.push(internal_locale.clone());
+            locales_pushed.push(internal_locale.clone());
+        } else if !skip_metadata {
+            // Failed metadata writes keep the locale out of the checkpoint and `success`
+            let mut metadata_errors: Vec<String> = Vec::new();
+            // Read metadata files and keep only the fields that differ from the store
+            let mut version_fields = [
+                (
+                    "description",
+                    read_file_if_exists(&path.join("description.txt")).await,
+                ),
+                (
+                    "keywords",
+                    read_file_if_exists(&path.join("keywords.txt")).await,
+                ),
+                (
+                    "whatsNew",
+                    read_file_if_exists(&path.join("release_notes.txt")).await,
+                ),
+                (
+                    "promotionalText",
+                    read_file_if_exists(&path.join("promotional_text.txt")).await,
+                ),
+                (
+                    "marketingUrl",
+                    read_file_if_exists(&path.join("marketing_url.txt")).await,
+                ),
+                (
+                    "supportUrl",
+                    read_file_if_exists(&path.join("support_url.txt")).await,
+                ),
+            ];
+            let mut app_info_fields = [
+                ("name", read_file_if_exists(&path.join("name.txt")).await),
+                (
+                    "subtitle",
+                    read_file_if_exists(&path.join("subtitle.txt")).await,
+                ),
+            ];
+            let remote = |attrs: &HashMap<String, Value>| {
+                attrs.get(&asc_locale).cloned().unwrap_or(Value::Null)
+            };
+
+            // {{app_name}} is the name this push leaves the locale with
+            let mut vars = resolved_vars.clone();
+            vars.insert("locale".to_string(), internal_locale.clone());
+            let app_name = app_info_fields[0]
+                .1
+                .clone()
+                .filter(|name| !name.contains("{{"))
+                .or_else(|| {
+                    remote(&app_info_loc_attrs)["name"]
+                        .as_str()
+                        .map(String::from)
+                });
+            if let Some(name) = app_name {
+                vars.insert("app_name".to_string(), name);
+            }
+            vars.extend(cli_vars.clone());
+            template::expand_fields(&mut version_fields, &vars)
+                .and_then(|_| template::expand_fields(&mut app_info_fields, &vars))
+                .map_err(|e| format!("{internal_locale}: {e}"))?;
+            let (mut attrs, unchanged) =
+                changed_fields(&version_fields, &remote(&version_loc_attrs));
+            let mut fields = field_report(&attrs, &unchanged);
 
             // Update or create version localization
-            if description.is_some()
-                || keywords.is_some()
-                || whats_new.is_some()
-                || promo_text.is_some()
-                || marketing_url.is_some()
-                || support_url.is_some()
-            {
-                let mut attrs = json!({});
-                if let Some(v) = &description {
-                    attrs["description"] = json!(v);
-                }
-                if let Some(v) = &keywords {
-                    attrs["keywords"] = json!(v);
+            if attrs.is_empty() {
+                if !unchanged.is_empty() {
+                    eprintln!("  Version localization unchanged");
                 }
-                if let Some(v) = &whats_new {
-                    attrs["whatsNew"] = json!(v);
-                }
-                if let Some(v) = &promo_text {
-                    attrs["promotionalText"] = json!(v);
-                }
-                if let Some(v) = &marketing_url {
-                    attrs["marketingUrl"] = json!(v);
-                }
-                if let Some(v) = &support_url {
-                    attrs["supportUrl"] = json!(v);
-                }
-
-                if let Some(loc_id) = version_loc_map.get(&asc_locale) {
-                    // Update existing
-                    let body = json!({
-                        "data": {
-                            "type": "appStoreVersionLocalizations",
-                            "id": loc_id,
-                            "attributes": attrs
-                        }
-                    });
-                    if let Err(e) = client
-                        .patch(&format!("/appStoreVersionLocalizations/{loc_id}"), &body)
-                        .await
-                    {
-                        eprintln!(
-                            "  Warning: Could not update version localization for {}: {}",
-                            asc_locale, e
-                        );
-                    } else {
-                        eprintln!("  Updated version localization");
+            } else if let Some(loc_id) = version_loc_map.get(&asc_locale) {
+                // Update existing
+                let body = json!({
+                    "data": {
+                        "type": "appStoreVersionLocalizations",
+                        "id": loc_id,
+                        "attributes": attrs
                     }
+                });
+                if let Err(e) = client
+                    .patch(&format!("/appStoreVersionLocalizations/{loc_id}"), &body)
+                    .await
+                {
+                    metadata_errors.push(format!("update version localization: {e}"));
+                    eprintln!(
+                        "  Warning: Could not update version localization for {}: {}",
+                        asc_locale, e
+                    );
                 } else {
-                    // Create new
-                    attrs["locale"] = json!(asc_locale);
-                    let body = json!({
-                        "data": {
-                            "type": "appStoreVersionLocalizations",
-                            "attributes": attrs,
-                            "relationships": {
-                                "appStoreVersion": {
-                                    "data": {
-                                        "type": "appStoreVersions",
-                                        "id": version_id
-                                    }
+                    eprintln!("  Updated version localization ({} field(s))", attrs.len());
+                }
+            } else {
+                // Create new
+                attrs.insert("locale".to_string(), json!(asc_locale));
+                let body = json!({
+                    "data": {
+                        "type": "appStoreVersionLocalizations",
+                        "attributes": attrs,
+                        "relationships": {
+                            "appStoreVersion": {
+                                "data": {
+                                    "type": "appStoreVersions",
+                                    "id": version_id
                                 }
                             }
                         }
-                    });
-                    match client.post("/appStoreVersionLocalizations", &body).await {
-                        Ok(result) => {
-                            if let Some(id) = result["data"]["id"].as_str() {
-                                version_loc_map.insert(asc_locale.clone(), id.to_string());
-                            }
-                            eprintln!("  Created version localization");
-                        }
-                        Err(e) => {
-                            eprintln!(
-                                "  Warning: Could not create version localization for {}: {}",
-                                asc_locale, e
-                            );
+                    }
+                });
+                match client.post("/appStoreVersionLocalizations", &body).await {
+                    Ok(result) => {
+                        if let Some(id) = result["data"]["id"].as_str() {
+                            version_loc_map.insert(asc_locale.clone(), id.to_string());
                         }
+                        eprintln!("  Created version localization");
+                    }
+                    Err(e) => {
+                        metadata_errors.push(format!("create version localization: {e}"));
+                        eprintln!(
+                            "  Warning: Could not create version localization for {}: {}",
+                            asc_locale, e
+                        );
                     }
                 }
             }
 
             // Update or create app info localization (name, subtitle)
             // Skip if app info is not editable
-            if (name.is_some() || subtitle.is_some()) && app_info_editable {
-                let mut attrs = json!({});
-                if let Some(v) = &name {
-                    attrs["name"] = json!(v);
+            let (mut attrs, unchanged) =
+                changed_fields(&app_info_fields, &remote(&app_info_loc_attrs));
+            if attrs.is_empty() {
+                if !unchanged.is_empty() {
+                    eprintln!("  App info localization unchanged");
                 }
-                if let Some(v) = &subtitle {
-                    attrs["subtitle"] = json!(v);
+                fields.extend(field_report(&attrs, &unchanged));
+            } else if !app_info_editable {
+                // Keep the change for when a new version opens up the app info
+                eprintln!("  Staging name/subtitle until the app info is editable");
+                staged.add(
+                    &app_id,
+                    &asc_locale,
+                    attrs.get("name").and_then(Value::as_str),
+                    attrs.get("subtitle").and_then(Value::as_str),
+                );
+                for field in attrs.keys() {
+                    fields.insert(field.clone(), json!("staged"));
                 }
-
+            } else {
+                fields.extend(field_report(&attrs, &unchanged));
                 if let Some(loc_id) = app_info_loc_map.get(&asc_locale) {
                     // Update existing - use match to handle errors gracefully
                     let body = json!({
@@ -829,11 +1144,14 @@ async fn handle_push(
                         .await
                     {
                         Ok(_) => eprintln!("  Updated app info localization"),
-                        Err(e) => eprintln!("  Warning: Could not update name/subtitle: {}", e),
+                        Err(e) => {
+                            metadata_errors.push(format!("update name/subtitle: {e}"));
+                            eprintln!("  Warning: Could not update name/subtitle: {}", e)
+                        }
                     }
                 } else {
                     // Create new - handle errors gracefully
-                    attrs["locale"] = json!(asc_locale);
+                    attrs.insert("locale".to_string(), json!(asc_locale));
                     let body = json!({
                         "data": {
                             "type": "appInfoLocalizations",
@@ -855,14 +1173,34 @@ async fn handle_push(
                             }
                             eprintln!("  Created app info localization");
                         }
-                        Err(e) => eprintln!("  Warning: Could not create name/subtitle: {}", e),
+                        Err(e) => {
+                            metadata_errors.push(format!("create name/subtitle: {e}"));
+                            eprintln!("  Warning: Could not create name/subtitle: {}", e)
+                        }
                     }
                 }
-            } else if name.is_some() || subtitle.is_some() {
-                eprintln!("  Skipping name/subtitle (app info not editable)");
             }
 
-            locales_pushed.push(internal_locale.clone());
+            if metadata_errors.is_empty() {
+                checkpoint.mark_done(internal_locale.clone());
+                stream::emit(
+                    "locale",
+                    json!({ "locale": internal_locale, "status": "pushed", "fields": fields }),
+                );
+            } else {
+                let reason = metadata_errors.join("; ");
+                stream::emit(
+                    "locale",
+                    json!({ "locale": internal_locale, "status": "failed", "error": reason }),
+                );
+                failed.push(json!({ "locale": internal_locale, "reason": reason }));
+            }
+            if !fields.is_empty() {
+                field_changes.insert(internal_locale.clone(), Value::Object(fields));
+            }
+            if metadata_errors.is_empty() {
+                locales_pushed.push(internal_locale.clone());
+            }
         }
 
         if !skip_screenshots {
@@ -940,7 +1278,14 @@ async fn handle_push(
                         }
 
                         let dir_name = ss_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                        let display_type = dir_to_display_type(dir_name);
+                        // Unmapped directories are taken to be raw display type names
+                        let display_type = dirs.display_type(dir_name).unwrap_or(dir_name);
+                        let set_key = format!("{internal_locale}/{dir_name}");
+                        if checkpoint.is_done(&set_key) {
+                            eprintln!("  Skipping {} (already uploaded)", dir_name);
+                            skipped.push(set_key);
+                            continue;
+                        }
 
                         // Get or create screenshot set
                         let set_id = if let Some(id) = set_map.get(display_type) {
@@ -1002,21 +1347,10 @@ async fn handle_push(
                             }
 
                             // Upload new screenshots
-                            let mut images: Vec<PathBuf> = Vec::new();
-                            let mut img_entries = fs::read_dir(&ss_path).await?;
-                            while let Some(img_entry) = img_entries.next_entry().await? {
-                                let img_path = img_entry.path();
-                                if img_path
-                                    .extension()
-                                    .map(|e| e == "png" || e == "jpg" || e == "jpeg")
-                                    .unwrap_or(false)
-                                {
-                                    images.push(img_path);
-                                }
-                            }
-                            images.sort();
+                            let images = ordered_images(&ss_path)?;
 
-                            let mut uploaded_ids = Vec::new();
+                            let mut uploaded = Vec::new();
+                            let mut set_failed = false;
                             for (idx, img_path) in images.iter().take(10).enumerate() {
                                 let filename = img_path
                                     .file_name()
@@ -1024,8 +1358,8 @@ async fn handle_push(
                                     .unwrap_or("screenshot.png");
 
                                 match upload_screenshot(client, &set_id, img_path, filename).await {
-                                    Ok(screenshot_id) => {
-                                        uploaded_ids.push(screenshot_id);
+                                    Ok(asset) => {
+                                        uploaded.push((asset, filename));
                                         screenshots_uploaded += 1;
                                         eprintln!(
                                             "  Uploaded: {}/{} ({}/{})",
@@ -1034,21 +1368,76 @@ async fn handle_push(
                                             idx + 1,
                                             images.len().min(10)
                                         );
+                                        stream::emit(
+                                            "screenshot",
+                                            json!({
+                                                "locale": internal_locale,
+                                                "file": format!("{dir_name}/{filename}"),
+                                                "status": "uploaded",
+                                            }),
+                                        );
                                     }
                                     Err(e) => {
+                                        set_failed = true;
                                         eprintln!("  Failed to upload {}: {}", filename, e);
+                                        failed.push(json!({
+                                            "locale": internal_locale,
+                                            "file": format!("{dir_name}/{filename}"),
+                                            "reason": e.to_string(),
+                                        }));
+                                        stream::emit(
+                                            "screenshot",
+                                            json!({
+                                                "locale": internal_locale,
+                                                "file": format!("{dir_name}/{filename}"),
+                                                "status": "failed",
+                                                "error": e.to_string(),
+                                            }),
+                                        );
                                     }
                                 }
                             }
 
+                            // A committed upload can still fail Apple's processing
+                            for (asset, filename) in &uploaded {
+                                let file = format!("{dir_name}/{filename}");
+                                match await_delivery(client, &asset.path, &asset.checksum).await {
+                                    Ok(Delivery::Complete) => {}
+                                    Ok(Delivery::Failed(reason)) => {
+                                        set_failed = true;
+                                        eprintln!("  Processing failed for {}: {}", file, reason);
+                                        stream::emit(
+                                            "screenshot",
+                                            json!({
+                                                "locale": internal_locale,
+                                                "file": file,
+                                                "status": "failed",
+                                                "error": reason,
+                                            }),
+                                        );
+                                        failed.push(json!({
+                                            "locale": internal_locale,
+                                            "file": file,
+                                            "reason": reason,
+                                        }));
+                                    }
+                                    Ok(Delivery::Pending) => warnings.push(format!(
+                                        "{internal_locale}/{file}: still processing"
+                                    )),
+                                    Err(e) => warnings.push(format!(
+                                        "{internal_locale}/{file}: could not check processing state: {e}"
+                                    )),
+                                }
+                            }
+
                             // Reorder screenshots if we uploaded multiple
-                            if uploaded_ids.len() > 1 {
-                                let data: Vec<Value> = uploaded_ids
+                            if uploaded.len() > 1 {
+                                let data: Vec<Value> = uploaded
                                     .iter()
-                                    .map(|id| {
+                                    .map(|(asset, _)| {
                                         json!({
                                             "type": "appScreenshots",
-                                            "id": id
+                                            "id": asset.id
                                         })
                                     })
                                     .collect();
@@ -1060,6 +1449,9 @@ async fn handle_push(
                                     )
                                     .await;
                             }
+                            if !set_failed {
+                                checkpoint.mark_done(set_key);
+                            }
                         }
                     }
                 }
@@ -1067,12 +1459,34 @@ async fn handle_push(
         }
     }
 
-    Ok(json!({
-        "success": true,
+    // Keep the checkpoint while anything failed so `--resume` retries just those sets
+    if failed.is_empty() {
+        checkpoint.finish();
+    }
+    staged.save(&stage_path)?;
+    let mut result = json!({
+        "success": failed.is_empty(),
         "app_id": app_id,
+        "version": version_decision,
         "locales_pushed": locales_pushed,
         "screenshots_uploaded": screenshots_uploaded
-    }))
+    });
+    if !field_changes.is_empty() {
+        result["fields"] = Value::Object(field_changes);
+    }
+    if !skipped.is_empty() {
+        result["skipped"] = json!(skipped);
+    }
+    if !failed.is_empty() {
+        result["failed"] = json!(failed);
+    }
+    if !warnings.is_empty() {
+        result["warnings"] = json!(warnings);
+    }
+    if let Some(applied) = staged_app_info {
+        result["staged_app_info"] = applied;
+    }
+    Ok(result)
 }
 
 async fn read_file_if_exists(path: &PathBuf) -> Option<String> {
@@ -1088,72 +1502,7 @@ async fn upload_screenshot(
     set_id: &str,
     file_path: &PathBuf,
     filename: &str,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let file_size = fs::metadata(file_path).await?.len();
-
-    // Step 1: Reserve the screenshot
-    let reservation = json!({
-        "data": {
-            "type": "appScreenshots",
-            "attributes": {
-                "fileName": filename,
-                "fileSize": file_size
-            },
-            "relationships": {
-                "appScreenshotSet": {
-                    "data": {
-                        "type": "appScreenshotSets",
-                        "id": set_id
-                    }
-                }
-            }
-        }
-    });
-
-    let reserved: Value = client.post("/appScreenshots", &reservation).await?;
-    let screenshot_id = reserved["data"]["id"]
-        .as_str()
-        .ok_or("no screenshot id in reservation response")?;
-
-    // Step 2: Upload the asset
-    let upload_ops = &reserved["data"]["attributes"]["uploadOperations"];
+) -> Result<UploadedAsset, Box<dyn std::error::Error>> {
     let file_bytes = fs::read(file_path).await?;
-
-    if let Some(ops) = upload_ops.as_array() {
-        let http_client = reqwest::Client::new();
-        for op in ops {
-            let url = op["url"].as_str().ok_or("missing upload url")?;
-            let offset = op["offset"].as_u64().unwrap_or(0) as usize;
-            let length = op["length"].as_u64().unwrap_or(file_bytes.len() as u64) as usize;
-            let chunk = &file_bytes[offset..std::cmp::min(offset + length, file_bytes.len())];
-
-            let mut req = http_client.put(url);
-            if let Some(headers) = op["requestHeaders"].as_array() {
-                for h in headers {
-                    if let (Some(name), Some(value)) = (h["name"].as_str(), h["value"].as_str()) {
-                        req = req.header(name, value);
-                    }
-                }
-            }
-            req.body(chunk.to_vec()).send().await?;
-        }
-    }
-
-    // Step 3: Commit the upload
-    let commit_body = json!({
-        "data": {
-            "type": "appScreenshots",
-            "id": screenshot_id,
-            "attributes": {
-                "uploaded": true,
-                "sourceFileChecksum": reserved["data"]["attributes"]["sourceFileChecksum"]
-            }
-        }
-    });
-
-    client
-        .patch(&format!("/appScreenshots/{screenshot_id}"), &commit_body)
-        .await?;
-
-    Ok(screenshot_id.to_string())
+    super::screenshots::upload_screenshot(client, set_id, filename, &file_bytes).await
 }
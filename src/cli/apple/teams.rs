@@ -0,0 +1,177 @@
+//! Which App Store Connect team the current API key belongs to.
+//!
+//! API keys are issued per team (the issuer ID identifies it), so a key only sees
+//! that team's apps. When a command works locally but fails with 403 in CI, the two
+//! environments usually carry keys of different teams; `apple teams` shows the key
+//! in use, the teams configured across profiles, and the apps the key can reach.
+
+use clap::Args;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use super::pricing::all_pages;
+use crate::api::apple_client::AppleClient;
+use crate::config::profiles::Credentials;
+use crate::config::Config;
+
+#[derive(Args)]
+pub struct TeamsArgs {
+    /// Check that this app ID or bundle ID is visible to the current key
+    #[arg(long)]
+    pub app: Option<String>,
+}
+
+/// The key in use: from `STOREOPS_APPLE_*` variables when all are set (as in CI),
+/// otherwise from the selected profile.
+fn current_key(config: &Config, profile: Option<&str>) -> Value {
+    if let (Ok(key_id), Ok(issuer_id), Ok(_)) = (
+        std::env::var("STOREOPS_APPLE_KEY_ID"),
+        std::env::var("STOREOPS_APPLE_ISSUER_ID"),
+        std::env::var("STOREOPS_APPLE_KEY_PATH"),
+    ) {
+        return json!({ "source": "environment", "key_id": key_id, "issuer_id": issuer_id });
+    }
+    let name = profile.or(config.active_profile.as_deref());
+    match name
+        .and_then(|n| config.profiles.get(n))
+        .map(|p| &p.credentials)
+    {
+        Some(Credentials::Apple {
+            key_id, issuer_id, ..
+        }) => json!({
+            "source": "profile",
+            "profile": name,
+            "key_id": key_id,
+            "issuer_id": issuer_id,
+        }),
+        _ => json!({ "source": "profile", "profile": name }),
+    }
+}
+
+/// Apple profiles grouped by issuer ID, one entry per team.
+fn configured_teams(config: &Config, current_issuer: Option<&str>) -> Vec<Value> {
+    let mut teams: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (name, profile) in &config.profiles {
+        if let Credentials::Apple { issuer_id, .. } = &profile.credentials {
+            teams.entry(issuer_id).or_default().push(name);
+        }
+    }
+    teams
+        .into_iter()
+        .map(|(issuer_id, mut profiles)| {
+            profiles.sort_unstable();
+            json!({
+                "issuer_id": issuer_id,
+                "profiles": profiles,
+                "current": current_issuer == Some(issuer_id),
+            })
+        })
+        .collect()
+}
+
+pub async fn handle(
+    args: &TeamsArgs,
+    client: &AppleClient,
+    profile: Option<&str>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let config = Config::load()?;
+    let key = current_key(&config, profile);
+    let issuer = key["issuer_id"].as_str();
+    let mut result = json!({
+        "key": key,
+        "teams": configured_teams(&config, issuer),
+    });
+
+    let apps = match all_pages(
+        "/apps",
+        &[("fields[apps]", "name,bundleId"), ("limit", "200")],
+        client,
+    )
+    .await
+    {
+        Ok((apps, _)) => apps,
+        Err(e) => {
+            result["error"] = json!(format!(
+                "the key cannot list apps ({e}); check that it is active and belongs to the team you expect"
+            ));
+            return Err(Box::new(crate::output::Failure(result)));
+        }
+    };
+    let apps: Vec<Value> = apps
+        .iter()
+        .map(|app| {
+            json!({
+                "id": app["id"],
+                "name": app["attributes"]["name"],
+                "bundle_id": app["attributes"]["bundleId"],
+            })
+        })
+        .collect();
+    result["app_count"] = json!(apps.len());
+
+    if let Some(wanted) = &args.app {
+        let found = apps
+            .iter()
+            .find(|a| a["id"] == wanted.as_str() || a["bundle_id"] == wanted.as_str());
+        result["app"] = json!({
+            "query": wanted,
+            "visible": found.is_some(),
+            "id": found.map(|a| a["id"].clone()),
+        });
+    }
+    result["apps"] = json!(apps);
+    if result["app"]["visible"] == false {
+        result["error"] = json!(format!(
+            "{} is not visible to key {}; it may belong to another team's key",
+            result["app"]["query"].as_str().unwrap_or_default(),
+            result["key"]["key_id"].as_str().unwrap_or("in use")
+        ));
+        return Err(Box::new(crate::output::Failure(result)));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_apple_profiles_by_issuer() {
+        let config: Config = toml::from_str(
+            r#"
+            active_profile = "local"
+
+            [profiles.local]
+            store = "apple"
+            key_id = "K1"
+            issuer_id = "TEAM-A"
+            key_path = "/tmp/k1.p8"
+
+            [profiles.ci]
+            store = "apple"
+            key_id = "K2"
+            issuer_id = "TEAM-B"
+            key_path = "/tmp/k2.p8"
+
+            [profiles.ci-old]
+            store = "apple"
+            key_id = "K3"
+            issuer_id = "TEAM-B"
+            key_path = "/tmp/k3.p8"
+
+            [profiles.play]
+            store = "google"
+            service_account_path = "/tmp/sa.json"
+            "#,
+        )
+        .unwrap();
+        let key = current_key(&config, Some("ci"));
+        assert_eq!(key["key_id"], "K2");
+        let teams = configured_teams(&config, key["issuer_id"].as_str());
+        assert_eq!(teams.len(), 2);
+        assert_eq!(teams[0]["issuer_id"], "TEAM-A");
+        assert_eq!(teams[0]["current"], false);
+        assert_eq!(teams[1]["profiles"], json!(["ci", "ci-old"]));
+        assert_eq!(teams[1]["current"], true);
+    }
+}
@@ -1,6 +1,7 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
 
+use super::resolve;
 use crate::api::apple_client::AppleClient;
 
 #[derive(Subcommand)]
@@ -21,17 +22,162 @@ pub enum TestflightCommand {
 pub enum GroupsCommand {
     /// List beta groups
     List {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
     },
     /// Create a beta group
     Create {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
         /// Group name
         #[arg(long)]
         name: String,
     },
+    /// Public TestFlight link of an external group
+    PublicLink {
+        #[command(subcommand)]
+        command: PublicLinkCommand,
+    },
+    /// Builds available to a group
+    Builds {
+        #[command(subcommand)]
+        command: GroupBuildsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GroupBuildsCommand {
+    /// List the builds assigned to a group
+    List {
+        /// Beta group ID
+        group_id: String,
+    },
+    /// Make builds available to a group
+    ///
+    /// Examples:
+    ///   storeops apple testflight groups builds add GROUP_ID --build BUILD_ID
+    Add {
+        /// Beta group ID
+        group_id: String,
+        /// Build ID (repeatable)
+        #[arg(long, required = true)]
+        build: Vec<String>,
+    },
+    /// Remove builds from a group
+    Remove {
+        /// Beta group ID
+        group_id: String,
+        /// Build ID (repeatable)
+        #[arg(long, required = true)]
+        build: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PublicLinkCommand {
+    /// Turn on the group's public link and print its URL
+    ///
+    /// Examples:
+    ///   storeops apple testflight groups public-link enable GROUP_ID --limit 500
+    Enable {
+        /// Beta group ID
+        group_id: String,
+        /// Maximum number of testers who can join through the link
+        #[arg(long)]
+        limit: Option<u32>,
+    },
+    /// Turn off the group's public link
+    Disable {
+        /// Beta group ID
+        group_id: String,
+    },
+    /// Cap how many testers can join through the public link
+    SetLimit {
+        /// Beta group ID
+        group_id: String,
+        /// Maximum number of testers (1-10000)
+        #[arg(long, conflicts_with = "none")]
+        limit: Option<u32>,
+        /// Remove the limit
+        #[arg(long)]
+        none: bool,
+    },
+}
+
+/// Relationship body linking builds to a group.
+fn build_linkage(builds: &[String]) -> Value {
+    let data: Vec<Value> = builds
+        .iter()
+        .map(|id| json!({ "type": "builds", "id": id }))
+        .collect();
+    json!({ "data": data })
+}
+
+/// One row per tester from a `betaTesterUsages` metrics response.
+fn tester_usage_rows(metrics: &Value) -> Vec<Value> {
+    let included = metrics["included"].as_array();
+    metrics["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|item| {
+            let tester_id = item["dimensions"]["betaTesters"]["data"]["id"].as_str();
+            let tester = included.and_then(|inc| {
+                inc.iter()
+                    .find(|t| t["type"] == "betaTesters" && t["id"].as_str() == tester_id)
+            });
+            let total = |key: &str| -> i64 {
+                item["dataPoints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|p| p["values"][key].as_i64())
+                    .sum()
+            };
+            json!({
+                "tester_id": tester_id,
+                "email": tester.and_then(|t| t["attributes"]["email"].as_str()),
+                "sessions": total("sessionCount"),
+                "crashes": total("crashCount"),
+                "feedback": total("feedbackCount"),
+            })
+        })
+        .collect()
+}
+
+/// Beta group attributes shown by `groups list`, including the public link URL.
+const GROUP_FIELDS: &str = "name,isInternalGroup,publicLinkEnabled,publicLink,publicLinkLimitEnabled,publicLinkLimit,feedbackEnabled,createdDate";
+const MAX_PUBLIC_LINK_LIMIT: u32 = 10_000;
+
+/// Attributes for a public link limit, or no limit when `limit` is `None`.
+fn limit_attributes(limit: Option<u32>) -> Result<Value, Box<dyn std::error::Error>> {
+    match limit {
+        Some(n) if (1..=MAX_PUBLIC_LINK_LIMIT).contains(&n) => Ok(json!({
+            "publicLinkLimitEnabled": true,
+            "publicLinkLimit": n
+        })),
+        Some(n) => {
+            Err(format!("--limit must be between 1 and {MAX_PUBLIC_LINK_LIMIT}, got {n}").into())
+        }
+        None => Ok(json!({ "publicLinkLimitEnabled": false })),
+    }
+}
+
+async fn update_group(
+    client: &AppleClient,
+    group_id: &str,
+    attributes: Value,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let body = json!({
+        "data": {
+            "type": "betaGroups",
+            "id": group_id,
+            "attributes": attributes
+        }
+    });
+    client
+        .patch(&format!("/betaGroups/{group_id}"), &body)
+        .await
 }
 
 #[derive(Subcommand)]
@@ -49,6 +195,17 @@ pub enum TestersCommand {
         #[arg(long)]
         email: String,
     },
+    /// Per-tester engagement in a group: sessions, crashes and feedback
+    ///
+    /// Examples:
+    ///   storeops apple testflight testers metrics GROUP_ID --period P7D
+    Metrics {
+        /// Beta group ID
+        group_id: String,
+        /// Reporting period
+        #[arg(long, default_value = "P30D", value_parser = ["P7D", "P30D", "P90D", "P365D"])]
+        period: String,
+    },
 }
 
 pub async fn handle(
@@ -69,13 +226,16 @@ async fn handle_groups(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         GroupsCommand::List { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             let mut query = vec![];
             let limit_str = limit.unwrap_or(50).to_string();
             query.push(("limit", limit_str.as_str()));
             query.push(("filter[app]", app_id.as_str()));
+            query.push(("fields[betaGroups]", GROUP_FIELDS));
             client.get("/betaGroups", &query).await
         }
         GroupsCommand::Create { app_id, name } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             let body = json!({
                 "data": {
                     "type": "betaGroups",
@@ -94,6 +254,63 @@ async fn handle_groups(
             });
             client.post("/betaGroups", &body).await
         }
+        GroupsCommand::PublicLink { command } => match command {
+            PublicLinkCommand::Enable { group_id, limit } => {
+                // Without --limit, leave any existing cap alone
+                let mut attributes = match limit {
+                    Some(_) => limit_attributes(*limit)?,
+                    None => json!({}),
+                };
+                attributes["publicLinkEnabled"] = json!(true);
+                let group = update_group(client, group_id, attributes).await?;
+                if let Some(url) = group["data"]["attributes"]["publicLink"].as_str() {
+                    eprintln!("Public link: {url}");
+                }
+                Ok(group)
+            }
+            PublicLinkCommand::Disable { group_id } => {
+                update_group(client, group_id, json!({ "publicLinkEnabled": false })).await
+            }
+            PublicLinkCommand::SetLimit {
+                group_id,
+                limit,
+                none,
+            } => {
+                if limit.is_none() && !none {
+                    return Err("pass --limit <n> or --none".into());
+                }
+                update_group(client, group_id, limit_attributes(*limit)?).await
+            }
+        },
+        GroupsCommand::Builds { command } => match command {
+            GroupBuildsCommand::List { group_id } => {
+                let limit_str = limit.unwrap_or(50).to_string();
+                client
+                    .get(
+                        &format!("/betaGroups/{group_id}/builds"),
+                        &[("limit", limit_str.as_str())],
+                    )
+                    .await
+            }
+            GroupBuildsCommand::Add { group_id, build } => {
+                client
+                    .post(
+                        &format!("/betaGroups/{group_id}/relationships/builds"),
+                        &build_linkage(build),
+                    )
+                    .await?;
+                Ok(json!({ "status": "ok", "group_id": group_id, "added": build }))
+            }
+            GroupBuildsCommand::Remove { group_id, build } => {
+                client
+                    .delete_with_body(
+                        &format!("/betaGroups/{group_id}/relationships/builds"),
+                        &build_linkage(build),
+                    )
+                    .await?;
+                Ok(json!({ "status": "ok", "group_id": group_id, "removed": build }))
+            }
+        },
     }
 }
 
@@ -132,5 +349,63 @@ async fn handle_testers(
             });
             client.post("/betaTesters", &tester_body).await
         }
+        TestersCommand::Metrics { group_id, period } => {
+            let limit_str = limit.unwrap_or(200).to_string();
+            let metrics: Value = client
+                .get(
+                    &format!("/betaGroups/{group_id}/metrics/betaTesterUsages"),
+                    &[
+                        ("period", period.as_str()),
+                        ("groupBy", "betaTesters"),
+                        ("limit", limit_str.as_str()),
+                    ],
+                )
+                .await?;
+            Ok(json!({
+                "group_id": group_id,
+                "period": period,
+                "testers": tester_usage_rows(&metrics),
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_link_limit_must_be_in_range() {
+        assert_eq!(
+            limit_attributes(Some(500)).unwrap(),
+            json!({"publicLinkLimitEnabled": true, "publicLinkLimit": 500})
+        );
+        assert_eq!(
+            limit_attributes(None).unwrap(),
+            json!({"publicLinkLimitEnabled": false})
+        );
+        assert!(limit_attributes(Some(0)).is_err());
+        assert!(limit_attributes(Some(10_001)).is_err());
+    }
+
+    #[test]
+    fn sums_usage_per_tester() {
+        let metrics = json!({
+            "data": [{
+                "dimensions": {"betaTesters": {"data": {"type": "betaTesters", "id": "T1"}}},
+                "dataPoints": [
+                    {"values": {"sessionCount": 3, "crashCount": 1, "feedbackCount": 0}},
+                    {"values": {"sessionCount": 4, "crashCount": 0, "feedbackCount": 2}}
+                ]
+            }],
+            "included": [
+                {"type": "betaTesters", "id": "T1", "attributes": {"email": "qa@example.com"}}
+            ]
+        });
+        let rows = tester_usage_rows(&metrics);
+        assert_eq!(rows[0]["email"], "qa@example.com");
+        assert_eq!(rows[0]["sessions"], 7);
+        assert_eq!(rows[0]["crashes"], 1);
+        assert_eq!(rows[0]["feedback"], 2);
     }
 }
@@ -1,22 +1,80 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
 
+use super::platform::Platform;
+use super::sync::{APP_INFO_EDITABLE_STATES, EDITABLE_STATES};
+use super::{app_info_stage, remediate, resolve};
 use crate::api::apple_client::AppleClient;
+use crate::api::apple_errors::{problem_of, StateProblem};
+use crate::cli::version::{bump_version, BumpPart};
+
+/// Version localization fields, editable only while the version is.
+const VERSION_FIELDS: &[&str] = &[
+    "description",
+    "keywords",
+    "whatsNew",
+    "marketingUrl",
+    "supportUrl",
+    "screenshots",
+];
+
+/// App info localization fields, editable only while the app info is.
+const APP_INFO_FIELDS: &[&str] = &["name", "subtitle", "privacyPolicyUrl", "categories"];
 
 #[derive(Subcommand)]
 pub enum VersionsCommand {
     /// List app versions
+    ///
+    /// Examples:
+    ///   storeops apple versions list 1234567890 --state PREPARE_FOR_SUBMISSION --platform ios
+    ///   storeops apple versions list 1234567890 --version 2.1.0 --include build,appStoreVersionSubmission
     List {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
+        /// App Store states (comma-separated, e.g., PREPARE_FOR_SUBMISSION,READY_FOR_SALE)
+        #[arg(long)]
+        state: Option<String>,
+        /// Only versions for this platform
+        #[arg(long, value_enum)]
+        platform: Option<Platform>,
+        /// Version strings (comma-separated)
+        #[arg(long)]
+        version: Option<String>,
+        /// Related resources to include (comma-separated, e.g., build,appStoreVersionSubmission)
+        #[arg(long)]
+        include: Option<String>,
     },
     /// Create a new version
+    ///
+    /// Examples:
+    ///   storeops apple versions create 1234567890 --version 1.2.0
+    ///   storeops apple versions create com.example.app --version 1.2.0 --platform macos
     Create {
-        /// App ID
-        app_id: String,
+        /// App ID or bundle ID
+        app_id: Option<String>,
         /// Version string (e.g., "1.2.0")
         #[arg(long)]
         version: String,
+        /// Platform of the new version
+        #[arg(long, value_enum, default_value_t = Platform::Ios)]
+        platform: Platform,
+        /// If another version is in progress, reuse it (renamed to --version)
+        #[arg(long)]
+        auto_remediate: bool,
+    },
+    /// Explain which metadata can be edited right now, why, and how to unlock the rest
+    ///
+    /// Version text (description, what's new, keywords, screenshots) follows the
+    /// newest version's state; name and subtitle follow the app info's state, which
+    /// only opens up together with a new version. Promotional text can always be
+    /// edited.
+    ///
+    /// Examples:
+    ///   storeops apple versions explain 1234567890
+    ///   storeops apple versions explain com.example.app
+    Explain {
+        /// App ID or bundle ID
+        app_id: Option<String>,
     },
 }
 
@@ -26,21 +84,47 @@ pub async fn handle(
     limit: Option<u32>,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
-        VersionsCommand::List { app_id } => {
+        VersionsCommand::List {
+            app_id,
+            state,
+            platform,
+            version,
+            include,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             let mut query = vec![];
             let limit_str = limit.unwrap_or(50).to_string();
             query.push(("limit", limit_str.as_str()));
+            let state = state.as_ref().map(|s| s.to_uppercase());
+            if let Some(s) = &state {
+                query.push(("filter[appStoreState]", s.as_str()));
+            }
+            if let Some(p) = platform {
+                query.push(("filter[platform]", p.api_value()));
+            }
+            if let Some(v) = version {
+                query.push(("filter[versionString]", v.as_str()));
+            }
+            if let Some(inc) = include {
+                query.push(("include", inc.as_str()));
+            }
             client
                 .get(&format!("/apps/{app_id}/appStoreVersions"), &query)
                 .await
         }
-        VersionsCommand::Create { app_id, version } => {
+        VersionsCommand::Create {
+            app_id,
+            version,
+            platform,
+            auto_remediate,
+        } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
             let body = json!({
                 "data": {
                     "type": "appStoreVersions",
                     "attributes": {
                         "versionString": version,
-                        "platform": "IOS"
+                        "platform": platform.api_value()
                     },
                     "relationships": {
                         "app": {
@@ -52,7 +136,198 @@ pub async fn handle(
                     }
                 }
             });
-            client.post("/appStoreVersions", &body).await
+            let mut result = match client.post("/appStoreVersions", &body).await {
+                Err(e)
+                    if *auto_remediate
+                        && problem_of(e.as_ref()) == Some(StateProblem::VersionInProgress) =>
+                {
+                    remediate::reuse_version_in_progress(app_id, version, client).await
+                }
+                result => result,
+            }?;
+            // A new version opens up the app info, so staged name/subtitle changes can land
+            match app_info_stage::apply_pending(app_id, client).await {
+                Ok(Some(applied)) => result["staged_app_info"] = applied,
+                Ok(None) => {}
+                Err(e) => eprintln!("Warning: could not apply staged name/subtitle: {e}"),
+            }
+            Ok(result)
+        }
+        VersionsCommand::Explain { app_id } => {
+            let app_id = &resolve::app_id(app_id.as_deref(), client).await?;
+            let versions: Value = client
+                .get(
+                    &format!("/apps/{app_id}/appStoreVersions"),
+                    &[("limit", "1")],
+                )
+                .await?;
+            let infos: Value = client
+                .get(&format!("/apps/{app_id}/appInfos"), &[("limit", "5")])
+                .await?;
+            let version = versions["data"]
+                .as_array()
+                .and_then(|arr| arr.first())
+                .ok_or("no App Store version found")?;
+            let infos = infos["data"].as_array().cloned().unwrap_or_default();
+            let mut result = explain(version, &infos);
+            result["app_id"] = json!(app_id);
+            Ok(result)
+        }
+    }
+}
+
+/// Why a version in `state` is (not) editable, and what would unlock it.
+fn version_state(state: &str, version: &str) -> (&'static str, Option<String>) {
+    let new_version = || {
+        Some(format!(
+            "create a new version: storeops apple versions create --version {}",
+            bump_version(version, BumpPart::Patch)
+        ))
+    };
+    match state {
+        "PREPARE_FOR_SUBMISSION" => ("the version has not been submitted for review yet", None),
+        "DEVELOPER_REJECTED" => (
+            "the version was removed from review and can be resubmitted",
+            None,
+        ),
+        "REJECTED" | "METADATA_REJECTED" => {
+            ("App Review rejected the version; fix it and resubmit", None)
         }
+        "WAITING_FOR_REVIEW" | "READY_FOR_REVIEW" => (
+            "the version is submitted and waiting for App Review",
+            Some(
+                "remove it from review in App Store Connect (it returns to DEVELOPER_REJECTED)"
+                    .into(),
+            ),
+        ),
+        "IN_REVIEW" => (
+            "App Review is looking at the version",
+            Some(
+                "wait for the review to finish, or remove it from review in App Store Connect"
+                    .into(),
+            ),
+        ),
+        "PENDING_DEVELOPER_RELEASE" | "PENDING_APPLE_RELEASE" | "PROCESSING_FOR_APP_STORE" => (
+            "the version is approved and about to be released",
+            Some(format!(
+                "release it, then {}",
+                new_version().unwrap_or_default()
+            )),
+        ),
+        "READY_FOR_SALE" => ("the version is live on the App Store", new_version()),
+        _ => ("the version is no longer in progress", new_version()),
+    }
+}
+
+/// Which metadata of the app can be edited given its newest version and app infos.
+fn explain(version: &Value, infos: &[Value]) -> Value {
+    let attrs = &version["attributes"];
+    let state = attrs["appStoreState"].as_str().unwrap_or_default();
+    let version_string = attrs["versionString"].as_str().unwrap_or("1.0.0");
+    let version_editable = EDITABLE_STATES.contains(&state);
+    let (reason, unlock) = version_state(state, version_string);
+
+    let info_state = |info: &Value| {
+        let attrs = &info["attributes"];
+        attrs["appStoreState"]
+            .as_str()
+            .or(attrs["state"].as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let editable_info = infos
+        .iter()
+        .find(|i| APP_INFO_EDITABLE_STATES.contains(&info_state(i).as_str()));
+    let info = editable_info.or(infos.first());
+    let (info_reason, info_unlock) = match (editable_info, version_editable) {
+        (Some(_), _) => (
+            "a new version is being prepared, so the app info is open too",
+            None,
+        ),
+        (None, true) => (
+            "the app info stays locked while the version is only being resubmitted",
+            Some("changes to name and subtitle need a new version".to_string()),
+        ),
+        (None, false) => (
+            "the app info is locked with the live or in-review version",
+            Some(format!(
+                "create a new version: storeops apple versions create --version {}",
+                bump_version(version_string, BumpPart::Patch)
+            )),
+        ),
+    };
+
+    let mut editable: Vec<&str> = vec!["promotionalText"];
+    let mut locked: Vec<&str> = Vec::new();
+    for (open, fields) in [
+        (version_editable, VERSION_FIELDS),
+        (editable_info.is_some(), APP_INFO_FIELDS),
+    ] {
+        if open {
+            editable.extend(fields);
+        } else {
+            locked.extend(fields);
+        }
+    }
+
+    json!({
+        "editable": editable,
+        "locked": locked,
+        "version": {
+            "id": version["id"],
+            "version": version_string,
+            "state": state,
+            "editable": version_editable,
+            "fields": VERSION_FIELDS,
+            "reason": reason,
+            "unlock": unlock,
+        },
+        "app_info": {
+            "id": info.map(|i| i["id"].clone()),
+            "state": info.map(info_state),
+            "editable": editable_info.is_some(),
+            "fields": APP_INFO_FIELDS,
+            "reason": info_reason,
+            "unlock": info_unlock,
+        },
+        "note": "promotionalText can be changed at any time, even on the live version",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explains_live_and_prepared_versions() {
+        let live = json!({"id": "v1", "attributes": {"appStoreState": "READY_FOR_SALE", "versionString": "2.3.1"}});
+        let live_info = json!({"id": "i1", "attributes": {"appStoreState": "READY_FOR_SALE"}});
+        let result = explain(&live, std::slice::from_ref(&live_info));
+        assert_eq!(result["editable"], json!(["promotionalText"]));
+        assert_eq!(result["version"]["editable"], false);
+        assert_eq!(
+            result["version"]["unlock"],
+            "create a new version: storeops apple versions create --version 2.3.2"
+        );
+        assert_eq!(result["app_info"]["editable"], false);
+
+        let prepared = json!({"id": "v2", "attributes": {"appStoreState": "PREPARE_FOR_SUBMISSION", "versionString": "2.3.2"}});
+        let new_info =
+            json!({"id": "i2", "attributes": {"appStoreState": "PREPARE_FOR_SUBMISSION"}});
+        let result = explain(&prepared, &[live_info, new_info]);
+        assert_eq!(result["locked"], json!([]));
+        assert_eq!(result["app_info"]["id"], "i2");
+        assert_eq!(result["version"]["unlock"], Value::Null);
+
+        let in_review = json!({"attributes": {"appStoreState": "WAITING_FOR_REVIEW", "versionString": "2.3.2"}});
+        let result = explain(&in_review, &[]);
+        assert!(result["locked"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("whatsNew")));
+        assert!(result["version"]["unlock"]
+            .as_str()
+            .unwrap()
+            .starts_with("remove it from review"));
     }
 }
@@ -0,0 +1,120 @@
+//! Run many commands in one process.
+//!
+//! `storeops batch --file commands.txt` reads one command per line (the leading
+//! `storeops` is optional), runs each in turn and prints one JSON line per command.
+//! Clients share one connection pool and one token per store credential (see
+//! `api::session`), so a long list is much faster than invoking the binary in a
+//! shell loop.
+
+use clap::Parser;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::path::Path;
+
+use super::Cli;
+
+/// Commands of a batch file: `(line number, command line)`, skipping blank lines
+/// and `#` comments.
+fn commands(content: &str) -> Vec<(usize, &str)> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// Parse and run one command line.
+async fn run_line(line: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut words = shell_words::split(line)?;
+    // Lines copied from a shell script may keep the program name
+    if words.first().map(String::as_str) == Some("storeops") {
+        words.remove(0);
+    }
+    run_words(words).await
+}
+
+/// Parse and run one command given as words (without the program name).
+///
+/// Also used by `serve`. Commands that run other commands cannot be nested.
+pub async fn run_words(words: Vec<String>) -> Result<Value, Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::iter::once("storeops".to_string())
+        .chain(words)
+        .collect();
+    let mut cli = Cli::try_parse_from(&args).map_err(|e| {
+        let message = e.render().to_string();
+        let first = message.lines().next().unwrap_or_default();
+        first.trim_start_matches("error: ").to_string()
+    })?;
+    match &cli.command {
+        None => return Err("no command given".into()),
+        Some(super::Command::Batch { .. }) => return Err("batch cannot be nested".into()),
+        Some(super::Command::Serve { .. }) => return Err("serve cannot be nested".into()),
+        Some(_) => {}
+    }
+    cli.apply_profile_defaults();
+    let flatten = cli.flatten;
+    let value = Box::pin(crate::dispatch(cli, &args)).await?;
+    Ok(if flatten {
+        crate::output::json::flatten(value)
+    } else {
+        value
+    })
+}
+
+pub async fn handle(file: &Path, stop_on_error: bool) -> Result<Value, Box<dyn std::error::Error>> {
+    let content = if file == Path::new("-") {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        std::fs::read_to_string(file).map_err(|e| format!("cannot read {}: {e}", file.display()))?
+    };
+
+    let mut succeeded = 0;
+    let mut failed = Vec::new();
+    for (line_no, line) in commands(&content) {
+        let record = match run_line(line).await {
+            Ok(result) => {
+                succeeded += 1;
+                json!({ "line": line_no, "command": line, "ok": true, "result": result })
+            }
+            Err(e) => {
+                failed.push(line_no);
+                json!({ "line": line_no, "command": line, "ok": false, "error": e.to_string() })
+            }
+        };
+        println!("{record}");
+        if stop_on_error && !failed.is_empty() {
+            break;
+        }
+    }
+
+    let summary = json!({
+        "status": if failed.is_empty() { "ok" } else { "failed" },
+        "succeeded": succeeded,
+        "failed_lines": failed,
+    });
+    if failed.is_empty() {
+        Ok(summary)
+    } else {
+        Err(Box::new(crate::output::Failure(summary)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_blank_lines_and_comments() {
+        let content = "# nightly checks\napple apps list\n\n  google apps info com.example.app  \n";
+        assert_eq!(
+            commands(content),
+            [
+                (2, "apple apps list"),
+                (4, "google apps info com.example.app")
+            ]
+        );
+    }
+}
@@ -0,0 +1,1348 @@
+//! In-app product catalog of both stores in one YAML file.
+//!
+//! `catalog export` writes the App Store in-app purchases and subscriptions of an
+//! app and the Play in-app products and subscriptions of a package to one file,
+//! merged by SKU (App Store product ID, Play SKU or product ID). `catalog import`
+//! creates the products missing from a store and updates their localizations and
+//! prices, so the catalog can live in version control next to the code.
+//!
+//! Type, billing period and localizations are shared. Fields only one store has
+//! live under the product's `apple:` or `google:` key, and import reports the fields
+//! it cannot carry to a store as `flags`:
+//!
+//! ```yaml
+//! products:
+//!   - sku: coins.100
+//!     type: consumable
+//!     stores: [apple, google]
+//!     localizations:
+//!       en-US:
+//!         name: 100 Coins
+//!         description: A small pile of coins
+//!     apple:
+//!       reference_name: Coins 100
+//!       prices:
+//!         USA: "0.99"
+//!     google:
+//!       default_price: 0.99 USD
+//!   - sku: pro.monthly
+//!     type: subscription
+//!     period: P1M
+//!     stores: [apple]
+//!     apple:
+//!       group: Pro
+//! ```
+
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use super::apple::price_matrix::{self, Product};
+use super::apple::pricing::all_pages;
+use super::apple::resolve;
+use super::Cli;
+use crate::api::apple_client::AppleClient;
+
+#[derive(Subcommand)]
+pub enum CatalogCommand {
+    /// Write the in-app products and subscriptions of both stores to a YAML file
+    ///
+    /// Examples:
+    ///   storeops catalog export --out catalog.yaml --app com.example.app --package com.example.app
+    ///   storeops catalog export --out catalog.yaml --app com.example.app --skip-prices
+    Export {
+        /// YAML file to write
+        #[arg(long)]
+        out: PathBuf,
+        /// Apple app ID or bundle ID (defaults to the Apple profile's default_app_id)
+        #[arg(long)]
+        app: Option<String>,
+        /// Google package name (defaults to the Google profile's default_package)
+        #[arg(long)]
+        package: Option<String>,
+        /// Profile for the Apple half (defaults to --profile or the active profile)
+        #[arg(long)]
+        apple_profile: Option<String>,
+        /// Profile for the Google half (defaults to --profile or the active profile)
+        #[arg(long)]
+        google_profile: Option<String>,
+        /// Leave out prices (fetching App Store prices takes a request per product)
+        #[arg(long)]
+        skip_prices: bool,
+    },
+    /// Create and update the products of a catalog file in both stores
+    ///
+    /// Products are never deleted. Missing products are created; existing ones get
+    /// the catalog's localizations and prices.
+    ///
+    /// Examples:
+    ///   storeops catalog import --file catalog.yaml --dry-run
+    ///   storeops catalog import --file catalog.yaml --app com.example.app --package com.example.app
+    Import {
+        /// Catalog file written by `export`
+        #[arg(long)]
+        file: PathBuf,
+        /// Apple app ID or bundle ID (defaults to the Apple profile's default_app_id)
+        #[arg(long)]
+        app: Option<String>,
+        /// Google package name (defaults to the Google profile's default_package)
+        #[arg(long)]
+        package: Option<String>,
+        /// Profile for the Apple half (defaults to --profile or the active profile)
+        #[arg(long)]
+        apple_profile: Option<String>,
+        /// Profile for the Google half (defaults to --profile or the active profile)
+        #[arg(long)]
+        google_profile: Option<String>,
+        /// Report what would change without writing to the stores
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Catalog {
+    #[serde(default)]
+    pub products: Vec<CatalogProduct>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductType {
+    Consumable,
+    NonConsumable,
+    /// App Store only; Google Play has no equivalent
+    NonRenewingSubscription,
+    Subscription,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Store {
+    Apple,
+    Google,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CatalogProduct {
+    pub sku: String,
+    #[serde(rename = "type")]
+    pub kind: ProductType,
+    /// Billing period of a subscription (P1W, P1M, P2M, P3M, P6M or P1Y)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>,
+    /// Stores selling the product
+    #[serde(default = "both_stores")]
+    pub stores: Vec<Store>,
+    /// Locale -> display name and description
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub localizations: BTreeMap<String, Localization>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub apple: Option<AppleFields>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub google: Option<GoogleFields>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Localization {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+/// App Store only fields.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppleFields {
+    /// Name shown in App Store Connect only (defaults to the SKU)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_name: Option<String>,
+    /// Reference name of the subscription group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family_sharable: Option<bool>,
+    /// Territory (e.g. USA) -> customer price
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prices: BTreeMap<String, String>,
+}
+
+/// Google Play only fields.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GoogleFields {
+    /// `active` or `inactive`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Locale of the product's default listing (defaults to the first localization)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_language: Option<String>,
+    /// Price and currency, e.g. `0.99 USD`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_price: Option<String>,
+    /// Region (e.g. US) -> price and currency
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prices: BTreeMap<String, String>,
+    /// Base plans of a subscription, as the Play API represents them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub base_plans: Vec<Value>,
+}
+
+fn both_stores() -> Vec<Store> {
+    vec![Store::Apple, Store::Google]
+}
+
+/// App Store subscription periods and their ISO 8601 durations.
+const PERIODS: &[(&str, &str)] = &[
+    ("ONE_WEEK", "P1W"),
+    ("ONE_MONTH", "P1M"),
+    ("TWO_MONTHS", "P2M"),
+    ("THREE_MONTHS", "P3M"),
+    ("SIX_MONTHS", "P6M"),
+    ("ONE_YEAR", "P1Y"),
+];
+
+impl ProductType {
+    fn from_apple(iap_type: &str) -> Option<Self> {
+        match iap_type {
+            "CONSUMABLE" => Some(Self::Consumable),
+            "NON_CONSUMABLE" => Some(Self::NonConsumable),
+            "NON_RENEWING_SUBSCRIPTION" => Some(Self::NonRenewingSubscription),
+            _ => None,
+        }
+    }
+
+    /// `inAppPurchaseType` of an App Store in-app purchase; `None` for subscriptions.
+    fn apple_iap_type(self) -> Option<&'static str> {
+        match self {
+            Self::Consumable => Some("CONSUMABLE"),
+            Self::NonConsumable => Some("NON_CONSUMABLE"),
+            Self::NonRenewingSubscription => Some("NON_RENEWING_SUBSCRIPTION"),
+            Self::Subscription => None,
+        }
+    }
+}
+
+impl CatalogProduct {
+    fn sold_on(&self, store: Store) -> bool {
+        self.stores.contains(&store)
+            && !(store == Store::Google && self.kind == ProductType::NonRenewingSubscription)
+    }
+}
+
+/// Fields of a product that a store it is sold on cannot take, or that are ignored.
+fn flags(product: &CatalogProduct) -> Vec<String> {
+    let mut notes = Vec::new();
+    let is_sub = product.kind == ProductType::Subscription;
+    if product.kind == ProductType::NonRenewingSubscription
+        && product.stores.contains(&Store::Google)
+    {
+        notes.push("google: Google Play has no non-renewing subscriptions; skipped".to_string());
+    }
+    if product.period.is_some() && !is_sub {
+        notes.push("period: only subscriptions have a billing period; ignored".to_string());
+    }
+    if let Some(apple) = &product.apple {
+        if !product.stores.contains(&Store::Apple) {
+            notes.push("apple: not sold on the App Store; fields ignored".to_string());
+        } else if is_sub && apple.group.is_none() {
+            notes.push("apple.group: needed to create the subscription".to_string());
+        } else if !is_sub && apple.group.is_some() {
+            notes.push("apple.group: only subscriptions have a group; ignored".to_string());
+        }
+    }
+    if let Some(google) = &product.google {
+        if !product.sold_on(Store::Google) {
+            notes.push("google: not sold on Google Play; fields ignored".to_string());
+        } else if is_sub && (google.default_price.is_some() || !google.prices.is_empty()) {
+            notes.push(
+                "google.prices: subscription prices live in google.base_plans; ignored".to_string(),
+            );
+        } else if !is_sub && !google.base_plans.is_empty() {
+            notes
+                .push("google.base_plans: only subscriptions have base plans; ignored".to_string());
+        }
+    }
+    notes
+}
+
+/// Play price micros and currency as `0.99 USD`.
+fn format_price(micros: &str, currency: &str) -> String {
+    let micros: u64 = micros.parse().unwrap_or(0);
+    let cents = format!("{:06}", micros % 1_000_000);
+    let cents = cents.trim_end_matches('0');
+    format!("{}.{:0<2} {currency}", micros / 1_000_000, cents)
+}
+
+/// `0.99 USD` as Play price micros and currency.
+fn parse_price(price: &str) -> Result<(String, String), String> {
+    let err = || format!("expected a price such as '0.99 USD', got '{price}'");
+    let (amount, currency) = price.trim().split_once(' ').ok_or_else(err)?;
+    let (units, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > 6
+        || !format!("{units}{fraction}")
+            .chars()
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(err());
+    }
+    let units: u64 = units.parse().map_err(|_| err())?;
+    let fraction: u64 = format!("{fraction:0<6}").parse().map_err(|_| err())?;
+    Ok((
+        (units * 1_000_000 + fraction).to_string(),
+        currency.trim().to_uppercase(),
+    ))
+}
+
+/// Merge the products of both stores by SKU; the App Store text wins where both
+/// stores have a locale. Returns the catalog and the differences found.
+fn merge(
+    apple: Vec<CatalogProduct>,
+    google: Vec<CatalogProduct>,
+) -> (Vec<CatalogProduct>, Vec<String>) {
+    let mut products = apple;
+    let mut conflicts = Vec::new();
+    for g in google {
+        let Some(p) = products.iter_mut().find(|p| p.sku == g.sku) else {
+            products.push(g);
+            continue;
+        };
+        p.stores.push(Store::Google);
+        if (p.kind == ProductType::Subscription) != (g.kind == ProductType::Subscription) {
+            conflicts.push(format!(
+                "{}: a subscription in one store but not the other",
+                p.sku
+            ));
+        }
+        match (&p.period, g.period) {
+            (None, period) => p.period = period,
+            (Some(a), Some(b)) if *a != b => conflicts.push(format!(
+                "{}: period is {a} on the App Store and {b} on Google Play",
+                p.sku
+            )),
+            _ => {}
+        }
+        for (locale, loc) in g.localizations {
+            match p.localizations.get(&locale) {
+                Some(existing) if *existing != loc => conflicts.push(format!(
+                    "{}: {locale} text differs between the stores; kept the App Store text",
+                    p.sku
+                )),
+                Some(_) => {}
+                None => {
+                    p.localizations.insert(locale, loc);
+                }
+            }
+        }
+        p.google = g.google;
+    }
+    products.sort_by(|a, b| a.sku.cmp(&b.sku));
+    (products, conflicts)
+}
+
+/// Locale -> localization from App Store localization resources.
+fn apple_localizations(locs: &Value) -> BTreeMap<String, Localization> {
+    locs["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|l| {
+            let attrs = &l["attributes"];
+            Some((
+                attrs["locale"].as_str()?.to_string(),
+                Localization {
+                    name: attrs["name"].as_str()?.to_string(),
+                    description: attrs["description"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Subscriptions of every subscription group of the app, with their group's name.
+async fn apple_subscriptions(
+    app_id: &str,
+    client: &AppleClient,
+) -> Result<Vec<(String, String, Value)>, Box<dyn std::error::Error>> {
+    let (groups, _) = all_pages(
+        &format!("/apps/{app_id}/subscriptionGroups"),
+        &[("limit", "200")],
+        client,
+    )
+    .await?;
+    let mut subs = Vec::new();
+    for group in &groups {
+        let (Some(group_id), Some(name)) = (
+            group["id"].as_str(),
+            group["attributes"]["referenceName"].as_str(),
+        ) else {
+            continue;
+        };
+        let (listed, _) = all_pages(
+            &format!("/subscriptionGroups/{group_id}/subscriptions"),
+            &[("limit", "200")],
+            client,
+        )
+        .await?;
+        for sub in listed {
+            subs.push((group_id.to_string(), name.to_string(), sub));
+        }
+    }
+    Ok(subs)
+}
+
+/// Every item of a Play list endpoint, under `key` in each page, passing the next
+/// page token as `token_param`. In-app products return it in
+/// `tokenPagination.nextPageToken`, monetization lists in `nextPageToken`.
+async fn google_pages(
+    path: &str,
+    key: &str,
+    token_param: &str,
+    client: &crate::api::google_client::GoogleClient,
+) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+    let mut items = Vec::new();
+    let mut token: Option<String> = None;
+    loop {
+        let mut query = Vec::new();
+        if let Some(t) = &token {
+            query.push((token_param, t.as_str()));
+        }
+        let page: Value = client.get(path, &query).await?;
+        items.extend(page[key].as_array().into_iter().flatten().cloned());
+        token = page["nextPageToken"]
+            .as_str()
+            .or(page["tokenPagination"]["nextPageToken"].as_str())
+            .filter(|t| !t.is_empty())
+            .map(String::from);
+        if token.is_none() {
+            return Ok(items);
+        }
+    }
+}
+
+/// Apple half of `export`.
+async fn apple_export(
+    app: Option<&str>,
+    profile: Option<&str>,
+    skip_prices: bool,
+) -> Result<Vec<CatalogProduct>, Box<dyn std::error::Error>> {
+    let client = super::apple::client_for_profile(profile)?;
+    let app_id = resolve::app_id(app, &client).await?;
+    let mut products = Vec::new();
+
+    let (iaps, _) = all_pages(
+        &format!("/apps/{app_id}/inAppPurchasesV2"),
+        &[("limit", "200")],
+        &client,
+    )
+    .await?;
+    for iap in &iaps {
+        let attrs = &iap["attributes"];
+        let (Some(id), Some(sku), Some(kind)) = (
+            iap["id"].as_str(),
+            attrs["productId"].as_str(),
+            attrs["inAppPurchaseType"]
+                .as_str()
+                .and_then(ProductType::from_apple),
+        ) else {
+            continue;
+        };
+        eprintln!("apple: reading {sku}");
+        let locs: Value = client
+            .get(
+                &format!("/inAppPurchasesV2/{id}/inAppPurchaseLocalizations"),
+                &[("limit", "200")],
+            )
+            .await?;
+        let prices = if skip_prices {
+            BTreeMap::new()
+        } else {
+            price_matrix::prices(Product::Iap, id, &client).await?
+        };
+        products.push(CatalogProduct {
+            sku: sku.to_string(),
+            kind,
+            period: None,
+            stores: vec![Store::Apple],
+            localizations: apple_localizations(&locs),
+            apple: Some(AppleFields {
+                reference_name: attrs["name"].as_str().map(String::from),
+                group: None,
+                family_sharable: attrs["familySharable"].as_bool(),
+                prices,
+            }),
+            google: None,
+        });
+    }
+
+    for (_, group, sub) in apple_subscriptions(&app_id, &client).await? {
+        let attrs = &sub["attributes"];
+        let (Some(id), Some(sku)) = (sub["id"].as_str(), attrs["productId"].as_str()) else {
+            continue;
+        };
+        eprintln!("apple: reading {sku}");
+        let locs: Value = client
+            .get(
+                &format!("/subscriptions/{id}/subscriptionLocalizations"),
+                &[("limit", "200")],
+            )
+            .await?;
+        let prices = if skip_prices {
+            BTreeMap::new()
+        } else {
+            price_matrix::prices(Product::Subscription, id, &client).await?
+        };
+        let period = attrs["subscriptionPeriod"]
+            .as_str()
+            .and_then(|p| PERIODS.iter().find(|(apple, _)| *apple == p))
+            .map(|(_, iso)| iso.to_string());
+        products.push(CatalogProduct {
+            sku: sku.to_string(),
+            kind: ProductType::Subscription,
+            period,
+            stores: vec![Store::Apple],
+            localizations: apple_localizations(&locs),
+            apple: Some(AppleFields {
+                reference_name: attrs["name"].as_str().map(String::from),
+                group: Some(group),
+                family_sharable: attrs["familySharable"].as_bool(),
+                prices,
+            }),
+            google: None,
+        });
+    }
+    Ok(products)
+}
+
+/// Google half of `export`.
+async fn google_export(
+    package: Option<&str>,
+    profile: Option<&str>,
+    skip_prices: bool,
+) -> Result<Vec<CatalogProduct>, Box<dyn std::error::Error>> {
+    let client = super::google::client_for_profile(profile).await?;
+    let package_name = &client.package_name(package)?;
+    let mut products = Vec::new();
+
+    let listed = google_pages(
+        &format!("/{package_name}/inappproducts"),
+        "inappproduct",
+        "token",
+        &client,
+    )
+    .await?;
+    for p in &listed {
+        let Some(sku) = p["sku"].as_str() else {
+            continue;
+        };
+        // Legacy subscriptions also appear here; the monetization API lists them below
+        if p["purchaseType"].as_str() == Some("subscription") {
+            continue;
+        }
+        let price = |v: &Value| {
+            Some(format_price(
+                v["priceMicros"].as_str()?,
+                v["currency"].as_str()?,
+            ))
+        };
+        let localizations = p["listings"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(locale, l)| {
+                Some((
+                    locale.clone(),
+                    Localization {
+                        name: l["title"].as_str()?.to_string(),
+                        description: l["description"].as_str().unwrap_or_default().to_string(),
+                    },
+                ))
+            })
+            .collect();
+        let prices = match skip_prices {
+            true => BTreeMap::new(),
+            false => p["prices"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter_map(|(region, v)| Some((region.clone(), price(v)?)))
+                .collect(),
+        };
+        products.push(CatalogProduct {
+            sku: sku.to_string(),
+            // Play does not record consumability
+            kind: ProductType::NonConsumable,
+            period: None,
+            stores: vec![Store::Google],
+            localizations,
+            apple: None,
+            google: Some(GoogleFields {
+                status: p["status"].as_str().map(String::from),
+                default_language: p["defaultLanguage"].as_str().map(String::from),
+                default_price: if skip_prices {
+                    None
+                } else {
+                    price(&p["defaultPrice"])
+                },
+                prices,
+                base_plans: Vec::new(),
+            }),
+        });
+    }
+
+    let subs = google_pages(
+        &format!("/{package_name}/monetization/subscriptions"),
+        "subscriptions",
+        "pageToken",
+        &client,
+    )
+    .await?;
+    for sub in &subs {
+        let Some(sku) = sub["productId"].as_str() else {
+            continue;
+        };
+        let base_plans = sub["basePlans"].as_array().cloned().unwrap_or_default();
+        let period = base_plans
+            .iter()
+            .find_map(|b| b["autoRenewingBasePlanType"]["billingPeriodDuration"].as_str())
+            .map(String::from);
+        let localizations = sub["listings"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|l| {
+                Some((
+                    l["languageCode"].as_str()?.to_string(),
+                    Localization {
+                        name: l["title"].as_str()?.to_string(),
+                        description: l["description"].as_str().unwrap_or_default().to_string(),
+                    },
+                ))
+            })
+            .collect();
+        products.push(CatalogProduct {
+            sku: sku.to_string(),
+            kind: ProductType::Subscription,
+            period,
+            stores: vec![Store::Google],
+            localizations,
+            apple: None,
+            google: Some(GoogleFields {
+                base_plans,
+                ..Default::default()
+            }),
+        });
+    }
+    Ok(products)
+}
+
+/// What an App Store import finds in the app before changing it.
+struct AppleExisting {
+    /// Product ID -> (ID, inAppPurchaseType)
+    iaps: HashMap<String, (String, String)>,
+    /// Product ID -> ID
+    subs: HashMap<String, String>,
+    /// Group reference name -> ID
+    groups: HashMap<String, String>,
+}
+
+/// Bring one product's localizations and prices on the App Store in line with the
+/// catalog, creating it first if needed.
+async fn apple_product(
+    product: &CatalogProduct,
+    app_id: &str,
+    existing: &mut AppleExisting,
+    dry_run: bool,
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let apple = product.apple.clone().unwrap_or_default();
+    let name = apple.reference_name.as_deref().unwrap_or(&product.sku);
+    let sku = product.sku.as_str();
+    let (price_kind, loc_type, loc_rel, loc_rel_type) = match product.kind {
+        ProductType::Subscription => (
+            Product::Subscription,
+            "subscriptionLocalizations",
+            "subscription",
+            "subscriptions",
+        ),
+        _ => (
+            Product::Iap,
+            "inAppPurchaseLocalizations",
+            "inAppPurchaseV2",
+            "inAppPurchases",
+        ),
+    };
+
+    let found = match product.kind.apple_iap_type() {
+        Some(iap_type) => match existing.iaps.get(sku) {
+            Some((_, current)) if current != iap_type => {
+                return Err(format!(
+                    "is {current} on the App Store and the type of a product cannot change"
+                )
+                .into())
+            }
+            Some((id, _)) => Some(id.clone()),
+            None => None,
+        },
+        None => existing.subs.get(sku).cloned(),
+    };
+    let created = found.is_none();
+    let id = match found {
+        Some(id) => Some(id),
+        None if dry_run => None,
+        None => {
+            let mut attrs = json!({ "name": name, "productId": sku });
+            if let Some(sharable) = apple.family_sharable {
+                attrs["familySharable"] = json!(sharable);
+            }
+            let created = match product.kind.apple_iap_type() {
+                Some(iap_type) => {
+                    attrs["inAppPurchaseType"] = json!(iap_type);
+                    let body = json!({
+                        "data": {
+                            "type": "inAppPurchases",
+                            "attributes": attrs,
+                            "relationships": {
+                                "app": { "data": { "type": "apps", "id": app_id } }
+                            }
+                        }
+                    });
+                    client.post("/inAppPurchasesV2", &body).await?
+                }
+                None => {
+                    let group = apple
+                        .group
+                        .as_deref()
+                        .ok_or("new subscriptions need apple.group")?;
+                    let period = product
+                        .period
+                        .as_deref()
+                        .and_then(|p| PERIODS.iter().find(|(_, iso)| *iso == p))
+                        .map(|(apple, _)| *apple)
+                        .ok_or(
+                            "new subscriptions need a period of P1W, P1M, P2M, P3M, P6M or P1Y",
+                        )?;
+                    let group_id = match existing.groups.get(group) {
+                        Some(id) => id.clone(),
+                        None => {
+                            let body = json!({
+                                "data": {
+                                    "type": "subscriptionGroups",
+                                    "attributes": { "referenceName": group },
+                                    "relationships": {
+                                        "app": { "data": { "type": "apps", "id": app_id } }
+                                    }
+                                }
+                            });
+                            let created = client.post("/subscriptionGroups", &body).await?;
+                            let id = created["data"]["id"]
+                                .as_str()
+                                .ok_or("created subscription group has no id")?
+                                .to_string();
+                            existing.groups.insert(group.to_string(), id.clone());
+                            id
+                        }
+                    };
+                    attrs["subscriptionPeriod"] = json!(period);
+                    let body = json!({
+                        "data": {
+                            "type": "subscriptions",
+                            "attributes": attrs,
+                            "relationships": {
+                                "group": { "data": { "type": "subscriptionGroups", "id": group_id } }
+                            }
+                        }
+                    });
+                    client.post("/subscriptions", &body).await?
+                }
+            };
+            eprintln!("apple: created {sku}");
+            Some(
+                created["data"]["id"]
+                    .as_str()
+                    .ok_or("created product has no id")?
+                    .to_string(),
+            )
+        }
+    };
+
+    let current = match &id {
+        Some(id) => {
+            let path = match product.kind {
+                ProductType::Subscription => {
+                    format!("/subscriptions/{id}/subscriptionLocalizations")
+                }
+                _ => format!("/inAppPurchasesV2/{id}/inAppPurchaseLocalizations"),
+            };
+            client.get::<Value>(&path, &[("limit", "200")]).await?
+        }
+        None => json!({ "data": [] }),
+    };
+    let loc_ids: HashMap<&str, &str> = current["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|l| Some((l["attributes"]["locale"].as_str()?, l["id"].as_str()?)))
+        .collect();
+    let current_text = apple_localizations(&current);
+    let mut localizations = serde_json::Map::new();
+    for (locale, loc) in &product.localizations {
+        if current_text.get(locale) == Some(loc) {
+            continue;
+        }
+        let attrs = json!({ "name": loc.name, "description": loc.description });
+        let status = match (loc_ids.get(locale.as_str()), &id) {
+            (Some(loc_id), _) => {
+                if !dry_run {
+                    let body = json!({
+                        "data": { "type": loc_type, "id": loc_id, "attributes": attrs }
+                    });
+                    client
+                        .patch(&format!("/{loc_type}/{loc_id}"), &body)
+                        .await?;
+                }
+                "updated"
+            }
+            (None, Some(id)) if !dry_run => {
+                let mut attrs = attrs;
+                attrs["locale"] = json!(locale);
+                let body = json!({
+                    "data": {
+                        "type": loc_type,
+                        "attributes": attrs,
+                        "relationships": {
+                            loc_rel: { "data": { "type": loc_rel_type, "id": id } }
+                        }
+                    }
+                });
+                client.post(&format!("/{loc_type}"), &body).await?;
+                "created"
+            }
+            (None, _) => "created",
+        };
+        localizations.insert(locale.clone(), json!(status));
+    }
+
+    let mut result = json!({
+        "sku": sku,
+        "action": if created { "created" } else if localizations.is_empty() { "unchanged" } else { "updated" },
+        "localizations": localizations,
+    });
+    if !apple.prices.is_empty() {
+        result["prices"] = match &id {
+            Some(id) => {
+                let (changed, unchanged, errors) =
+                    price_matrix::set_prices(price_kind, id, &apple.prices, dry_run, client)
+                        .await?;
+                if changed > 0 && result["action"] == "unchanged" {
+                    result["action"] = json!("updated");
+                }
+                json!({
+                    "changed": changed,
+                    "unchanged": unchanged,
+                    "errors": errors
+                        .into_iter()
+                        .map(|(territory, error)| json!({ "territory": territory, "error": error }))
+                        .collect::<Vec<_>>(),
+                })
+            }
+            None => json!({ "changed": apple.prices.len(), "unchanged": 0, "errors": [] }),
+        };
+    }
+    Ok(result)
+}
+
+/// Apple half of `import`.
+async fn apple_import(
+    catalog: &Catalog,
+    app: Option<&str>,
+    profile: Option<&str>,
+    dry_run: bool,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = super::apple::client_for_profile(profile)?;
+    let app_id = resolve::app_id(app, &client).await?;
+    let (iaps, _) = all_pages(
+        &format!("/apps/{app_id}/inAppPurchasesV2"),
+        &[("limit", "200")],
+        &client,
+    )
+    .await?;
+    let subs = apple_subscriptions(&app_id, &client).await?;
+    let mut existing = AppleExisting {
+        iaps: iaps
+            .iter()
+            .filter_map(|i| {
+                let attrs = &i["attributes"];
+                Some((
+                    attrs["productId"].as_str()?.to_string(),
+                    (
+                        i["id"].as_str()?.to_string(),
+                        attrs["inAppPurchaseType"].as_str()?.to_string(),
+                    ),
+                ))
+            })
+            .collect(),
+        subs: subs
+            .iter()
+            .filter_map(|(_, _, s)| {
+                Some((
+                    s["attributes"]["productId"].as_str()?.to_string(),
+                    s["id"].as_str()?.to_string(),
+                ))
+            })
+            .collect(),
+        groups: subs
+            .iter()
+            .map(|(id, name, _)| (name.clone(), id.clone()))
+            .collect(),
+    };
+
+    let mut products = Vec::new();
+    for product in catalog.products.iter().filter(|p| p.sold_on(Store::Apple)) {
+        match apple_product(product, &app_id, &mut existing, dry_run, &client).await {
+            Ok(result) => products.push(result),
+            Err(e) => {
+                eprintln!("apple: {}: {e}", product.sku);
+                products.push(json!({ "sku": product.sku, "error": e.to_string() }));
+            }
+        }
+    }
+    Ok(json!({ "app_id": app_id, "products": products }))
+}
+
+/// Play in-app product `product` should become: `existing` with the catalog's
+/// fields applied, or a new product.
+fn inapp_body(
+    product: &CatalogProduct,
+    package_name: &str,
+    existing: Option<&Value>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let google = product.google.clone().unwrap_or_default();
+    let mut body = existing.cloned().unwrap_or_else(|| {
+        json!({
+            "packageName": package_name,
+            "sku": product.sku,
+            "purchaseType": "managedUser",
+            "status": "active",
+        })
+    });
+    if let Some(status) = &google.status {
+        body["status"] = json!(status);
+    }
+    match &google.default_price {
+        Some(price) => {
+            let (micros, currency) = parse_price(price)?;
+            body["defaultPrice"] = json!({ "priceMicros": micros, "currency": currency });
+        }
+        None if existing.is_none() => {
+            return Err("new Google Play products need google.default_price".into())
+        }
+        None => {}
+    }
+    for (region, price) in &google.prices {
+        let (micros, currency) = parse_price(price)?;
+        body["prices"][region] = json!({ "priceMicros": micros, "currency": currency });
+    }
+    for (locale, loc) in &product.localizations {
+        body["listings"][locale] = json!({ "title": loc.name, "description": loc.description });
+    }
+    let default_language = google
+        .default_language
+        .or_else(|| body["defaultLanguage"].as_str().map(String::from))
+        .or_else(|| product.localizations.keys().next().cloned())
+        .ok_or("new Google Play products need a localization")?;
+    if body["listings"][&default_language].is_null() {
+        return Err(format!("no {default_language} localization for the default language").into());
+    }
+    body["defaultLanguage"] = json!(default_language);
+    Ok(body)
+}
+
+/// Bring one Play in-app product or subscription in line with the catalog.
+async fn google_product(
+    product: &CatalogProduct,
+    package_name: &str,
+    existing: Option<&Value>,
+    dry_run: bool,
+    client: &crate::api::google_client::GoogleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let google = product.google.clone().unwrap_or_default();
+    let sku = product.sku.as_str();
+    let action = |changed: bool| match (existing, changed) {
+        (None, _) => "created",
+        (Some(_), true) => "updated",
+        (Some(_), false) => "unchanged",
+    };
+
+    if product.kind != ProductType::Subscription {
+        let body = inapp_body(product, package_name, existing)?;
+        let changed = existing.is_some_and(|e| *e != body);
+        if !dry_run && (existing.is_none() || changed) {
+            match existing {
+                Some(_) => {
+                    client
+                        .put(
+                            &format!(
+                                "/{package_name}/inappproducts/{sku}?autoConvertMissingPrices=true"
+                            ),
+                            &body,
+                        )
+                        .await?
+                }
+                None => {
+                    client
+                        .post(
+                            &format!("/{package_name}/inappproducts?autoConvertMissingPrices=true"),
+                            &body,
+                        )
+                        .await?
+                }
+            };
+            eprintln!("google: {} {sku}", action(changed));
+        }
+        return Ok(json!({ "sku": sku, "action": action(changed) }));
+    }
+
+    let mut listings: Vec<Value> = existing
+        .and_then(|e| e["listings"].as_array().cloned())
+        .unwrap_or_default();
+    for (locale, loc) in &product.localizations {
+        let listing =
+            json!({ "languageCode": locale, "title": loc.name, "description": loc.description });
+        match listings
+            .iter_mut()
+            .find(|l| l["languageCode"].as_str() == Some(locale))
+        {
+            // Keep fields the catalog does not cover, such as benefits
+            Some(current) => {
+                current["title"] = listing["title"].clone();
+                current["description"] = listing["description"].clone();
+            }
+            None => listings.push(listing),
+        }
+    }
+    let changed = existing.is_some_and(|e| e["listings"] != json!(listings));
+    match existing {
+        Some(current) if changed && !dry_run => {
+            let mut body = current.clone();
+            body["listings"] = json!(listings);
+            client
+                .patch(
+                    &format!("/{package_name}/monetization/subscriptions/{sku}?updateMask=listings&regionsVersion.version=2022/02"),
+                    &body,
+                )
+                .await?;
+        }
+        Some(_) => {}
+        None => {
+            if google.base_plans.is_empty() {
+                return Err("new Google Play subscriptions need google.base_plans".into());
+            }
+            if !dry_run {
+                let body = json!({
+                    "packageName": package_name,
+                    "productId": sku,
+                    "listings": listings,
+                    "basePlans": google.base_plans,
+                });
+                client
+                    .post(
+                        &format!("/{package_name}/monetization/subscriptions?productId={sku}&regionsVersion.version=2022/02"),
+                        &body,
+                    )
+                    .await?;
+            }
+        }
+    }
+    if !dry_run && (existing.is_none() || changed) {
+        eprintln!("google: {} {sku}", action(changed));
+    }
+    Ok(json!({ "sku": sku, "action": action(changed) }))
+}
+
+/// Google half of `import`.
+async fn google_import(
+    catalog: &Catalog,
+    package: Option<&str>,
+    profile: Option<&str>,
+    dry_run: bool,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = super::google::client_for_profile(profile).await?;
+    let package_name = &client.package_name(package)?;
+    let listed = google_pages(
+        &format!("/{package_name}/inappproducts"),
+        "inappproduct",
+        "token",
+        &client,
+    )
+    .await?;
+    let subs = google_pages(
+        &format!("/{package_name}/monetization/subscriptions"),
+        "subscriptions",
+        "pageToken",
+        &client,
+    )
+    .await?;
+    let existing: HashMap<&str, &Value> = listed
+        .iter()
+        .filter_map(|p| Some((p["sku"].as_str()?, p)))
+        .chain(
+            subs.iter()
+                .filter_map(|s| Some((s["productId"].as_str()?, s))),
+        )
+        .collect();
+
+    let mut products = Vec::new();
+    for product in catalog.products.iter().filter(|p| p.sold_on(Store::Google)) {
+        let current = existing.get(product.sku.as_str()).copied();
+        match google_product(product, package_name, current, dry_run, &client).await {
+            Ok(result) => products.push(result),
+            Err(e) => {
+                eprintln!("google: {}: {e}", product.sku);
+                products.push(json!({ "sku": product.sku, "error": e.to_string() }));
+            }
+        }
+    }
+    Ok(json!({ "package_name": package_name, "products": products }))
+}
+
+/// Which stores a catalog command works on: those asked about, or both when nothing
+/// store-specific was given.
+fn selected_stores(
+    app: Option<&str>,
+    package: Option<&str>,
+    apple_profile: Option<&str>,
+    google_profile: Option<&str>,
+) -> (bool, bool) {
+    let wants_apple = app.is_some() || apple_profile.is_some();
+    let wants_google = package.is_some() || google_profile.is_some();
+    let both = !wants_apple && !wants_google;
+    (both || wants_apple, both || wants_google)
+}
+
+pub async fn handle(cmd: &CatalogCommand, cli: &Cli) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        CatalogCommand::Export {
+            out,
+            app,
+            package,
+            apple_profile,
+            google_profile,
+            skip_prices,
+        } => {
+            let (apple, google) = selected_stores(
+                app.as_deref(),
+                package.as_deref(),
+                apple_profile.as_deref(),
+                google_profile.as_deref(),
+            );
+            let apple_profile = apple_profile.as_deref().or(cli.profile.as_deref());
+            let google_profile = google_profile.as_deref().or(cli.profile.as_deref());
+            // A partial catalog would read as products missing from a store, so
+            // either store failing fails the export
+            let (apple_products, google_products) = tokio::try_join!(
+                async {
+                    match apple {
+                        true => apple_export(app.as_deref(), apple_profile, *skip_prices).await,
+                        false => Ok(Vec::new()),
+                    }
+                },
+                async {
+                    match google {
+                        true => {
+                            google_export(package.as_deref(), google_profile, *skip_prices).await
+                        }
+                        false => Ok(Vec::new()),
+                    }
+                },
+            )?;
+            let counts = (apple_products.len(), google_products.len());
+            let (products, conflicts) = merge(apple_products, google_products);
+            let count = products.len();
+            std::fs::write(out, serde_yaml::to_string(&Catalog { products })?)?;
+            Ok(json!({
+                "status": "ok",
+                "file": out.display().to_string(),
+                "products": count,
+                "apple_products": counts.0,
+                "google_products": counts.1,
+                "conflicts": conflicts,
+            }))
+        }
+        CatalogCommand::Import {
+            file,
+            app,
+            package,
+            apple_profile,
+            google_profile,
+            dry_run,
+        } => {
+            let text = std::fs::read_to_string(file)
+                .map_err(|e| format!("cannot read {}: {e}", file.display()))?;
+            let catalog: Catalog = serde_yaml::from_str(&text)
+                .map_err(|e| format!("cannot parse {}: {e}", file.display()))?;
+            let flags: Vec<Value> = catalog
+                .products
+                .iter()
+                .flat_map(|p| {
+                    flags(p)
+                        .into_iter()
+                        .map(|note| json!({ "sku": p.sku, "note": note }))
+                })
+                .collect();
+
+            let (apple, google) = selected_stores(
+                app.as_deref(),
+                package.as_deref(),
+                apple_profile.as_deref(),
+                google_profile.as_deref(),
+            );
+            let apple_profile = apple_profile.as_deref().or(cli.profile.as_deref());
+            let google_profile = google_profile.as_deref().or(cli.profile.as_deref());
+            let mut result = super::join_stores(
+                apple.then(|| apple_import(&catalog, app.as_deref(), apple_profile, *dry_run)),
+                google
+                    .then(|| google_import(&catalog, package.as_deref(), google_profile, *dry_run)),
+            )
+            .await;
+            result["flags"] = json!(flags);
+            result["dry_run"] = json!(dry_run);
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(sku: &str, kind: ProductType, store: Store) -> CatalogProduct {
+        CatalogProduct {
+            sku: sku.to_string(),
+            kind,
+            period: None,
+            stores: vec![store],
+            localizations: BTreeMap::new(),
+            apple: None,
+            google: None,
+        }
+    }
+
+    fn loc(name: &str) -> Localization {
+        Localization {
+            name: name.to_string(),
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn converts_play_prices() {
+        assert_eq!(format_price("990000", "USD"), "0.99 USD");
+        assert_eq!(format_price("1000000", "EUR"), "1.00 EUR");
+        assert_eq!(format_price("12345000", "JPY"), "12.345 JPY");
+        assert_eq!(
+            parse_price("0.99 usd").unwrap(),
+            ("990000".to_string(), "USD".to_string())
+        );
+        assert_eq!(
+            parse_price("120 JPY").unwrap(),
+            ("120000000".to_string(), "JPY".to_string())
+        );
+        assert!(parse_price("0.99").is_err());
+        assert!(parse_price("1,99 EUR").is_err());
+    }
+
+    #[test]
+    fn merges_products_by_sku_keeping_app_store_text() {
+        let mut a = product("coins.100", ProductType::Consumable, Store::Apple);
+        a.localizations
+            .insert("en-US".to_string(), loc("100 Coins"));
+        let mut g = product("coins.100", ProductType::NonConsumable, Store::Google);
+        g.localizations
+            .insert("en-US".to_string(), loc("Coins x100"));
+        g.localizations
+            .insert("fr-FR".to_string(), loc("100 pièces"));
+        g.google = Some(GoogleFields {
+            default_price: Some("0.99 USD".to_string()),
+            ..Default::default()
+        });
+        let only_google = product("gems", ProductType::NonConsumable, Store::Google);
+
+        let (products, conflicts) = merge(vec![a], vec![g, only_google]);
+        assert_eq!(products.len(), 2);
+        let coins = &products[0];
+        assert_eq!(coins.kind, ProductType::Consumable);
+        assert_eq!(coins.stores, vec![Store::Apple, Store::Google]);
+        assert_eq!(coins.localizations["en-US"].name, "100 Coins");
+        assert_eq!(coins.localizations["fr-FR"].name, "100 pièces");
+        assert!(coins.google.is_some());
+        assert_eq!(products[1].stores, vec![Store::Google]);
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].contains("en-US"));
+    }
+
+    #[test]
+    fn flags_fields_a_store_cannot_take() {
+        let mut pass = product("pass", ProductType::NonRenewingSubscription, Store::Apple);
+        pass.stores = both_stores();
+        assert!(!pass.sold_on(Store::Google));
+        assert!(flags(&pass)[0].contains("non-renewing"));
+
+        let mut sub = product("pro.monthly", ProductType::Subscription, Store::Apple);
+        sub.apple = Some(AppleFields::default());
+        sub.google = Some(GoogleFields {
+            base_plans: vec![json!({ "basePlanId": "monthly" })],
+            ..Default::default()
+        });
+        let notes = flags(&sub);
+        assert!(notes.iter().any(|n| n.starts_with("apple.group")));
+        assert!(notes.iter().any(|n| n.contains("not sold on Google Play")));
+    }
+
+    #[test]
+    fn builds_new_play_product_with_default_language() {
+        let mut coins = product("coins.100", ProductType::Consumable, Store::Google);
+        coins
+            .localizations
+            .insert("fr-FR".to_string(), loc("100 pièces"));
+        coins
+            .localizations
+            .insert("en-US".to_string(), loc("100 Coins"));
+        coins.google = Some(GoogleFields {
+            default_price: Some("0.99 USD".to_string()),
+            ..Default::default()
+        });
+        let body = inapp_body(&coins, "com.example.app", None).unwrap();
+        assert_eq!(body["sku"], "coins.100");
+        assert_eq!(body["defaultLanguage"], "en-US");
+        assert_eq!(body["listings"]["en-US"]["title"], "100 Coins");
+        assert_eq!(body["defaultPrice"]["priceMicros"], "990000");
+
+        coins.google.as_mut().unwrap().default_language = Some("fr-FR".to_string());
+        let body = inapp_body(&coins, "com.example.app", None).unwrap();
+        assert_eq!(body["defaultLanguage"], "fr-FR");
+
+        coins.google.as_mut().unwrap().default_language = Some("de-DE".to_string());
+        assert!(inapp_body(&coins, "com.example.app", None).is_err());
+
+        coins.localizations.clear();
+        coins.google.as_mut().unwrap().default_language = None;
+        assert!(inapp_body(&coins, "com.example.app", None).is_err());
+    }
+
+    #[test]
+    fn parses_catalog_yaml_with_defaults() {
+        let catalog: Catalog = serde_yaml::from_str(
+            "products:\n  - sku: coins.100\n    type: consumable\n    localizations:\n      en-US:\n        name: 100 Coins\n    google:\n      default_price: 0.99 USD\n",
+        )
+        .unwrap();
+        let coins = &catalog.products[0];
+        assert_eq!(coins.stores, both_stores());
+        assert_eq!(coins.localizations["en-US"].description, "");
+        let text = serde_yaml::to_string(&catalog).unwrap();
+        assert_eq!(serde_yaml::from_str::<Catalog>(&text).unwrap(), catalog);
+    }
+}
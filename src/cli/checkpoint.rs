@@ -0,0 +1,117 @@
+//! Checkpoint for resuming an interrupted `sync push`.
+//!
+//! Push records each finished locale and screenshot set in
+//! `<metadata-dir>/.storeops-push.json` as it goes. Re-running with `--resume`
+//! skips everything recorded there (and, on Google Play, keeps using the same edit)
+//! instead of deleting and re-uploading every asset. The file is removed once the
+//! push completes.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = ".storeops-push.json";
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct PushCheckpoint {
+    /// Bundle ID or package name the checkpoint belongs to
+    pub target: String,
+    /// Google Play edit holding the staged changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit_id: Option<String>,
+    /// Finished work: `<locale>` for metadata, `<locale>/<dir>` for screenshot sets
+    #[serde(default)]
+    pub done: BTreeSet<String>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl PushCheckpoint {
+    /// Start a checkpoint for `target`, picking up a previous run's progress when resuming.
+    ///
+    /// A checkpoint left by a push for a different app is ignored.
+    pub fn open(metadata_dir: &Path, target: &str, resume: bool) -> Self {
+        let path = metadata_dir.join(FILE_NAME);
+        let previous = resume
+            .then(|| std::fs::read_to_string(&path).ok())
+            .flatten()
+            .and_then(|s| serde_json::from_str::<PushCheckpoint>(&s).ok())
+            .filter(|c| c.target == target);
+        match previous {
+            Some(c) => {
+                eprintln!("Resuming push: {} item(s) already done", c.done.len());
+                Self { path, ..c }
+            }
+            None => Self {
+                target: target.to_string(),
+                path,
+                ..Self::default()
+            },
+        }
+    }
+
+    pub fn is_done(&self, key: &str) -> bool {
+        self.done.contains(key)
+    }
+
+    /// Record finished work and persist the checkpoint.
+    pub fn mark_done(&mut self, key: String) {
+        self.done.insert(key);
+        self.save();
+    }
+
+    pub fn set_edit_id(&mut self, edit_id: &str) {
+        self.edit_id = Some(edit_id.to_string());
+        self.save();
+    }
+
+    /// Forget recorded progress, e.g. when the Google edit it belonged to has expired.
+    pub fn reset(&mut self) {
+        self.edit_id = None;
+        self.done.clear();
+    }
+
+    fn save(&self) {
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            if let Err(e) = std::fs::write(&self.path, json) {
+                eprintln!("Warning: Could not write checkpoint: {e}");
+            }
+        }
+    }
+
+    /// Remove the checkpoint after a successful push.
+    pub fn finish(self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resume_picks_up_progress_for_the_same_target_only() {
+        let dir = std::env::temp_dir().join(format!("storeops-checkpoint-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut first = PushCheckpoint::open(&dir, "com.example.app", false);
+        first.set_edit_id("edit-1");
+        first.mark_done("en-US".into());
+        first.mark_done("en-US/iphone67".into());
+
+        let resumed = PushCheckpoint::open(&dir, "com.example.app", true);
+        assert_eq!(resumed.edit_id.as_deref(), Some("edit-1"));
+        assert!(resumed.is_done("en-US/iphone67"));
+
+        assert!(PushCheckpoint::open(&dir, "com.example.app", false)
+            .done
+            .is_empty());
+        assert!(PushCheckpoint::open(&dir, "com.other.app", true)
+            .done
+            .is_empty());
+
+        resumed.finish();
+        assert!(!dir.join(FILE_NAME).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
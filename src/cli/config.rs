@@ -0,0 +1,52 @@
+use clap::Subcommand;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+use crate::config::{validate, Config};
+use crate::output::Failure;
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Check the config file for syntax errors, unknown keys and missing credentials
+    ///
+    /// Each problem names the line and key at fault, with a suggestion when a key
+    /// looks like a misspelling. Exits non-zero when the file is invalid.
+    ///
+    /// Examples:
+    ///   storeops config validate
+    ///   storeops config validate --file ./ci/config.toml
+    Validate {
+        /// Config file to check (defaults to the active config path)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+}
+
+pub async fn handle(cmd: &ConfigCommand) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        ConfigCommand::Validate { file } => {
+            let path = match file {
+                Some(path) => path.clone(),
+                None => Config::config_path().ok_or("cannot determine config directory")?,
+            };
+            if !path.exists() {
+                return Err(format!("config file not found: {}", path.display()).into());
+            }
+            let content = tokio::fs::read_to_string(&path).await?;
+            let problems: Vec<String> = validate::check(&content)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            let result = json!({
+                "path": path.display().to_string(),
+                "valid": problems.is_empty(),
+                "problems": problems,
+            });
+            if problems.is_empty() {
+                Ok(result)
+            } else {
+                Err(Box::new(Failure(result)))
+            }
+        }
+    }
+}
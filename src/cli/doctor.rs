@@ -0,0 +1,334 @@
+//! `storeops doctor`: one report of everything that commonly breaks a setup.
+//!
+//! Checks the config file, every profile's credential file, that the store APIs can
+//! be reached, the local clock (Apple rejects tokens from a skewed clock), and
+//! whether a newer release exists. Each check passes, warns or fails; any failure
+//! makes the command exit non-zero.
+
+use clap::Args;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::auth::{google::ServiceAccount, store};
+use crate::config::profiles::Credentials;
+use crate::config::{validate, Config};
+
+/// Hosts every command depends on, probed with an unauthenticated request.
+const ENDPOINTS: &[(&str, &str)] = &[
+    (
+        "App Store Connect API",
+        "https://api.appstoreconnect.apple.com/v1",
+    ),
+    (
+        "Google Play Developer API",
+        "https://androidpublisher.googleapis.com/androidpublisher/v3/applications",
+    ),
+    ("Google OAuth", "https://oauth2.googleapis.com/token"),
+];
+
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Skew beyond which token timestamps are rejected.
+const MAX_CLOCK_SKEW_SECS: i64 = 60;
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Only check local files; skip the network, clock and update checks
+    #[arg(long)]
+    pub offline: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Status::Pass => "pass",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+        }
+    }
+}
+
+struct Check {
+    name: String,
+    status: Status,
+    detail: String,
+}
+
+fn check(name: impl Into<String>, status: Status, detail: impl Into<String>) -> Check {
+    Check {
+        name: name.into(),
+        status,
+        detail: detail.into(),
+    }
+}
+
+/// The config file parses and has no unknown keys or missing credentials.
+fn config_checks(path: Option<&Path>) -> Vec<Check> {
+    let Some(path) = path else {
+        return vec![check(
+            "config",
+            Status::Fail,
+            "cannot determine config directory",
+        )];
+    };
+    let shown = path.display();
+    if !path.exists() {
+        return vec![check(
+            "config",
+            Status::Warn,
+            format!("no config file at {shown}; run `storeops auth login`"),
+        )];
+    }
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            return vec![check(
+                "config",
+                Status::Fail,
+                format!("cannot read {shown}: {e}"),
+            )]
+        }
+    };
+    let problems: Vec<String> = validate::check(&content)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let mut checks = vec![if problems.is_empty() {
+        check("config", Status::Pass, format!("{shown} is valid"))
+    } else {
+        check("config", Status::Fail, problems.join("; "))
+    }];
+    if let Some(warning) = store::permission_warning(&path.to_string_lossy()) {
+        checks.push(check("config permissions", Status::Warn, warning));
+    }
+    checks
+}
+
+/// A credential file exists, is private and holds a usable key.
+fn credential_check(name: &str, credentials: &Credentials) -> Check {
+    let name = format!("credentials: {name}");
+    let (path, kind) = match credentials {
+        Credentials::Apple { key_path, .. } => (key_path, "API key"),
+        Credentials::Google {
+            service_account_path,
+        } => (service_account_path, "service account"),
+    };
+    let path = match store::expand_path(path) {
+        Ok(path) => path,
+        Err(e) => return check(name, Status::Fail, e),
+    };
+    let content = match std::fs::read(&path) {
+        Ok(content) => content,
+        Err(e) => return check(name, Status::Fail, format!("cannot read {path}: {e}")),
+    };
+    let parsed = match credentials {
+        Credentials::Apple { .. } => jsonwebtoken::EncodingKey::from_ec_pem(&content)
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        Credentials::Google { .. } => serde_json::from_slice::<ServiceAccount>(&content)
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+    };
+    if let Err(e) = parsed {
+        return check(
+            name,
+            Status::Fail,
+            format!("{path} is not a valid {kind}: {e}"),
+        );
+    }
+    match store::permission_warning(&path) {
+        Some(warning) => check(name, Status::Warn, warning),
+        None => check(name, Status::Pass, path),
+    }
+}
+
+/// Whether the local clock is close enough to a server's `Date` header.
+fn clock_check(server_date: &str, now: chrono::DateTime<chrono::Utc>) -> Check {
+    let server = match chrono::DateTime::parse_from_rfc2822(server_date) {
+        Ok(date) => date,
+        Err(e) => {
+            return check(
+                "clock",
+                Status::Warn,
+                format!("cannot parse server date '{server_date}': {e}"),
+            )
+        }
+    };
+    let skew = (now - server.to_utc()).num_seconds();
+    if skew.abs() > MAX_CLOCK_SKEW_SECS {
+        check(
+            "clock",
+            Status::Fail,
+            format!(
+                "local clock is {}s {} the server's; API tokens will be rejected until it is synced",
+                skew.abs(),
+                if skew > 0 { "ahead of" } else { "behind" }
+            ),
+        )
+    } else {
+        check(
+            "clock",
+            Status::Pass,
+            format!("within {}s of the server", skew.abs()),
+        )
+    }
+}
+
+/// Probe each API host; any HTTP response means it is reachable. The first `Date`
+/// header seen is returned for the clock check.
+async fn network_checks() -> (Vec<Check>, Option<String>) {
+    let client = crate::api::session::http();
+    let probes = ENDPOINTS.iter().map(|(name, url)| {
+        let client = client.clone();
+        async move {
+            let resp = client.get(*url).timeout(NETWORK_TIMEOUT).send().await;
+            (*name, *url, resp)
+        }
+    });
+    let mut checks = Vec::new();
+    let mut date = None;
+    for (name, url, resp) in futures::future::join_all(probes).await {
+        match resp {
+            Ok(resp) => {
+                if date.is_none() {
+                    date = resp
+                        .headers()
+                        .get(reqwest::header::DATE)
+                        .and_then(|d| d.to_str().ok())
+                        .map(String::from);
+                }
+                checks.push(check(name, Status::Pass, format!("{url} reachable")));
+            }
+            Err(e) => checks.push(check(name, Status::Fail, format!("{url} unreachable: {e}"))),
+        }
+    }
+    (checks, date)
+}
+
+async fn update_check() -> Check {
+    let current = env!("CARGO_PKG_VERSION");
+    match crate::update::latest_release().await {
+        Ok((latest, true)) => check(
+            "update",
+            Status::Warn,
+            format!("{latest} is available (running {current}); run `storeops update`"),
+        ),
+        Ok(_) => check(
+            "update",
+            Status::Pass,
+            format!("{current} is the latest release"),
+        ),
+        Err(e) => check(
+            "update",
+            Status::Warn,
+            format!("cannot check for updates: {e}"),
+        ),
+    }
+}
+
+fn report(checks: &[Check]) -> Value {
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let failed = count(Status::Fail);
+    json!({
+        "status": if failed == 0 { "pass" } else { "fail" },
+        "summary": {
+            "pass": count(Status::Pass),
+            "warn": count(Status::Warn),
+            "fail": failed,
+        },
+        "checks": checks
+            .iter()
+            .map(|c| json!({ "check": c.name, "status": c.status.as_str(), "detail": c.detail }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+pub async fn handle(args: &DoctorArgs) -> Result<Value, Box<dyn std::error::Error>> {
+    let path = Config::config_path();
+    let mut checks = config_checks(path.as_deref());
+
+    // Profiles can only be checked once the config loads; config_checks covers the rest
+    if let Ok(config) = Config::load() {
+        let mut profiles: Vec<_> = config.profiles.iter().collect();
+        profiles.sort_by_key(|(name, _)| name.as_str());
+        for (name, profile) in profiles {
+            checks.push(credential_check(name, &profile.credentials));
+        }
+    }
+    if let Ok(key_path) = std::env::var("STOREOPS_APPLE_KEY_PATH") {
+        let credentials = Credentials::Apple {
+            key_id: String::new(),
+            issuer_id: String::new(),
+            key_path,
+        };
+        checks.push(credential_check("STOREOPS_APPLE_KEY_PATH", &credentials));
+    }
+    if let Ok(service_account_path) = std::env::var("STOREOPS_GOOGLE_SERVICE_ACCOUNT") {
+        let credentials = Credentials::Google {
+            service_account_path,
+        };
+        checks.push(credential_check(
+            "STOREOPS_GOOGLE_SERVICE_ACCOUNT",
+            &credentials,
+        ));
+    }
+
+    if !args.offline {
+        eprintln!("Checking network, clock and updates...");
+        let ((network, date), update) = tokio::join!(network_checks(), update_check());
+        checks.extend(network);
+        checks.push(match date {
+            Some(date) => clock_check(&date, chrono::Utc::now()),
+            None => check(
+                "clock",
+                Status::Warn,
+                "no server reachable to compare against",
+            ),
+        });
+        checks.push(update);
+    }
+
+    let result = report(&checks);
+    if result["status"] == "fail" {
+        return Err(Box::new(crate::output::Failure(result)));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_clock_skew_and_failed_checks() {
+        let now = chrono::DateTime::parse_from_rfc3339("2024-11-15T08:12:31Z")
+            .unwrap()
+            .to_utc();
+        assert_eq!(
+            clock_check("Fri, 15 Nov 2024 08:12:01 GMT", now).status,
+            Status::Pass
+        );
+        let ahead = clock_check("Fri, 15 Nov 2024 08:07:31 GMT", now);
+        assert_eq!(ahead.status, Status::Fail);
+        assert!(ahead.detail.starts_with("local clock is 300s ahead of"));
+        assert_eq!(clock_check("yesterday", now).status, Status::Warn);
+
+        let checks = [
+            check("config", Status::Pass, "ok"),
+            check("clock", Status::Warn, "unknown"),
+            check("credentials: ci", Status::Fail, "missing"),
+        ];
+        let value = report(&checks);
+        assert_eq!(value["status"], "fail");
+        assert_eq!(value["summary"], json!({ "pass": 1, "warn": 1, "fail": 1 }));
+        assert_eq!(value["checks"][2]["check"], "credentials: ci");
+    }
+}
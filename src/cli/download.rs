@@ -0,0 +1,136 @@
+//! Concurrent image downloads for `sync pull`.
+//!
+//! Both stores collect every image of a pull first and download them here, through
+//! one shared HTTP client and at most `concurrency` requests at a time. Images that
+//! already exist locally are requested with their previous ETag and skipped when the
+//! server answers `304 Not Modified`.
+
+use futures::stream::{self, StreamExt};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::api::recording::RecordedSend;
+use crate::output::stream as progress;
+
+/// Images downloaded at the same time unless `--concurrency` says otherwise.
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+/// How `sync pull` fetches from the stores.
+#[derive(Clone, Copy)]
+pub struct FetchOptions {
+    /// Images downloaded at the same time
+    pub concurrency: usize,
+    /// Ignore ETags remembered by the previous pull
+    pub no_cache: bool,
+}
+
+/// One image to fetch into the metadata directory.
+pub struct Download {
+    pub url: String,
+    pub path: PathBuf,
+    /// Internal locale the image belongs to
+    pub locale: String,
+    /// Path relative to the locale directory, used in progress output
+    pub file: String,
+    /// ETag from the previous pull, sent as `If-None-Match`
+    pub etag: Option<String>,
+}
+
+impl Download {
+    /// Key of the image in the pull manifest.
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.locale, self.file)
+    }
+}
+
+/// Outcome of `download_all`.
+#[derive(Default)]
+pub struct Downloaded {
+    pub downloaded: u32,
+    pub unchanged: u32,
+    /// New ETag (or `None`) per manifest key of every successfully fetched image
+    pub etags: Vec<(String, Option<String>)>,
+}
+
+enum Fetched {
+    Modified(Option<String>),
+    Unchanged,
+}
+
+async fn fetch(
+    http: &reqwest::Client,
+    d: &Download,
+) -> Result<Fetched, Box<dyn std::error::Error>> {
+    let mut req = http.get(&d.url);
+    if let Some(etag) = d.etag.as_deref().filter(|_| d.path.exists()) {
+        req = req.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    let response = req.send_recorded().await?;
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::Unchanged);
+    }
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}: {}", response.status(), d.url).into());
+    }
+    let etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    let bytes = response.bytes().await?;
+    if let Some(parent) = d.path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&d.path, bytes).await?;
+    Ok(Fetched::Modified(etag))
+}
+
+/// Download everything, reporting each image as it finishes.
+pub async fn download_all(downloads: Vec<Download>, concurrency: usize) -> Downloaded {
+    let http = crate::api::session::http();
+    let done = Mutex::new(Downloaded::default());
+    stream::iter(&downloads)
+        .for_each_concurrent(concurrency.max(1), |d| {
+            let http = &http;
+            let done = &done;
+            async move {
+                match fetch(http, d).await {
+                    Ok(Fetched::Unchanged) => {
+                        if let Ok(mut done) = done.lock() {
+                            done.unchanged += 1;
+                        }
+                        progress::emit(
+                            "screenshot",
+                            json!({ "locale": d.locale, "file": d.file, "status": "unchanged" }),
+                        );
+                    }
+                    Ok(Fetched::Modified(etag)) => {
+                        if let Ok(mut done) = done.lock() {
+                            done.downloaded += 1;
+                            done.etags.push((d.key(), etag));
+                        }
+                        eprintln!("  Downloaded: {}/{}", d.locale, d.file);
+                        progress::emit(
+                            "screenshot",
+                            json!({ "locale": d.locale, "file": d.file, "status": "downloaded" }),
+                        );
+                    }
+                    Err(e) => {
+                        eprintln!("  Failed to download {}/{}: {e}", d.locale, d.file);
+                        progress::emit(
+                            "screenshot",
+                            json!({
+                                "locale": d.locale,
+                                "file": d.file,
+                                "status": "failed",
+                                "error": e.to_string(),
+                            }),
+                        );
+                    }
+                }
+            }
+        })
+        .await;
+    done.into_inner().unwrap_or_default()
+}
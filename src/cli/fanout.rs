@@ -0,0 +1,189 @@
+//! Run one read-only command against several profiles at once.
+//!
+//! `--all-profiles` selects every profile of the command's store; `--profiles a,b`
+//! names them explicitly. Each profile gets its own parse of the original arguments,
+//! so per-profile defaults apply, and results are merged into one object keyed by
+//! profile name. A failing profile reports `{"error": ...}` without failing the rest.
+
+use clap::{CommandFactory, Parser};
+use serde_json::{json, Map, Value};
+
+use super::Cli;
+use crate::config::profiles::Store;
+use crate::config::Config;
+
+/// Leaf subcommands that only read from the stores.
+const READ_ONLY_COMMANDS: &[&str] = &[
+    "list",
+    "get",
+    "info",
+    "status",
+    "coverage",
+    "lookup",
+    "latest",
+    "diff",
+    "icons",
+    "points",
+    "territories",
+    "sales",
+];
+
+/// Whether a leaf subcommand only reads from the stores.
+pub fn is_read_only(leaf: &str) -> bool {
+    READ_ONLY_COMMANDS.contains(&leaf)
+}
+
+/// Subcommand names from the top level down to the leaf.
+fn command_path(args: &[String]) -> Vec<String> {
+    let Ok(matches) = Cli::command().try_get_matches_from(args) else {
+        return Vec::new();
+    };
+    let mut path = Vec::new();
+    let mut current = &matches;
+    while let Some((name, sub)) = current.subcommand() {
+        path.push(name.to_string());
+        current = sub;
+    }
+    path
+}
+
+/// Profiles to run against, validated against the command's store.
+fn select_profiles(cli: &Cli, config: &Config, store: &str) -> Result<Vec<String>, String> {
+    let matches_store = |name: &str| {
+        config.profiles.get(name).is_some_and(|p| {
+            matches!(
+                (&p.store, store),
+                (Store::Apple, "apple") | (Store::Google, "google")
+            )
+        })
+    };
+    let mut names: Vec<String> = if cli.all_profiles {
+        config
+            .profiles
+            .keys()
+            .filter(|n| matches_store(n))
+            .cloned()
+            .collect()
+    } else {
+        for name in &cli.profiles {
+            if !config.profiles.contains_key(name) {
+                return Err(format!("profile '{name}' not found"));
+            }
+            if !matches_store(name) {
+                return Err(format!("profile '{name}' is not an {store} profile"));
+            }
+        }
+        cli.profiles.clone()
+    };
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        return Err(format!("no {store} profiles configured"));
+    }
+    Ok(names)
+}
+
+/// Execute `args` once per selected profile, concurrently.
+pub async fn run(cli: &Cli, args: &[String]) -> Result<Value, Box<dyn std::error::Error>> {
+    let path = command_path(args);
+    let store = match path.first().map(String::as_str) {
+        Some(store @ ("apple" | "google")) => store,
+        _ => {
+            return Err("--all-profiles/--profiles only apply to apple and google commands".into())
+        }
+    };
+    let leaf = path.last().map(String::as_str).unwrap_or_default();
+    if !is_read_only(leaf) {
+        return Err(format!(
+            "'{}' may modify store data; only read-only commands can run across profiles",
+            path.join(" ")
+        )
+        .into());
+    }
+
+    let config = Config::load()?;
+    let profiles = select_profiles(cli, &config, store)?;
+
+    let runs = profiles.iter().map(|name| async move {
+        let mut cli = Cli::try_parse_from(args)?;
+        cli.all_profiles = false;
+        cli.profiles.clear();
+        cli.profile = Some(name.clone());
+        cli.apply_profile_defaults();
+        crate::run(cli).await
+    });
+    let results = futures::future::join_all(runs).await;
+
+    let mut merged = Map::new();
+    for (name, result) in profiles.into_iter().zip(results) {
+        let value = result.unwrap_or_else(|e| json!({ "error": e.to_string() }));
+        merged.insert(name, value);
+    }
+    Ok(Value::Object(merged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::profiles::{Credentials, Profile, ProfileDefaults};
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        for (name, store) in [
+            ("a", Store::Apple),
+            ("b", Store::Apple),
+            ("g", Store::Google),
+        ] {
+            let credentials = match store {
+                Store::Apple => Credentials::Apple {
+                    key_id: "K".to_string(),
+                    issuer_id: "I".to_string(),
+                    key_path: "/tmp/key.p8".to_string(),
+                },
+                Store::Google => Credentials::Google {
+                    service_account_path: "/tmp/sa.json".to_string(),
+                },
+            };
+            config.profiles.insert(
+                name.to_string(),
+                Profile {
+                    store,
+                    credentials,
+                    defaults: ProfileDefaults::default(),
+                    retired_keys: Vec::new(),
+                },
+            );
+        }
+        config
+    }
+
+    #[test]
+    fn command_path_reaches_the_leaf() {
+        let path = command_path(&args("storeops apple builds list 123 --all-profiles"));
+        assert_eq!(path, vec!["apple", "builds", "list"]);
+    }
+
+    #[test]
+    fn all_profiles_selects_the_commands_store() {
+        let cli = Cli::parse_from(args("storeops apple apps list --all-profiles"));
+        assert_eq!(
+            select_profiles(&cli, &config(), "apple").unwrap(),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            select_profiles(&cli, &config(), "google").unwrap(),
+            vec!["g"]
+        );
+    }
+
+    #[test]
+    fn named_profiles_must_match_the_store() {
+        let cli = Cli::parse_from(args("storeops apple apps list --profiles b,g"));
+        let err = select_profiles(&cli, &config(), "apple").unwrap_err();
+        assert!(err.contains("'g'"));
+    }
+}
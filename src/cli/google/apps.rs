@@ -1,18 +1,76 @@
 use clap::Subcommand;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 use crate::api::google_client::GoogleClient;
 
 #[derive(Subcommand)]
 pub enum AppsCommand {
-    /// Get app details from Google Play Console
+    /// List the apps these credentials can act on
+    ///
+    /// Covers every developer account the service account has been invited to.
+    /// Apps only appear once the service account has access to them in Play Console
+    /// (Users and permissions).
+    ///
+    /// Examples:
+    ///   storeops google apps list
+    ///   storeops google apps list --all-profiles
+    List,
+    /// Get app details and the latest production release from Google Play Console
     ///
     /// Examples:
     ///   storeops google apps info com.example.app
     Info {
         /// Your app's package name (e.g., com.example.app)
-        package_name: String,
+        package_name: Option<String>,
     },
+    /// Update the app's default language and contact details
+    ///
+    /// Examples:
+    ///   storeops google apps update-details com.example.app --contact-email support@example.com
+    ///   storeops google apps update-details com.example.app --default-language en-US --contact-website https://example.com
+    UpdateDetails {
+        /// Your app's package name (e.g., com.example.app)
+        package_name: Option<String>,
+        /// Default listing language (BCP-47, e.g., en-US)
+        #[arg(long)]
+        default_language: Option<String>,
+        /// Contact email shown on the store listing
+        #[arg(long)]
+        contact_email: Option<String>,
+        /// Contact phone number shown on the store listing
+        #[arg(long)]
+        contact_phone: Option<String>,
+        /// Contact website shown on the store listing
+        #[arg(long)]
+        contact_website: Option<String>,
+    },
+}
+
+/// The release with the highest version code on a track, if any.
+pub fn latest_release(track: &Value) -> Option<&Value> {
+    let max_code = |r: &Value| {
+        r["versionCodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|c| c.as_str()?.parse::<i64>().ok())
+            .max()
+            .unwrap_or(0)
+    };
+    track["releases"]
+        .as_array()?
+        .iter()
+        .max_by_key(|r| max_code(r))
+}
+
+/// Row of `apps list`, flagging the profile's `default_package`.
+fn app_summary(app: &Value, default_package: Option<&str>) -> Value {
+    let package_name = app["packageName"].as_str().unwrap_or_default();
+    json!({
+        "package_name": package_name,
+        "display_name": app["displayName"],
+        "default": default_package == Some(package_name),
+    })
 }
 
 pub async fn handle(
@@ -20,18 +78,112 @@ pub async fn handle(
     client: &GoogleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
+        AppsCommand::List => {
+            let default_package = client.package_name(None).ok();
+            let mut apps = Vec::new();
+            let mut token: Option<String> = None;
+            loop {
+                let page = client.search_apps(token.as_deref()).await?;
+                apps.extend(
+                    page["apps"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|app| app_summary(app, default_package.as_deref())),
+                );
+                token = page["nextPageToken"]
+                    .as_str()
+                    .filter(|t| !t.is_empty())
+                    .map(String::from);
+                if token.is_none() {
+                    return Ok(Value::Array(apps));
+                }
+            }
+        }
         AppsCommand::Info { package_name } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &serde_json::json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let details: Value = client
                 .get(&format!("/{package_name}/edits/{edit_id}/details"), &[])
                 .await?;
-            let _ = client
-                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
-                .await;
-            Ok(details)
+            // Apps that never shipped to production have no track to read
+            let production: Value = client
+                .get(
+                    &format!("/{package_name}/edits/{edit_id}/tracks/production"),
+                    &[],
+                )
+                .await
+                .unwrap_or(Value::Null);
+            client.discard_edit(package_name, edit_id).await;
+            Ok(json!({
+                "package_name": package_name,
+                "default_language": details["defaultLanguage"],
+                "contact_email": details["contactEmail"],
+                "contact_phone": details["contactPhone"],
+                "contact_website": details["contactWebsite"],
+                "production_release": latest_release(&production),
+            }))
+        }
+        AppsCommand::UpdateDetails {
+            package_name,
+            default_language,
+            contact_email,
+            contact_phone,
+            contact_website,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let mut body = json!({});
+            if let Some(l) = default_language {
+                body["defaultLanguage"] = json!(l);
+            }
+            if let Some(e) = contact_email {
+                body["contactEmail"] = json!(e);
+            }
+            if let Some(p) = contact_phone {
+                body["contactPhone"] = json!(p);
+            }
+            if let Some(w) = contact_website {
+                body["contactWebsite"] = json!(w);
+            }
+            if body.as_object().is_some_and(|o| o.is_empty()) {
+                return Err("nothing to update: pass at least one of --default-language, --contact-email, --contact-phone, --contact-website".into());
+            }
+
+            let edit_id = &client.open_edit(package_name).await?;
+            let result = client
+                .patch(&format!("/{package_name}/edits/{edit_id}/details"), &body)
+                .await?;
+            client.commit_edit(package_name, edit_id).await?;
+            Ok(result)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latest_release_has_highest_version_code() {
+        let track = json!({"releases": [
+            {"name": "1.9", "versionCodes": ["9", "10"], "status": "completed"},
+            {"name": "2.0", "versionCodes": ["12"], "status": "inProgress"}
+        ]});
+        assert_eq!(latest_release(&track).unwrap()["name"], "2.0");
+        assert!(latest_release(&Value::Null).is_none());
+    }
+
+    #[test]
+    fn app_summary_flags_the_default_package() {
+        let app = json!({
+            "name": "apps/com.example.app",
+            "packageName": "com.example.app",
+            "displayName": "Example"
+        });
+        let row = app_summary(&app, Some("com.example.app"));
+        assert_eq!(row["package_name"], "com.example.app");
+        assert_eq!(row["display_name"], "Example");
+        assert_eq!(row["default"], true);
+        assert_eq!(app_summary(&app, None)["default"], false);
+    }
+}
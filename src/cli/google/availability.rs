@@ -2,13 +2,14 @@ use clap::Subcommand;
 use serde_json::{json, Value};
 
 use crate::api::google_client::GoogleClient;
+use crate::config::territories::{self, CodeStyle};
 
 #[derive(Subcommand)]
 pub enum AvailabilityCommand {
     /// Get country targeting / availability for a track
     Get {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Track name (e.g., production, beta)
         #[arg(long)]
         track: String,
@@ -16,7 +17,7 @@ pub enum AvailabilityCommand {
     /// List all available country codes for a track
     Countries {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Track name (e.g., production, beta)
         #[arg(long)]
         track: String,
@@ -24,11 +25,11 @@ pub enum AvailabilityCommand {
     /// Update country targeting for a release
     Update {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Track name
         #[arg(long)]
         track: String,
-        /// Country codes to include (comma-separated, e.g., US,GB,JP)
+        /// Country codes or @groups to include (comma-separated, e.g., US,GB,@latam)
         #[arg(long, value_delimiter = ',')]
         countries: Vec<String>,
         /// Whether to target rest of world by default
@@ -46,38 +47,30 @@ pub async fn handle(
             package_name,
             track,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let result: Value = client
                 .get(
                     &format!("/{package_name}/edits/{edit_id}/countryAvailability/{track}"),
                     &[],
                 )
                 .await?;
-            let _ = client
-                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
-                .await;
+            client.discard_edit(package_name, edit_id).await;
             Ok(result)
         }
         AvailabilityCommand::Countries {
             package_name,
             track,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let result: Value = client
                 .get(
                     &format!("/{package_name}/edits/{edit_id}/countryAvailability/{track}"),
                     &[],
                 )
                 .await?;
-            let _ = client
-                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
-                .await;
+            client.discard_edit(package_name, edit_id).await;
             Ok(result)
         }
         AvailabilityCommand::Update {
@@ -86,11 +79,10 @@ pub async fn handle(
             countries,
             rest_of_world,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
 
+            let countries = territories::resolve(countries, CodeStyle::Alpha2)?;
             let country_targeting: Vec<Value> = countries
                 .iter()
                 .map(|c| json!({ "countryCode": c }))
@@ -107,12 +99,7 @@ pub async fn handle(
                     &body,
                 )
                 .await?;
-            client
-                .post(
-                    &format!("/{package_name}/edits/{edit_id}:commit"),
-                    &json!({}),
-                )
-                .await?;
+            client.commit_edit(package_name, edit_id).await?;
             Ok(result)
         }
     }
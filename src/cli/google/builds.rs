@@ -1,5 +1,5 @@
 use clap::Subcommand;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 use crate::api::google_client::GoogleClient;
 
@@ -8,16 +8,51 @@ pub enum BuildsCommand {
     /// List bundles/APKs
     List {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
     },
     /// Upload a bundle/APK
     Upload {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Path to .aab or .apk file
         #[arg(long)]
         file: String,
     },
+    /// Highest version code released on a track
+    Latest {
+        /// Package name
+        package_name: Option<String>,
+        /// Track name
+        #[arg(long, default_value = "production")]
+        track: String,
+    },
+    /// Next free version code: one above the highest on any track or uploaded build
+    ///
+    /// Examples:
+    ///   storeops google builds next-code com.example.app
+    NextCode {
+        /// Package name
+        package_name: Option<String>,
+    },
+}
+
+/// Highest version code across the releases of a track.
+fn max_track_code(track: &Value) -> Option<i64> {
+    track["releases"]
+        .as_array()?
+        .iter()
+        .flat_map(|r| r["versionCodes"].as_array().into_iter().flatten())
+        .filter_map(|c| c.as_str()?.parse::<i64>().ok())
+        .max()
+}
+
+/// Highest `versionCode` in a bundles or APKs listing.
+fn max_uploaded_code(listing: &Value, key: &str) -> Option<i64> {
+    listing[key]
+        .as_array()?
+        .iter()
+        .filter_map(|b| b["versionCode"].as_i64())
+        .max()
 }
 
 pub async fn handle(
@@ -26,31 +61,92 @@ pub async fn handle(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         BuildsCommand::List { package_name } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &serde_json::json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let bundles: Value = client
                 .get(&format!("/{package_name}/edits/{edit_id}/bundles"), &[])
                 .await?;
-            let _ = client
-                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
-                .await;
+            client.discard_edit(package_name, edit_id).await;
             Ok(bundles)
         }
         BuildsCommand::Upload { package_name, file } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &serde_json::json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let result = client.upload_file(package_name, edit_id, file).await?;
-            let _ = client
-                .post(
-                    &format!("/{package_name}/edits/{edit_id}:commit"),
-                    &serde_json::json!({}),
-                )
-                .await?;
+            let _ = client.commit_edit(package_name, edit_id).await?;
             Ok(result)
         }
+        BuildsCommand::Latest {
+            package_name,
+            track,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
+            let track_info = client
+                .get::<Value>(
+                    &format!("/{package_name}/edits/{edit_id}/tracks/{track}"),
+                    &[],
+                )
+                .await;
+            client.discard_edit(package_name, edit_id).await;
+            Ok(json!({
+                "package_name": package_name,
+                "track": track,
+                "version_code": max_track_code(&track_info?),
+            }))
+        }
+        BuildsCommand::NextCode { package_name } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
+            let base = format!("/{package_name}/edits/{edit_id}");
+            let listings = async {
+                let tracks: Value = client.get(&format!("{base}/tracks"), &[]).await?;
+                let bundles: Value = client.get(&format!("{base}/bundles"), &[]).await?;
+                let apks: Value = client.get(&format!("{base}/apks"), &[]).await?;
+                Ok::<_, Box<dyn std::error::Error>>((tracks, bundles, apks))
+            }
+            .await;
+            client.discard_edit(package_name, edit_id).await;
+            let (tracks, bundles, apks) = listings?;
+
+            let by_track: serde_json::Map<String, Value> = tracks["tracks"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|t| Some((t["track"].as_str()?.to_string(), json!(max_track_code(t)))))
+                .collect();
+            let highest = by_track
+                .values()
+                .filter_map(Value::as_i64)
+                .chain(max_uploaded_code(&bundles, "bundles"))
+                .chain(max_uploaded_code(&apks, "apks"))
+                .max()
+                .unwrap_or(0);
+            Ok(json!({
+                "package_name": package_name,
+                "highest": highest,
+                "next": highest + 1,
+                "tracks": by_track,
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_highest_codes_in_tracks_and_uploads() {
+        let track = json!({"releases": [
+            {"versionCodes": ["41", "39"]},
+            {"versionCodes": ["105"]}
+        ]});
+        assert_eq!(max_track_code(&track), Some(105));
+        assert_eq!(max_track_code(&json!({"track": "alpha"})), None);
+
+        let bundles = json!({"bundles": [{"versionCode": 7}, {"versionCode": 110}]});
+        assert_eq!(max_uploaded_code(&bundles, "bundles"), Some(110));
+        assert_eq!(max_uploaded_code(&json!({}), "apks"), None);
     }
 }
@@ -0,0 +1,125 @@
+//! Google Play data safety declarations.
+//!
+//! Play accepts the declaration as the CSV exported from Play Console. A JSON
+//! array of row objects keyed by the CSV headers is also accepted and converted.
+
+use clap::Subcommand;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+use crate::api::google_client::GoogleClient;
+
+/// Column order of the Play Console data safety CSV export.
+const CSV_HEADERS: &[&str] = &[
+    "Question ID (machine readable)",
+    "Response ID (machine readable)",
+    "Response value",
+    "Answer requirement",
+    "Human-friendly question label",
+];
+
+#[derive(Subcommand)]
+pub enum DataSafetyCommand {
+    /// Upload the data safety declaration
+    ///
+    /// Examples:
+    ///   storeops google datasafety update com.example.app --file datasafety.csv
+    ///   storeops google datasafety update com.example.app --file datasafety.json
+    Update {
+        /// Package name
+        package_name: Option<String>,
+        /// Data safety CSV (Play Console export) or JSON array of rows
+        #[arg(long)]
+        file: PathBuf,
+    },
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Convert a JSON array of rows keyed by the CSV headers into Play's CSV format.
+fn rows_to_csv(rows: &Value) -> Result<String, String> {
+    let rows = rows
+        .as_array()
+        .ok_or("data safety JSON must be an array of rows")?;
+    let mut lines = vec![CSV_HEADERS
+        .iter()
+        .map(|h| csv_field(h))
+        .collect::<Vec<_>>()
+        .join(",")];
+    for (i, row) in rows.iter().enumerate() {
+        let obj = row
+            .as_object()
+            .ok_or_else(|| format!("row {} is not an object", i + 1))?;
+        let cells: Vec<String> = CSV_HEADERS
+            .iter()
+            .map(|h| match obj.get(*h) {
+                Some(Value::String(s)) => csv_field(s),
+                Some(Value::Null) | None => String::new(),
+                Some(other) => csv_field(&other.to_string()),
+            })
+            .collect();
+        lines.push(cells.join(","));
+    }
+    Ok(lines.join("\n"))
+}
+
+pub async fn handle(
+    cmd: &DataSafetyCommand,
+    client: &GoogleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        DataSafetyCommand::Update { package_name, file } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let content = std::fs::read_to_string(file)
+                .map_err(|e| format!("cannot read {}: {e}", file.display()))?;
+            let is_json = file
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+            let csv = if is_json {
+                rows_to_csv(&serde_json::from_str(&content)?)?
+            } else {
+                content
+            };
+
+            client
+                .post(
+                    &format!("/{package_name}/dataSafety"),
+                    &json!({ "safetyLabels": csv }),
+                )
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_to_csv_writes_header_and_quotes() {
+        let rows = json!([{
+            "Question ID (machine readable)": "PSL_DATA_COLLECTION_COLLECTS_PERSONAL_DATA",
+            "Response value": "true",
+            "Human-friendly question label": "Does your app collect, share, or use data?"
+        }]);
+        let csv = rows_to_csv(&rows).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("Question ID (machine readable),"));
+        assert_eq!(
+            lines[1],
+            "PSL_DATA_COLLECTION_COLLECTS_PERSONAL_DATA,,true,,\"Does your app collect, share, or use data?\""
+        );
+    }
+
+    #[test]
+    fn rows_to_csv_rejects_non_array() {
+        assert!(rows_to_csv(&json!({"rows": []})).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! Explicit Google Play edit sessions.
+//!
+//! Every edit-based command normally opens its own edit and commits it at once.
+//! `edits open` starts a shared edit instead: pass its ID with `--edit-id` (the REPL
+//! reuses it automatically) and changes are staged until `edits commit` publishes
+//! them atomically, or `edits abort` throws them away. `edits validate` runs Play's
+//! checks on the staged changes without committing.
+
+use clap::Subcommand;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+
+use crate::api::google_client::{EditSession, GoogleClient};
+
+/// Session opened by `edits open` in this process (kept across REPL commands).
+static SESSION: Mutex<Option<EditSession>> = Mutex::new(None);
+
+#[derive(Subcommand)]
+pub enum EditsCommand {
+    /// Open an edit to batch changes from several commands
+    ///
+    /// Examples:
+    ///   storeops google edits open com.example.app
+    ///   storeops google listings update com.example.app --locale en-US --title "App" --edit-id EDIT_ID
+    ///   storeops google edits commit com.example.app --edit-id EDIT_ID
+    Open {
+        /// Package name
+        package_name: Option<String>,
+    },
+    /// Validate the open edit without committing it
+    Validate {
+        /// Package name
+        package_name: Option<String>,
+    },
+    /// Commit the open edit, publishing all staged changes at once
+    Commit {
+        /// Package name
+        package_name: Option<String>,
+    },
+    /// Discard the open edit and every change staged in it
+    Abort {
+        /// Package name
+        package_name: Option<String>,
+    },
+}
+
+/// The edit opened in this process, if any.
+pub fn current_session() -> Option<EditSession> {
+    SESSION.lock().ok().and_then(|s| s.clone())
+}
+
+fn set_session(session: Option<EditSession>) {
+    if let Ok(mut s) = SESSION.lock() {
+        *s = session;
+    }
+}
+
+/// Edit ID from `--edit-id`, falling back to this process's session for the package.
+fn resolve_edit_id(package_name: &str, edit_id: Option<&str>) -> Result<String, String> {
+    if let Some(id) = edit_id {
+        return Ok(id.to_string());
+    }
+    current_session()
+        .filter(|s| s.package_name.as_deref() == Some(package_name))
+        .map(|s| s.edit_id)
+        .ok_or_else(|| format!("no open edit for {package_name}; pass --edit-id"))
+}
+
+pub async fn handle(
+    cmd: &EditsCommand,
+    edit_id: Option<&str>,
+    client: &GoogleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        EditsCommand::Open { package_name } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            if let Some(open) = current_session() {
+                return Err(format!(
+                    "edit {} is already open; commit or abort it first",
+                    open.edit_id
+                )
+                .into());
+            }
+            let edit: Value = client
+                .post(&format!("/{package_name}/edits"), &json!({}))
+                .await?;
+            let id = edit["id"].as_str().ok_or("no edit id")?;
+            set_session(Some(EditSession {
+                package_name: Some(package_name.clone()),
+                edit_id: id.to_string(),
+            }));
+            eprintln!("Opened edit {id}; pass --edit-id {id} to other google commands");
+            Ok(json!({
+                "package_name": package_name,
+                "edit_id": id,
+                "expiry_time_seconds": edit["expiryTimeSeconds"],
+            }))
+        }
+        EditsCommand::Validate { package_name } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let id = resolve_edit_id(package_name, edit_id)?;
+            client
+                .post(&format!("/{package_name}/edits/{id}:validate"), &json!({}))
+                .await
+        }
+        EditsCommand::Commit { package_name } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let id = resolve_edit_id(package_name, edit_id)?;
+            let result = client.commit_edit(package_name, &id).await?;
+            set_session(None);
+            Ok(result)
+        }
+        EditsCommand::Abort { package_name } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let id = resolve_edit_id(package_name, edit_id)?;
+            client
+                .delete_path(&format!("/{package_name}/edits/{id}"))
+                .await?;
+            set_session(None);
+            Ok(json!({
+                "package_name": package_name,
+                "edit_id": id,
+                "status": "aborted",
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_edit_id_wins_over_session() {
+        assert_eq!(
+            resolve_edit_id("com.example.app", Some("E1")).unwrap(),
+            "E1"
+        );
+        assert!(resolve_edit_id("com.example.unknown", None).is_err());
+    }
+}
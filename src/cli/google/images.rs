@@ -1,5 +1,5 @@
 use clap::Subcommand;
-use serde_json::{json, Value};
+use serde_json::Value;
 
 use crate::api::google_client::GoogleClient;
 
@@ -8,7 +8,7 @@ pub enum ImagesCommand {
     /// List images for a locale and image type
     List {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Locale (e.g., en-US)
         #[arg(long)]
         locale: String,
@@ -20,7 +20,7 @@ pub enum ImagesCommand {
     /// Upload an image
     Upload {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Locale (e.g., en-US)
         #[arg(long)]
         locale: String,
@@ -34,7 +34,7 @@ pub enum ImagesCommand {
     /// Delete an image
     Delete {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Locale
         #[arg(long)]
         locale: String,
@@ -48,7 +48,7 @@ pub enum ImagesCommand {
     /// Delete all images of a given type for a locale
     DeleteAll {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Locale
         #[arg(long)]
         locale: String,
@@ -68,19 +68,15 @@ pub async fn handle(
             locale,
             image_type,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let result: Value = client
                 .get(
                     &format!("/{package_name}/edits/{edit_id}/listings/{locale}/{image_type}"),
                     &[],
                 )
                 .await?;
-            let _ = client
-                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
-                .await;
+            client.discard_edit(package_name, edit_id).await;
             Ok(result)
         }
         ImagesCommand::Upload {
@@ -89,21 +85,14 @@ pub async fn handle(
             image_type,
             file,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
 
             let result = client
                 .upload_image(package_name, edit_id, locale, image_type, file)
                 .await?;
 
-            client
-                .post(
-                    &format!("/{package_name}/edits/{edit_id}:commit"),
-                    &json!({}),
-                )
-                .await?;
+            client.commit_edit(package_name, edit_id).await?;
             Ok(result)
         }
         ImagesCommand::Delete {
@@ -112,21 +101,14 @@ pub async fn handle(
             image_type,
             image_id,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let result = client
                 .delete_path(&format!(
                     "/{package_name}/edits/{edit_id}/listings/{locale}/{image_type}/{image_id}"
                 ))
                 .await?;
-            client
-                .post(
-                    &format!("/{package_name}/edits/{edit_id}:commit"),
-                    &json!({}),
-                )
-                .await?;
+            client.commit_edit(package_name, edit_id).await?;
             Ok(result)
         }
         ImagesCommand::DeleteAll {
@@ -134,21 +116,14 @@ pub async fn handle(
             locale,
             image_type,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let result = client
                 .delete_path(&format!(
                     "/{package_name}/edits/{edit_id}/listings/{locale}/{image_type}"
                 ))
                 .await?;
-            client
-                .post(
-                    &format!("/{package_name}/edits/{edit_id}:commit"),
-                    &json!({}),
-                )
-                .await?;
+            client.commit_edit(package_name, edit_id).await?;
             Ok(result)
         }
     }
@@ -22,12 +22,12 @@ pub enum ProductsCommand {
     /// List in-app products
     List {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
     },
     /// Get in-app product details
     Get {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// SKU / product ID
         #[arg(long)]
         sku: String,
@@ -35,7 +35,7 @@ pub enum ProductsCommand {
     /// Create an in-app product
     Create {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// SKU / product ID
         #[arg(long)]
         sku: String,
@@ -58,7 +58,7 @@ pub enum ProductsCommand {
     /// Update an in-app product
     Update {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// SKU / product ID
         #[arg(long)]
         sku: String,
@@ -78,7 +78,7 @@ pub enum ProductsCommand {
     /// Delete an in-app product
     Delete {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// SKU / product ID
         #[arg(long)]
         sku: String,
@@ -90,12 +90,12 @@ pub enum SubscriptionsCommand {
     /// List subscriptions
     List {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
     },
     /// Get subscription details
     Get {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Product ID
         #[arg(long)]
         product_id: String,
@@ -103,7 +103,7 @@ pub enum SubscriptionsCommand {
     /// Create a subscription
     Create {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Product ID
         #[arg(long)]
         product_id: String,
@@ -114,7 +114,7 @@ pub enum SubscriptionsCommand {
     /// Archive (soft-delete) a subscription
     Archive {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Product ID
         #[arg(long)]
         product_id: String,
@@ -137,11 +137,13 @@ async fn handle_products(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         ProductsCommand::List { package_name } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             client
                 .get(&format!("/{package_name}/inappproducts"), &[])
                 .await
         }
         ProductsCommand::Get { package_name, sku } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             client
                 .get::<Value>(&format!("/{package_name}/inappproducts/{sku}"), &[])
                 .await
@@ -155,6 +157,7 @@ async fn handle_products(
             description,
             status,
         } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             let body = json!({
                 "sku": sku,
                 "status": status,
@@ -182,6 +185,7 @@ async fn handle_products(
             title,
             description,
         } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             let mut current: Value = client
                 .get::<Value>(&format!("/{package_name}/inappproducts/{sku}"), &[])
                 .await?;
@@ -200,6 +204,7 @@ async fn handle_products(
                 .await
         }
         ProductsCommand::Delete { package_name, sku } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             client
                 .delete_path(&format!("/{package_name}/inappproducts/{sku}"))
                 .await
@@ -213,6 +218,7 @@ async fn handle_subscriptions(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         SubscriptionsCommand::List { package_name } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             client
                 .get(&format!("/{package_name}/monetization/subscriptions"), &[])
                 .await
@@ -221,6 +227,7 @@ async fn handle_subscriptions(
             package_name,
             product_id,
         } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             client
                 .get::<Value>(
                     &format!("/{package_name}/monetization/subscriptions/{product_id}"),
@@ -233,6 +240,7 @@ async fn handle_subscriptions(
             product_id,
             body,
         } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             let body_json: Value = serde_json::from_str(body)?;
             client
                 .post(
@@ -245,6 +253,7 @@ async fn handle_subscriptions(
             package_name,
             product_id,
         } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             client
                 .post(
                     &format!("/{package_name}/monetization/subscriptions/{product_id}:archive"),
@@ -1,19 +1,44 @@
 use clap::Subcommand;
 use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
 
 use crate::api::google_client::GoogleClient;
+use crate::config::territories;
+use crate::output::stream;
+
+/// Listing fields required before a locale can be published
+const REQUIRED_FIELDS: &[&str] = &["title", "shortDescription", "fullDescription"];
+
+/// Text fields shown by `preview`: listing field, metadata file, character limit.
+const PREVIEW_FIELDS: &[(&str, &str, usize)] = &[
+    ("title", "title.txt", 30),
+    ("shortDescription", "short_description.txt", 80),
+    ("fullDescription", "full_description.txt", 4000),
+];
+
+/// Import CSV columns (with accepted aliases) mapped to listing fields and length limits.
+const IMPORT_COLUMNS: &[(&[&str], &str, Option<usize>)] = &[
+    (&["title"], "title", Some(30)),
+    (
+        &["short", "short_description"],
+        "shortDescription",
+        Some(80),
+    ),
+    (&["full", "full_description"], "fullDescription", Some(4000)),
+    (&["video"], "video", None),
+];
 
 #[derive(Subcommand)]
 pub enum ListingsCommand {
     /// List all store listings (all locales)
     List {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
     },
     /// Get a store listing for a specific locale
     Get {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Locale (e.g., en-US, pt-BR)
         #[arg(long)]
         locale: String,
@@ -21,7 +46,7 @@ pub enum ListingsCommand {
     /// Create or update a store listing for a locale
     Update {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Locale (e.g., en-US)
         #[arg(long)]
         locale: String,
@@ -41,11 +66,230 @@ pub enum ListingsCommand {
     /// Delete a store listing for a locale
     Delete {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Locale
         #[arg(long)]
         locale: String,
     },
+    /// Update many locales from a CSV file in a single edit
+    ///
+    /// Columns: locale, title, short, full, video. Empty cells leave the field unchanged.
+    ///
+    /// Examples:
+    ///   storeops google listings import com.example.app --file listings.csv
+    ///   storeops google listings import com.example.app --file listings.csv --dry-run
+    Import {
+        /// Package name
+        package_name: Option<String>,
+        /// CSV file with a header row
+        #[arg(long)]
+        file: PathBuf,
+        /// Validate the file without calling the API
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Preview a listing's text with character counts before pushing it
+    ///
+    /// Shows the title, short and full description against their limits, the text
+    /// past each limit, and Markdown syntax that Google Play would show literally.
+    ///
+    /// Examples:
+    ///   storeops google listings preview com.example.app --locale en-US
+    ///   storeops google listings preview --locale de-DE --metadata-dir metadata/google
+    Preview {
+        /// Package name
+        package_name: Option<String>,
+        /// Locale (e.g., en-US)
+        #[arg(long)]
+        locale: String,
+        /// Read the text from a pulled metadata directory instead of Google Play
+        #[arg(long)]
+        metadata_dir: Option<PathBuf>,
+    },
+    /// Report locale coverage: missing required fields and unlocalized markets
+    ///
+    /// Examples:
+    ///   storeops google listings coverage com.example.app
+    ///   storeops google listings coverage com.example.app --track beta
+    Coverage {
+        /// Package name
+        package_name: Option<String>,
+        /// Track whose country availability is compared against the listings
+        #[arg(long, default_value = "production")]
+        track: String,
+    },
+}
+
+/// Parse CSV (RFC 4180: quoted fields may contain commas, quotes and newlines).
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|f| !f.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                } else {
+                    row.clear();
+                }
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("unterminated quoted field".to_string());
+    }
+    row.push(field);
+    if row.iter().any(|f| !f.is_empty()) {
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Turn CSV rows into `(locale, listing body)` pairs, validating lengths.
+fn listings_from_csv(rows: &[Vec<String>]) -> Result<Vec<(String, Value)>, String> {
+    let (header, records) = rows.split_first().ok_or("CSV file is empty")?;
+    let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+    let locale_col = header
+        .iter()
+        .position(|h| h == "locale" || h == "language")
+        .ok_or("CSV needs a 'locale' column")?;
+
+    let mut listings = Vec::new();
+    let mut problems = Vec::new();
+    for (i, record) in records.iter().enumerate() {
+        let line = i + 2;
+        let locale = record.get(locale_col).map(|l| l.trim()).unwrap_or("");
+        if locale.is_empty() {
+            problems.push(format!("row {line}: missing locale"));
+            continue;
+        }
+        let mut body = json!({ "language": locale });
+        for (aliases, field, limit) in IMPORT_COLUMNS {
+            let Some(col) = header.iter().position(|h| aliases.contains(&h.as_str())) else {
+                continue;
+            };
+            let value = record.get(col).map(|v| v.trim()).unwrap_or("");
+            if value.is_empty() {
+                continue;
+            }
+            if let Some(limit) = limit {
+                let len = value.chars().count();
+                if len > *limit {
+                    problems.push(format!(
+                        "row {line} ({locale}): {field} is {len} chars, max {limit}"
+                    ));
+                }
+            }
+            body[*field] = json!(value);
+        }
+        listings.push((locale.to_string(), body));
+    }
+    if !problems.is_empty() {
+        return Err(problems.join("; "));
+    }
+    Ok(listings)
+}
+
+/// Locales of a listings response with the required fields each is missing.
+fn listing_coverage(listings: &Value) -> Vec<(String, Vec<String>)> {
+    listings["listings"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|l| {
+            let locale = l["language"].as_str()?.to_string();
+            let missing = REQUIRED_FIELDS
+                .iter()
+                .filter(|f| l[**f].as_str().is_none_or(|v| v.trim().is_empty()))
+                .map(|f| f.to_string())
+                .collect();
+            Some((locale, missing))
+        })
+        .collect()
+}
+
+/// Markdown syntax in `text`, which Google Play renders as plain characters.
+fn markdown_syntax(text: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_start();
+        if line.starts_with('#') {
+            found.push("# heading".to_string());
+        }
+        if line.starts_with("* ") || line.starts_with("- ") {
+            found.push(format!("{} list item", &line[..1]));
+        }
+        if line.starts_with("> ") {
+            found.push("> quote".to_string());
+        }
+    }
+    for marker in ["**", "__", "`"] {
+        if text.contains(marker) {
+            found.push(marker.to_string());
+        }
+    }
+    if text.contains("](") {
+        found.push("[link](url)".to_string());
+    }
+    found.dedup();
+    found
+}
+
+/// One row per previewed field: its text, length, limit and any problems.
+fn preview_rows(listing: &Value) -> Vec<Value> {
+    PREVIEW_FIELDS
+        .iter()
+        .map(|(field, _, limit)| {
+            let text = listing[*field].as_str().unwrap_or("");
+            let chars = text.chars().count();
+            let overflow: String = text.chars().skip(*limit).collect();
+            let markdown = markdown_syntax(text);
+            json!({
+                "field": field,
+                "chars": chars,
+                "limit": limit,
+                "ok": chars <= *limit && markdown.is_empty(),
+                "over_limit": (!overflow.is_empty()).then_some(overflow),
+                "markdown": (!markdown.is_empty()).then(|| markdown.join(", ")),
+                "text": text,
+            })
+        })
+        .collect()
+}
+
+fn preview(locale: &str, listing: &Value) -> Value {
+    let rows = preview_rows(listing);
+    let ok = rows.iter().all(|r| r["ok"] == json!(true));
+    json!({ "locale": locale, "ok": ok, "fields": rows })
+}
+
+/// `preview` of a pulled metadata directory; needs no credentials.
+pub fn preview_local(locale: &str, dir: &Path) -> Result<Value, Box<dyn std::error::Error>> {
+    let dir = dir.join(super::sync::gp_to_internal_locale(locale));
+    if !dir.is_dir() {
+        return Err(format!("no metadata for {locale} in {}", dir.display()).into());
+    }
+    let mut listing = json!({ "language": locale });
+    for (field, file, _) in PREVIEW_FIELDS {
+        if let Ok(text) = std::fs::read_to_string(dir.join(file)) {
+            listing[*field] = json!(text.trim());
+        }
+    }
+    Ok(preview(locale, &listing))
 }
 
 pub async fn handle(
@@ -54,35 +298,27 @@ pub async fn handle(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         ListingsCommand::List { package_name } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let result: Value = client
                 .get(&format!("/{package_name}/edits/{edit_id}/listings"), &[])
                 .await?;
-            let _ = client
-                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
-                .await;
+            client.discard_edit(package_name, edit_id).await;
             Ok(result)
         }
         ListingsCommand::Get {
             package_name,
             locale,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let result: Value = client
                 .get(
                     &format!("/{package_name}/edits/{edit_id}/listings/{locale}"),
                     &[],
                 )
                 .await?;
-            let _ = client
-                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
-                .await;
+            client.discard_edit(package_name, edit_id).await;
             Ok(result)
         }
         ListingsCommand::Update {
@@ -93,10 +329,8 @@ pub async fn handle(
             short_description,
             video,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
 
             let mut body = json!({ "language": locale });
             if let Some(t) = title {
@@ -118,34 +352,195 @@ pub async fn handle(
                     &body,
                 )
                 .await?;
-            client
-                .post(
-                    &format!("/{package_name}/edits/{edit_id}:commit"),
-                    &json!({}),
-                )
-                .await?;
+            client.commit_edit(package_name, edit_id).await?;
             Ok(result)
         }
         ListingsCommand::Delete {
             package_name,
             locale,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let result = client
                 .delete_path(&format!(
                     "/{package_name}/edits/{edit_id}/listings/{locale}"
                 ))
                 .await?;
-            client
-                .post(
-                    &format!("/{package_name}/edits/{edit_id}:commit"),
-                    &json!({}),
+            client.commit_edit(package_name, edit_id).await?;
+            Ok(result)
+        }
+        ListingsCommand::Import {
+            package_name,
+            file,
+            dry_run,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let content = std::fs::read_to_string(file)
+                .map_err(|e| format!("cannot read {}: {e}", file.display()))?;
+            let listings = listings_from_csv(&parse_csv(&content)?)?;
+            let locales: Vec<&str> = listings.iter().map(|(l, _)| l.as_str()).collect();
+            if *dry_run {
+                return Ok(json!({
+                    "status": "dry_run",
+                    "locales": locales,
+                }));
+            }
+
+            let edit_id = &client.open_edit(package_name).await?;
+            let current: Value = match client
+                .get(&format!("/{package_name}/edits/{edit_id}/listings"), &[])
+                .await
+            {
+                Ok(v) => v,
+                Err(e) => {
+                    client.discard_edit(package_name, edit_id).await;
+                    return Err(e);
+                }
+            };
+            let existing: Vec<&str> = current["listings"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|l| l["language"].as_str())
+                .collect();
+            for (locale, body) in &listings {
+                let path = format!("/{package_name}/edits/{edit_id}/listings/{locale}");
+                // PATCH keeps fields left empty in the CSV; only new locales get a PUT
+                let result = if existing.contains(&locale.as_str()) {
+                    client.patch(&path, body).await
+                } else {
+                    client.put(&path, body).await
+                };
+                if let Err(e) = result {
+                    client.discard_edit(package_name, edit_id).await;
+                    return Err(format!("{locale}: {e} (nothing was committed)").into());
+                }
+                eprintln!("Updated {locale}");
+                stream::emit("locale", json!({ "locale": locale, "status": "staged" }));
+            }
+            client.commit_edit(package_name, edit_id).await?;
+            stream::emit("step", json!({ "step": "committed", "edit_id": edit_id }));
+            Ok(json!({
+                "status": "ok",
+                "package_name": package_name,
+                "locales": locales,
+            }))
+        }
+        ListingsCommand::Preview {
+            package_name,
+            locale,
+            metadata_dir,
+        } => {
+            if let Some(dir) = metadata_dir {
+                return preview_local(locale, dir);
+            }
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
+            let listing = client
+                .get::<Value>(
+                    &format!("/{package_name}/edits/{edit_id}/listings/{locale}"),
+                    &[],
+                )
+                .await;
+            client.discard_edit(package_name, edit_id).await;
+            Ok(preview(locale, &listing?))
+        }
+        ListingsCommand::Coverage {
+            package_name,
+            track,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
+            let listings: Value = client
+                .get(&format!("/{package_name}/edits/{edit_id}/listings"), &[])
+                .await?;
+            let availability: Value = client
+                .get(
+                    &format!("/{package_name}/edits/{edit_id}/countryAvailability/{track}"),
+                    &[],
                 )
                 .await?;
-            Ok(result)
+            client.discard_edit(package_name, edit_id).await;
+
+            let coverage = listing_coverage(&listings);
+            let locales: Vec<String> = coverage.iter().map(|(l, _)| l.clone()).collect();
+            let countries: Vec<String> = availability["countries"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|c| c["countryCode"].as_str().map(String::from))
+                .collect();
+            Ok(json!({
+                "package_name": package_name,
+                "track": track,
+                "locales": coverage
+                    .into_iter()
+                    .map(|(locale, missing)| json!({
+                        "locale": locale,
+                        "complete": missing.is_empty(),
+                        "missing": missing,
+                    }))
+                    .collect::<Vec<_>>(),
+                "countries": countries.len(),
+                "rest_of_world": availability["restOfWorld"],
+                "uncovered_markets": territories::uncovered_markets(&countries, &locales),
+            }))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_csv_handles_quotes_and_newlines() {
+        let rows = parse_csv("locale,full\r\nen-US,\"Line one,\nline \"\"two\"\"\"\n\n").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1], vec!["en-US", "Line one,\nline \"two\""]);
+        assert!(parse_csv("a,\"b").is_err());
+    }
+
+    #[test]
+    fn listings_from_csv_skips_empty_cells_and_checks_limits() {
+        let rows = parse_csv("locale,title,short,video\nde-DE,Meine App,,\n").unwrap();
+        let listings = listings_from_csv(&rows).unwrap();
+        assert_eq!(
+            listings[0].1,
+            json!({"language": "de-DE", "title": "Meine App"})
+        );
+
+        let long = "x".repeat(31);
+        let rows = parse_csv(&format!("locale,title\nfr-FR,{long}")).unwrap();
+        assert!(listings_from_csv(&rows).unwrap_err().contains("max 30"));
+    }
+
+    #[test]
+    fn preview_flags_overflow_and_markdown() {
+        let listing = json!({
+            "title": "A title that is much too long to fit",
+            "shortDescription": "Plain and short",
+            "fullDescription": "## Features\n- **Fast** sync\nSee [docs](https://example.com)",
+        });
+        let rows = preview_rows(&listing);
+        assert_eq!(rows[0]["chars"], 36);
+        assert_eq!(rows[0]["over_limit"], "to fit");
+        assert_eq!(rows[1]["ok"], true);
+        assert_eq!(rows[1]["over_limit"], Value::Null);
+        assert_eq!(
+            rows[2]["markdown"],
+            "# heading, - list item, **, [link](url)"
+        );
+    }
+
+    #[test]
+    fn listing_coverage_reports_missing_fields() {
+        let listings = json!({"listings": [
+            {"language": "en-US", "title": "App", "shortDescription": "s", "fullDescription": "f"},
+            {"language": "de-DE", "title": "App", "fullDescription": ""}
+        ]});
+        let coverage = listing_coverage(&listings);
+        assert!(coverage[0].1.is_empty());
+        assert_eq!(coverage[1].1, vec!["shortDescription", "fullDescription"]);
+    }
+}
@@ -1,16 +1,20 @@
 pub mod apps;
 pub mod availability;
 pub mod builds;
+pub mod datasafety;
+pub mod edits;
 pub mod images;
 pub mod inapp;
 pub mod listings;
+pub mod reports;
 pub mod reviews;
+pub mod signing;
 pub mod submit;
 pub mod sync;
 pub mod testers;
 pub mod tracks;
 
-use clap::Subcommand;
+use clap::{Args, Subcommand};
 
 #[derive(Subcommand)]
 pub enum GoogleCommand {
@@ -29,6 +33,11 @@ pub enum GoogleCommand {
         #[command(subcommand)]
         command: builds::BuildsCommand,
     },
+    /// App signing and upload certificate fingerprints
+    Signing {
+        #[command(subcommand)]
+        command: signing::SigningCommand,
+    },
     /// Tester management
     Testers {
         #[command(subcommand)]
@@ -37,7 +46,7 @@ pub enum GoogleCommand {
     /// Submit/promote to production
     Submit {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Target track
         #[arg(long, default_value = "production")]
         track: String,
@@ -47,6 +56,11 @@ pub enum GoogleCommand {
         #[command(subcommand)]
         command: reviews::ReviewsCommand,
     },
+    /// Statistics and Android Vitals reports
+    Reports {
+        #[command(subcommand)]
+        command: reports::ReportsCommand,
+    },
     /// Manage store listings (title, description, etc. per locale)
     Listings {
         #[command(subcommand)]
@@ -62,6 +76,16 @@ pub enum GoogleCommand {
         #[command(subcommand)]
         command: inapp::InAppCommand,
     },
+    /// Open, commit or abort a shared edit to batch changes atomically
+    Edits {
+        #[command(subcommand)]
+        command: edits::EditsCommand,
+    },
+    /// Manage the data safety declaration
+    Datasafety {
+        #[command(subcommand)]
+        command: datasafety::DataSafetyCommand,
+    },
     /// Manage country availability
     Availability {
         #[command(subcommand)]
@@ -72,31 +96,153 @@ pub enum GoogleCommand {
         #[command(subcommand)]
         command: sync::SyncCommand,
     },
+    /// Send a raw Google Play Developer API request with the profile's credentials
+    ///
+    /// Paths are relative to
+    /// https://androidpublisher.googleapis.com/androidpublisher/v3/applications; full
+    /// URLs on other googleapis.com APIs are also accepted.
+    ///
+    /// Examples:
+    ///   storeops google api GET /com.example.app/reviews --query maxResults=10
+    ///   storeops google api PUT /com.example.app/edits/EDIT_ID/tracks/beta --body-file track.json
+    Api(crate::cli::api_request::ApiArgs),
+}
+
+/// Options shared by every edit-based Google command.
+#[derive(Args)]
+pub struct EditArgs {
+    /// Stage edit-based changes in this edit instead of committing each command
+    #[arg(long, global = true)]
+    pub edit_id: Option<String>,
+    /// Send committed changes for review (default, except for `sync push`)
+    #[arg(long, global = true, overrides_with = "no_send_for_review")]
+    pub send_for_review: bool,
+    /// Commit with changesNotSentForReview; send for review from the Play Console later
+    #[arg(long, global = true, overrides_with = "send_for_review")]
+    pub no_send_for_review: bool,
+}
+
+impl EditArgs {
+    fn send_for_review(&self) -> Option<bool> {
+        match (self.send_for_review, self.no_send_for_review) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Build an authenticated Google Play client for the selected profile.
+pub async fn client(
+    cli: &crate::cli::Cli,
+) -> Result<crate::api::google_client::GoogleClient, Box<dyn std::error::Error>> {
+    client_for_profile(cli.profile.as_deref()).await
+}
+
+/// Build an authenticated Google Play client for a named (or the active) profile.
+pub async fn client_for_profile(
+    profile: Option<&str>,
+) -> Result<crate::api::google_client::GoogleClient, Box<dyn std::error::Error>> {
+    let config = crate::config::Config::load()?;
+    let defaults = config.profile_defaults(profile);
+    if crate::api::recording::is_replaying() {
+        return Ok(crate::api::google_client::GoogleClient::new(String::new())
+            .with_default_package(defaults.default_package));
+    }
+    let sa_path = crate::auth::store::resolve_google_credentials(&config, profile)?;
+    let cache_key = format!("google:{sa_path}");
+    let token = match crate::api::session::cached_token(&cache_key) {
+        Some(token) => token,
+        None => {
+            let token = crate::auth::google::get_access_token(&sa_path).await?;
+            crate::api::session::store_token(
+                &cache_key,
+                &token,
+                crate::auth::google::TOKEN_LIFETIME,
+            );
+            token
+        }
+    };
+    Ok(
+        crate::api::google_client::GoogleClient::with_client(crate::api::session::http(), token)
+            .with_default_package(defaults.default_package),
+    )
 }
 
 pub async fn execute(
     cmd: &GoogleCommand,
+    edit: &EditArgs,
     cli: &crate::cli::Cli,
 ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
-    let config = crate::config::Config::load()?;
-    let sa_path = crate::auth::store::resolve_google_credentials(&config, cli.profile.as_deref())?;
-    let token = crate::auth::google::get_access_token(&sa_path).await?;
-    let client = crate::api::google_client::GoogleClient::new(token);
+    if let GoogleCommand::Api(args) = cmd {
+        if args.dry_run {
+            return args.preview(crate::cli::api_request::Api::Google);
+        }
+    }
+    if let GoogleCommand::Listings {
+        command:
+            listings::ListingsCommand::Preview {
+                locale,
+                metadata_dir: Some(dir),
+                ..
+            },
+    } = cmd
+    {
+        return listings::preview_local(locale, dir);
+    }
+    let base = client(cli)
+        .await?
+        .with_send_for_review(edit.send_for_review());
+    let edit_id = edit.edit_id.as_deref();
+
+    // Stage changes in the shared edit when one is given or open in this process
+    let session = match edit_id {
+        Some(id) => Some(crate::api::google_client::EditSession {
+            package_name: None,
+            edit_id: id.to_string(),
+        }),
+        None => edits::current_session(),
+    };
+    let client = base.clone().with_edit_session(session);
 
-    match cmd {
+    let mut result = match cmd {
         GoogleCommand::Apps { command } => apps::handle(command, &client).await,
         GoogleCommand::Tracks { command } => tracks::handle(command, &client).await,
         GoogleCommand::Builds { command } => builds::handle(command, &client).await,
+        GoogleCommand::Signing { command } => signing::handle(command, &client).await,
         GoogleCommand::Testers { command } => testers::handle(command, &client).await,
         GoogleCommand::Submit {
             package_name,
             track,
-        } => submit::handle(package_name, track, &client).await,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            submit::handle(package_name, track, &client).await
+        }
         GoogleCommand::Reviews { command } => reviews::handle(command, &client).await,
+        GoogleCommand::Reports { command } => reports::handle(command, &client).await,
         GoogleCommand::Listings { command } => listings::handle(command, &client).await,
         GoogleCommand::Images { command } => images::handle(command, &client).await,
         GoogleCommand::Inapp { command } => inapp::handle(command, &client).await,
+        GoogleCommand::Edits { command } => edits::handle(command, edit_id, &base).await,
+        GoogleCommand::Datasafety { command } => datasafety::handle(command, &client).await,
         GoogleCommand::Availability { command } => availability::handle(command, &client).await,
         GoogleCommand::Sync { command } => sync::handle(command, &client).await,
+        GoogleCommand::Api(args) => {
+            let body = args.validate(crate::cli::api_request::Api::Google)?;
+            client
+                .request(
+                    args.method.as_reqwest(),
+                    &args.path,
+                    &args.query,
+                    body.as_ref(),
+                )
+                .await
+        }
+    }?;
+
+    // Report which review mode a commit used (clones share the commit state)
+    if let (Some(sent), Some(obj)) = (base.committed_send_for_review(), result.as_object_mut()) {
+        obj.insert("send_for_review".to_string(), serde_json::json!(sent));
     }
+    Ok(result)
 }
@@ -1,7 +1,41 @@
-use clap::Subcommand;
-use serde_json::Value;
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use clap::{Subcommand, ValueEnum};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::PathBuf;
 
 use crate::api::google_client::GoogleClient;
+use crate::config::Config;
+use crate::output::{chart, csv};
+
+/// Widest bar drawn by `--chart`, in terminal cells
+const CHART_WIDTH: usize = 40;
+const STORAGE_URL: &str = "https://storage.googleapis.com/storage/v1";
+/// Earnings report columns holding amounts, parsed as numbers
+const EARNINGS_NUMERIC: &[&str] = &[
+    "Amount (Buyer Currency)",
+    "Currency Conversion Rate",
+    "Amount (Merchant Currency)",
+];
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum VitalsMetric {
+    /// Share of daily users who experienced a crash
+    CrashRate,
+    /// Share of daily users who experienced an ANR
+    AnrRate,
+}
+
+impl VitalsMetric {
+    /// `(metric set, metric)` in the Play Developer Reporting API.
+    fn query_names(self) -> (&'static str, &'static str) {
+        match self {
+            VitalsMetric::CrashRate => ("crashRateMetricSet", "crashRate"),
+            VitalsMetric::AnrRate => ("anrRateMetricSet", "anrRate"),
+        }
+    }
+}
 
 #[derive(Subcommand)]
 pub enum ReportsCommand {
@@ -13,11 +47,218 @@ pub enum ReportsCommand {
         #[arg(long, default_value = "overview")]
         dimension: String,
     },
+    /// Daily Android Vitals crash or ANR rate, in percent of users
+    ///
+    /// Examples:
+    ///   storeops google reports vitals com.example.app --chart
+    ///   storeops google reports vitals com.example.app --metric anr-rate --days 60
+    Vitals {
+        /// Package name
+        package_name: Option<String>,
+        /// Metric to report
+        #[arg(long, value_enum, default_value = "crash-rate")]
+        metric: VitalsMetric,
+        /// Number of days to report
+        #[arg(long, default_value = "30")]
+        days: i64,
+        /// Chart the trend in the terminal
+        #[arg(long)]
+        chart: bool,
+    },
+    /// Download a month's earnings report from the Play Console reports bucket
+    ///
+    /// Transactions (charges, Google fees, taxes and refunds) are returned with
+    /// snake_case fields, ISO dates and numeric amounts, plus totals per transaction
+    /// type and merchant currency. The service account needs access to the bucket.
+    ///
+    /// Examples:
+    ///   storeops google reports earnings --month 2024-09 --bucket pubsite_prod_rev_0123
+    ///   storeops google reports earnings --month 2024-09 --package com.example.app --file earnings.csv
+    Earnings {
+        /// Month of the report
+        #[arg(long, value_name = "YYYY-MM")]
+        month: String,
+        /// Reports bucket, `pubsite_prod_rev_...` (default: `[revenue] google_bucket`)
+        #[arg(long)]
+        bucket: Option<String>,
+        /// Only transactions of this package
+        #[arg(long)]
+        package: Option<String>,
+        /// Write the transactions to this CSV file instead of the output
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+}
+
+/// Error unless `month` is `YYYY-MM`.
+pub fn check_month(month: &str) -> Result<(), String> {
+    NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| format!("--month must be YYYY-MM, got `{month}`"))
+}
+
+/// Object name prefix of a month's earnings reports in the reports bucket.
+pub fn earnings_prefix(month: &str) -> String {
+    format!("earnings/earnings_{}", month.replace('-', ""))
+}
+
+/// `(file name, text)` of every CSV under `prefix` in a Play Console reports bucket;
+/// zipped reports are unpacked.
+pub async fn bucket_csvs(
+    client: &GoogleClient,
+    bucket: &str,
+    prefix: &str,
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let listing = client
+        .request(
+            reqwest::Method::GET,
+            &format!("{STORAGE_URL}/b/{bucket}/o"),
+            &[("prefix".to_string(), prefix.to_string())],
+            None,
+        )
+        .await?;
+    let objects: Vec<&Value> = listing["items"].as_array().into_iter().flatten().collect();
+    if objects.is_empty() {
+        return Err(format!("no Google Play reports under gs://{bucket}/{prefix}").into());
+    }
+
+    let mut files = Vec::new();
+    for object in objects {
+        let (Some(name), Some(link)) = (object["name"].as_str(), object["mediaLink"].as_str())
+        else {
+            continue;
+        };
+        eprintln!("Downloading gs://{bucket}/{name}...");
+        let bytes = client.download(link).await?;
+        if name.ends_with(".zip") {
+            let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+            for i in 0..archive.len() {
+                let mut file = archive.by_index(i)?;
+                if !file.name().ends_with(".csv") {
+                    continue;
+                }
+                let mut text = String::new();
+                file.read_to_string(&mut text)?;
+                files.push((file.name().to_string(), text));
+            }
+        } else {
+            files.push((
+                name.to_string(),
+                String::from_utf8_lossy(&bytes).into_owned(),
+            ));
+        }
+    }
+    Ok(files)
+}
+
+/// `Amount (Merchant Currency)` → `amount_merchant_currency`.
+fn field_name(header: &str) -> String {
+    header
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Transactions of an earnings CSV with normalized field names and values.
+///
+/// Returns the field names in report order and one object per transaction.
+fn parse_earnings(text: &str) -> (Vec<String>, Vec<Value>) {
+    let mut records = csv::parse(text.trim_start_matches('\u{feff}')).into_iter();
+    let Some(header) = records.next() else {
+        return (Vec::new(), Vec::new());
+    };
+    let header: Vec<&str> = header.iter().map(|h| h.trim()).collect();
+    let fields: Vec<String> = header.iter().map(|h| field_name(h)).collect();
+    let rows = records
+        .map(|record| {
+            let mut row = Map::new();
+            for ((name, field), value) in header.iter().zip(&fields).zip(&record) {
+                let value = value.trim();
+                let normalized = if EARNINGS_NUMERIC.contains(name) {
+                    value
+                        .replace(',', "")
+                        .parse::<f64>()
+                        .map(Value::from)
+                        .unwrap_or(Value::Null)
+                } else if *name == "Transaction Date" {
+                    NaiveDate::parse_from_str(value, "%b %d, %Y")
+                        .map(|d| json!(d.to_string()))
+                        .unwrap_or_else(|_| json!(value))
+                } else {
+                    json!(value)
+                };
+                row.insert(field.clone(), normalized);
+            }
+            Value::Object(row)
+        })
+        .collect();
+    (fields, rows)
+}
+
+/// Sum of `amount_merchant_currency` per transaction type and merchant currency.
+fn earnings_totals(rows: &[Value]) -> Vec<Value> {
+    let mut totals: BTreeMap<(String, String), (f64, usize)> = BTreeMap::new();
+    for row in rows {
+        let key = (
+            row["transaction_type"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            row["merchant_currency"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        );
+        let entry = totals.entry(key).or_default();
+        entry.0 += row["amount_merchant_currency"].as_f64().unwrap_or_default();
+        entry.1 += 1;
+    }
+    totals
+        .into_iter()
+        .map(|((kind, currency), (amount, count))| {
+            json!({
+                "transaction_type": kind,
+                "merchant_currency": currency,
+                "transactions": count,
+                "amount": (amount * 100.0).round() / 100.0,
+            })
+        })
+        .collect()
+}
+
+/// `(date, percent)` per day of a metric set query, oldest first.
+fn daily_rates(metrics: &Value, metric: &str) -> Vec<(String, Option<f64>)> {
+    let mut rows: Vec<(String, Option<f64>)> = metrics["rows"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|row| {
+            let start = &row["startTime"];
+            let date = format!(
+                "{:04}-{:02}-{:02}",
+                start["year"].as_i64().unwrap_or_default(),
+                start["month"].as_i64().unwrap_or_default(),
+                start["day"].as_i64().unwrap_or_default()
+            );
+            let rate = row["metrics"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|m| m["metric"].as_str() == Some(metric))
+                .and_then(|m| m["decimalValue"]["value"].as_str()?.parse::<f64>().ok())
+                .map(|rate| rate * 100.0);
+            (date, rate)
+        })
+        .collect();
+    rows.sort_by(|a, b| a.0.cmp(&b.0));
+    rows
 }
 
 pub async fn handle(
     cmd: &ReportsCommand,
-    _client: &GoogleClient,
+    client: &GoogleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         ReportsCommand::Stats {
@@ -33,5 +274,140 @@ pub async fn handle(
                 )
             }))
         }
+        ReportsCommand::Vitals {
+            package_name,
+            metric,
+            days,
+            chart,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let (metric_set, metric_name) = metric.query_names();
+            let end = Utc::now().date_naive();
+            let start = end - Duration::days(*days);
+            let query = json!({
+                "timelineSpec": {
+                    "aggregationPeriod": "DAILY",
+                    "startTime": {
+                        "year": start.year(), "month": start.month(), "day": start.day(),
+                        "timeZone": { "id": "America/Los_Angeles" }
+                    },
+                    "endTime": {
+                        "year": end.year(), "month": end.month(), "day": end.day(),
+                        "timeZone": { "id": "America/Los_Angeles" }
+                    }
+                },
+                "metrics": [metric_name],
+            });
+            let metrics = client
+                .query_metric_set(package_name, metric_set, &query)
+                .await?;
+            let series = daily_rates(&metrics, metric_name);
+
+            let values: Vec<Option<f64>> = series.iter().map(|(_, v)| *v).collect();
+            let sparkline = chart::sparkline(&values);
+            if *chart {
+                eprintln!("{metric_name} (% of users, daily): {sparkline}");
+                eprint!("{}", chart::bars(&series, CHART_WIDTH));
+            }
+            Ok(json!({
+                "package_name": package_name,
+                "metric": metric_name,
+                "peak": values.iter().flatten().copied().reduce(f64::max),
+                "sparkline": sparkline,
+                "series": series
+                    .iter()
+                    .map(|(date, value)| json!({ "date": date, "percent": value }))
+                    .collect::<Vec<_>>(),
+            }))
+        }
+        ReportsCommand::Earnings {
+            month,
+            bucket,
+            package,
+            file,
+        } => {
+            check_month(month)?;
+            let configured = Config::load()?.revenue.and_then(|r| r.google_bucket);
+            let bucket = bucket
+                .as_deref()
+                .or(configured.as_deref())
+                .ok_or("--bucket is required (or set google_bucket under [revenue])")?;
+            let mut fields: Vec<String> = Vec::new();
+            let mut rows = Vec::new();
+            let files = bucket_csvs(client, bucket, &earnings_prefix(month)).await?;
+            for (_, text) in &files {
+                let (file_fields, file_rows) = parse_earnings(text);
+                for field in file_fields {
+                    if !fields.contains(&field) {
+                        fields.push(field);
+                    }
+                }
+                rows.extend(file_rows);
+            }
+            if let Some(package) = package {
+                rows.retain(|r| r["product_id"].as_str() == Some(package.as_str()));
+            }
+
+            let mut result = json!({
+                "month": month,
+                "bucket": bucket,
+                "files": files.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+                "transactions": rows.len(),
+                "totals": earnings_totals(&rows),
+            });
+            match file {
+                Some(path) => {
+                    let columns: Vec<&str> = fields.iter().map(String::as_str).collect();
+                    std::fs::write(path, csv::render(&rows, &columns))?;
+                    result["file"] = json!(path.display().to_string());
+                }
+                None => result["rows"] = json!(rows),
+            }
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_rates_are_dated_percentages_in_order() {
+        let metrics = json!({"rows": [
+            {
+                "startTime": {"year": 2025, "month": 3, "day": 2},
+                "metrics": [{"metric": "crashRate", "decimalValue": {"value": "0.021"}}]
+            },
+            {
+                "startTime": {"year": 2025, "month": 3, "day": 1},
+                "metrics": [{"metric": "crashRate", "decimalValue": {"value": "0.004"}}]
+            }
+        ]});
+        let rates = daily_rates(&metrics, "crashRate");
+        assert_eq!(rates[0].0, "2025-03-01");
+        assert!((rates[0].1.unwrap() - 0.4).abs() < 1e-9);
+        assert_eq!(rates[1].0, "2025-03-02");
+        assert!(daily_rates(&metrics, "anrRate")[0].1.is_none());
+    }
+
+    #[test]
+    fn normalizes_earnings_transactions() {
+        let text = "\u{feff}Description,Transaction Date,Transaction Type,Product id,Merchant Currency,Amount (Merchant Currency)\n\
+                    GPA.1,\"Sep 1, 2024\",Charge,com.example.app,EUR,\"1,000.00\"\n\
+                    GPA.1,\"Sep 1, 2024\",Google fee,com.example.app,EUR,-150.00\n\
+                    GPA.2,\"Sep 2, 2024\",Charge,com.example.app,EUR,5.50\n";
+        let (fields, rows) = parse_earnings(text);
+        assert_eq!(fields[1], "transaction_date");
+        assert_eq!(fields[5], "amount_merchant_currency");
+        assert_eq!(rows[0]["transaction_date"], "2024-09-01");
+        assert_eq!(rows[0]["amount_merchant_currency"], 1000.0);
+        assert_eq!(
+            earnings_totals(&rows),
+            [
+                json!({"transaction_type": "Charge", "merchant_currency": "EUR", "transactions": 2, "amount": 1005.5}),
+                json!({"transaction_type": "Google fee", "merchant_currency": "EUR", "transactions": 1, "amount": -150.0}),
+            ]
+        );
     }
 }
@@ -2,24 +2,54 @@ use clap::Subcommand;
 use serde_json::{json, Value};
 
 use crate::api::google_client::GoogleClient;
+use crate::cli::review_export::{self, ExportArgs};
 
 #[derive(Subcommand)]
 pub enum ReviewsCommand {
     /// List reviews
     List {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Sort order
         #[arg(long, default_value = "recent")]
         sort: String,
+        /// Only reviews without a developer reply
+        #[arg(long)]
+        unreplied_only: bool,
     },
-    /// Reply to a review
+    /// Show a review with its full comment thread and device metadata
+    ///
+    /// Examples:
+    ///   storeops google reviews get REVIEW_ID --package-name com.example.app
+    Get {
+        /// Review ID
+        review_id: String,
+        /// Package name
+        #[arg(long)]
+        package_name: Option<String>,
+        /// Translate the review text to this language (e.g. en)
+        #[arg(long)]
+        translation_language: Option<String>,
+    },
+    /// Export all reviews in a date range to CSV or JSON lines
+    ///
+    /// The Play API only returns reviews from the last week.
+    ///
+    /// Examples:
+    ///   storeops google reviews export com.example.app --file reviews.jsonl --format jsonl --sentiment
+    Export {
+        /// Package name
+        package_name: Option<String>,
+        #[command(flatten)]
+        args: ExportArgs,
+    },
+    /// Reply to a review; returns the updated thread
     Reply {
         /// Review ID
         review_id: String,
         /// Package name
         #[arg(long)]
-        package_name: String,
+        package_name: Option<String>,
         /// Reply text
         #[arg(long)]
         body: String,
@@ -31,14 +61,49 @@ pub async fn handle(
     client: &GoogleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
-        ReviewsCommand::List { package_name, .. } => {
-            client.get(&format!("/{package_name}/reviews"), &[]).await
+        ReviewsCommand::List {
+            package_name,
+            unreplied_only,
+            ..
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            if crate::output::jsonl::enabled() {
+                return stream_reviews(package_name, *unreplied_only, client).await;
+            }
+            let mut reviews: Value = client.get(&format!("/{package_name}/reviews"), &[]).await?;
+            if *unreplied_only {
+                if let Some(list) = reviews["reviews"].as_array_mut() {
+                    list.retain(|r| !has_reply(r));
+                }
+            }
+            Ok(reviews)
+        }
+        ReviewsCommand::Get {
+            review_id,
+            package_name,
+            translation_language,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let mut query = vec![];
+            if let Some(lang) = translation_language {
+                query.push(("translationLanguage", lang.as_str()));
+            }
+            let review: Value = client
+                .get(&format!("/{package_name}/reviews/{review_id}"), &query)
+                .await?;
+            Ok(thread(&review))
+        }
+        ReviewsCommand::Export { package_name, args } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let rows = export_rows(package_name, args, client).await?;
+            review_export::write(rows, args)
         }
         ReviewsCommand::Reply {
             review_id,
             package_name,
             body,
         } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             client
                 .post(
                     &format!("/{package_name}/reviews/{review_id}:reply"),
@@ -46,7 +111,202 @@ pub async fn handle(
                         "replyText": body
                     }),
                 )
-                .await
+                .await?;
+            let review: Value = client
+                .get(&format!("/{package_name}/reviews/{review_id}"), &[])
+                .await?;
+            Ok(thread(&review))
+        }
+    }
+}
+
+/// Page through every review and normalize those in range to export rows.
+pub async fn export_rows(
+    package_name: &str,
+    args: &ExportArgs,
+    client: &GoogleClient,
+) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+    let mut rows = Vec::new();
+    let mut token: Option<String> = None;
+    loop {
+        let mut query = vec![("maxResults", "100")];
+        if let Some(t) = &token {
+            query.push(("token", t.as_str()));
         }
+        let page: Value = client
+            .get(&format!("/{package_name}/reviews"), &query)
+            .await?;
+        for review in page["reviews"].as_array().into_iter().flatten() {
+            let Some(user) = review["comments"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find_map(|c| c.get("userComment"))
+            else {
+                continue;
+            };
+            let date = user["lastModified"]["seconds"]
+                .as_str()
+                .and_then(|s| s.parse::<i64>().ok())
+                .and_then(|s| chrono::DateTime::from_timestamp(s, 0));
+            if !date.is_some_and(|d| args.in_range(&d)) {
+                continue;
+            }
+            rows.push(json!({
+                "store": "google",
+                "review_id": review["reviewId"],
+                "date": timestamp(&user["lastModified"]),
+                "rating": user["starRating"],
+                "body": user["text"],
+                "author": review["authorName"],
+                "language": user["reviewerLanguage"],
+                "app_version": user["appVersionName"],
+                "replied": has_reply(review),
+            }));
+        }
+        eprintln!("Fetched {} review(s)", rows.len());
+        token = page["tokenPagination"]["nextPageToken"]
+            .as_str()
+            .map(String::from);
+        if token.is_none() {
+            return Ok(rows);
+        }
+    }
+}
+
+/// Print every review as JSON lines (`--output jsonl`), page by page, without
+/// holding them in memory.
+async fn stream_reviews(
+    package_name: &str,
+    unreplied_only: bool,
+    client: &GoogleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let path = format!("/{package_name}/reviews");
+    let mut count = 0;
+    let mut token: Option<String> = None;
+    loop {
+        let mut query = vec![("maxResults", "100")];
+        if let Some(t) = &token {
+            query.push(("token", t.as_str()));
+        }
+        let rest = client
+            .get_streamed(&path, &query, "reviews", &mut |review| {
+                if !(unreplied_only && has_reply(&review)) {
+                    crate::output::jsonl::emit(&review);
+                    count += 1;
+                }
+            })
+            .await?;
+        token = rest["tokenPagination"]["nextPageToken"]
+            .as_str()
+            .map(String::from);
+        if token.is_none() {
+            eprintln!("Streamed {count} review(s)");
+            return Ok(Value::Null);
+        }
+    }
+}
+
+/// Whether the developer has replied to a review.
+fn has_reply(review: &Value) -> bool {
+    review["comments"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|c| c.get("developerComment").is_some())
+}
+
+/// Play's `lastModified` timestamp (`{"seconds": "..."}`) as RFC 3339.
+fn timestamp(ts: &Value) -> Value {
+    let seconds = ts["seconds"]
+        .as_str()
+        .and_then(|s| s.parse::<i64>().ok())
+        .or_else(|| ts["seconds"].as_i64());
+    seconds
+        .and_then(|s| chrono::DateTime::from_timestamp(s, 0))
+        .map(|t| json!(t.to_rfc3339()))
+        .unwrap_or(Value::Null)
+}
+
+/// A review as a chronological thread of user and developer comments, with the
+/// device metadata Play attaches to the user's comment.
+fn thread(review: &Value) -> Value {
+    let mut device = Value::Null;
+    let comments: Vec<Value> = review["comments"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|c| {
+            if let Some(user) = c.get("userComment") {
+                device = json!({
+                    "device": user["device"],
+                    "android_os_version": user["androidOsVersion"],
+                    "app_version_code": user["appVersionCode"],
+                    "app_version_name": user["appVersionName"],
+                    "reviewer_language": user["reviewerLanguage"],
+                    "metadata": user["deviceMetadata"],
+                });
+                Some(json!({
+                    "from": "user",
+                    "text": user["text"],
+                    "original_text": user["originalText"],
+                    "rating": user["starRating"],
+                    "thumbs_up": user["thumbsUpCount"],
+                    "thumbs_down": user["thumbsDownCount"],
+                    "last_modified": timestamp(&user["lastModified"]),
+                }))
+            } else {
+                c.get("developerComment").map(|dev| {
+                    json!({
+                        "from": "developer",
+                        "text": dev["text"],
+                        "last_modified": timestamp(&dev["lastModified"]),
+                    })
+                })
+            }
+        })
+        .collect();
+    json!({
+        "review_id": review["reviewId"],
+        "author": review["authorName"],
+        "replied": has_reply(review),
+        "thread": comments,
+        "device": device,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_thread_with_device_metadata() {
+        let review = json!({
+            "reviewId": "r1",
+            "authorName": "Sam",
+            "comments": [
+                {"userComment": {
+                    "text": "Crashes on start",
+                    "starRating": 2,
+                    "lastModified": {"seconds": "1700000000"},
+                    "device": "a10",
+                    "androidOsVersion": 34,
+                    "deviceMetadata": {"manufacturer": "Samsung"}
+                }},
+                {"developerComment": {"text": "Fixed in 2.1", "lastModified": {"seconds": "1700003600"}}}
+            ]
+        });
+        let t = thread(&review);
+        assert_eq!(t["replied"], true);
+        assert_eq!(t["thread"][0]["from"], "user");
+        assert_eq!(t["thread"][0]["last_modified"], "2023-11-14T22:13:20+00:00");
+        assert_eq!(t["thread"][1]["text"], "Fixed in 2.1");
+        assert_eq!(t["device"]["metadata"]["manufacturer"], "Samsung");
+    }
+
+    #[test]
+    fn unreplied_reviews_have_only_user_comments() {
+        let review = json!({"comments": [{"userComment": {"text": "Nice"}}]});
+        assert!(!has_reply(&review));
     }
 }
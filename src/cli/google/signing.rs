@@ -0,0 +1,192 @@
+//! App signing and upload certificate fingerprints.
+//!
+//! Play only exposes the app signing certificate through generated APKs, as a SHA-256
+//! hash. The upload certificate is not available from the API, so `fingerprint`
+//! works on a certificate file (e.g. `upload_cert.der` exported from the Play Console
+//! or `keytool -exportcert`) and can check it against an expected fingerprint.
+
+use base64::Engine;
+use clap::Subcommand;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+use crate::api::google_client::GoogleClient;
+
+#[derive(Subcommand)]
+pub enum SigningCommand {
+    /// Show the SHA-256 of the app signing certificate Play signs a build with
+    ///
+    /// Examples:
+    ///   storeops google signing app-key com.example.app --version-code 42
+    AppKey {
+        /// Package name
+        package_name: Option<String>,
+        /// Version code of an uploaded bundle
+        #[arg(long)]
+        version_code: i64,
+    },
+    /// Fingerprint a certificate file (PEM or DER), optionally verifying it
+    ///
+    /// Examples:
+    ///   storeops google signing fingerprint --cert upload_cert.der --pem-out upload_cert.pem
+    ///   storeops google signing fingerprint --cert upload_cert.pem --expect AB:CD:...
+    Fingerprint {
+        /// Certificate file, PEM or DER encoded
+        #[arg(long)]
+        cert: PathBuf,
+        /// Fail unless the SHA-1 or SHA-256 fingerprint matches (colons optional)
+        #[arg(long)]
+        expect: Option<String>,
+        /// Also write the certificate as PEM to this file
+        #[arg(long)]
+        pem_out: Option<PathBuf>,
+    },
+}
+
+/// Uppercase colon-separated hex, the form keytool and the Play Console show.
+fn colon_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Fingerprint in comparable form: hex digits only, uppercase.
+fn normalize_fingerprint(s: &str) -> String {
+    s.chars()
+        .filter(char::is_ascii_hexdigit)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// DER bytes of a PEM or DER certificate file.
+fn certificate_der(data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return Ok(data.to_vec());
+    };
+    if !text.contains("-----BEGIN CERTIFICATE-----") {
+        return Ok(data.to_vec());
+    }
+    let body: String = text
+        .lines()
+        .skip_while(|l| !l.starts_with("-----BEGIN CERTIFICATE-----"))
+        .skip(1)
+        .take_while(|l| !l.starts_with("-----END CERTIFICATE-----"))
+        .map(str::trim)
+        .collect();
+    Ok(base64::engine::general_purpose::STANDARD.decode(body)?)
+}
+
+fn to_pem(der: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(der);
+    let lines: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(64)
+        .filter_map(|c| std::str::from_utf8(c).ok())
+        .collect();
+    format!(
+        "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
+        lines.join("\n")
+    )
+}
+
+fn fingerprints(der: &[u8]) -> (String, String) {
+    let sha1 = ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, der);
+    (colon_hex(sha1.as_ref()), colon_hex(&Sha256::digest(der)))
+}
+
+pub async fn handle(
+    cmd: &SigningCommand,
+    client: &GoogleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        SigningCommand::AppKey {
+            package_name,
+            version_code,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let generated: Value = client
+                .get(
+                    &format!("/{package_name}/generatedApks/{version_code}"),
+                    &[],
+                )
+                .await?;
+            let mut hashes: Vec<String> = generated["generatedApks"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|g| g["certificateSha256Hash"].as_str())
+                .map(|h| colon_hex(&hex::decode(normalize_fingerprint(h)).unwrap_or_default()))
+                .collect();
+            hashes.dedup();
+            if hashes.is_empty() {
+                return Err(format!(
+                    "no generated APKs for version code {version_code}; is the app enrolled in Play App Signing?"
+                )
+                .into());
+            }
+            Ok(json!({
+                "package_name": package_name,
+                "version_code": version_code,
+                "app_signing_sha256": hashes,
+            }))
+        }
+        SigningCommand::Fingerprint {
+            cert,
+            expect,
+            pem_out,
+        } => {
+            let der = certificate_der(&std::fs::read(cert)?)?;
+            let (sha1, sha256) = fingerprints(&der);
+            if let Some(path) = pem_out {
+                std::fs::write(path, to_pem(&der))?;
+                eprintln!("Wrote {}", path.display());
+            }
+            let mut result = json!({
+                "file": cert.display().to_string(),
+                "sha1": sha1,
+                "sha256": sha256,
+            });
+            if let Some(expected) = expect {
+                let expected = normalize_fingerprint(expected);
+                let matches = [&sha1, &sha256]
+                    .iter()
+                    .any(|f| normalize_fingerprint(f) == expected);
+                if !matches {
+                    return Err(format!(
+                        "{} does not match the expected fingerprint (SHA-1 {sha1}, SHA-256 {sha256})",
+                        cert.display()
+                    )
+                    .into());
+                }
+                result["matches"] = json!(true);
+            }
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pem_and_der_give_the_same_fingerprints() {
+        let der: Vec<u8> = (0..200u8).collect();
+        let pem = to_pem(&der);
+        assert_eq!(certificate_der(pem.as_bytes()).unwrap(), der);
+        assert_eq!(certificate_der(&der).unwrap(), der);
+
+        let (sha1, sha256) = fingerprints(b"abc");
+        assert_eq!(
+            sha1,
+            "A9:99:3E:36:47:06:81:6A:BA:3E:25:71:78:50:C2:6C:9C:D0:D8:9D"
+        );
+        assert_eq!(
+            normalize_fingerprint(&sha256),
+            "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD"
+        );
+    }
+}
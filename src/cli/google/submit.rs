@@ -7,10 +7,7 @@ pub async fn handle(
     track: &str,
     client: &GoogleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
-    let edit: Value = client
-        .post(&format!("/{package_name}/edits"), &json!({}))
-        .await?;
-    let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+    let edit_id = &client.open_edit(package_name).await?;
 
     let track_info: Value = client
         .get(
@@ -19,12 +16,7 @@ pub async fn handle(
         )
         .await?;
 
-    let commit: Value = client
-        .post(
-            &format!("/{package_name}/edits/{edit_id}:commit"),
-            &json!({}),
-        )
-        .await?;
+    let commit: Value = client.commit_edit(package_name, edit_id).await?;
 
     Ok(json!({
         "track": track_info,
@@ -3,16 +3,26 @@
 //! Provides pull (download) and push (upload) functionality for app metadata and screenshots
 //! across all locales in a single operation.
 
-use clap::Subcommand;
+use clap::{Subcommand, ValueEnum};
 use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use tokio::fs;
 
 use crate::api::google_client::GoogleClient;
+use crate::cli::checkpoint::PushCheckpoint;
+use crate::cli::download::{download_all, Download, FetchOptions, DEFAULT_CONCURRENCY};
+use crate::cli::pull_manifest::PullManifest;
+use crate::cli::screenshots::google_upload_problem;
+use crate::cli::sync::{
+    changed_fields, field_report, locale_selected, ordered_images, write_order,
+};
+use crate::cli::template;
+use crate::output::stream;
 
 /// Google Play locale codes mapped to internal standardized codes.
 /// Format: "gp_locale" -> "internal_locale"
-fn gp_to_internal_locale(gp_locale: &str) -> String {
+pub(super) fn gp_to_internal_locale(gp_locale: &str) -> String {
     match gp_locale {
         "pl-PL" => "pl".to_string(),
         "sv-SE" => "sv".to_string(),
@@ -32,28 +42,66 @@ fn internal_to_gp_locale(internal_locale: &str) -> String {
     }
 }
 
+/// Device form factors a listing has images for, selected with `--only`.
+///
+/// The Play Developer API has no image types for Android Auto or Automotive OS, so
+/// those listing assets can only be managed in the Play Console.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FormFactor {
+    /// Phone screenshots, plus the feature graphic and icon
+    Phone,
+    /// 7" and 10" tablet screenshots
+    Tablet,
+    /// Android TV screenshots and banner
+    Tv,
+    /// Wear OS screenshots
+    Wear,
+}
+
 /// Google Play image types for screenshots
-const SCREENSHOT_TYPES: &[(&str, &str)] = &[
-    ("phoneScreenshots", "phoneScreenshots"),
-    ("sevenInchScreenshots", "sevenInchScreenshots"),
-    ("tenInchScreenshots", "tenInchScreenshots"),
-    ("tvScreenshots", "tvScreenshots"),
-    ("wearScreenshots", "wearScreenshots"),
+const SCREENSHOT_TYPES: &[(&str, &str, FormFactor)] = &[
+    ("phoneScreenshots", "phoneScreenshots", FormFactor::Phone),
+    (
+        "sevenInchScreenshots",
+        "sevenInchScreenshots",
+        FormFactor::Tablet,
+    ),
+    (
+        "tenInchScreenshots",
+        "tenInchScreenshots",
+        FormFactor::Tablet,
+    ),
+    ("tvScreenshots", "tvScreenshots", FormFactor::Tv),
+    ("wearScreenshots", "wearScreenshots", FormFactor::Wear),
 ];
 
 /// Google Play image types for graphics
-const GRAPHIC_TYPES: &[(&str, &str)] = &[
-    ("featureGraphic", "featureGraphic"),
-    ("icon", "icon"),
-    ("tvBanner", "tvBanner"),
+const GRAPHIC_TYPES: &[(&str, &str, FormFactor)] = &[
+    ("featureGraphic", "featureGraphic", FormFactor::Phone),
+    ("icon", "icon", FormFactor::Phone),
+    ("tvBanner", "tvBanner", FormFactor::Tv),
 ];
 
+/// Screenshots Google Play keeps per image type and locale
+const MAX_SCREENSHOTS: usize = 8;
+
+/// Image types of the selected form factors (all of them when `only` is empty).
+fn selected<'a>(
+    types: &'a [(&'a str, &'a str, FormFactor)],
+    only: &'a [FormFactor],
+) -> impl Iterator<Item = (&'a str, &'a str)> {
+    types
+        .iter()
+        .filter(move |(_, _, form_factor)| only.is_empty() || only.contains(form_factor))
+        .map(|(image_type, name, _)| (*image_type, *name))
+}
+
 #[derive(Subcommand)]
 pub enum SyncCommand {
     /// Pull (download) all metadata and screenshots for an app
     Pull {
         /// Android Package Name (e.g., com.example.app)
-        package_name: String,
+        package_name: Option<String>,
         /// Output directory for downloaded metadata
         #[arg(long)]
         output_dir: PathBuf,
@@ -66,11 +114,23 @@ pub enum SyncCommand {
         /// Return screenshot URLs in JSON output instead of downloading
         #[arg(long, default_value = "false")]
         urls_only: bool,
+        /// Images downloaded at the same time
+        #[arg(long, default_value_t = DEFAULT_CONCURRENCY)]
+        concurrency: usize,
+        /// Ignore ETags from the previous pull and fetch everything
+        #[arg(long)]
+        no_cache: bool,
+        /// Only images for these form factors (comma-separated)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        only: Vec<FormFactor>,
+        /// Only these locales (comma-separated; `*` globs such as `es-*`)
+        #[arg(long, value_delimiter = ',')]
+        locales: Vec<String>,
     },
     /// Push (upload) all metadata and screenshots for an app
     Push {
         /// Android Package Name (e.g., com.example.app)
-        package_name: String,
+        package_name: Option<String>,
         /// Directory containing metadata to upload
         #[arg(long)]
         metadata_dir: PathBuf,
@@ -80,6 +140,18 @@ pub enum SyncCommand {
         /// Skip uploading metadata
         #[arg(long, default_value = "false")]
         skip_metadata: bool,
+        /// Skip locales and screenshot sets finished by an interrupted push
+        #[arg(long)]
+        resume: bool,
+        /// Only images for these form factors (comma-separated)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        only: Vec<FormFactor>,
+        /// Only these locales (comma-separated; `*` globs such as `es-*`)
+        #[arg(long, value_delimiter = ',')]
+        locales: Vec<String>,
+        /// Value for a `{{key}}` placeholder in metadata files (repeatable)
+        #[arg(long = "var", value_name = "KEY=VALUE")]
+        vars: Vec<String>,
     },
 }
 
@@ -94,13 +166,26 @@ pub async fn handle(
             skip_screenshots,
             skip_metadata,
             urls_only,
+            concurrency,
+            no_cache,
+            only,
+            locales,
         } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             handle_pull(
                 package_name,
                 output_dir,
-                *skip_screenshots,
-                *skip_metadata,
-                *urls_only,
+                PullParts {
+                    skip_screenshots: *skip_screenshots,
+                    skip_metadata: *skip_metadata,
+                    urls_only: *urls_only,
+                    only,
+                    locales,
+                },
+                FetchOptions {
+                    concurrency: *concurrency,
+                    no_cache: *no_cache,
+                },
                 client,
             )
             .await
@@ -110,12 +195,23 @@ pub async fn handle(
             metadata_dir,
             skip_screenshots,
             skip_metadata,
+            resume,
+            only,
+            locales,
+            vars,
         } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
             handle_push(
                 package_name,
                 metadata_dir,
-                *skip_screenshots,
-                *skip_metadata,
+                PushParts {
+                    skip_screenshots: *skip_screenshots,
+                    skip_metadata: *skip_metadata,
+                    only,
+                    locales,
+                    vars,
+                },
+                *resume,
                 client,
             )
             .await
@@ -123,23 +219,68 @@ pub async fn handle(
     }
 }
 
-async fn handle_pull(
+/// Pull only the metadata text files into `output_dir`, bypassing the pull manifest.
+///
+/// Used by `sync check` to compare remote content against a local directory.
+pub async fn pull_metadata(
     package_name: &str,
     output_dir: &PathBuf,
+    client: &GoogleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let fetch = FetchOptions {
+        concurrency: DEFAULT_CONCURRENCY,
+        no_cache: true,
+    };
+    let parts = PullParts {
+        skip_screenshots: true,
+        skip_metadata: false,
+        urls_only: false,
+        only: &[],
+        locales: &[],
+    };
+    handle_pull(package_name, output_dir, parts, fetch, client).await
+}
+
+/// Which parts of the listing a pull fetches.
+struct PullParts<'a> {
     skip_screenshots: bool,
     skip_metadata: bool,
     urls_only: bool,
+    only: &'a [FormFactor],
+    locales: &'a [String],
+}
+
+/// Which parts of the listing a push uploads.
+struct PushParts<'a> {
+    skip_screenshots: bool,
+    skip_metadata: bool,
+    only: &'a [FormFactor],
+    locales: &'a [String],
+    /// `--var KEY=VALUE` placeholder values
+    vars: &'a [String],
+}
+
+async fn handle_pull(
+    package_name: &str,
+    output_dir: &PathBuf,
+    parts: PullParts<'_>,
+    fetch: FetchOptions,
     client: &GoogleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     use std::collections::HashMap;
 
+    let PullParts {
+        skip_screenshots,
+        skip_metadata,
+        urls_only,
+        only,
+        locales,
+    } = parts;
+
     eprintln!("Creating edit for package: {}", package_name);
 
     // Create an edit session
-    let edit: Value = client
-        .post(&format!("/{package_name}/edits"), &json!({}))
-        .await?;
-    let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+    let edit_id = &client.open_edit(package_name).await?;
     eprintln!("Edit ID: {}", edit_id);
 
     // Create output directory
@@ -149,18 +290,46 @@ async fn handle_pull(
     let mut screenshots_downloaded = 0u32;
     // For urls_only mode: locale -> device -> [urls]
     let mut screenshot_urls: HashMap<String, HashMap<String, Vec<String>>> = HashMap::new();
+    let mut downloads = Vec::new();
+    let mut manifest = PullManifest::open(output_dir, fetch.no_cache);
+    let mut metadata_unchanged = false;
+    let mut images_unchanged = 0u32;
 
     if !skip_metadata {
-        // Get all store listings
+        // Get all store listings, unless unchanged since the last pull
         eprintln!("Fetching store listings...");
-        let listings: Value = client
-            .get(&format!("/{package_name}/edits/{edit_id}/listings"), &[])
-            .await?;
+        let listings_key = format!("google:{package_name}:listings");
+        let listings = match client
+            .get_if_none_match(
+                &format!("/{package_name}/edits/{edit_id}/listings"),
+                &[],
+                manifest.etag(&listings_key),
+            )
+            .await?
+        {
+            Some((listings, etag)) => {
+                // A partial pull must not mark the other locales as up to date
+                if locales.is_empty() {
+                    manifest.set(&listings_key, etag);
+                }
+                listings
+            }
+            None => {
+                eprintln!("Metadata unchanged since last pull");
+                metadata_unchanged = true;
+                Value::Null
+            }
+        };
 
         if let Some(listings_arr) = listings["listings"].as_array() {
             for listing in listings_arr {
                 let gp_locale = listing["language"].as_str().unwrap_or("en-US");
                 let internal_locale = gp_to_internal_locale(gp_locale);
+                if !locale_selected(locales, &internal_locale)
+                    && !locale_selected(locales, gp_locale)
+                {
+                    continue;
+                }
                 let locale_dir = output_dir.join(&internal_locale);
                 fs::create_dir_all(&locale_dir).await?;
 
@@ -192,6 +361,10 @@ async fn handle_pull(
                     }
                 }
 
+                stream::emit(
+                    "locale",
+                    json!({ "locale": internal_locale, "status": "downloaded" }),
+                );
                 locales_downloaded.push(internal_locale.clone());
                 eprintln!("  Downloaded metadata for: {}", internal_locale);
             }
@@ -209,21 +382,25 @@ async fn handle_pull(
             .get(&format!("/{package_name}/edits/{edit_id}/listings"), &[])
             .await?;
 
-        let locales: Vec<String> = if let Some(listings_arr) = listings["listings"].as_array() {
-            listings_arr
-                .iter()
-                .filter_map(|l| l["language"].as_str())
-                .map(|s| s.to_string())
-                .collect()
-        } else {
-            vec![]
-        };
-
-        for gp_locale in &locales {
+        let listed_locales: Vec<String> =
+            if let Some(listings_arr) = listings["listings"].as_array() {
+                listings_arr
+                    .iter()
+                    .filter_map(|l| l["language"].as_str())
+                    .map(|s| s.to_string())
+                    .collect()
+            } else {
+                vec![]
+            };
+
+        for gp_locale in &listed_locales {
             let internal_locale = gp_to_internal_locale(gp_locale);
+            if !locale_selected(locales, &internal_locale) && !locale_selected(locales, gp_locale) {
+                continue;
+            }
 
             // Download screenshots
-            for (image_type, dir_name) in SCREENSHOT_TYPES {
+            for (image_type, dir_name) in selected(SCREENSHOT_TYPES, only) {
                 let response: Value = match client
                     .get(
                         &format!(
@@ -243,6 +420,7 @@ async fn handle_pull(
                         continue;
                     }
 
+                    let mut pulled = Vec::new();
                     for (idx, img) in images_arr.iter().enumerate() {
                         if let Some(url) = img["url"].as_str() {
                             if urls_only {
@@ -255,34 +433,36 @@ async fn handle_pull(
                                     .push(url.to_string());
                                 screenshots_downloaded += 1;
                             } else if !skip_screenshots {
-                                // Download the image
-                                let images_dir = output_dir.join(&internal_locale).join("images");
-                                let ss_dir = images_dir.join(dir_name);
-                                fs::create_dir_all(&ss_dir).await?;
-
                                 let filename = format!("{:02}.png", idx + 1);
-                                let file_path = ss_dir.join(&filename);
-
-                                match download_image(url, &file_path).await {
-                                    Ok(_) => {
-                                        screenshots_downloaded += 1;
-                                        eprintln!(
-                                            "  Downloaded: {}/{}/{}",
-                                            internal_locale, dir_name, filename
-                                        );
-                                    }
-                                    Err(e) => {
-                                        eprintln!("  Failed to download: {}", e);
-                                    }
-                                }
+                                downloads.push(Download {
+                                    url: url.to_string(),
+                                    path: output_dir
+                                        .join(&internal_locale)
+                                        .join("images")
+                                        .join(dir_name)
+                                        .join(&filename),
+                                    locale: internal_locale.clone(),
+                                    file: format!("images/{dir_name}/{filename}"),
+                                    etag: None,
+                                });
+                                pulled.push(filename);
                             }
                         }
                     }
+                    if !pulled.is_empty() {
+                        write_order(
+                            &output_dir
+                                .join(&internal_locale)
+                                .join("images")
+                                .join(dir_name),
+                            &pulled,
+                        )?;
+                    }
                 }
             }
 
             // Download/collect graphics (feature graphic, icon, etc.)
-            for (image_type, file_name) in GRAPHIC_TYPES {
+            for (image_type, file_name) in selected(GRAPHIC_TYPES, only) {
                 let response: Value = match client
                     .get(
                         &format!(
@@ -310,22 +490,16 @@ async fn handle_pull(
                                     .push(url.to_string());
                                 screenshots_downloaded += 1;
                             } else if !skip_screenshots {
-                                let images_dir = output_dir.join(&internal_locale).join("images");
-                                fs::create_dir_all(&images_dir).await?;
-                                let file_path = images_dir.join(format!("{}.png", file_name));
-
-                                match download_image(url, &file_path).await {
-                                    Ok(_) => {
-                                        screenshots_downloaded += 1;
-                                        eprintln!(
-                                            "  Downloaded: {}/{}.png",
-                                            internal_locale, file_name
-                                        );
-                                    }
-                                    Err(e) => {
-                                        eprintln!("  Failed to download {}: {}", file_name, e);
-                                    }
-                                }
+                                downloads.push(Download {
+                                    url: url.to_string(),
+                                    path: output_dir
+                                        .join(&internal_locale)
+                                        .join("images")
+                                        .join(format!("{file_name}.png")),
+                                    locale: internal_locale.clone(),
+                                    file: format!("images/{file_name}.png"),
+                                    etag: None,
+                                });
                             }
                         }
                     }
@@ -334,16 +508,30 @@ async fn handle_pull(
         }
     }
 
-    // Discard the edit (we were only reading)
-    let _ = client
-        .delete_path(&format!("/{package_name}/edits/{edit_id}"))
-        .await;
+    // Discard the edit (we were only reading); image URLs stay valid without it
+    client.discard_edit(package_name, edit_id).await;
+
+    if !downloads.is_empty() {
+        eprintln!("Downloading {} image(s)...", downloads.len());
+        for d in &mut downloads {
+            d.etag = manifest.etag(&d.key()).map(String::from);
+        }
+        let done = download_all(downloads, fetch.concurrency).await;
+        screenshots_downloaded += done.downloaded;
+        images_unchanged = done.unchanged;
+        for (key, etag) in done.etags {
+            manifest.set(&key, etag);
+        }
+    }
+    manifest.save();
 
     let mut result = json!({
         "success": true,
         "package_name": package_name,
         "locales_downloaded": locales_downloaded,
+        "metadata_unchanged": metadata_unchanged,
         "screenshots_downloaded": screenshots_downloaded,
+        "screenshots_unchanged": images_unchanged,
         "output_dir": output_dir.to_string_lossy()
     });
 
@@ -354,40 +542,56 @@ async fn handle_pull(
     Ok(result)
 }
 
-async fn download_image(url: &str, path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    let response = client.get(url).send().await?;
-
-    if !response.status().is_success() {
-        return Err(format!("HTTP {}: {}", response.status(), url).into());
-    }
-
-    let bytes = response.bytes().await?;
-    fs::write(path, bytes).await?;
-    Ok(())
-}
-
 async fn handle_push(
     package_name: &str,
     metadata_dir: &PathBuf,
-    skip_screenshots: bool,
-    skip_metadata: bool,
+    parts: PushParts<'_>,
+    resume: bool,
     client: &GoogleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
+    let PushParts {
+        skip_screenshots,
+        skip_metadata,
+        only,
+        locales,
+        vars,
+    } = parts;
+    let cli_vars = template::parse_vars(vars)?;
     eprintln!(
         "PUSH START: Package={} Dir={:?}",
         package_name, metadata_dir
     );
 
-    // Create an edit session
-    let edit: Value = client
-        .post(&format!("/{package_name}/edits"), &json!({}))
+    // Create an edit session, or keep staging into the one an interrupted push left open
+    let mut checkpoint = PushCheckpoint::open(metadata_dir, package_name, resume);
+    let previous = checkpoint.edit_id.clone();
+    let edit_id = &client
+        .resume_edit(package_name, previous.as_deref())
+        .await?;
+    if previous.as_deref() != Some(edit_id.as_str()) {
+        // Work recorded against another edit was never committed
+        checkpoint.reset();
+        checkpoint.set_edit_id(edit_id);
+    }
+    eprintln!("Using Edit Session: {}", edit_id);
+
+    // Current listings, so only fields that differ are sent
+    let listings: Value = client
+        .get(&format!("/{package_name}/edits/{edit_id}/listings"), &[])
         .await?;
-    let edit_id = edit["id"].as_str().ok_or("no edit id")?;
-    eprintln!("Created Edit Session: {}", edit_id);
+    let remote_listings: HashMap<String, Value> = listings["listings"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|l| Some((l["language"].as_str()?.to_string(), l.clone())))
+        .collect();
 
     let mut locales_pushed = Vec::new();
+    let mut field_changes = serde_json::Map::new();
+    let mut changes = 0u32;
     let mut screenshots_uploaded = 0u32;
+    let mut skipped = Vec::new();
+    let mut invalid = Vec::new();
 
     // Scan metadata directory for locale folders
     let mut entries = fs::read_dir(metadata_dir).await?;
@@ -412,48 +616,79 @@ async fn handle_push(
         }
 
         let gp_locale = internal_to_gp_locale(&internal_locale);
+        if !locale_selected(locales, &internal_locale) && !locale_selected(locales, &gp_locale) {
+            continue;
+        }
         eprintln!("Processing locale: {} (GP: {})", internal_locale, gp_locale);
 
         let mut locale_updated = false;
 
-        if !skip_metadata {
-            // Read metadata files
-            let title = read_file_if_exists(&path.join("title.txt")).await;
-            let short_description = read_file_if_exists(&path.join("short_description.txt")).await;
-            let full_description = read_file_if_exists(&path.join("full_description.txt")).await;
-            let video = read_file_if_exists(&path.join("video.txt")).await;
-
-            // Update store listing if we have any content
-            if title.is_some()
-                || short_description.is_some()
-                || full_description.is_some()
-                || video.is_some()
-            {
-                let mut body = json!({ "language": gp_locale });
-
-                if let Some(v) = &title {
-                    body["title"] = json!(v);
-                }
-                if let Some(v) = &short_description {
-                    body["shortDescription"] = json!(v);
-                }
-                if let Some(v) = &full_description {
-                    body["fullDescription"] = json!(v);
-                }
-                if let Some(v) = &video {
-                    body["video"] = json!(v);
-                }
+        if !skip_metadata && checkpoint.is_done(&internal_locale) {
+            eprintln!("  Listing already staged");
+            skipped.push(internal_locale.clone());
+            locale_updated = true;
+        } else if !skip_metadata {
+            // Read metadata files and keep only the fields that differ from the store
+            let mut local = [
+                ("title", read_file_if_exists(&path.join("title.txt")).await),
+                (
+                    "shortDescription",
+                    read_file_if_exists(&path.join("short_description.txt")).await,
+                ),
+                (
+                    "fullDescription",
+                    read_file_if_exists(&path.join("full_description.txt")).await,
+                ),
+                ("video", read_file_if_exists(&path.join("video.txt")).await),
+            ];
+            let remote = remote_listings.get(&gp_locale);
+
+            // Placeholders: {{app_name}} is the title this push leaves the locale with;
+            // Play has no version to resolve, so {{version}} comes from --var
+            let mut vars = BTreeMap::from([
+                ("date".to_string(), template::today()),
+                ("locale".to_string(), internal_locale.clone()),
+            ]);
+            let app_name = local[0]
+                .1
+                .clone()
+                .filter(|title| !title.contains("{{"))
+                .or_else(|| remote.and_then(|r| r["title"].as_str()).map(String::from));
+            if let Some(name) = app_name {
+                vars.insert("app_name".to_string(), name);
+            }
+            vars.extend(cli_vars.clone());
+            template::expand_fields(&mut local, &vars)
+                .map_err(|e| format!("{internal_locale}: {e}"))?;
+            let (mut body, unchanged) = changed_fields(&local, remote.unwrap_or(&Value::Null));
+            if !body.is_empty() || !unchanged.is_empty() {
+                field_changes.insert(
+                    internal_locale.clone(),
+                    Value::Object(field_report(&body, &unchanged)),
+                );
+            }
 
-                match client
-                    .put(
-                        &format!("/{package_name}/edits/{edit_id}/listings/{gp_locale}"),
-                        &body,
-                    )
-                    .await
-                {
+            if body.is_empty() {
+                if !unchanged.is_empty() {
+                    eprintln!("  Store listing unchanged");
+                    locale_updated = true;
+                    checkpoint.mark_done(internal_locale.clone());
+                }
+            } else {
+                // A new listing is created whole; an existing one is patched field by field
+                let listing_path = format!("/{package_name}/edits/{edit_id}/listings/{gp_locale}");
+                let response = if remote.is_some() {
+                    client.patch(&listing_path, &Value::Object(body)).await
+                } else {
+                    body.insert("language".to_string(), json!(gp_locale));
+                    client.put(&listing_path, &Value::Object(body)).await
+                };
+                match response {
                     Ok(_) => {
                         eprintln!("  Updated store listing");
+                        changes += 1;
                         locale_updated = true;
+                        checkpoint.mark_done(internal_locale.clone());
                     }
                     Err(e) => {
                         eprintln!(
@@ -469,11 +704,49 @@ async fn handle_push(
             let images_dir = path.join("images");
             if images_dir.exists() {
                 // Upload screenshots
-                for (image_type, dir_name) in SCREENSHOT_TYPES {
+                for (image_type, dir_name) in selected(SCREENSHOT_TYPES, only) {
                     let ss_dir = images_dir.join(dir_name);
                     if !ss_dir.exists() {
                         continue;
                     }
+                    let set_key = format!("{internal_locale}/{dir_name}");
+                    if checkpoint.is_done(&set_key) {
+                        eprintln!("  Skipping {} (already uploaded)", image_type);
+                        skipped.push(set_key);
+                        locale_updated = true;
+                        continue;
+                    }
+
+                    // Get sorted list of images
+                    let images = ordered_images(&ss_dir)?;
+                    if images.len() > MAX_SCREENSHOTS {
+                        eprintln!(
+                            "  Warning: {} has {} images; only the first {} are uploaded",
+                            image_type,
+                            images.len(),
+                            MAX_SCREENSHOTS
+                        );
+                    }
+
+                    // Keep the store's set when any image would be rejected
+                    let problems: Vec<Value> = images
+                        .iter()
+                        .take(MAX_SCREENSHOTS)
+                        .filter_map(|img_path| {
+                            let filename = img_path.file_name()?.to_string_lossy();
+                            let reason = google_upload_problem(image_type, img_path)?;
+                            eprintln!("  Invalid {dir_name}/{filename}: {reason}");
+                            Some(json!({
+                                "locale": internal_locale,
+                                "file": format!("{dir_name}/{filename}"),
+                                "reason": reason,
+                            }))
+                        })
+                        .collect();
+                    if !problems.is_empty() {
+                        invalid.extend(problems);
+                        continue;
+                    }
 
                     // Delete existing screenshots of this type
                     match client
@@ -488,32 +761,14 @@ async fn handle_push(
                         }
                     }
 
-                    // Get sorted list of images
-                    let mut images: Vec<PathBuf> = Vec::new();
-                    let mut img_entries = fs::read_dir(&ss_dir).await?;
-                    while let Some(img_entry) = img_entries.next_entry().await? {
-                        let img_path = img_entry.path();
-                        if img_path
-                            .extension()
-                            .map(|e| {
-                                let e_str = e.to_string_lossy().to_lowercase();
-                                e_str == "png" || e_str == "jpg" || e_str == "jpeg"
-                            })
-                            .unwrap_or(false)
-                        {
-                            images.push(img_path);
-                        }
-                    }
-                    images.sort();
-
                     eprintln!(
                         "  Found {} images to upload for {}",
                         images.len(),
                         image_type
                     );
 
-                    // Upload images (max 8 per type)
-                    for (idx, img_path) in images.iter().take(8).enumerate() {
+                    let mut set_failed = false;
+                    for (idx, img_path) in images.iter().take(MAX_SCREENSHOTS).enumerate() {
                         let filename = img_path.file_name().unwrap_or_default().to_string_lossy();
                         match client
                             .upload_image(
@@ -533,21 +788,48 @@ async fn handle_push(
                                     dir_name,
                                     filename,
                                     idx + 1,
-                                    images.len().min(8)
+                                    images.len().min(MAX_SCREENSHOTS)
+                                );
+                                stream::emit(
+                                    "screenshot",
+                                    json!({
+                                        "locale": internal_locale,
+                                        "file": format!("{dir_name}/{filename}"),
+                                        "status": "uploaded",
+                                    }),
                                 );
                             }
                             Err(e) => {
+                                set_failed = true;
                                 eprintln!(
                                     "  Failed to upload {} (type: {}): {}",
                                     filename, image_type, e
                                 );
+                                stream::emit(
+                                    "screenshot",
+                                    json!({
+                                        "locale": internal_locale,
+                                        "file": format!("{dir_name}/{filename}"),
+                                        "status": "failed",
+                                        "error": e.to_string(),
+                                    }),
+                                );
                             }
                         }
                     }
+                    if !set_failed {
+                        checkpoint.mark_done(set_key);
+                    }
                 }
 
                 // Upload graphics (feature graphic, icon, etc.)
-                for (image_type, file_name) in GRAPHIC_TYPES {
+                for (image_type, file_name) in selected(GRAPHIC_TYPES, only) {
+                    let set_key = format!("{internal_locale}/{file_name}");
+                    if checkpoint.is_done(&set_key) {
+                        skipped.push(set_key);
+                        locale_updated = true;
+                        continue;
+                    }
                     // Check for various extensions
                     let possible_paths = [
                         images_dir.join(format!("{}.png", file_name)),
@@ -557,6 +839,15 @@ async fn handle_push(
 
                     for img_path in &possible_paths {
                         if img_path.exists() {
+                            if let Some(reason) = google_upload_problem(image_type, img_path) {
+                                eprintln!("  Invalid {}: {}", file_name, reason);
+                                invalid.push(json!({
+                                    "locale": internal_locale,
+                                    "file": file_name,
+                                    "reason": reason,
+                                }));
+                                break;
+                            }
                             // Delete existing
                             let _ = client
                                 .delete_path(&format!(
@@ -578,10 +869,24 @@ async fn handle_push(
                                 Ok(_) => {
                                     screenshots_uploaded += 1;
                                     locale_updated = true;
+                                    checkpoint.mark_done(set_key.clone());
                                     eprintln!("  Uploaded: {}", file_name);
+                                    stream::emit(
+                                        "screenshot",
+                                        json!({ "locale": internal_locale, "file": file_name, "status": "uploaded" }),
+                                    );
                                 }
                                 Err(e) => {
                                     eprintln!("  Failed to upload {}: {}", file_name, e);
+                                    stream::emit(
+                                        "screenshot",
+                                        json!({
+                                            "locale": internal_locale,
+                                            "file": file_name,
+                                            "status": "failed",
+                                            "error": e.to_string(),
+                                        }),
+                                    );
                                 }
                             }
                             break;
@@ -592,6 +897,10 @@ async fn handle_push(
         }
 
         if locale_updated {
+            stream::emit(
+                "locale",
+                json!({ "locale": internal_locale, "status": "pushed" }),
+            );
             locales_pushed.push(internal_locale.clone());
         }
         eprintln!("Locale Session SUCCESS: {}", internal_locale);
@@ -601,24 +910,56 @@ async fn handle_push(
         return Err("No locales found to push in metadata_dir".into());
     }
 
+    // Nothing differs from the store: leave the app untouched instead of committing
+    if changes == 0 && screenshots_uploaded == 0 && skipped.is_empty() {
+        eprintln!("Everything matches the store; discarding the edit.");
+        client.discard_edit(package_name, edit_id).await;
+        checkpoint.finish();
+        let mut result = json!({
+            "success": invalid.is_empty(),
+            "package_name": package_name,
+            "locales_pushed": locales_pushed,
+            "screenshots_uploaded": 0,
+            "committed": false,
+            "fields": field_changes,
+        });
+        if !invalid.is_empty() {
+            result["invalid"] = json!(invalid);
+        }
+        return Ok(result);
+    }
+
+    eprintln!("Validating edit...");
+    client.validate_edit(package_name, edit_id).await?;
+    stream::emit("step", json!({ "step": "validated", "edit_id": edit_id }));
+
     // Commit the edit
-    // Use changesNotSentForReview=true to allow commits when managed publishing is enabled
-    // or when the app is in a state that doesn't allow automatic review submission
+    // Defaults to changesNotSentForReview=true to allow commits when managed publishing is
+    // enabled or when the app is in a state that doesn't allow automatic review submission
     eprintln!("Committing changes...");
     client
-        .post(
-            &format!("/{package_name}/edits/{edit_id}:commit?changesNotSentForReview=true"),
-            &json!({}),
-        )
+        .commit_edit_with(package_name, edit_id, false)
         .await?;
 
     eprintln!("COMMIT SUCCESSFUL.");
-    Ok(json!({
-        "success": true,
+    stream::emit("step", json!({ "step": "committed", "edit_id": edit_id }));
+    checkpoint.finish();
+    let mut result = json!({
+        "success": invalid.is_empty(),
         "package_name": package_name,
         "locales_pushed": locales_pushed,
         "screenshots_uploaded": screenshots_uploaded
-    }))
+    });
+    if !field_changes.is_empty() {
+        result["fields"] = Value::Object(field_changes);
+    }
+    if !skipped.is_empty() {
+        result["skipped"] = json!(skipped);
+    }
+    if !invalid.is_empty() {
+        result["invalid"] = json!(invalid);
+    }
+    Ok(result)
 }
 
 async fn read_file_if_exists(path: &PathBuf) -> Option<String> {
@@ -3,27 +3,111 @@ use serde_json::{json, Value};
 
 use crate::api::google_client::GoogleClient;
 
+/// Domains of personal Google accounts, which cannot be Google Groups.
+const PERSONAL_DOMAINS: &[&str] = &["gmail.com", "googlemail.com"];
+
 #[derive(Subcommand)]
 pub enum TestersCommand {
-    /// List testers for a track
+    /// List the Google Groups testing a track
     List {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Track name
         #[arg(long)]
         track: String,
     },
-    /// Add tester to a track
+    /// Add a Google Group to a track's testers
     Add {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Track name
         #[arg(long)]
         track: String,
-        /// Tester email
+        /// Google Group address
         #[arg(long)]
         email: String,
     },
+    /// Remove a Google Group from a track's testers
+    Remove {
+        /// Package name
+        package_name: Option<String>,
+        /// Track name
+        #[arg(long)]
+        track: String,
+        /// Google Group address
+        #[arg(long)]
+        email: String,
+    },
+    /// Replace a track's testers with the given Google Groups
+    ///
+    /// Examples:
+    ///   storeops google testers set com.example.app --track beta --email qa@example.com --email beta@example.com
+    Set {
+        /// Package name
+        package_name: Option<String>,
+        /// Track name
+        #[arg(long)]
+        track: String,
+        /// Google Group address (repeatable)
+        #[arg(long, required = true)]
+        email: Vec<String>,
+    },
+}
+
+/// Normalize a tester address, rejecting anything that cannot be a Google Group.
+fn group_address(email: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let email = email.trim().to_lowercase();
+    let (local, domain) = email
+        .split_once('@')
+        .ok_or_else(|| format!("'{email}' is not an email address"))?;
+    if local.is_empty() || !domain.contains('.') || domain.contains('@') {
+        return Err(format!("'{email}' is not an email address").into());
+    }
+    if PERSONAL_DOMAINS.contains(&domain) {
+        return Err(format!(
+            "'{email}' is a personal account; track testers must be Google Groups"
+        )
+        .into());
+    }
+    Ok(email)
+}
+
+fn groups(testers: &Value) -> Vec<String> {
+    testers["googleGroups"]
+        .as_array()
+        .map(|a| {
+            a.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Apply `change` to a track's Google Groups and commit the edit.
+async fn update(
+    client: &GoogleClient,
+    package_name: &str,
+    track: &str,
+    change: impl FnOnce(Vec<String>) -> Result<Vec<String>, Box<dyn std::error::Error>>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let edit_id = &client.open_edit(package_name).await?;
+    let path = format!("/{package_name}/edits/{edit_id}/testers/{track}");
+    let result = async {
+        let current: Value = client.get(&path, &[]).await?;
+        let updated = change(groups(&current))?;
+        client.put(&path, &json!({ "googleGroups": updated })).await
+    }
+    .await;
+    match result {
+        Ok(result) => {
+            client.commit_edit(package_name, edit_id).await?;
+            Ok(result)
+        }
+        Err(e) => {
+            client.discard_edit(package_name, edit_id).await;
+            Err(e)
+        }
+    }
 }
 
 pub async fn handle(
@@ -35,61 +119,83 @@ pub async fn handle(
             package_name,
             track,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
-            let testers: Value = client
-                .get(
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
+            let testers = client
+                .get::<Value>(
                     &format!("/{package_name}/edits/{edit_id}/testers/{track}"),
                     &[],
                 )
-                .await?;
-            let _ = client
-                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
                 .await;
-            Ok(testers)
+            client.discard_edit(package_name, edit_id).await;
+            testers
         }
         TestersCommand::Add {
             package_name,
             track,
             email,
         } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
-            let current: Value = client
-                .get(
-                    &format!("/{package_name}/edits/{edit_id}/testers/{track}"),
-                    &[],
-                )
-                .await
-                .unwrap_or(json!({"googleGroups": [], "googlePlusCommunities": []}));
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let email = group_address(email)?;
+            update(client, package_name, track, |mut emails| {
+                if !emails.iter().any(|e| e.eq_ignore_ascii_case(&email)) {
+                    emails.push(email);
+                }
+                Ok(emails)
+            })
+            .await
+        }
+        TestersCommand::Remove {
+            package_name,
+            track,
+            email,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let email = email.trim();
+            update(client, package_name, track, |emails| {
+                let before = emails.len();
+                let kept: Vec<String> = emails
+                    .into_iter()
+                    .filter(|e| !e.eq_ignore_ascii_case(email))
+                    .collect();
+                if kept.len() == before {
+                    return Err(format!("{email} is not a tester of track {track}").into());
+                }
+                Ok(kept)
+            })
+            .await
+        }
+        TestersCommand::Set {
+            package_name,
+            track,
+            email,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let mut emails: Vec<String> = Vec::new();
+            for e in email {
+                let e = group_address(e)?;
+                if !emails.contains(&e) {
+                    emails.push(e);
+                }
+            }
+            update(client, package_name, track, |_| Ok(emails)).await
+        }
+    }
+}
 
-            let mut emails: Vec<String> = current["googleGroups"]
-                .as_array()
-                .map(|a| {
-                    a.iter()
-                        .filter_map(|v| v.as_str().map(String::from))
-                        .collect()
-                })
-                .unwrap_or_default();
-            emails.push(email.clone());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            let result = client
-                .put(
-                    &format!("/{package_name}/edits/{edit_id}/testers/{track}"),
-                    &json!({ "googleGroups": emails }),
-                )
-                .await?;
-            let _ = client
-                .post(
-                    &format!("/{package_name}/edits/{edit_id}:commit"),
-                    &json!({}),
-                )
-                .await?;
-            Ok(result)
-        }
+    #[test]
+    fn accepts_only_group_addresses() {
+        assert_eq!(
+            group_address(" QA-Team@Example.com ").unwrap(),
+            "qa-team@example.com"
+        );
+        assert!(group_address("someone@gmail.com").is_err());
+        assert!(group_address("not-an-email").is_err());
+        assert!(group_address("@example.com").is_err());
+        assert!(group_address("qa@localhost").is_err());
     }
 }
@@ -1,5 +1,7 @@
+use chrono::{Datelike, Duration, Utc};
 use clap::Subcommand;
-use serde_json::Value;
+use serde_json::{json, Value};
+use std::path::PathBuf;
 
 use crate::api::google_client::GoogleClient;
 
@@ -8,49 +10,148 @@ pub enum TracksCommand {
     /// List all tracks
     List {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
     },
     /// Update a track release
     Update {
         /// Package name
-        package_name: String,
+        package_name: Option<String>,
         /// Track name (internal, alpha, beta, production)
         #[arg(long)]
         track: String,
         /// Release JSON body
+        #[arg(
+            long,
+            required_unless_present = "body_file",
+            conflicts_with = "body_file"
+        )]
+        release: Option<String>,
+        /// File containing the release JSON body
         #[arg(long)]
-        release: String,
+        body_file: Option<PathBuf>,
+    },
+    /// Copy the latest release (version codes and notes) from one track to another
+    ///
+    /// Examples:
+    ///   storeops google tracks promote com.example.app --from internal --to beta
+    ///   storeops google tracks promote com.example.app --from beta --to production --fraction 0.1
+    Promote {
+        /// Package name
+        package_name: Option<String>,
+        /// Track to copy the release from
+        #[arg(long)]
+        from: String,
+        /// Track to release to
+        #[arg(long)]
+        to: String,
+        /// Start a staged rollout to this fraction of users (0 < fraction < 1)
+        #[arg(long)]
+        fraction: Option<f64>,
+    },
+    /// Staged rollout controls
+    Rollout {
+        #[command(subcommand)]
+        command: RolloutCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RolloutCommand {
+    /// Halt the in-progress rollout if Android Vitals crash rate exceeds a threshold
+    ///
+    /// Examples:
+    ///   storeops google tracks rollout guard com.example.app --max-crash-rate 1.5
+    Guard {
+        /// Package name
+        package_name: Option<String>,
+        /// Track name
+        #[arg(long, default_value = "production")]
+        track: String,
+        /// Maximum acceptable crash rate, in percent of users
+        #[arg(long)]
+        max_crash_rate: f64,
+        /// Number of days of vitals to consider
+        #[arg(long, default_value = "3")]
+        days: i64,
+        /// Write an incident report to this file when the rollout is halted
+        #[arg(long)]
+        report_file: Option<PathBuf>,
+        /// Report what would happen without halting the rollout
+        #[arg(long)]
+        dry_run: bool,
     },
 }
 
+/// Return the first staged (`inProgress`) release of a track, if any.
+fn in_progress_release(track: &Value) -> Option<&Value> {
+    track["releases"]
+        .as_array()?
+        .iter()
+        .find(|r| r["status"].as_str() == Some("inProgress"))
+}
+
+/// The release to put on the destination track when promoting `source`.
+fn promoted_release(
+    source: &Value,
+    fraction: Option<f64>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut release = json!({ "versionCodes": source["versionCodes"] });
+    for key in ["name", "releaseNotes"] {
+        if !source[key].is_null() {
+            release[key] = source[key].clone();
+        }
+    }
+    match fraction {
+        Some(f) if f > 0.0 && f < 1.0 => {
+            release["status"] = json!("inProgress");
+            release["userFraction"] = json!(f);
+        }
+        Some(f) => return Err(format!("--fraction must be between 0 and 1, got {f}").into()),
+        None => release["status"] = json!("completed"),
+    }
+    Ok(release)
+}
+
+/// Highest daily crash rate (as a percentage) across the returned metric rows.
+fn peak_crash_rate(metrics: &Value) -> Option<f64> {
+    metrics["rows"]
+        .as_array()?
+        .iter()
+        .flat_map(|row| row["metrics"].as_array().into_iter().flatten())
+        .filter(|m| m["metric"].as_str() == Some("crashRate"))
+        .filter_map(|m| m["decimalValue"]["value"].as_str()?.parse::<f64>().ok())
+        .map(|rate| rate * 100.0)
+        .reduce(f64::max)
+}
+
 pub async fn handle(
     cmd: &TracksCommand,
     client: &GoogleClient,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         TracksCommand::List { package_name } => {
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &serde_json::json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
             let tracks: Value = client
                 .get(&format!("/{package_name}/edits/{edit_id}/tracks"), &[])
                 .await?;
-            let _ = client
-                .delete_path(&format!("/{package_name}/edits/{edit_id}"))
-                .await;
+            client.discard_edit(package_name, edit_id).await;
             Ok(tracks)
         }
         TracksCommand::Update {
             package_name,
             track,
             release,
+            body_file,
         } => {
-            let release_json: Value = serde_json::from_str(release)?;
-            let edit: Value = client
-                .post(&format!("/{package_name}/edits"), &serde_json::json!({}))
-                .await?;
-            let edit_id = edit["id"].as_str().ok_or("no edit id")?;
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let release = match (release, body_file) {
+                (Some(release), _) => release.clone(),
+                (None, Some(path)) => std::fs::read_to_string(path)?,
+                (None, None) => return Err("pass --release or --body-file".into()),
+            };
+            let release_json: Value = serde_json::from_str(&release)?;
+            let edit_id = &client.open_edit(package_name).await?;
             let result = client
                 .put(
                     &format!("/{package_name}/edits/{edit_id}/tracks/{track}"),
@@ -60,13 +161,267 @@ pub async fn handle(
                     }),
                 )
                 .await?;
-            let _ = client
-                .post(
-                    &format!("/{package_name}/edits/{edit_id}:commit"),
-                    &serde_json::json!({}),
+            client.validate_edit(package_name, edit_id).await?;
+            client.commit_edit(package_name, edit_id).await?;
+            Ok(result)
+        }
+        TracksCommand::Promote {
+            package_name,
+            from,
+            to,
+            fraction,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
+            let source: Value = match client
+                .get(
+                    &format!("/{package_name}/edits/{edit_id}/tracks/{from}"),
+                    &[],
+                )
+                .await
+            {
+                Ok(track) => track,
+                Err(e) => {
+                    client.discard_edit(package_name, edit_id).await;
+                    return Err(e);
+                }
+            };
+            let release = match super::apps::latest_release(&source)
+                .ok_or_else(|| format!("track {from} has no release to promote").into())
+                .and_then(|r| promoted_release(r, *fraction))
+            {
+                Ok(release) => release,
+                Err(e) => {
+                    client.discard_edit(package_name, edit_id).await;
+                    return Err(e);
+                }
+            };
+            eprintln!(
+                "Promoting version codes {} from {from} to {to}",
+                release["versionCodes"]
+            );
+            client
+                .put(
+                    &format!("/{package_name}/edits/{edit_id}/tracks/{to}"),
+                    &json!({ "track": to, "releases": [release] }),
                 )
                 .await?;
-            Ok(result)
+            client.validate_edit(package_name, edit_id).await?;
+            client.commit_edit(package_name, edit_id).await?;
+            Ok(json!({
+                "package_name": package_name,
+                "from": from,
+                "to": to,
+                "release": release,
+            }))
+        }
+        TracksCommand::Rollout { command } => handle_rollout(command, client).await,
+    }
+}
+
+async fn handle_rollout(
+    cmd: &RolloutCommand,
+    client: &GoogleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        RolloutCommand::Guard {
+            package_name,
+            track,
+            max_crash_rate,
+            days,
+            report_file,
+            dry_run,
+        } => {
+            let package_name = &client.package_name(package_name.as_deref())?;
+            let edit_id = &client.open_edit(package_name).await?;
+            // Read, decide and stage the halt; the edit is discarded on any error here
+            let result = async {
+                let track_info: Value = client
+                    .get(
+                        &format!("/{package_name}/edits/{edit_id}/tracks/{track}"),
+                        &[],
+                    )
+                    .await?;
+
+                let Some(release) = in_progress_release(&track_info) else {
+                    client.discard_edit(package_name, edit_id).await;
+                    return Ok((
+                        json!({
+                            "package_name": package_name,
+                            "track": track,
+                            "status": "no_rollout",
+                            "message": "no in-progress staged rollout on this track",
+                        }),
+                        false,
+                    ));
+                };
+                let version_codes: Vec<String> = release["versionCodes"]
+                    .as_array()
+                    .map(|a| {
+                        a.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let end = Utc::now().date_naive();
+                let start = end - Duration::days(*days);
+                let filter = version_codes
+                    .iter()
+                    .map(|vc| format!("versionCode = {vc}"))
+                    .collect::<Vec<_>>()
+                    .join(" OR ");
+                let query = json!({
+                    "timelineSpec": {
+                        "aggregationPeriod": "DAILY",
+                        "startTime": {
+                            "year": start.year(), "month": start.month(), "day": start.day(),
+                            "timeZone": { "id": "America/Los_Angeles" }
+                        },
+                        "endTime": {
+                            "year": end.year(), "month": end.month(), "day": end.day(),
+                            "timeZone": { "id": "America/Los_Angeles" }
+                        }
+                    },
+                    "dimensions": ["versionCode"],
+                    "metrics": ["crashRate"],
+                    "filter": filter,
+                });
+                let metrics = client
+                    .query_metric_set(package_name, "crashRateMetricSet", &query)
+                    .await?;
+                let crash_rate = peak_crash_rate(&metrics);
+                let exceeded = crash_rate.is_some_and(|r| r > *max_crash_rate);
+
+                if !exceeded || *dry_run {
+                    client.discard_edit(package_name, edit_id).await;
+                    return Ok((
+                        json!({
+                            "package_name": package_name,
+                            "track": track,
+                            "status": if exceeded { "would_halt" } else { "healthy" },
+                            "version_codes": version_codes,
+                            "crash_rate": crash_rate,
+                            "max_crash_rate": max_crash_rate,
+                        }),
+                        false,
+                    ));
+                }
+
+                eprintln!(
+                    "Crash rate {:.2}% exceeds {:.2}%, halting rollout",
+                    crash_rate.unwrap_or_default(),
+                    max_crash_rate
+                );
+                let mut halted = track_info.clone();
+                if let Some(releases) = halted["releases"].as_array_mut() {
+                    for r in releases
+                        .iter_mut()
+                        .filter(|r| r["status"].as_str() == Some("inProgress"))
+                    {
+                        r["status"] = json!("halted");
+                    }
+                }
+                client
+                    .put(
+                        &format!("/{package_name}/edits/{edit_id}/tracks/{track}"),
+                        &halted,
+                    )
+                    .await?;
+
+                let report = json!({
+                    "package_name": package_name,
+                    "track": track,
+                    "status": "halted",
+                    "halted_at": Utc::now().to_rfc3339(),
+                    "version_codes": version_codes,
+                    "user_fraction": release["userFraction"],
+                    "crash_rate": crash_rate,
+                    "max_crash_rate": max_crash_rate,
+                    "window_days": days,
+                    "vitals": metrics["rows"],
+                });
+                Ok::<_, Box<dyn std::error::Error>>((report, true))
+            }
+            .await;
+            let (mut report, halt) = match result {
+                Ok(r) => r,
+                Err(e) => {
+                    client.discard_edit(package_name, edit_id).await;
+                    return Err(e);
+                }
+            };
+            if !halt {
+                return Ok(report);
+            }
+            if let Err(e) = client.commit_edit(package_name, edit_id).await {
+                client.discard_edit(package_name, edit_id).await;
+                return Err(e);
+            }
+
+            // The rollout is halted now; a report that cannot be written is only a warning
+            if let Some(path) = report_file {
+                let written = serde_json::to_string_pretty(&report)
+                    .map_err(std::io::Error::from)
+                    .and_then(|text| std::fs::write(path, text));
+                match written {
+                    Ok(()) => eprintln!("Incident report written to {}", path.display()),
+                    Err(e) => {
+                        eprintln!(
+                            "Warning: cannot write incident report {}: {e}",
+                            path.display()
+                        );
+                        report["report_error"] = json!(e.to_string());
+                    }
+                }
+            }
+            Ok(report)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_in_progress_release() {
+        let track = json!({"releases": [
+            {"status": "completed", "versionCodes": ["41"]},
+            {"status": "inProgress", "versionCodes": ["42"], "userFraction": 0.1}
+        ]});
+        let release = in_progress_release(&track).unwrap();
+        assert_eq!(release["versionCodes"][0], "42");
+        assert!(in_progress_release(&json!({"releases": []})).is_none());
+    }
+
+    #[test]
+    fn promoted_release_keeps_codes_and_notes() {
+        let source = json!({
+            "name": "2.0.0",
+            "status": "completed",
+            "versionCodes": ["42"],
+            "releaseNotes": [{"language": "en-US", "text": "Fixes"}]
+        });
+        let full = promoted_release(&source, None).unwrap();
+        assert_eq!(full["status"], "completed");
+        assert_eq!(full["versionCodes"][0], "42");
+        assert_eq!(full["releaseNotes"][0]["text"], "Fixes");
+
+        let staged = promoted_release(&source, Some(0.1)).unwrap();
+        assert_eq!(staged["status"], "inProgress");
+        assert_eq!(staged["userFraction"], 0.1);
+        assert!(promoted_release(&source, Some(1.5)).is_err());
+    }
+
+    #[test]
+    fn peak_crash_rate_is_a_percentage() {
+        let metrics = json!({"rows": [
+            {"metrics": [{"metric": "crashRate", "decimalValue": {"value": "0.004"}}]},
+            {"metrics": [{"metric": "crashRate", "decimalValue": {"value": "0.021"}}]}
+        ]});
+        let peak = peak_crash_rate(&metrics).unwrap();
+        assert!((peak - 2.1).abs() < 1e-9);
+        assert!(peak_crash_rate(&json!({"rows": []})).is_none());
+    }
+}
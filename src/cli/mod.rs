@@ -1,5 +1,27 @@
+pub mod api_request;
 pub mod apple;
+pub mod batch;
+pub mod catalog;
+pub mod checkpoint;
+pub mod config;
+pub mod doctor;
+pub mod download;
+pub mod fanout;
 pub mod google;
+pub mod pull_manifest;
+pub mod release_notes;
+pub mod revenue;
+pub mod review_export;
+pub mod review_triage;
+pub mod schema;
+pub mod screenshots;
+pub mod search;
+pub mod serve;
+pub mod status;
+pub mod sync;
+pub mod template;
+pub mod version;
+pub mod warehouse;
 
 use clap::{Parser, Subcommand, ValueEnum};
 
@@ -17,14 +39,38 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub json: bool,
 
+    /// Output format (--json is shorthand for --output json)
+    #[arg(long, global = true, value_enum)]
+    pub output: Option<crate::output::OutputFormat>,
+
     /// Pretty-print JSON output (only with --json)
     #[arg(long, global = true)]
     pub pretty: bool,
 
+    /// Show raw enum values in table output instead of labels
+    #[arg(long, global = true)]
+    pub raw: bool,
+
+    /// Flatten JSON:API resources in JSON output (merge attributes, drop links/relationships)
+    #[arg(long, global = true)]
+    pub flatten: bool,
+
+    /// Stream progress of sync and batch operations as JSON lines on stdout
+    #[arg(long, global = true)]
+    pub stream: bool,
+
     /// Auth profile to use
     #[arg(long, global = true)]
     pub profile: Option<String>,
 
+    /// Run a read-only command against every profile of the command's store
+    #[arg(long, global = true, conflicts_with_all = ["profile", "profiles"])]
+    pub all_profiles: bool,
+
+    /// Run a read-only command against these profiles (comma-separated)
+    #[arg(long, global = true, value_delimiter = ',', conflicts_with = "profile")]
+    pub profiles: Vec<String>,
+
     /// Pagination limit
     #[arg(long, global = true)]
     pub limit: Option<u32>,
@@ -44,6 +90,45 @@ pub struct Cli {
     /// Enable verbose/debug logging
     #[arg(long, global = true)]
     pub verbose: bool,
+
+    /// Record HTTP traffic (secrets redacted) to this session file
+    #[arg(long, global = true, value_name = "FILE", conflicts_with = "replay")]
+    pub record: Option<std::path::PathBuf>,
+
+    /// Answer HTTP requests from a recorded session file instead of the network
+    #[arg(long, global = true, value_name = "FILE")]
+    pub replay: Option<std::path::PathBuf>,
+}
+
+impl Cli {
+    /// Fill in `--output` and `--limit` from the selected profile's defaults when omitted.
+    pub fn apply_profile_defaults(&mut self) {
+        let config = crate::config::Config::load().unwrap_or_default();
+        let defaults = config.profile_defaults(self.profile.as_deref());
+        if self.limit.is_none() {
+            self.limit = defaults.default_limit;
+        }
+        if self.output.is_none() {
+            self.output = defaults
+                .default_output
+                .as_deref()
+                .and_then(|o| crate::output::OutputFormat::from_str(o, true).ok());
+        }
+    }
+
+    /// Effective output format: `--json`, then `--output`, then JSON when streaming,
+    /// then table.
+    pub fn output_format(&self) -> crate::output::OutputFormat {
+        if self.json {
+            crate::output::OutputFormat::Json
+        } else if let Some(format) = self.output {
+            format
+        } else if self.stream {
+            crate::output::OutputFormat::Json
+        } else {
+            crate::output::OutputFormat::Table
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -60,11 +145,246 @@ pub enum Command {
     },
     /// Google Play Store commands
     Google {
+        #[command(flatten)]
+        edit: google::EditArgs,
         #[command(subcommand)]
         command: google::GoogleCommand,
     },
+    /// Version helpers shared across stores
+    Version {
+        #[command(subcommand)]
+        command: version::VersionCommand,
+    },
+    /// Validate and fix screenshots before uploading
+    Screenshots {
+        #[command(subcommand)]
+        command: screenshots::ScreenshotsCommand,
+    },
+    /// Release status of an app in both stores at once
+    ///
+    /// Examples:
+    ///   storeops status --app com.example.app --package com.example.app
+    ///   storeops status --apple-profile work-apple --google-profile work-google
+    Status {
+        /// Apple app ID or bundle ID (defaults to the Apple profile's default_app_id)
+        #[arg(long)]
+        app: Option<String>,
+        /// Google package name (defaults to the Google profile's default_package)
+        #[arg(long)]
+        package: Option<String>,
+        /// Profile for the Apple half (defaults to --profile or the active profile)
+        #[arg(long)]
+        apple_profile: Option<String>,
+        /// Profile for the Google half (defaults to --profile or the active profile)
+        #[arg(long)]
+        google_profile: Option<String>,
+    },
+    /// Run commands from a file, one per line, printing one JSON result per line
+    ///
+    /// Each line is a storeops command (the leading `storeops` is optional); blank
+    /// lines and `#` comments are skipped. All commands share one connection pool and one
+    /// token per store, which is much faster than invoking the binary in a loop.
+    /// Exits non-zero when any command fails.
+    ///
+    /// Examples:
+    ///   storeops batch --file nightly.txt
+    ///   printf 'apple apps list\ngoogle apps info com.example.app\n' | storeops batch --file -
+    Batch {
+        /// File of commands, or `-` for stdin
+        #[arg(long)]
+        file: std::path::PathBuf,
+        /// Stop at the first failing command
+        #[arg(long)]
+        stop_on_error: bool,
+    },
+    /// Serve the command surface as a local HTTP/JSON API
+    ///
+    /// `GET /apple/apps/list?limit=5` runs `apple apps list --limit 5`: path segments
+    /// are command words, parameters are flags (`arg=` for positionals), and a POST
+    /// JSON object body adds more flags. GET only runs read-only commands. `POST /run`
+    /// takes `{"args": [...]}`; `GET /health` checks the server. Listens on
+    /// 127.0.0.1 unless --host is given. Every request needs the bearer token, which
+    /// is generated and printed when --token is not set; browser requests (with an
+    /// `Origin` header), other `Host`s, and file-path flags are refused.
+    ///
+    /// Examples:
+    ///   storeops serve --port 8080
+    ///   STOREOPS_SERVE_TOKEN=s3cret storeops serve --host 0.0.0.0 --port 8080
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Bearer token every request must send (default: $STOREOPS_SERVE_TOKEN, else generated)
+        #[arg(long)]
+        token: Option<String>,
+    },
+    /// Print the JSON Schema for a command's output
+    ///
+    /// Lists the commands with a published schema when none is given.
+    ///
+    /// Examples:
+    ///   storeops schema
+    ///   storeops schema apple reviews list
+    ///   storeops schema status
+    Schema {
+        /// Command words, e.g. `apple apps list`
+        command: Vec<String>,
+    },
+    /// Find apps by name across the Apple and Google profiles
+    ///
+    /// Google Play cannot list an account's apps, so only the default packages of
+    /// configured Google profiles (and any --package) are searched there.
+    ///
+    /// Examples:
+    ///   storeops search photo
+    ///   storeops search photo --package com.example.photo.lite
+    Search {
+        /// Name, bundle ID or package name substring
+        query: String,
+        /// Extra Google package to check (repeatable)
+        #[arg(long)]
+        package: Vec<String>,
+        /// Profile for the Apple half (defaults to --profile or the active profile)
+        #[arg(long)]
+        apple_profile: Option<String>,
+        /// Profile for the Google half (defaults to --profile or the active profile)
+        #[arg(long)]
+        google_profile: Option<String>,
+    },
+    /// Cross-store review workflows
+    Reviews {
+        #[command(subcommand)]
+        command: review_triage::ReviewsCommand,
+    },
+    /// Release notes for both stores from one template
+    ReleaseNotes {
+        #[command(subcommand)]
+        command: release_notes::ReleaseNotesCommand,
+    },
+    /// In-app products and subscriptions of both stores as one YAML catalog
+    Catalog {
+        #[command(subcommand)]
+        command: catalog::CatalogCommand,
+    },
+    /// Metadata directory helpers shared across stores
+    Sync {
+        #[command(subcommand)]
+        command: sync::SyncCommand,
+    },
+    /// Revenue of a month across both stores, per app and country, in one currency
+    ///
+    /// Apple figures are developer proceeds from the monthly sales summary; Google
+    /// figures come from the Play Console earnings (or estimated sales) reports in
+    /// the developer's Cloud Storage bucket. Exchange rates come from --rates, the
+    /// `[revenue]` config section's rates_url, or its fixed `[revenue.rates]` table.
+    ///
+    /// Examples:
+    ///   storeops revenue --month 2024-09 --vendor-number 85012345 --bucket pubsite_prod_rev_0123
+    ///   storeops revenue --month 2024-09 --currency EUR --rates rates.json
+    Revenue(revenue::RevenueArgs),
+    /// Local SQLite warehouse of App Store sales and finance reports
+    Warehouse {
+        #[command(subcommand)]
+        command: warehouse::WarehouseCommand,
+    },
+    /// Inspect and validate the config file
+    Config {
+        #[command(subcommand)]
+        command: config::ConfigCommand,
+    },
+    /// Diagnose the setup: config, credential files, API reachability, clock, updates
+    ///
+    /// Prints a pass/warn/fail report and exits non-zero if any check fails. A clock
+    /// more than a minute off makes App Store Connect reject every token.
+    ///
+    /// Examples:
+    ///   storeops doctor
+    ///   storeops doctor --offline
+    Doctor(doctor::DoctorArgs),
     /// Update storeops to the latest release
-    Update,
+    ///
+    /// Downloads the release archive for this platform, verifies it against the
+    /// release's published SHA-256 checksums, and replaces the running binary.
+    ///
+    /// Examples:
+    ///   storeops update
+    ///   storeops update --check
+    ///   storeops update --channel prerelease
+    ///   storeops update --version 0.4.2
+    Update {
+        /// Release channel to follow
+        #[arg(long, value_enum, default_value_t = UpdateChannel::Stable)]
+        channel: UpdateChannel,
+        /// Install this exact release (X.Y.Z), even if it is older
+        #[arg(long, value_name = "X.Y.Z", conflicts_with = "channel")]
+        version: Option<String>,
+        /// Only report whether an update is available
+        #[arg(long)]
+        check: bool,
+        /// Replace the binary even if Homebrew or cargo manages it
+        #[arg(long)]
+        force_binary: bool,
+    },
+}
+
+/// Run the Apple and Google halves of a cross-store command concurrently.
+///
+/// Skipped halves (`None`) are left out of the result. A failing half is reported as
+/// `{"error": ...}` under its store and listed in `failed_stores`, so one store's
+/// outage never hides the other's data.
+pub async fn join_stores<A, G>(apple: Option<A>, google: Option<G>) -> serde_json::Value
+where
+    A: std::future::Future<Output = Result<serde_json::Value, Box<dyn std::error::Error>>>,
+    G: std::future::Future<Output = Result<serde_json::Value, Box<dyn std::error::Error>>>,
+{
+    let (apple, google) = tokio::join!(
+        async {
+            match apple {
+                Some(f) => Some(f.await),
+                None => None,
+            }
+        },
+        async {
+            match google {
+                Some(f) => Some(f.await),
+                None => None,
+            }
+        },
+    );
+
+    let mut out = serde_json::Map::new();
+    let mut failed = Vec::new();
+    for (store, result) in [("apple", apple), ("google", google)] {
+        match result {
+            Some(Ok(value)) => {
+                out.insert(store.to_string(), value);
+            }
+            Some(Err(e)) => {
+                eprintln!("{store}: {e}");
+                out.insert(
+                    store.to_string(),
+                    serde_json::json!({ "error": e.to_string() }),
+                );
+                failed.push(store);
+            }
+            None => {}
+        }
+    }
+    if !failed.is_empty() {
+        out.insert("failed_stores".to_string(), serde_json::json!(failed));
+    }
+    serde_json::Value::Object(out)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UpdateChannel {
+    /// Latest full release
+    Stable,
+    /// Newest release, including pre-releases
+    Prerelease,
 }
 
 #[derive(Subcommand)]
@@ -90,6 +410,33 @@ pub enum AuthCommand {
         #[arg(long)]
         name: Option<String>,
     },
+    /// Replace a profile's Apple API key without locking yourself out
+    ///
+    /// The new key is checked with a live App Store Connect call first; only when it
+    /// works is the profile updated and the old key ID and path archived under
+    /// `retired_keys`, so it can be revoked afterwards. A rejected key leaves the
+    /// config untouched.
+    ///
+    /// Examples:
+    ///   storeops auth rotate --store apple --new-key-id NEWKEY1234 --new-key-path AuthKey_NEWKEY1234.p8
+    ///   storeops auth rotate --store apple --name work --new-key-id NEWKEY1234 --new-key-path ~/keys/AuthKey_NEWKEY1234.p8
+    Rotate {
+        /// Store whose key to rotate (only `apple` is supported)
+        #[arg(long)]
+        store: StoreArg,
+        /// ID of the new API key
+        #[arg(long)]
+        new_key_id: String,
+        /// Path to the new .p8 key file
+        #[arg(long)]
+        new_key_path: String,
+        /// Issuer ID, if it changes with the new key
+        #[arg(long)]
+        issuer_id: Option<String>,
+        /// Profile to update (default: the active profile)
+        #[arg(long)]
+        name: Option<String>,
+    },
     /// Switch active profile
     Switch {
         /// Profile name to activate
@@ -106,3 +453,32 @@ pub enum StoreArg {
     Apple,
     Google,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[tokio::test]
+    async fn join_stores_reports_partial_failures() {
+        let apple = async { Ok(serde_json::json!({"version": "1.2.0"})) };
+        let google = async { Err::<serde_json::Value, _>("service unavailable".into()) };
+        let out = join_stores(Some(apple), Some(google)).await;
+        assert_eq!(out["apple"]["version"], "1.2.0");
+        assert_eq!(out["google"]["error"], "service unavailable");
+        assert_eq!(out["failed_stores"], serde_json::json!(["google"]));
+    }
+
+    #[tokio::test]
+    async fn join_stores_omits_skipped_stores() {
+        let apple = async { Ok(serde_json::json!({})) };
+        let out = join_stores(Some(apple), None::<std::future::Ready<_>>).await;
+        assert!(out.get("google").is_none());
+        assert!(out.get("failed_stores").is_none());
+    }
+}
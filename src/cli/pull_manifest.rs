@@ -0,0 +1,79 @@
+//! ETags remembered between `sync pull` runs.
+//!
+//! Pull stores the ETag of every localization listing and image it fetched in
+//! `<output-dir>/.storeops-pull.json`. The next pull into the same directory sends
+//! them as `If-None-Match`, so unchanged metadata and images come back as
+//! `304 Not Modified` and are not transferred or rewritten again.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = ".storeops-pull.json";
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct PullManifest {
+    /// ETag per resource: a listing name such as `apple:versionLocalizations`, or an
+    /// image path relative to the output directory
+    #[serde(default)]
+    pub etags: BTreeMap<String, String>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl PullManifest {
+    /// Load the manifest of a previous pull, or start empty when `fresh` is set.
+    pub fn open(output_dir: &Path, fresh: bool) -> Self {
+        let path = output_dir.join(FILE_NAME);
+        let previous = (!fresh)
+            .then(|| std::fs::read_to_string(&path).ok())
+            .flatten()
+            .and_then(|s| serde_json::from_str::<PullManifest>(&s).ok())
+            .unwrap_or_default();
+        Self { path, ..previous }
+    }
+
+    pub fn etag(&self, key: &str) -> Option<&str> {
+        self.etags.get(key).map(String::as_str)
+    }
+
+    /// Remember (or, without an ETag, forget) the version of a resource.
+    pub fn set(&mut self, key: &str, etag: Option<String>) {
+        match etag {
+            Some(etag) => self.etags.insert(key.to_string(), etag),
+            None => self.etags.remove(key),
+        };
+    }
+
+    pub fn save(&self) {
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            if let Err(e) = std::fs::write(&self.path, json) {
+                eprintln!("Warning: Could not write pull manifest: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_etags_unless_fresh() {
+        let dir = std::env::temp_dir().join(format!("storeops-pull-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut manifest = PullManifest::open(&dir, false);
+        manifest.set("apple:versionLocalizations", Some("\"abc\"".into()));
+        manifest.set("en-US/screenshots/iphone67/01.png", Some("\"img\"".into()));
+        manifest.save();
+
+        let mut reopened = PullManifest::open(&dir, false);
+        assert_eq!(reopened.etag("apple:versionLocalizations"), Some("\"abc\""));
+        reopened.set("en-US/screenshots/iphone67/01.png", None);
+        assert!(reopened.etag("en-US/screenshots/iphone67/01.png").is_none());
+
+        assert!(PullManifest::open(&dir, true).etags.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,336 @@
+//! Release notes rendered from one template and written to both stores.
+//!
+//! The template (and any per-locale override file) may use `{{version}}`, `{{date}}`,
+//! `{{locale}}`, `{{store}}` and variables passed with `--var key=value`. Apple gets
+//! the notes as `whatsNew` on every localization of the version; Google gets them as
+//! the release notes of the matching track release, in every listing language.
+
+use clap::Subcommand;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::apple::resolve;
+use super::template::{self, render};
+use super::Cli;
+
+const APPLE_MAX_LEN: usize = 4000;
+const GOOGLE_MAX_LEN: usize = 500;
+
+#[derive(Subcommand)]
+pub enum ReleaseNotesCommand {
+    /// Render a release notes template and write it to both stores
+    ///
+    /// Examples:
+    ///   storeops release-notes set --template notes.md --version 1.4.0 --app com.example.app --package com.example.app
+    ///   storeops release-notes set --template notes.md --version 1.4.0 --overrides notes/ --var codename=Aurora --dry-run
+    Set {
+        /// Template file
+        #[arg(long)]
+        template: PathBuf,
+        /// Version the notes are for (App Store version string / Play release name)
+        #[arg(long)]
+        version: String,
+        /// Directory of per-locale templates (`<locale>.md` or `<locale>.txt`)
+        #[arg(long)]
+        overrides: Option<PathBuf>,
+        /// Extra template variable (repeatable)
+        #[arg(long = "var", value_name = "KEY=VALUE")]
+        vars: Vec<String>,
+        /// Apple app ID or bundle ID (defaults to the Apple profile's default_app_id)
+        #[arg(long)]
+        app: Option<String>,
+        /// Google package name (defaults to the Google profile's default_package)
+        #[arg(long)]
+        package: Option<String>,
+        /// Google Play track holding the release
+        #[arg(long, default_value = "production")]
+        track: String,
+        /// Profile for the Apple half (defaults to --profile or the active profile)
+        #[arg(long)]
+        apple_profile: Option<String>,
+        /// Profile for the Google half (defaults to --profile or the active profile)
+        #[arg(long)]
+        google_profile: Option<String>,
+        /// Render and report the notes without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// The template, per-locale overrides and variables shared by both stores.
+struct Notes {
+    template: String,
+    overrides: Option<PathBuf>,
+    vars: BTreeMap<String, String>,
+}
+
+impl Notes {
+    fn load(
+        template: &Path,
+        overrides: Option<&Path>,
+        version: &str,
+        extra: &[String],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut vars = BTreeMap::new();
+        vars.insert("version".to_string(), version.to_string());
+        vars.insert("date".to_string(), template::today());
+        vars.extend(template::parse_vars(extra)?);
+        Ok(Self {
+            template: std::fs::read_to_string(template)?,
+            overrides: overrides.map(Path::to_path_buf),
+            vars,
+        })
+    }
+
+    /// Override file for a locale, trying the full code and then its language.
+    fn override_for(&self, locale: &str) -> Option<String> {
+        let dir = self.overrides.as_ref()?;
+        let language = locale.split('-').next().unwrap_or(locale);
+        [locale, language]
+            .iter()
+            .flat_map(|name| ["md", "txt"].map(|ext| dir.join(format!("{name}.{ext}"))))
+            .find_map(|path| std::fs::read_to_string(path).ok())
+    }
+
+    fn for_locale(&self, locale: &str, store: &str, max_len: usize) -> Result<String, String> {
+        let mut vars = self.vars.clone();
+        vars.insert("locale".to_string(), locale.to_string());
+        vars.insert("store".to_string(), store.to_string());
+        let template = self.override_for(locale);
+        let text = render(template.as_deref().unwrap_or(&self.template), &vars)?;
+        let len = text.chars().count();
+        if len > max_len {
+            return Err(format!(
+                "{store} release notes for {locale} are {len} characters (max {max_len})"
+            ));
+        }
+        Ok(text)
+    }
+}
+
+/// Apple half: `whatsNew` on every localization of the version.
+async fn apple_notes(
+    app: Option<&str>,
+    profile: Option<&str>,
+    version: &str,
+    notes: &Notes,
+    dry_run: bool,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = super::apple::client_for_profile(profile)?;
+    let app_id = resolve::app_id(app, &client).await?;
+    let versions: Value = client
+        .get(
+            &format!("/apps/{app_id}/appStoreVersions"),
+            &[("filter[versionString]", version), ("limit", "1")],
+        )
+        .await?;
+    let version_id = versions["data"][0]["id"]
+        .as_str()
+        .ok_or_else(|| format!("App Store version {version} not found"))?;
+    let locs: Value = client
+        .get(
+            &format!("/appStoreVersions/{version_id}/appStoreVersionLocalizations"),
+            &[("limit", "100")],
+        )
+        .await?;
+
+    // Render every locale before writing any, so a bad template changes nothing
+    let mut pending = Vec::new();
+    for loc in locs["data"].as_array().into_iter().flatten() {
+        let (Some(id), Some(locale)) = (loc["id"].as_str(), loc["attributes"]["locale"].as_str())
+        else {
+            continue;
+        };
+        pending.push((
+            id,
+            locale,
+            notes.for_locale(locale, "apple", APPLE_MAX_LEN)?,
+        ));
+    }
+
+    let mut rendered = BTreeMap::new();
+    for (id, locale, text) in pending {
+        if !dry_run {
+            let body = json!({
+                "data": {
+                    "type": "appStoreVersionLocalizations",
+                    "id": id,
+                    "attributes": { "whatsNew": text }
+                }
+            });
+            client
+                .patch(&format!("/appStoreVersionLocalizations/{id}"), &body)
+                .await?;
+            eprintln!("apple: updated {locale}");
+        }
+        rendered.insert(locale.to_string(), text);
+    }
+    Ok(json!({
+        "app_id": app_id,
+        "version_id": version_id,
+        "notes": rendered,
+    }))
+}
+
+/// Google half: release notes of the track release named after the version, in
+/// every listing language.
+async fn google_notes(
+    package: Option<&str>,
+    profile: Option<&str>,
+    track: &str,
+    version: &str,
+    notes: &Notes,
+    dry_run: bool,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = super::google::client_for_profile(profile).await?;
+    let package_name = &client.package_name(package)?;
+    let edit_id = &client.open_edit(package_name).await?;
+    let result = async {
+        let mut track_body: Value = client
+            .get(
+                &format!("/{package_name}/edits/{edit_id}/tracks/{track}"),
+                &[],
+            )
+            .await?;
+        let listings: Value = client
+            .get(&format!("/{package_name}/edits/{edit_id}/listings"), &[])
+            .await?;
+
+        let mut rendered = BTreeMap::new();
+        for listing in listings["listings"].as_array().into_iter().flatten() {
+            if let Some(language) = listing["language"].as_str() {
+                let text = notes.for_locale(language, "google", GOOGLE_MAX_LEN)?;
+                rendered.insert(language.to_string(), text);
+            }
+        }
+
+        let release_notes: Vec<Value> = rendered
+            .iter()
+            .map(|(language, text)| json!({ "language": language, "text": text }))
+            .collect();
+        let release = track_body["releases"]
+            .as_array_mut()
+            .and_then(|releases| {
+                releases
+                    .iter_mut()
+                    .find(|r| r["name"].as_str() == Some(version))
+            })
+            .ok_or_else(|| format!("release {version} not found on {track}"))?;
+        release["releaseNotes"] = json!(release_notes);
+
+        Ok::<_, Box<dyn std::error::Error>>((track_body, version, rendered))
+    }
+    .await;
+    let (track_body, release_name, rendered) = match result {
+        Ok(r) => r,
+        Err(e) => {
+            client.discard_edit(package_name, edit_id).await;
+            return Err(e);
+        }
+    };
+
+    if dry_run {
+        client.discard_edit(package_name, edit_id).await;
+    } else {
+        client
+            .put(
+                &format!("/{package_name}/edits/{edit_id}/tracks/{track}"),
+                &track_body,
+            )
+            .await?;
+        client.validate_edit(package_name, edit_id).await?;
+        client.commit_edit(package_name, edit_id).await?;
+        eprintln!("google: updated {} language(s)", rendered.len());
+    }
+    Ok(json!({
+        "package_name": package_name,
+        "track": track,
+        "release": release_name,
+        "notes": rendered,
+    }))
+}
+
+pub async fn handle(
+    cmd: &ReleaseNotesCommand,
+    cli: &Cli,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        ReleaseNotesCommand::Set {
+            template,
+            version,
+            overrides,
+            vars,
+            app,
+            package,
+            track,
+            apple_profile,
+            google_profile,
+            dry_run,
+        } => {
+            let notes = Notes::load(template, overrides.as_deref(), version, vars)?;
+            // Write only to the stores asked about, or both when nothing store-specific was given
+            let wants_apple = app.is_some() || apple_profile.is_some();
+            let wants_google = package.is_some() || google_profile.is_some();
+            let both = !wants_apple && !wants_google;
+            let apple_profile = apple_profile.as_deref().or(cli.profile.as_deref());
+            let google_profile = google_profile.as_deref().or(cli.profile.as_deref());
+
+            let mut result = super::join_stores(
+                (both || wants_apple)
+                    .then(|| apple_notes(app.as_deref(), apple_profile, version, &notes, *dry_run)),
+                (both || wants_google).then(|| {
+                    google_notes(
+                        package.as_deref(),
+                        google_profile,
+                        track,
+                        version,
+                        &notes,
+                        *dry_run,
+                    )
+                }),
+            )
+            .await;
+            result["version"] = json!(version);
+            result["dry_run"] = json!(dry_run);
+            if result.get("failed_stores").is_some() && !*dry_run {
+                return Err(Box::new(crate::output::Failure(result)));
+            }
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn locale_override_falls_back_to_language() {
+        let dir = std::env::temp_dir().join(format!("storeops-notes-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("de.md"), "Neu in {{version}}").unwrap();
+        let notes = Notes {
+            template: "New in {{version}} for {{store}}".to_string(),
+            overrides: Some(dir.clone()),
+            vars: vars(&[("version", "1.4.0")]),
+        };
+        assert_eq!(
+            notes.for_locale("de-DE", "apple", APPLE_MAX_LEN).unwrap(),
+            "Neu in 1.4.0"
+        );
+        assert_eq!(
+            notes.for_locale("en-US", "google", GOOGLE_MAX_LEN).unwrap(),
+            "New in 1.4.0 for google"
+        );
+        assert!(notes.for_locale("en-US", "google", 5).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,412 @@
+//! Monthly revenue across both stores, in one currency.
+//!
+//! The Apple half reads the monthly sales summary report (developer proceeds); the
+//! Google half reads the Play Console earnings reports (net of Google's fees) or,
+//! during the month, the estimated sales reports (gross) from the developer's Cloud
+//! Storage bucket. Amounts are converted with the configured exchange rates and
+//! summed per app and country. Like `status`, a failing store is reported next to
+//! the other's figures.
+
+use clap::{Args, ValueEnum};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use super::apple::analytics::parse_report;
+use super::google::reports;
+use crate::config::{revenue::RevenueConfig, Config};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum GoogleSource {
+    /// Earnings reports: net of Google's fees and refunds, published after month end
+    Earnings,
+    /// Estimated sales reports: gross charged amounts, updated during the month
+    Sales,
+}
+
+#[derive(Args)]
+pub struct RevenueArgs {
+    /// Month to summarise
+    #[arg(long, value_name = "YYYY-MM")]
+    pub month: String,
+    /// Currency to report in (default: `[revenue] currency`, or USD)
+    #[arg(long)]
+    pub currency: Option<String>,
+    /// Exchange rates JSON file or URL (default: `[revenue] rates_url`, then `[revenue.rates]`)
+    #[arg(long)]
+    pub rates: Option<String>,
+    /// Apple vendor number (default: `[revenue] apple_vendor_number`)
+    #[arg(long)]
+    pub vendor_number: Option<String>,
+    /// Play Console reports bucket, `pubsite_prod_rev_...` (default: `[revenue] google_bucket`)
+    #[arg(long)]
+    pub bucket: Option<String>,
+    /// Google Play report to read
+    #[arg(long, value_enum, default_value = "earnings")]
+    pub google_source: GoogleSource,
+    /// Profile for the Apple half (defaults to --profile or the active profile)
+    #[arg(long)]
+    pub apple_profile: Option<String>,
+    /// Profile for the Google half (defaults to --profile or the active profile)
+    #[arg(long)]
+    pub google_profile: Option<String>,
+}
+
+/// Exchange rates as units of each currency per one unit of `base`.
+struct Rates {
+    base: String,
+    rates: HashMap<String, f64>,
+}
+
+impl Rates {
+    /// Rates from a `{"base": .., "rates": {..}}` document (a bare map of rates is
+    /// accepted too); the base defaults to `default_base`.
+    fn from_json(value: &Value, default_base: &str) -> Result<Self, String> {
+        let base = value["base"]
+            .as_str()
+            .or(value["base_code"].as_str())
+            .unwrap_or(default_base)
+            .to_uppercase();
+        let map = value
+            .get("rates")
+            .unwrap_or(value)
+            .as_object()
+            .ok_or("exchange rates must be a JSON object of currency rates")?;
+        let rates = map
+            .iter()
+            .filter_map(|(currency, rate)| Some((currency.to_uppercase(), rate.as_f64()?)))
+            .collect();
+        Ok(Rates { base, rates })
+    }
+
+    fn rate(&self, currency: &str) -> Option<f64> {
+        if currency.eq_ignore_ascii_case(&self.base) {
+            return Some(1.0);
+        }
+        self.rates
+            .get(&currency.to_uppercase())
+            .copied()
+            .filter(|r| *r > 0.0)
+    }
+
+    /// `amount` of `from` in `to`, when both rates are known.
+    fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        if from.eq_ignore_ascii_case(to) {
+            return Some(amount);
+        }
+        Some(amount / self.rate(from)? * self.rate(to)?)
+    }
+}
+
+/// Rates from `--rates`, the configured URL, or the fixed `[revenue.rates]` table.
+async fn load_rates(
+    source: Option<&str>,
+    config: &RevenueConfig,
+    currency: &str,
+) -> Result<Rates, Box<dyn std::error::Error>> {
+    let source = source.or(config.rates_url.as_deref());
+    let Some(source) = source else {
+        return Ok(Rates {
+            base: currency.to_uppercase(),
+            rates: config
+                .rates
+                .iter()
+                .map(|(c, r)| (c.to_uppercase(), *r))
+                .collect(),
+        });
+    };
+    let document: Value = if source.starts_with("https://") || source.starts_with("http://") {
+        eprintln!("Fetching exchange rates from {source}...");
+        let resp = crate::api::session::http().get(source).send().await?;
+        if !resp.status().is_success() {
+            return Err(format!("exchange rates request failed: {}", resp.status()).into());
+        }
+        resp.json().await?
+    } else {
+        let text = std::fs::read_to_string(source)
+            .map_err(|e| format!("cannot read exchange rates {source}: {e}"))?;
+        serde_json::from_str(&text).map_err(|e| format!("invalid exchange rates {source}: {e}"))?
+    };
+    Ok(Rates::from_json(&document, currency)?)
+}
+
+/// One revenue line in its original currency.
+fn line(app: &str, country: &str, currency: &str, amount: f64) -> Value {
+    json!({ "app": app, "country": country, "currency": currency, "amount": amount })
+}
+
+/// Apple half: developer proceeds per app (SKU, with in-app purchases counted
+/// under their parent app) and country from the monthly sales summary.
+async fn apple_lines(
+    month: &str,
+    vendor_number: &str,
+    profile: Option<&str>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = super::apple::client_for_profile(profile)?;
+    let report = client
+        .get_report(
+            "/salesReports",
+            &[
+                ("filter[vendorNumber]", vendor_number),
+                ("filter[frequency]", "MONTHLY"),
+                ("filter[reportType]", "SALES"),
+                ("filter[reportSubType]", "SUMMARY"),
+                ("filter[version]", "1_0"),
+                ("filter[reportDate]", month),
+            ],
+        )
+        .await?
+        .ok_or_else(|| format!("no Apple sales report for {month} yet"))?;
+    let number = |v: Option<&String>| v.and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+    let lines: Vec<Value> = parse_report(&report)?
+        .iter()
+        .filter_map(|row| {
+            let amount = number(row.get("Units")) * number(row.get("Developer Proceeds"));
+            if amount == 0.0 {
+                return None;
+            }
+            let app = row
+                .get("Parent Identifier")
+                .filter(|p| !p.is_empty())
+                .or(row.get("SKU"))?;
+            Some(line(
+                app,
+                row.get("Country Code")?,
+                row.get("Currency of Proceeds")?,
+                amount,
+            ))
+        })
+        .collect();
+    Ok(json!({ "lines": lines }))
+}
+
+/// Revenue lines of a Play Console earnings or estimated sales CSV.
+fn google_csv_lines(text: &str, source: GoogleSource) -> Vec<Value> {
+    let (app, country, currency, amount) = match source {
+        GoogleSource::Earnings => (
+            "Product id",
+            "Buyer Country",
+            "Merchant Currency",
+            "Amount (Merchant Currency)",
+        ),
+        GoogleSource::Sales => (
+            "Product ID",
+            "Country of Buyer",
+            "Currency of Sale",
+            "Charged Amount",
+        ),
+    };
+    let mut records = crate::output::csv::parse(text.trim_start_matches('\u{feff}')).into_iter();
+    let Some(header) = records.next() else {
+        return Vec::new();
+    };
+    let index = |name: &str| header.iter().position(|h| h.trim() == name);
+    let (Some(app), Some(country), Some(currency), Some(amount)) =
+        (index(app), index(country), index(currency), index(amount))
+    else {
+        return Vec::new();
+    };
+    records
+        .filter_map(|record| {
+            let value = record
+                .get(amount)?
+                .replace(',', "")
+                .trim()
+                .parse::<f64>()
+                .ok()?;
+            Some(line(
+                record.get(app)?,
+                record.get(country)?,
+                record.get(currency)?,
+                value,
+            ))
+        })
+        .collect()
+}
+
+/// Google half: every report object of the month in the bucket, unzipped.
+async fn google_lines(
+    month: &str,
+    bucket: &str,
+    source: GoogleSource,
+    profile: Option<&str>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = super::google::client_for_profile(profile).await?;
+    let prefix = match source {
+        GoogleSource::Earnings => reports::earnings_prefix(month),
+        GoogleSource::Sales => format!("sales/salesreport_{}", month.replace('-', "")),
+    };
+    let lines = reports::bucket_csvs(&client, bucket, &prefix)
+        .await?
+        .iter()
+        .flat_map(|(_, text)| google_csv_lines(text, source))
+        .collect::<Vec<_>>();
+    Ok(json!({ "lines": lines }))
+}
+
+fn round(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Per-app and per-country totals of each store's lines in `currency`.
+///
+/// Lines in a currency without a known rate are listed under `unconverted`
+/// instead of being summed.
+fn summarize(stores: &Value, rates: &Rates, currency: &str) -> Value {
+    let mut rows: BTreeMap<(String, String, String), f64> = BTreeMap::new();
+    let mut unconverted: BTreeMap<(String, String, String), f64> = BTreeMap::new();
+    let mut store_totals = serde_json::Map::new();
+    for store in ["apple", "google"] {
+        let Some(lines) = stores[store]["lines"].as_array() else {
+            continue;
+        };
+        let mut total = 0.0;
+        for l in lines {
+            let text = |key: &str| l[key].as_str().unwrap_or_default().to_string();
+            let amount = l["amount"].as_f64().unwrap_or_default();
+            match rates.convert(amount, &text("currency"), currency) {
+                Some(converted) => {
+                    total += converted;
+                    *rows
+                        .entry((store.to_string(), text("app"), text("country")))
+                        .or_default() += converted;
+                }
+                None => {
+                    *unconverted
+                        .entry((store.to_string(), text("app"), text("currency")))
+                        .or_default() += amount;
+                }
+            }
+        }
+        store_totals.insert(store.to_string(), json!(round(total)));
+    }
+
+    let mut apps: BTreeMap<(String, String), f64> = BTreeMap::new();
+    for ((store, app, _), amount) in &rows {
+        *apps.entry((store.clone(), app.clone())).or_default() += amount;
+    }
+    let mut apps: Vec<((String, String), f64)> = apps.into_iter().collect();
+    apps.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut rows: Vec<((String, String, String), f64)> = rows.into_iter().collect();
+    rows.sort_by(|a, b| {
+        (&a.0 .0, &a.0 .1)
+            .cmp(&(&b.0 .0, &b.0 .1))
+            .then(b.1.total_cmp(&a.1))
+    });
+
+    json!({
+        "currency": currency,
+        "total": round(store_totals.values().filter_map(Value::as_f64).sum()),
+        "stores": store_totals,
+        "apps": apps
+            .iter()
+            .map(|((store, app), amount)| json!({ "store": store, "app": app, "amount": round(*amount) }))
+            .collect::<Vec<_>>(),
+        "rows": rows
+            .iter()
+            .map(|((store, app, country), amount)| {
+                json!({ "store": store, "app": app, "country": country, "amount": round(*amount) })
+            })
+            .collect::<Vec<_>>(),
+        "unconverted": unconverted
+            .iter()
+            .map(|((store, app, from), amount)| {
+                json!({ "store": store, "app": app, "currency": from, "amount": round(*amount) })
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+pub async fn handle(
+    args: &RevenueArgs,
+    cli: &super::Cli,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    reports::check_month(&args.month)?;
+    let config = Config::load()?.revenue.unwrap_or_default();
+    let currency = args
+        .currency
+        .as_deref()
+        .unwrap_or(config.currency())
+        .to_uppercase();
+    let vendor_number = args
+        .vendor_number
+        .as_deref()
+        .or(config.apple_vendor_number.as_deref());
+    let bucket = args.bucket.as_deref().or(config.google_bucket.as_deref());
+    if vendor_number.is_none() && bucket.is_none() {
+        return Err(
+            "set --vendor-number and/or --bucket (or apple_vendor_number / google_bucket under [revenue])"
+                .into(),
+        );
+    }
+    let rates = load_rates(args.rates.as_deref(), &config, &currency).await?;
+
+    let apple_profile = args.apple_profile.as_deref().or(cli.profile.as_deref());
+    let google_profile = args.google_profile.as_deref().or(cli.profile.as_deref());
+    let stores = super::join_stores(
+        vendor_number.map(|v| apple_lines(&args.month, v, apple_profile)),
+        bucket.map(|b| google_lines(&args.month, b, args.google_source, google_profile)),
+    )
+    .await;
+
+    let mut summary = summarize(&stores, &rates, &currency);
+    summary["month"] = json!(args.month);
+    for store in ["apple", "google"] {
+        if let Some(error) = stores[store].get("error") {
+            summary["errors"][store] = error.clone();
+        }
+    }
+    if let Some(failed) = stores.get("failed_stores") {
+        summary["failed_stores"] = failed.clone();
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_through_the_rates_base() {
+        let rates = Rates::from_json(
+            &json!({"base": "EUR", "rates": {"USD": 1.25, "GBP": 0.8}}),
+            "USD",
+        )
+        .unwrap();
+        assert_eq!(rates.convert(10.0, "EUR", "USD"), Some(12.5));
+        assert_eq!(rates.convert(8.0, "GBP", "EUR"), Some(10.0));
+        assert_eq!(rates.convert(8.0, "GBP", "USD"), Some(12.5));
+        assert_eq!(rates.convert(3.0, "JPY", "JPY"), Some(3.0));
+        assert!(rates.convert(3.0, "JPY", "USD").is_none());
+    }
+
+    #[test]
+    fn sums_both_stores_per_app_and_country() {
+        let earnings = "\u{feff}Description,Product id,Buyer Country,Merchant Currency,Amount (Merchant Currency)\n\
+                        GPA.1,com.example.app,DE,EUR,\"1,000.00\"\n\
+                        GPA.1 fee,com.example.app,DE,EUR,-150.00\n\
+                        GPA.2,com.example.app,JP,JPY,500\n";
+        let stores = json!({
+            "apple": {"lines": [
+                line("photo", "US", "USD", 70.0),
+                line("photo", "DE", "EUR", 20.0),
+            ]},
+            "google": {"lines": google_csv_lines(earnings, GoogleSource::Earnings)},
+        });
+        let rates = Rates::from_json(&json!({"EUR": 0.5}), "USD").unwrap();
+        let summary = summarize(&stores, &rates, "USD");
+        assert_eq!(summary["stores"], json!({"apple": 110.0, "google": 1700.0}));
+        assert_eq!(summary["total"], 1810.0);
+        assert_eq!(
+            summary["apps"][0],
+            json!({"store": "google", "app": "com.example.app", "amount": 1700.0})
+        );
+        assert_eq!(
+            summary["rows"][0],
+            json!({"store": "apple", "app": "photo", "country": "US", "amount": 70.0})
+        );
+        assert_eq!(
+            summary["unconverted"],
+            json!([{"store": "google", "app": "com.example.app", "currency": "JPY", "amount": 500.0}])
+        );
+    }
+}
@@ -0,0 +1,294 @@
+//! Review export shared by `apple reviews export` and `google reviews export`.
+//!
+//! Each store pages its reviews for the requested date range and normalizes them to
+//! rows with the same columns; this module filters by date, optionally scores
+//! sentiment and extracts keywords, and writes the rows as CSV or JSON lines.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, ValueEnum};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+/// Columns of every exported row, in CSV order.
+const COLUMNS: &[&str] = &[
+    "store",
+    "review_id",
+    "date",
+    "rating",
+    "title",
+    "body",
+    "author",
+    "territory",
+    "language",
+    "app_version",
+    "replied",
+];
+const SENTIMENT_COLUMNS: &[&str] = &["sentiment", "sentiment_score", "keywords"];
+
+const POSITIVE: &[&str] = &[
+    "amazing",
+    "awesome",
+    "best",
+    "easy",
+    "excellent",
+    "fantastic",
+    "fast",
+    "good",
+    "great",
+    "helpful",
+    "love",
+    "loved",
+    "nice",
+    "perfect",
+    "recommend",
+    "simple",
+    "smooth",
+    "thanks",
+    "useful",
+    "wonderful",
+];
+const NEGATIVE: &[&str] = &[
+    "annoying",
+    "awful",
+    "bad",
+    "broken",
+    "bug",
+    "bugs",
+    "confusing",
+    "crash",
+    "crashes",
+    "disappointed",
+    "error",
+    "expensive",
+    "freeze",
+    "freezes",
+    "hate",
+    "horrible",
+    "slow",
+    "terrible",
+    "useless",
+    "worst",
+];
+const STOPWORDS: &[&str] = &[
+    "about", "after", "also", "been", "before", "could", "does", "even", "every", "from", "have",
+    "just", "like", "make", "many", "more", "much", "only", "really", "should", "since", "some",
+    "than", "that", "their", "them", "then", "there", "they", "this", "very", "what", "when",
+    "which", "while", "will", "with", "would", "your",
+];
+const TOP_KEYWORDS: usize = 10;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+}
+
+/// Options shared by the per-store `reviews export` commands.
+#[derive(Args)]
+pub struct ExportArgs {
+    /// File to write
+    #[arg(long)]
+    pub file: PathBuf,
+    /// Output file format
+    #[arg(long, value_enum, default_value = "csv")]
+    pub format: ExportFormat,
+    /// First day to include (YYYY-MM-DD)
+    #[arg(long)]
+    pub since: Option<NaiveDate>,
+    /// Last day to include (YYYY-MM-DD)
+    #[arg(long)]
+    pub until: Option<NaiveDate>,
+    /// Add sentiment (positive/neutral/negative) and keyword columns
+    #[arg(long)]
+    pub sentiment: bool,
+}
+
+impl ExportArgs {
+    /// Whether a review date falls inside `--since`/`--until`.
+    pub fn in_range(&self, date: &DateTime<Utc>) -> bool {
+        let day = date.date_naive();
+        self.since.is_none_or(|s| day >= s) && self.until.is_none_or(|u| day <= u)
+    }
+
+    /// Whether a page sorted newest first has gone past `--since` and paging can stop.
+    pub fn before_range(&self, date: &DateTime<Utc>) -> bool {
+        self.since.is_some_and(|s| date.date_naive() < s)
+    }
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(|w| w.trim_matches('\'').to_lowercase())
+}
+
+/// Lexicon score of a review, nudged by its star rating.
+fn sentiment_score(text: &str, rating: Option<i64>) -> i64 {
+    let mut score: i64 = words(text)
+        .map(|w| {
+            if POSITIVE.contains(&w.as_str()) {
+                1
+            } else if NEGATIVE.contains(&w.as_str()) {
+                -1
+            } else {
+                0
+            }
+        })
+        .sum();
+    match rating {
+        Some(r) if r >= 4 => score += 1,
+        Some(r) if r <= 2 => score -= 1,
+        _ => {}
+    }
+    score
+}
+
+fn sentiment_label(score: i64) -> &'static str {
+    match score {
+        s if s > 0 => "positive",
+        s if s < 0 => "negative",
+        _ => "neutral",
+    }
+}
+
+/// Most frequent meaningful words across all reviews, counted once per review.
+fn top_keywords(rows: &[Value]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for row in rows {
+        let text = format!(
+            "{} {}",
+            row["title"].as_str().unwrap_or(""),
+            row["body"].as_str().unwrap_or("")
+        );
+        let unique: HashSet<String> = words(&text)
+            .filter(|w| w.len() >= 4 && !STOPWORDS.contains(&w.as_str()))
+            .collect();
+        for w in unique {
+            *counts.entry(w).or_default() += 1;
+        }
+    }
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().filter(|(_, n)| *n > 1).collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.truncate(TOP_KEYWORDS);
+    ranked
+}
+
+/// Add sentiment and keyword columns to every row; returns the summary counts.
+fn analyze(rows: &mut [Value]) -> Value {
+    let keywords = top_keywords(rows);
+    let mut totals: BTreeMap<&str, usize> = BTreeMap::new();
+    for row in rows.iter_mut() {
+        let text = format!(
+            "{} {}",
+            row["title"].as_str().unwrap_or(""),
+            row["body"].as_str().unwrap_or("")
+        );
+        let score = sentiment_score(&text, row["rating"].as_i64());
+        let label = sentiment_label(score);
+        *totals.entry(label).or_default() += 1;
+        let present: HashSet<String> = words(&text).collect();
+        let matched: Vec<&str> = keywords
+            .iter()
+            .filter(|(k, _)| present.contains(k))
+            .map(|(k, _)| k.as_str())
+            .collect();
+        row["sentiment"] = json!(label);
+        row["sentiment_score"] = json!(score);
+        row["keywords"] = json!(matched.join(";"));
+    }
+    json!({
+        "sentiment": totals,
+        "top_keywords": keywords
+            .iter()
+            .map(|(k, n)| json!({ "keyword": k, "reviews": n }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn render(rows: &[Value], columns: &[&str], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => crate::output::csv::render(rows, columns),
+        ExportFormat::Jsonl => rows
+            .iter()
+            .map(|row| {
+                let obj: Map<String, Value> = columns
+                    .iter()
+                    .map(|c| (c.to_string(), row[*c].clone()))
+                    .collect();
+                Value::Object(obj).to_string() + "\n"
+            })
+            .collect(),
+    }
+}
+
+/// Write normalized review rows to `--file` and summarize the export.
+pub fn write(mut rows: Vec<Value>, args: &ExportArgs) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut columns = COLUMNS.to_vec();
+    let analysis = if args.sentiment {
+        columns.extend(SENTIMENT_COLUMNS);
+        Some(analyze(&mut rows))
+    } else {
+        None
+    };
+    std::fs::write(&args.file, render(&rows, &columns, args.format))?;
+
+    let mut summary = json!({
+        "status": "ok",
+        "file": args.file.display().to_string(),
+        "reviews": rows.len(),
+        "since": args.since.map(|d| d.to_string()),
+        "until": args.until.map(|d| d.to_string()),
+    });
+    if let Some(Value::Object(analysis)) = analysis {
+        for (k, v) in analysis {
+            summary[k] = v;
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_sentiment_from_words_and_rating() {
+        assert_eq!(
+            sentiment_label(sentiment_score("Love it, great app", Some(5))),
+            "positive"
+        );
+        assert_eq!(
+            sentiment_label(sentiment_score("Crashes on start. Terrible", Some(1))),
+            "negative"
+        );
+        assert_eq!(
+            sentiment_label(sentiment_score("It opens", Some(3))),
+            "neutral"
+        );
+    }
+
+    #[test]
+    fn analysis_adds_shared_keywords() {
+        let mut rows = vec![
+            json!({"title": "Sync broken", "body": "Calendar sync fails", "rating": 2}),
+            json!({"title": "", "body": "Great but sync is slow", "rating": 4}),
+            json!({"title": "Nice", "body": "Widgets are nice", "rating": 5}),
+        ];
+        let summary = analyze(&mut rows);
+        assert_eq!(summary["top_keywords"][0]["keyword"], "sync");
+        assert_eq!(rows[0]["keywords"], "sync");
+        assert_eq!(rows[0]["sentiment"], "negative");
+        assert_eq!(summary["sentiment"]["positive"], 2);
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_separators() {
+        let rows = vec![json!({"store": "apple", "body": "Good, \"really\"\nfast", "rating": 5})];
+        let csv = render(&rows, &["store", "rating", "body"], ExportFormat::Csv);
+        assert_eq!(
+            csv,
+            "store,rating,body\napple,5,\"Good, \"\"really\"\"\nfast\"\n"
+        );
+    }
+}
@@ -0,0 +1,432 @@
+//! Local triage queue for negative reviews across both stores.
+//!
+//! `reviews triage list --app/--package` pulls recent low-rated reviews into
+//! `<data dir>/review-triage.json`; `claim` and `done` record who is answering
+//! which one. Reviews that already have a reply in the store are closed on the next
+//! refresh. The queue is a plain JSON file, and `list --status all --json` exports it
+//! for a support dashboard.
+
+use chrono::{NaiveDate, Utc};
+use clap::{Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::review_export::{ExportArgs, ExportFormat};
+use super::Cli;
+use crate::config::Config;
+
+const FILE_NAME: &str = "review-triage.json";
+
+/// How far back a refresh looks when `--since` is not given.
+const DEFAULT_DAYS: i64 = 7;
+
+#[derive(Subcommand)]
+pub enum ReviewsCommand {
+    /// Track which negative reviews have been answered, and by whom
+    Triage {
+        #[command(subcommand)]
+        command: TriageCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TriageCommand {
+    /// List the triage queue, refreshing it from the stores given
+    ///
+    /// Examples:
+    ///   storeops reviews triage list --app com.example.app --package com.example.app
+    ///   storeops reviews triage list --status claimed
+    ///   storeops reviews triage list --status all --json > triage.json
+    List {
+        /// Pull new reviews of this Apple app ID or bundle ID first
+        #[arg(long)]
+        app: Option<String>,
+        /// Pull new reviews of this Google package first
+        #[arg(long)]
+        package: Option<String>,
+        /// Oldest review day to pull (YYYY-MM-DD, defaults to a week ago)
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Highest star rating that counts as negative
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(1..=5))]
+        max_rating: u8,
+        /// Entries to show
+        #[arg(long, value_enum, default_value_t = StatusFilter::Pending)]
+        status: StatusFilter,
+    },
+    /// Take a review, so others know it is being answered
+    ///
+    /// Examples:
+    ///   storeops reviews triage claim 6fd3e2a1-... --as alice
+    ///   storeops reviews triage claim google:gp:AOqpTOE...
+    Claim {
+        /// Review ID, optionally prefixed with `apple:` or `google:`
+        review_id: String,
+        /// Who is answering (defaults to $USER)
+        #[arg(long = "as")]
+        assignee: Option<String>,
+        /// Take over a review someone else claimed
+        #[arg(long)]
+        force: bool,
+    },
+    /// Mark a review as answered
+    ///
+    /// Examples:
+    ///   storeops reviews triage done 6fd3e2a1-... --note "refund issued"
+    Done {
+        /// Review ID, optionally prefixed with `apple:` or `google:`
+        review_id: String,
+        /// Who answered (defaults to the claimant, then $USER)
+        #[arg(long = "as")]
+        assignee: Option<String>,
+        /// Free-form note for the dashboard
+        #[arg(long)]
+        note: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatusFilter {
+    /// Open and claimed
+    Pending,
+    Open,
+    Claimed,
+    Done,
+    All,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Open,
+    Claimed,
+    Done,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub store: String,
+    pub review_id: String,
+    /// App ID or package name
+    pub app: String,
+    pub rating: Option<i64>,
+    pub date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub status: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claimed_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// The queue, keyed by `<store>:<review id>`.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Queue {
+    pub reviews: BTreeMap<String, Entry>,
+}
+
+impl Queue {
+    fn path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Ok(Config::data_dir()
+            .ok_or("cannot determine data directory")?
+            .join(FILE_NAME))
+    }
+
+    fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("cannot parse {}: {e}", path.display()).into()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add new negative reviews from an export row list, and close those answered
+    /// in the store. Returns how many were added and closed.
+    fn merge(&mut self, app: &str, rows: &[Value], max_rating: u8) -> (usize, usize) {
+        let (mut added, mut closed) = (0, 0);
+        let now = Utc::now().to_rfc3339();
+        for row in rows {
+            let (Some(store), Some(id)) = (row["store"].as_str(), row["review_id"].as_str()) else {
+                continue;
+            };
+            let key = format!("{store}:{id}");
+            let replied = row["replied"].as_bool().unwrap_or(false);
+            match self.reviews.get_mut(&key) {
+                Some(entry) if replied && entry.status != Status::Done => {
+                    entry.status = Status::Done;
+                    entry.done_at = Some(now.clone());
+                    entry
+                        .note
+                        .get_or_insert_with(|| "replied in the store".to_string());
+                    closed += 1;
+                }
+                Some(_) => {}
+                None if !replied
+                    && row["rating"]
+                        .as_i64()
+                        .is_some_and(|r| r <= i64::from(max_rating)) =>
+                {
+                    let text = |field: &str| row[field].as_str().map(String::from);
+                    self.reviews.insert(
+                        key,
+                        Entry {
+                            store: store.to_string(),
+                            review_id: id.to_string(),
+                            app: app.to_string(),
+                            rating: row["rating"].as_i64(),
+                            date: text("date"),
+                            title: text("title"),
+                            body: text("body"),
+                            status: Status::Open,
+                            assignee: None,
+                            claimed_at: None,
+                            done_at: None,
+                            note: None,
+                        },
+                    );
+                    added += 1;
+                }
+                None => {}
+            }
+        }
+        (added, closed)
+    }
+
+    /// The key of a review given as `<id>` or `<store>:<id>`.
+    fn find(&self, review_id: &str) -> Result<String, String> {
+        if self.reviews.contains_key(review_id) {
+            return Ok(review_id.to_string());
+        }
+        let matches: Vec<&String> = self
+            .reviews
+            .iter()
+            .filter(|(_, e)| e.review_id == review_id)
+            .map(|(k, _)| k)
+            .collect();
+        match matches.as_slice() {
+            [key] => Ok((*key).clone()),
+            [] => Err(format!(
+                "review {review_id} is not in the triage queue; refresh it with `reviews triage list --app/--package`"
+            )),
+            _ => Err(format!(
+                "review {review_id} exists in both stores; prefix it with apple: or google:"
+            )),
+        }
+    }
+}
+
+fn current_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+}
+
+fn claim(
+    queue: &mut Queue,
+    review_id: &str,
+    assignee: Option<&str>,
+    force: bool,
+) -> Result<Entry, Box<dyn std::error::Error>> {
+    let key = queue.find(review_id)?;
+    let who = assignee
+        .map(String::from)
+        .or_else(current_user)
+        .ok_or("pass --as <name>; $USER is not set")?;
+    let entry = queue.reviews.get_mut(&key).ok_or("review not found")?;
+    match (&entry.status, &entry.assignee) {
+        (Status::Done, _) => return Err(format!("review {review_id} is already done").into()),
+        (Status::Claimed, Some(current)) if *current != who && !force => {
+            return Err(format!(
+                "review {review_id} is claimed by {current}; pass --force to take it over"
+            )
+            .into())
+        }
+        _ => {}
+    }
+    entry.status = Status::Claimed;
+    entry.assignee = Some(who);
+    entry.claimed_at = Some(Utc::now().to_rfc3339());
+    Ok(entry.clone())
+}
+
+fn done(
+    queue: &mut Queue,
+    review_id: &str,
+    assignee: Option<&str>,
+    note: Option<&str>,
+) -> Result<Entry, Box<dyn std::error::Error>> {
+    let key = queue.find(review_id)?;
+    let entry = queue.reviews.get_mut(&key).ok_or("review not found")?;
+    if let Some(who) = assignee
+        .map(String::from)
+        .or_else(|| entry.assignee.clone().or_else(current_user))
+    {
+        entry.assignee = Some(who);
+    }
+    entry.status = Status::Done;
+    entry.done_at = Some(Utc::now().to_rfc3339());
+    if let Some(note) = note {
+        entry.note = Some(note.to_string());
+    }
+    Ok(entry.clone())
+}
+
+fn matches(filter: StatusFilter, status: Status) -> bool {
+    match filter {
+        StatusFilter::Pending => status != Status::Done,
+        StatusFilter::Open => status == Status::Open,
+        StatusFilter::Claimed => status == Status::Claimed,
+        StatusFilter::Done => status == Status::Done,
+        StatusFilter::All => true,
+    }
+}
+
+/// Date-range arguments for the per-store export pagers.
+fn range(since: NaiveDate) -> ExportArgs {
+    ExportArgs {
+        file: PathBuf::new(),
+        format: ExportFormat::Jsonl,
+        since: Some(since),
+        until: None,
+        sentiment: false,
+    }
+}
+
+pub async fn handle(cmd: &ReviewsCommand, cli: &Cli) -> Result<Value, Box<dyn std::error::Error>> {
+    let ReviewsCommand::Triage { command } = cmd;
+    let path = Queue::path()?;
+    let mut queue = Queue::load(&path)?;
+    match command {
+        TriageCommand::List {
+            app,
+            package,
+            since,
+            max_rating,
+            status,
+        } => {
+            let since = since.unwrap_or_else(|| {
+                (Utc::now() - chrono::Duration::days(DEFAULT_DAYS)).date_naive()
+            });
+            let mut refreshed = Vec::new();
+            if let Some(app) = app {
+                let client = super::apple::client_for_profile(cli.profile.as_deref())?;
+                let app_id = super::apple::resolve::app_id(Some(app), &client).await?;
+                let rows =
+                    super::apple::reviews::export_rows(&app_id, &range(since), &client).await?;
+                let (added, closed) = queue.merge(&app_id, &rows, *max_rating);
+                refreshed.push(
+                    json!({ "store": "apple", "app": app_id, "added": added, "closed": closed }),
+                );
+            }
+            if let Some(package) = package {
+                let client = super::google::client_for_profile(cli.profile.as_deref()).await?;
+                let rows =
+                    super::google::reviews::export_rows(package, &range(since), &client).await?;
+                let (added, closed) = queue.merge(package, &rows, *max_rating);
+                refreshed.push(
+                    json!({ "store": "google", "app": package, "added": added, "closed": closed }),
+                );
+            }
+            if !refreshed.is_empty() {
+                queue.save(&path)?;
+            }
+
+            let mut entries: Vec<&Entry> = queue
+                .reviews
+                .values()
+                .filter(|e| matches(*status, e.status))
+                .collect();
+            entries.sort_by(|a, b| b.date.cmp(&a.date));
+            let count = |s: Status| queue.reviews.values().filter(|e| e.status == s).count();
+            let mut result = json!({
+                "path": path.display().to_string(),
+                "counts": {
+                    "open": count(Status::Open),
+                    "claimed": count(Status::Claimed),
+                    "done": count(Status::Done),
+                },
+                "reviews": entries,
+            });
+            if !refreshed.is_empty() {
+                result["refreshed"] = json!(refreshed);
+            }
+            Ok(result)
+        }
+        TriageCommand::Claim {
+            review_id,
+            assignee,
+            force,
+        } => {
+            let entry = claim(&mut queue, review_id, assignee.as_deref(), *force)?;
+            queue.save(&path)?;
+            Ok(json!(entry))
+        }
+        TriageCommand::Done {
+            review_id,
+            assignee,
+            note,
+        } => {
+            let entry = done(&mut queue, review_id, assignee.as_deref(), note.as_deref())?;
+            queue.save(&path)?;
+            Ok(json!(entry))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queues_negative_reviews_and_tracks_claims() {
+        let mut queue = Queue::default();
+        let rows = [
+            json!({"store": "apple", "review_id": "r1", "rating": 1, "body": "Crashes", "replied": false}),
+            json!({"store": "apple", "review_id": "r2", "rating": 5, "body": "Great", "replied": false}),
+            json!({"store": "apple", "review_id": "r3", "rating": 2, "body": "Slow", "replied": true}),
+        ];
+        assert_eq!(queue.merge("123", &rows, 2), (1, 0));
+        assert_eq!(queue.reviews["apple:r1"].status, Status::Open);
+
+        let entry = claim(&mut queue, "r1", Some("alice"), false).unwrap();
+        assert_eq!(entry.assignee.as_deref(), Some("alice"));
+        assert!(claim(&mut queue, "apple:r1", Some("bob"), false)
+            .unwrap_err()
+            .to_string()
+            .contains("claimed by alice"));
+        assert!(claim(&mut queue, "missing", Some("bob"), false).is_err());
+
+        let entry = done(&mut queue, "r1", None, Some("refunded")).unwrap();
+        assert_eq!(entry.status, Status::Done);
+        assert_eq!(entry.assignee.as_deref(), Some("alice"));
+
+        // A reply made in the store closes a queued review on the next refresh
+        queue.merge(
+            "pkg",
+            &[json!({"store": "google", "review_id": "g1", "rating": 1, "replied": false})],
+            2,
+        );
+        let replied = [json!({"store": "google", "review_id": "g1", "rating": 1, "replied": true})];
+        assert_eq!(queue.merge("pkg", &replied, 2), (0, 1));
+        assert_eq!(queue.reviews["google:g1"].status, Status::Done);
+        assert!(matches(StatusFilter::Pending, Status::Claimed));
+        assert!(!matches(StatusFilter::Pending, Status::Done));
+    }
+}
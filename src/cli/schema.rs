@@ -0,0 +1,60 @@
+//! JSON Schemas for the outputs of core commands.
+//!
+//! The schemas live in `schemas/` at the repository root and are compiled in, so
+//! `storeops schema <command>` always matches the published files.
+
+use serde_json::{json, Value};
+
+/// Command name and its schema document.
+const SCHEMAS: &[(&str, &str)] = &[
+    (
+        "apple apps list",
+        include_str!("../../schemas/apple-apps-list.json"),
+    ),
+    (
+        "apple reviews list",
+        include_str!("../../schemas/apple-reviews-list.json"),
+    ),
+    (
+        "google reviews list",
+        include_str!("../../schemas/google-reviews-list.json"),
+    ),
+    ("status", include_str!("../../schemas/status.json")),
+];
+
+pub fn handle(command: &[String]) -> Result<Value, Box<dyn std::error::Error>> {
+    if command.is_empty() {
+        let names: Vec<&str> = SCHEMAS.iter().map(|(name, _)| *name).collect();
+        return Ok(json!({ "schemas": names }));
+    }
+    let name = command.join(" ");
+    let (_, schema) = SCHEMAS.iter().find(|(n, _)| *n == name).ok_or_else(|| {
+        let names: Vec<&str> = SCHEMAS.iter().map(|(n, _)| *n).collect();
+        format!("no schema for `{name}`; available: {}", names.join(", "))
+    })?;
+    Ok(serde_json::from_str(schema)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn published_schemas_are_valid_json_titled_after_their_command() {
+        for (name, schema) in SCHEMAS {
+            let schema: Value = serde_json::from_str(schema).unwrap();
+            assert_eq!(schema["title"], format!("storeops {name}"));
+            assert_eq!(
+                schema["$schema"],
+                "https://json-schema.org/draft/2020-12/schema"
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_multi_word_command_names() {
+        let words = ["apple", "apps", "list"].map(String::from);
+        assert_eq!(handle(&words).unwrap()["title"], "storeops apple apps list");
+        assert!(handle(&["apps".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,447 @@
+//! Screenshot validation and auto-fixing ahead of `sync push`.
+//!
+//! Walks a metadata directory in the `sync pull` layout, checks every image against
+//! the store's size rules and writes fixed copies: alpha is flattened, images are
+//! resized when the aspect ratio already matches and padded when it is close.
+//! Anything else is reported so it can be fixed before an upload is attempted.
+
+use clap::Subcommand;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+use crate::output::stream;
+
+#[derive(Subcommand)]
+pub enum ScreenshotsCommand {
+    /// Validate screenshots and write store-ready copies
+    ///
+    /// Apple screenshots are read from `<locale>/screenshots/<display>/`, Google images
+    /// from `<locale>/images/<imageType>/` and `<locale>/images/<graphic>.png`.
+    ///
+    /// Examples:
+    ///   storeops screenshots prepare --in metadata
+    ///   storeops screenshots prepare --in metadata --out prepared
+    Prepare {
+        /// Metadata directory (as produced by `sync pull`)
+        #[arg(long = "in")]
+        input: PathBuf,
+        /// Directory for fixed copies (validate only when omitted)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Accepted Apple sizes per screenshot directory (either orientation); the first is the
+/// resize target.
+const APPLE_SIZES: &[(&str, &[(u32, u32)])] = &[
+    ("iphone69", &[(1320, 2868), (1290, 2796)]),
+    ("iphone67", &[(1290, 2796), (1320, 2868)]),
+    ("iphone65", &[(1284, 2778), (1242, 2688)]),
+    ("iphone61", &[(1179, 2556), (1170, 2532)]),
+    ("iphone58", &[(1125, 2436)]),
+    ("iphone55", &[(1242, 2208)]),
+    ("ipadPro129", &[(2048, 2732), (2064, 2752)]),
+    ("ipadPro11", &[(1668, 2388), (1640, 2360)]),
+    (
+        "mac",
+        &[(2880, 1800), (2560, 1600), (1440, 900), (1280, 800)],
+    ),
+    ("appletv", &[(3840, 2160), (1920, 1080)]),
+    ("visionpro", &[(3840, 2160)]),
+];
+
+/// Google graphics with exact required sizes.
+const GOOGLE_GRAPHICS: &[(&str, (u32, u32))] = &[
+    ("featureGraphic", (1024, 500)),
+    ("icon", (512, 512)),
+    ("tvBanner", (1280, 720)),
+];
+
+const GOOGLE_SCREENSHOT_TYPES: &[&str] = &[
+    "phoneScreenshots",
+    "sevenInchScreenshots",
+    "tenInchScreenshots",
+    "tvScreenshots",
+    "wearScreenshots",
+];
+
+/// Accepted Google TV screenshot sizes (16:9 landscape); the first is the resize target.
+const GOOGLE_TV_SIZES: &[(u32, u32)] = &[(1920, 1080), (1280, 720), (3840, 2160)];
+
+const GOOGLE_MIN_SIDE: u32 = 320;
+const GOOGLE_MAX_SIDE: u32 = 3840;
+const WEAR_MIN_SIDE: u32 = 384;
+
+/// Largest aspect-ratio difference that is fixed by resizing alone.
+const RESIZE_TOLERANCE: f64 = 0.01;
+/// Largest aspect-ratio difference that is fixed by padding.
+const PAD_TOLERANCE: f64 = 0.15;
+/// Largest upscale applied before an image is considered too small.
+const MAX_UPSCALE: f64 = 1.5;
+
+#[derive(Debug, PartialEq)]
+enum Plan {
+    Ok,
+    Resize(u32, u32),
+    Pad(u32, u32),
+    Unfixable(String),
+}
+
+/// What the image is, based on where it sits in the metadata directory.
+enum Kind {
+    Apple(&'static [(u32, u32)]),
+    GoogleScreenshot(String),
+    GoogleGraphic((u32, u32)),
+}
+
+fn classify(path: &Path) -> Option<Kind> {
+    let parent = path.parent()?;
+    let parent_name = parent.file_name()?.to_str()?;
+    let grandparent_name = parent.parent()?.file_name()?.to_str()?;
+    let stem = path.file_stem()?.to_str()?;
+
+    if grandparent_name == "screenshots" {
+        let (_, sizes) = APPLE_SIZES.iter().find(|(d, _)| *d == parent_name)?;
+        return Some(Kind::Apple(sizes));
+    }
+    if grandparent_name == "images" && GOOGLE_SCREENSHOT_TYPES.contains(&parent_name) {
+        return Some(Kind::GoogleScreenshot(parent_name.to_string()));
+    }
+    if parent_name == "images" {
+        let (_, size) = GOOGLE_GRAPHICS.iter().find(|(n, _)| *n == stem)?;
+        return Some(Kind::GoogleGraphic(*size));
+    }
+    None
+}
+
+/// Plan for a store that accepts only a fixed set of sizes.
+fn plan_fixed(w: u32, h: u32, accepted: &[(u32, u32)], allow_rotation: bool) -> Plan {
+    let rotate = allow_rotation && (w > h) != (accepted[0].0 > accepted[0].1);
+    let accepted: Vec<(u32, u32)> = accepted
+        .iter()
+        .map(|&(aw, ah)| if rotate { (ah, aw) } else { (aw, ah) })
+        .collect();
+    if accepted.contains(&(w, h)) {
+        return Plan::Ok;
+    }
+
+    let (tw, th) = accepted[0];
+    let ratio = w as f64 / h as f64;
+    let target_ratio = tw as f64 / th as f64;
+    let diff = (ratio - target_ratio).abs() / target_ratio;
+    let scale = (tw as f64 / w as f64).min(th as f64 / h as f64);
+    if scale > MAX_UPSCALE {
+        return Plan::Unfixable(format!(
+            "{w}x{h} is too small for {tw}x{th} (would upscale {scale:.1}x)"
+        ));
+    }
+    if diff <= RESIZE_TOLERANCE {
+        Plan::Resize(tw, th)
+    } else if diff <= PAD_TOLERANCE {
+        Plan::Pad(tw, th)
+    } else {
+        Plan::Unfixable(format!("{w}x{h} aspect ratio is too far from {tw}x{th}"))
+    }
+}
+
+/// Plan for Google screenshots, which accept a range of sizes up to a 2:1 ratio
+/// (square for Wear OS, 16:9 landscape for TV).
+fn plan_google_screenshot(kind: &str, w: u32, h: u32) -> Plan {
+    if kind == "tvScreenshots" {
+        return plan_fixed(w, h, GOOGLE_TV_SIZES, false);
+    }
+    let (short, long) = (w.min(h), w.max(h));
+    if kind == "wearScreenshots" {
+        if short < WEAR_MIN_SIDE {
+            return Plan::Unfixable(format!("{w}x{h} is smaller than {WEAR_MIN_SIDE}px"));
+        }
+        return if w == h {
+            Plan::Ok
+        } else {
+            Plan::Pad(long, long)
+        };
+    }
+    if short < GOOGLE_MIN_SIDE {
+        return Plan::Unfixable(format!("{w}x{h} is smaller than {GOOGLE_MIN_SIDE}px"));
+    }
+
+    // Pad the short side up to a 2:1 ratio, then scale down to the maximum side
+    let padded_short = short.max(long.div_ceil(2));
+    let (pw, ph) = if w <= h {
+        (padded_short, long)
+    } else {
+        (long, padded_short)
+    };
+    let scale = (GOOGLE_MAX_SIDE as f64 / long as f64).min(1.0);
+    let (tw, th) = (
+        (pw as f64 * scale).round() as u32,
+        (ph as f64 * scale).round() as u32,
+    );
+    if padded_short != short {
+        Plan::Pad(tw, th)
+    } else if scale < 1.0 {
+        Plan::Resize(tw, th)
+    } else {
+        Plan::Ok
+    }
+}
+
+/// Why Google would reject an image of `image_type`, checked by `google sync push`
+/// before uploading. `None` when it fits or the type has no size rules.
+pub fn google_upload_problem(image_type: &str, path: &Path) -> Option<String> {
+    let (w, h) = match image::image_dimensions(path) {
+        Ok(dimensions) => dimensions,
+        Err(e) => return Some(e.to_string()),
+    };
+    let plan = match GOOGLE_GRAPHICS.iter().find(|(n, _)| *n == image_type) {
+        Some((_, size)) => plan_fixed(w, h, &[*size], false),
+        None if GOOGLE_SCREENSHOT_TYPES.contains(&image_type) => {
+            plan_google_screenshot(image_type, w, h)
+        }
+        None => return None,
+    };
+    match plan {
+        Plan::Ok => None,
+        Plan::Unfixable(reason) => Some(reason),
+        Plan::Resize(tw, th) | Plan::Pad(tw, th) => Some(format!(
+            "{w}x{h} must be {tw}x{th} (`storeops screenshots prepare` can fix it)"
+        )),
+    }
+}
+
+fn flatten(img: &DynamicImage) -> DynamicImage {
+    let rgba = img.to_rgba8();
+    let mut out = RgbaImage::from_pixel(rgba.width(), rgba.height(), Rgba([255, 255, 255, 255]));
+    imageops::overlay(&mut out, &rgba, 0, 0);
+    DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(out).to_rgb8())
+}
+
+/// Fit the image inside `w`x`h` and center it on a canvas filled with its corner color.
+fn pad(img: &DynamicImage, w: u32, h: u32) -> DynamicImage {
+    let fitted = img.resize(w, h, FilterType::Lanczos3).to_rgba8();
+    let fill = img.get_pixel(0, 0);
+    let mut canvas = RgbaImage::from_pixel(w, h, fill);
+    let x = (w - fitted.width()) / 2;
+    let y = (h - fitted.height()) / 2;
+    imageops::overlay(&mut canvas, &fitted, x as i64, y as i64);
+    let canvas = DynamicImage::ImageRgba8(canvas);
+    if img.color().has_alpha() {
+        canvas
+    } else {
+        DynamicImage::ImageRgb8(canvas.to_rgb8())
+    }
+}
+
+fn collect_images(dir: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_images(&path, out)?;
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e.to_ascii_lowercase().as_str(), "png" | "jpg" | "jpeg"))
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+pub async fn handle(cmd: &ScreenshotsCommand) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        ScreenshotsCommand::Prepare { input, out } => prepare(input, out.as_deref()),
+    }
+}
+
+fn prepare(input: &Path, out: Option<&Path>) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut paths = Vec::new();
+    collect_images(input, &mut paths)
+        .map_err(|e| format!("cannot read {}: {e}", input.display()))?;
+    paths.sort();
+
+    let mut files = Vec::new();
+    let mut failed = Vec::new();
+    let mut skipped = 0u32;
+
+    for path in &paths {
+        let rel = path.strip_prefix(input)?;
+        let Some(kind) = classify(path) else {
+            skipped += 1;
+            continue;
+        };
+        let img = match image::open(path) {
+            Ok(img) => img,
+            Err(e) => {
+                let entry = json!({ "file": rel.display().to_string(), "reason": e.to_string() });
+                stream::emit(
+                    "file",
+                    json!({ "action": "failed", "file": rel.display().to_string(), "reason": e.to_string() }),
+                );
+                failed.push(entry);
+                continue;
+            }
+        };
+        let (w, h) = img.dimensions();
+        let (plan, keep_alpha) = match &kind {
+            Kind::Apple(sizes) => (plan_fixed(w, h, sizes, true), false),
+            Kind::GoogleScreenshot(t) => (plan_google_screenshot(t, w, h), false),
+            Kind::GoogleGraphic(size) => (plan_fixed(w, h, &[*size], false), *size == (512, 512)),
+        };
+        let needs_flatten = !keep_alpha && img.color().has_alpha();
+
+        let (action, fixed) = match plan {
+            Plan::Unfixable(reason) => {
+                let entry = json!({ "file": rel.display().to_string(), "reason": reason });
+                stream::emit(
+                    "file",
+                    json!({ "action": "failed", "file": rel.display().to_string(), "reason": reason }),
+                );
+                failed.push(entry);
+                continue;
+            }
+            Plan::Ok if needs_flatten => ("flatten", Some(flatten(&img))),
+            Plan::Ok => ("ok", None),
+            Plan::Resize(tw, th) => {
+                let resized = img.resize_exact(tw, th, FilterType::Lanczos3);
+                let resized = if needs_flatten {
+                    flatten(&resized)
+                } else {
+                    resized
+                };
+                ("resize", Some(resized))
+            }
+            Plan::Pad(tw, th) => {
+                let base = if needs_flatten {
+                    flatten(&img)
+                } else {
+                    img.clone()
+                };
+                ("pad", Some(pad(&base, tw, th)))
+            }
+        };
+        let (fw, fh) = fixed.as_ref().map(|f| f.dimensions()).unwrap_or((w, h));
+
+        if let Some(out) = out {
+            let dest = out.join(rel);
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            match &fixed {
+                Some(f) => f.save(&dest)?,
+                None => {
+                    std::fs::copy(path, &dest)?;
+                }
+            }
+        }
+        if action != "ok" {
+            eprintln!("{}: {action} {w}x{h} -> {fw}x{fh}", rel.display());
+        }
+        let entry = json!({
+            "file": rel.display().to_string(),
+            "action": action,
+            "from": format!("{w}x{h}"),
+            "to": format!("{fw}x{fh}"),
+        });
+        stream::emit("file", entry.clone());
+        files.push(entry);
+    }
+
+    Ok(json!({
+        "valid": failed.is_empty(),
+        "written": out.is_some(),
+        "files": files,
+        "failed": failed,
+        "skipped": skipped,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IPHONE67: &[(u32, u32)] = &[(1290, 2796), (1320, 2868)];
+
+    #[test]
+    fn apple_exact_sizes_pass_in_either_orientation() {
+        assert_eq!(plan_fixed(1320, 2868, IPHONE67, true), Plan::Ok);
+        assert_eq!(plan_fixed(2796, 1290, IPHONE67, true), Plan::Ok);
+    }
+
+    #[test]
+    fn apple_close_ratios_are_resized_or_padded() {
+        // 6.5" screenshots share the 6.7" aspect ratio
+        assert_eq!(
+            plan_fixed(1242, 2688, IPHONE67, true),
+            Plan::Resize(1290, 2796)
+        );
+        assert_eq!(
+            plan_fixed(1170, 2400, IPHONE67, true),
+            Plan::Pad(1290, 2796)
+        );
+        assert!(matches!(
+            plan_fixed(1242, 2208, IPHONE67, true),
+            Plan::Unfixable(_)
+        ));
+        assert!(matches!(
+            plan_fixed(645, 1398, IPHONE67, true),
+            Plan::Unfixable(_)
+        ));
+    }
+
+    #[test]
+    fn google_graphics_must_match_exactly() {
+        assert_eq!(plan_fixed(1024, 500, &[(1024, 500)], false), Plan::Ok);
+        assert_eq!(
+            plan_fixed(2048, 1000, &[(1024, 500)], false),
+            Plan::Resize(1024, 500)
+        );
+        assert!(matches!(
+            plan_fixed(500, 1024, &[(1024, 500)], false),
+            Plan::Unfixable(_)
+        ));
+    }
+
+    #[test]
+    fn google_screenshots_are_padded_to_two_to_one_and_capped() {
+        assert_eq!(
+            plan_google_screenshot("phoneScreenshots", 1080, 1920),
+            Plan::Ok
+        );
+        assert_eq!(
+            plan_google_screenshot("phoneScreenshots", 1000, 2400),
+            Plan::Pad(1200, 2400)
+        );
+        assert_eq!(
+            plan_google_screenshot("tenInchScreenshots", 2560, 7680),
+            Plan::Pad(1920, 3840)
+        );
+        assert_eq!(
+            plan_google_screenshot("phoneScreenshots", 4000, 6000),
+            Plan::Resize(2560, 3840)
+        );
+        assert!(matches!(
+            plan_google_screenshot("phoneScreenshots", 200, 400),
+            Plan::Unfixable(_)
+        ));
+        assert_eq!(
+            plan_google_screenshot("wearScreenshots", 400, 450),
+            Plan::Pad(450, 450)
+        );
+    }
+
+    #[test]
+    fn google_tv_screenshots_are_landscape_sixteen_by_nine() {
+        assert_eq!(plan_google_screenshot("tvScreenshots", 1280, 720), Plan::Ok);
+        assert_eq!(
+            plan_google_screenshot("tvScreenshots", 2560, 1440),
+            Plan::Resize(1920, 1080)
+        );
+        assert!(matches!(
+            plan_google_screenshot("tvScreenshots", 1080, 1920),
+            Plan::Unfixable(_)
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+//! Cross-store app lookup by name.
+//!
+//! Apple lists every app the API key can see, so matching is done on the full list.
+//! Google Play has no endpoint to list a developer account's apps; the candidates
+//! are the default packages of the configured Google profiles plus any `--package`
+//! given, each checked with the selected Google profile's credentials.
+
+use serde_json::{json, Value};
+
+use super::apple::reviews::next_cursor;
+use super::Cli;
+use crate::config::profiles::Store;
+
+fn matches(query: &str, candidates: &[Option<&str>]) -> bool {
+    let query = query.to_lowercase();
+    candidates
+        .iter()
+        .flatten()
+        .any(|c| c.to_lowercase().contains(&query))
+}
+
+/// Apple half: every app whose name or bundle ID contains the query.
+async fn apple_search(
+    query: &str,
+    profile: Option<&str>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = super::apple::client_for_profile(profile)?;
+    let mut apps = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut params = vec![("limit", "200"), ("fields[apps]", "name,bundleId,sku")];
+        if let Some(c) = &cursor {
+            params.push(("cursor", c.as_str()));
+        }
+        let page: Value = client.get("/apps", &params).await?;
+        apps.extend(
+            page["data"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|a| {
+                    let attrs = &a["attributes"];
+                    matches(query, &[attrs["name"].as_str(), attrs["bundleId"].as_str()])
+                })
+                .cloned(),
+        );
+        cursor = page["links"]["next"].as_str().and_then(next_cursor);
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    let mut results = Vec::new();
+    for app in apps {
+        let app_id = app["id"].as_str().unwrap_or_default();
+        let live: Value = client
+            .get(
+                &format!("/apps/{app_id}/appStoreVersions"),
+                &[("filter[appStoreState]", "READY_FOR_SALE"), ("limit", "1")],
+            )
+            .await
+            .unwrap_or(Value::Null);
+        results.push(json!({
+            "app_id": app_id,
+            "name": app["attributes"]["name"],
+            "bundle_id": app["attributes"]["bundleId"],
+            "sku": app["attributes"]["sku"],
+            "live_version": live["data"][0]["attributes"]["versionString"],
+        }));
+    }
+    Ok(json!(results))
+}
+
+/// Title, default language and live version name of one package, read from a throwaway edit.
+async fn google_app(
+    client: &crate::api::google_client::GoogleClient,
+    package_name: &str,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let edit_id = &client.open_edit(package_name).await?;
+    let base = format!("/{package_name}/edits/{edit_id}");
+    let info = async {
+        let details: Value = client.get(&format!("{base}/details"), &[]).await?;
+        let language = details["defaultLanguage"].as_str().unwrap_or("en-US");
+        let listing: Value = client
+            .get(&format!("{base}/listings/{language}"), &[])
+            .await
+            .unwrap_or(Value::Null);
+        let production: Value = client
+            .get(&format!("{base}/tracks/production"), &[])
+            .await
+            .unwrap_or(Value::Null);
+        let live = super::google::apps::latest_release(&production).cloned();
+        Ok::<_, Box<dyn std::error::Error>>(json!({
+            "package_name": package_name,
+            "title": listing["title"],
+            "default_language": language,
+            "live_version": live.as_ref().map(|r| r["name"].clone()),
+            "live_version_codes": live.as_ref().map(|r| r["versionCodes"].clone()),
+        }))
+    }
+    .await;
+    client.discard_edit(package_name, edit_id).await;
+    info
+}
+
+/// Google half: configured packages whose name or store title contains the query.
+async fn google_search(
+    query: &str,
+    extra_packages: &[String],
+    profile: Option<&str>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let config = crate::config::Config::load()?;
+    let mut packages: Vec<String> = config
+        .profiles
+        .values()
+        .filter(|p| matches!(p.store, Store::Google))
+        .filter_map(|p| p.defaults.default_package.clone())
+        .chain(extra_packages.iter().cloned())
+        .collect();
+    packages.sort();
+    packages.dedup();
+
+    let client = super::google::client_for_profile(profile).await?;
+    let mut results = Vec::new();
+    for package_name in &packages {
+        match google_app(&client, package_name).await {
+            Ok(app) if matches(query, &[Some(package_name.as_str()), app["title"].as_str()]) => {
+                results.push(app)
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("google: skipping {package_name}: {e}"),
+        }
+    }
+    Ok(json!(results))
+}
+
+pub async fn handle(
+    query: &str,
+    packages: &[String],
+    apple_profile: Option<&str>,
+    google_profile: Option<&str>,
+    cli: &Cli,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let apple_profile = apple_profile.or(cli.profile.as_deref());
+    let google_profile = google_profile.or(cli.profile.as_deref());
+    Ok(super::join_stores(
+        Some(apple_search(query, apple_profile)),
+        Some(google_search(query, packages, google_profile)),
+    )
+    .await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_name_or_identifier_case_insensitively() {
+        assert!(matches(
+            "photo",
+            &[Some("PhotoBooth Pro"), Some("com.example.pb")]
+        ));
+        assert!(matches("example.pb", &[None, Some("com.example.pb")]));
+        assert!(!matches("camera", &[Some("PhotoBooth Pro"), None]));
+    }
+}
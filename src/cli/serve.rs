@@ -0,0 +1,611 @@
+//! Local HTTP API over the command surface.
+//!
+//! `storeops serve` accepts the same commands as the CLI over HTTP/1.1 and answers
+//! with JSON, so dashboards and chat bots can drive the stores without spawning a
+//! process per call. Commands run with the config's profiles and share connections
+//! and tokens like `batch`.
+//!
+//! - `GET /health` reports the server version.
+//! - `GET|POST /<command words>` runs a command, e.g. `GET /apple/apps/list?limit=5`.
+//!   Query parameters (and, for POST, a JSON object body) become flags: `key=value`
+//!   is `--key value`, `key=true` is `--key`, and `arg=value` is a positional
+//!   argument. GET only runs read-only commands.
+//! - `POST /run` with `{"args": ["apple", "apps", "list"]}` runs the words as given.
+//!
+//! Every request needs the bearer token (generated and printed at startup when none
+//! is configured). Requests from browsers (an `Origin` header) or for another `Host`
+//! are refused, and only output flags and the command's own non-file flags are
+//! accepted, so a web page cannot drive the server or make it write local files.
+
+use super::Cli;
+use clap::{CommandFactory, ValueHint};
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde_json::{json, Map, Value};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+/// Top-level commands that must not be started over HTTP: they run other commands,
+/// replace the binary, or write credentials and profiles.
+const DENIED_COMMANDS: &[&str] = &["batch", "serve", "update", "auth"];
+/// Global flags a request may set; the others (`--record`, `--replay`, ...) read or
+/// write local files.
+const ALLOWED_GLOBAL_FLAGS: &[&str] = &[
+    "json",
+    "output",
+    "pretty",
+    "raw",
+    "flatten",
+    "profile",
+    "all-profiles",
+    "profiles",
+    "limit",
+    "next",
+    "paginate",
+    "timeout",
+];
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    authorization: Option<String>,
+    origin: Option<String>,
+    host: Option<String>,
+    body: Vec<u8>,
+}
+
+/// What every request is checked against before it runs.
+struct Guard {
+    token: String,
+    /// Accepted `Host` header values: the bind address and, on loopback, its aliases.
+    hosts: Vec<String>,
+}
+
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn error(status: u16, message: impl std::fmt::Display) -> Self {
+        Response {
+            status,
+            body: json!({ "ok": false, "error": message.to_string() }),
+        }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Read one request: the head up to the blank line, then `Content-Length` bytes.
+async fn read_request(stream: &mut TcpStream) -> Result<Request, Response> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let head_len = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return Err(Response::error(413, "request head too large"));
+        }
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| Response::error(400, e))?;
+        if n == 0 {
+            return Err(Response::error(400, "connection closed mid-request"));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut parsed = httparse::Request::new(&mut headers);
+    parsed
+        .parse(&buf[..head_len])
+        .map_err(|e| Response::error(400, e))?;
+    let header = |name: &str| {
+        parsed
+            .headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+            .and_then(|h| std::str::from_utf8(h.value).ok())
+            .map(String::from)
+    };
+    let content_length: usize = header("content-length")
+        .map(|v| v.trim().parse())
+        .transpose()
+        .map_err(|_| Response::error(400, "invalid Content-Length"))?
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return Err(Response::error(413, "request body too large"));
+    }
+    let target = parsed.path.unwrap_or("/").to_string();
+    let method = parsed.method.unwrap_or("GET").to_string();
+    let authorization = header("authorization");
+    let origin = header("origin");
+    let host = header("host");
+
+    let mut body = buf[head_len..].to_vec();
+    while body.len() < content_length {
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| Response::error(400, e))?;
+        if n == 0 {
+            return Err(Response::error(400, "connection closed mid-body"));
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    let url = reqwest::Url::parse(&format!("http://localhost{target}"))
+        .map_err(|e| Response::error(400, e))?;
+    Ok(Request {
+        method,
+        path: url.path().to_string(),
+        query: url.query_pairs().into_owned().collect(),
+        authorization,
+        origin,
+        host,
+        body,
+    })
+}
+
+/// Flag and positional arguments from query parameters and a JSON object body.
+fn flag_args(query: &[(String, String)], body: &Map<String, Value>) -> Vec<String> {
+    let mut flags = Vec::new();
+    let mut positional = Vec::new();
+    let mut push = |key: &str, value: &Value| {
+        let text = match value {
+            Value::Bool(false) | Value::Null => return,
+            Value::Bool(true) => None,
+            Value::String(s) if s == "true" && key != "arg" => None,
+            Value::String(s) if s == "false" && key != "arg" => return,
+            Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        };
+        if key == "arg" {
+            positional.extend(text);
+        } else {
+            flags.push(format!("--{}", key.replace('_', "-")));
+            flags.extend(text);
+        }
+    };
+    for (key, value) in query {
+        push(key, &Value::String(value.clone()));
+    }
+    for (key, value) in body {
+        match value {
+            Value::Array(items) => items.iter().for_each(|item| push(key, item)),
+            value => push(key, value),
+        }
+    }
+    positional.extend(flags);
+    positional
+}
+
+/// The command words a request asks to run.
+fn command_words(request: &Request) -> Result<Vec<String>, Response> {
+    let body: Value = if request.body.is_empty() {
+        json!({})
+    } else {
+        serde_json::from_slice(&request.body)
+            .map_err(|e| Response::error(400, format!("invalid JSON body: {e}")))?
+    };
+
+    if request.path == "/run" {
+        if request.method != "POST" {
+            return Err(Response::error(405, "use POST for /run"));
+        }
+        return body["args"]
+            .as_array()
+            .and_then(|args| {
+                args.iter()
+                    .map(|a| a.as_str().map(String::from))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| Response::error(400, "body must be {\"args\": [\"...\"]}"));
+    }
+
+    let words: Vec<String> = request
+        .path
+        .split('/')
+        .filter(|w| !w.is_empty())
+        .map(String::from)
+        .collect();
+    if !matches!(request.method.as_str(), "GET" | "POST") {
+        return Err(Response::error(405, "use GET or POST"));
+    }
+    let empty = Map::new();
+    let body = match &body {
+        Value::Object(map) => map,
+        _ => &empty,
+    };
+    let mut args = words;
+    args.extend(flag_args(&request.query, body));
+    Ok(args)
+}
+
+/// Whether a path-valued flag: its type is a path, or its name says it holds one.
+fn takes_path(arg: &clap::Arg) -> bool {
+    matches!(
+        arg.get_value_hint(),
+        ValueHint::AnyPath | ValueHint::FilePath | ValueHint::DirPath | ValueHint::ExecutablePath
+    ) || arg
+        .get_long()
+        .is_some_and(|long| ["file", "dir", "path"].iter().any(|s| long.ends_with(s)))
+}
+
+/// Whether a request may pass `--<name>` to the command at `path`: an allowed global
+/// flag, or one of the command's own flags that does not name a local file.
+fn allowed_flag(path: &[String], name: &str) -> bool {
+    if ALLOWED_GLOBAL_FLAGS.contains(&name) {
+        return true;
+    }
+    let mut command = Cli::command();
+    for word in path {
+        let Some(sub) = command.find_subcommand(word) else {
+            return false;
+        };
+        command = sub.clone();
+    }
+    let allowed = command
+        .get_arguments()
+        .any(|arg| arg.get_long() == Some(name) && !arg.is_global_set() && !takes_path(arg));
+    allowed
+}
+
+/// The subcommand path clap parses the words to, e.g. `apple apps delete` for
+/// `apple apps delete list` (where `list` is a positional). Missing required
+/// arguments are ignored so the check still sees which command was asked for.
+fn command_path(words: &[String]) -> Vec<String> {
+    let args = std::iter::once("storeops").chain(words.iter().map(String::as_str));
+    let Ok(matches) = Cli::command()
+        .ignore_errors(true)
+        .try_get_matches_from(args)
+    else {
+        return Vec::new();
+    };
+    let mut path = Vec::new();
+    let mut current = &matches;
+    while let Some((name, sub)) = current.subcommand() {
+        path.push(name.to_string());
+        current = sub;
+    }
+    path
+}
+
+/// Check the command the words parse to, not the words themselves: denied commands,
+/// GET on a command that may write, and flags outside the allow-list.
+fn check_command(method: &str, words: &[String]) -> Result<(), Response> {
+    let path = command_path(words);
+    let Some(first) = path.first() else {
+        return Err(Response::error(
+            400,
+            format!("'{}' is not a storeops command", words.join(" ")),
+        ));
+    };
+    if DENIED_COMMANDS.contains(&first.as_str()) {
+        return Err(Response::error(
+            403,
+            format!("`{first}` is not available over HTTP"),
+        ));
+    }
+    let leaf = path.last().map(String::as_str).unwrap_or_default();
+    if method == "GET" && !super::fanout::is_read_only(leaf) {
+        return Err(Response::error(
+            405,
+            format!("'{}' may modify store data; use POST", path.join(" ")),
+        ));
+    }
+    for word in words {
+        let Some(flag) = word.strip_prefix("--") else {
+            continue;
+        };
+        let name = flag.split('=').next().unwrap_or_default();
+        if !allowed_flag(&path, name) {
+            return Err(Response::error(
+                403,
+                format!("`--{name}` is not available over HTTP"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Refuse requests without the token, from a browser, or addressed to another host.
+fn check_caller(request: &Request, guard: &Guard) -> Result<(), Response> {
+    if request.authorization.as_deref() != Some(&format!("Bearer {}", guard.token)) {
+        return Err(Response::error(401, "missing or wrong bearer token"));
+    }
+    if request.origin.is_some() {
+        return Err(Response::error(
+            403,
+            "browser requests (with an Origin header) are not accepted",
+        ));
+    }
+    match &request.host {
+        Some(host) if guard.hosts.iter().any(|h| h.eq_ignore_ascii_case(host)) => Ok(()),
+        _ => Err(Response::error(
+            403,
+            "Host does not match the listen address",
+        )),
+    }
+}
+
+async fn respond(request: Request, guard: &Guard) -> Response {
+    if let Err(response) = check_caller(&request, guard) {
+        return response;
+    }
+    if request.path == "/health" {
+        return Response {
+            status: 200,
+            body: json!({ "ok": true, "version": env!("CARGO_PKG_VERSION") }),
+        };
+    }
+    let words = match command_words(&request) {
+        Ok(words) => words,
+        Err(response) => return response,
+    };
+    if words.is_empty() {
+        return Response::error(404, "no command in path");
+    }
+    if let Err(response) = check_command(&request.method, &words) {
+        return response;
+    }
+
+    eprintln!("{} {}", request.method, words.join(" "));
+    match super::batch::run_words(words).await {
+        Ok(result) => Response {
+            status: 200,
+            body: json!({ "ok": true, "result": result }),
+        },
+        Err(e) => match e.downcast_ref::<crate::output::Failure>() {
+            Some(crate::output::Failure(result)) => Response {
+                status: 422,
+                body: json!({ "ok": false, "result": result }),
+            },
+            None => Response::error(500, e),
+        },
+    }
+}
+
+async fn serve_connection(mut stream: TcpStream, guard: Rc<Guard>) {
+    let response = match read_request(&mut stream).await {
+        Ok(request) => respond(request, &guard).await,
+        Err(response) => response,
+    };
+    let body = response.body.to_string();
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        body.len()
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+pub async fn handle(
+    host: &str,
+    port: u16,
+    token: Option<&str>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let env_token = std::env::var("STOREOPS_SERVE_TOKEN").ok();
+    let token = match token.or(env_token.as_deref()).filter(|t| !t.is_empty()) {
+        Some(token) => token.to_string(),
+        None => {
+            let token = generate_token()?;
+            eprintln!("Token: {token}");
+            token
+        }
+    };
+    let loopback = host == "localhost"
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback());
+    let listener = TcpListener::bind((host, port)).await?;
+    let addr = listener.local_addr()?;
+    let mut hosts = vec![format!("{host}:{}", addr.port()), addr.to_string()];
+    if loopback {
+        for alias in ["localhost", "127.0.0.1", "[::1]"] {
+            hosts.push(format!("{alias}:{}", addr.port()));
+        }
+    }
+    let guard = Rc::new(Guard { token, hosts });
+    eprintln!("Listening on http://{addr}");
+
+    // Connections are served concurrently on this task; commands are not `Send`
+    let mut connections: FuturesUnordered<Pin<Box<dyn Future<Output = ()>>>> =
+        FuturesUnordered::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                connections.push(Box::pin(serve_connection(stream, guard.clone())));
+            }
+            Some(()) = connections.next(), if !connections.is_empty() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    Ok(json!({ "status": "stopped" }))
+}
+
+/// A random 128-bit bearer token, hex-encoded.
+fn generate_token() -> Result<String, Box<dyn std::error::Error>> {
+    use ring::rand::SecureRandom;
+    let mut bytes = [0u8; 16];
+    ring::rand::SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "could not generate a serve token")?;
+    Ok(hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, query: &[(&str, &str)], body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            authorization: Some("Bearer t".to_string()),
+            origin: None,
+            host: Some("127.0.0.1:8080".to_string()),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn maps_paths_and_parameters_to_command_words() {
+        let get = request(
+            "GET",
+            "/apple/builds/list",
+            &[
+                ("arg", "com.example.app"),
+                ("limit", "5"),
+                ("pretty", "true"),
+            ],
+            "",
+        );
+        assert_eq!(
+            command_words(&get).unwrap_or_else(|_| panic!("rejected")),
+            [
+                "apple",
+                "builds",
+                "list",
+                "com.example.app",
+                "--limit",
+                "5",
+                "--pretty"
+            ]
+        );
+
+        let post = request(
+            "POST",
+            "/google/listings/import",
+            &[],
+            r#"{"arg": "com.example.app", "file": "listings.csv", "dry_run": true}"#,
+        );
+        assert_eq!(
+            command_words(&post).unwrap_or_else(|_| panic!("rejected")),
+            [
+                "google",
+                "listings",
+                "import",
+                "com.example.app",
+                "--dry-run",
+                "--file",
+                "listings.csv"
+            ]
+        );
+    }
+
+    fn checked(request: &Request) -> Option<u16> {
+        let words = command_words(request).unwrap_or_else(|_| panic!("rejected"));
+        check_command(&request.method, &words)
+            .err()
+            .map(|r| r.status)
+    }
+
+    #[test]
+    fn get_is_limited_to_read_only_commands() {
+        let get = request("GET", "/apple/apps/delete", &[], "");
+        assert_eq!(checked(&get), Some(405));
+        // "list" is a positional of `delete` here, not the command
+        let get = request("GET", "/apple/apps/delete/list", &[], "");
+        assert_eq!(checked(&get), Some(405));
+        let get = request("GET", "/apple/builds/list", &[("arg", "123")], "");
+        assert_eq!(checked(&get), None);
+        let run = request(
+            "POST",
+            "/run",
+            &[],
+            r#"{"args": ["apple", "apps", "list"]}"#,
+        );
+        assert_eq!(
+            command_words(&run).unwrap_or_else(|_| panic!("rejected")),
+            ["apple", "apps", "list"]
+        );
+    }
+
+    #[test]
+    fn denies_commands_by_parsed_path_and_flags_outside_the_allow_list() {
+        let run = request("POST", "/run", &[], r#"{"args": ["--pretty", "update"]}"#);
+        assert_eq!(checked(&run), Some(403));
+        let run = request("POST", "/run", &[], r#"{"args": ["auth", "rotate"]}"#);
+        assert_eq!(checked(&run), Some(403));
+        let get = request(
+            "GET",
+            "/apple/builds/list",
+            &[("arg", "123"), ("record", "/tmp/x")],
+            "",
+        );
+        assert_eq!(checked(&get), Some(403));
+        let post = request(
+            "POST",
+            "/google/listings/import",
+            &[],
+            r#"{"arg": "com.example.app", "file": "listings.csv"}"#,
+        );
+        assert_eq!(checked(&post), Some(403));
+        let post = request(
+            "POST",
+            "/apple/apps/list",
+            &[("limit", "5"), ("profile", "main")],
+            "",
+        );
+        assert_eq!(checked(&post), None);
+    }
+
+    #[test]
+    fn rejects_browser_and_foreign_host_requests() {
+        let guard = Guard {
+            token: "t".to_string(),
+            hosts: vec!["127.0.0.1:8080".to_string()],
+        };
+        let ok = request("GET", "/health", &[], "");
+        assert!(check_caller(&ok, &guard).is_ok());
+
+        let mut no_token = request("GET", "/health", &[], "");
+        no_token.authorization = None;
+        assert_eq!(
+            check_caller(&no_token, &guard).err().map(|r| r.status),
+            Some(401)
+        );
+        let mut browser = request("GET", "/health", &[], "");
+        browser.origin = Some("https://example.com".to_string());
+        assert_eq!(
+            check_caller(&browser, &guard).err().map(|r| r.status),
+            Some(403)
+        );
+        let mut rebound = request("GET", "/health", &[], "");
+        rebound.host = Some("evil.example:8080".to_string());
+        assert_eq!(
+            check_caller(&rebound, &guard).err().map(|r| r.status),
+            Some(403)
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Cross-store release status.
+//!
+//! Reports the newest App Store version and the Google Play track releases of an app
+//! in one response. Both stores are queried concurrently, and a failing store is
+//! reported next to the other's data instead of failing the whole command.
+
+use serde_json::{json, Value};
+
+use super::apple::{resolve, sync};
+use super::Cli;
+
+/// Apple half: the editable (or newest) version and its review state.
+async fn apple_status(
+    app: Option<&str>,
+    profile: Option<&str>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = super::apple::client_for_profile(profile)?;
+    let app_id = resolve::app_id(app, &client).await?;
+    let version = sync::get_editable_version(&app_id, None, &client).await?;
+    let attrs = &version["attributes"];
+    Ok(json!({
+        "app_id": app_id,
+        "version": attrs["versionString"],
+        "platform": attrs["platform"],
+        "state": attrs["appStoreState"],
+    }))
+}
+
+/// Google half: every track with its releases.
+async fn google_status(
+    package: Option<&str>,
+    profile: Option<&str>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = super::google::client_for_profile(profile).await?;
+    let package_name = &client.package_name(package)?;
+    let edit_id = &client.open_edit(package_name).await?;
+    let tracks: Result<Value, _> = client
+        .get(&format!("/{package_name}/edits/{edit_id}/tracks"), &[])
+        .await;
+    client.discard_edit(package_name, edit_id).await;
+    let tracks: Vec<Value> = tracks?["tracks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|t| {
+            let releases: Vec<Value> = t["releases"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|r| {
+                    json!({
+                        "name": r["name"],
+                        "status": r["status"],
+                        "version_codes": r["versionCodes"],
+                        "user_fraction": r["userFraction"],
+                    })
+                })
+                .collect();
+            json!({ "track": t["track"], "releases": releases })
+        })
+        .collect();
+    Ok(json!({ "package_name": package_name, "tracks": tracks }))
+}
+
+pub async fn handle(
+    app: Option<&str>,
+    package: Option<&str>,
+    apple_profile: Option<&str>,
+    google_profile: Option<&str>,
+    cli: &Cli,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    // Query only the stores asked about, or both when nothing store-specific was given
+    let wants_apple = app.is_some() || apple_profile.is_some();
+    let wants_google = package.is_some() || google_profile.is_some();
+    let both = !wants_apple && !wants_google;
+    let apple_profile = apple_profile.or(cli.profile.as_deref());
+    let google_profile = google_profile.or(cli.profile.as_deref());
+
+    Ok(super::join_stores(
+        (both || wants_apple).then(|| apple_status(app, apple_profile)),
+        (both || wants_google).then(|| google_status(package, google_profile)),
+    )
+    .await)
+}
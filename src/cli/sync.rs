@@ -0,0 +1,648 @@
+//! Store-agnostic helpers for `sync pull`/`sync push` metadata directories.
+//!
+//! Both stores lay metadata out as `<dir>/<locale>/<field>.txt`, so these commands
+//! work on a directory pulled from either one.
+
+use clap::{Subcommand, ValueEnum};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::api::translator::Translator;
+use crate::config::translation::Provider;
+use crate::config::Config;
+use crate::output::stream;
+
+#[derive(Subcommand)]
+pub enum SyncCommand {
+    /// Fill missing locale files by machine-translating a source locale
+    ///
+    /// Only files that do not exist in the target locale are written. URL fields are
+    /// copied verbatim; translations over the store's length limit are truncated
+    /// with a warning.
+    ///
+    /// Examples:
+    ///   storeops sync translate --metadata-dir metadata --from en-US --to de-DE,fr-FR
+    ///   storeops sync translate --metadata-dir metadata --from en-US --to ja --provider openai --dry-run
+    Translate {
+        /// Directory produced by `apple sync pull` or `google sync pull`
+        #[arg(long)]
+        metadata_dir: PathBuf,
+        /// Source locale directory
+        #[arg(long)]
+        from: String,
+        /// Target locales (comma-separated)
+        #[arg(long, value_delimiter = ',', required = true)]
+        to: Vec<String>,
+        /// Translation backend (defaults to `provider` under [translation] in the config)
+        #[arg(long)]
+        provider: Option<ProviderArg>,
+        /// List the files that would be written without calling the provider
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Compare a metadata directory against the live store listing
+    ///
+    /// Pulls the current metadata text files into a temporary directory and reports
+    /// every `<locale>/<field>.txt` that differs. Trailing whitespace is ignored, and
+    /// an empty local file matches a field that is unset in the store. Screenshots
+    /// are not compared.
+    ///
+    /// Examples:
+    ///   storeops sync check --metadata-dir metadata/apple --app com.example.app
+    ///   storeops sync check --metadata-dir metadata/google --package com.example.app --fail-on-drift
+    Check {
+        /// Directory produced by `apple sync pull` or `google sync pull`
+        #[arg(long)]
+        metadata_dir: PathBuf,
+        /// Bundle ID of the App Store app to compare against
+        #[arg(long, required_unless_present = "package", conflicts_with = "package")]
+        app: Option<String>,
+        /// Package name of the Google Play app to compare against
+        #[arg(long)]
+        package: Option<String>,
+        /// Exit non-zero when any file differs from the store
+        #[arg(long)]
+        fail_on_drift: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ProviderArg {
+    Deepl,
+    Openai,
+}
+
+impl From<ProviderArg> for Provider {
+    fn from(arg: ProviderArg) -> Self {
+        match arg {
+            ProviderArg::Deepl => Provider::Deepl,
+            ProviderArg::Openai => Provider::Openai,
+        }
+    }
+}
+
+/// Files copied as-is rather than translated.
+const VERBATIM_FILES: &[&str] = &[
+    "marketing_url.txt",
+    "support_url.txt",
+    "privacy_url.txt",
+    "video.txt",
+];
+
+/// Character limit enforced by the store for a metadata file.
+fn field_limit(file_name: &str) -> Option<usize> {
+    match file_name {
+        "name.txt" | "subtitle.txt" | "title.txt" => Some(30),
+        "short_description.txt" => Some(80),
+        "keywords.txt" => Some(100),
+        "promotional_text.txt" => Some(170),
+        "description.txt" | "release_notes.txt" | "full_description.txt" => Some(4000),
+        _ => None,
+    }
+}
+
+/// Cut `text` to `limit` characters at a natural boundary.
+///
+/// Keyword lists drop whole keywords; other text is cut at the last whitespace.
+fn truncate_to_limit(text: &str, limit: usize, is_keywords: bool) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(limit).collect();
+    let sep = if is_keywords {
+        cut.rfind(',')
+    } else {
+        cut.rfind(char::is_whitespace)
+    };
+    match sep {
+        Some(i) if i > 0 => cut[..i].trim_end().to_string(),
+        _ => cut,
+    }
+}
+
+pub async fn handle(
+    cmd: &SyncCommand,
+    cli: &crate::cli::Cli,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        SyncCommand::Translate {
+            metadata_dir,
+            from,
+            to,
+            provider,
+            dry_run,
+        } => translate(metadata_dir, from, to, *provider, *dry_run).await,
+        SyncCommand::Check {
+            metadata_dir,
+            app,
+            package,
+            fail_on_drift,
+        } => {
+            check(
+                metadata_dir,
+                app.as_deref(),
+                package.as_deref(),
+                *fail_on_drift,
+                cli,
+            )
+            .await
+        }
+    }
+}
+
+async fn check(
+    metadata_dir: &Path,
+    app: Option<&str>,
+    package: Option<&str>,
+    fail_on_drift: bool,
+    cli: &crate::cli::Cli,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    if !metadata_dir.is_dir() {
+        return Err(format!("metadata directory not found: {}", metadata_dir.display()).into());
+    }
+
+    let remote_dir = std::env::temp_dir().join(format!("storeops-check-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&remote_dir).await;
+    let pulled = match (app, package) {
+        (Some(bundle_id), _) => {
+            let client = super::apple::client(cli)?;
+            super::apple::sync::pull_metadata(bundle_id, &remote_dir, &client).await
+        }
+        (None, Some(package)) => {
+            let client = super::google::client(cli).await?;
+            super::google::sync::pull_metadata(package, &remote_dir, &client).await
+        }
+        (None, None) => Err("pass --app or --package".into()),
+    };
+    let drift = pulled.and_then(|_| diff_dirs(metadata_dir, &remote_dir).map_err(Into::into));
+    let _ = fs::remove_dir_all(&remote_dir).await;
+    let drift = drift?;
+
+    let mut result = json!({
+        "store": if app.is_some() { "apple" } else { "google" },
+        "app": app.or(package),
+        "metadata_dir": metadata_dir.display().to_string(),
+        "in_sync": drift.is_empty(),
+        "drifted": drift.len(),
+        "drift": drift,
+    });
+    if drift.is_empty() || !fail_on_drift {
+        return Ok(result);
+    }
+    let problems: Vec<String> = drift
+        .iter()
+        .map(|d| {
+            format!(
+                "{}: {}",
+                d["file"].as_str().unwrap_or_default(),
+                d["status"].as_str().unwrap_or_default()
+            )
+        })
+        .collect();
+    result["problems"] = json!(problems);
+    result["error"] = json!(format!(
+        "{} metadata file(s) differ from the store",
+        drift.len()
+    ));
+    Err(Box::new(crate::output::Failure(result)))
+}
+
+/// Read every `<locale>/<field>.txt` under `dir`, keyed by relative path.
+///
+/// Hidden entries (such as the pull manifest) and non-text files are skipped, and
+/// content is compared with trailing whitespace removed.
+fn read_metadata(dir: &Path) -> std::io::Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    for locale in std::fs::read_dir(dir)? {
+        let locale = locale?;
+        let locale_name = locale.file_name().to_string_lossy().to_string();
+        if locale_name.starts_with('.') || !locale.file_type()?.is_dir() {
+            continue;
+        }
+        for file in std::fs::read_dir(locale.path())? {
+            let file = file?;
+            let name = file.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') || !name.ends_with(".txt") || !file.file_type()?.is_file() {
+                continue;
+            }
+            let content = std::fs::read_to_string(file.path())?;
+            files.insert(
+                format!("{locale_name}/{name}"),
+                content.trim_end().to_string(),
+            );
+        }
+    }
+    Ok(files)
+}
+
+/// Compare a local metadata directory with a freshly pulled one.
+///
+/// Each entry is `changed`, `local_only` (not set in the store) or `remote_only`
+/// (missing from the repo). Empty files count as unset.
+fn diff_dirs(local: &Path, remote: &Path) -> std::io::Result<Vec<Value>> {
+    let local = read_metadata(local)?;
+    let remote = read_metadata(remote)?;
+    let keys: BTreeSet<&String> = local.keys().chain(remote.keys()).collect();
+
+    let mut drift = Vec::new();
+    for key in keys {
+        let ours = local.get(key).filter(|s| !s.is_empty());
+        let theirs = remote.get(key).filter(|s| !s.is_empty());
+        let entry = match (ours, theirs) {
+            (Some(a), Some(b)) if a == b => continue,
+            (None, None) => continue,
+            (Some(a), Some(b)) => {
+                let line = a
+                    .lines()
+                    .zip(b.lines())
+                    .position(|(x, y)| x != y)
+                    .unwrap_or_else(|| a.lines().count().min(b.lines().count()));
+                json!({
+                    "file": key,
+                    "status": "changed",
+                    "local_chars": a.chars().count(),
+                    "remote_chars": b.chars().count(),
+                    "first_diff_line": line + 1,
+                })
+            }
+            (Some(a), None) => json!({
+                "file": key,
+                "status": "local_only",
+                "local_chars": a.chars().count(),
+            }),
+            (None, Some(b)) => json!({
+                "file": key,
+                "status": "remote_only",
+                "remote_chars": b.chars().count(),
+            }),
+        };
+        drift.push(entry);
+    }
+    Ok(drift)
+}
+
+async fn translate(
+    metadata_dir: &Path,
+    from: &str,
+    to: &[String],
+    provider: Option<ProviderArg>,
+    dry_run: bool,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let source_dir = metadata_dir.join(from);
+    if !source_dir.is_dir() {
+        return Err(format!(
+            "source locale directory not found: {}",
+            source_dir.display()
+        )
+        .into());
+    }
+
+    let mut sources: Vec<(String, String)> = Vec::new();
+    let mut entries = fs::read_dir(&source_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.ends_with(".txt") || !entry.file_type().await?.is_file() {
+            continue;
+        }
+        let content = fs::read_to_string(entry.path()).await?;
+        if !content.trim().is_empty() {
+            sources.push((name, content));
+        }
+    }
+    sources.sort();
+
+    let translator = if dry_run {
+        None
+    } else {
+        let translation = Config::load()
+            .unwrap_or_default()
+            .translation
+            .unwrap_or_default();
+        let provider = provider
+            .map(Provider::from)
+            .or(translation.provider)
+            .ok_or("no translation provider; pass --provider or set it in the config")?;
+        Some(Translator::new(provider, &translation)?)
+    };
+
+    let mut written = Vec::new();
+    let mut warnings = Vec::new();
+    let mut skipped = 0u32;
+
+    for locale in to {
+        let target_dir = metadata_dir.join(locale);
+        for (name, content) in &sources {
+            let target = target_dir.join(name);
+            if target.exists() {
+                skipped += 1;
+                continue;
+            }
+            let verbatim = VERBATIM_FILES.contains(&name.as_str());
+            let Some(translator) = &translator else {
+                written.push(json!({
+                    "locale": locale,
+                    "file": name,
+                    "action": if verbatim { "copy" } else { "translate" },
+                }));
+                continue;
+            };
+
+            let mut text = if verbatim {
+                content.clone()
+            } else {
+                eprintln!("Translating {from}/{name} -> {locale}...");
+                translator.translate(content.trim(), from, locale).await?
+            };
+            let mut truncated = false;
+            if let Some(limit) = field_limit(name) {
+                let len = text.chars().count();
+                if len > limit {
+                    text = truncate_to_limit(&text, limit, name == "keywords.txt");
+                    truncated = true;
+                    let warning =
+                        format!("{locale}/{name}: {len} chars exceeds limit of {limit}, truncated");
+                    eprintln!("Warning: {warning}");
+                    warnings.push(warning);
+                }
+            }
+
+            fs::create_dir_all(&target_dir).await?;
+            fs::write(&target, &text).await?;
+            let entry = json!({
+                "locale": locale,
+                "file": name,
+                "action": if verbatim { "copy" } else { "translate" },
+                "chars": text.chars().count(),
+                "truncated": truncated,
+            });
+            stream::emit("file", entry.clone());
+            written.push(entry);
+        }
+    }
+
+    Ok(json!({
+        "status": if dry_run { "dry_run" } else { "ok" },
+        "from": from,
+        "written": written,
+        "skipped_existing": skipped,
+        "warnings": warnings,
+    }))
+}
+
+/// Split the fields read from a locale directory into those that differ from the
+/// store's current `remote` attributes and the names of those that match.
+///
+/// Fields without a local file are left out of both. Values are compared with
+/// surrounding whitespace removed, as pull writes them; with no remote resource
+/// (`Value::Null`) every field counts as changed.
+pub fn changed_fields(
+    local: &[(&str, Option<String>)],
+    remote: &Value,
+) -> (Map<String, Value>, Vec<String>) {
+    let mut changed = Map::new();
+    let mut unchanged = Vec::new();
+    for (field, value) in local {
+        let Some(value) = value else { continue };
+        if remote[*field].as_str().map(str::trim) == Some(value.trim()) {
+            unchanged.push(field.to_string());
+        } else {
+            changed.insert(field.to_string(), json!(value));
+        }
+    }
+    (changed, unchanged)
+}
+
+/// Per-field push outcome for the result: `updated` or `unchanged`.
+pub fn field_report(changed: &Map<String, Value>, unchanged: &[String]) -> Map<String, Value> {
+    let updated = changed.keys().map(|f| (f.clone(), json!("updated")));
+    let same = unchanged.iter().map(|f| (f.clone(), json!("unchanged")));
+    updated.chain(same).collect()
+}
+
+/// Whether `sync pull/push --locales` selects `locale`.
+///
+/// Patterns are locale codes, or globs where `*` matches any run of characters
+/// (`es-*`), compared case-insensitively. No patterns select every locale.
+pub fn locale_selected(patterns: &[String], locale: &str) -> bool {
+    patterns.is_empty()
+        || patterns
+            .iter()
+            .any(|p| glob_match(&p.to_lowercase(), &locale.to_lowercase()))
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(text) = text.strip_prefix(prefix) else {
+        return false;
+    };
+    // Try every split point for the part after the first `*`
+    (0..=text.len())
+        .filter(|&i| text.is_char_boundary(i))
+        .any(|i| glob_match(rest, &text[i..]))
+}
+
+/// File in a screenshot directory listing its images in upload order.
+pub const ORDER_FILE: &str = "order.txt";
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+/// Images of a screenshot directory in upload order.
+///
+/// Files named in `order.txt` (one per line, `#` comments allowed) come first, in
+/// that order; the rest follow sorted by name with digit runs compared as numbers,
+/// so `2.png` sorts before `10.png`. A name in `order.txt` without a matching file
+/// is an error rather than a silently shorter upload.
+pub fn ordered_images(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut images: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|e| {
+                    IMAGE_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str())
+                })
+        })
+        .collect();
+    images.sort_by_key(|path| natural_key(&path.file_name().unwrap_or_default().to_string_lossy()));
+
+    let order_path = dir.join(ORDER_FILE);
+    if !order_path.is_file() {
+        return Ok(images);
+    }
+    let mut ordered = Vec::new();
+    for name in std::fs::read_to_string(&order_path)?
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+    {
+        let position = images
+            .iter()
+            .position(|p| p.file_name().is_some_and(|n| n == name))
+            .ok_or_else(|| {
+                format!(
+                    "{} lists {name}, which does not exist",
+                    order_path.display()
+                )
+            })?;
+        ordered.push(images.remove(position));
+    }
+    ordered.extend(images);
+    Ok(ordered)
+}
+
+/// Record the store's screenshot order next to pulled files.
+pub fn write_order(dir: &Path, files: &[String]) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let mut content = files.join("\n");
+    content.push('\n');
+    std::fs::write(dir.join(ORDER_FILE), content)
+}
+
+/// Sort key comparing runs of digits by value: `shot2` < `shot10`.
+fn natural_key(name: &str) -> Vec<(u64, String)> {
+    let mut key = Vec::new();
+    let mut rest = name;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits > 0 {
+            key.push((rest[..digits].parse().unwrap_or(u64::MAX), String::new()));
+            rest = &rest[digits..];
+        } else {
+            let text = rest
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            key.push((0, rest[..text].to_lowercase()));
+            rest = &rest[text..];
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_patterns_match_codes_and_globs() {
+        let patterns = vec!["en-US".to_string(), "es-*".to_string()];
+        assert!(locale_selected(&patterns, "en-US"));
+        assert!(locale_selected(&patterns, "en-us"));
+        assert!(locale_selected(&patterns, "es-MX"));
+        assert!(locale_selected(&patterns, "es-ES"));
+        assert!(!locale_selected(&patterns, "en-GB"));
+        assert!(!locale_selected(&patterns, "es"));
+        assert!(locale_selected(&[], "de-DE"));
+        assert!(locale_selected(&["*-CH".to_string()], "fr-CH"));
+        assert!(!locale_selected(&["zh-*".to_string()], "ja"));
+    }
+
+    #[test]
+    fn truncates_keywords_on_commas() {
+        let keywords = "alpha,beta,gamma";
+        assert_eq!(truncate_to_limit(keywords, 12, true), "alpha,beta");
+        assert_eq!(truncate_to_limit(keywords, 100, true), keywords);
+    }
+
+    #[test]
+    fn diff_reports_changed_and_missing_files() {
+        let root = std::env::temp_dir().join(format!("storeops-diff-{}", std::process::id()));
+        let (local, remote) = (root.join("local"), root.join("remote"));
+        for (dir, file, text) in [
+            (&local, "en-US/name.txt", "My App\n"),
+            (&remote, "en-US/name.txt", "My App"),
+            (&local, "en-US/description.txt", "One\nTwo"),
+            (&remote, "en-US/description.txt", "One\nThree"),
+            (&local, "de-DE/name.txt", "Meine App"),
+            (&local, "en-US/subtitle.txt", ""),
+            (&remote, "fr-FR/name.txt", "Mon App"),
+            (&remote, ".storeops-pull.json", "{}"),
+        ] {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        }
+
+        let drift = diff_dirs(&local, &remote).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let summary: Vec<(&str, &str)> = drift
+            .iter()
+            .map(|d| (d["file"].as_str().unwrap(), d["status"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("de-DE/name.txt", "local_only"),
+                ("en-US/description.txt", "changed"),
+                ("fr-FR/name.txt", "remote_only"),
+            ]
+        );
+        assert_eq!(drift[1]["first_diff_line"], 2);
+    }
+
+    #[test]
+    fn only_differing_fields_are_changed() {
+        let local = [
+            ("description", Some("Same text".to_string())),
+            ("keywords", Some("new,words".to_string())),
+            ("whatsNew", None),
+            ("supportUrl", Some("https://example.com".to_string())),
+        ];
+        let remote = json!({
+            "description": "Same text\n",
+            "keywords": "old,words",
+            "whatsNew": "Bug fixes",
+            "supportUrl": null,
+        });
+        let (changed, unchanged) = changed_fields(&local, &remote);
+        assert_eq!(
+            Value::Object(changed),
+            json!({ "keywords": "new,words", "supportUrl": "https://example.com" })
+        );
+        assert_eq!(unchanged, ["description"]);
+        assert_eq!(changed_fields(&local, &Value::Null).0.len(), 3);
+    }
+
+    #[test]
+    fn orders_images_by_order_file_then_number() {
+        let dir = std::env::temp_dir().join(format!("storeops-order-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["1.png", "2.png", "10.png", "9.jpg", "notes.md"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let names = |dir: &Path| -> Vec<String> {
+            ordered_images(dir)
+                .unwrap()
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+        assert_eq!(names(&dir), ["1.png", "2.png", "9.jpg", "10.png"]);
+
+        write_order(&dir, &["10.png".to_string(), "2.png".to_string()]).unwrap();
+        assert_eq!(names(&dir), ["10.png", "2.png", "1.png", "9.jpg"]);
+
+        std::fs::write(dir.join(ORDER_FILE), "# hero first\n11.png\n").unwrap();
+        let missing = ordered_images(&dir).unwrap_err().to_string();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(missing.ends_with("lists 11.png, which does not exist"));
+    }
+
+    #[test]
+    fn truncates_text_on_whitespace() {
+        assert_eq!(
+            truncate_to_limit("Ein sehr langer Titel", 12, false),
+            "Ein sehr"
+        );
+        assert_eq!(field_limit("short_description.txt"), Some(80));
+        assert_eq!(field_limit("support_url.txt"), None);
+    }
+}
@@ -0,0 +1,115 @@
+//! `{{name}}` placeholders in release notes templates and metadata text files.
+//!
+//! Names may be padded with spaces (`{{ date }}`). A name without a value is an
+//! error rather than being left in place, so typos don't ship to a store listing.
+
+use std::collections::BTreeMap;
+
+/// Substitute `{{name}}` placeholders; unknown names are an error so typos don't ship.
+pub fn render(template: &str, vars: &BTreeMap<String, String>) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or("unclosed '{{' in template")?;
+        let name = after[..end].trim();
+        let value = vars
+            .get(name)
+            .ok_or_else(|| format!("unknown template variable '{name}'"))?;
+        out.push_str(value);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out.trim().to_string())
+}
+
+/// Parse `--var KEY=VALUE` arguments.
+pub fn parse_vars(pairs: &[String]) -> Result<BTreeMap<String, String>, String> {
+    pairs
+        .iter()
+        .map(|pair| {
+            let (k, v) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("--var must be KEY=VALUE, got '{pair}'"))?;
+            Ok((k.trim().to_string(), v.to_string()))
+        })
+        .collect()
+}
+
+/// Today's date (UTC) as `{{date}}` expands to it.
+pub fn today() -> String {
+    chrono::Utc::now().format("%Y-%m-%d").to_string()
+}
+
+/// Expand placeholders in metadata fields read from a locale directory before push.
+///
+/// Errors name the field whose text could not be rendered.
+pub fn expand_fields(
+    fields: &mut [(&str, Option<String>)],
+    vars: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    for (field, value) in fields.iter_mut() {
+        if let Some(text) = value.as_mut().filter(|t| t.contains("{{")) {
+            *text = render(text, vars).map_err(|e| format!("{field}: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn renders_placeholders_with_optional_spaces() {
+        let out = render(
+            "Version {{version}} ({{ date }})\n",
+            &vars(&[("version", "1.4.0"), ("date", "2026-10-16")]),
+        )
+        .unwrap();
+        assert_eq!(out, "Version 1.4.0 (2026-10-16)");
+    }
+
+    #[test]
+    fn rejects_unknown_and_unclosed_placeholders() {
+        assert!(render("{{versoin}}", &vars(&[("version", "1")]))
+            .unwrap_err()
+            .contains("versoin"));
+        assert!(render("{{version", &vars(&[("version", "1")])).is_err());
+    }
+
+    #[test]
+    fn expands_only_fields_with_placeholders() {
+        let mut fields = [
+            ("description", Some("{{app_name}} {{version}}".to_string())),
+            ("keywords", Some("a,b".to_string())),
+            ("whatsNew", None),
+        ];
+        expand_fields(
+            &mut fields,
+            &vars(&[("app_name", "Notes"), ("version", "2.0")]),
+        )
+        .unwrap();
+        assert_eq!(fields[0].1.as_deref(), Some("Notes 2.0"));
+        assert_eq!(fields[1].1.as_deref(), Some("a,b"));
+
+        let mut fields = [("supportUrl", Some("{{site}}/help".to_string()))];
+        let err = expand_fields(&mut fields, &vars(&[])).unwrap_err();
+        assert!(err.starts_with("supportUrl:"));
+    }
+
+    #[test]
+    fn parses_var_pairs() {
+        let parsed = parse_vars(&["site=https://example.com/a=b".to_string()]).unwrap();
+        assert_eq!(parsed["site"], "https://example.com/a=b");
+        assert!(parse_vars(&["oops".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,251 @@
+//! Cross-store version helpers.
+//!
+//! Reads the current live version from either store and computes the next one,
+//! optionally creating it on App Store Connect.
+
+use clap::Subcommand;
+use serde_json::{json, Value};
+
+use super::StoreArg;
+
+#[derive(Subcommand)]
+pub enum VersionCommand {
+    /// Compute (and optionally create) the next version from the live one
+    ///
+    /// Examples:
+    ///   storeops version bump --store apple --app 1234567890 --minor
+    ///   storeops version bump --store apple --app 1234567890 --patch --create
+    ///   storeops version bump --store google --app com.example.app --major
+    Bump {
+        /// Store to read the current version from
+        #[arg(long)]
+        store: StoreArg,
+        /// Apple app ID or Google package name
+        #[arg(long)]
+        app: String,
+        /// Bump the major component (1.2.3 -> 2.0.0)
+        #[arg(long, conflicts_with_all = ["minor", "patch"])]
+        major: bool,
+        /// Bump the minor component (1.2.3 -> 1.3.0)
+        #[arg(long, conflicts_with_all = ["major", "patch"])]
+        minor: bool,
+        /// Bump the patch component (1.2.3 -> 1.2.4, default)
+        #[arg(long, conflicts_with_all = ["major", "minor"])]
+        patch: bool,
+        /// Apple: platform of the version
+        #[arg(long, default_value = "ios")]
+        platform: String,
+        /// Google: track to read the live release from
+        #[arg(long, default_value = "production")]
+        track: String,
+        /// Apple: create the bumped version in App Store Connect
+        #[arg(long)]
+        create: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum BumpPart {
+    Major,
+    Minor,
+    Patch,
+}
+
+/// Split a version into its dotted core and whether it had a pre-release suffix.
+///
+/// Build metadata (`+...`) is dropped: "2.0.0-rc1+42" -> ("2.0.0", true).
+fn split_suffix(version: &str) -> (&str, bool) {
+    let version = version.trim();
+    let version = version.split_once('+').map_or(version, |(v, _)| v);
+    match version.split_once('-') {
+        Some((core, _)) => (core, true),
+        None => (version, false),
+    }
+}
+
+/// The version without pre-release or build metadata suffixes, as App Store
+/// Connect accepts it: "2.0.0-rc1" -> "2.0.0".
+pub fn release_version(version: &str) -> &str {
+    split_suffix(version).0
+}
+
+/// Bump a dotted version string.
+///
+/// `Major` and `Minor` reset the lower components. `Patch` increments the last
+/// component, so "1.2.3" -> "1.2.4" and "1.2" -> "1.3". A pre-release is bumped
+/// to the release it leads up to when that release is at least the requested
+/// bump, as in semver: "2.0.0-rc1" -> "2.0.0" for any part, "1.3.0-beta" ->
+/// "1.3.0" for minor or patch but "2.0.0" for major. Build metadata is dropped.
+pub fn bump_version(version: &str, part: BumpPart) -> String {
+    let (core, pre_release) = split_suffix(version);
+    let parts: Vec<&str> = core.split('.').collect();
+    let num = |i: usize| -> u32 { parts.get(i).and_then(|p| p.parse().ok()).unwrap_or(0) };
+    match part {
+        BumpPart::Major if pre_release && num(1) == 0 && num(2) == 0 => {
+            format!("{}.0.0", num(0))
+        }
+        BumpPart::Major => format!("{}.0.0", num(0) + 1),
+        BumpPart::Minor if pre_release && num(2) == 0 => format!("{}.{}.0", num(0), num(1)),
+        BumpPart::Minor => format!("{}.{}.0", num(0), num(1) + 1),
+        BumpPart::Patch if pre_release => core.to_string(),
+        BumpPart::Patch => match parts.len() {
+            3 => format!("{}.{}.{}", parts[0], parts[1], num(2) + 1),
+            2 => format!("{}.{}", parts[0], num(1) + 1),
+            _ => format!("{}.1", core),
+        },
+    }
+}
+
+pub async fn handle(
+    cmd: &VersionCommand,
+    cli: &crate::cli::Cli,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        VersionCommand::Bump {
+            store,
+            app,
+            major,
+            minor,
+            patch: _,
+            platform,
+            track,
+            create,
+        } => {
+            let part = if *major {
+                BumpPart::Major
+            } else if *minor {
+                BumpPart::Minor
+            } else {
+                BumpPart::Patch
+            };
+
+            match store {
+                StoreArg::Apple => {
+                    let client = crate::cli::apple::client(cli)?;
+                    let platform = platform.to_uppercase();
+                    let versions: Value = client
+                        .get(
+                            &format!("/apps/{app}/appStoreVersions"),
+                            &[
+                                ("filter[appStoreState]", "READY_FOR_SALE"),
+                                ("filter[platform]", platform.as_str()),
+                                ("limit", "1"),
+                            ],
+                        )
+                        .await?;
+                    let current = versions["data"][0]["attributes"]["versionString"]
+                        .as_str()
+                        .ok_or("no live version found")?
+                        .to_string();
+                    let next = bump_version(&current, part);
+
+                    let mut result = json!({
+                        "store": "apple",
+                        "app": app,
+                        "current_version": current,
+                        "next_version": next,
+                    });
+                    if *create {
+                        let body = json!({
+                            "data": {
+                                "type": "appStoreVersions",
+                                "attributes": {
+                                    "versionString": next,
+                                    "platform": platform
+                                },
+                                "relationships": {
+                                    "app": {
+                                        "data": { "type": "apps", "id": app }
+                                    }
+                                }
+                            }
+                        });
+                        let created = client.post("/appStoreVersions", &body).await?;
+                        result["created"] = created["data"].clone();
+                    }
+                    Ok(result)
+                }
+                StoreArg::Google => {
+                    if *create {
+                        return Err(
+                            "--create is Apple-only; Google versions come from the uploaded bundle"
+                                .into(),
+                        );
+                    }
+                    let client = crate::cli::google::client(cli).await?;
+                    let edit_id = &client.open_edit(app).await?;
+                    let track_info: Result<Value, _> = client
+                        .get(&format!("/{app}/edits/{edit_id}/tracks/{track}"), &[])
+                        .await;
+                    client.discard_edit(app, edit_id).await;
+                    let track_info = track_info?;
+
+                    let release = track_info["releases"]
+                        .as_array()
+                        .and_then(|r| {
+                            r.iter()
+                                .find(|r| r["status"].as_str() == Some("completed"))
+                                .or_else(|| r.first())
+                        })
+                        .ok_or_else(|| format!("no releases on track '{track}'"))?;
+                    let current = release["name"]
+                        .as_str()
+                        .ok_or("live release has no version name")?
+                        .to_string();
+                    let next = bump_version(&current, part);
+
+                    Ok(json!({
+                        "store": "google",
+                        "app": app,
+                        "track": track,
+                        "current_version": current,
+                        "next_version": next,
+                        "version_codes": release["versionCodes"],
+                    }))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bump_major_resets_lower_components() {
+        assert_eq!(bump_version("1.2.3", BumpPart::Major), "2.0.0");
+        assert_eq!(bump_version("4", BumpPart::Major), "5.0.0");
+    }
+
+    #[test]
+    fn bump_minor_resets_patch() {
+        assert_eq!(bump_version("1.2.3", BumpPart::Minor), "1.3.0");
+        assert_eq!(bump_version("1.2", BumpPart::Minor), "1.3.0");
+    }
+
+    #[test]
+    fn bump_patch_increments_last_component() {
+        assert_eq!(bump_version("1.2.3", BumpPart::Patch), "1.2.4");
+        assert_eq!(bump_version("1.2", BumpPart::Patch), "1.3");
+        assert_eq!(bump_version("7", BumpPart::Patch), "7.1");
+    }
+
+    #[test]
+    fn bump_pre_release_to_its_release() {
+        assert_eq!(bump_version("2.0.0-rc1", BumpPart::Patch), "2.0.0");
+        assert_eq!(bump_version("2.0.0-rc1", BumpPart::Minor), "2.0.0");
+        assert_eq!(bump_version("2.0.0-rc1", BumpPart::Major), "2.0.0");
+        assert_eq!(bump_version("1.3.0-beta.2", BumpPart::Minor), "1.3.0");
+        assert_eq!(bump_version("1.3.0-beta.2", BumpPart::Major), "2.0.0");
+        assert_eq!(bump_version("1.3.1-beta", BumpPart::Minor), "1.4.0");
+    }
+
+    #[test]
+    fn bump_drops_build_metadata() {
+        assert_eq!(bump_version("1.2.3+456", BumpPart::Patch), "1.2.4");
+        assert_eq!(bump_version("2.0.0-rc1+build.7", BumpPart::Patch), "2.0.0");
+        assert_eq!(release_version("2.0.0-rc1+build.7"), "2.0.0");
+        assert_eq!(release_version("1.4.0"), "1.4.0");
+    }
+}
@@ -0,0 +1,497 @@
+//! Local warehouse of App Store sales and finance reports.
+//!
+//! `warehouse sync` downloads daily sales summaries and monthly financial reports
+//! into a SQLite database under the data directory. Reports already in the database
+//! are not downloaded again, so a scheduled sync only fetches what is new, and
+//! months of data can be analysed with `warehouse query` in plain SQL.
+
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use clap::Subcommand;
+use rusqlite::{params, types::ValueRef, Connection, OpenFlags, OptionalExtension};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::apple::analytics::parse_report;
+use super::Cli;
+use crate::api::apple_client::AppleClient;
+use crate::config::Config;
+
+/// App Store Connect keeps daily sales reports for a year
+const SALES_RETENTION_DAYS: i64 = 365;
+/// Reports this recent may not be published yet; older missing reports (days
+/// without sales) are recorded as empty so they are not requested again.
+const PUBLISH_DELAY_DAYS: i64 = 3;
+
+/// `(column, report header, numeric)` of the `sales` table
+const SALES_COLUMNS: &[(&str, &str, bool)] = &[
+    ("begin_date", "Begin Date", false),
+    ("end_date", "End Date", false),
+    ("sku", "SKU", false),
+    ("title", "Title", false),
+    ("version", "Version", false),
+    ("product_type", "Product Type Identifier", false),
+    ("apple_id", "Apple Identifier", false),
+    ("parent_id", "Parent Identifier", false),
+    ("country", "Country Code", false),
+    ("device", "Device", false),
+    ("units", "Units", true),
+    ("developer_proceeds", "Developer Proceeds", true),
+    ("proceeds_currency", "Currency of Proceeds", false),
+    ("customer_price", "Customer Price", true),
+    ("customer_currency", "Customer Currency", false),
+    ("subscription", "Subscription", false),
+    ("period", "Period", false),
+];
+
+/// `(column, report header, numeric)` of the `finance` table
+const FINANCE_COLUMNS: &[(&str, &str, bool)] = &[
+    ("start_date", "Start Date", false),
+    ("end_date", "End Date", false),
+    ("sku", "Vendor Identifier", false),
+    ("title", "Title", false),
+    ("apple_id", "Apple Identifier", false),
+    ("product_type", "Product Type Identifier", false),
+    ("country", "Country Of Sale", false),
+    ("quantity", "Quantity", true),
+    ("partner_share", "Partner Share", true),
+    ("extended_partner_share", "Extended Partner Share", true),
+    ("partner_share_currency", "Partner Share Currency", false),
+    ("customer_price", "Customer Price", true),
+    ("customer_currency", "Customer Currency", false),
+    ("sale_or_return", "Sale or Return", false),
+];
+
+#[derive(Subcommand)]
+pub enum WarehouseCommand {
+    /// Download new sales (and optionally finance) reports into the warehouse
+    ///
+    /// Daily sales summaries go into the `sales` table and monthly financial reports
+    /// into `finance`; `synced_reports` lists what has been downloaded. Run it on a
+    /// schedule to keep the warehouse current.
+    ///
+    /// Examples:
+    ///   storeops warehouse sync --vendor-number 85012345
+    ///   storeops warehouse sync --vendor-number 85012345 --finance --since 2024-01-01
+    Sync {
+        /// Vendor number from Payments and Financial Reports
+        #[arg(long)]
+        vendor_number: String,
+        /// First day to download (default: a year ago, the oldest Apple keeps)
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        since: Option<NaiveDate>,
+        /// Also download monthly financial reports (all regions)
+        #[arg(long)]
+        finance: bool,
+        /// Database file (default: warehouse.db in the data directory)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Run a read-only SQL query against the warehouse
+    ///
+    /// Examples:
+    ///   storeops warehouse query "SELECT title, SUM(units) FROM sales GROUP BY title"
+    ///   storeops warehouse query "SELECT substr(report_date, 1, 7) AS month, SUM(units * developer_proceeds) AS proceeds FROM sales GROUP BY month"
+    Query {
+        /// SQL statement
+        sql: String,
+        /// Database file (default: warehouse.db in the data directory)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum Report {
+    Sales,
+    Finance,
+}
+
+impl Report {
+    fn table(self) -> &'static str {
+        match self {
+            Report::Sales => "sales",
+            Report::Finance => "finance",
+        }
+    }
+
+    fn columns(self) -> &'static [(&'static str, &'static str, bool)] {
+        match self {
+            Report::Sales => SALES_COLUMNS,
+            Report::Finance => FINANCE_COLUMNS,
+        }
+    }
+
+    /// Download the report for `date` (a day for sales, a fiscal month for finance).
+    async fn fetch(
+        self,
+        vendor_number: &str,
+        date: &str,
+        client: &AppleClient,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        match self {
+            Report::Sales => {
+                client
+                    .get_report(
+                        "/salesReports",
+                        &[
+                            ("filter[vendorNumber]", vendor_number),
+                            ("filter[frequency]", "DAILY"),
+                            ("filter[reportType]", "SALES"),
+                            ("filter[reportSubType]", "SUMMARY"),
+                            ("filter[version]", "1_0"),
+                            ("filter[reportDate]", date),
+                        ],
+                    )
+                    .await
+            }
+            Report::Finance => {
+                client
+                    .get_report(
+                        "/financeReports",
+                        &[
+                            ("filter[vendorNumber]", vendor_number),
+                            ("filter[reportType]", "FINANCIAL"),
+                            ("filter[regionCode]", "ZZ"),
+                            ("filter[reportDate]", date),
+                        ],
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+fn default_db() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(Config::data_dir()
+        .ok_or("cannot determine data directory")?
+        .join("warehouse.db"))
+}
+
+/// Create the warehouse tables if they do not exist.
+fn init(conn: &Connection) -> rusqlite::Result<()> {
+    let mut sql = String::from(
+        "CREATE TABLE IF NOT EXISTS synced_reports (
+            report TEXT NOT NULL,
+            vendor_number TEXT NOT NULL,
+            report_date TEXT NOT NULL,
+            rows INTEGER NOT NULL,
+            synced_at TEXT NOT NULL,
+            PRIMARY KEY (report, vendor_number, report_date)
+        );",
+    );
+    for report in [Report::Sales, Report::Finance] {
+        let columns: Vec<String> = report
+            .columns()
+            .iter()
+            .map(|(name, _, numeric)| format!("{name} {}", if *numeric { "REAL" } else { "TEXT" }))
+            .collect();
+        let table = report.table();
+        sql.push_str(&format!(
+            "CREATE TABLE IF NOT EXISTS {table} (
+                vendor_number TEXT NOT NULL,
+                report_date TEXT NOT NULL,
+                {}
+            );
+            CREATE INDEX IF NOT EXISTS {table}_report_date ON {table} (vendor_number, report_date);",
+            columns.join(",\n                ")
+        ));
+    }
+    conn.execute_batch(&sql)
+}
+
+/// `MM/DD/YYYY` report dates as `YYYY-MM-DD`, so they sort and compare in SQL.
+fn iso_date(value: &str) -> Option<String> {
+    NaiveDate::parse_from_str(value, "%m/%d/%Y")
+        .ok()
+        .map(|d| d.to_string())
+}
+
+/// Replace the rows of one report, returning how many were stored.
+///
+/// Lines without a valid first date (such as finance report totals) are skipped.
+fn store(
+    conn: &mut Connection,
+    report: Report,
+    vendor_number: &str,
+    date: &str,
+    rows: &[HashMap<String, String>],
+) -> rusqlite::Result<usize> {
+    let table = report.table();
+    let columns = report.columns();
+    let tx = conn.transaction()?;
+    tx.execute(
+        &format!("DELETE FROM {table} WHERE vendor_number = ?1 AND report_date = ?2"),
+        params![vendor_number, date],
+    )?;
+    let names: Vec<&str> = columns.iter().map(|(name, _, _)| *name).collect();
+    let placeholders: Vec<String> = (1..=columns.len() + 2).map(|i| format!("?{i}")).collect();
+    let mut stored = 0;
+    {
+        let mut insert = tx.prepare(&format!(
+            "INSERT INTO {table} (vendor_number, report_date, {}) VALUES ({})",
+            names.join(", "),
+            placeholders.join(", ")
+        ))?;
+        for row in rows {
+            let cell = |header: &str| row.get(header).map(String::as_str).unwrap_or_default();
+            let Some(first_date) = iso_date(cell(columns[0].1)) else {
+                continue;
+            };
+            let mut values: Vec<rusqlite::types::Value> =
+                vec![vendor_number.to_string().into(), date.to_string().into()];
+            for (i, (_, header, numeric)) in columns.iter().enumerate() {
+                let text = cell(header);
+                values.push(if i == 0 {
+                    first_date.clone().into()
+                } else if *numeric {
+                    text.parse::<f64>()
+                        .map(rusqlite::types::Value::from)
+                        .unwrap_or(rusqlite::types::Value::Null)
+                } else if let Some(iso) = iso_date(text) {
+                    iso.into()
+                } else if text.is_empty() {
+                    rusqlite::types::Value::Null
+                } else {
+                    text.to_string().into()
+                });
+            }
+            insert.execute(rusqlite::params_from_iter(values))?;
+            stored += 1;
+        }
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO synced_reports (report, vendor_number, report_date, rows, synced_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            table,
+            vendor_number,
+            date,
+            stored as i64,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    tx.commit()?;
+    Ok(stored)
+}
+
+fn is_synced(
+    conn: &Connection,
+    report: Report,
+    vendor_number: &str,
+    date: &str,
+) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT 1 FROM synced_reports WHERE report = ?1 AND vendor_number = ?2 AND report_date = ?3",
+        params![report.table(), vendor_number, date],
+        |_| Ok(()),
+    )
+    .optional()
+    .map(|row| row.is_some())
+}
+
+/// Download every report in `dates` not yet in the warehouse.
+async fn sync_reports(
+    conn: &mut Connection,
+    report: Report,
+    vendor_number: &str,
+    dates: &[(String, NaiveDate)],
+    client: &AppleClient,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let published_before = Utc::now().date_naive() - Duration::days(PUBLISH_DELAY_DAYS);
+    let (mut fetched, mut stored, mut skipped, mut pending) = (0, 0, 0, Vec::new());
+    for (date, last_day) in dates {
+        if is_synced(conn, report, vendor_number, date)? {
+            skipped += 1;
+            continue;
+        }
+        eprintln!("Downloading {} report {date}...", report.table());
+        match report.fetch(vendor_number, date, client).await? {
+            Some(bytes) => {
+                stored += store(conn, report, vendor_number, date, &parse_report(&bytes)?)?;
+                fetched += 1;
+            }
+            None if *last_day < published_before => {
+                store(conn, report, vendor_number, date, &[])?;
+                fetched += 1;
+            }
+            None => pending.push(date.clone()),
+        }
+    }
+    Ok(json!({
+        "downloaded": fetched,
+        "rows": stored,
+        "already_synced": skipped,
+        "not_yet_available": pending,
+    }))
+}
+
+/// Sales report days from `since` to yesterday, with the day each covers.
+fn sales_dates(since: NaiveDate, today: NaiveDate) -> Vec<(String, NaiveDate)> {
+    since
+        .iter_days()
+        .take_while(|d| *d < today)
+        .map(|d| (d.to_string(), d))
+        .collect()
+}
+
+/// Finance report months from the month of `since` to last month, with each
+/// month's last day.
+fn finance_months(since: NaiveDate, today: NaiveDate) -> Vec<(String, NaiveDate)> {
+    let this_month = today.with_day(1).unwrap_or(today);
+    let mut month = since.with_day(1).unwrap_or(since);
+    let mut months = Vec::new();
+    while month < this_month {
+        let next = (month + Duration::days(32))
+            .with_day(1)
+            .unwrap_or(this_month);
+        months.push((month.format("%Y-%m").to_string(), next - Duration::days(1)));
+        month = next;
+    }
+    months
+}
+
+/// Rows of a query as JSON objects keyed by column name.
+fn run_query(conn: &Connection, sql: &str) -> rusqlite::Result<Vec<Value>> {
+    let mut statement = conn.prepare(sql)?;
+    let names: Vec<String> = statement
+        .column_names()
+        .iter()
+        .map(|n| n.to_string())
+        .collect();
+    let rows = statement.query_map([], |row| {
+        let mut object = Map::new();
+        for (i, name) in names.iter().enumerate() {
+            let value = match row.get_ref(i)? {
+                ValueRef::Null => Value::Null,
+                ValueRef::Integer(n) => json!(n),
+                ValueRef::Real(f) => json!(f),
+                ValueRef::Text(t) => json!(String::from_utf8_lossy(t)),
+                ValueRef::Blob(b) => json!({ "bytes": b.len() }),
+            };
+            object.insert(name.clone(), value);
+        }
+        Ok(Value::Object(object))
+    })?;
+    rows.collect()
+}
+
+pub async fn handle(
+    cmd: &WarehouseCommand,
+    cli: &Cli,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match cmd {
+        WarehouseCommand::Sync {
+            vendor_number,
+            since,
+            finance,
+            db,
+        } => {
+            let path = match db {
+                Some(path) => path.clone(),
+                None => default_db()?,
+            };
+            if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir)?;
+            }
+            let mut conn = Connection::open(&path)?;
+            init(&conn)?;
+
+            let client = super::apple::client_for_profile(cli.profile.as_deref())?;
+            let today = Utc::now().date_naive();
+            let oldest = today - Duration::days(SALES_RETENTION_DAYS);
+            let since = since.unwrap_or(oldest);
+            let sales = sync_reports(
+                &mut conn,
+                Report::Sales,
+                vendor_number,
+                &sales_dates(since.max(oldest), today),
+                &client,
+            )
+            .await?;
+            let mut result = json!({
+                "database": path.display().to_string(),
+                "vendor_number": vendor_number,
+                "sales": sales,
+            });
+            if *finance {
+                result["finance"] = sync_reports(
+                    &mut conn,
+                    Report::Finance,
+                    vendor_number,
+                    &finance_months(since, today),
+                    &client,
+                )
+                .await?;
+            }
+            Ok(result)
+        }
+        WarehouseCommand::Query { sql, db } => {
+            let path = match db {
+                Some(path) => path.clone(),
+                None => default_db()?,
+            };
+            if !path.exists() {
+                return Err(format!(
+                    "no warehouse at {}; run `storeops warehouse sync` first",
+                    path.display()
+                )
+                .into());
+            }
+            let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            Ok(Value::Array(run_query(&conn, sql)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_reports_idempotently_and_queries_them() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init(&conn).unwrap();
+        let rows = parse_report(
+            b"Begin Date\tSKU\tTitle\tProduct Type Identifier\tUnits\tDeveloper Proceeds\tCountry Code\n\
+              03/01/2025\tphoto\tPhotoLab\t1\t12\t0\tUS\n\
+              03/01/2025\tphoto.pro\tPhotoLab Pro\tIA1\t3\t0.70\tDE\n",
+        )
+        .unwrap();
+        for _ in 0..2 {
+            assert_eq!(
+                store(&mut conn, Report::Sales, "8501", "2025-03-01", &rows).unwrap(),
+                2
+            );
+        }
+        assert!(is_synced(&conn, Report::Sales, "8501", "2025-03-01").unwrap());
+        assert!(!is_synced(&conn, Report::Sales, "8501", "2025-03-02").unwrap());
+
+        let result = run_query(
+            &conn,
+            "SELECT begin_date, SUM(units) AS units, SUM(units * developer_proceeds) AS proceeds
+             FROM sales GROUP BY begin_date",
+        )
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0]["begin_date"], "2025-03-01");
+        assert_eq!(result[0]["units"], 15.0);
+        assert!((result[0]["proceeds"].as_f64().unwrap() - 2.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lists_report_days_and_months_to_sync() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let since = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        let days = sales_dates(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(), today);
+        assert_eq!(
+            days.iter().map(|(d, _)| d.as_str()).collect::<Vec<_>>(),
+            ["2025-03-01", "2025-03-02"]
+        );
+        let months = finance_months(since, today);
+        assert_eq!(months[0].0, "2025-01");
+        assert_eq!(months[1].0, "2025-02");
+        assert_eq!(months[1].1, NaiveDate::from_ymd_opt(2025, 2, 28).unwrap());
+        assert_eq!(months.len(), 2);
+    }
+}
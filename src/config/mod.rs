@@ -1,16 +1,40 @@
 pub mod profiles;
+pub mod revenue;
+pub mod territories;
+pub mod translation;
+pub mod validate;
 
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once schema problems have been reported, so repeated loads warn only once.
+static SCHEMA_WARNED: AtomicBool = AtomicBool::new(false);
 
 #[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(default)]
     pub active_profile: Option<String>,
     #[serde(default)]
     pub profiles: HashMap<String, profiles::Profile>,
+    /// Named territory/country lists, referenced as `@name`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub territory_groups: HashMap<String, Vec<String>>,
+    /// Machine translation backend for `sync translate`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation: Option<translation::TranslationConfig>,
+    /// Report sources and exchange rates for `revenue`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revenue: Option<revenue::RevenueConfig>,
+    /// Table output labels for raw enum values and keys, e.g. `READY_FOR_SALE = "Live"`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
+    /// `sync` screenshot directories per Apple display type, e.g. `APP_WATCH_ULTRA = "watch"`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub screenshot_dirs: HashMap<String, String>,
 }
 
 impl Config {
@@ -18,6 +42,11 @@ impl Config {
         ProjectDirs::from("com", "storeops", "storeops").map(|d| d.config_dir().to_path_buf())
     }
 
+    /// Directory for local data such as the sales warehouse.
+    pub fn data_dir() -> Option<PathBuf> {
+        ProjectDirs::from("com", "storeops", "storeops").map(|d| d.data_dir().to_path_buf())
+    }
+
     pub fn config_path() -> Option<PathBuf> {
         Self::config_dir().map(|d| d.join("config.toml"))
     }
@@ -28,7 +57,28 @@ impl Config {
             return Ok(Self::default());
         }
         let content = std::fs::read_to_string(&path)?;
-        Ok(toml::from_str(&content)?)
+        let problems = validate::check(&content);
+        let details: Vec<String> = problems.iter().map(|p| format!("  {p}")).collect();
+        match toml::from_str::<Self>(&content) {
+            Ok(config) => {
+                // Schema problems that still parse, such as a dangling active_profile,
+                // must not lock out `auth switch` and `auth login`, which fix them
+                if !details.is_empty() && !SCHEMA_WARNED.swap(true, Ordering::Relaxed) {
+                    eprintln!(
+                        "Warning: config {} has problems:\n{}",
+                        path.display(),
+                        details.join("\n")
+                    );
+                }
+                Ok(config)
+            }
+            Err(e) if details.is_empty() => {
+                Err(format!("invalid config {}: {e}", path.display()).into())
+            }
+            Err(_) => {
+                Err(format!("invalid config {}:\n{}", path.display(), details.join("\n")).into())
+            }
+        }
     }
 
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
@@ -36,11 +86,19 @@ impl Config {
         std::fs::create_dir_all(&dir)?;
         let path = dir.join("config.toml");
         let content = toml::to_string_pretty(self)?;
-        std::fs::write(&path, content)?;
+        // Create the file owner-only so credentials are never briefly world-readable
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        std::io::Write::write_all(&mut options.open(&path)?, content.as_bytes())?;
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
-            let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
         }
         Ok(())
     }
@@ -50,12 +108,21 @@ impl Config {
             .as_ref()
             .and_then(|name| self.profiles.get(name))
     }
+
+    /// Defaults of the named profile, or of the active one when no name is given.
+    pub fn profile_defaults(&self, name: Option<&str>) -> profiles::ProfileDefaults {
+        let profile = match name {
+            Some(name) => self.profiles.get(name),
+            None => self.active_profile(),
+        };
+        profile.map(|p| p.defaults.clone()).unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use profiles::{Credentials, Profile, Store};
+    use profiles::{Credentials, Profile, ProfileDefaults, Store};
 
     #[test]
     fn default_config_has_no_profiles() {
@@ -79,6 +146,8 @@ mod tests {
                     issuer_id: "I1".to_string(),
                     key_path: "/tmp/key.p8".to_string(),
                 },
+                defaults: ProfileDefaults::default(),
+                retired_keys: Vec::new(),
             },
         );
 
@@ -99,6 +168,8 @@ mod tests {
                 credentials: Credentials::Google {
                     service_account_path: "/tmp/sa.json".to_string(),
                 },
+                defaults: ProfileDefaults::default(),
+                retired_keys: Vec::new(),
             },
         );
         config.active_profile = Some("myprofile".to_string());
@@ -107,6 +178,61 @@ mod tests {
         assert!(matches!(active.store, Store::Google));
     }
 
+    #[test]
+    fn profile_defaults_round_trip_alongside_credentials() {
+        let toml = r#"
+            active_profile = "work"
+
+            [profiles.work]
+            store = "google"
+            service_account_path = "/tmp/sa.json"
+            default_package = "com.example.app"
+            default_limit = 20
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let profile = config.active_profile().unwrap();
+        assert!(matches!(profile.credentials, Credentials::Google { .. }));
+        let defaults = config.profile_defaults(None);
+        assert_eq!(defaults.default_package.as_deref(), Some("com.example.app"));
+        assert_eq!(defaults.default_limit, Some(20));
+        assert!(config
+            .profile_defaults(Some("missing"))
+            .default_package
+            .is_none());
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        assert!(serialized.contains("default_package"));
+        assert!(!serialized.contains("default_app_id"));
+    }
+
+    #[test]
+    fn retired_keys_round_trip_under_their_profile() {
+        let toml = r#"
+            [profiles.work]
+            store = "apple"
+            key_id = "NEWKEY"
+            issuer_id = "ISS"
+            key_path = "/tmp/AuthKey_NEWKEY.p8"
+
+            [[profiles.work.retired_keys]]
+            key_id = "OLDKEY"
+            issuer_id = "ISS"
+            key_path = "/tmp/AuthKey_OLDKEY.p8"
+            retired_at = "2025-03-01T10:00:00+00:00"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let profile = &config.profiles["work"];
+        assert!(
+            matches!(&profile.credentials, Credentials::Apple { key_id, .. } if key_id == "NEWKEY")
+        );
+        assert_eq!(profile.retired_keys[0].key_id, "OLDKEY");
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        let reparsed: Config = toml::from_str(&serialized).unwrap();
+        assert_eq!(reparsed.profiles["work"].retired_keys.len(), 1);
+        assert!(crate::config::validate::check(&serialized).is_empty());
+    }
+
     #[test]
     fn active_profile_returns_none_when_not_set() {
         let config = Config::default();
@@ -5,6 +5,38 @@ pub struct Profile {
     pub store: Store,
     #[serde(flatten)]
     pub credentials: Credentials,
+    #[serde(flatten, default)]
+    pub defaults: ProfileDefaults,
+    /// Apple keys replaced by `auth rotate`, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retired_keys: Vec<RetiredKey>,
+}
+
+/// An Apple API key kept after rotation, so it can be revoked in App Store Connect.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetiredKey {
+    pub key_id: String,
+    pub issuer_id: String,
+    pub key_path: String,
+    /// RFC 3339 time of the rotation
+    pub retired_at: String,
+}
+
+/// Values applied when the corresponding argument or flag is omitted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileDefaults {
+    /// App ID or bundle ID used by Apple commands when none is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_app_id: Option<String>,
+    /// Package name used by Google commands when none is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_package: Option<String>,
+    /// Output format when `--output` is not passed (`table`, `json`, `ci` or `jsonl`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_output: Option<String>,
+    /// Pagination limit when `--limit` is not passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_limit: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,68 @@
+//! Report sources and currency settings for `storeops revenue`.
+//!
+//! ```toml
+//! [revenue]
+//! currency = "EUR"
+//! apple_vendor_number = "85012345"
+//! google_bucket = "pubsite_prod_rev_01234567890987654321"
+//! rates_url = "https://open.er-api.com/v6/latest/EUR"
+//!
+//! [revenue.rates]
+//! USD = 1.08
+//! GBP = 0.84
+//! ```
+//!
+//! Rates are units of each currency per one unit of `currency`. `rates_url` must
+//! return JSON with a `rates` object (and optionally its `base`); when it is not
+//! set, the fixed `[revenue.rates]` table is used.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RevenueConfig {
+    /// Currency the summary is reported in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub apple_vendor_number: Option<String>,
+    /// Cloud Storage bucket of the Play Console reports (`pubsite_prod_rev_...`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub google_bucket: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rates_url: Option<String>,
+    /// Fixed exchange rates, per one unit of `currency`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub rates: HashMap<String, f64>,
+}
+
+impl RevenueConfig {
+    pub fn currency(&self) -> &str {
+        self.currency.as_deref().unwrap_or(DEFAULT_CURRENCY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parses_revenue_table_with_fixed_rates() {
+        let config: crate::config::Config = toml::from_str(
+            r#"
+            [revenue]
+            currency = "EUR"
+            google_bucket = "pubsite_prod_rev_0123"
+
+            [revenue.rates]
+            USD = 1.08
+            "#,
+        )
+        .unwrap();
+        let revenue = config.revenue.unwrap();
+        assert_eq!(revenue.currency(), "EUR");
+        assert_eq!(revenue.rates["USD"], 1.08);
+        assert!(revenue.apple_vendor_number.is_none());
+    }
+}
@@ -0,0 +1,276 @@
+//! Named territory groups usable wherever a territory/country list is accepted.
+//!
+//! `@name` expands to a group from the config's `[territory_groups]` table, or to
+//! one of the built-in regions. User groups are inserted verbatim; built-in groups
+//! are emitted as ISO 3166-1 alpha-3 codes for Apple and alpha-2 codes for Google.
+
+use super::Config;
+
+/// Country code style expected by each store.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CodeStyle {
+    /// ISO 3166-1 alpha-3 (App Store Connect territories, e.g. `USA`)
+    Alpha3,
+    /// ISO 3166-1 alpha-2 (Google Play countries, e.g. `US`)
+    Alpha2,
+}
+
+type Group = (&'static str, &'static [(&'static str, &'static str)]);
+
+/// Built-in regions as `(alpha-2, alpha-3)` pairs.
+const BUILTIN_GROUPS: &[Group] = &[
+    (
+        "eu",
+        &[
+            ("AT", "AUT"),
+            ("BE", "BEL"),
+            ("BG", "BGR"),
+            ("HR", "HRV"),
+            ("CY", "CYP"),
+            ("CZ", "CZE"),
+            ("DK", "DNK"),
+            ("EE", "EST"),
+            ("FI", "FIN"),
+            ("FR", "FRA"),
+            ("DE", "DEU"),
+            ("GR", "GRC"),
+            ("HU", "HUN"),
+            ("IE", "IRL"),
+            ("IT", "ITA"),
+            ("LV", "LVA"),
+            ("LT", "LTU"),
+            ("LU", "LUX"),
+            ("MT", "MLT"),
+            ("NL", "NLD"),
+            ("PL", "POL"),
+            ("PT", "PRT"),
+            ("RO", "ROU"),
+            ("SK", "SVK"),
+            ("SI", "SVN"),
+            ("ES", "ESP"),
+            ("SE", "SWE"),
+        ],
+    ),
+    (
+        "latam",
+        &[
+            ("AR", "ARG"),
+            ("BO", "BOL"),
+            ("BR", "BRA"),
+            ("CL", "CHL"),
+            ("CO", "COL"),
+            ("CR", "CRI"),
+            ("DO", "DOM"),
+            ("EC", "ECU"),
+            ("SV", "SLV"),
+            ("GT", "GTM"),
+            ("HN", "HND"),
+            ("MX", "MEX"),
+            ("NI", "NIC"),
+            ("PA", "PAN"),
+            ("PY", "PRY"),
+            ("PE", "PER"),
+            ("UY", "URY"),
+            ("VE", "VEN"),
+        ],
+    ),
+    (
+        "nordics",
+        &[
+            ("DK", "DNK"),
+            ("FI", "FIN"),
+            ("IS", "ISL"),
+            ("NO", "NOR"),
+            ("SE", "SWE"),
+        ],
+    ),
+    ("dach", &[("DE", "DEU"), ("AT", "AUT"), ("CH", "CHE")]),
+    ("na", &[("US", "USA"), ("CA", "CAN")]),
+    ("anz", &[("AU", "AUS"), ("NZ", "NZL")]),
+];
+
+fn builtin_group(name: &str, style: CodeStyle) -> Option<Vec<String>> {
+    BUILTIN_GROUPS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, members)| {
+            members
+                .iter()
+                .map(|(a2, a3)| match style {
+                    CodeStyle::Alpha2 => a2.to_string(),
+                    CodeStyle::Alpha3 => a3.to_string(),
+                })
+                .collect()
+        })
+}
+
+/// Primary language of common markets as `(alpha-2, alpha-3, language)`.
+const MARKET_LANGUAGES: &[(&str, &str, &str)] = &[
+    ("US", "USA", "en"),
+    ("GB", "GBR", "en"),
+    ("CA", "CAN", "en"),
+    ("AU", "AUS", "en"),
+    ("NZ", "NZL", "en"),
+    ("IE", "IRL", "en"),
+    ("IN", "IND", "en"),
+    ("DE", "DEU", "de"),
+    ("AT", "AUT", "de"),
+    ("CH", "CHE", "de"),
+    ("FR", "FRA", "fr"),
+    ("BE", "BEL", "fr"),
+    ("ES", "ESP", "es"),
+    ("MX", "MEX", "es"),
+    ("AR", "ARG", "es"),
+    ("CL", "CHL", "es"),
+    ("CO", "COL", "es"),
+    ("PE", "PER", "es"),
+    ("IT", "ITA", "it"),
+    ("PT", "PRT", "pt"),
+    ("BR", "BRA", "pt"),
+    ("NL", "NLD", "nl"),
+    ("SE", "SWE", "sv"),
+    ("NO", "NOR", "no"),
+    ("DK", "DNK", "da"),
+    ("FI", "FIN", "fi"),
+    ("PL", "POL", "pl"),
+    ("CZ", "CZE", "cs"),
+    ("SK", "SVK", "sk"),
+    ("HU", "HUN", "hu"),
+    ("RO", "ROU", "ro"),
+    ("GR", "GRC", "el"),
+    ("TR", "TUR", "tr"),
+    ("RU", "RUS", "ru"),
+    ("UA", "UKR", "uk"),
+    ("JP", "JPN", "ja"),
+    ("KR", "KOR", "ko"),
+    ("CN", "CHN", "zh"),
+    ("TW", "TWN", "zh"),
+    ("HK", "HKG", "zh"),
+    ("TH", "THA", "th"),
+    ("VN", "VNM", "vi"),
+    ("ID", "IDN", "id"),
+    ("MY", "MYS", "ms"),
+    ("SA", "SAU", "ar"),
+    ("AE", "ARE", "ar"),
+    ("EG", "EGY", "ar"),
+    ("IL", "ISR", "he"),
+];
+
+/// Primary language code for a territory given in either alpha-2 or alpha-3 form.
+pub fn market_language(code: &str) -> Option<&'static str> {
+    let code = code.to_uppercase();
+    MARKET_LANGUAGES
+        .iter()
+        .find(|(a2, a3, _)| *a2 == code || *a3 == code)
+        .map(|(_, _, lang)| *lang)
+}
+
+/// Markets whose primary language has no matching locale.
+///
+/// Locales match on their language prefix, so `pt-BR` covers both `BRA` and `PRT`.
+/// Territories without a known language are not reported.
+pub fn uncovered_markets(territories: &[String], locales: &[String]) -> Vec<String> {
+    let languages: Vec<String> = locales
+        .iter()
+        .map(|l| {
+            let lang = l.split(['-', '_']).next().unwrap_or(l).to_lowercase();
+            // Google Play still uses the legacy code for Hebrew
+            if lang == "iw" {
+                "he".to_string()
+            } else {
+                lang
+            }
+        })
+        .collect();
+    territories
+        .iter()
+        .filter(|t| market_language(t).is_some_and(|lang| !languages.iter().any(|l| l == lang)))
+        .cloned()
+        .collect()
+}
+
+/// Expand `@group` references in a territory list, de-duplicating while keeping order.
+pub fn expand(list: &[String], config: &Config, style: CodeStyle) -> Result<Vec<String>, String> {
+    let mut out: Vec<String> = Vec::new();
+    for item in list {
+        let item = item.trim();
+        let members = match item.strip_prefix('@') {
+            Some(name) => {
+                let name = name.to_lowercase();
+                config
+                    .territory_groups
+                    .get(&name)
+                    .cloned()
+                    .or_else(|| builtin_group(&name, style))
+                    .ok_or_else(|| format!("unknown territory group: @{name}"))?
+            }
+            None => vec![item.to_string()],
+        };
+        for m in members {
+            let m = m.to_uppercase();
+            if !m.is_empty() && !out.contains(&m) {
+                out.push(m);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Expand a territory list using the on-disk config (or an empty one if unreadable).
+pub fn resolve(list: &[String], style: CodeStyle) -> Result<Vec<String>, String> {
+    let config = Config::load().unwrap_or_default();
+    expand(list, &config, style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn expands_builtin_groups_per_store() {
+        let config = Config::default();
+        let apple = expand(&strings(&["@dach"]), &config, CodeStyle::Alpha3).unwrap();
+        assert_eq!(apple, vec!["DEU", "AUT", "CHE"]);
+        let google = expand(&strings(&["@DACH"]), &config, CodeStyle::Alpha2).unwrap();
+        assert_eq!(google, vec!["DE", "AT", "CH"]);
+    }
+
+    #[test]
+    fn user_groups_override_builtins_and_dedupe() {
+        let mut config = Config::default();
+        config
+            .territory_groups
+            .insert("na".to_string(), strings(&["USA"]));
+        let out = expand(&strings(&["usa", "@na", "GBR"]), &config, CodeStyle::Alpha3).unwrap();
+        assert_eq!(out, vec!["USA", "GBR"]);
+    }
+
+    #[test]
+    fn unknown_group_is_an_error() {
+        let err = expand(&strings(&["@mars"]), &Config::default(), CodeStyle::Alpha2);
+        assert!(err.unwrap_err().contains("@mars"));
+    }
+
+    #[test]
+    fn market_language_accepts_both_code_styles() {
+        assert_eq!(market_language("DEU"), Some("de"));
+        assert_eq!(market_language("br"), Some("pt"));
+        assert_eq!(market_language("ATA"), None);
+    }
+
+    #[test]
+    fn uncovered_markets_match_on_language() {
+        let territories = strings(&["USA", "BRA", "PRT", "JPN", "ISR", "ATA"]);
+        let locales = strings(&["en-US", "pt-BR", "iw-IL"]);
+        assert_eq!(uncovered_markets(&territories, &locales), vec!["JPN"]);
+    }
+
+    #[test]
+    fn eu_has_27_members() {
+        assert_eq!(builtin_group("eu", CodeStyle::Alpha3).unwrap().len(), 27);
+    }
+}
@@ -0,0 +1,87 @@
+//! Machine translation settings for `storeops sync translate`.
+//!
+//! ```toml
+//! [translation]
+//! provider = "deepl"
+//! deepl_api_key = "..."
+//! openai_api_key = "..."
+//! openai_model = "gpt-4o-mini"
+//! ```
+//!
+//! API keys fall back to the `DEEPL_API_KEY` / `OPENAI_API_KEY` environment variables.
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Deepl,
+    Openai,
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Provider::Deepl => write!(f, "deepl"),
+            Provider::Openai => write!(f, "openai"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TranslationConfig {
+    /// Provider used when `--provider` is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<Provider>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deepl_api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openai_api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openai_model: Option<String>,
+}
+
+impl TranslationConfig {
+    /// API key for a provider, from the config or its environment variable.
+    pub fn api_key(&self, provider: Provider) -> Option<String> {
+        let (configured, env) = match provider {
+            Provider::Deepl => (&self.deepl_api_key, "DEEPL_API_KEY"),
+            Provider::Openai => (&self.openai_api_key, "OPENAI_API_KEY"),
+        };
+        configured
+            .clone()
+            .or_else(|| std::env::var(env).ok())
+            .filter(|k| !k.is_empty())
+    }
+
+    pub fn openai_model(&self) -> &str {
+        self.openai_model.as_deref().unwrap_or(DEFAULT_OPENAI_MODEL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_translation_table() {
+        let config: crate::config::Config = toml::from_str(
+            r#"
+            [translation]
+            provider = "openai"
+            openai_api_key = "sk-test"
+            "#,
+        )
+        .unwrap();
+        let translation = config.translation.unwrap();
+        assert_eq!(translation.provider, Some(Provider::Openai));
+        assert_eq!(
+            translation.api_key(Provider::Openai).as_deref(),
+            Some("sk-test")
+        );
+        assert_eq!(translation.openai_model(), DEFAULT_OPENAI_MODEL);
+    }
+}
@@ -0,0 +1,291 @@
+//! Schema checks for `config.toml` that point at the offending line and key.
+//!
+//! Profiles flatten their credentials and defaults, which serde cannot combine with
+//! `deny_unknown_fields`, so unknown keys are found by walking the parsed table
+//! against the known key lists below.
+
+use std::fmt;
+
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "active_profile",
+    "labels",
+    "profiles",
+    "revenue",
+    "screenshot_dirs",
+    "territory_groups",
+    "translation",
+];
+const APPLE_KEYS: &[&str] = &["key_id", "issuer_id", "key_path"];
+const GOOGLE_KEYS: &[&str] = &["service_account_path"];
+const PROFILE_KEYS: &[&str] = &[
+    "store",
+    "key_id",
+    "issuer_id",
+    "key_path",
+    "service_account_path",
+    "default_app_id",
+    "default_package",
+    "default_output",
+    "default_limit",
+    "retired_keys",
+];
+const REVENUE_KEYS: &[&str] = &[
+    "currency",
+    "apple_vendor_number",
+    "google_bucket",
+    "rates_url",
+    "rates",
+];
+const TRANSLATION_KEYS: &[&str] = &[
+    "provider",
+    "deepl_api_key",
+    "openai_api_key",
+    "openai_model",
+];
+
+/// One problem found in a config file.
+#[derive(Debug, PartialEq)]
+pub struct Problem {
+    /// 1-based line number, when the key could be located
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Check config file contents, returning every problem found.
+pub fn check(content: &str) -> Vec<Problem> {
+    let table: toml::Table = match toml::from_str(content) {
+        Ok(table) => table,
+        Err(e) => {
+            let line = e
+                .span()
+                .map(|span| content[..span.start].matches('\n').count() + 1);
+            return vec![Problem {
+                line,
+                message: e.message().to_string(),
+            }];
+        }
+    };
+
+    let mut problems = Vec::new();
+    unknown_keys(content, None, &table, TOP_LEVEL_KEYS, &mut problems);
+
+    let profiles = table.get("profiles").and_then(toml::Value::as_table);
+    for (name, profile) in profiles.into_iter().flatten() {
+        let section = format!("profiles.{name}");
+        let Some(profile) = profile.as_table() else {
+            problems.push(Problem {
+                line: line_of(content, Some("profiles"), name),
+                message: format!("profile `{name}` must be a table"),
+            });
+            continue;
+        };
+        unknown_keys(
+            content,
+            Some(&section),
+            profile,
+            PROFILE_KEYS,
+            &mut problems,
+        );
+
+        let required = match profile.get("store").and_then(toml::Value::as_str) {
+            Some("apple") => APPLE_KEYS,
+            Some("google") => GOOGLE_KEYS,
+            Some(other) => {
+                problems.push(Problem {
+                    line: line_of(content, Some(&section), "store"),
+                    message: format!(
+                        "[{section}] store must be `apple` or `google`, got `{other}`"
+                    ),
+                });
+                continue;
+            }
+            None => {
+                problems.push(Problem {
+                    line: header_line(content, &section),
+                    message: format!("[{section}] is missing `store`"),
+                });
+                continue;
+            }
+        };
+        for key in required.iter().filter(|k| !profile.contains_key(**k)) {
+            problems.push(Problem {
+                line: header_line(content, &section),
+                message: format!("[{section}] is missing `{key}`"),
+            });
+        }
+        if let Some(output) = profile.get("default_output").and_then(toml::Value::as_str) {
+            if !["table", "json", "ci", "jsonl"].contains(&output) {
+                problems.push(Problem {
+                    line: line_of(content, Some(&section), "default_output"),
+                    message: format!(
+                        "[{section}] default_output must be `table`, `json`, `ci` or `jsonl`, got `{output}`"
+                    ),
+                });
+            }
+        }
+    }
+
+    if let Some(active) = table.get("active_profile").and_then(toml::Value::as_str) {
+        if !profiles.is_some_and(|p| p.contains_key(active)) {
+            problems.push(Problem {
+                line: line_of(content, None, "active_profile"),
+                message: format!("active_profile `{active}` does not match any profile"),
+            });
+        }
+    }
+
+    if let Some(translation) = table.get("translation").and_then(toml::Value::as_table) {
+        unknown_keys(
+            content,
+            Some("translation"),
+            translation,
+            TRANSLATION_KEYS,
+            &mut problems,
+        );
+    }
+
+    if let Some(revenue) = table.get("revenue").and_then(toml::Value::as_table) {
+        unknown_keys(
+            content,
+            Some("revenue"),
+            revenue,
+            REVENUE_KEYS,
+            &mut problems,
+        );
+    }
+
+    problems
+}
+
+fn unknown_keys(
+    content: &str,
+    section: Option<&str>,
+    table: &toml::Table,
+    known: &[&str],
+    problems: &mut Vec<Problem>,
+) {
+    for key in table.keys().filter(|k| !known.contains(&k.as_str())) {
+        let location = section.map(|s| format!(" in [{s}]")).unwrap_or_default();
+        let hint = suggest(key, known)
+            .map(|s| format!(" (did you mean `{s}`?)"))
+            .unwrap_or_default();
+        problems.push(Problem {
+            line: line_of(content, section, key),
+            message: format!("unknown key `{key}`{location}{hint}"),
+        });
+    }
+}
+
+/// Closest known key within a small edit distance.
+fn suggest<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|k| (edit_distance(key, k), *k))
+        .filter(|(d, k)| *d <= 2.max(k.len() / 4))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, k)| k)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(cur)
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+/// Line of the `[section]` header.
+fn header_line(content: &str, section: &str) -> Option<usize> {
+    let header = format!("[{section}]");
+    content
+        .lines()
+        .position(|l| l.trim() == header)
+        .map(|i| i + 1)
+}
+
+/// Line on which `key` is assigned inside `section` (`None` for the top level).
+fn line_of(content: &str, section: Option<&str>, key: &str) -> Option<usize> {
+    let mut current: Option<String> = None;
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = Some(header.trim().to_string());
+            continue;
+        }
+        let Some((name, _)) = line.split_once('=') else {
+            continue;
+        };
+        if current.as_deref() == section && name.trim().trim_matches('"') == key {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_close_keys_with_line_numbers() {
+        let content = r#"active_profile = "work"
+
+[profiles.work]
+store = "apple"
+key_id = "K"
+issuer_id = "I"
+keypath = "/tmp/key.p8"
+"#;
+        let problems = check(content);
+        assert_eq!(
+            problems,
+            [
+                Problem {
+                    line: Some(7),
+                    message: "unknown key `keypath` in [profiles.work] (did you mean `key_path`?)"
+                        .to_string(),
+                },
+                Problem {
+                    line: Some(3),
+                    message: "[profiles.work] is missing `key_path`".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors_and_dangling_active_profile() {
+        let broken = check("active_profile = \n");
+        assert_eq!(broken[0].line, Some(1));
+
+        let problems = check("active_profile = \"gone\"\ncolour = 1\n");
+        let messages: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "line 2: unknown key `colour`",
+                "line 1: active_profile `gone` does not match any profile",
+            ]
+        );
+    }
+}
@@ -8,14 +8,16 @@ mod update;
 
 use clap::Parser;
 use cli::{AuthCommand, Cli, Command};
-use config::profiles::{Credentials, Profile, Store};
+use config::profiles::{Credentials, Profile, ProfileDefaults, RetiredKey, Store};
 use config::Config;
+use output::OutputFormat;
 use serde_json::{json, Value};
 use std::process;
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
+    let args: Vec<String> = std::env::args().collect();
+    let mut cli = Cli::parse_from(&args);
 
     if cli.command.is_none() {
         tokio::spawn(update::check_for_update_background());
@@ -23,23 +25,69 @@ async fn main() {
         process::exit(0);
     }
 
-    let json_output = cli.json;
+    cli.apply_profile_defaults();
+    output::labels::configure(!cli.raw);
+    if cli.stream {
+        output::stream::enable();
+    }
+    let format = cli.output_format();
+    if format == OutputFormat::Jsonl {
+        output::jsonl::enable();
+    }
     let pretty = cli.pretty;
+    let flatten = cli.flatten;
 
-    let is_update = matches!(cli.command, Some(Command::Update));
+    let is_update = matches!(cli.command, Some(Command::Update { .. }));
     if !is_update {
         tokio::spawn(update::check_for_update_background());
     }
 
-    let result = run(cli).await;
+    if let Some(path) = &cli.record {
+        api::recording::record(path);
+    }
+    let result = match &cli.replay {
+        Some(path) => match api::recording::replay(path) {
+            Ok(()) => dispatch(cli, &args).await,
+            Err(e) => Err(e),
+        },
+        None => dispatch(cli, &args).await,
+    };
+    api::recording::finish();
 
     match result {
-        Ok(value) => {
-            println!("{}", output::render_value(&value, json_output, pretty));
+        Ok(mut value) => {
+            if flatten && format != OutputFormat::Table {
+                value = output::json::flatten(value);
+            }
+            if format == OutputFormat::Json {
+                api::rate_limit::attach(&mut value);
+            }
+            let rendered = output::render_value(&value, format, pretty);
+            // Streamed results were printed as they arrived
+            if !rendered.is_empty() {
+                println!("{rendered}");
+            }
+            // In CI mode, reported validation failures fail the step
+            if format == OutputFormat::Ci && output::ci::has_failures(&value) {
+                process::exit(1);
+            }
             process::exit(0);
         }
         Err(e) => {
-            let err = json!({ "error": e.to_string() });
+            if let Some(cancelled) = e.downcast_ref::<api::inflight::Cancelled>() {
+                let value = json!({ "status": "cancelled", "cleaned_up": cancelled.cleaned_up });
+                println!("{}", output::render_value(&value, format, pretty));
+                process::exit(130);
+            }
+            if let Some(output::Failure(value)) = e.downcast_ref::<output::Failure>() {
+                println!("{}", output::render_value(value, format, pretty));
+                process::exit(1);
+            }
+            if format == OutputFormat::Ci {
+                println!("{}", output::ci::render_error(&e.to_string()));
+            }
+            let mut err = json!({ "error": e.to_string() });
+            api::rate_limit::attach(&mut err);
             eprintln!(
                 "{}",
                 serde_json::to_string(&err).unwrap_or_else(|_| format!("{{\"error\":\"{}\"}}", e))
@@ -49,12 +97,86 @@ async fn main() {
     }
 }
 
+/// Run a parsed command, fanning out across profiles when requested.
+///
+/// Ctrl-C aborts the command: open Google edits and reserved-but-uncommitted Apple
+/// uploads are cleaned up and an `api::inflight::Cancelled` error is returned.
+pub async fn dispatch(cli: Cli, args: &[String]) -> Result<Value, Box<dyn std::error::Error>> {
+    let command = async {
+        if cli.all_profiles || !cli.profiles.is_empty() {
+            return cli::fanout::run(&cli, args).await;
+        }
+        run(cli).await
+    };
+    tokio::select! {
+        result = command => result,
+        _ = tokio::signal::ctrl_c() => {
+            eprintln!("Cancelling...");
+            let cleaned_up = api::inflight::cleanup().await;
+            Err(Box::new(api::inflight::Cancelled { cleaned_up }))
+        }
+    }
+}
+
 pub async fn run(cli: Cli) -> Result<Value, Box<dyn std::error::Error>> {
     match &cli.command {
         Some(Command::Auth { command }) => handle_auth(command).await,
         Some(Command::Apple { command }) => cli::apple::execute(command, &cli).await,
-        Some(Command::Google { command }) => cli::google::execute(command, &cli).await,
-        Some(Command::Update) => update::handle_update().await,
+        Some(Command::Google { command, edit }) => cli::google::execute(command, edit, &cli).await,
+        Some(Command::Version { command }) => cli::version::handle(command, &cli).await,
+        Some(Command::Screenshots { command }) => cli::screenshots::handle(command).await,
+        Some(Command::Status {
+            app,
+            package,
+            apple_profile,
+            google_profile,
+        }) => {
+            cli::status::handle(
+                app.as_deref(),
+                package.as_deref(),
+                apple_profile.as_deref(),
+                google_profile.as_deref(),
+                &cli,
+            )
+            .await
+        }
+        Some(Command::Search {
+            query,
+            package,
+            apple_profile,
+            google_profile,
+        }) => {
+            cli::search::handle(
+                query,
+                package,
+                apple_profile.as_deref(),
+                google_profile.as_deref(),
+                &cli,
+            )
+            .await
+        }
+        Some(Command::Reviews { command }) => cli::review_triage::handle(command, &cli).await,
+        Some(Command::ReleaseNotes { command }) => cli::release_notes::handle(command, &cli).await,
+        Some(Command::Catalog { command }) => cli::catalog::handle(command, &cli).await,
+        Some(Command::Sync { command }) => cli::sync::handle(command, &cli).await,
+        Some(Command::Revenue(args)) => cli::revenue::handle(args, &cli).await,
+        Some(Command::Warehouse { command }) => cli::warehouse::handle(command, &cli).await,
+        Some(Command::Config { command }) => cli::config::handle(command).await,
+        Some(Command::Doctor(args)) => cli::doctor::handle(args).await,
+        Some(Command::Schema { command }) => cli::schema::handle(command),
+        Some(Command::Serve { port, host, token }) => {
+            cli::serve::handle(host, *port, token.as_deref()).await
+        }
+        Some(Command::Batch {
+            file,
+            stop_on_error,
+        }) => cli::batch::handle(file, *stop_on_error).await,
+        Some(Command::Update {
+            channel,
+            version,
+            check,
+            force_binary,
+        }) => update::handle_update(*channel, version.as_deref(), *check, *force_binary).await,
         None => Err("no command provided".into()),
     }
 }
@@ -62,7 +184,7 @@ pub async fn run(cli: Cli) -> Result<Value, Box<dyn std::error::Error>> {
 async fn handle_auth(cmd: &AuthCommand) -> Result<Value, Box<dyn std::error::Error>> {
     match cmd {
         AuthCommand::Init => {
-            let mut config = Config::load().unwrap_or_default();
+            let mut config = Config::load()?;
             if config.profiles.is_empty() {
                 config.profiles.insert(
                     "apple-default".to_string(),
@@ -73,6 +195,8 @@ async fn handle_auth(cmd: &AuthCommand) -> Result<Value, Box<dyn std::error::Err
                             issuer_id: "YOUR_ISSUER_ID".to_string(),
                             key_path: "/path/to/AuthKey.p8".to_string(),
                         },
+                        defaults: ProfileDefaults::default(),
+                        retired_keys: Vec::new(),
                     },
                 );
                 config.profiles.insert(
@@ -82,6 +206,8 @@ async fn handle_auth(cmd: &AuthCommand) -> Result<Value, Box<dyn std::error::Err
                         credentials: Credentials::Google {
                             service_account_path: "/path/to/service-account.json".to_string(),
                         },
+                        defaults: ProfileDefaults::default(),
+                        retired_keys: Vec::new(),
                     },
                 );
             }
@@ -108,9 +234,30 @@ async fn handle_auth(cmd: &AuthCommand) -> Result<Value, Box<dyn std::error::Err
                     })
                 })
                 .collect();
+            let mut warnings = Vec::new();
+            if let Some(path) = Config::config_path() {
+                warnings.extend(auth::store::permission_warning(&path.to_string_lossy()));
+            }
+            for (name, p) in &config.profiles {
+                let path = match &p.credentials {
+                    Credentials::Apple { key_path, .. } => key_path,
+                    Credentials::Google {
+                        service_account_path,
+                    } => service_account_path,
+                };
+                let warning = match auth::store::expand_path(path) {
+                    Ok(path) => auth::store::permission_warning(&path),
+                    Err(e) => Some(e),
+                };
+                warnings.extend(warning.map(|w| format!("profile '{name}': {w}")));
+            }
+            for warning in &warnings {
+                eprintln!("Warning: {warning}");
+            }
             Ok(json!({
                 "active_profile": config.active_profile,
                 "profiles": profiles,
+                "warnings": warnings,
             }))
         }
         AuthCommand::Switch { profile } => {
@@ -125,6 +272,78 @@ async fn handle_auth(cmd: &AuthCommand) -> Result<Value, Box<dyn std::error::Err
                 "active_profile": profile,
             }))
         }
+        AuthCommand::Rotate {
+            store,
+            new_key_id,
+            new_key_path,
+            issuer_id,
+            name,
+        } => {
+            if !matches!(store, cli::StoreArg::Apple) {
+                return Err("key rotation is only supported for --store apple".into());
+            }
+            let mut config = Config::load()?;
+            let profile_name = match name {
+                Some(name) => name.clone(),
+                None => config
+                    .active_profile
+                    .clone()
+                    .ok_or("no active profile set")?,
+            };
+            let profile = config
+                .profiles
+                .get_mut(&profile_name)
+                .ok_or_else(|| format!("profile '{profile_name}' not found"))?;
+            let Credentials::Apple {
+                key_id: old_key_id,
+                issuer_id: old_issuer_id,
+                key_path: old_key_path,
+            } = &profile.credentials
+            else {
+                return Err(format!("profile '{profile_name}' is not an Apple profile").into());
+            };
+            if old_key_id == new_key_id {
+                return Err(
+                    format!("profile '{profile_name}' already uses key {new_key_id}").into(),
+                );
+            }
+            let issuer_id = issuer_id.clone().unwrap_or_else(|| old_issuer_id.clone());
+
+            let path = auth::store::expand_path(new_key_path)?;
+            let key = std::fs::read(&path).map_err(|e| format!("cannot read key {path}: {e}"))?;
+            let token = auth::apple::generate_token(new_key_id, &issuer_id, &key)?;
+            eprintln!("Verifying key {new_key_id}...");
+            api::apple_client::AppleClient::new(token)
+                .get::<Value>("/apps", &[("limit", "1")])
+                .await
+                .map_err(|e| {
+                    format!(
+                        "key {new_key_id} was rejected; profile '{profile_name}' is unchanged: {e}"
+                    )
+                })?;
+
+            let retired = RetiredKey {
+                key_id: old_key_id.clone(),
+                issuer_id: old_issuer_id.clone(),
+                key_path: old_key_path.clone(),
+                retired_at: chrono::Utc::now().to_rfc3339(),
+            };
+            profile.credentials = Credentials::Apple {
+                key_id: new_key_id.clone(),
+                issuer_id,
+                key_path: new_key_path.clone(),
+            };
+            let retired_key_id = retired.key_id.clone();
+            profile.retired_keys.push(retired);
+            config.save()?;
+            Ok(json!({
+                "status": "ok",
+                "profile": profile_name,
+                "key_id": new_key_id,
+                "retired_key_id": retired_key_id,
+                "message": format!("Key {new_key_id} verified and saved. Revoke {retired_key_id} in App Store Connect once nothing else uses it."),
+            }))
+        }
         AuthCommand::Login {
             store,
             key_id,
@@ -133,9 +352,9 @@ async fn handle_auth(cmd: &AuthCommand) -> Result<Value, Box<dyn std::error::Err
             service_account,
             name,
         } => {
-            let mut config = Config::load().unwrap_or_default();
+            let mut config = Config::load()?;
             let profile_name;
-            let profile;
+            let mut profile;
 
             match store {
                 cli::StoreArg::Apple => {
@@ -150,6 +369,8 @@ async fn handle_auth(cmd: &AuthCommand) -> Result<Value, Box<dyn std::error::Err
                             issuer_id: iss.clone(),
                             key_path: kp.clone(),
                         },
+                        defaults: ProfileDefaults::default(),
+                        retired_keys: Vec::new(),
                     };
                 }
                 cli::StoreArg::Google => {
@@ -162,10 +383,17 @@ async fn handle_auth(cmd: &AuthCommand) -> Result<Value, Box<dyn std::error::Err
                         credentials: Credentials::Google {
                             service_account_path: sa.clone(),
                         },
+                        defaults: ProfileDefaults::default(),
+                        retired_keys: Vec::new(),
                     };
                 }
             }
 
+            // Re-authenticating keeps any defaults configured for the profile
+            if let Some(existing) = config.profiles.get(&profile_name) {
+                profile.defaults = existing.defaults.clone();
+                profile.retired_keys = existing.retired_keys.clone();
+            }
             config.profiles.insert(profile_name.clone(), profile);
             if config.active_profile.is_none() {
                 config.active_profile = Some(profile_name.clone());
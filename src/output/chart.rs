@@ -0,0 +1,90 @@
+//! Terminal charts for short time series.
+//!
+//! Charts are drawn with Unicode block characters so a trend can be read at a
+//! glance without exporting the data. Missing points (reports not yet available)
+//! are left blank rather than drawn as zero.
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Partial blocks for the fractional end of a bar, in eighths
+const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+fn max(values: impl Iterator<Item = f64>) -> f64 {
+    values.fold(0.0, f64::max)
+}
+
+/// One character per point, scaled between zero and the largest value.
+pub fn sparkline(values: &[Option<f64>]) -> String {
+    let top = max(values.iter().flatten().copied());
+    values
+        .iter()
+        .map(|v| match v {
+            None => ' ',
+            Some(_) if top <= 0.0 => SPARKS[0],
+            Some(v) => {
+                let level = (v.max(0.0) / top * (SPARKS.len() - 1) as f64).round() as usize;
+                SPARKS[level.min(SPARKS.len() - 1)]
+            }
+        })
+        .collect()
+}
+
+/// A horizontal bar per point: `label │████▌ value`, bars at most `width` cells.
+pub fn bars(points: &[(String, Option<f64>)], width: usize) -> String {
+    let top = max(points.iter().filter_map(|(_, v)| *v));
+    let label_width = points
+        .iter()
+        .map(|(l, _)| l.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for (label, value) in points {
+        let bar = match value {
+            None => " (no data)".to_string(),
+            Some(v) => {
+                let eighths = if top > 0.0 {
+                    (v.max(0.0) / top * (width * 8) as f64).round() as usize
+                } else {
+                    0
+                };
+                let mut bar = "█".repeat(eighths / 8);
+                if eighths % 8 > 0 {
+                    bar.push(EIGHTHS[eighths % 8]);
+                }
+                format!("{bar} {}", format_value(*v))
+            }
+        };
+        out.push_str(&format!("{label:<label_width$} │{bar}\n"));
+    }
+    out
+}
+
+/// Whole numbers without decimals, others with two.
+fn format_value(v: f64) -> String {
+    if v.fract() == 0.0 {
+        format!("{v:.0}")
+    } else {
+        format!("{v:.2}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_points_and_leaves_gaps_blank() {
+        assert_eq!(sparkline(&[Some(0.0), Some(7.0), None, Some(14.0)]), "▁▅ █");
+        assert_eq!(sparkline(&[Some(0.0), Some(0.0)]), "▁▁");
+        assert_eq!(
+            bars(
+                &[
+                    ("mon".to_string(), Some(4.0)),
+                    ("tue".to_string(), Some(1.5)),
+                    ("wed".to_string(), None),
+                ],
+                4
+            ),
+            "mon │████ 4\ntue │█▌ 1.50\nwed │ (no data)\n"
+        );
+    }
+}
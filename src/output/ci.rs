@@ -0,0 +1,215 @@
+//! GitHub Actions output: workflow command annotations plus a step summary.
+//!
+//! Validation results (`"valid": false` with `problems`/`failed`), per-store or
+//! per-profile `error` entries, and `warnings` become `::error::`/`::warning::`
+//! annotations. Top-level scalar fields are reported as a `::notice::` summary and,
+//! when `GITHUB_STEP_SUMMARY` is set, appended to the job summary as Markdown.
+//! The JSON result follows the annotations so later steps can still parse it.
+
+use serde_json::Value;
+use std::io::Write;
+
+/// Escape the message part of a workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+fn command(kind: &str, title: Option<&str>, file: Option<&str>, message: &str) -> String {
+    let mut props = Vec::new();
+    if let Some(file) = file {
+        props.push(format!("file={}", escape_property(file)));
+    }
+    if let Some(title) = title {
+        props.push(format!("title={}", escape_property(title)));
+    }
+    if props.is_empty() {
+        format!("::{kind}::{}", escape_data(message))
+    } else {
+        format!("::{kind} {}::{}", props.join(","), escape_data(message))
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Collect annotations from a result, descending into nested objects so fan-out and
+/// cross-store results annotate each profile or store under its own title.
+fn collect(value: &Value, path: &str, out: &mut Vec<String>) {
+    let Value::Object(map) = value else { return };
+    let title = (!path.is_empty()).then_some(path);
+
+    if let Some(error) = map.get("error") {
+        out.push(command("error", title, None, &text(error)));
+    }
+    for problem in map
+        .get("problems")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        out.push(command("error", title, None, &text(problem)));
+    }
+    for failure in map
+        .get("failed")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let file = failure["file"].as_str();
+        let reason = failure
+            .get("reason")
+            .map(text)
+            .unwrap_or_else(|| text(failure));
+        out.push(command("error", title, file, &reason));
+    }
+    for warning in map
+        .get("warnings")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        out.push(command("warning", title, None, &text(warning)));
+    }
+    if map.get("valid") == Some(&Value::Bool(false))
+        && !map.contains_key("problems")
+        && !map.contains_key("failed")
+    {
+        out.push(command("error", title, None, "validation failed"));
+    }
+
+    for (key, child) in map {
+        if child.is_object() {
+            let child_path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{path}.{key}")
+            };
+            collect(child, &child_path, out);
+        }
+    }
+}
+
+/// Top-level scalar fields as `key=value` pairs.
+fn summary_fields(value: &Value) -> Vec<(String, String)> {
+    let Value::Object(map) = value else {
+        return Vec::new();
+    };
+    map.iter()
+        .filter_map(|(k, v)| match v {
+            Value::String(_) | Value::Number(_) | Value::Bool(_) => Some((k.clone(), text(v))),
+            Value::Array(items) => Some((k.clone(), format!("{} item(s)", items.len()))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the result describes a failure the CI step should fail on.
+pub fn has_failures(value: &Value) -> bool {
+    let mut annotations = Vec::new();
+    collect(value, "", &mut annotations);
+    annotations.iter().any(|a| a.starts_with("::error"))
+}
+
+pub fn render(value: &Value) -> String {
+    let mut lines = Vec::new();
+    collect(value, "", &mut lines);
+
+    let fields = summary_fields(value);
+    if !fields.is_empty() {
+        let summary = fields
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(command("notice", Some("storeops"), None, &summary));
+        write_step_summary(&fields);
+    }
+
+    lines.push(super::json::render(value, false));
+    lines.join("\n")
+}
+
+/// Append a Markdown table to the job summary when running in GitHub Actions.
+fn write_step_summary(fields: &[(String, String)]) {
+    let Ok(path) = std::env::var("GITHUB_STEP_SUMMARY") else {
+        return;
+    };
+    let mut md = String::from("### storeops\n\n| Field | Value |\n|---|---|\n");
+    for (k, v) in fields {
+        md.push_str(&format!("| {k} | {} |\n", v.replace('|', "\\|")));
+    }
+    md.push('\n');
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        let _ = file.write_all(md.as_bytes());
+    }
+}
+
+/// Annotation for a command that failed outright.
+pub fn render_error(message: &str) -> String {
+    command("error", Some("storeops"), None, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn validation_failures_become_error_annotations() {
+        let val = json!({
+            "valid": false,
+            "problems": ["row 2: missing locale"],
+            "failed": [{"file": "en-US/1.png", "reason": "too small"}]
+        });
+        let mut out = Vec::new();
+        collect(&val, "", &mut out);
+        assert_eq!(
+            out,
+            vec![
+                "::error::row 2: missing locale",
+                "::error file=en-US/1.png::too small",
+            ]
+        );
+        assert!(has_failures(&val));
+    }
+
+    #[test]
+    fn nested_errors_are_titled_by_path() {
+        let val = json!({"apple": {"version": "1.0"}, "google": {"error": "boom"}});
+        let mut out = Vec::new();
+        collect(&val, "", &mut out);
+        assert_eq!(out, vec!["::error title=google::boom"]);
+    }
+
+    #[test]
+    fn escapes_newlines_and_property_separators() {
+        assert_eq!(
+            command("error", Some("a:b,c"), None, "50%\nnext"),
+            "::error title=a%3Ab%2Cc::50%25%0Anext"
+        );
+    }
+
+    #[test]
+    fn successful_results_only_get_a_notice() {
+        let val = json!({"status": "ok", "uploaded": 3, "files": [1, 2]});
+        let rendered = render(&val);
+        assert!(rendered.starts_with("::notice title=storeops::"));
+        assert!(rendered.contains("uploaded=3"));
+        assert!(!has_failures(&val));
+    }
+}
@@ -0,0 +1,99 @@
+//! Minimal CSV writing and reading for exports that finance and ops open in a
+//! spreadsheet.
+
+use serde_json::Value;
+
+/// One CSV field, quoted when it contains a separator, quote or newline.
+pub fn field(value: &Value) -> String {
+    let s = match value {
+        Value::Null => return String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s
+    }
+}
+
+/// A header row plus one line per row object, taking `columns` in order.
+pub fn render(rows: &[Value], columns: &[&str]) -> String {
+    let mut lines = vec![columns.join(",")];
+    lines.extend(rows.iter().map(|row| {
+        columns
+            .iter()
+            .map(|c| field(&row[*c]))
+            .collect::<Vec<_>>()
+            .join(",")
+    }));
+    lines.join("\n") + "\n"
+}
+
+/// Parse CSV text into records, honouring quoted fields with `""` escapes and
+/// embedded newlines. Blank lines are skipped.
+pub fn parse(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            c => field.push(c),
+        }
+    }
+    record.push(field);
+    if record.iter().any(|f| !f.is_empty()) {
+        records.push(record);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn quotes_fields_with_separators() {
+        let rows = [
+            json!({"a": "x,y", "b": 2, "c": null}),
+            json!({"a": "say \"hi\""}),
+        ];
+        assert_eq!(
+            render(&rows, &["a", "b", "c"]),
+            "a,b,c\n\"x,y\",2,\n\"say \"\"hi\"\"\",,\n"
+        );
+    }
+
+    #[test]
+    fn parse_round_trips_rendered_rows() {
+        let rows = [
+            json!({"a": "x,y", "b": "line\nbreak"}),
+            json!({"a": "say \"hi\"", "b": ""}),
+        ];
+        assert_eq!(
+            parse(&render(&rows, &["a", "b"])),
+            [
+                vec!["a", "b"],
+                vec!["x,y", "line\nbreak"],
+                vec!["say \"hi\"", ""],
+            ]
+        );
+    }
+}
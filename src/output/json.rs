@@ -1,5 +1,22 @@
 use serde_json::Value;
 
+/// Flatten every JSON:API resource in `value` the way the table renderer does:
+/// `attributes` are merged into the resource and `relationships`/`links` dropped.
+/// Document-level keys such as `meta` and the pagination `links` are kept.
+pub fn flatten(value: Value) -> Value {
+    match value {
+        Value::Object(obj) if matches!(obj.get("attributes"), Some(Value::Object(_))) => {
+            let flat = super::table::flatten_object(&obj);
+            Value::Object(flat.into_iter().map(|(k, v)| (k, flatten(v))).collect())
+        }
+        Value::Object(obj) => {
+            Value::Object(obj.into_iter().map(|(k, v)| (k, flatten(v))).collect())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(flatten).collect()),
+        other => other,
+    }
+}
+
 pub fn render(value: &Value, pretty: bool) -> String {
     if pretty {
         serde_json::to_string_pretty(value).unwrap_or_else(|e| format!("{{\"error\":\"{e}\"}}"))
@@ -29,6 +46,27 @@ mod tests {
         assert!(!output.contains('\n'));
     }
 
+    #[test]
+    fn flatten_merges_attributes_and_keeps_document_links() {
+        let val = json!({
+            "data": [{
+                "type": "apps",
+                "id": "1",
+                "attributes": {"name": "App", "bundleId": "com.example"},
+                "relationships": {"builds": {}},
+                "links": {"self": "https://example.com/apps/1"}
+            }],
+            "links": {"next": "https://example.com/apps?cursor=x"}
+        });
+        assert_eq!(
+            flatten(val),
+            json!({
+                "data": [{"type": "apps", "id": "1", "name": "App", "bundleId": "com.example"}],
+                "links": {"next": "https://example.com/apps?cursor=x"}
+            })
+        );
+    }
+
     #[test]
     fn renders_arrays() {
         let val = json!([1, 2, 3]);
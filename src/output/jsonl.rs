@@ -0,0 +1,58 @@
+//! `--output jsonl`: one JSON record per line.
+//!
+//! List results print one element per line. Commands that support it stream their
+//! records with `emit` as each page is parsed, so exports of tens of thousands of
+//! records are written incrementally instead of being held in memory; they then
+//! return `Value::Null`, which renders as nothing.
+
+use serde_json::Value;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether list commands should stream records instead of returning them.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Print one record as a line.
+pub fn emit(record: &Value) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{record}");
+}
+
+pub fn render(value: &Value) -> String {
+    let items = value
+        .get("data")
+        .and_then(Value::as_array)
+        .or(value.as_array());
+    match (items, value) {
+        (Some(items), _) => items
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        (None, Value::Null) => String::new(),
+        (None, value) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_list_elements_one_per_line() {
+        let page = json!({"data": [{"id": "1"}, {"id": "2"}], "links": {}});
+        assert_eq!(render(&page), "{\"id\":\"1\"}\n{\"id\":\"2\"}");
+        assert_eq!(render(&json!([1, 2])), "1\n2");
+        assert_eq!(render(&json!({"status": "ok"})), "{\"status\":\"ok\"}");
+        assert_eq!(render(&Value::Null), "");
+    }
+}
@@ -0,0 +1,203 @@
+//! Human labels for raw API enum values in table output.
+//!
+//! Table output shows `READY_FOR_SALE` as "Ready for Distribution" and the Play
+//! `userFraction` field as "Rollout"; JSON and CI output always keep the raw values.
+//! A `[labels]` table in the config adds or overrides labels, and `--raw` turns
+//! them off:
+//!
+//! ```toml
+//! [labels]
+//! READY_FOR_SALE = "Live"
+//! inProgress = "Rolling out"
+//! ```
+//!
+//! Labels apply to object keys that match exactly and to string values of the
+//! enum-bearing fields in [`ENUM_KEYS`]; there, other `SCREAMING_SNAKE` values are
+//! title-cased (`WAITING_FOR_REVIEW` → "Waiting for Review"). Values of other
+//! fields, such as product IDs and reference names, are never changed.
+
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::config::Config;
+
+/// Labels that differ from the title-cased value, or that are not `SCREAMING_SNAKE`.
+const BUILTIN: &[(&str, &str)] = &[
+    // App Store Connect shows READY_FOR_SALE under its newer name
+    ("READY_FOR_SALE", "Ready for Distribution"),
+    ("IOS", "iOS"),
+    ("MAC_OS", "macOS"),
+    ("TV_OS", "tvOS"),
+    ("VISION_OS", "visionOS"),
+    // Google Play release statuses and fields
+    ("inProgress", "In progress"),
+    ("completed", "Completed"),
+    ("halted", "Halted"),
+    ("draft", "Draft"),
+    ("userFraction", "Rollout"),
+    ("inAppUpdatePriority", "Update priority"),
+    ("versionCodes", "Version codes"),
+];
+
+/// Fields whose string values are API enums and get labels.
+const ENUM_KEYS: &[&str] = &[
+    "appStoreState",
+    "appVersionState",
+    "assetDeliveryState",
+    "inAppPurchaseType",
+    "phasedReleaseState",
+    "platform",
+    "platforms",
+    "processingState",
+    "purchaseType",
+    "reviewState",
+    "state",
+    "status",
+];
+
+/// Words kept lower-case inside a title-cased label.
+const SMALL_WORDS: &[&str] = &["and", "for", "from", "in", "of", "on", "or", "to"];
+
+/// `None` while labels are off (`--raw`, or before `configure`).
+static LABELS: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+
+/// Turn labels on, with the config's `[labels]` overrides, or off for `--raw`.
+pub fn configure(enabled: bool) {
+    let custom = enabled.then(|| Config::load().map(|c| c.labels).unwrap_or_default());
+    if let Ok(mut labels) = LABELS.write() {
+        *labels = custom;
+    }
+}
+
+/// `value` with labels applied, if they are on.
+pub fn localize(value: &Value) -> Value {
+    match LABELS.read().ok().as_deref() {
+        Some(Some(custom)) => apply(value, custom),
+        _ => value.clone(),
+    }
+}
+
+/// Configured or built-in label for exactly `raw`.
+fn known(raw: &str, custom: &HashMap<String, String>) -> Option<String> {
+    custom.get(raw).cloned().or_else(|| {
+        BUILTIN
+            .iter()
+            .find(|(r, _)| *r == raw)
+            .map(|(_, label)| label.to_string())
+    })
+}
+
+fn label(raw: &str, custom: &HashMap<String, String>) -> Option<String> {
+    known(raw, custom).or_else(|| is_screaming_snake(raw).then(|| title_case(raw)))
+}
+
+/// `WORD_WORD`: at least two upper-case words, so codes like `USA` are left alone.
+fn is_screaming_snake(s: &str) -> bool {
+    s.contains('_')
+        && s.split('_')
+            .all(|w| !w.is_empty() && w.chars().all(|c| c.is_ascii_uppercase()))
+}
+
+fn title_case(s: &str) -> String {
+    s.split('_')
+        .enumerate()
+        .map(|(i, word)| {
+            let word = word.to_lowercase();
+            if i > 0 && SMALL_WORDS.contains(&word.as_str()) {
+                return word;
+            }
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => word,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn apply(value: &Value, custom: &HashMap<String, String>) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(|v| apply(v, custom)).collect()),
+        Value::Object(obj) => {
+            let mut out = Map::new();
+            for (key, v) in obj {
+                let v = if ENUM_KEYS.contains(&key.as_str()) {
+                    apply_enum(v, custom)
+                } else {
+                    apply(v, custom)
+                };
+                let key = known(key, custom).unwrap_or_else(|| key.clone());
+                out.insert(key, v);
+            }
+            Value::Object(out)
+        }
+        _ => value.clone(),
+    }
+}
+
+/// Labels for the value of an enum-bearing field: a string or a list of them.
+fn apply_enum(value: &Value, custom: &HashMap<String, String>) -> Value {
+    match value {
+        Value::String(s) => Value::String(label(s, custom).unwrap_or_else(|| s.clone())),
+        Value::Array(items) => Value::Array(items.iter().map(|v| apply_enum(v, custom)).collect()),
+        _ => apply(value, custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn labels_enum_values_and_known_keys() {
+        let custom = HashMap::from([("DEVELOPER_REJECTED".to_string(), "Pulled".to_string())]);
+        let value = json!({
+            "data": [
+                {"appStoreState": "PREPARE_FOR_SUBMISSION", "platform": "IOS", "territory": "USA"},
+                {"appStoreState": "READY_FOR_SALE", "versionString": "1.2.0"},
+                {"appStoreState": "DEVELOPER_REJECTED"}
+            ],
+            "release": {"status": "inProgress", "userFraction": 0.1}
+        });
+        let out = apply(&value, &custom);
+        assert_eq!(out["data"][0]["appStoreState"], "Prepare for Submission");
+        assert_eq!(out["data"][0]["platform"], "iOS");
+        assert_eq!(out["data"][0]["territory"], "USA");
+        assert_eq!(out["data"][1]["appStoreState"], "Ready for Distribution");
+        assert_eq!(out["data"][1]["versionString"], "1.2.0");
+        assert_eq!(out["data"][2]["appStoreState"], "Pulled");
+        assert_eq!(out["release"]["status"], "In progress");
+        assert_eq!(out["release"]["Rollout"], 0.1);
+        assert_eq!(
+            title_case("PROCESSING_FOR_APP_STORE"),
+            "Processing for App Store"
+        );
+    }
+
+    #[test]
+    fn leaves_values_of_other_fields_alone() {
+        let value = json!({
+            "data": [{
+                "productId": "PRO_MONTHLY",
+                "referenceName": "draft",
+                "state": "READY_TO_SUBMIT",
+                "platforms": ["MAC_OS", "IOS"],
+                "assetDeliveryState": {"state": "AWAITING_UPLOAD"}
+            }],
+            "note": "completed"
+        });
+        let out = apply(&value, &HashMap::new());
+        assert_eq!(out["data"][0]["productId"], "PRO_MONTHLY");
+        assert_eq!(out["data"][0]["referenceName"], "draft");
+        assert_eq!(out["data"][0]["state"], "Ready to Submit");
+        assert_eq!(out["data"][0]["platforms"], json!(["macOS", "iOS"]));
+        assert_eq!(
+            out["data"][0]["assetDeliveryState"]["state"],
+            "Awaiting Upload"
+        );
+        assert_eq!(out["note"], "completed");
+    }
+}
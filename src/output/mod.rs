@@ -1,12 +1,50 @@
+pub mod chart;
+pub mod ci;
+pub mod csv;
 pub mod json;
+pub mod jsonl;
+pub mod labels;
+pub mod stream;
 pub mod table;
 
+use clap::ValueEnum;
 use serde_json::Value;
 
-pub fn render_value(value: &Value, json: bool, pretty: bool) -> String {
-    if json {
-        json::render(value, pretty)
-    } else {
-        table::render_value(value)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable table
+    Table,
+    /// JSON (compact unless --pretty)
+    Json,
+    /// GitHub Actions annotations followed by compact JSON
+    Ci,
+    /// One JSON record per line; list commands stream records as pages arrive
+    Jsonl,
+}
+
+pub fn render_value(value: &Value, format: OutputFormat, pretty: bool) -> String {
+    match format {
+        OutputFormat::Json => json::render(value, pretty),
+        OutputFormat::Table => table::render_value(value),
+        OutputFormat::Ci => ci::render(value),
+        OutputFormat::Jsonl => jsonl::render(value),
     }
 }
+
+/// A result that should still be printed, but with a failing exit status.
+///
+/// Used by checks such as `sync check --fail-on-drift`, where the report is the
+/// useful part of the output even though the command fails.
+#[derive(Debug)]
+pub struct Failure(pub Value);
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.get("error").and_then(Value::as_str) {
+            Some(message) => f.write_str(message),
+            None => f.write_str("command reported failures"),
+        }
+    }
+}
+
+impl std::error::Error for Failure {}
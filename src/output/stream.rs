@@ -0,0 +1,52 @@
+//! JSONL progress events for long-running commands (`--stream`).
+//!
+//! When enabled, each completed unit of work is printed to stdout as one JSON object
+//! per line, e.g. `{"event":"locale","locale":"de-DE","status":"updated"}`, so that
+//! orchestration tools can follow progress. The command's final result is still
+//! printed last.
+
+use serde_json::{json, Value};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Merge the event name into the given fields.
+fn line(event: &str, fields: Value) -> Value {
+    let mut obj = serde_json::Map::new();
+    obj.insert("event".to_string(), json!(event));
+    if let Value::Object(fields) = fields {
+        obj.extend(fields);
+    }
+    Value::Object(obj)
+}
+
+/// Emit one progress event if streaming is enabled.
+pub fn emit(event: &str, fields: Value) {
+    if !enabled() {
+        return;
+    }
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line(event, fields));
+    let _ = stdout.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_name_is_merged_with_fields() {
+        let val = line("upload", json!({"file": "01.png", "status": "ok"}));
+        assert_eq!(val["event"], "upload");
+        assert_eq!(val["file"], "01.png");
+    }
+}
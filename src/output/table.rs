@@ -10,6 +10,7 @@ const MAX_VALUE_LEN: usize = 60;
 const SKIP_KEYS: &[&str] = &["relationships", "links", "self"];
 
 pub fn render_value(value: &Value) -> String {
+    let value = &super::labels::localize(value);
     let inner = unwrap_data(value);
 
     match inner {
@@ -29,7 +30,7 @@ fn unwrap_data(value: &Value) -> &Value {
 
 /// Flatten a JSON:API-style object: inline `attributes` keys alongside `id`/`type`,
 /// and drop noisy keys like `relationships` and `links`.
-fn flatten_object(obj: &Map<String, Value>) -> Map<String, Value> {
+pub(crate) fn flatten_object(obj: &Map<String, Value>) -> Map<String, Value> {
     let mut flat = Map::new();
     for (key, val) in obj {
         if SKIP_KEYS.contains(&key.as_str()) {
@@ -40,18 +40,235 @@ fn build_prompt() -> String {
         Err(_) => String::new(),
     };
 
+    // Google commands reuse an edit opened with `google edits open` until it is committed
+    let edit = crate::cli::google::edits::current_session()
+        .map(|s| format!(" {}", format!("[edit {}]", s.edit_id).yellow()))
+        .unwrap_or_default();
+
     if profile.is_empty() {
-        format!("{} {} ", "storeops".bold().bright_cyan(), "›".bright_cyan())
+        format!(
+            "{}{} {} ",
+            "storeops".bold().bright_cyan(),
+            edit,
+            "›".bright_cyan()
+        )
     } else {
         format!(
-            "{} {} {} ",
+            "{} {}{} {} ",
             "storeops".bold().bright_cyan(),
             format!("({})", profile).dimmed(),
+            edit,
             "›".bright_cyan()
         )
     }
 }
 
+/// Run a storeops command line from inside the REPL.
+async fn run_command(args: &[&str]) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let full_args: Vec<String> = std::iter::once("storeops")
+        .chain(args.iter().copied())
+        .map(String::from)
+        .collect();
+    let mut cli = crate::cli::Cli::try_parse_from(&full_args)?;
+    cli.apply_profile_defaults();
+    crate::dispatch(cli, &full_args).await
+}
+
+/// Prompt for a value; `None` when the user cancels with Ctrl-C or Ctrl-D.
+fn ask(rl: &mut Editor<(), DefaultHistory>, label: &str, default: Option<&str>) -> Option<String> {
+    let prompt = match default {
+        Some(d) => format!("  {label} {}: ", format!("[{d}]").dimmed()),
+        None => format!("  {label}: "),
+    };
+    loop {
+        let answer = rl.readline(&prompt).ok()?;
+        match (answer.trim(), default) {
+            ("", Some(d)) => return Some(d.to_string()),
+            ("", None) => continue,
+            (answer, _) => return Some(answer.to_string()),
+        }
+    }
+}
+
+/// Prompt for a path to an existing file.
+fn ask_file(rl: &mut Editor<(), DefaultHistory>, label: &str) -> Option<String> {
+    loop {
+        let path = ask(rl, label, None)?;
+        match crate::auth::store::expand_path(&path) {
+            Ok(expanded) if std::path::Path::new(&expanded).is_file() => return Some(path),
+            Ok(expanded) => eprintln!("  {} no file at {expanded}", "error:".bright_red().bold()),
+            Err(e) => eprintln!("  {} {e}", "error:".bright_red().bold()),
+        }
+    }
+}
+
+fn setup_failed(e: Box<dyn std::error::Error>) {
+    eprintln!("  {} {e}", "error:".bright_red().bold());
+    println!("  Run {} to try again.\n", "setup".bold().bright_yellow());
+}
+
+/// Set a default app or package on a saved profile.
+fn save_default(profile: &str, app: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = crate::config::Config::load()?;
+    let entry = config
+        .profiles
+        .get_mut(profile)
+        .ok_or_else(|| format!("profile '{profile}' not found"))?;
+    match entry.store {
+        crate::config::profiles::Store::Apple => entry.defaults.default_app_id = Some(app.into()),
+        crate::config::profiles::Store::Google => entry.defaults.default_package = Some(app.into()),
+    }
+    config.save()
+}
+
+/// Guided onboarding: create a profile, check its credentials and pick a default
+/// app. Runs on first start and again with `setup`.
+async fn run_setup(rl: &mut Editor<(), DefaultHistory>) {
+    println!("  {}", "Profile setup".bold().bright_white());
+    println!(
+        "  {}\n",
+        "Press Ctrl-C at any prompt to skip; `auth login` does the same non-interactively."
+            .dimmed()
+    );
+
+    let store = loop {
+        let Some(store) = ask(rl, "Store (apple/google)", Some("apple")) else {
+            println!();
+            return;
+        };
+        match store.to_lowercase().as_str() {
+            "apple" => break "apple",
+            "google" => break "google",
+            _ => eprintln!("  {} enter apple or google", "error:".bright_red().bold()),
+        }
+    };
+
+    let mut login = vec![
+        "auth".to_string(),
+        "login".into(),
+        "--store".into(),
+        store.into(),
+    ];
+    if store == "apple" {
+        println!(
+            "  {}",
+            "Create an API key under Users and Access → Integrations in App Store Connect."
+                .dimmed()
+        );
+        let Some(key_id) = ask(rl, "Key ID", None) else {
+            return;
+        };
+        let Some(issuer_id) = ask(rl, "Issuer ID", None) else {
+            return;
+        };
+        let Some(key_path) = ask_file(rl, "Path to AuthKey .p8 file") else {
+            return;
+        };
+        login.extend(["--key-id".into(), key_id, "--issuer-id".into(), issuer_id]);
+        login.extend(["--key-path".into(), key_path]);
+    } else {
+        println!(
+            "  {}",
+            "Use a service account JSON key with access to your Play Console apps.".dimmed()
+        );
+        let Some(path) = ask_file(rl, "Path to service account JSON") else {
+            return;
+        };
+        login.extend(["--service-account".into(), path]);
+    }
+    let default_name = format!("{store}-default");
+    let Some(name) = ask(rl, "Profile name", Some(&default_name)) else {
+        return;
+    };
+    login.extend(["--name".into(), name.clone()]);
+
+    let args: Vec<&str> = login.iter().map(String::as_str).collect();
+    if let Err(e) = run_command(&args).await {
+        return setup_failed(e);
+    }
+    println!("  {} saved profile {}", "✓".bright_green(), name.bold());
+
+    // Verify the credentials with a read-only call, then offer a default app
+    let app = if store == "apple" {
+        let apps = match run_command(&["apple", "apps", "list", "--profile", &name]).await {
+            Ok(apps) => apps,
+            Err(e) => return setup_failed(e),
+        };
+        let apps: Vec<(String, String)> = apps["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|a| {
+                let attrs = &a["attributes"];
+                let label = format!(
+                    "{} ({})",
+                    attrs["name"].as_str()?,
+                    attrs["bundleId"].as_str().unwrap_or("")
+                );
+                Some((a["id"].as_str()?.to_string(), label))
+            })
+            .collect();
+        println!(
+            "  {} credentials work; {} app(s) found",
+            "✓".bright_green(),
+            apps.len()
+        );
+        for (i, (_, label)) in apps.iter().enumerate() {
+            println!("    {}. {label}", i + 1);
+        }
+        loop {
+            if apps.is_empty() {
+                break None;
+            }
+            let Some(choice) = ask(rl, "Default app number (0 for none)", Some("1")) else {
+                break None;
+            };
+            match choice.parse::<usize>() {
+                Ok(0) => break None,
+                Ok(n) if n <= apps.len() => break Some(apps[n - 1].0.clone()),
+                _ => eprintln!("  {} enter 0-{}", "error:".bright_red().bold(), apps.len()),
+            }
+        }
+    } else {
+        // Play has no API to list a developer's apps, so verify against a named one
+        let package = ask(
+            rl,
+            "Default package name (e.g. com.example.app)",
+            Some("none"),
+        )
+        .filter(|p| p != "none");
+        if let Some(package) = &package {
+            let info = run_command(&["google", "apps", "info", package, "--profile", &name]).await;
+            if let Err(e) = info {
+                return setup_failed(e);
+            }
+            println!("  {} credentials work", "✓".bright_green());
+        }
+        package
+    };
+
+    if let Some(app) = app {
+        if let Err(e) = save_default(&name, &app) {
+            return setup_failed(e);
+        }
+        println!("  {} default app set to {}", "✓".bright_green(), app.bold());
+    }
+    let active = crate::config::Config::load()
+        .ok()
+        .and_then(|c| c.active_profile);
+    if active.as_deref() != Some(name.as_str())
+        && ask(rl, "Make it the active profile? (y/n)", Some("y")).as_deref() == Some("y")
+    {
+        if let Err(e) = run_command(&["auth", "switch", &name]).await {
+            return setup_failed(e);
+        }
+    }
+    println!(
+        "\n  Setup complete. Try {}.\n",
+        "status".bold().bright_yellow()
+    );
+}
+
 pub async fn run_repl() {
     print!("\x1B[2J\x1B[1;1H");
     print_banner();
@@ -69,6 +286,13 @@ pub async fn run_repl() {
         let _ = rl.load_history(path);
     }
 
+    // A missing config would make every command fail; an invalid one is reported
+    // by the first command instead of being replaced here
+    let configured = crate::config::Config::load().map(|c| !c.profiles.is_empty());
+    if let Ok(false) = configured {
+        run_setup(&mut rl).await;
+    }
+
     loop {
         let prompt = build_prompt();
         match rl.readline(&prompt) {
@@ -82,6 +306,10 @@ pub async fn run_repl() {
 
                 match line {
                     "exit" | "quit" => break,
+                    "setup" => {
+                        run_setup(&mut rl).await;
+                        continue;
+                    }
                     "clear" => {
                         print!("\x1B[2J\x1B[1;1H");
                         print_banner();
@@ -103,15 +331,14 @@ pub async fn run_repl() {
                     .collect();
 
                 match crate::cli::Cli::try_parse_from(&full_args) {
-                    Ok(cli) => {
-                        let json_output = cli.json;
+                    Ok(mut cli) => {
+                        cli.apply_profile_defaults();
+                        crate::output::labels::configure(!cli.raw);
+                        let format = cli.output_format();
                         let pretty = cli.pretty;
-                        match crate::run(cli).await {
+                        match crate::dispatch(cli, &full_args).await {
                             Ok(value) => {
-                                println!(
-                                    "{}",
-                                    crate::output::render_value(&value, json_output, pretty)
-                                );
+                                println!("{}", crate::output::render_value(&value, format, pretty));
                             }
                             Err(e) => {
                                 eprintln!(
@@ -147,5 +374,13 @@ pub async fn run_repl() {
         }
     }
 
+    if let Some(session) = crate::cli::google::edits::current_session() {
+        eprintln!(
+            "  {} edit {} was not committed; its changes are discarded when it expires",
+            "warning:".yellow().bold(),
+            session.edit_id
+        );
+    }
+
     println!("\n  {} 👋\n", "Goodbye!".dimmed());
 }
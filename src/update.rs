@@ -2,9 +2,11 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::cli::UpdateChannel;
+
 const REPO: &str = "fbonesso/storeops";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const CHECK_INTERVAL_SECS: u64 = 86400; // 24 hours
@@ -12,6 +14,8 @@ const CHECK_INTERVAL_SECS: u64 = 86400; // 24 hours
 #[derive(Deserialize)]
 struct GitHubRelease {
     tag_name: String,
+    #[serde(default)]
+    draft: bool,
     assets: Vec<GitHubAsset>,
 }
 
@@ -59,21 +63,44 @@ fn normalize_version(v: &str) -> &str {
     v.strip_prefix('v').unwrap_or(v)
 }
 
-fn is_newer(remote: &str, local: &str) -> bool {
-    let parse = |v: &str| -> Vec<u64> {
-        normalize_version(v)
-            .split('.')
-            .filter_map(|s| s.parse().ok())
-            .collect()
+/// One dot-separated pre-release identifier. As in semver, numeric identifiers compare
+/// numerically and sort below alphanumeric ones.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum PreId {
+    Numeric(u64),
+    Text(String),
+}
+
+/// Numeric version core plus pre-release identifiers; a release sorts above its
+/// pre-releases.
+fn version_key(v: &str) -> (Vec<u64>, bool, Vec<PreId>) {
+    let v = normalize_version(v);
+    let (core, pre) = match v.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (v, None),
     };
-    let r = parse(remote);
-    let l = parse(local);
-    r > l
+    let numbers = core.split('.').filter_map(|s| s.parse().ok()).collect();
+    let ids = pre
+        .unwrap_or_default()
+        .split('.')
+        .filter(|s| !s.is_empty())
+        .map(|s| match s.parse() {
+            Ok(n) => PreId::Numeric(n),
+            Err(_) => PreId::Text(s.to_string()),
+        })
+        .collect();
+    (numbers, pre.is_none(), ids)
 }
 
-async fn fetch_latest_release() -> Result<GitHubRelease, Box<dyn std::error::Error>> {
-    let url = format!("https://api.github.com/repos/{REPO}/releases/latest");
-    let client = reqwest::Client::new();
+fn is_newer(remote: &str, local: &str) -> bool {
+    version_key(remote) > version_key(local)
+}
+
+async fn github_get<T: serde::de::DeserializeOwned>(
+    path: &str,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let url = format!("https://api.github.com/repos/{REPO}/{path}");
+    let client = crate::api::session::http();
     let resp = client
         .get(&url)
         .header("User-Agent", format!("storeops/{CURRENT_VERSION}"))
@@ -93,6 +120,47 @@ async fn fetch_latest_release() -> Result<GitHubRelease, Box<dyn std::error::Err
     Ok(resp.json().await?)
 }
 
+async fn fetch_latest_release() -> Result<GitHubRelease, Box<dyn std::error::Error>> {
+    github_get("releases/latest").await
+}
+
+/// The release to install: a pinned version, or the newest on the channel.
+async fn fetch_release(
+    channel: UpdateChannel,
+    version: Option<&str>,
+) -> Result<GitHubRelease, Box<dyn std::error::Error>> {
+    if let Some(version) = version {
+        let version = normalize_version(version);
+        return match github_get(&format!("releases/tags/v{version}")).await {
+            Ok(release) => Ok(release),
+            Err(_) => github_get(&format!("releases/tags/{version}"))
+                .await
+                .map_err(|_| format!("release {version} not found").into()),
+        };
+    }
+    match channel {
+        UpdateChannel::Stable => fetch_latest_release().await,
+        UpdateChannel::Prerelease => {
+            let releases: Vec<GitHubRelease> = github_get("releases?per_page=30").await?;
+            releases
+                .into_iter()
+                .filter(|r| !r.draft)
+                .max_by(|a, b| version_key(&a.tag_name).cmp(&version_key(&b.tag_name)))
+                .ok_or_else(|| "no releases found".into())
+        }
+    }
+}
+
+/// Expected SHA-256 for `asset_name` from a `sha256sum`-style checksums file.
+fn expected_checksum<'a>(checksums: &'a str, asset_name: &str) -> Option<&'a str> {
+    checksums.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        let name = parts.next()?.trim_start_matches('*');
+        (name == asset_name).then_some(hash)
+    })
+}
+
 fn detect_target() -> String {
     let arch = if cfg!(target_arch = "x86_64") {
         "x86_64"
@@ -116,7 +184,58 @@ fn detect_target() -> String {
 }
 
 fn find_asset_for_target<'a>(assets: &'a [GitHubAsset], target: &str) -> Option<&'a GitHubAsset> {
-    assets.iter().find(|a| a.name.contains(target))
+    assets
+        .iter()
+        .find(|a| a.name.contains(target) && !a.name.ends_with(".sha256"))
+}
+
+/// How the running binary was installed, guessed from its path.
+#[derive(Debug, PartialEq, Eq)]
+enum InstallMethod {
+    Homebrew,
+    Cargo,
+    Standalone,
+}
+
+impl InstallMethod {
+    fn detect(exe: &Path) -> Self {
+        let path = exe.to_string_lossy().replace('\\', "/");
+        let cargo_home = env::var("CARGO_HOME")
+            .ok()
+            .map(|h| format!("{}/bin/", h.trim_end_matches('/')));
+        if path.contains("/Cellar/") || path.contains("/homebrew/") || path.contains("/linuxbrew/")
+        {
+            InstallMethod::Homebrew
+        } else if path.contains("/.cargo/bin/")
+            || cargo_home.is_some_and(|bin| path.starts_with(&bin))
+        {
+            InstallMethod::Cargo
+        } else {
+            InstallMethod::Standalone
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            InstallMethod::Homebrew => "homebrew",
+            InstallMethod::Cargo => "cargo",
+            InstallMethod::Standalone => "standalone",
+        }
+    }
+
+    /// Command that upgrades a managed install, if the binary is managed.
+    fn upgrade_command(&self, version: Option<&str>) -> Option<String> {
+        match self {
+            InstallMethod::Homebrew => Some("brew upgrade storeops".to_string()),
+            InstallMethod::Cargo => Some(match version {
+                Some(v) => format!(
+                    "cargo install --locked --force --git https://github.com/{REPO} --tag v{v}"
+                ),
+                None => format!("cargo install --locked --force --git https://github.com/{REPO}"),
+            }),
+            InstallMethod::Standalone => None,
+        }
+    }
 }
 
 fn current_exe_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
@@ -126,6 +245,14 @@ fn current_exe_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
         .ok_or_else(|| "cannot determine executable directory".into())
 }
 
+/// The latest stable release, and whether it is newer than the running binary.
+pub async fn latest_release() -> Result<(String, bool), Box<dyn std::error::Error>> {
+    let release = fetch_latest_release().await?;
+    let remote = normalize_version(&release.tag_name).to_string();
+    let newer = is_newer(&remote, CURRENT_VERSION);
+    Ok((remote, newer))
+}
+
 pub async fn check_for_update_background() {
     if env::var("STOREOPS_NO_UPDATE_CHECK").is_ok() {
         return;
@@ -150,19 +277,63 @@ pub async fn check_for_update_background() {
     }
 }
 
-pub async fn handle_update() -> Result<Value, Box<dyn std::error::Error>> {
+pub async fn handle_update(
+    channel: UpdateChannel,
+    version: Option<&str>,
+    check: bool,
+    force_binary: bool,
+) -> Result<Value, Box<dyn std::error::Error>> {
     eprintln!("Checking for updates...");
 
-    let release = fetch_latest_release().await?;
+    let release = fetch_release(channel, version).await?;
     let remote = normalize_version(&release.tag_name);
+    let channel_name = match channel {
+        UpdateChannel::Stable => "stable",
+        UpdateChannel::Prerelease => "prerelease",
+    };
+    let wanted = match version {
+        Some(_) => remote != CURRENT_VERSION,
+        None => is_newer(remote, CURRENT_VERSION),
+    };
+
+    // Homebrew links bin/storeops into the Cellar, so judge by the resolved path
+    let exe = env::current_exe()?;
+    let method = InstallMethod::detect(&exe.canonicalize().unwrap_or(exe));
 
-    if !is_newer(remote, CURRENT_VERSION) {
+    if check {
+        return Ok(json!({
+            "status": if wanted { "update_available" } else { "up_to_date" },
+            "current_version": CURRENT_VERSION,
+            "available_version": remote,
+            "channel": if version.is_some() { "pinned" } else { channel_name },
+            "install_method": method.name(),
+        }));
+    }
+
+    if !wanted {
         return Ok(json!({
             "status": "up_to_date",
             "current_version": CURRENT_VERSION,
         }));
     }
 
+    // Overwriting a package-managed binary leaves the manager's records stale
+    if let Some(command) = method.upgrade_command(version.map(normalize_version)) {
+        if !force_binary {
+            eprintln!(
+                "storeops was installed with {}; run `{command}` to upgrade (or pass --force-binary)",
+                method.name()
+            );
+            return Ok(json!({
+                "status": "managed_install",
+                "install_method": method.name(),
+                "current_version": CURRENT_VERSION,
+                "available_version": remote,
+                "upgrade_command": command,
+            }));
+        }
+    }
+
     eprintln!("Updating storeops: {} -> {}", CURRENT_VERSION, remote);
 
     let target = detect_target();
@@ -171,7 +342,7 @@ pub async fn handle_update() -> Result<Value, Box<dyn std::error::Error>> {
 
     eprintln!("Downloading {}...", asset.name);
 
-    let client = reqwest::Client::new();
+    let client = crate::api::session::http();
     let bytes = client
         .get(&asset.browser_download_url)
         .header("User-Agent", format!("storeops/{CURRENT_VERSION}"))
@@ -182,10 +353,17 @@ pub async fn handle_update() -> Result<Value, Box<dyn std::error::Error>> {
         .await?;
 
     eprintln!("Verifying checksum...");
-    let checksums_url = format!(
-        "https://github.com/{REPO}/releases/download/{}/checksums.sha256",
-        release.tag_name
-    );
+    let checksums_url = release
+        .assets
+        .iter()
+        .find(|a| a.name == "checksums.sha256")
+        .map(|a| a.browser_download_url.clone())
+        .unwrap_or_else(|| {
+            format!(
+                "https://github.com/{REPO}/releases/download/{}/checksums.sha256",
+                release.tag_name
+            )
+        });
     let checksums_resp = client
         .get(&checksums_url)
         .header("User-Agent", format!("storeops/{CURRENT_VERSION}"))
@@ -195,14 +373,11 @@ pub async fn handle_update() -> Result<Value, Box<dyn std::error::Error>> {
         return Err("failed to download checksums file for verification".into());
     }
     let checksums_text = checksums_resp.text().await?;
-    let expected_hash = checksums_text
-        .lines()
-        .find(|line| line.ends_with(&asset.name))
-        .and_then(|line| line.split_whitespace().next())
+    let expected_hash = expected_checksum(&checksums_text, &asset.name)
         .ok_or("checksum not found for this asset")?;
 
     let actual_hash = hex::encode(Sha256::digest(&bytes));
-    if actual_hash != expected_hash {
+    if !actual_hash.eq_ignore_ascii_case(expected_hash) {
         return Err(
             format!("checksum mismatch: expected {expected_hash}, got {actual_hash}").into(),
         );
@@ -334,6 +509,56 @@ mod tests {
         assert!(!is_newer("0.0.9", "0.1.0"));
     }
 
+    #[test]
+    fn prereleases_sort_below_their_release() {
+        assert!(is_newer("v0.5.0-rc.1", "0.4.9"));
+        assert!(is_newer("0.5.0", "0.5.0-rc.2"));
+        assert!(is_newer("0.5.0-rc.2", "0.5.0-rc.1"));
+        assert!(!is_newer("0.5.0-rc.1", "0.5.0"));
+    }
+
+    #[test]
+    fn prerelease_numbers_compare_numerically() {
+        assert!(is_newer("0.5.0-rc.10", "0.5.0-rc.9"));
+        assert!(is_newer("0.5.0-rc.1", "0.5.0-rc"));
+        assert!(is_newer("0.5.0-rc.1", "0.5.0-beta.11"));
+        assert!(is_newer("0.5.0-alpha", "0.5.0-1"));
+    }
+
+    #[test]
+    fn expected_checksum_matches_exact_asset_name() {
+        let sums = "aaa  storeops-x86_64-unknown-linux-gnu.tar.gz.sig\n\
+                    bbb *storeops-x86_64-unknown-linux-gnu.tar.gz\n";
+        assert_eq!(
+            expected_checksum(sums, "storeops-x86_64-unknown-linux-gnu.tar.gz"),
+            Some("bbb")
+        );
+        assert_eq!(expected_checksum(sums, "storeops.zip"), None);
+    }
+
+    #[test]
+    fn detects_install_method_from_path() {
+        assert_eq!(
+            InstallMethod::detect(Path::new(
+                "/opt/homebrew/Cellar/storeops/0.4.0/bin/storeops"
+            )),
+            InstallMethod::Homebrew
+        );
+        assert_eq!(
+            InstallMethod::detect(Path::new("/home/me/.cargo/bin/storeops")),
+            InstallMethod::Cargo
+        );
+        assert_eq!(
+            InstallMethod::detect(Path::new("/usr/local/bin/storeops")),
+            InstallMethod::Standalone
+        );
+        assert!(InstallMethod::Standalone.upgrade_command(None).is_none());
+        assert!(InstallMethod::Cargo
+            .upgrade_command(Some("0.4.2"))
+            .unwrap()
+            .ends_with("--tag v0.4.2"));
+    }
+
     #[test]
     fn normalize_strips_v_prefix() {
         assert_eq!(normalize_version("v1.2.3"), "1.2.3");
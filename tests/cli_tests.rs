@@ -200,7 +200,9 @@ fn google_testers_help_shows_subsubcommands() {
         .assert()
         .success()
         .stdout(predicate::str::contains("list"))
-        .stdout(predicate::str::contains("add"));
+        .stdout(predicate::str::contains("add"))
+        .stdout(predicate::str::contains("remove"))
+        .stdout(predicate::str::contains("set"));
 }
 
 #[test]
@@ -243,3 +245,577 @@ fn google_submit_shows_required_args() {
         .stdout(predicate::str::contains("PACKAGE_NAME"))
         .stdout(predicate::str::contains("--track"));
 }
+
+#[test]
+fn apple_phased_release_help_shows_monitor() {
+    storeops()
+        .args(["apple", "phased-release", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("monitor"));
+}
+
+#[test]
+fn metadata_coverage_available_for_both_stores() {
+    storeops()
+        .args(["apple", "metadata", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("coverage"));
+    storeops()
+        .args(["google", "listings", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("coverage"));
+}
+
+#[test]
+fn sync_translate_dry_run_lists_missing_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("en-US")).unwrap();
+    std::fs::create_dir_all(dir.path().join("de-DE")).unwrap();
+    std::fs::write(dir.path().join("en-US/name.txt"), "My App").unwrap();
+    std::fs::write(dir.path().join("en-US/description.txt"), "An app").unwrap();
+    std::fs::write(dir.path().join("de-DE/name.txt"), "Meine App").unwrap();
+
+    storeops()
+        .args(["sync", "translate", "--from", "en-US", "--to", "de-DE"])
+        .arg("--metadata-dir")
+        .arg(dir.path())
+        .args(["--dry-run", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("description.txt"))
+        .stdout(predicate::str::contains("\"skipped_existing\":1"));
+}
+
+#[test]
+fn screenshots_prepare_skips_unknown_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "not an image").unwrap();
+
+    storeops()
+        .args(["screenshots", "prepare", "--json", "--in"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"valid\":true"));
+}
+
+#[test]
+fn screenshots_prepare_streams_one_line_per_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let sets = dir.path().join("en-US/screenshots/iphone67");
+    std::fs::create_dir_all(&sets).unwrap();
+    std::fs::write(sets.join("01.png"), "not a png").unwrap();
+
+    let output = storeops()
+        .args(["screenshots", "prepare", "--stream", "--in"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("\"event\":\"file\""));
+    assert!(lines[0].contains("\"action\":\"failed\""));
+    assert!(lines[1].contains("\"valid\":false"));
+}
+
+#[test]
+fn apple_screenshot_sets_help_shows_prune_and_migrate() {
+    storeops()
+        .args(["apple", "screenshots", "sets", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("prune"))
+        .stdout(predicate::str::contains("migrate"));
+}
+
+#[test]
+fn google_commands_accept_edit_id() {
+    storeops()
+        .args(["google", "edits", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("open"))
+        .stdout(predicate::str::contains("validate"))
+        .stdout(predicate::str::contains("commit"))
+        .stdout(predicate::str::contains("abort"));
+    storeops()
+        .args(["google", "listings", "update", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--edit-id"))
+        .stdout(predicate::str::contains("--no-send-for-review"));
+}
+
+#[test]
+fn apple_versions_list_has_filters() {
+    storeops()
+        .args(["apple", "versions", "list", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--state"))
+        .stdout(predicate::str::contains("--platform"))
+        .stdout(predicate::str::contains("--include"));
+}
+
+#[test]
+fn apple_apps_has_lookup_and_filters() {
+    storeops()
+        .args(["apple", "apps", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lookup"));
+    storeops()
+        .args(["apple", "apps", "list", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--bundle-id"))
+        .stdout(predicate::str::contains("--sku"));
+}
+
+#[test]
+fn apple_id_arguments_accept_references() {
+    storeops()
+        .args(["apple", "metadata", "localizations", "update", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("bundle[@version]/locale"));
+    storeops()
+        .args(["apple", "versions", "list", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("bundle ID"));
+}
+
+#[test]
+fn apple_builds_list_has_filters() {
+    storeops()
+        .args(["apple", "builds", "list", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--version"))
+        .stdout(predicate::str::contains("--state"))
+        .stdout(predicate::str::contains("--beta-group"))
+        .stdout(predicate::str::contains("--latest"));
+}
+
+#[test]
+fn google_apps_update_details_shows_contact_flags() {
+    storeops()
+        .args(["google", "apps", "update-details", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--default-language"))
+        .stdout(predicate::str::contains("--contact-email"))
+        .stdout(predicate::str::contains("--contact-website"));
+}
+
+#[test]
+fn app_and_package_arguments_are_optional() {
+    storeops()
+        .args(["apple", "versions", "list", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[APP_ID]"));
+    storeops()
+        .args(["google", "listings", "list", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[PACKAGE_NAME]"));
+}
+
+#[test]
+fn fan_out_rejects_write_commands() {
+    storeops()
+        .args([
+            "apple",
+            "versions",
+            "create",
+            "123",
+            "--version",
+            "1.0",
+            "--all-profiles",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("read-only"));
+}
+
+#[test]
+fn apple_sync_push_rejects_unknown_create_version() {
+    storeops()
+        .args([
+            "apple",
+            "sync",
+            "push",
+            "com.example.app",
+            "--metadata-dir",
+            "metadata",
+            "--create-version",
+            "sometimes",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "expected never, auto or a version",
+        ));
+}
+
+#[test]
+fn apple_sync_push_version_file_conflicts_with_create_version() {
+    storeops()
+        .args([
+            "apple",
+            "sync",
+            "push",
+            "com.example.app",
+            "--metadata-dir",
+            "metadata",
+            "--version-file",
+            "VERSION",
+            "--create-version",
+            "2.0.0",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn status_shows_per_store_flags() {
+    storeops()
+        .args(["status", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--app"))
+        .stdout(predicate::str::contains("--package"))
+        .stdout(predicate::str::contains("--apple-profile"))
+        .stdout(predicate::str::contains("--google-profile"));
+}
+
+#[test]
+fn ci_output_annotates_errors() {
+    storeops()
+        .args([
+            "apple",
+            "versions",
+            "create",
+            "123",
+            "--version",
+            "1.0",
+            "--all-profiles",
+        ])
+        .args(["--output", "ci"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::starts_with("::error title=storeops::"));
+}
+
+#[test]
+fn apple_submit_keeps_version_flag_and_adds_review_details() {
+    storeops()
+        .args(["apple", "submit", "com.example.app"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--version"));
+    storeops()
+        .args(["apple", "submit", "review-details", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--demo-account-name"))
+        .stdout(predicate::str::contains("--attachment"));
+}
+
+#[test]
+fn api_dry_run_validates_without_credentials() {
+    storeops()
+        .args([
+            "apple",
+            "api",
+            "get",
+            "/apps",
+            "--query",
+            "limit=5",
+            "--dry-run",
+            "--json",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"method\":\"GET\""))
+        .stdout(predicate::str::contains("limit=5"));
+    storeops()
+        .args([
+            "apple",
+            "api",
+            "patch",
+            "/apps/1",
+            "--body",
+            "{\"name\":1}",
+            "--dry-run",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("data"));
+    storeops()
+        .args([
+            "google",
+            "api",
+            "post",
+            "/com.example.app/edits",
+            "--dry-run",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--body-file"));
+}
+
+#[test]
+fn config_validate_points_at_misspelled_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        "[profiles.work]\nstore = \"google\"\nservice_acount_path = \"/tmp/sa.json\"\n",
+    )
+    .unwrap();
+    storeops()
+        .args(["config", "validate", "--json", "--file"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"valid\":false"))
+        .stdout(predicate::str::contains(
+            "line 3: unknown key `service_acount_path` in [profiles.work] (did you mean `service_account_path`?)",
+        ));
+
+    std::fs::write(
+        &path,
+        "[profiles.work]\nstore = \"google\"\nservice_account_path = \"/tmp/sa.json\"\n",
+    )
+    .unwrap();
+    storeops()
+        .args(["config", "validate", "--json", "--file"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"valid\":true"));
+}
+
+#[test]
+fn replay_serves_recorded_responses_without_credentials() {
+    let dir = tempfile::tempdir().unwrap();
+    let session = dir.path().join("session.json");
+    std::fs::write(
+        &session,
+        r#"{"entries":[{"method":"GET","url":"https://api.appstoreconnect.apple.com/v1/apps?limit=1",
+            "status":200,"headers":[["content-type","application/json"]],
+            "body":{"data":[{"id":"123","attributes":{"name":"Replayed App"}}]}}]}"#,
+    )
+    .unwrap();
+    let replay = |limit: &str| {
+        let mut cmd = storeops();
+        cmd.env("HOME", dir.path())
+            .env("XDG_CONFIG_HOME", dir.path())
+            .args([
+                "apple", "apps", "list", "--json", "--limit", limit, "--replay",
+            ])
+            .arg(&session);
+        cmd
+    };
+    replay("1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Replayed App"));
+    replay("2")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no recorded response for GET"));
+}
+
+#[test]
+fn batch_prints_one_json_line_per_command() {
+    let dir = tempfile::tempdir().unwrap();
+    storeops()
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .args(["batch", "--json", "--file", "-"])
+        .write_stdin("# schemas\nschema\nstoreops schema status\nschema nope\n")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "{\"command\":\"schema\",\"line\":2,\"ok\":true,",
+        ))
+        .stdout(predicate::str::contains(
+            "\"command\":\"storeops schema status\",\"line\":3,\"ok\":true,",
+        ))
+        .stdout(predicate::str::contains("\"line\":4,\"ok\":false"))
+        .stdout(predicate::str::contains(
+            "{\"failed_lines\":[4],\"status\":\"failed\",\"succeeded\":2}",
+        ));
+}
+
+#[test]
+fn warehouse_query_requires_a_synced_warehouse() {
+    let dir = tempfile::tempdir().unwrap();
+    storeops()
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_DATA_HOME", dir.path())
+        .args(["warehouse", "query", "SELECT 1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "run `storeops warehouse sync` first",
+        ));
+}
+
+#[test]
+fn revenue_requires_a_report_source() {
+    let dir = tempfile::tempdir().unwrap();
+    storeops()
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .args(["revenue", "--month", "2024-09"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "set --vendor-number and/or --bucket",
+        ));
+}
+
+#[test]
+fn auth_rotate_leaves_config_untouched_when_new_key_is_unreadable() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_dir = dir.path().join("storeops");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    let config = "active_profile = \"work\"\n\n[profiles.work]\nstore = \"apple\"\nkey_id = \"OLDKEY\"\nissuer_id = \"ISS\"\nkey_path = \"/tmp/AuthKey_OLDKEY.p8\"\n";
+    std::fs::write(config_dir.join("config.toml"), config).unwrap();
+    storeops()
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .args([
+            "auth",
+            "rotate",
+            "--store",
+            "apple",
+            "--new-key-id",
+            "NEWKEY",
+        ])
+        .arg("--new-key-path")
+        .arg(dir.path().join("missing.p8"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot read key"));
+    assert_eq!(
+        std::fs::read_to_string(config_dir.join("config.toml")).unwrap(),
+        config
+    );
+}
+
+#[test]
+fn jsonl_output_streams_every_page_of_a_list() {
+    let dir = tempfile::tempdir().unwrap();
+    let session = dir.path().join("session.json");
+    std::fs::write(
+        &session,
+        r#"{"entries":[
+            {"method":"GET","url":"https://api.appstoreconnect.apple.com/v1/territories?limit=200",
+             "status":200,"headers":[["content-type","application/json"]],
+             "body":{"data":[{"type":"territories","id":"USA"},{"type":"territories","id":"DEU"}],
+                     "links":{"next":"https://api.appstoreconnect.apple.com/v1/territories?cursor=Mg&limit=200"}}},
+            {"method":"GET","url":"https://api.appstoreconnect.apple.com/v1/territories?limit=200&cursor=Mg",
+             "status":200,"headers":[["content-type","application/json"]],
+             "body":{"data":[{"type":"territories","id":"JPN"}],"links":{}}}]}"#,
+    )
+    .unwrap();
+    storeops()
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .args([
+            "apple",
+            "availability",
+            "territories",
+            "--output",
+            "jsonl",
+            "--replay",
+        ])
+        .arg(&session)
+        .assert()
+        .success()
+        .stdout(
+            "{\"id\":\"USA\",\"type\":\"territories\"}\n\
+             {\"id\":\"DEU\",\"type\":\"territories\"}\n\
+             {\"id\":\"JPN\",\"type\":\"territories\"}\n",
+        )
+        .stderr(predicate::str::contains("Streamed 3 record(s)"));
+}
+
+#[test]
+fn doctor_offline_reports_missing_credential_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_dir = dir.path().join("storeops");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.toml"),
+        "active_profile = \"ci\"\n\n[profiles.ci]\nstore = \"apple\"\nkey_id = \"K1\"\nissuer_id = \"I1\"\nkey_path = \"/nonexistent/AuthKey.p8\"\n",
+    )
+    .unwrap();
+    storeops()
+        .args(["doctor", "--offline", "--json"])
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env_remove("STOREOPS_APPLE_KEY_PATH")
+        .env_remove("STOREOPS_GOOGLE_SERVICE_ACCOUNT")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"status\":\"fail\""))
+        .stdout(predicate::str::contains("\"check\":\"credentials: ci\""))
+        .stdout(predicate::str::contains(
+            "cannot read /nonexistent/AuthKey.p8",
+        ));
+}
+
+#[test]
+fn review_triage_starts_empty_and_rejects_unknown_reviews() {
+    let dir = tempfile::tempdir().unwrap();
+    storeops()
+        .args(["reviews", "triage", "list", "--json"])
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_DATA_HOME", dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"reviews\":[]"));
+    storeops()
+        .args(["reviews", "triage", "claim", "r1", "--as", "alice"])
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_DATA_HOME", dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not in the triage queue"));
+}
+
+#[test]
+fn version_bump_shows_part_flags() {
+    storeops()
+        .args(["version", "bump", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--major"))
+        .stdout(predicate::str::contains("--minor"))
+        .stdout(predicate::str::contains("--patch"));
+}
+
+#[test]
+fn version_bump_rejects_multiple_parts() {
+    storeops()
+        .args([
+            "version", "bump", "--store", "apple", "--app", "1", "--major", "--minor",
+        ])
+        .assert()
+        .failure();
+}